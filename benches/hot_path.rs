@@ -0,0 +1,310 @@
+//! Signal path benchmarks: `cargo bench --bench hot_path`
+//!
+//! Synthetic but realistic data: a BTCUSDT-like random walk around 50 000 with
+//! Bybit-sized frames (50-level books, 1-20 trades per publicTrade frame).
+//! Compare against a saved baseline before deploying:
+//! `cargo bench --bench hot_path -- --save-baseline main`, then `--baseline main`.
+
+use bybit_scalper_bot::actors::execution::ExecutionSender;
+use bybit_scalper_bot::actors::strategy::StrategyEngine;
+use bybit_scalper_bot::actors::ws_decoder::{Decoded, FrameDecoder};
+use bybit_scalper_bot::actors::{StrategyMessage, StrategyStatus};
+use bybit_scalper_bot::channel::{metered_channel, MeteredSender, OverflowPolicy};
+use bybit_scalper_bot::config::Config;
+use bybit_scalper_bot::events::EventBus;
+use bybit_scalper_bot::exchange::SymbolSpecs;
+use bybit_scalper_bot::health::HealthRegistry;
+use bybit_scalper_bot::journal::Journal;
+use bybit_scalper_bot::metrics::Metrics;
+use bybit_scalper_bot::models::{
+    vwap, vwap_fast, CandleSeries, FastTick, LocalOrderBook, OrderBookSnapshot, RingBuffer, Symbol, TradeSide,
+    TradeTick, ATR_PERIOD,
+};
+use bybit_scalper_bot::risk_limits::TempBlacklist;
+use bybit_scalper_bot::spsc::{market_ring, RingSender};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::time::Duration;
+
+const NOW: i64 = 1_700_000_000_000;
+const DEPTH: usize = 50;
+
+type Levels = Vec<(Decimal, Decimal)>;
+
+/// Deterministic random walk, so runs are comparable
+struct Walk {
+    state: u64,
+    price: f64,
+}
+
+impl Walk {
+    fn new() -> Self {
+        Self {
+            state: 0x9E37_79B9_7F4A_7C15,
+            price: 50_000.0,
+        }
+    }
+
+    fn next_unit(&mut self) -> f64 {
+        // xorshift64
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// One trade: ±0.01% steps, sizes 0.001-0.5
+    fn tick(&mut self, symbol: &Symbol, timestamp: i64) -> TradeTick {
+        self.price *= 1.0 + (self.next_unit() - 0.5) * 0.0002;
+        let size = 0.001 + self.next_unit() * 0.5;
+        TradeTick {
+            symbol: symbol.clone(),
+            price: Decimal::from_f64_retain(self.price).unwrap_or_default().round_dp(1),
+            size: Decimal::from_f64_retain(size).unwrap_or_default().round_dp(3),
+            timestamp,
+            side: if self.next_unit() < 0.5 { TradeSide::Buy } else { TradeSide::Sell },
+        }
+    }
+
+    fn ticks(&mut self, symbol: &Symbol, n: usize) -> Vec<TradeTick> {
+        (0..n).map(|i| self.tick(symbol, NOW + i as i64 * 50)).collect()
+    }
+
+    /// `DEPTH` levels per side, 0.5 apart, around the current price
+    fn levels(&mut self) -> (Levels, Levels) {
+        let mid = (self.price * 2.0).round() / 2.0;
+        let level = |walk: &mut Walk, price: f64| {
+            (
+                Decimal::from_f64_retain(price).unwrap_or_default().round_dp(1),
+                Decimal::from_f64_retain(0.01 + walk.next_unit() * 3.0).unwrap_or_default().round_dp(3),
+            )
+        };
+        let bids = (1..=DEPTH).map(|i| level(self, mid - i as f64 * 0.5)).collect();
+        let asks = (1..=DEPTH).map(|i| level(self, mid + i as f64 * 0.5)).collect();
+        (bids, asks)
+    }
+}
+
+fn json_levels(levels: &[(Decimal, Decimal)]) -> String {
+    let levels: Vec<String> = levels.iter().map(|(p, s)| format!("[\"{}\",\"{}\"]", p, s)).collect();
+    format!("[{}]", levels.join(","))
+}
+
+fn orderbook_frame(walk: &mut Walk, frame_type: &str, update_id: i64) -> String {
+    let (bids, asks) = walk.levels();
+    format!(
+        r#"{{"topic":"orderbook.50.BTCUSDT","type":"{}","ts":{},"data":{{"s":"BTCUSDT","b":{},"a":{},"u":{}}}}}"#,
+        frame_type,
+        NOW,
+        json_levels(&bids),
+        json_levels(&asks),
+        update_id
+    )
+}
+
+fn trade_frame(ticks: &[TradeTick]) -> String {
+    let entries: Vec<String> = ticks
+        .iter()
+        .map(|t| {
+            format!(
+                r#"{{"T":{},"s":"BTCUSDT","S":"{:?}","v":"{}","p":"{}","L":"PlusTick","i":"0f1c0e3a-6a9c-5d3e-8f41-1e2b3c4d5e6f","BT":false}}"#,
+                t.timestamp, t.side, t.size, t.price
+            )
+        })
+        .collect();
+    format!(
+        r#"{{"topic":"publicTrade.BTCUSDT","type":"snapshot","ts":{},"data":[{}]}}"#,
+        NOW,
+        entries.join(",")
+    )
+}
+
+fn bench_ws_decode(c: &mut Criterion) {
+    let btc = Symbol::from("BTCUSDT");
+    let mut walk = Walk::new();
+    let snapshot = orderbook_frame(&mut walk, "snapshot", 1);
+    let delta = orderbook_frame(&mut walk, "delta", 2);
+    let trades = trade_frame(&walk.ticks(&btc, 20));
+
+    let mut decoder = FrameDecoder::new(i64::MAX, DEPTH);
+    decoder.subscribe(&btc);
+    decoder.handle_message(&snapshot, NOW).unwrap();
+
+    let mut group = c.benchmark_group("ws_decode");
+    group.bench_function("orderbook_delta_50", |b| {
+        b.iter(|| decoder.handle_message(black_box(&delta), NOW).unwrap())
+    });
+    group.throughput(Throughput::Elements(20));
+    group.bench_function("public_trade_20", |b| {
+        b.iter(|| match decoder.handle_message(black_box(&trades), NOW).unwrap() {
+            Decoded::Trades(ticks) => decoder.recycle(ticks),
+            other => panic!("expected trades, got {:?}", other),
+        })
+    });
+    group.finish();
+}
+
+fn bench_orderbook_snapshot(c: &mut Criterion) {
+    let btc = Symbol::from("BTCUSDT");
+    let mut walk = Walk::new();
+    let (bids, asks) = walk.levels();
+    let mut book = LocalOrderBook::new();
+    book.apply_snapshot(&bids, &asks);
+    let (delta_bids, delta_asks) = walk.levels();
+
+    let mut group = c.benchmark_group("orderbook");
+    group.bench_function("apply_snapshot_50", |b| {
+        b.iter_batched_ref(
+            LocalOrderBook::new,
+            |book| book.apply_snapshot(black_box(&bids), black_box(&asks)),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("apply_delta_50", |b| {
+        b.iter_batched_ref(
+            || book.clone(),
+            |book| {
+                book.apply_delta(black_box(&delta_bids), black_box(&delta_asks));
+                book.trim(DEPTH);
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("to_snapshot_50", |b| {
+        b.iter(|| book.to_snapshot(btc.clone(), NOW, DEPTH).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_indicators(c: &mut Criterion) {
+    let btc = Symbol::from("BTCUSDT");
+    let mut walk = Walk::new();
+    let mut ticks = RingBuffer::new(300);
+    let mut fast = RingBuffer::new(300);
+    for tick in walk.ticks(&btc, 300) {
+        fast.push(FastTick::from(&tick));
+        ticks.push(tick);
+    }
+    // 200 one-minute candles, ~30 trades each
+    let mut candles = CandleSeries::new(60_000, 200);
+    for tick in walk.ticks(&btc, 6_000) {
+        let timestamp = NOW + (tick.timestamp - NOW) * 40;
+        candles.push_trade(tick.price, tick.size, timestamp);
+    }
+
+    let mut group = c.benchmark_group("indicators");
+    group.bench_function("vwap_decimal_200", |b| b.iter(|| vwap(ticks.iter_rev().take(200)).unwrap()));
+    group.bench_function("vwap_fast_200", |b| b.iter(|| vwap_fast(fast.iter_rev().take(200)).unwrap()));
+    group.bench_function("atr_14", |b| b.iter(|| candles.atr(ATR_PERIOD).unwrap()));
+    group.bench_function("regime", |b| b.iter(|| candles.regime().unwrap()));
+    group.finish();
+}
+
+/// Strategy actor with no exchange behind it. The momentum threshold is out
+/// of reach so the bench times signal evaluation, not order handling.
+fn strategy_engine() -> (MeteredSender<StrategyMessage>, RingSender<StrategyMessage>, StrategyEngine) {
+    let data_dir = std::env::temp_dir().join(format!("hot-path-bench-{}", std::process::id()));
+    std::env::set_var("BYBIT_API_KEY", "bench");
+    std::env::set_var("BYBIT_API_SECRET", "bench");
+    std::env::set_var("DATA_DIR", &data_dir);
+    std::env::set_var("MOMENTUM_THRESHOLD", "1000");
+    let config = Arc::new(Config::from_env().expect("bench config"));
+
+    let metrics = Metrics::new();
+    let block = OverflowPolicy::Block { timeout: None };
+    let (strategy_tx, strategy_rx) = metered_channel("strategy", 1000, block, &metrics);
+    let (market_tx, market_rx) = market_ring("market_data", 1000, &metrics);
+    let (execution_tx, _) = metered_channel("execution", 100, block, &metrics);
+    let (priority_tx, _) = metered_channel("execution_priority", 100, block, &metrics);
+    let (notifier_tx, _) = metered_channel("notifier", 100, OverflowPolicy::DropOldest, &metrics);
+    let health = HealthRegistry::new();
+
+    let engine = StrategyEngine::new(
+        config.clone(),
+        strategy_rx,
+        market_rx,
+        ExecutionSender::new(execution_tx, priority_tx),
+        notifier_tx,
+        Journal::new(data_dir.join("journal.jsonl")),
+        TempBlacklist::new(),
+        None,
+        EventBus::new(config.bot_id.as_str()),
+        health.register("strategy", Duration::from_secs(60)),
+    );
+    (strategy_tx, market_tx, engine)
+}
+
+/// Round trip through the actor once the ring is drained: every tick sent before has been handled
+async fn status(strategy_tx: &MeteredSender<StrategyMessage>, market_tx: &RingSender<StrategyMessage>) -> StrategyStatus {
+    while market_tx.depth() > 0 {
+        tokio::task::yield_now().await;
+    }
+    let (reply_tx, reply_rx) = oneshot::channel();
+    strategy_tx.send(StrategyMessage::Status(reply_tx)).await.unwrap();
+    reply_rx.await.unwrap()
+}
+
+fn bench_strategy(c: &mut Criterion) {
+    const BATCH: usize = 1_000;
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let btc = Symbol::from("BTCUSDT");
+    let mut walk = Walk::new();
+
+    let (strategy_tx, mut market_tx, engine) = strategy_engine();
+    runtime.block_on(async {
+        tokio::spawn(engine.run());
+        strategy_tx
+            .send(StrategyMessage::SymbolChanged {
+                symbol: btc.clone(),
+                specs: SymbolSpecs {
+                    symbol: "BTCUSDT".to_string(),
+                    qty_step: Decimal::new(1, 3),
+                    min_order_qty: Decimal::new(1, 3),
+                    max_order_qty: Decimal::from(100),
+                    tick_size: Decimal::new(1, 1),
+                },
+                price_change_24h: 0.01,
+            })
+            .await
+            .unwrap();
+        let (bids, asks) = walk.levels();
+        let (best_bid, best_ask) = (bids[0], asks[0]);
+        let snapshot = OrderBookSnapshot::new(btc.clone(), NOW, best_bid.0, best_ask.0, best_bid.1, best_ask.1)
+            .with_depth(bids, asks);
+        market_tx.try_send(StrategyMessage::OrderBook(snapshot)).unwrap();
+        // Fill the tick buffer so VWAP/momentum run on every tick
+        for tick in walk.ticks(&btc, 300) {
+            market_tx.send_timeout(StrategyMessage::Trade(tick), Duration::from_secs(1)).await.unwrap();
+        }
+        status(&strategy_tx, &market_tx).await;
+    });
+
+    let mut group = c.benchmark_group("strategy");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("handle_trade_1000", |b| {
+        b.iter_batched(
+            || walk.ticks(&btc, BATCH),
+            |ticks| {
+                runtime.block_on(async {
+                    for tick in ticks {
+                        market_tx.send_timeout(StrategyMessage::Trade(tick), Duration::from_secs(1)).await.unwrap();
+                    }
+                    status(&strategy_tx, &market_tx).await
+                })
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_ws_decode,
+    bench_orderbook_snapshot,
+    bench_indicators,
+    bench_strategy
+);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+//! Raw frames through `FrameDecoder::handle_message`, one frame per line so
+//! book state carries across frames like on a live socket.
+#![no_main]
+
+use bybit_scalper_bot::actors::ws_decoder::FrameDecoder;
+use bybit_scalper_bot::models::Symbol;
+use libfuzzer_sys::fuzz_target;
+
+const NOW: i64 = 1_700_000_000_000;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let mut decoder = FrameDecoder::new(1000, 50);
+    decoder.subscribe(&Symbol::from("BTCUSDT"));
+    for frame in text.lines() {
+        let _ = decoder.handle_message(frame, NOW);
+    }
+});
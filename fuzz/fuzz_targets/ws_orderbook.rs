@@ -0,0 +1,40 @@
+//! Arbitrary JSON as the `data` of orderbook frames (first byte picks snapshot/delta and ts)
+#![no_main]
+
+use bybit_scalper_bot::actors::ws_decoder::{FrameDecoder, FrameType, WsFrame};
+use bybit_scalper_bot::models::Symbol;
+use libfuzzer_sys::fuzz_target;
+use serde_json::value::RawValue;
+
+const NOW: i64 = 1_700_000_000_000;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&mode, rest)) = data.split_first() else {
+        return;
+    };
+    let mut decoder = FrameDecoder::new(1000, 50);
+    decoder.subscribe(&Symbol::from("BTCUSDT"));
+
+    for chunk in rest.split(|&b| b == b'\n') {
+        let Ok(text) = std::str::from_utf8(chunk) else {
+            continue;
+        };
+        let Ok(raw) = serde_json::from_str::<&RawValue>(text) else {
+            continue;
+        };
+        let frame = WsFrame {
+            topic: Some("orderbook.50.BTCUSDT".into()),
+            frame_type: Some(if mode & 1 == 0 { FrameType::Snapshot } else { FrameType::Delta }),
+            ts: match mode >> 1 & 3 {
+                0 => None,
+                1 => Some(i64::MIN),
+                2 => Some(i64::MAX),
+                _ => Some(NOW),
+            },
+            data: Some(raw),
+        };
+        if let Ok(Some(snapshot)) = decoder.handle_orderbook(frame, NOW) {
+            assert!(snapshot.best_bid < snapshot.best_ask);
+        }
+    }
+});
@@ -0,0 +1,31 @@
+//! Arbitrary JSON as the `data` of publicTrade frames
+#![no_main]
+
+use bybit_scalper_bot::actors::ws_decoder::{FrameDecoder, FrameType, WsFrame};
+use bybit_scalper_bot::models::Symbol;
+use libfuzzer_sys::fuzz_target;
+use rust_decimal::Decimal;
+use serde_json::value::RawValue;
+
+const NOW: i64 = 1_700_000_000_000;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(raw) = serde_json::from_str::<&RawValue>(text) else {
+        return;
+    };
+    let mut decoder = FrameDecoder::new(1000, 50);
+    decoder.subscribe(&Symbol::from("BTCUSDT"));
+
+    let frame = WsFrame {
+        topic: Some("publicTrade.BTCUSDT".into()),
+        frame_type: Some(FrameType::Snapshot),
+        ts: Some(NOW),
+        data: Some(raw),
+    };
+    for tick in decoder.handle_trade(frame, NOW).unwrap_or_default() {
+        assert!(tick.price > Decimal::ZERO && tick.size > Decimal::ZERO);
+    }
+});
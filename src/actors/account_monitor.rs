@@ -0,0 +1,209 @@
+use crate::actors::messages::{ControlCommand, NotifierMessage, StrategyMessage};
+use crate::channel::MeteredSender;
+use crate::config::Config;
+use crate::drawdown::HighWaterMark;
+use crate::exchange::{BybitClient, WalletBalance};
+use crate::health::ActorHeartbeat;
+use crate::notifications::Alert;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::time::{interval, Duration};
+use tracing::{debug, error, info, warn};
+
+/// Account margin health, from the unified account's maintenance margin rate
+/// (maintenance margin / equity; the exchange liquidates at 100%)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MarginLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl MarginLevel {
+    pub fn classify(mm_rate: f64, warn_ratio: f64, critical_ratio: f64) -> Self {
+        if mm_rate >= critical_ratio {
+            MarginLevel::Critical
+        } else if mm_rate >= warn_ratio {
+            MarginLevel::Warning
+        } else {
+            MarginLevel::Normal
+        }
+    }
+}
+
+/// AccountMonitorActor - Polls the wallet: margin-rate alerts before cross-margin liquidation
+/// cascades, and the drawdown breaker against the persisted equity high-water mark
+pub struct AccountMonitorActor {
+    client: BybitClient,
+    config: Arc<Config>,
+    strategy_tx: MeteredSender<StrategyMessage>,
+    notifier_tx: MeteredSender<NotifierMessage>,
+    heartbeat: ActorHeartbeat,
+    /// Level at the previous check (alert on changes only)
+    margin_level: MarginLevel,
+    /// Peak equity, survives restarts
+    high_water_mark: HighWaterMark,
+    /// Breaker already fired this episode
+    drawdown_tripped: bool,
+}
+
+impl AccountMonitorActor {
+    pub fn new(
+        client: BybitClient,
+        config: Arc<Config>,
+        strategy_tx: MeteredSender<StrategyMessage>,
+        notifier_tx: MeteredSender<NotifierMessage>,
+        heartbeat: ActorHeartbeat,
+    ) -> Self {
+        Self {
+            client,
+            high_water_mark: HighWaterMark::load(config.data_path("equity_hwm.json")),
+            config,
+            strategy_tx,
+            notifier_tx,
+            heartbeat,
+            margin_level: MarginLevel::Normal,
+            drawdown_tripped: false,
+        }
+    }
+
+    pub async fn run(mut self) {
+        info!(
+            "🏦 AccountMonitorActor started (every {}s, warn at MM rate {:.0}%, critical at {:.0}%)",
+            self.config.margin_check_interval_secs,
+            self.config.margin_warn_ratio * 100.0,
+            self.config.margin_critical_ratio * 100.0
+        );
+
+        let mut check_interval = interval(Duration::from_secs(self.config.margin_check_interval_secs));
+
+        loop {
+            self.heartbeat.beat();
+            check_interval.tick().await;
+            self.heartbeat.beat();
+
+            match self.client.get_wallet_balance().await {
+                Ok(wallet) => {
+                    self.check_margin(&wallet).await;
+                    self.check_drawdown(&wallet).await;
+                }
+                Err(e) => warn!("⚠️ Margin check failed: {}", e),
+            }
+        }
+    }
+
+    async fn check_margin(&mut self, wallet: &WalletBalance) {
+        // Classic accounts report no account-level rates
+        let Ok(mm_rate) = wallet.account_mm_rate.parse::<f64>() else {
+            debug!("No maintenance margin rate for {} account", wallet.account_type);
+            return;
+        };
+        let level = MarginLevel::classify(mm_rate, self.config.margin_warn_ratio, self.config.margin_critical_ratio);
+        let previous = std::mem::replace(&mut self.margin_level, level);
+        if level == previous {
+            return;
+        }
+
+        let details = format!(
+            "MM rate: <b>{:.1}%</b> | IM rate: {}%\nEquity: {} | Maintenance margin: {}",
+            mm_rate * 100.0,
+            wallet.account_im_rate.parse::<f64>().map(|r| format!("{:.1}", r * 100.0)).unwrap_or_else(|_| "-".to_string()),
+            wallet.total_equity,
+            wallet.total_maintenance_margin
+        );
+
+        match level {
+            MarginLevel::Normal => {
+                info!("🏦 Margin back to normal (MM rate {:.1}%)", mm_rate * 100.0);
+                self.notify(Alert::success(format!("🏦 <b>Margin back to normal</b>\n{}", details)));
+            }
+            MarginLevel::Warning => {
+                warn!("🏦 Margin warning: MM rate {:.1}%", mm_rate * 100.0);
+                self.notify(Alert::warning(format!("🏦 <b>MARGIN WARNING</b>\n{}", details)));
+            }
+            MarginLevel::Critical => {
+                error!("🏦 Margin CRITICAL: MM rate {:.1}%", mm_rate * 100.0);
+                let mut text = format!("🏦 <b>MARGIN CRITICAL</b> — liquidation at 100%\n{}", details);
+
+                // Once per episode: closing frees the margin this bot's position holds
+                if self.config.margin_auto_reduce {
+                    match self
+                        .strategy_tx
+                        .send(StrategyMessage::Control(ControlCommand::ClosePosition))
+                        .await
+                    {
+                        Ok(()) => text.push_str("\nAuto-reduce: closing the bot's position"),
+                        Err(e) => {
+                            error!("Failed to send auto-reduce close: {}", e);
+                            text.push_str("\n⚠️ Auto-reduce failed — CHECK THE EXCHANGE");
+                        }
+                    }
+                }
+                self.notify(Alert::critical(text));
+            }
+        }
+    }
+
+    /// ✅ DRAWDOWN BREAKER: Equity too far below the (persisted) peak engages the kill switch.
+    /// After a restart the old peak still applies, so the breaker fires again right away.
+    async fn check_drawdown(&mut self, wallet: &WalletBalance) {
+        let Ok(equity) = Decimal::from_str(&wallet.total_equity) else {
+            return;
+        };
+        // Tracked even with the breaker off, so enabling it later has a real peak
+        let peak = self.high_water_mark.observe(equity);
+        let limit = self.config.max_drawdown_percent;
+        if limit <= 0.0 {
+            return;
+        }
+
+        let drawdown = self.high_water_mark.drawdown_percent(equity);
+        if drawdown < limit {
+            self.drawdown_tripped = false;
+            return;
+        }
+        if self.drawdown_tripped {
+            return;
+        }
+        self.drawdown_tripped = true;
+
+        error!("📉 DRAWDOWN BREAKER: equity {} is {:.2}% below peak {} (limit {:.2}%)", equity, drawdown, peak, limit);
+        let mut text = format!(
+            "📉 <b>DRAWDOWN BREAKER</b>\nEquity {} is <b>{:.2}%</b> below the peak {} (limit {:.2}%)",
+            equity, drawdown, peak, limit
+        );
+        match self
+            .strategy_tx
+            .send(StrategyMessage::Control(ControlCommand::KillSwitch))
+            .await
+        {
+            Ok(()) => text.push_str("\nKill switch engaged. Delete equity_hwm.json in DATA_DIR to reset the peak."),
+            Err(e) => {
+                error!("Failed to engage kill switch: {}", e);
+                text.push_str("\n⚠️ Kill switch failed — CHECK THE EXCHANGE");
+            }
+        }
+        self.notify(Alert::critical(text));
+    }
+
+    fn notify(&self, alert: Alert) {
+        if let Err(e) = self.notifier_tx.try_send(NotifierMessage::Alert(alert)) {
+            debug!("Dropped alert: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_margin_level_thresholds() {
+        assert_eq!(MarginLevel::classify(0.10, 0.5, 0.8), MarginLevel::Normal);
+        assert_eq!(MarginLevel::classify(0.50, 0.5, 0.8), MarginLevel::Warning);
+        assert_eq!(MarginLevel::classify(0.79, 0.5, 0.8), MarginLevel::Warning);
+        assert_eq!(MarginLevel::classify(0.95, 0.5, 0.8), MarginLevel::Critical);
+        assert!(MarginLevel::Critical > MarginLevel::Warning);
+    }
+}
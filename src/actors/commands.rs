@@ -0,0 +1,491 @@
+use crate::actors::messages::{ControlCommand, ParamPatch, ScannerMessage, StrategyMessage};
+use crate::channel::MeteredSender;
+use crate::config::Config;
+use crate::journal::Journal;
+use crate::notifications::{escape_html, CallbackQuery, Message, TelegramClient, Update};
+use crate::symbol_lists::{Edit, ListKind, SymbolLists};
+use crate::shadow::Arena;
+use crate::stats;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+use tracing::{debug, info, warn};
+
+const POLL_TIMEOUT_SECS: u64 = 30;
+/// Unconfirmed commands expire after this long
+const CONFIRMATION_TTL_SECS: u64 = 60;
+
+/// What a confirmation tap sets off
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    Control(ControlCommand),
+    /// Live engine takes the runtime parameters of a paper profile
+    Promote { profile: String, patch: ParamPatch },
+    /// Coin goes back into rotation
+    Unblacklist(String),
+}
+
+impl Action {
+    /// None for edits the actor applies itself
+    fn message(&self) -> Option<StrategyMessage> {
+        match self {
+            Action::Control(command) => Some(StrategyMessage::Control(*command)),
+            Action::Promote { patch, .. } => Some(StrategyMessage::PatchParams(*patch)),
+            Action::Unblacklist(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Control(command) => write!(f, "{}", command),
+            Action::Promote { profile, .. } => write!(f, "PROMOTE {}", profile),
+            Action::Unblacklist(symbol) => write!(f, "UNBLACKLIST {}", symbol),
+        }
+    }
+}
+
+/// Destructive command waiting for a confirmation tap
+struct PendingConfirmation {
+    action: Action,
+    user_id: i64,
+    created: Instant,
+}
+
+/// TelegramCommandActor - Operator commands via Telegram
+///
+/// - Only users on the allow-list can issue commands or tap buttons
+/// - Every command (/close, /killswitch, /pause, /resume, /promote, /blacklist remove) requires an inline-button confirmation
+/// - Read-only queries (/stats, /leaderboard) are answered right away
+/// - /blacklist add and list apply right away too (fencing a coin off only ever blocks entries)
+pub struct TelegramCommandActor {
+    telegram: TelegramClient,
+    allowed_users: HashSet<i64>,
+    strategy_tx: MeteredSender<StrategyMessage>,
+    scanner_tx: MeteredSender<ScannerMessage>,
+    symbols: SymbolLists,
+    journal: Journal,
+    arena: Arena,
+    pending: HashMap<u64, PendingConfirmation>,
+    next_confirmation_id: u64,
+    offset: i64,
+}
+
+impl TelegramCommandActor {
+    /// None if Telegram commands are not configured (no token or empty allow-list)
+    pub fn new(
+        config: Arc<Config>,
+        strategy_tx: MeteredSender<StrategyMessage>,
+        scanner_tx: MeteredSender<ScannerMessage>,
+        symbols: SymbolLists,
+        journal: Journal,
+        arena: Arena,
+    ) -> Option<Self> {
+        let token = config.telegram_bot_token.clone()?;
+        if config.telegram_allowed_users.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            telegram: TelegramClient::new(token),
+            allowed_users: config.telegram_allowed_users.iter().copied().collect(),
+            strategy_tx,
+            scanner_tx,
+            symbols,
+            journal,
+            arena,
+            pending: HashMap::new(),
+            next_confirmation_id: 1,
+            offset: 0,
+        })
+    }
+
+    pub async fn run(mut self) {
+        info!(
+            "🕹️  TelegramCommandActor started ({} authorized users)",
+            self.allowed_users.len()
+        );
+
+        loop {
+            let updates = match self.telegram.get_updates(self.offset, POLL_TIMEOUT_SECS).await {
+                Ok(updates) => updates,
+                Err(e) => {
+                    warn!("Telegram getUpdates failed: {}, retrying in 5s", e);
+                    sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+
+            for update in updates {
+                self.offset = self.offset.max(update.update_id + 1);
+                self.handle_update(update).await;
+            }
+
+            self.pending
+                .retain(|_, p| p.created.elapsed().as_secs() < CONFIRMATION_TTL_SECS);
+        }
+    }
+
+    async fn handle_update(&mut self, update: Update) {
+        if let Some(message) = update.message {
+            self.handle_message(message).await;
+        } else if let Some(callback) = update.callback_query {
+            self.handle_callback(callback).await;
+        }
+    }
+
+    async fn handle_message(&mut self, message: Message) {
+        let Some(text) = message.text.as_deref() else {
+            return;
+        };
+        let query = parse_query(text);
+        let command = parse_command(text);
+        let promote = parse_promote(text);
+        let blacklist = parse_blacklist(text);
+        if query.is_none() && command.is_none() && promote.is_none() && blacklist.is_none() {
+            return;
+        }
+        let Some(user) = message.from else {
+            return;
+        };
+
+        if !self.allowed_users.contains(&user.id) {
+            warn!(
+                "🚫 Unauthorized Telegram command '{}' from user {} (@{})",
+                text,
+                user.id,
+                user.username.as_deref().unwrap_or("-")
+            );
+            return;
+        }
+
+        if let Some(query) = query {
+            self.answer_query(query, &message.chat.id.to_string()).await;
+            return;
+        }
+        let action = match (blacklist, command, promote) {
+            (Some(BlacklistEdit::Remove(symbol)), _, _) if self.symbols.list(ListKind::Blacklist).contains(&symbol) => {
+                Action::Unblacklist(symbol)
+            }
+            (Some(edit), _, _) => {
+                self.edit_blacklist(edit, user.id, &message.chat.id.to_string()).await;
+                return;
+            }
+            (None, Some(command), _) => Action::Control(command),
+            (None, None, Some(profile)) => match self.arena.promotion(&profile) {
+                Ok(patch) => Action::Promote { profile, patch },
+                Err(e) => {
+                    if let Err(e) = self.telegram.send_message(&message.chat.id.to_string(), &escape_html(&e)).await {
+                        warn!("Failed to answer /promote: {}", e);
+                    }
+                    return;
+                }
+            },
+            (None, None, None) => return,
+        };
+
+        let id = self.next_confirmation_id;
+        self.next_confirmation_id += 1;
+        self.pending.insert(
+            id,
+            PendingConfirmation {
+                action: action.clone(),
+                user_id: user.id,
+                created: Instant::now(),
+            },
+        );
+
+        let prompt = format!(
+            "⚠️ Confirm <b>{}</b>?{}\n<i>Expires in {}s</i>",
+            action,
+            promotion_details(&action),
+            CONFIRMATION_TTL_SECS
+        );
+        let confirm = format!("confirm:{}", id);
+        let cancel = format!("cancel:{}", id);
+        if let Err(e) = self
+            .telegram
+            .send_with_buttons(
+                &message.chat.id.to_string(),
+                &prompt,
+                &[("✅ Confirm", &confirm), ("❌ Cancel", &cancel)],
+            )
+            .await
+        {
+            warn!("Failed to send confirmation prompt: {}", e);
+            self.pending.remove(&id);
+        }
+    }
+
+    async fn handle_callback(&mut self, callback: CallbackQuery) {
+        if !self.allowed_users.contains(&callback.from.id) {
+            warn!("🚫 Unauthorized Telegram button tap from user {}", callback.from.id);
+            self.answer(&callback.id, "Not authorized").await;
+            return;
+        }
+
+        let Some((action, id)) = callback
+            .data
+            .as_deref()
+            .and_then(|d| d.split_once(':'))
+            .and_then(|(action, id)| Some((action, id.parse::<u64>().ok()?)))
+        else {
+            self.answer(&callback.id, "Unknown action").await;
+            return;
+        };
+
+        // Only the user who issued the command may confirm it
+        let pending = match self.pending.get(&id) {
+            Some(p) if p.created.elapsed().as_secs() >= CONFIRMATION_TTL_SECS => {
+                self.pending.remove(&id);
+                None
+            }
+            Some(p) if p.user_id != callback.from.id => {
+                self.answer(&callback.id, "Only the requester can confirm").await;
+                return;
+            }
+            Some(_) => self.pending.remove(&id),
+            None => None,
+        };
+
+        let Some(pending) = pending else {
+            self.answer(&callback.id, "Expired").await;
+            self.edit(&callback, "⌛ Confirmation expired").await;
+            return;
+        };
+
+        match action {
+            "confirm" => {
+                info!("🕹️  {} confirmed by Telegram user {}", pending.action, callback.from.id);
+                let Some(message) = pending.action.message() else {
+                    let text = self.apply(&pending.action, callback.from.id).await;
+                    self.answer(&callback.id, "Confirmed").await;
+                    self.edit(&callback, &text).await;
+                    return;
+                };
+                match self.strategy_tx.send(message).await {
+                    Ok(_) => {
+                        self.answer(&callback.id, "Confirmed").await;
+                        self.edit(&callback, &format!("✅ <b>{}</b> confirmed", pending.action)).await;
+                    }
+                    Err(e) => {
+                        warn!("Failed to forward control command: {}", e);
+                        self.answer(&callback.id, "Strategy unavailable").await;
+                    }
+                }
+            }
+            _ => {
+                debug!("{} cancelled by Telegram user {}", pending.action, callback.from.id);
+                self.answer(&callback.id, "Cancelled").await;
+                self.edit(&callback, &format!("❌ <b>{}</b> cancelled", pending.action)).await;
+            }
+        }
+    }
+
+    async fn answer_query(&self, query: Query, chat_id: &str) {
+        let text = match query {
+            Query::Stats => match self.journal.trades() {
+                Ok(trades) => format!("{}\n\n{}", stats::render_by_mode(&trades), stats::render_by_symbol(&trades)),
+                Err(e) => {
+                    warn!("Failed to read journal for /stats: {:#}", e);
+                    "⚠️ Journal unavailable".to_string()
+                }
+            },
+            Query::Leaderboard { .. } if self.arena.is_empty() => "🏆 No paper profiles (PAPER_PROFILES)".to_string(),
+            Query::Leaderboard { days } => match self.arena.leaderboard(&self.journal, days) {
+                Ok(text) => text,
+                Err(e) => {
+                    warn!("Failed to read journals for /leaderboard: {:#}", e);
+                    "⚠️ Journal unavailable".to_string()
+                }
+            },
+        };
+        if let Err(e) = self.telegram.send_message(chat_id, &text).await {
+            warn!("Failed to answer {:?}: {}", query, e);
+        }
+    }
+
+    /// Confirmed edit the actor makes itself; the text replaces the prompt
+    async fn apply(&self, action: &Action, user_id: i64) -> String {
+        let by = format!("telegram:{}", user_id);
+        match action {
+            Action::Unblacklist(symbol) if self.symbols.remove(ListKind::Blacklist, symbol, &by).await == Edit::Changed => {
+                format!("✅ <b>{}</b> removed from the blacklist", symbol)
+            }
+            Action::Unblacklist(symbol) => format!("{} is not blacklisted", symbol),
+            _ => format!("✅ <b>{}</b> confirmed", action),
+        }
+    }
+
+    /// ✅ SYMBOL LISTS: Persisted and audited right away; an addition makes the scanner
+    /// rescan so a blacklisted current coin is left without waiting for the hold time
+    async fn edit_blacklist(&self, edit: BlacklistEdit, user_id: i64, chat_id: &str) {
+        let by = format!("telegram:{}", user_id);
+        let text = match edit {
+            BlacklistEdit::List => match self.symbols.list(ListKind::Blacklist) {
+                list if list.is_empty() => "⛔ Blacklist is empty".to_string(),
+                list => format!("⛔ <b>Blacklist</b> ({})\n{}", list.len(), list.join(", ")),
+            },
+            BlacklistEdit::Add(symbol) if self.symbols.add(ListKind::Blacklist, &symbol, &by).await == Edit::Changed => {
+                if let Err(e) = self.scanner_tx.send(ScannerMessage::SymbolListsChanged).await {
+                    warn!("Failed to tell the scanner about the blacklist: {}", e);
+                }
+                format!("⛔ <b>{}</b> blacklisted\n<i>No new entries; the scanner moves off it if it's the active coin</i>", symbol)
+            }
+            BlacklistEdit::Add(symbol) => format!("{} is already blacklisted", symbol),
+            // Blacklisted coins are removed through a confirmation prompt
+            BlacklistEdit::Remove(symbol) => format!("{} is not blacklisted", symbol),
+            BlacklistEdit::Usage => "Usage: /blacklist add SYMBOL | remove SYMBOL | list".to_string(),
+        };
+        if let Err(e) = self.telegram.send_message(chat_id, &text).await {
+            warn!("Failed to answer /blacklist: {}", e);
+        }
+    }
+
+    async fn answer(&self, callback_id: &str, text: &str) {
+        if let Err(e) = self.telegram.answer_callback_query(callback_id, text).await {
+            debug!("Failed to answer callback query: {}", e);
+        }
+    }
+
+    /// Replace the prompt so the buttons can't be tapped twice
+    async fn edit(&self, callback: &CallbackQuery, text: &str) {
+        if let Some(ref message) = callback.message {
+            if let Err(e) = self
+                .telegram
+                .edit_message_text(message.chat.id, message.message_id, text)
+                .await
+            {
+                debug!("Failed to edit confirmation prompt: {}", e);
+            }
+        }
+    }
+}
+
+/// Read-only commands, no confirmation needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Query {
+    Stats,
+    /// Paper profiles vs. live over the last `days`
+    Leaderboard { days: u32 },
+}
+
+/// `/leaderboard` without a window
+const LEADERBOARD_DEFAULT_DAYS: u32 = 7;
+
+/// "/close" from "/close@BotName now", lowercased
+fn command_name(text: &str) -> Option<String> {
+    let word = text.split_whitespace().next()?;
+    Some(word.split('@').next()?.to_lowercase())
+}
+
+/// "/stats", "/leaderboard [days]"
+fn parse_query(text: &str) -> Option<Query> {
+    match command_name(text)?.as_str() {
+        "/stats" => Some(Query::Stats),
+        "/leaderboard" => {
+            let days = text.split_whitespace().nth(1).and_then(|d| d.parse().ok());
+            Some(Query::Leaderboard { days: days.unwrap_or(LEADERBOARD_DEFAULT_DAYS).clamp(1, 365) })
+        }
+        _ => None,
+    }
+}
+
+/// Profile name from "/promote tight" ("" when missing, which no profile matches)
+fn parse_promote(text: &str) -> Option<String> {
+    if command_name(text)? != "/promote" {
+        return None;
+    }
+    Some(text.split_whitespace().nth(1).unwrap_or_default().to_lowercase())
+}
+
+/// `/blacklist` subcommand
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BlacklistEdit {
+    Add(String),
+    Remove(String),
+    List,
+    /// Unknown subcommand or not a symbol
+    Usage,
+}
+
+/// "/blacklist add SYMBOL", "/blacklist remove SYMBOL", "/blacklist [list]"
+fn parse_blacklist(text: &str) -> Option<BlacklistEdit> {
+    if command_name(text)? != "/blacklist" {
+        return None;
+    }
+    let mut args = text.split_whitespace().skip(1);
+    let subcommand = args.next().map(str::to_lowercase);
+    let symbol = args
+        .next()
+        .map(str::to_uppercase)
+        .filter(|s| s.chars().all(|c| c.is_ascii_alphanumeric()));
+    Some(match (subcommand.as_deref(), symbol) {
+        (None | Some("list"), _) => BlacklistEdit::List,
+        (Some("add"), Some(symbol)) => BlacklistEdit::Add(symbol),
+        (Some("remove" | "rm"), Some(symbol)) => BlacklistEdit::Remove(symbol),
+        _ => BlacklistEdit::Usage,
+    })
+}
+
+/// What a promotion changes now, and what it can't
+fn promotion_details(action: &Action) -> String {
+    let Action::Promote { patch, .. } = action else {
+        return String::new();
+    };
+    format!(
+        "\nMomentum threshold → {:.3}%, max trades/day → {}\n<i>Other settings of the profile need .env and a restart</i>",
+        patch.momentum_threshold.unwrap_or_default(),
+        patch.max_trades_per_day.unwrap_or_default()
+    )
+}
+
+/// "/close", "/killswitch", "/pause", "/resume" (optionally "/close@BotName")
+pub(crate) fn parse_command(text: &str) -> Option<ControlCommand> {
+    match command_name(text)?.as_str() {
+        "/close" => Some(ControlCommand::ClosePosition),
+        "/killswitch" | "/kill" => Some(ControlCommand::KillSwitch),
+        "/pause" => Some(ControlCommand::Pause),
+        "/resume" => Some(ControlCommand::Resume),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command("/close"), Some(ControlCommand::ClosePosition));
+        assert_eq!(parse_command("/close@scalper_bot now"), Some(ControlCommand::ClosePosition));
+        assert_eq!(parse_command("/KILLSWITCH"), Some(ControlCommand::KillSwitch));
+        assert_eq!(parse_command("/resume"), Some(ControlCommand::Resume));
+        assert_eq!(parse_command("/pause"), Some(ControlCommand::Pause));
+        assert_eq!(parse_command("close"), None);
+        assert_eq!(parse_command("/status"), None);
+        assert_eq!(parse_command("/stats"), None);
+        assert_eq!(parse_query("/stats@scalper_bot"), Some(Query::Stats));
+        assert_eq!(parse_query("/close"), None);
+        assert_eq!(parse_query("/leaderboard"), Some(Query::Leaderboard { days: LEADERBOARD_DEFAULT_DAYS }));
+        assert_eq!(parse_query("/leaderboard 30"), Some(Query::Leaderboard { days: 30 }));
+        assert_eq!(parse_promote("/promote@scalper_bot Tight"), Some("tight".to_string()));
+        assert_eq!(parse_promote("/promote"), Some(String::new()));
+        assert_eq!(parse_promote("/pause"), None);
+        assert_eq!(parse_blacklist("/blacklist add pepeusdt"), Some(BlacklistEdit::Add("PEPEUSDT".to_string())));
+        assert_eq!(parse_blacklist("/blacklist@scalper_bot remove DOGEUSDT"), Some(BlacklistEdit::Remove("DOGEUSDT".to_string())));
+        assert_eq!(parse_blacklist("/blacklist"), Some(BlacklistEdit::List));
+        assert_eq!(parse_blacklist("/blacklist add"), Some(BlacklistEdit::Usage));
+        assert_eq!(parse_blacklist("/blacklist add <b>"), Some(BlacklistEdit::Usage));
+        assert_eq!(parse_blacklist("/stats"), None);
+    }
+
+    #[test]
+    fn test_unblacklist_is_applied_by_the_actor() {
+        let action = Action::Unblacklist("DOGEUSDT".to_string());
+        assert!(action.message().is_none());
+        assert_eq!(action.to_string(), "UNBLACKLIST DOGEUSDT");
+        assert!(Action::Control(ControlCommand::Pause).message().is_some());
+    }
+}
@@ -0,0 +1,155 @@
+//! Duplicate Order Guard
+//!
+//! Final backstop against a state machine that fires the same entry twice:
+//! an entry with the same symbol and side as one the exchange took within the last
+//! `DUPLICATE_ORDER_WINDOW_SECS`, and a quantity within `QTY_TOLERANCE` of
+//! it, is refused unless the order carries `allow_duplicate`. Unlike the
+//! strategy's duplicate-signal guard this looks at the orders themselves, so
+//! it also catches a double send from a single signal.
+
+use crate::models::{Order, OrderSide, Symbol};
+use rust_decimal::Decimal;
+use std::fmt;
+use tokio::time::{Duration, Instant};
+
+/// Quantities within 2% count as the same order (margin sizing can shave a step off)
+const QTY_TOLERANCE: Decimal = Decimal::from_parts(2, 0, 0, false, 2);
+
+/// The earlier order a refused one repeats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub qty: Decimal,
+    pub ago: Duration,
+}
+
+impl fmt::Display for Duplicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Duplicate of the {} {:?} {} entry sent {:.1}s ago",
+            self.symbol,
+            self.side,
+            self.qty,
+            self.ago.as_secs_f64()
+        )
+    }
+}
+
+struct SentOrder {
+    symbol: Symbol,
+    side: OrderSide,
+    qty: Decimal,
+    at: Instant,
+}
+
+pub struct DuplicateOrderGuard {
+    /// Zero = guard off
+    window: Duration,
+    recent: Vec<SentOrder>,
+}
+
+impl DuplicateOrderGuard {
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window: Duration::from_secs(window_secs),
+            recent: Vec::new(),
+        }
+    }
+
+    /// Refuse `order` if it repeats a recent one
+    pub fn check(&mut self, order: &Order, now: Instant) -> Result<(), Duplicate> {
+        let window = self.window;
+        self.recent.retain(|sent| now.duration_since(sent.at) < window);
+        if order.allow_duplicate {
+            return Ok(());
+        }
+        match self.recent.iter().rev().find(|sent| sent.matches(order)) {
+            Some(sent) => Err(Duplicate {
+                symbol: sent.symbol.clone(),
+                side: sent.side,
+                qty: sent.qty,
+                ago: now.duration_since(sent.at),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Remember an order the exchange took (a refused one may be sent again)
+    pub fn record(&mut self, order: &Order, now: Instant) {
+        if self.window.is_zero() {
+            return;
+        }
+        self.recent.push(SentOrder {
+            symbol: order.symbol.clone(),
+            side: order.side,
+            qty: order.qty,
+            at: now,
+        });
+    }
+}
+
+impl SentOrder {
+    fn matches(&self, order: &Order) -> bool {
+        self.symbol == order.symbol
+            && self.side == order.side
+            && (self.qty - order.qty).abs() <= self.qty.max(order.qty) * QTY_TOLERANCE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OrderType, TimeInForce};
+
+    fn entry(symbol: &str, side: OrderSide, qty: i64) -> Order {
+        Order {
+            symbol: Symbol::from(symbol),
+            side,
+            order_type: OrderType::Market,
+            qty: Decimal::from(qty),
+            price: None,
+            time_in_force: TimeInForce::IOC,
+            reduce_only: false,
+            qty_step: None,
+            tick_size: None,
+            ref_price: None,
+            allow_duplicate: false,
+            hard_stop_percent: None,
+            position_idx: 0,
+        }
+    }
+
+    #[test]
+    fn test_repeat_within_window_is_refused() {
+        let start = Instant::now();
+        let mut guard = DuplicateOrderGuard::new(10);
+        let order = entry("BTCUSDT", OrderSide::Buy, 100);
+        assert!(guard.check(&order, start).is_ok());
+        guard.record(&order, start);
+
+        // ~qty: sized down by a step is still the same entry
+        let repeat = entry("BTCUSDT", OrderSide::Buy, 99);
+        let duplicate = guard.check(&repeat, start + Duration::from_secs(2)).unwrap_err();
+        assert_eq!(duplicate.to_string(), "Duplicate of the BTCUSDT Buy 100 entry sent 2.0s ago");
+
+        // Other side, other symbol, clearly different size, or flagged: not duplicates
+        assert!(guard.check(&entry("BTCUSDT", OrderSide::Sell, 100), start).is_ok());
+        assert!(guard.check(&entry("ETHUSDT", OrderSide::Buy, 100), start).is_ok());
+        assert!(guard.check(&entry("BTCUSDT", OrderSide::Buy, 150), start).is_ok());
+        assert!(guard.check(&Order { allow_duplicate: true, ..repeat.clone() }, start).is_ok());
+
+        // The window has passed
+        assert!(guard.check(&repeat, start + Duration::from_secs(10)).is_ok());
+    }
+
+    #[test]
+    fn test_zero_window_disables_the_guard() {
+        let start = Instant::now();
+        let mut guard = DuplicateOrderGuard::new(0);
+        let order = entry("BTCUSDT", OrderSide::Buy, 100);
+        guard.record(&order, start);
+        assert!(guard.check(&order, start).is_ok());
+    }
+}
@@ -0,0 +1,143 @@
+//! Entry Pricing Policy
+//!
+//! Where each entry is priced: join the passive side of the book (the best
+//! bid for a buy), improve it by one tick, or cross the spread with a market
+//! order. Urgency is the signal's momentum as a multiple of the momentum
+//! threshold. A move at `ENTRY_CROSS_URGENCY` or stronger is taken at once,
+//! since a missed fill costs more than the spread; a weaker one waits as a
+//! maker, one tick inside the spread when it is at least
+//! `ENTRY_IMPROVE_SPREAD_BPS` wide (first in the queue), at the touch
+//! otherwise. `ENTRY_CROSS_URGENCY=0` prices every entry as a Market IOC.
+
+use crate::config::Config;
+use crate::models::{OrderBookSnapshot, OrderSide, OrderType, TimeInForce};
+use rust_decimal::Decimal;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pricing {
+    /// PostOnly limit at our side's best price
+    Join,
+    /// PostOnly limit one tick inside the spread
+    Improve,
+    /// Market IOC
+    Cross,
+}
+
+impl fmt::Display for Pricing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pricing::Join => write!(f, "join"),
+            Pricing::Improve => write!(f, "improve"),
+            Pricing::Cross => write!(f, "cross"),
+        }
+    }
+}
+
+/// Order type, limit price and time in force for one entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryTerms {
+    pub pricing: Pricing,
+    pub order_type: OrderType,
+    pub price: Option<Decimal>,
+    pub time_in_force: TimeInForce,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EntryPricing {
+    /// Zero = always cross
+    cross_urgency: f64,
+    improve_spread_bps: f64,
+}
+
+impl EntryPricing {
+    pub fn new(cross_urgency: f64, improve_spread_bps: f64) -> Self {
+        Self { cross_urgency, improve_spread_bps }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.entry_cross_urgency, config.entry_improve_spread_bps)
+    }
+
+    /// `urgency` = |momentum| / momentum threshold; without a tick size the spread can't be improved
+    pub fn terms(&self, side: OrderSide, book: &OrderBookSnapshot, tick_size: Option<Decimal>, urgency: f64) -> EntryTerms {
+        let tick = tick_size.filter(|tick| *tick > Decimal::ZERO);
+        let (touch, inside) = match side {
+            OrderSide::Buy => (book.best_bid, tick.map(|tick| book.best_bid + tick)),
+            OrderSide::Sell => (book.best_ask, tick.map(|tick| book.best_ask - tick)),
+        };
+        // Improving a one-tick spread would take the other side: that is crossing
+        let inside = inside.filter(|price| *price > book.best_bid && *price < book.best_ask);
+
+        let pricing = if self.cross_urgency <= 0.0 || urgency >= self.cross_urgency {
+            Pricing::Cross
+        } else if inside.is_some() && book.spread_bps >= self.improve_spread_bps {
+            Pricing::Improve
+        } else {
+            Pricing::Join
+        };
+
+        let limit = |price| EntryTerms {
+            pricing,
+            order_type: OrderType::Limit,
+            price: Some(price),
+            time_in_force: TimeInForce::PostOnly,
+        };
+        match (pricing, inside) {
+            (Pricing::Cross, _) => EntryTerms {
+                pricing,
+                order_type: OrderType::Market,
+                price: None,
+                time_in_force: TimeInForce::IOC,
+            },
+            (Pricing::Improve, Some(price)) => limit(price),
+            _ => limit(touch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    /// Top of book with 5 on each side
+    fn book(best_bid: &str, best_ask: &str) -> OrderBookSnapshot {
+        OrderBookSnapshot::new(Symbol::from("BTCUSDT"), 0, d(best_bid), d(best_ask), d("5"), d("5"))
+    }
+
+    #[test]
+    fn test_urgency_and_spread_pick_the_price() {
+        let policy = EntryPricing::new(2.0, 5.0);
+        let tick = Some(d("0.01"));
+        let wide = book("100.00", "100.10");
+
+        let strong = policy.terms(OrderSide::Buy, &wide, tick, 2.5);
+        assert_eq!((strong.pricing, strong.order_type, strong.price), (Pricing::Cross, OrderType::Market, None));
+        assert_eq!(strong.time_in_force, TimeInForce::IOC);
+
+        let buy = policy.terms(OrderSide::Buy, &wide, tick, 1.2);
+        assert_eq!((buy.pricing, buy.price, buy.time_in_force), (Pricing::Improve, Some(d("100.01")), TimeInForce::PostOnly));
+        let sell = policy.terms(OrderSide::Sell, &wide, tick, 1.2);
+        assert_eq!((sell.pricing, sell.price), (Pricing::Improve, Some(d("100.09"))));
+
+        // Tight spread, one-tick spread or no tick size: join the touch
+        let tight = book("100.00", "100.03");
+        assert_eq!(policy.terms(OrderSide::Buy, &tight, tick, 1.2).price, Some(d("100.00")));
+        let one_tick = book("100.00", "100.01");
+        let sell = policy.terms(OrderSide::Sell, &one_tick, Some(d("0.01")), 1.0);
+        assert_eq!((sell.pricing, sell.price), (Pricing::Join, Some(d("100.01"))));
+        assert_eq!(policy.terms(OrderSide::Buy, &wide, None, 1.2).pricing, Pricing::Join);
+    }
+
+    #[test]
+    fn test_zero_cross_urgency_always_crosses() {
+        let policy = EntryPricing::new(0.0, 5.0);
+        let terms = policy.terms(OrderSide::Buy, &book("100.00", "100.10"), Some(d("0.01")), 1.0);
+        assert_eq!((terms.pricing, terms.order_type), (Pricing::Cross, OrderType::Market));
+    }
+}
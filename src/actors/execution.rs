@@ -1,61 +1,540 @@
-use crate::actors::messages::{ExecutionMessage, StrategyMessage};
+use crate::actors::messages::{
+    ControlCommand, ExecutionMessage, ExecutionResponse, NotifierMessage, PairOrder, PairReport, StrategyMessage, Traced,
+};
+use crate::actors::duplicate_order::DuplicateOrderGuard;
+use crate::actors::order_lifecycle::{Action, OrderLifecycle, Outcome, PollTiming};
+use crate::actors::order_throttle::OrderThrottle;
+use crate::actors::private_stream::PrivateState;
+use crate::actors::queue_position::{QueueBoard, QueueCall, QueueEstimate};
+use crate::channel::{MeteredReceiver, MeteredSender, SendError};
 use crate::config::Config;
-use crate::exchange::BybitClient;
+use crate::crash::OwnedPositions;
+use crate::exchange::{BybitClient, BybitError, OrderStatusResponse, PositionInfo, SpecsCache};
+use crate::health::ActorHeartbeat;
+use crate::journal::{Journal, SnapshotReason};
 use crate::models::*;
+use crate::notifications::{escape_html, Alert};
+use parking_lot::Mutex;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
+use std::collections::HashSet;
 use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::mpsc;
-use tracing::{debug, error, info, warn};
+use tokio::sync::{broadcast, oneshot};
+use tokio::time::{interval, Duration, Instant};
+use tracing::{debug, error, info, warn, Instrument};
+
+/// Idle loop tick so the watchdog sees progress without order traffic
+pub const EXECUTION_LIVENESS_SECS: u64 = 5;
+
+/// How long Shutdown waits for an in-flight close on the priority lane before aborting it
+const PRIORITY_DRAIN_SECS: u64 = 30;
+
+/// Share of the available balance an entry may commit as initial margin (the rest covers fees/slippage)
+const MARGIN_USAGE_LIMIT: Decimal = Decimal::from_parts(95, 0, 0, false, 2);
+
+/// Strategy-side handle that routes each message to the right execution lane
+#[derive(Clone)]
+pub struct ExecutionSender {
+    normal: MeteredSender<Traced<ExecutionMessage>>,
+    priority: MeteredSender<Traced<ExecutionMessage>>,
+}
+
+impl ExecutionSender {
+    pub fn new(
+        normal: MeteredSender<Traced<ExecutionMessage>>,
+        priority: MeteredSender<Traced<ExecutionMessage>>,
+    ) -> Self {
+        Self { normal, priority }
+    }
+
+    /// Routes by priority; the caller's span travels with the message.
+    /// Shutdown goes to both lanes, the priority one first so it finishes its closes
+    pub async fn send(&self, msg: ExecutionMessage) -> Result<(), SendError<ExecutionMessage>> {
+        if matches!(msg, ExecutionMessage::Shutdown) {
+            if let Err(e) = self.priority.send(Traced::current(ExecutionMessage::Shutdown)).await {
+                warn!("Priority lane did not take Shutdown: {}", e);
+            }
+        }
+        let lane = if msg.is_priority() { &self.priority } else { &self.normal };
+        lane.send(Traced::current(msg))
+            .await
+            .map_err(|e| match e {
+                SendError::Full(t) => SendError::Full(t.msg),
+                SendError::Timeout(t) => SendError::Timeout(t.msg),
+                SendError::Closed(t) => SendError::Closed(t.msg),
+            })
+    }
+
+    /// Send a message carrying a `reply` sender and wait for the answer (None = execution is gone)
+    pub async fn request<R>(&self, msg: impl FnOnce(oneshot::Sender<R>) -> ExecutionMessage) -> Option<R> {
+        let (reply, answer) = oneshot::channel();
+        if let Err(e) = self.send(msg(reply)).await {
+            error!("Failed to send execution request: {}", e);
+            return None;
+        }
+        answer.await.ok()
+    }
+}
 
 /// ExecutionActor - Order placement and position tracking
+///
+/// ✅ PRIORITY LANE: Closes and position checks run on their own task, so a
+/// stop-loss close never waits behind a PlaceOrder confirmation poll (up to 10s).
 pub struct ExecutionActor {
+    core: Arc<ExecutionCore>,
+    message_rx: MeteredReceiver<Traced<ExecutionMessage>>,
+    priority_rx: MeteredReceiver<Traced<ExecutionMessage>>,
+    heartbeat: ActorHeartbeat,
+    /// The priority lane reports on its own, so a stalled close shows up in the watchdog
+    priority_heartbeat: ActorHeartbeat,
+}
+
+/// State shared by both lanes
+struct ExecutionCore {
     client: BybitClient,
-    #[allow(dead_code)]
     config: Arc<Config>,
-    message_rx: mpsc::Receiver<ExecutionMessage>,
-    strategy_tx: mpsc::Sender<StrategyMessage>,
+    strategy_tx: MeteredSender<StrategyMessage>,
+    notifier_tx: MeteredSender<NotifierMessage>,
+    specs_cache: SpecsCache,
+    /// Entries sent in the last minute/hour
+    throttle: Mutex<OrderThrottle>,
+    /// Entries sent in the last DUPLICATE_ORDER_WINDOW_SECS
+    duplicates: Mutex<DuplicateOrderGuard>,
+    /// Symbols that already got the configured LEVERAGE this run
+    leverage_applied: Mutex<HashSet<String>>,
+    /// Positions with an exchange-side hard stop behind them (set after a verified fill)
+    hard_stops: Mutex<HashSet<Symbol>>,
+    /// Equity snapshot after every close
+    journal: Journal,
+    /// Queue estimates of resting maker entries, fed by MarketDataActor
+    queue: QueueBoard,
+    /// Entry poll cadence (ORDER_POLL_INTERVAL_MS, ENTRY_MAX_POLLS, CANCEL_SETTLE_MS)
+    timing: PollTiming,
+    /// Private stream: its final order statuses end poll waits early (None = polls only)
+    private_state: Option<PrivateState>,
+    /// The normal lane's heartbeat, beaten per placement of a repriced entry
+    entry_heartbeat: ActorHeartbeat,
+    /// Symbols with an entry of ours on them, for the crash flatten
+    owned: OwnedPositions,
 }
 
 impl ExecutionActor {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         client: BybitClient,
         config: Arc<Config>,
-        message_rx: mpsc::Receiver<ExecutionMessage>,
-        strategy_tx: mpsc::Sender<StrategyMessage>,
+        message_rx: MeteredReceiver<Traced<ExecutionMessage>>,
+        priority_rx: MeteredReceiver<Traced<ExecutionMessage>>,
+        strategy_tx: MeteredSender<StrategyMessage>,
+        notifier_tx: MeteredSender<NotifierMessage>,
+        specs_cache: SpecsCache,
+        journal: Journal,
+        queue: QueueBoard,
+        private_state: Option<PrivateState>,
+        owned: OwnedPositions,
+        heartbeat: ActorHeartbeat,
+        priority_heartbeat: ActorHeartbeat,
     ) -> Self {
+        let throttle = OrderThrottle::new(config.max_orders_per_minute, config.max_orders_per_hour);
+        let duplicates = DuplicateOrderGuard::new(config.duplicate_order_window_secs);
+        let timing = PollTiming::from_config(&config);
         Self {
-            client,
-            config,
+            core: Arc::new(ExecutionCore {
+                client,
+                config,
+                strategy_tx,
+                notifier_tx,
+                specs_cache,
+                throttle: Mutex::new(throttle),
+                duplicates: Mutex::new(duplicates),
+                leverage_applied: Mutex::new(HashSet::new()),
+                hard_stops: Mutex::new(HashSet::new()),
+                journal,
+                queue,
+                timing,
+                private_state,
+                entry_heartbeat: heartbeat.clone(),
+                owned,
+            }),
             message_rx,
-            strategy_tx,
+            priority_rx,
+            heartbeat,
+            priority_heartbeat,
         }
     }
 
-    pub async fn run(mut self) {
+    pub async fn run(self) {
         info!("💼 ExecutionActor started");
 
-        while let Some(msg) = self.message_rx.recv().await {
-            match msg {
-                ExecutionMessage::PlaceOrder(order) => {
-                    self.handle_place_order(order).await;
-                }
-                ExecutionMessage::ClosePosition { symbol, position_side } => {
-                    self.handle_close_position(symbol, position_side).await;
+        let ExecutionActor {
+            core,
+            mut message_rx,
+            mut priority_rx,
+            heartbeat,
+            priority_heartbeat,
+        } = self;
+
+        let priority_core = core.clone();
+        let mut priority_lane = tokio::spawn(async move {
+            let mut liveness_interval = interval(Duration::from_secs(EXECUTION_LIVENESS_SECS));
+            loop {
+                priority_heartbeat.beat();
+                tokio::select! {
+                    Some(Traced { msg, span }) = priority_rx.recv() => {
+                        if !priority_core.handle(msg).instrument(span).await {
+                            break;
+                        }
+                    }
+                    _ = liveness_interval.tick() => {}
+                    else => break,
                 }
-                ExecutionMessage::GetPosition(symbol) => {
-                    self.handle_get_position(symbol).await;
+            }
+        });
+
+        // ✅ WATCHDOG: Idle tick so a quiet (but healthy) actor still reports progress
+        let mut liveness_interval = interval(Duration::from_secs(EXECUTION_LIVENESS_SECS));
+
+        loop {
+            heartbeat.beat();
+            tokio::select! {
+                Some(Traced { msg, span }) = message_rx.recv() => {
+                    if !core.handle(msg).instrument(span).await {
+                        break;
+                    }
                 }
-                ExecutionMessage::Shutdown => {
-                    info!("ExecutionActor shutting down");
-                    break;
+                _ = liveness_interval.tick() => {}
+                else => break,
+            }
+        }
+
+        // Let an in-flight close (or close ladder) finish verifying before the lane goes away
+        if tokio::time::timeout(Duration::from_secs(PRIORITY_DRAIN_SECS), &mut priority_lane)
+            .await
+            .is_err()
+        {
+            warn!("⚠️ Priority lane still busy after {}s, aborting it", PRIORITY_DRAIN_SECS);
+            priority_lane.abort();
+        }
+    }
+}
+
+impl ExecutionCore {
+    /// Returns false on Shutdown
+    async fn handle(&self, msg: ExecutionMessage) -> bool {
+        match msg {
+            ExecutionMessage::PlaceOrder(order) => {
+                self.handle_place_order(order).await;
+            }
+            ExecutionMessage::ClosePosition { symbol, position_side, reason } => {
+                self.handle_close_position(symbol, position_side, reason).await;
+            }
+            ExecutionMessage::GetPosition { symbol, query } => {
+                self.report_position(symbol, Some(query)).await;
+            }
+            ExecutionMessage::PlaceOrderWithReply { order, reply } => {
+                let _ = reply.send(self.handle_place_order(order).await);
+            }
+            ExecutionMessage::GetPositionWithReply { symbol, reply } => {
+                let response = match self.fetch_position(&symbol).await {
+                    Ok(position) => ExecutionResponse::CurrentPosition(position),
+                    Err(e) => ExecutionResponse::Error(e),
+                };
+                let _ = reply.send(response);
+            }
+            ExecutionMessage::Reconcile(symbol) => {
+                self.handle_reconcile(symbol).await;
+            }
+            ExecutionMessage::PlacePair { legs, reply } => {
+                let _ = reply.send(self.handle_place_pair(legs).await);
+            }
+            ExecutionMessage::ClosePair { long, short, reply } => {
+                let _ = reply.send(self.handle_close_pair(long, short).await);
+            }
+            ExecutionMessage::Shutdown => {
+                info!("ExecutionActor shutting down");
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Drop the cached specs for a symbol and push freshly fetched ones to the strategy
+    async fn refresh_specs(&self, symbol: &str) {
+        self.specs_cache.invalidate(symbol);
+        match self.specs_cache.refresh(&self.client, symbol).await {
+            Ok(specs) => {
+                if let Err(e) = self.strategy_tx.send(StrategyMessage::SpecsRefreshed(specs)).await {
+                    error!("Failed to send SpecsRefreshed message: {}", e);
                 }
             }
+            Err(e) => warn!("⚠️ Failed to refetch specs for {}: {}", symbol, e),
         }
     }
 
-    async fn handle_place_order(&self, order: Order) {
+    /// Apply the configured leverage once per symbol (a failure leaves the exchange setting in place)
+    async fn ensure_leverage(&self, symbol: &str) {
+        let Some(leverage) = self.config.leverage else {
+            return;
+        };
+        if self.leverage_applied.lock().contains(symbol) {
+            return;
+        }
+        match self.client.set_leverage(symbol, &leverage.to_string()).await {
+            Ok(()) => {
+                info!("⚙️ Leverage for {} set to {}x", symbol, leverage);
+                self.leverage_applied.lock().insert(symbol.to_string());
+            }
+            Err(e) => warn!("⚠️ Failed to set leverage for {}: {}", symbol, e),
+        }
+    }
+
+    /// Configured leverage once it's applied on the symbol, else the symbol's current
+    /// exchange setting (also after a failed `set_leverage`), else 1x
+    async fn effective_leverage(&self, symbol: &str) -> Decimal {
+        if self.leverage_applied.lock().contains(symbol) {
+            if let Some(leverage) = self.config.leverage.and_then(Decimal::from_f64) {
+                return leverage;
+            }
+        }
+        match self.client.get_position(symbol).await {
+            Ok(positions) => positions
+                .first()
+                .and_then(|p| Decimal::from_str(&p.leverage).ok())
+                .filter(|l| *l >= Decimal::ONE)
+                .unwrap_or(Decimal::ONE),
+            Err(_) => Decimal::ONE,
+        }
+    }
+
+    /// ✅ MARGIN CHECK: Size an entry down to the available margin at our leverage, or refuse it,
+    /// instead of letting the exchange reject it as "insufficient balance".
+    /// If the check itself can't run (no price, wallet unavailable) the order goes out unchanged.
+    async fn fit_to_margin(&self, order: Order) -> Result<Order, String> {
+        let symbol = order.symbol.0.clone();
+        self.ensure_leverage(&symbol).await;
+
+        let Some(price) = order.price.or(order.ref_price) else {
+            return Ok(order);
+        };
+        let Some(available) = self.available_margin().await else {
+            return Ok(order);
+        };
+
+        let leverage = self.effective_leverage(&symbol).await;
+        let max_notional = available * leverage * MARGIN_USAGE_LIMIT;
+        let notional = order.qty * price;
+        if notional <= max_notional {
+            return Ok(order);
+        }
+
+        match self.specs_cache.get_or_default(&symbol).fit_notional(order.qty, price, max_notional) {
+            Some(qty) => {
+                warn!(
+                    "💸 Sizing {} down {} → {}: needs {:.2} USDT margin at {}x, available {:.2}",
+                    symbol, order.qty, qty, notional / leverage, leverage, available
+                );
+                Ok(Order { qty, ..order })
+            }
+            None => Err(format!(
+                "Insufficient margin for {} {}: needs {:.2} USDT at {}x, available {:.2}",
+                symbol, order.qty, notional / leverage, leverage, available
+            )),
+        }
+    }
+
+    /// Both pair legs scaled down by the same factor until their margin fits together, so a
+    /// short wallet never leaves one leg bigger than the other. Same fallbacks as `fit_to_margin`
+    async fn fit_pair_to_margin(&self, long: Order, short: Order) -> Result<(Order, Order), String> {
+        tokio::join!(self.ensure_leverage(&long.symbol.0), self.ensure_leverage(&short.symbol.0));
+
+        let (Some(long_price), Some(short_price)) = (long.price.or(long.ref_price), short.price.or(short.ref_price)) else {
+            return Ok((long, short));
+        };
+        let Some(available) = self.available_margin().await else {
+            return Ok((long, short));
+        };
+
+        let (long_leverage, short_leverage) =
+            tokio::join!(self.effective_leverage(&long.symbol.0), self.effective_leverage(&short.symbol.0));
+        let (long_notional, short_notional) = (long.qty * long_price, short.qty * short_price);
+        let needed = long_notional / long_leverage + short_notional / short_leverage;
+        let budget = available * MARGIN_USAGE_LIMIT;
+        if needed <= budget {
+            return Ok((long, short));
+        }
+
+        let scale = budget / needed;
+        let long_qty = self.specs_cache.get_or_default(&long.symbol.0).fit_notional(long.qty, long_price, long_notional * scale);
+        let short_qty = self.specs_cache.get_or_default(&short.symbol.0).fit_notional(short.qty, short_price, short_notional * scale);
+        match (long_qty, short_qty) {
+            (Some(long_qty), Some(short_qty)) => {
+                warn!(
+                    "💸 Sizing pair down: {} {} → {}, {} {} → {} (needs {:.2} USDT margin, available {:.2})",
+                    long.symbol, long.qty, long_qty, short.symbol, short.qty, short_qty, needed, available
+                );
+                Ok((Order { qty: long_qty, ..long }, Order { qty: short_qty, ..short }))
+            }
+            _ => Err(format!(
+                "Insufficient margin for pair {} / {}: needs {:.2} USDT, available {:.2}",
+                long.symbol, short.symbol, needed, available
+            )),
+        }
+    }
+
+    /// Available balance for the margin checks (None = unknown, the check is skipped)
+    async fn available_margin(&self) -> Option<Decimal> {
+        let wallet = match self.client.get_wallet_balance().await {
+            Ok(wallet) => wallet,
+            Err(e) => {
+                warn!("⚠️ Margin check skipped, wallet unavailable: {}", e);
+                return None;
+            }
+        };
+        match Decimal::from_str(&wallet.total_available_balance) {
+            Ok(available) => Some(available),
+            Err(_) => {
+                warn!("⚠️ Margin check skipped, no available balance for {} account", wallet.account_type);
+                None
+            }
+        }
+    }
+
+    /// The answer for a caller waiting on the entry; the strategy is told either way
+    async fn handle_place_order(&self, order: Order) -> ExecutionResponse {
         let symbol = order.symbol.clone();
+        let requested = order.clone();
+        let now = Instant::now();
+
+        // ✅ DUPLICATE GUARD + THROTTLE: A double-fired or runaway strategy is stopped here,
+        // not by the exchange. Closes are never held back
+        if !order.reduce_only {
+            let duplicate = self.duplicates.lock().check(&order, now);
+            if let Err(duplicate) = duplicate {
+                error!("🛑 Entry blocked: {}", duplicate);
+                self.notify(Alert::error(format!(
+                    "🛑 <b>Duplicate entry blocked</b>\n{}.\nThe strategy sent the same order twice - check its state machine",
+                    duplicate
+                )));
+                return self.fail_entry(symbol, duplicate.to_string()).await;
+            }
+
+            let throttled = self.throttle.lock().admit(now);
+            if let Err(throttled) = throttled {
+                error!("🛑 Entry blocked: {}", throttled);
+                if throttled.first {
+                    self.notify(Alert::critical(format!(
+                        "🛑 <b>Order throttle tripped</b>\n{} {:?} {} blocked: {}.\nEntries stay blocked while the rate holds - check the strategy for a signal loop",
+                        symbol, order.side, order.qty, throttled
+                    )));
+                }
+                return self.fail_entry(symbol, throttled.to_string()).await;
+            }
+        }
+
+        // Closes are reduce-only and never need new margin
+        let order = if order.reduce_only {
+            order
+        } else {
+            match self.fit_to_margin(order).await {
+                Ok(order) => order,
+                Err(reason) => {
+                    warn!("💸 Entry skipped: {}", reason);
+                    return self.fail_entry(symbol, reason).await;
+                }
+            }
+        };
+
+        let (order_id, outcome) = self.run_entry(&order).await;
+        // Only an entry the exchange took blocks a repeat: a refused one may be sent again
+        if !order.reduce_only && !matches!(outcome, Outcome::Failed { .. }) {
+            self.duplicates.lock().record(&requested, now);
+        }
+        let response = self.report_entry(symbol, &order_id, outcome).await;
+        if let (Some(percent), ExecutionResponse::OrderFilled { fill, .. }) = (order.hard_stop_percent, &response) {
+            self.arm_hard_stop(fill, percent).await;
+        }
+        response
+    }
+
+    /// ✅ HARD STOP: Exchange-side stop-loss behind the strategy's own (soft) stop. It only
+    /// fires if the bot can't close in time: crashed, cut off, or price gapped through the soft stop
+    async fn arm_hard_stop(&self, fill: &Fill, percent: f64) {
+        let Some(distance) = Decimal::from_f64(percent / 100.0) else {
+            return;
+        };
+        let raw = match fill.side {
+            OrderSide::Buy => fill.avg_price * (Decimal::ONE - distance),
+            OrderSide::Sell => fill.avg_price * (Decimal::ONE + distance),
+        };
+        let stop_price = self.specs_cache.get_or_default(&fill.symbol.0).round_price(raw);
+        match self.client.set_stop_loss(&fill.symbol.0, stop_price).await {
+            Ok(()) => {
+                info!("🧱 Hard stop for {} at {} ({:.2}% from entry {})", fill.symbol, stop_price, percent, fill.avg_price);
+                self.hard_stops.lock().insert(fill.symbol.clone());
+            }
+            Err(e) => {
+                error!("🧱 Failed to set hard stop for {}: {}", fill.symbol, e);
+                self.notify(Alert::error(format!(
+                    "🧱 <b>Hard stop not set</b>\n{} {:?} @ {}: {}\nOnly the bot's own stop covers the position",
+                    fill.symbol,
+                    fill.side,
+                    fill.avg_price,
+                    escape_html(&e.to_string())
+                )));
+            }
+        }
+    }
+
+    /// Entry refused before reaching the exchange (not retryable)
+    async fn fail_entry(&self, symbol: Symbol, reason: String) -> ExecutionResponse {
+        self.send_order_failed(symbol, reason.clone(), false).await;
+        ExecutionResponse::OrderFailed { reason, retryable: false }
+    }
+
+    /// Place an entry and follow it to a verified outcome. Returns the order ID ("" if never placed)
+    ///
+    /// ✅ QUEUE POSITION: A PostOnly entry whose queue won't reach it in time (or that the
+    /// touch left behind) is withdrawn and re-placed, up to ENTRY_MAX_REPRICES times and no
+    /// further than ENTRY_MAX_REPRICE_BPS from the first price. Each re-place is a new order
+    /// for the throttle
+    async fn run_entry(&self, order: &Order) -> (String, Outcome) {
+        let mut order = order.clone();
+        let first_price = order.price;
+        let mut reprices = 0;
+        loop {
+            // Every placement can hold the lane for a whole poll window
+            self.entry_heartbeat.beat();
+            let queued = reprices < self.config.entry_max_reprices
+                && !order.reduce_only
+                && order.order_type == OrderType::Limit
+                && order.time_in_force == TimeInForce::PostOnly;
+            let reprice_from = first_price.filter(|_| queued);
+            let (order_id, outcome, reprice) = self.place_and_follow(&order, reprice_from).await;
+            match (reprice, outcome) {
+                // Withdrawn with nothing filled: go again at the new price
+                (Some(price), Outcome::Failed { .. }) => {
+                    let throttled = self.throttle.lock().admit(Instant::now());
+                    if let Err(throttled) = throttled {
+                        warn!("🛑 Reprice of {} entry blocked: {}", order.symbol, throttled);
+                        return (order_id, Outcome::Failed { reason: throttled.to_string(), retryable: false });
+                    }
+                    reprices += 1;
+                    info!(
+                        "🔁 Repricing {} entry {:?} → {} ({}/{})",
+                        order.symbol, order.price, price, reprices, self.config.entry_max_reprices
+                    );
+                    order.price = Some(price);
+                }
+                (_, outcome) => return (order_id, outcome),
+            }
+        }
+    }
+
+    /// One placement of `order`; with `reprice_from` (the first placement's price), also the
+    /// price it was withdrawn to reprice at
+    async fn place_and_follow(&self, order: &Order, reprice_from: Option<Decimal>) -> (String, Outcome, Option<Decimal>) {
+        let symbol = &order.symbol;
         let symbol_str = symbol.0.clone();
 
         info!(
@@ -64,338 +543,762 @@ impl ExecutionActor {
         );
 
         // Step 1: Place order
-        let order_id = match self.client.place_order(&order).await {
+        // ✅ LIFECYCLE: place → poll → cancel → verify is decided in OrderLifecycle, the API calls happen here
+        let mut lifecycle = OrderLifecycle::new(self.timing);
+        let mut finished = self.finished_orders();
+        if !order.reduce_only {
+            self.owned.opening(symbol);
+        }
+        let mut action = match self.client.place_order(order).await {
             Ok(response) => {
                 info!("✅ Order accepted by exchange: {}", response.order_id);
-                response.order_id
+                lifecycle.placed(response.order_id)
             }
             Err(e) => {
                 let error_msg = format!("Failed to place order: {}", e);
-                error!("❌ {}", error_msg);
-
-                // Notify strategy that order failed
-                if let Err(e) = self
-                    .strategy_tx
-                    .send(StrategyMessage::OrderFailed(error_msg))
-                    .await
-                {
-                    error!("Failed to send OrderFailed message: {}", e);
+                match e {
+                    BybitError::Auth { .. } => error!("🔑 {} (check API key permissions / IP whitelist)", error_msg),
+                    BybitError::InsufficientBalance { .. } => error!("💸 {}", error_msg),
+                    _ => error!("❌ {}", error_msg),
                 }
-                return;
-            }
-        };
-
-        // ✅ FIXED: Step 2 - Poll for order confirmation (up to 10 seconds)
-        let max_polls = 20; // 20 polls × 500ms = 10 seconds
-        let poll_interval = tokio::time::Duration::from_millis(500);
 
-        for attempt in 1..=max_polls {
-            tokio::time::sleep(poll_interval).await;
+                // ✅ SPECS: Lot/tick size probably changed on the exchange - refetch before the next attempt
+                if e.is_precision_rejection() {
+                    self.refresh_specs(&symbol_str).await;
+                }
 
-            match self.client.get_order_status(&symbol_str, &order_id).await {
-                Ok(order_status) => {
-                    info!(
-                        "📊 Order {} status: {} (attempt {}/{})",
-                        order_id, order_status.order_status, attempt, max_polls
-                    );
+                lifecycle.place_failed(error_msg, e.is_retryable())
+            }
+        };
+        let order_id = lifecycle.order_id().unwrap_or_default().to_string();
 
-                    match order_status.order_status.as_str() {
-                        "Filled" => {
-                            info!("✅ Order {} FILLED", order_id);
-
-                            // Notify strategy
-                            if let Err(e) = self
-                                .strategy_tx
-                                .send(StrategyMessage::OrderFilled(symbol.clone()))
-                                .await
-                            {
-                                error!("Failed to send OrderFilled message: {}", e);
-                            }
+        // The lifecycle cancels a resting order after ENTRY_MAX_POLLS polls
+        let placed_at = Instant::now();
+        let deadline = placed_at + self.timing.window();
+        let queued = match (reprice_from.is_some() && !order_id.is_empty(), order.price) {
+            (true, Some(price)) => {
+                self.queue.track(symbol.clone(), QueueEstimate::new(order.side, price, order.qty, placed_at));
+                true
+            }
+            _ => false,
+        };
+        let tick_size = queued.then(|| self.specs_cache.get_or_default(&symbol_str).tick_size);
+        let mut reprice = None;
 
-                            // Query position and send update
-                            self.handle_get_position(symbol).await;
-                            return;
-                        }
-                        "Cancelled" | "Rejected" => {
-                            let error_msg = format!("Order {} {}", order_id, order_status.order_status);
-                            error!("❌ {}", error_msg);
-
-                            if let Err(e) = self
-                                .strategy_tx
-                                .send(StrategyMessage::OrderFailed(error_msg))
-                                .await
-                            {
-                                error!("Failed to send OrderFailed message: {}", e);
-                            }
-                            return;
+        // ✅ FIXED: Step 2 - Poll for order confirmation (up to 10 seconds), then cancel and verify
+        let outcome = loop {
+            action = match action {
+                Action::Poll { after } => {
+                    self.wait_for_order(&mut finished, &order_id, after).await;
+                    let next = lifecycle.polled(self.client.get_order_status(&symbol_str, &order_id).await);
+                    match (&next, self.queue.assess(symbol, tick_size, Instant::now(), deadline)) {
+                        (Action::Poll { .. }, Some((QueueCall::Reprice(price), ahead)))
+                            if reprice_from.is_some_and(|from| {
+                                reprice_drift_bps(from, price) <= self.config.entry_max_reprice_bps
+                            }) =>
+                        {
+                            info!("🚶 Order {} queue: ~{:?} ahead, withdrawing to reprice at {}", order_id, ahead, price);
+                            reprice = Some(price);
+                            lifecycle.withdraw()
                         }
-                        "PartiallyFilled" | "New" => {
-                            // Continue polling
-                            continue;
+                        (_, Some((QueueCall::Reprice(price), _))) => {
+                            debug!("🚶 Order {} stays: {} is past ENTRY_MAX_REPRICE_BPS from the first price", order_id, price);
+                            next
                         }
-                        _ => {
-                            warn!("Unknown order status: {}", order_status.order_status);
-                            continue;
+                        (_, Some((QueueCall::Wait, ahead))) => {
+                            debug!("🚶 Order {} queue: ~{:?} ahead", order_id, ahead);
+                            next
                         }
+                        _ => next,
                     }
                 }
-                Err(e) => {
-                    warn!(
-                        "Failed to query order status (attempt {}/{}): {}",
-                        attempt, max_polls, e
-                    );
-                    continue;
+                Action::Cancel => {
+                    // Timeout - order not filled within 10 seconds
+                    // ✅ FIX BUG #20 & #21: CRITICAL race condition!
+                    // Between cancel request and response, order might FILL or PARTIALLY FILL
+                    // We MUST verify final state before reporting failure
+                    if reprice.is_none() {
+                        warn!("⏰ Order {} timeout after 10s, attempting to cancel...", order_id);
+                    }
+                    if let Err(e) = self.client.cancel_order(&symbol_str, &order_id).await {
+                        error!("Failed to cancel timed-out order: {}", e);
+                    }
+                    lifecycle.cancel_sent()
                 }
-            }
+                Action::Verify { after } => {
+                    // ✅ CRITICAL: Query final order status after cancel
+                    // The order might have filled DURING the cancel API call!
+                    self.wait_for_order(&mut finished, &order_id, after).await; // Let cancel settle
+                    lifecycle.verified(self.client.get_order_status(&symbol_str, &order_id).await)
+                }
+                Action::Finish(outcome) => break outcome,
+            };
+        };
+        if queued {
+            self.queue.untrack(symbol);
         }
+        (order_id, outcome, reprice)
+    }
 
-        // Timeout - order not filled within 10 seconds
-        // ✅ FIX BUG #20 & #21: CRITICAL race condition!
-        // Between cancel request and response, order might FILL or PARTIALLY FILL
-        // We MUST verify final state before reporting failure
-        warn!("⏰ Order {} timeout after 10s, attempting to cancel...", order_id);
-
-        if let Err(e) = self.client.cancel_order(&symbol_str, &order_id).await {
-            error!("Failed to cancel timed-out order: {}", e);
-        }
+    /// Tell the strategy how the entry ended (and confirm any position it left)
+    async fn report_entry(&self, symbol: Symbol, order_id: &str, outcome: Outcome) -> ExecutionResponse {
+        match outcome {
+            Outcome::Filled { status, during_cancel } => {
+                if during_cancel {
+                    // ✅ Order FILLED during cancel! This is the race condition!
+                    warn!("⚠️  BUG #20 CAUGHT! Order {} filled DURING cancel attempt", order_id);
+                    info!("✅ Order {} FILLED (detected after cancel)", order_id);
+                } else {
+                    info!("✅ Order {} FILLED", order_id);
+                }
 
-        // ✅ CRITICAL: Query final order status after cancel
-        // The order might have filled DURING the cancel API call!
-        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await; // Let cancel settle
+                // Notify strategy
+                let fill = fill_from_status(&symbol, &status);
+                if let Err(e) = self.strategy_tx.send(StrategyMessage::OrderFilled(fill.clone())).await {
+                    error!("Failed to send OrderFilled message: {}", e);
+                }
 
-        match self.client.get_order_status(&symbol_str, &order_id).await {
-            Ok(final_status) => {
-                match final_status.order_status.as_str() {
-                    "Filled" => {
-                        // ✅ Order FILLED during cancel! This is the race condition!
-                        warn!("⚠️  BUG #20 CAUGHT! Order {} filled DURING cancel attempt", order_id);
-                        info!("✅ Order {} FILLED (detected after cancel)", order_id);
+                // Query position and send update
+                self.handle_get_position(symbol).await;
+                ExecutionResponse::OrderFilled { fill, partial: false }
+            }
+            Outcome::PartiallyFilled(status) => {
+                // ✅ BUG #21: Partial fill exists!
+                warn!("⚠️  BUG #21 CAUGHT! Order {} PARTIALLY filled: {}/{}",
+                      order_id, status.cum_exec_qty, status.qty);
 
-                        if let Err(e) = self
-                            .strategy_tx
-                            .send(StrategyMessage::OrderFilled(symbol.clone()))
-                            .await
-                        {
-                            error!("Failed to send OrderFilled message: {}", e);
-                        }
+                // Query position - partial position exists!
+                self.handle_get_position(symbol.clone()).await;
 
-                        // Query position to confirm
-                        self.handle_get_position(symbol).await;
-                        return;
-                    }
-                    "PartiallyFilled" => {
-                        // ✅ BUG #21: Partial fill exists!
-                        warn!("⚠️  BUG #21 CAUGHT! Order {} PARTIALLY filled: {}/{}",
-                              order_id, final_status.cum_exec_qty, final_status.qty);
-
-                        // Query position - partial position exists!
-                        self.handle_get_position(symbol).await;
-
-                        // Notify strategy that partial fill occurred (not a full failure)
-                        let error_msg = format!(
-                            "Order {} partially filled ({}/{}), then cancelled",
-                            order_id, final_status.cum_exec_qty, final_status.qty
-                        );
-                        warn!("{}", error_msg);
-
-                        if let Err(e) = self
-                            .strategy_tx
-                            .send(StrategyMessage::OrderFailed(error_msg))
-                            .await
-                        {
-                            error!("Failed to send OrderFailed message: {}", e);
-                        }
-                        return;
-                    }
-                    "Cancelled" | "Rejected" => {
-                        // Truly cancelled/rejected - safe to report failure
-                        let error_msg = format!("Order {} {} after timeout", order_id, final_status.order_status);
-                        info!("✅ Verified: {}", error_msg);
-
-                        if let Err(e) = self
-                            .strategy_tx
-                            .send(StrategyMessage::OrderFailed(error_msg))
-                            .await
-                        {
-                            error!("Failed to send OrderFailed message: {}", e);
-                        }
-                        return;
-                    }
-                    _ => {
-                        warn!("Unknown final order status: {}", final_status.order_status);
-                    }
+                // Notify strategy that partial fill occurred (not a full failure)
+                let reason = format!(
+                    "Order {} partially filled ({}/{}), then cancelled",
+                    order_id, status.cum_exec_qty, status.qty
+                );
+                warn!("{}", reason);
+                self.send_order_failed(symbol.clone(), reason, false).await;
+                ExecutionResponse::OrderFilled { fill: fill_from_status(&symbol, &status), partial: true }
+            }
+            Outcome::Failed { reason, retryable } => {
+                // Nothing on the exchange - safe to report failure (placement errors are already logged)
+                if !order_id.is_empty() {
+                    error!("❌ {}", reason);
                 }
+                self.send_order_failed(symbol, reason.clone(), retryable).await;
+                ExecutionResponse::OrderFailed { reason, retryable }
             }
-            Err(e) => {
+            Outcome::Unknown { reason, retryable } => {
                 // ✅ DEFENSIVE: If we can't query status, check position anyway
-                error!("Failed to verify final order status: {}", e);
+                error!("Failed to verify final order status: {}", reason);
                 warn!("⚠️  Cannot confirm order state, checking position defensively...");
-                self.handle_get_position(symbol).await;
+                self.handle_get_position(symbol.clone()).await;
 
                 // Report failure but position check will reveal truth
-                let error_msg = format!("Order {} cancel attempted, final state unknown", order_id);
-                if let Err(e) = self
-                    .strategy_tx
-                    .send(StrategyMessage::OrderFailed(error_msg))
-                    .await
-                {
-                    error!("Failed to send OrderFailed message: {}", e);
+                self.send_order_failed(symbol, reason.clone(), retryable).await;
+                ExecutionResponse::Error(reason)
+            }
+            Outcome::Unrecognized(status) => {
+                warn!("Unknown final order status: {}", status);
+                ExecutionResponse::Error(format!("order {} ended {}", order_id, status))
+            }
+        }
+    }
+
+    /// Queue an alert without waiting on the notifier
+    fn notify(&self, alert: Alert) {
+        if let Err(e) = self.notifier_tx.try_send(NotifierMessage::Alert(alert)) {
+            debug!("Dropped alert: {}", e);
+        }
+    }
+
+    async fn send_order_failed(&self, symbol: Symbol, reason: String, retryable: bool) {
+        if let Err(e) = self
+            .strategy_tx
+            .send(StrategyMessage::OrderFailed { symbol, reason, retryable })
+            .await
+        {
+            error!("Failed to send OrderFailed message: {}", e);
+        }
+    }
+
+    /// ✅ PAIR TRADING: Both legs go out at once and a pair is only ever held whole. If either
+    /// leg fails, both symbols are flattened (an unverified leg may have filled after all)
+    /// before the entry is reported failed
+    async fn handle_place_pair(&self, legs: PairOrder) -> PairReport {
+        let PairOrder { long, short } = legs;
+        info!("🔗 Pair entry: long {} {} / short {} {}", long.qty, long.symbol, short.qty, short.symbol);
+
+        // Two entries for the throttle; the duplicate guard doesn't apply (two symbols by design).
+        // Slots of a pair that never goes out are given back
+        let now = Instant::now();
+        for admitted in 0..2 {
+            let throttled = self.throttle.lock().admit(now);
+            if let Err(throttled) = throttled {
+                error!("🛑 Pair entry blocked: {}", throttled);
+                self.refund_throttle(now, admitted);
+                return PairReport::Failed(throttled.to_string());
+            }
+        }
+        let (long, short) = match self.fit_pair_to_margin(long, short).await {
+            Ok(legs) => legs,
+            Err(reason) => {
+                warn!("💸 Pair entry skipped: {}", reason);
+                self.refund_throttle(now, 2);
+                return PairReport::Failed(reason);
+            }
+        };
+
+        let (long_symbol, short_symbol) = (long.symbol.clone(), short.symbol.clone());
+        let (long_fill, short_fill) = tokio::join!(self.enter_leg(long), self.enter_leg(short));
+        let failures = match (long_fill, short_fill) {
+            (Ok(long), Ok(short)) => {
+                info!("🔗 Pair open: long {} @ {} / short {} @ {}", long.symbol, long.avg_price, short.symbol, short.avg_price);
+                return PairReport::Opened { long, short };
+            }
+            (long_fill, short_fill) => [long_fill.err(), short_fill.err()].into_iter().flatten().collect::<Vec<_>>(),
+        };
+
+        let reason = failures.join("; ");
+        warn!("🔗 Pair entry failed ({}), unwinding both legs", reason);
+        let unwound = self.flatten_pair(&long_symbol, &short_symbol).await;
+        match unwound {
+            Ok(_) => PairReport::Failed(reason),
+            Err(stranded) => self.strand_pair(&format!("Entry failed ({}), unwind failed", reason), stranded).await,
+        }
+    }
+
+    fn refund_throttle(&self, admitted_at: Instant, slots: usize) {
+        let mut throttle = self.throttle.lock();
+        for _ in 0..slots {
+            throttle.refund(admitted_at);
+        }
+    }
+
+    async fn handle_close_pair(&self, long: Symbol, short: Symbol) -> PairReport {
+        info!("🔗 Pair exit: {} / {}", long, short);
+        match self.flatten_pair(&long, &short).await {
+            Ok((long, short)) => PairReport::Closed { long, short },
+            Err(stranded) => self.strand_pair("Exit failed", stranded).await,
+        }
+    }
+
+    /// One pair leg, already sized: the entry lifecycle, without reporting to the strategy
+    /// (a smaller, partially filled leg still counts as entered)
+    async fn enter_leg(&self, order: Order) -> Result<Fill, String> {
+        let symbol = order.symbol.clone();
+        match self.run_entry(&order).await {
+            (_, Outcome::Filled { status, .. }) | (_, Outcome::PartiallyFilled(status)) => Ok(fill_from_status(&symbol, &status)),
+            (_, Outcome::Failed { reason, .. }) | (_, Outcome::Unknown { reason, .. }) => Err(format!("{}: {}", symbol, reason)),
+            (order_id, Outcome::Unrecognized(status)) => Err(format!("{}: order {} ended {}", symbol, order_id, status)),
+        }
+    }
+
+    /// Flatten both legs, each retried once after cancelling the symbol's open orders.
+    /// Err lists the legs that are still open
+    async fn flatten_pair(&self, long: &Symbol, short: &Symbol) -> Result<(Option<Fill>, Option<Fill>), Vec<String>> {
+        let (long_fill, short_fill) = tokio::join!(self.flatten_leg_retried(long), self.flatten_leg_retried(short));
+        match (long_fill, short_fill) {
+            (Ok(long), Ok(short)) => Ok((long, short)),
+            (long_fill, short_fill) => Err([long_fill.err(), short_fill.err()].into_iter().flatten().collect()),
+        }
+    }
+
+    async fn flatten_leg_retried(&self, symbol: &Symbol) -> Result<Option<Fill>, String> {
+        let flattened = match self.flatten_leg(symbol).await {
+            Ok(fill) => Ok(fill),
+            Err(failure) => {
+                warn!("🪜 Pair leg {} close failed ({}), retrying after cancelling open orders", symbol, failure);
+                if let Err(e) = self.client.cancel_own_orders(&symbol.0).await {
+                    warn!("Failed to cancel open orders on {}: {}", symbol, e);
                 }
+                self.flatten_leg(symbol).await
             }
+        };
+        if flattened.is_ok() {
+            self.owned.closed(symbol);
         }
+        flattened
     }
 
-    async fn handle_close_position(&self, symbol: Symbol, position_side: PositionSide) {
-        info!("🔒 Closing position for {} {:?}", symbol, position_side);
+    /// Market reduce-only close of whatever `symbol` holds. Pair legs aren't the strategy's
+    /// positions, so nothing is reported to it. Ok(None) = nothing was open
+    async fn flatten_leg(&self, symbol: &Symbol) -> Result<Option<Fill>, String> {
+        let positions = self
+            .client
+            .get_position(&symbol.0)
+            .await
+            .map_err(|e| format!("{}: failed to get position: {}", symbol, e))?;
+        let Some((side, size)) = positions.iter().find_map(|p| {
+            let size = Decimal::from_str(&p.size).unwrap_or(Decimal::ZERO);
+            (size > Decimal::ZERO).then(|| (p.side.clone(), size))
+        }) else {
+            return Ok(None);
+        };
+
+        let order = Order {
+            symbol: symbol.clone(),
+            side: if side == "Buy" { OrderSide::Sell } else { OrderSide::Buy },
+            order_type: OrderType::Market,
+            qty: size,
+            price: None,
+            time_in_force: TimeInForce::IOC,
+            reduce_only: true,
+            qty_step: Some(self.specs_cache.get_or_default(&symbol.0).qty_step),
+            tick_size: None,
+            ref_price: None,
+            allow_duplicate: false,
+            hard_stop_percent: None,
+            position_idx: 0,
+        };
+        info!("📤 Closing pair leg {}: {:?} {} (reduce_only)", symbol, order.side, size);
+        let mut finished = self.finished_orders();
+        let order_id = match self.client.place_order(&order).await {
+            Ok(response) => response.order_id,
+            // 110017: reduce-only rejected because the position is already flat
+            Err(BybitError::InvalidOrder { code: 110017, .. }) => return Ok(None),
+            Err(e) => return Err(format!("{}: close rejected: {}", symbol, e)),
+        };
 
+        for _ in 0..self.config.close_max_polls {
+            self.wait_for_order(&mut finished, &order_id, self.timing.interval).await;
+            match self.client.get_order_status(&symbol.0, &order_id).await {
+                Ok(status) => match status.order_status.as_str() {
+                    "Filled" => return Ok(Some(fill_from_status(symbol, &status))),
+                    "Cancelled" | "Rejected" | "PartiallyFilledCanceled" => {
+                        return Err(format!("{}: close order {} {}", symbol, order_id, status.order_status))
+                    }
+                    _ => continue,
+                },
+                Err(e) => warn!("Pair leg close poll for {} failed: {}", symbol, e),
+            }
+        }
+        Err(format!("{}: close order {} unverified", symbol, order_id))
+    }
+
+    /// A leg that can't be closed: stop the strategy's entries and ask for a manual close
+    async fn strand_pair(&self, what: &str, stranded: Vec<String>) -> PairReport {
+        let reason = format!("{}: {}", what, stranded.join("; "));
+        error!("🛑 Pair leg still open, engaging kill switch: {}", reason);
+        let mut text = format!("🛑 <b>PAIR LEG STRANDED</b>\n{}", escape_html(&reason));
+        match self
+            .strategy_tx
+            .send(StrategyMessage::Control(ControlCommand::KillSwitch))
+            .await
+        {
+            Ok(()) => text.push_str("\nKill switch engaged. CLOSE THE LEG ON THE EXCHANGE"),
+            Err(e) => {
+                error!("Failed to engage kill switch: {}", e);
+                text.push_str("\n⚠️ Kill switch failed — CHECK THE EXCHANGE");
+            }
+        }
+        self.notify(Alert::critical(text));
+        PairReport::Stranded(reason)
+    }
+
+    /// ✅ CLOSE LADDER: A close that is rejected or can't be verified is not left at
+    /// "POSITION STILL EXISTS!". It is retried at the exchange's current size with fresh lot
+    /// specs, then once more after cancelling the bot's own open orders on the symbol (resting
+    /// reduce-only orders can lock the size; the hard stop stays). What failed rungs filled is
+    /// reported with the close that finishes the position. If the position still stands, the
+    /// kill switch stops new entries and a critical alert asks for a manual close.
+    async fn handle_close_position(&self, symbol: Symbol, position_side: PositionSide, reason: ExitReason) {
+        info!("🔒 Closing position for {} {:?} ({})", symbol, position_side, reason);
+
+        let mut failures = Vec::new();
+        let mut filled = None;
+        for step in CloseStep::LADDER {
+            match step {
+                CloseStep::First => {}
+                CloseStep::AdjustedSize => {
+                    warn!("🪜 Close retry for {}: current size, fresh lot specs", symbol);
+                    self.refresh_specs(&symbol.0).await;
+                }
+                CloseStep::AfterCancelOwn => {
+                    warn!("🪜 Close retry for {}: cancelling our open orders first", symbol);
+                    if let Err(e) = self.client.cancel_own_orders(&symbol.0).await {
+                        warn!("Failed to cancel open orders on {}: {}", symbol, e);
+                    }
+                }
+            }
+            match self.try_close(&symbol, reason, step, &mut filled).await {
+                Ok(()) => return,
+                Err(failure) => {
+                    error!("❌ Close of {} failed ({}): {}", symbol, step, failure);
+                    failures.push(format!("{}: {}", step, failure));
+                }
+            }
+        }
+
+        error!("🛑 Position {} {:?} still open after every close attempt, engaging kill switch", symbol, position_side);
+        let mut text = format!(
+            "🛑 <b>CLOSE FAILED</b>\n{} {:?} ({}) is still open after {} attempts:\n{}",
+            symbol,
+            position_side,
+            reason,
+            failures.len(),
+            escape_html(&failures.join("\n"))
+        );
+        if let Some(fill) = filled {
+            text.push_str(&format!("\n{} already closed @ {}", fill.qty, fill.avg_price));
+            // The strategy books what did close and takes the rest from the position update below
+            self.send_close_filled(fill, reason).await;
+        }
+        // Before the position update: the strategy must not answer it with a new close of its own
+        match self
+            .strategy_tx
+            .send(StrategyMessage::Control(ControlCommand::KillSwitch))
+            .await
+        {
+            Ok(()) => text.push_str("\nKill switch engaged. CLOSE THE POSITION ON THE EXCHANGE"),
+            Err(e) => {
+                error!("Failed to engage kill switch: {}", e);
+                text.push_str("\n⚠️ Kill switch failed — CHECK THE EXCHANGE");
+            }
+        }
+        self.notify(Alert::critical(text));
+        self.handle_get_position(symbol).await;
+    }
+
+    /// One rung of the close ladder; Err = the position (probably) still exists.
+    /// `filled` carries what earlier rungs filled into the report of the closing one
+    async fn try_close(
+        &self,
+        symbol: &Symbol,
+        reason: ExitReason,
+        step: CloseStep,
+        filled: &mut Option<Fill>,
+    ) -> Result<(), String> {
         // First, get current position to determine size
-        match self.client.get_position(&symbol.0).await {
-            Ok(positions) => {
-                if positions.is_empty() {
-                    warn!("No position found for {}", symbol);
-                    // ✅ Still send PositionUpdate(None) so Strategy transitions correctly
-                    if let Err(e) = self
-                        .strategy_tx
-                        .send(StrategyMessage::PositionUpdate(None))
-                        .await
-                    {
-                        error!("Failed to send PositionUpdate(None): {}", e);
+        let positions = self
+            .client
+            .get_position(&symbol.0)
+            .await
+            .map_err(|e| format!("failed to get position: {}", e))?;
+        if positions.is_empty() {
+            warn!("No position found for {}", symbol);
+            if let Some(fill) = filled.take() {
+                self.send_close_filled(fill, reason).await;
+            }
+            // ✅ Still send PositionUpdate(None) so Strategy transitions correctly
+            self.send_flat(symbol).await;
+            return Ok(());
+        }
+
+        for pos_info in positions {
+            let size = Decimal::from_str(&pos_info.size).unwrap_or(Decimal::ZERO);
+
+            if size == Decimal::ZERO {
+                continue;
+            }
+
+            // Determine closing side (opposite of position)
+            let close_side = if pos_info.side == "Buy" {
+                OrderSide::Sell
+            } else {
+                OrderSide::Buy
+            };
+
+            // ✅ ESCALATING CLOSE: TP and trailing exits try a limit at the touch first
+            let mut size = size;
+            if step == CloseStep::First && self.escalates(symbol, reason) {
+                let (fill, remaining) = self.close_at_touch(symbol, close_side, size).await;
+                if remaining <= Decimal::ZERO {
+                    match fill {
+                        Some(fill) => {
+                            info!("✅ Close limit FILLED");
+                            self.send_close_filled(merge_fills(filled.take(), fill), reason).await;
+                            self.send_flat(symbol).await;
+                        }
+                        None => self.handle_get_position(symbol.clone()).await,
                     }
-                    return;
+                    return Ok(());
                 }
+                warn!("⏫ Close limit left {} of {} open, escalating to market", remaining, size);
+                if let Some(fill) = fill {
+                    keep_partial(filled, fill);
+                }
+                size = remaining;
+            }
 
-                for pos_info in positions {
-                    let size = Decimal::from_str(&pos_info.size).unwrap_or(Decimal::ZERO);
+            // Retries round the size with the freshly fetched lot specs
+            let qty_step = (step != CloseStep::First).then(|| self.specs_cache.get_or_default(&symbol.0).qty_step);
 
-                    if size == Decimal::ZERO {
-                        continue;
+            // Create closing market order
+            let close_order = Order {
+                symbol: symbol.clone(),
+                side: close_side,
+                order_type: OrderType::Market,
+                qty: size,
+                price: None,
+                time_in_force: TimeInForce::IOC,
+                reduce_only: true,
+                qty_step,
+                tick_size: None,
+                ref_price: None,
+                allow_duplicate: false,
+                hard_stop_percent: None,
+                position_idx: 0,
+            };
+
+            info!(
+                "📤 Closing order: {:?} {} (reduce_only)",
+                close_side, size
+            );
+
+            let mut finished = self.finished_orders();
+            let response = match self.client.place_order(&close_order).await {
+                Ok(response) => response,
+                // 110017: reduce-only rejected because the position is already flat
+                Err(BybitError::InvalidOrder { code: 110017, msg }) => {
+                    warn!("Close rejected, position already flat? ({}) Re-syncing position...", msg);
+                    if let Some(fill) = filled.take() {
+                        self.send_close_filled(fill, reason).await;
                     }
+                    self.handle_get_position(symbol.clone()).await;
+                    return Ok(());
+                }
+                Err(e) => return Err(format!("close order rejected: {}", e)),
+            };
+            info!("✅ Close order placed: {}", response.order_id);
 
-                    // Determine closing side (opposite of position)
-                    let close_side = if pos_info.side == "Buy" {
-                        OrderSide::Sell
-                    } else {
-                        OrderSide::Buy
-                    };
-
-                    // Create closing market order
-                    let close_order = Order {
-                        symbol: symbol.clone(),
-                        side: close_side,
-                        order_type: OrderType::Market,
-                        qty: size,
-                        price: None,
-                        time_in_force: TimeInForce::IOC,
-                        reduce_only: true,
-                        qty_step: None,
-                        tick_size: None,
-                    };
+            // ✅ FIX BUG #3: Poll for close order confirmation (CLOSE_MAX_POLLS, 5 seconds by default)
+            let max_polls = self.config.close_max_polls;
 
-                    info!(
-                        "📤 Closing order: {:?} {} (reduce_only)",
-                        close_side, size
-                    );
+            for attempt in 1..=max_polls {
+                self.wait_for_order(&mut finished, &response.order_id, self.timing.interval).await;
 
-                    match self.client.place_order(&close_order).await {
-                        Ok(response) => {
-                            info!("✅ Close order placed: {}", response.order_id);
-
-                            // ✅ FIX BUG #3: Poll for close order confirmation
-                            let max_polls = 10; // 5 seconds for close orders
-                            let poll_interval = tokio::time::Duration::from_millis(500);
-
-                            for attempt in 1..=max_polls {
-                                tokio::time::sleep(poll_interval).await;
-
-                                match self.client.get_order_status(&symbol.0, &response.order_id).await {
-                                    Ok(status) => {
-                                        match status.order_status.as_str() {
-                                            "Filled" => {
-                                                info!("✅ Close order FILLED");
-                                                if let Err(e) = self
-                                                    .strategy_tx
-                                                    .send(StrategyMessage::PositionUpdate(None))
-                                                    .await
-                                                {
-                                                    error!("Failed to send PositionUpdate(None): {}", e);
-                                                }
-                                                return;
-                                            }
-                                            "Cancelled" | "Rejected" => {
-                                                error!("❌ Close order {}: {}", response.order_id, status.order_status);
-                                                // Don't send PositionUpdate - position still exists!
-                                                return;
-                                            }
-                                            _ => continue,
-                                        }
-                                    }
-                                    Err(e) => {
-                                        warn!("Close order poll {}/{} failed: {}", attempt, max_polls, e);
-                                        continue;
-                                    }
-                                }
+                match self.client.get_order_status(&symbol.0, &response.order_id).await {
+                    Ok(status) => {
+                        match status.order_status.as_str() {
+                            "Filled" => {
+                                info!("✅ Close order FILLED");
+                                self.send_close_filled(merge_fills(filled.take(), fill_from_status(symbol, &status)), reason).await;
+                                self.send_flat(symbol).await;
+                                return Ok(());
                             }
-
-                            // ✅ FIX BUG #22 (CRITICAL): NEVER assume filled!
-                            // Market orders CAN be rejected (insufficient liquidity, price protection, risk limits)
-                            // If we assume filled but position still exists → Strategy thinks closed → money bleeds!
-                            warn!("⏰ Close order {} timeout after 5s, verifying position state...", response.order_id);
-
-                            // Query final order status
-                            match self.client.get_order_status(&symbol.0, &response.order_id).await {
-                                Ok(final_status) => {
-                                    match final_status.order_status.as_str() {
-                                        "Filled" => {
-                                            info!("✅ Close order {} verified FILLED", response.order_id);
-                                            if let Err(e) = self
-                                                .strategy_tx
-                                                .send(StrategyMessage::PositionUpdate(None))
-                                                .await
-                                            {
-                                                error!("Failed to send PositionUpdate(None): {}", e);
-                                            }
-                                        }
-                                        "PartiallyFilled" => {
-                                            warn!("⚠️  Close order {} PARTIALLY filled: {}/{}",
-                                                  response.order_id, final_status.cum_exec_qty, final_status.qty);
-                                            // Query position - partial position still exists!
-                                            self.handle_get_position(symbol.clone()).await;
-                                        }
-                                        "Cancelled" | "Rejected" => {
-                                            error!("❌ Close order {} {}: POSITION STILL EXISTS!",
-                                                   response.order_id, final_status.order_status);
-                                            // DO NOT send PositionUpdate(None) - position still open!
-                                            // Query position to send correct state
-                                            self.handle_get_position(symbol.clone()).await;
-                                        }
-                                        _ => {
-                                            warn!("Unknown close order status: {}, querying position...", final_status.order_status);
-                                            self.handle_get_position(symbol.clone()).await;
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    // ✅ DEFENSIVE: Cannot verify order status → Query position directly
-                                    error!("Failed to verify close order status: {}", e);
-                                    warn!("⚠️  BUG #22 PROTECTION: Querying position to verify close...");
-                                    self.handle_get_position(symbol.clone()).await;
-                                }
+                            "Cancelled" | "Rejected" | "PartiallyFilledCanceled" => {
+                                // Don't send PositionUpdate - position still exists!
+                                keep_partial(filled, fill_from_status(symbol, &status));
+                                return Err(format!("close order {} {}", response.order_id, status.order_status));
                             }
+                            _ => continue,
                         }
-                        Err(e) => {
-                            error!("❌ Failed to close position: {}", e);
-                            // Don't send PositionUpdate - position still exists!
-                        }
                     }
+                    Err(e) => {
+                        warn!("Close order poll {}/{} failed: {}", attempt, max_polls, e);
+                        continue;
+                    }
+                }
+            }
+
+            // ✅ FIX BUG #22 (CRITICAL): NEVER assume filled!
+            // Market orders CAN be rejected (insufficient liquidity, price protection, risk limits)
+            // If we assume filled but position still exists → Strategy thinks closed → money bleeds!
+            warn!(
+                "⏰ Close order {} timeout after {:?}, verifying position state...",
+                response.order_id,
+                self.timing.interval * max_polls
+            );
+
+            // Query final order status
+            // ✅ DEFENSIVE: Anything short of a verified fill goes down the ladder, which re-reads the position
+            let final_status = self
+                .client
+                .get_order_status(&symbol.0, &response.order_id)
+                .await
+                .map_err(|e| format!("close order {} unverified: {}", response.order_id, e))?;
+            return match final_status.order_status.as_str() {
+                "Filled" => {
+                    info!("✅ Close order {} verified FILLED", response.order_id);
+                    self.send_close_filled(merge_fills(filled.take(), fill_from_status(symbol, &final_status)), reason).await;
+                    self.send_flat(symbol).await;
+                    Ok(())
+                }
+                "PartiallyFilled" => {
+                    keep_partial(filled, fill_from_status(symbol, &final_status));
+                    Err(format!(
+                        "close order {} partially filled: {}/{}",
+                        response.order_id, final_status.cum_exec_qty, final_status.qty
+                    ))
+                }
+                other => {
+                    keep_partial(filled, fill_from_status(symbol, &final_status));
+                    Err(format!("close order {} {}", response.order_id, other))
                 }
+            };
+        }
+
+        // Only zero-size entries: nothing to close
+        if let Some(fill) = filled.take() {
+            self.send_close_filled(fill, reason).await;
+        }
+        self.send_flat(symbol).await;
+        Ok(())
+    }
+
+    async fn send_flat(&self, symbol: &Symbol) {
+        // The exchange drops a position's stop with the position
+        self.hard_stops.lock().remove(symbol);
+        self.owned.closed(symbol);
+        if let Err(e) = self
+            .strategy_tx
+            .send(StrategyMessage::PositionUpdate { symbol: symbol.clone(), position: None, query: None })
+            .await
+        {
+            error!("Failed to send PositionUpdate(None): {}", e);
+        }
+    }
+
+    /// Final-status announcements of the private stream; subscribe before placing so none is missed
+    fn finished_orders(&self) -> Option<broadcast::Receiver<String>> {
+        self.private_state.as_ref().map(PrivateState::subscribe_finished)
+    }
+
+    /// ✅ STREAM-DRIVEN POLLS: Sleep `after`, but wake as soon as the private stream reports
+    /// `order_id` done, so the next status query confirms it without waiting out the cadence
+    async fn wait_for_order(&self, finished: &mut Option<broadcast::Receiver<String>>, order_id: &str, after: Duration) {
+        let Some(updates) = finished.as_mut() else {
+            tokio::time::sleep(after).await;
+            return;
+        };
+        let deadline = tokio::time::sleep(after);
+        tokio::pin!(deadline);
+        loop {
+            tokio::select! {
+                _ = &mut deadline => return,
+                update = updates.recv() => match update {
+                    Ok(id) if id == order_id => return,
+                    Ok(_) => continue,
+                    // Ours may be among the missed ones: query now
+                    Err(broadcast::error::RecvError::Lagged(_)) => return,
+                    Err(broadcast::error::RecvError::Closed) => {
+                        *finished = None;
+                        deadline.await;
+                        return;
+                    }
+                },
+            }
+        }
+    }
+
+    /// TP and trailing exits are worth a few bps of waiting; emergencies are not, and stops
+    /// only when a hard stop on the exchange covers the wait (soft stop). Adopted positions,
+    /// entries of unknown outcome and failed `set_stop_loss` calls have none
+    fn escalates(&self, symbol: &Symbol, reason: ExitReason) -> bool {
+        self.config.close_limit_window_ms > 0
+            && match reason {
+                ExitReason::TakeProfit | ExitReason::TrailingStop => true,
+                ExitReason::StopLoss => self.hard_stops.lock().contains(symbol),
+                _ => false,
+            }
+    }
+
+    /// Reduce-only limit at the touch (marketable: takes the best level without sweeping the book).
+    /// Whatever is left after CLOSE_LIMIT_WINDOW_MS is cancelled for the market order.
+    /// Returns the limit's fill and the size still open
+    async fn close_at_touch(&self, symbol: &Symbol, side: OrderSide, size: Decimal) -> (Option<Fill>, Decimal) {
+        let (best_bid, best_ask) = match self.client.get_top_of_book(&symbol.0).await {
+            Ok(top) => top,
+            Err(e) => {
+                warn!("⚠️ No top of book for {} ({}), closing at market", symbol, e);
+                return (None, size);
+            }
+        };
+        let price = match side {
+            OrderSide::Sell => best_bid,
+            OrderSide::Buy => best_ask,
+        };
+        let specs = self.specs_cache.get_or_default(&symbol.0);
+        let order = Order {
+            symbol: symbol.clone(),
+            side,
+            order_type: OrderType::Limit,
+            qty: size,
+            price: Some(price),
+            time_in_force: TimeInForce::GTC,
+            reduce_only: true,
+            qty_step: Some(specs.qty_step),
+            tick_size: Some(specs.tick_size),
+            ref_price: None,
+            allow_duplicate: false,
+            hard_stop_percent: None,
+            position_idx: 0,
+        };
+        let mut finished = self.finished_orders();
+        let order_id = match self.client.place_order(&order).await {
+            Ok(response) => response.order_id,
+            Err(e) => {
+                warn!("⚠️ Close limit rejected ({}), closing at market", e);
+                return (None, size);
+            }
+        };
+
+        let window = Duration::from_millis(self.config.close_limit_window_ms);
+        info!("📤 Close limit {}: {:?} {} @ {} (market after {:?})", order_id, side, size, price, window);
+        self.wait_for_order(&mut finished, &order_id, window).await;
+        if let Ok(status) = self.client.get_order_status(&symbol.0, &order_id).await {
+            if status.order_status == "Filled" {
+                return (Some(fill_from_status(symbol, &status)), Decimal::ZERO);
+            }
+        }
+
+        // Pull the rest; whatever filled until the cancel landed still counts
+        if let Err(e) = self.client.cancel_order(&symbol.0, &order_id).await {
+            warn!("Failed to cancel close limit {}: {}", order_id, e);
+        }
+        self.wait_for_order(&mut finished, &order_id, self.timing.cancel_settle).await;
+        match self.client.get_order_status(&symbol.0, &order_id).await {
+            Ok(status) => {
+                let filled = Decimal::from_str(&status.cum_exec_qty).unwrap_or(Decimal::ZERO);
+                let fill = (filled > Decimal::ZERO).then(|| fill_from_status(symbol, &status));
+                (fill, size - filled)
             }
             Err(e) => {
-                error!("Failed to get position for closing: {}", e);
+                // Reduce-only caps the market order at what is really left
+                warn!("Failed to read close limit {} after cancel: {}", order_id, e);
+                (None, size)
             }
         }
     }
 
+    /// Report close fill details (price, fee) so the strategy can finalize the trade record
+    async fn send_close_filled(&self, fill: Fill, reason: ExitReason) {
+        if let Err(e) = self
+            .strategy_tx
+            .send(StrategyMessage::CloseFilled { fill, reason })
+            .await
+        {
+            error!("Failed to send CloseFilled message: {}", e);
+        }
+
+        // Off the priority lane: the wallet query must not delay the position update
+        let (journal, client) = (self.journal.clone(), self.client.clone());
+        tokio::spawn(async move {
+            journal.snapshot_equity(&client, SnapshotReason::TradeClose).await;
+        });
+    }
+
     async fn handle_get_position(&self, symbol: Symbol) {
+        self.report_position(symbol, None).await;
+    }
+
+    /// Send the exchange's position; `query` = the verification request it answers
+    async fn report_position(&self, symbol: Symbol, query: Option<u64>) {
+        // Don't send PositionUpdate if the query failed - we don't know the state!
+        let Ok(position) = self.fetch_position(&symbol).await else {
+            return;
+        };
+        if let Err(e) = self
+            .strategy_tx
+            .send(StrategyMessage::PositionUpdate { symbol, position, query })
+            .await
+        {
+            error!("Failed to send PositionUpdate: {}", e);
+        }
+    }
+
+    /// The exchange's position on `symbol` (Err = it could not be queried)
+    async fn fetch_position(&self, symbol: &Symbol) -> Result<Option<Position>, String> {
         // ✅ FIX BUG #23 (HIGH): Empty array ambiguity
         // API can return empty array due to lag even if position exists!
         // This is especially dangerous after OrderFilled where we KNOW position should exist.
@@ -418,14 +1321,8 @@ impl ExecutionActor {
                         } else {
                             // Last attempt still empty - accept as no position
                             info!("✅ Position confirmed empty after {} retries", MAX_RETRIES);
-                            if let Err(e) = self
-                                .strategy_tx
-                                .send(StrategyMessage::PositionUpdate(None))
-                                .await
-                            {
-                                error!("Failed to send PositionUpdate(None): {}", e);
-                            }
-                            return;
+                            self.hard_stops.lock().remove(symbol);
+                            return Ok(None);
                         }
                     }
 
@@ -434,49 +1331,9 @@ impl ExecutionActor {
                         let size = Decimal::from_str(&pos_info.size).unwrap_or(Decimal::ZERO);
 
                         if size > Decimal::ZERO {
-                            let entry_price = Decimal::from_str(&pos_info.avg_price)
-                                .unwrap_or(Decimal::ZERO);
-                            let is_long = pos_info.side == "Buy";
-
-                            // ✅ FIX BUG #2: Calculate stop_loss based on config
-                            let sl_percent = Decimal::from_str(&self.config.stop_loss_percent.to_string())
-                                .unwrap_or(Decimal::new(5, 1)); // 0.5% default
-                            let sl_multiplier = Decimal::ONE - (sl_percent / Decimal::from(100));
-                            let sl_multiplier_short = Decimal::ONE + (sl_percent / Decimal::from(100));
-
-                            let stop_loss = if is_long {
-                                entry_price * sl_multiplier  // Long: SL below entry
-                            } else {
-                                entry_price * sl_multiplier_short  // Short: SL above entry
-                            };
-
-                            let position = Position {
-                                symbol: symbol.clone(),
-                                side: if is_long {
-                                    PositionSide::Long
-                                } else {
-                                    PositionSide::Short
-                                },
-                                size,
-                                entry_price,
-                                current_price: Decimal::from_str(&pos_info.avg_price)
-                                    .unwrap_or(Decimal::ZERO),
-                                unrealized_pnl: Decimal::from_str(&pos_info.unrealised_pnl)
-                                    .unwrap_or(Decimal::ZERO),
-                                stop_loss: Some(stop_loss),  // ✅ Now properly set!
-                            };
-
-                            debug!("📊 Position found: {:?}, SL: {}", position.side, stop_loss);
-
-                            if let Err(e) = self
-                                .strategy_tx
-                                .send(StrategyMessage::PositionUpdate(Some(position)))
-                                .await
-                            {
-                                error!("Failed to send PositionUpdate(Some): {}", e);
-                            }
-
-                            return; // Position found, exit retry loop
+                            let position = self.position_from_info(symbol, &pos_info, size);
+                            debug!("📊 Position found: {:?}, SL: {:?}", position.side, position.stop_loss);
+                            return Ok(Some(position)); // Position found, exit retry loop
                         }
                     }
 
@@ -487,14 +1344,7 @@ impl ExecutionActor {
                         continue;
                     } else {
                         warn!("All positions have size=0 after {} retries", MAX_RETRIES);
-                        if let Err(e) = self
-                            .strategy_tx
-                            .send(StrategyMessage::PositionUpdate(None))
-                            .await
-                        {
-                            error!("Failed to send PositionUpdate(None) after loop: {}", e);
-                        }
-                        return;
+                        return Ok(None);
                     }
                 }
                 Err(e) => {
@@ -505,11 +1355,479 @@ impl ExecutionActor {
                         continue;
                     } else {
                         error!("Failed to get position after {} retries: {}", MAX_RETRIES, e);
-                        // Don't send PositionUpdate - we don't know the state!
-                        return;
+                        return Err(format!("failed to get position: {}", e));
                     }
                 }
             }
         }
+        Err("failed to get position".to_string())
+    }
+
+    /// Build a Position from the exchange's view, with SL at the configured distance from entry
+    fn position_from_info(&self, symbol: &Symbol, pos_info: &PositionInfo, size: Decimal) -> Position {
+        let entry_price = Decimal::from_str(&pos_info.avg_price)
+            .unwrap_or(Decimal::ZERO);
+        let is_long = pos_info.side == "Buy";
+
+        // ✅ FIX BUG #2: Calculate stop_loss based on config
+        let sl_percent = Decimal::from_str(&self.config.stop_loss_percent.to_string())
+            .unwrap_or(Decimal::new(5, 1)); // 0.5% default
+        let sl_multiplier = Decimal::ONE - (sl_percent / Decimal::from(100));
+        let sl_multiplier_short = Decimal::ONE + (sl_percent / Decimal::from(100));
+
+        let stop_loss = if is_long {
+            entry_price * sl_multiplier  // Long: SL below entry
+        } else {
+            entry_price * sl_multiplier_short  // Short: SL above entry
+        };
+
+        Position {
+            symbol: symbol.clone(),
+            side: if is_long {
+                PositionSide::Long
+            } else {
+                PositionSide::Short
+            },
+            size,
+            entry_price,
+            current_price: entry_price,
+            unrealized_pnl: Decimal::from_str(&pos_info.unrealised_pnl)
+                .unwrap_or(Decimal::ZERO),
+            stop_loss: Some(stop_loss),  // ✅ Now properly set!
+            leverage: Decimal::from_str(&pos_info.leverage)
+                .ok()
+                .filter(|l| *l >= Decimal::ONE)
+                .unwrap_or(Decimal::ONE),
+            liq_price: Decimal::from_str(&pos_info.liq_price).ok(),
+            realized_pnl: Decimal::from_str(&pos_info.cur_realised_pnl).unwrap_or(Decimal::ZERO),
+            cum_realized_pnl: Decimal::from_str(&pos_info.cum_realised_pnl).unwrap_or(Decimal::ZERO),
+        }
+    }
+
+    /// ✅ STUCK STATE: one-shot view of the exchange after the strategy waited too long.
+    /// Stray orders are cancelled first so nothing fills after the answer is sent.
+    async fn handle_reconcile(&self, symbol: Symbol) {
+        warn!("🔄 Reconciling {} with the exchange", symbol);
+
+        if let Err(e) = self.client.cancel_all_orders(&symbol.0).await {
+            warn!("Reconcile: failed to cancel open orders for {}: {}", symbol, e);
+        }
+        // Whatever it adopts, stop losses go straight to market until an entry arms a new hard stop
+        self.hard_stops.lock().remove(&symbol);
+
+        let positions = match self.client.get_position(&symbol.0).await {
+            Ok(positions) => positions,
+            Err(e) => {
+                // The state machine re-arms its stuck check, so the strategy asks again after a backoff
+                error!("Reconcile: failed to get position for {}: {}", symbol, e);
+                return;
+            }
+        };
+
+        let position = positions.iter().find_map(|pos_info| {
+            let size = Decimal::from_str(&pos_info.size).unwrap_or(Decimal::ZERO);
+            (size > Decimal::ZERO).then(|| self.position_from_info(&symbol, pos_info, size))
+        });
+
+        if let Err(e) = self
+            .strategy_tx
+            .send(StrategyMessage::Reconciled { symbol, position })
+            .await
+        {
+            error!("Failed to send Reconciled: {}", e);
+        }
+    }
+}
+
+/// Rungs of the close ladder, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CloseStep {
+    First,
+    AdjustedSize,
+    AfterCancelOwn,
+}
+
+/// Close attempts before the kill switch (each polls up to CLOSE_MAX_POLLS)
+pub const CLOSE_LADDER_RUNGS: usize = CloseStep::LADDER.len();
+
+impl CloseStep {
+    const LADDER: [CloseStep; 3] = [CloseStep::First, CloseStep::AdjustedSize, CloseStep::AfterCancelOwn];
+}
+
+impl std::fmt::Display for CloseStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CloseStep::First => write!(f, "close"),
+            CloseStep::AdjustedSize => write!(f, "retry at current size"),
+            CloseStep::AfterCancelOwn => write!(f, "retry after cancelling our orders"),
+        }
+    }
+}
+
+/// How far `price` is from `from`, bps
+fn reprice_drift_bps(from: Decimal, price: Decimal) -> f64 {
+    if from <= Decimal::ZERO {
+        return f64::INFINITY;
+    }
+    ((price - from) / from).abs().to_f64().unwrap_or(f64::INFINITY) * 10_000.0
+}
+
+/// One close fill out of the touch limit's part and the market order's
+fn merge_fills(first: Option<Fill>, second: Fill) -> Fill {
+    let Some(first) = first else {
+        return second;
+    };
+    let qty = first.qty + second.qty;
+    if qty.is_zero() {
+        return second;
+    }
+    Fill {
+        avg_price: (first.avg_price * first.qty + second.avg_price * second.qty) / qty,
+        qty,
+        fee: first.fee + second.fee,
+        ..second
+    }
+}
+
+/// Keep what a failed close filled for the report of the close that finishes the position
+fn keep_partial(filled: &mut Option<Fill>, fill: Fill) {
+    if fill.qty > Decimal::ZERO {
+        *filled = Some(merge_fills(filled.take(), fill));
+    }
+}
+
+/// Build a fill report from an order status query
+fn fill_from_status(symbol: &Symbol, status: &OrderStatusResponse) -> Fill {
+    Fill {
+        symbol: symbol.clone(),
+        order_id: status.order_id.clone(),
+        side: if status.side == "Buy" { OrderSide::Buy } else { OrderSide::Sell },
+        qty: Decimal::from_str(&status.cum_exec_qty).unwrap_or(Decimal::ZERO),
+        avg_price: Decimal::from_str(&status.avg_price).unwrap_or(Decimal::ZERO),
+        fee: Decimal::from_str(&status.cum_exec_fee).unwrap_or(Decimal::ZERO),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::{metered_channel, OverflowPolicy};
+    use crate::health::HealthRegistry;
+    use crate::metrics::Metrics;
+    use serde_json::{json, Value};
+    use wiremock::matchers::{path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn ok(result: Value) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({ "retCode": 0, "retMsg": "OK", "result": result }))
+    }
+
+    fn position(size: &str) -> ResponseTemplate {
+        ok(json!({ "list": [
+            { "symbol": "BTCUSDT", "side": "Buy", "size": size, "avgPrice": "100", "unrealisedPnl": "0" }
+        ] }))
+    }
+
+    fn order(order_id: &str, link_id: &str, status: &str, cum_exec_qty: &str, avg_price: &str) -> Value {
+        json!({
+            "orderId": order_id, "orderLinkId": link_id, "symbol": "BTCUSDT", "orderStatus": status,
+            "orderType": "Market", "side": "Sell", "price": "0", "qty": "1", "cumExecQty": cum_exec_qty,
+            "cumExecValue": "0", "cumExecFee": "0.01", "avgPrice": avg_price, "reduceOnly": true
+        })
+    }
+
+    async fn mount_status(server: &MockServer, order_id: &str, status: &str, cum_exec_qty: &str, avg_price: &str) {
+        Mock::given(path("/v5/order/realtime"))
+            .and(query_param("orderId", order_id))
+            .respond_with(ok(json!({ "list": [order(order_id, "", status, cum_exec_qty, avg_price)] })))
+            .mount(server)
+            .await;
+    }
+
+    /// Both lanes' core against `server`, polling every 10ms; the strategy's inbox comes back with it
+    fn core(
+        server: &MockServer,
+        client: BybitClient,
+        metrics: &Metrics,
+    ) -> (Arc<ExecutionCore>, MeteredReceiver<StrategyMessage>, MeteredReceiver<NotifierMessage>) {
+        core_with(server, client, metrics, |_| {})
+    }
+
+    /// `core` with settings of its own on top
+    fn core_with(
+        server: &MockServer,
+        client: BybitClient,
+        metrics: &Metrics,
+        configure: impl FnOnce(&mut Config),
+    ) -> (Arc<ExecutionCore>, MeteredReceiver<StrategyMessage>, MeteredReceiver<NotifierMessage>) {
+        std::env::set_var("BYBIT_API_KEY", "test");
+        std::env::set_var("BYBIT_API_SECRET", "test");
+        let mut config = Config::from_env().unwrap();
+        config.order_poll_interval_ms = 10;
+        config.close_max_polls = 2;
+        config.close_limit_window_ms = 0;
+        configure(&mut config);
+        let policy = OverflowPolicy::Block { timeout: None };
+        let (strategy_tx, strategy_rx) = metered_channel("strategy", 100, policy, metrics);
+        let (notifier_tx, notifier_rx) = metered_channel("notifier", 100, policy, metrics);
+        let (_, message_rx) = metered_channel("execution", 1, policy, metrics);
+        let (_, priority_rx) = metered_channel("execution_priority", 1, policy, metrics);
+        let health = HealthRegistry::new();
+        let journal = Journal::new(std::env::temp_dir().join(format!("execution-test-{}.jsonl", server.address().port())));
+        let actor = ExecutionActor::new(
+            client,
+            Arc::new(config),
+            message_rx,
+            priority_rx,
+            strategy_tx,
+            notifier_tx,
+            SpecsCache::new(),
+            journal,
+            QueueBoard::new(),
+            None,
+            OwnedPositions::default(),
+            health.register("execution", Duration::from_secs(1)),
+            health.register("execution_priority", Duration::from_secs(1)),
+        );
+        (actor.core, strategy_rx, notifier_rx)
+    }
+
+    fn client(server: &MockServer) -> BybitClient {
+        BybitClient::new("test".to_string(), "test".to_string(), server.uri()).with_retry_base_delay(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn test_get_position_with_reply_answers_the_caller() {
+        let server = MockServer::start().await;
+        Mock::given(path("/v5/position/list")).respond_with(position("2")).mount(&server).await;
+
+        let metrics = Metrics::new();
+        let (core, mut strategy_rx, _notifier_rx) = core(&server, client(&server), &metrics);
+        let (reply, answer) = oneshot::channel();
+        assert!(core.handle(ExecutionMessage::GetPositionWithReply { symbol: Symbol::from("BTCUSDT"), reply }).await);
+
+        let Ok(ExecutionResponse::CurrentPosition(Some(position))) = answer.await else {
+            panic!("expected the position on the reply channel");
+        };
+        assert_eq!(position.size, Decimal::from(2));
+        // The answer goes to the caller, not to the strategy
+        assert!(strategy_rx.try_recv().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_position_with_reply_reports_a_failed_query() {
+        let server = MockServer::start().await;
+        Mock::given(path("/v5/position/list"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "retCode": 10001, "retMsg": "params error" })))
+            .mount(&server)
+            .await;
+
+        let metrics = Metrics::new();
+        let (core, _strategy_rx, _notifier_rx) = core(&server, client(&server), &metrics);
+        let (reply, answer) = oneshot::channel();
+        core.handle(ExecutionMessage::GetPositionWithReply { symbol: Symbol::from("BTCUSDT"), reply }).await;
+
+        assert!(matches!(answer.await, Ok(ExecutionResponse::Error(_))));
+    }
+
+    fn pair_leg(symbol: &str, side: OrderSide, qty: i64, price: i64) -> Order {
+        Order {
+            symbol: Symbol::from(symbol),
+            side,
+            order_type: OrderType::Market,
+            qty: Decimal::from(qty),
+            price: None,
+            time_in_force: TimeInForce::IOC,
+            reduce_only: false,
+            qty_step: None,
+            tick_size: None,
+            ref_price: Some(Decimal::from(price)),
+            allow_duplicate: false,
+            hard_stop_percent: None,
+            position_idx: 0,
+        }
+    }
+
+    async fn mount_wallet(server: &MockServer, available: &str) {
+        Mock::given(path("/v5/account/wallet-balance"))
+            .respond_with(ok(json!({ "list": [{
+                "accountType": "UNIFIED", "totalEquity": available, "totalWalletBalance": available,
+                "totalAvailableBalance": available, "totalInitialMargin": "0", "totalMaintenanceMargin": "0",
+                "accountIMRate": "0", "accountMMRate": "0"
+            }] })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_pair_legs_are_sized_down_together() {
+        let server = MockServer::start().await;
+        mount_wallet(&server, "100").await;
+        Mock::given(path("/v5/position/list")).respond_with(position("0")).mount(&server).await;
+
+        let metrics = Metrics::new();
+        let (core, _strategy_rx, _notifier_rx) = core(&server, client(&server), &metrics);
+        // 200 + 200 USDT at 1x against 95 usable: both legs shrink to ~47.5 USDT
+        let (long, short) = core
+            .fit_pair_to_margin(pair_leg("BTCUSDT", OrderSide::Buy, 2, 100), pair_leg("ETHUSDT", OrderSide::Sell, 4, 50))
+            .await
+            .unwrap();
+        assert_eq!(long.qty, "0.47".parse::<Decimal>().unwrap());
+        assert_eq!(short.qty, "0.95".parse::<Decimal>().unwrap());
+
+        // Not even the minimum of both fits: neither leg goes out
+        let refused = core
+            .fit_pair_to_margin(pair_leg("BTCUSDT", OrderSide::Buy, 2, 100_000), pair_leg("ETHUSDT", OrderSide::Sell, 4, 50))
+            .await;
+        assert!(refused.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_blocked_pair_gives_its_throttle_slots_back() {
+        let server = MockServer::start().await;
+        let metrics = Metrics::new();
+        let (core, _strategy_rx, _notifier_rx) = core_with(&server, client(&server), &metrics, |config| {
+            config.max_orders_per_minute = 1;
+        });
+
+        let legs = PairOrder {
+            long: pair_leg("BTCUSDT", OrderSide::Buy, 1, 100),
+            short: pair_leg("ETHUSDT", OrderSide::Sell, 1, 50),
+        };
+        assert!(matches!(core.handle_place_pair(legs).await, PairReport::Failed(_)));
+        // The first leg's slot was returned: a single entry still fits the minute
+        assert!(core.throttle.lock().admit(Instant::now()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_close_ladder_reports_what_every_rung_filled() {
+        let server = MockServer::start().await;
+        Mock::given(path("/v5/position/list")).respond_with(position("2")).up_to_n_times(1).mount(&server).await;
+        Mock::given(path("/v5/position/list")).respond_with(position("1")).mount(&server).await;
+        Mock::given(path("/v5/order/create"))
+            .respond_with(ok(json!({ "orderId": "c1", "orderLinkId": "" })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(path("/v5/order/create"))
+            .respond_with(ok(json!({ "orderId": "c2", "orderLinkId": "" })))
+            .mount(&server)
+            .await;
+        // The first close is cut short after half the position
+        mount_status(&server, "c1", "Cancelled", "1", "100").await;
+        mount_status(&server, "c2", "Filled", "1", "102").await;
+
+        let metrics = Metrics::new();
+        let (core, mut strategy_rx, _notifier_rx) = core(&server, client(&server), &metrics);
+        core.handle_close_position(Symbol::from("BTCUSDT"), PositionSide::Long, ExitReason::StopLoss).await;
+
+        let Some(StrategyMessage::CloseFilled { fill, .. }) = strategy_rx.try_recv() else {
+            panic!("expected a close fill");
+        };
+        assert_eq!(fill.qty, Decimal::from(2));
+        assert_eq!(fill.avg_price, Decimal::from(101));
+        assert_eq!(fill.fee, Decimal::from_str("0.02").unwrap());
+        assert!(matches!(strategy_rx.try_recv(), Some(StrategyMessage::PositionUpdate { position: None, .. })));
+    }
+
+    #[tokio::test]
+    async fn test_failed_close_reports_what_the_touch_limit_filled() {
+        let server = MockServer::start().await;
+        Mock::given(path("/v5/position/list")).respond_with(position("2")).mount(&server).await;
+        Mock::given(path("/v5/market/orderbook"))
+            .respond_with(ok(json!({ "s": "BTCUSDT", "b": [["100", "5"]], "a": [["100.1", "5"]], "ts": 1, "u": 1 })))
+            .mount(&server)
+            .await;
+        Mock::given(path("/v5/order/create"))
+            .respond_with(ok(json!({ "orderId": "limit", "orderLinkId": "" })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        // Every market leg is refused
+        Mock::given(path("/v5/order/create"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "retCode": 110001, "retMsg": "refused" })))
+            .mount(&server)
+            .await;
+        Mock::given(path("/v5/order/cancel")).respond_with(ok(json!({}))).mount(&server).await;
+        // Half the position went at the touch before the limit was pulled
+        mount_status(&server, "limit", "Cancelled", "1", "100").await;
+
+        let metrics = Metrics::new();
+        let (core, mut strategy_rx, _notifier_rx) =
+            core_with(&server, client(&server), &metrics, |config| config.close_limit_window_ms = 10);
+        core.handle_close_position(Symbol::from("BTCUSDT"), PositionSide::Long, ExitReason::TakeProfit).await;
+
+        let Some(StrategyMessage::CloseFilled { fill, reason: ExitReason::TakeProfit }) = strategy_rx.try_recv() else {
+            panic!("expected the limit's fill");
+        };
+        assert_eq!((fill.qty, fill.avg_price), (Decimal::from(1), Decimal::from(100)));
+        assert!(matches!(strategy_rx.try_recv(), Some(StrategyMessage::Control(ControlCommand::KillSwitch))));
+        assert!(matches!(
+            strategy_rx.try_recv(),
+            Some(StrategyMessage::PositionUpdate { position: Some(p), .. }) if p.size == Decimal::from(2)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_close_ladder_cancels_only_our_orders() {
+        let server = MockServer::start().await;
+        Mock::given(path("/v5/position/list")).respond_with(position("1")).mount(&server).await;
+        Mock::given(path("/v5/order/create"))
+            .respond_with(ok(json!({ "orderId": "close", "orderLinkId": "bot-c1-0" })))
+            .mount(&server)
+            .await;
+        mount_status(&server, "close", "Rejected", "0", "0").await;
+        Mock::given(path("/v5/order/realtime"))
+            .and(query_param("orderFilter", "Order"))
+            .respond_with(ok(json!({ "list": [
+                order("ours", "bot-c1-1", "New", "0", "0"),
+                order("manual", "", "New", "0", "0")
+            ] })))
+            .mount(&server)
+            .await;
+        Mock::given(path("/v5/order/cancel")).respond_with(ok(json!({}))).mount(&server).await;
+
+        let metrics = Metrics::new();
+        let (core, mut strategy_rx, _notifier_rx) = core(&server, client(&server).with_order_links("bot-"), &metrics);
+        core.handle_close_position(Symbol::from("BTCUSDT"), PositionSide::Long, ExitReason::StopLoss).await;
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests.iter().all(|r| r.url.path() != "/v5/order/cancel-all"));
+        let cancelled: Vec<Value> = requests
+            .iter()
+            .filter(|r| r.url.path() == "/v5/order/cancel")
+            .map(|r| serde_json::from_slice(&r.body).unwrap())
+            .collect();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0]["orderId"], "ours");
+        assert!(matches!(
+            strategy_rx.try_recv(),
+            Some(StrategyMessage::Control(ControlCommand::KillSwitch))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_reaches_both_lanes() {
+        let metrics = Metrics::new();
+        let policy = OverflowPolicy::Block { timeout: None };
+        let (normal_tx, mut normal_rx) = metered_channel("execution", 10, policy, &metrics);
+        let (priority_tx, mut priority_rx) = metered_channel("execution_priority", 10, policy, &metrics);
+        let execution = ExecutionSender::new(normal_tx, priority_tx);
+
+        execution.send(ExecutionMessage::GetPosition { symbol: Symbol::from("BTCUSDT"), query: 1 }).await.unwrap();
+        execution.send(ExecutionMessage::Shutdown).await.unwrap();
+
+        assert!(matches!(priority_rx.recv().await.map(|t| t.msg), Some(ExecutionMessage::GetPosition { .. })));
+        assert!(matches!(priority_rx.recv().await.map(|t| t.msg), Some(ExecutionMessage::Shutdown)));
+        assert!(matches!(normal_rx.recv().await.map(|t| t.msg), Some(ExecutionMessage::Shutdown)));
+        assert!(normal_rx.try_recv().is_none());
+    }
+
+    #[test]
+    fn test_reprice_drift_bps() {
+        let from = Decimal::from(100);
+        assert!((reprice_drift_bps(from, Decimal::from_str("100.10").unwrap()) - 10.0).abs() < 1e-9);
+        assert!((reprice_drift_bps(from, Decimal::from_str("99.95").unwrap()) - 5.0).abs() < 1e-9);
+        assert_eq!(reprice_drift_bps(Decimal::ZERO, from), f64::INFINITY);
     }
 }
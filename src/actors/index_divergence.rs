@@ -0,0 +1,122 @@
+//! Mark/Index Divergence
+//!
+//! A perp trades around its index (the spot average). When the last or mark
+//! price stretches far from the index the premium tends to snap back, so a
+//! momentum entry in the direction of the stretch buys the top of it:
+//! with `MAX_INDEX_DIVERGENCE_BPS` set, a long is refused while the last
+//! price sits that far above the index, a short while it sits that far below.
+//! Both divergences also go into the signal dataset as mean-reversion cues.
+//! Bybit's `tickers` stream sends a snapshot, then only the prices that
+//! changed, so the latest of each is kept here.
+
+use crate::models::TickerUpdate;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use tokio::time::{Duration, Instant};
+
+/// Index older than this is no reference (ticker stream stalled)
+const STALE_AFTER: Duration = Duration::from_secs(10);
+
+/// Latest ticker prices of the traded symbol
+#[derive(Debug, Default)]
+pub struct IndexDivergence {
+    last_price: Option<Decimal>,
+    mark_price: Option<Decimal>,
+    index_price: Option<Decimal>,
+    updated_at: Option<Instant>,
+}
+
+/// Divergences from the index, bps (positive = above the index)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Divergence {
+    pub mark_index_bps: Option<f64>,
+    pub last_index_bps: Option<f64>,
+}
+
+impl IndexDivergence {
+    pub fn apply(&mut self, ticker: &TickerUpdate, now: Instant) {
+        self.last_price = ticker.last_price.or(self.last_price);
+        self.mark_price = ticker.mark_price.or(self.mark_price);
+        self.index_price = ticker.index_price.or(self.index_price);
+        self.updated_at = Some(now);
+    }
+
+    /// Forget the previous symbol's prices
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// None without a fresh index
+    pub fn divergence(&self, now: Instant) -> Option<Divergence> {
+        let index = self.index_price.filter(|index| !index.is_zero())?;
+        if self.updated_at.is_none_or(|at| now.duration_since(at) > STALE_AFTER) {
+            return None;
+        }
+        let bps = |price: Option<Decimal>| {
+            price.and_then(|price| ((price - index) / index * Decimal::from(10_000)).to_f64())
+        };
+        Some(Divergence {
+            mark_index_bps: bps(self.mark_price),
+            last_index_bps: bps(self.last_price),
+        })
+    }
+
+    /// The last price is stretched past `max_bps` in the signal's direction (0 = never)
+    pub fn blocks_entry(&self, bullish: bool, max_bps: f64, now: Instant) -> Option<f64> {
+        if max_bps <= 0.0 {
+            return None;
+        }
+        let stretch = self.divergence(now)?.last_index_bps?;
+        let with_signal = if bullish { stretch } else { -stretch };
+        (with_signal > max_bps).then_some(stretch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+
+    fn ticker(last: Option<i64>, mark: Option<i64>, index: Option<i64>) -> TickerUpdate {
+        TickerUpdate {
+            symbol: Symbol::from("BTCUSDT"),
+            last_price: last.map(Decimal::from),
+            mark_price: mark.map(Decimal::from),
+            index_price: index.map(Decimal::from),
+        }
+    }
+
+    #[test]
+    fn test_deltas_merge_into_divergences() {
+        let now = Instant::now();
+        let mut prices = IndexDivergence::default();
+        prices.apply(&ticker(Some(10_050), None, None), now);
+        // No index yet
+        assert!(prices.divergence(now).is_none());
+
+        prices.apply(&ticker(None, Some(10_020), Some(10_000)), now);
+        let divergence = prices.divergence(now).unwrap();
+        assert_eq!(divergence.last_index_bps, Some(50.0));
+        assert_eq!(divergence.mark_index_bps, Some(20.0));
+
+        assert!(prices.divergence(now + Duration::from_secs(11)).is_none());
+        prices.reset();
+        assert!(prices.divergence(now).is_none());
+    }
+
+    #[test]
+    fn test_stretch_blocks_entries_in_its_direction() {
+        let now = Instant::now();
+        let mut prices = IndexDivergence::default();
+        prices.apply(&ticker(Some(10_050), Some(10_040), Some(10_000)), now);
+
+        assert_eq!(prices.blocks_entry(true, 30.0, now), Some(50.0));
+        // Shorting the stretch is the mean-reversion side
+        assert_eq!(prices.blocks_entry(false, 30.0, now), None);
+        assert_eq!(prices.blocks_entry(true, 60.0, now), None);
+        assert_eq!(prices.blocks_entry(true, 0.0, now), None);
+
+        prices.apply(&ticker(Some(9_940), None, None), now);
+        assert_eq!(prices.blocks_entry(false, 30.0, now), Some(-60.0));
+    }
+}
@@ -1,5 +1,12 @@
 use crate::models::*;
 use crate::exchange::SymbolSpecs;
+use crate::notifications::{Alert, AlertLevel};
+use crate::webhook::ExternalSignal;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use tracing::Span;
 
 /// Messages between actors
 
@@ -7,36 +14,61 @@ use crate::exchange::SymbolSpecs;
 pub enum ScannerMessage {
     /// New top coin detected
     NewCoinDetected { symbol: Symbol, score: f64 },
+    /// Operator override: trade this symbol instead of the scan result (None = back to scanning)
+    PinSymbol(Option<Symbol>),
+    /// A position adopted at startup: pinned like `PinSymbol`, and unpinned once it is closed
+    AdoptSymbol(Symbol),
+    /// The operator black/whitelist changed: rescan now, leaving a current symbol no longer allowed
+    SymbolListsChanged,
 }
 
 #[derive(Debug, Clone)]
 pub enum MarketDataMessage {
-    /// Switch to new symbol
+    /// Switch to new symbol (the one the strategy trades)
     SwitchSymbol(Symbol),
+    /// Stream an additional symbol alongside the traded one (watchlist, warm-up)
+    Subscribe(Symbol),
+    /// Stop streaming a symbol added with `Subscribe`
+    Unsubscribe(Symbol),
     /// Shutdown command
     Shutdown,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub enum StrategyMessage {
     /// New orderbook snapshot
     OrderBook(OrderBookSnapshot),
     /// New trade tick
     Trade(TradeTick),
-    /// Position update from execution
-    PositionUpdate(Option<Position>),
+    /// Last/mark/index prices (only the ones that changed)
+    Ticker(TickerUpdate),
+    /// Recent trades fetched via REST after a symbol switch (oldest first)
+    WarmupTicks { symbol: Symbol, ticks: Vec<TradeTick> },
+    /// Recent 1m klines fetched via REST after a symbol switch (oldest first)
+    WarmupCandles { symbol: Symbol, candles: Vec<Candle> },
+    /// Position update from execution (None = `symbol` is flat); `query` = the verification
+    /// request it answers (None = execution's own report after an order or close)
+    PositionUpdate { symbol: Symbol, position: Option<Position>, query: Option<u64> },
     /// Symbol switched with new specs and 24h price change
     SymbolChanged {
         symbol: Symbol,
         specs: SymbolSpecs,
         price_change_24h: f64, // Daily price change percentage (e.g., 0.25 = +25%)
     },
+    /// Instrument specs refetched (TTL expiry or precision rejection) without a symbol switch
+    SpecsRefreshed(SymbolSpecs),
 
     // ✅ CRITICAL: Feedback from execution to prevent order spam
-    /// Order successfully placed and filled
-    OrderFilled(Symbol),
-    /// Order placement failed
-    OrderFailed(String),
+    /// Entry order successfully placed and filled
+    OrderFilled(Fill),
+    /// Close order filled (sent before the flat PositionUpdate that follows)
+    CloseFilled { fill: Fill, reason: ExitReason },
+    /// Order placement failed; `retryable` = transient exchange/network error (feeds the circuit breaker)
+    OrderFailed { symbol: Symbol, reason: String, retryable: bool },
+    /// The position monitor fired an exit at `price` and sent the close to execution
+    MonitorExit { symbol: Symbol, reason: ExitReason, price: Decimal },
+    /// Answer to `ExecutionMessage::Reconcile`: the exchange's position once nothing is working on `symbol`
+    Reconciled { symbol: Symbol, position: Option<Position> },
 
     // ✅ HARMONY: Live update of market stats (e.g. 24h change) without resetting state
     /// Updates market statistics for the current symbol
@@ -44,28 +76,303 @@ pub enum StrategyMessage {
         symbol: Symbol,
         price_change_24h: f64,
     },
+
+    /// Operator command (Telegram etc.)
+    Control(ControlCommand),
+
+    /// Entry signal from the webhook (TradingView etc.), still subject to every risk check
+    ExternalSignal(ExternalSignal),
+
+    /// Snapshot of the strategy for the control API
+    Status(oneshot::Sender<StrategyStatus>),
+    /// Live parameter change from the control API
+    PatchParams(ParamPatch),
 }
 
-#[derive(Debug, Clone)]
+/// What the strategy is doing right now
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyStatus {
+    pub symbol: Option<Symbol>,
+    pub state: String,
+    pub position: Option<Position>,
+    /// Best PnL of the open position so far, %
+    pub peak_pnl_percent: f64,
+    pub orderbook: Option<OrderBookSnapshot>,
+    pub last_price: Option<Decimal>,
+    /// Ticks in the momentum buffer
+    pub ticks_buffered: usize,
+    /// Last price vs. short VWAP, %
+    pub momentum_percent: Option<f64>,
+    /// 1m ATR, % of price
+    pub atr_percent: Option<f64>,
+    pub regime: Option<String>,
+    pub uptime_secs: u64,
+    pub last_tick_age_secs: Option<u64>,
+    pub paused: bool,
+    pub kill_switch: bool,
+    pub circuit_breaker: bool,
+    pub loss_streak_halted: bool,
+    pub loss_streak_paused_until: Option<DateTime<Utc>>,
+    pub consecutive_losses: u32,
+    pub trades_today: u32,
+    pub params: StrategyParams,
+}
+
+impl StrategyStatus {
+    /// Why no new position may be opened right now: kill switch (also the drawdown
+    /// breaker's), pauses, the loss streak or the daily trade limit. None = clear
+    pub fn entry_block(&self) -> Option<String> {
+        if self.kill_switch {
+            return Some("kill switch engaged".to_string());
+        }
+        if self.paused {
+            return Some("paused by operator".to_string());
+        }
+        if self.circuit_breaker {
+            return Some("circuit breaker pause".to_string());
+        }
+        if self.loss_streak_halted {
+            return Some(format!("halted after {} losses", self.consecutive_losses));
+        }
+        if let Some(until) = self.loss_streak_paused_until {
+            return Some(format!("loss streak pause until {}", until.format("%H:%M UTC")));
+        }
+        let max = self.params.max_trades_per_day;
+        if max > 0 && self.trades_today >= max {
+            return Some(format!("daily trade limit ({}) reached", max));
+        }
+        None
+    }
+}
+
+/// Parameters that can be changed at runtime (until restart)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StrategyParams {
+    /// Entry momentum threshold, %
+    pub momentum_threshold: f64,
+    pub trade_cooldown_secs: u64,
+    /// 0 = unlimited
+    pub max_trades_per_day: u32,
+}
+
+/// Fields to change; absent ones stay as they are
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamPatch {
+    pub momentum_threshold: Option<f64>,
+    pub trade_cooldown_secs: Option<u64>,
+    pub max_trades_per_day: Option<u32>,
+}
+
+impl ParamPatch {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self.momentum_threshold {
+            Some(t) if !(t.is_finite() && t > 0.0) => Err(format!("momentum_threshold must be > 0, got {}", t)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Operator commands that change what the strategy is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlCommand {
+    /// Market-close the open position
+    ClosePosition,
+    /// Close the open position and stop opening new ones until restart
+    KillSwitch,
+    /// Stop opening new positions (the open one keeps its exits) until resumed
+    Pause,
+    /// Lift a manual pause and a loss-streak halt
+    Resume,
+}
+
+impl std::fmt::Display for ControlCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlCommand::ClosePosition => write!(f, "CLOSE POSITION"),
+            ControlCommand::KillSwitch => write!(f, "KILL SWITCH"),
+            ControlCommand::Pause => write!(f, "PAUSE"),
+            ControlCommand::Resume => write!(f, "RESUME"),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub enum ExecutionMessage {
     /// Place a new order
     PlaceOrder(Order),
-    /// Close position immediately (market order)
-    ClosePosition { symbol: Symbol, position_side: PositionSide },
-    /// Request current position
-    GetPosition(Symbol),
+    /// Close position: market order (TP/trailing first try a limit at the touch, CLOSE_LIMIT_WINDOW_MS)
+    ClosePosition {
+        symbol: Symbol,
+        position_side: PositionSide,
+        reason: ExitReason,
+    },
+    /// Request current position; the `PositionUpdate` carries `query` back
+    GetPosition { symbol: Symbol, query: u64 },
+    /// `PlaceOrder`, and answer on `reply` once the entry has settled (the strategy hears about it as usual)
+    PlaceOrderWithReply { order: Order, reply: oneshot::Sender<ExecutionResponse> },
+    /// Look up the position and answer on `reply` instead of a `PositionUpdate`
+    GetPositionWithReply { symbol: Symbol, reply: oneshot::Sender<ExecutionResponse> },
+    /// Cancel every working order on the symbol, then report the position (`StrategyMessage::Reconciled`)
+    Reconcile(Symbol),
+    /// Enter both legs of a pair together; a leg that fails unwinds the other
+    PlacePair { legs: PairOrder, reply: oneshot::Sender<PairReport> },
+    /// Exit both legs of a pair together
+    ClosePair { long: Symbol, short: Symbol, reply: oneshot::Sender<PairReport> },
     /// Shutdown
     Shutdown,
 }
 
+/// The two entries of a pair trade (market orders)
+#[derive(Debug, Clone)]
+pub struct PairOrder {
+    pub long: Order,
+    pub short: Order,
+}
+
+/// How a pair entry or exit ended
+#[derive(Debug)]
+pub enum PairReport {
+    /// Both legs filled
+    Opened { long: Fill, short: Fill },
+    /// Both legs flat (None = the leg held nothing)
+    Closed { long: Option<Fill>, short: Option<Fill> },
+    /// Nothing held: the entry failed and whatever filled was unwound
+    Failed(String),
+    /// A leg could not be closed: kill switch engaged, check the exchange
+    Stranded(String),
+}
+
+impl StrategyMessage {
+    /// Orderbooks conflate per symbol: only the newest queued one is processed
+    pub fn conflation_key(&self) -> Option<&Symbol> {
+        match self {
+            StrategyMessage::OrderBook(snapshot) => Some(&snapshot.symbol),
+            _ => None,
+        }
+    }
+
+    /// Symbol the message is about (None = meant for every strategy shard)
+    pub fn symbol(&self) -> Option<&str> {
+        match self {
+            StrategyMessage::OrderBook(snapshot) => Some(snapshot.symbol.as_str()),
+            StrategyMessage::Trade(tick) => Some(tick.symbol.as_str()),
+            StrategyMessage::Ticker(ticker) => Some(ticker.symbol.as_str()),
+            StrategyMessage::WarmupTicks { symbol, .. }
+            | StrategyMessage::WarmupCandles { symbol, .. }
+            | StrategyMessage::PositionUpdate { symbol, .. }
+            | StrategyMessage::SymbolChanged { symbol, .. }
+            | StrategyMessage::OrderFailed { symbol, .. }
+            | StrategyMessage::MonitorExit { symbol, .. }
+            | StrategyMessage::Reconciled { symbol, .. }
+            | StrategyMessage::UpdateMarketStats { symbol, .. } => Some(symbol.as_str()),
+            StrategyMessage::SpecsRefreshed(specs) => Some(specs.symbol.as_str()),
+            StrategyMessage::OrderFilled(fill) | StrategyMessage::CloseFilled { fill, .. } => Some(fill.symbol.as_str()),
+            StrategyMessage::ExternalSignal(signal) => Some(signal.symbol.as_str()),
+            StrategyMessage::Control(_) | StrategyMessage::Status(_) | StrategyMessage::PatchParams(_) => None,
+        }
+    }
+
+    /// Copy for a shadow strategy: market inputs only (its orders and fills are its own,
+    /// operator commands and parameter patches are meant for the live engine)
+    pub fn shadow_copy(&self) -> Option<StrategyMessage> {
+        Some(match self {
+            StrategyMessage::OrderBook(snapshot) => StrategyMessage::OrderBook(snapshot.clone()),
+            StrategyMessage::Trade(tick) => StrategyMessage::Trade(tick.clone()),
+            StrategyMessage::Ticker(ticker) => StrategyMessage::Ticker(ticker.clone()),
+            StrategyMessage::WarmupTicks { symbol, ticks } => StrategyMessage::WarmupTicks {
+                symbol: symbol.clone(),
+                ticks: ticks.clone(),
+            },
+            StrategyMessage::WarmupCandles { symbol, candles } => StrategyMessage::WarmupCandles {
+                symbol: symbol.clone(),
+                candles: candles.clone(),
+            },
+            StrategyMessage::SymbolChanged { symbol, specs, price_change_24h } => StrategyMessage::SymbolChanged {
+                symbol: symbol.clone(),
+                specs: specs.clone(),
+                price_change_24h: *price_change_24h,
+            },
+            StrategyMessage::SpecsRefreshed(specs) => StrategyMessage::SpecsRefreshed(specs.clone()),
+            StrategyMessage::UpdateMarketStats { symbol, price_change_24h } => StrategyMessage::UpdateMarketStats {
+                symbol: symbol.clone(),
+                price_change_24h: *price_change_24h,
+            },
+            StrategyMessage::ExternalSignal(signal) => StrategyMessage::ExternalSignal(signal.clone()),
+            StrategyMessage::PositionUpdate { .. }
+            | StrategyMessage::OrderFilled(_)
+            | StrategyMessage::CloseFilled { .. }
+            | StrategyMessage::OrderFailed { .. }
+            | StrategyMessage::MonitorExit { .. }
+            | StrategyMessage::Reconciled { .. }
+            | StrategyMessage::Control(_)
+            | StrategyMessage::Status(_)
+            | StrategyMessage::PatchParams(_) => return None,
+        })
+    }
+}
+
+impl ExecutionMessage {
+    /// Goes through the priority lane (never queued behind an entry poll loop).
+    /// Position checks and reconciliation ride along so they stay ordered with closes.
+    pub fn is_priority(&self) -> bool {
+        matches!(
+            self,
+            ExecutionMessage::ClosePosition { .. }
+                | ExecutionMessage::GetPosition { .. }
+                | ExecutionMessage::GetPositionWithReply { .. }
+                | ExecutionMessage::Reconcile(_)
+                | ExecutionMessage::ClosePair { .. }
+        )
+    }
+}
+
+/// Message plus the tracing span it was sent from, so the receiving actor
+/// logs under the same correlation ID (e.g. `trade{cid=...}`)
+#[derive(Debug)]
+pub struct Traced<T> {
+    pub msg: T,
+    pub span: Span,
+}
+
+impl<T> Traced<T> {
+    /// Capture the caller's current span
+    pub fn current(msg: T) -> Self {
+        Self { msg, span: Span::current() }
+    }
+}
+
+/// Direct answer to `PlaceOrderWithReply` / `GetPositionWithReply`
 #[derive(Debug, Clone)]
 pub enum ExecutionResponse {
-    /// Order placed successfully
-    OrderPlaced { order_id: String },
-    /// Position closed
-    PositionClosed,
+    /// Entry filled (`partial`: part of it filled, the rest was cancelled)
+    OrderFilled { fill: Fill, partial: bool },
+    /// Nothing filled
+    OrderFailed { reason: String, retryable: bool },
     /// Current position state
     CurrentPosition(Option<Position>),
-    /// Error occurred
+    /// Outcome unknown: the exchange could not be queried
     Error(String),
 }
+
+#[derive(Debug, Clone)]
+pub enum NotifierMessage {
+    /// Deliver an alert
+    Alert(Alert),
+    /// Shutdown
+    Shutdown,
+}
+
+impl NotifierMessage {
+    /// Never dropped when the notifier queue overflows (an alert storm evicts the rest)
+    pub fn must_deliver(&self) -> bool {
+        match self {
+            NotifierMessage::Alert(alert) => alert.level == AlertLevel::Critical,
+            NotifierMessage::Shutdown => true,
+        }
+    }
+}
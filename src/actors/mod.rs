@@ -1,7 +1,31 @@
 pub mod messages;
 pub mod scanner;
 pub mod websocket;
+pub mod ws_decoder;
+pub mod tick_aggregator;
+pub mod position_monitor;
+pub mod router;
+pub mod strategy_state;
+pub mod position_drift;
+pub mod entry_pricing;
+pub mod index_divergence;
+pub mod taker_flow;
+pub mod spread_gate;
+pub mod reward_risk;
+pub mod switch_ticks;
 pub mod strategy;
+pub mod order_lifecycle;
+pub mod queue_position;
+pub mod order_throttle;
+pub mod duplicate_order;
+pub mod pair_trader;
 pub mod execution;
+pub mod paper_execution;
+pub mod notifier;
+pub mod commands;
+pub mod watchdog;
+pub mod private_stream;
+pub mod reference_feed;
+pub mod account_monitor;
 
 pub use messages::*;
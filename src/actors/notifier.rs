@@ -0,0 +1,140 @@
+use crate::actors::messages::NotifierMessage;
+use crate::channel::MeteredReceiver;
+use crate::config::Config;
+use crate::health::ActorHeartbeat;
+use crate::redact::redact;
+use crate::notifications::{
+    build_digest, Alert, AlertLevel, AlertRouter, AlertThrottle, QuietHours, QuietHoursMode, TelegramClient,
+};
+use std::sync::Arc;
+use tokio::time::{interval, Duration, Instant};
+use tracing::{error, info, warn};
+
+/// NotifierActor - delivers alerts to Telegram (or just logs them if not configured)
+pub struct NotifierActor {
+    telegram: Option<TelegramClient>,
+    // ✅ ROUTING: Which chats receive which alert levels
+    router: AlertRouter,
+    message_rx: MeteredReceiver<NotifierMessage>,
+    // ✅ RATE LIMITING: Dedup identical alerts, batch low-priority ones
+    throttle: AlertThrottle,
+    // ✅ QUIET HOURS: Low-priority alerts held back overnight
+    quiet_hours: Option<QuietHours>,
+    quiet_hours_mode: QuietHoursMode,
+    quiet_queue: Vec<Alert>,
+    heartbeat: ActorHeartbeat,
+}
+
+impl NotifierActor {
+    pub fn new(
+        config: Arc<Config>,
+        message_rx: MeteredReceiver<NotifierMessage>,
+        heartbeat: ActorHeartbeat,
+    ) -> Self {
+        let router = AlertRouter::new(config.telegram_chat_id.clone(), config.telegram_routes.clone());
+        let telegram = match &config.telegram_bot_token {
+            Some(token) if router.has_recipients() => Some(TelegramClient::new(token.clone())),
+            _ => None,
+        };
+
+        Self {
+            telegram,
+            router,
+            message_rx,
+            throttle: AlertThrottle::new(config.alert_dedup_window_secs, config.alert_digest_interval_secs),
+            quiet_hours: config.quiet_hours,
+            quiet_hours_mode: config.quiet_hours_mode,
+            quiet_queue: Vec::new(),
+            heartbeat,
+        }
+    }
+
+    pub async fn run(mut self) {
+        if self.telegram.is_some() {
+            info!("📨 NotifierActor started (Telegram enabled)");
+        } else {
+            info!("📨 NotifierActor started (Telegram not configured, alerts are logged only)");
+        }
+
+        let mut flush_interval = interval(Duration::from_secs(1));
+
+        loop {
+            self.heartbeat.beat();
+            tokio::select! {
+                Some(msg) = self.message_rx.recv() => {
+                    match msg {
+                        NotifierMessage::Alert(alert) => {
+                            Self::log(&alert);
+                            if let Some(alert) = self.throttle.admit(alert, Instant::now()) {
+                                self.deliver_or_hold(alert).await;
+                            }
+                        }
+                        NotifierMessage::Shutdown => {
+                            info!("NotifierActor shutting down");
+                            // Don't lose queued digest items on shutdown
+                            for digest in self.throttle.take_digest() {
+                                self.deliver(&digest).await;
+                            }
+                            for digest in build_digest("🌅 <b>Held during quiet hours", std::mem::take(&mut self.quiet_queue)) {
+                                self.deliver(&digest).await;
+                            }
+                            break;
+                        }
+                    }
+                }
+
+                _ = flush_interval.tick() => {
+                    for alert in self.throttle.flush(Instant::now()) {
+                        self.deliver_or_hold(alert).await;
+                    }
+                    // Morning digest once quiet hours are over
+                    if !self.quiet_queue.is_empty() && !self.is_quiet_now() {
+                        let held = std::mem::take(&mut self.quiet_queue);
+                        for digest in build_digest("🌅 <b>Morning digest", held) {
+                            self.deliver(&digest).await;
+                        }
+                    }
+                }
+
+                else => break,
+            }
+        }
+    }
+
+    /// Every alert is logged, even if Telegram delivery is throttled
+    fn log(alert: &Alert) {
+        match alert.level {
+            AlertLevel::Warning => warn!("🔔 [{}] {}", alert.level, alert.text),
+            AlertLevel::Error | AlertLevel::Critical => error!("🔔 [{}] {}", alert.level, alert.text),
+            _ => info!("🔔 [{}] {}", alert.level, alert.text),
+        }
+    }
+
+    /// Hold Info/Success alerts during quiet hours, deliver everything else
+    async fn deliver_or_hold(&mut self, alert: Alert) {
+        if AlertThrottle::is_low_priority(alert.level) && self.is_quiet_now() {
+            if self.quiet_hours_mode == QuietHoursMode::Digest {
+                self.quiet_queue.push(alert);
+            }
+            return;
+        }
+        self.deliver(&alert).await;
+    }
+
+    fn is_quiet_now(&self) -> bool {
+        self.quiet_hours
+            .map(|q| q.contains(chrono::Utc::now().time()))
+            .unwrap_or(false)
+    }
+
+    async fn deliver(&self, alert: &Alert) {
+        if let Some(ref telegram) = self.telegram {
+            let text = redact(&alert.render()).into_owned();
+            for chat_id in self.router.recipients(alert.level) {
+                if let Err(e) = telegram.send_message(chat_id, &text).await {
+                    warn!("Failed to deliver Telegram alert to {}: {}", chat_id, e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! Entry Order Lifecycle
+//!
+//! place → poll → cancel → verify for one entry order, as a state machine
+//! without the IO: `ExecutionActor` makes the API calls and feeds each result
+//! in, the lifecycle answers with the next `Action` until it hands back an
+//! `Outcome`. The races live here, where they can be tested: an order that
+//! fills while the cancel is in flight (BUG #20) and one that is cancelled
+//! half filled (BUG #21) both leave a position behind and must not be
+//! reported as a plain failure.
+
+use crate::config::Config;
+use crate::exchange::{BybitError, OrderStatusResponse};
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+/// Wait between status polls (default of ORDER_POLL_INTERVAL_MS)
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Polls before the order is cancelled (default of ENTRY_MAX_POLLS: 20 × 500ms = 10 seconds)
+pub const MAX_POLLS: u32 = 20;
+
+/// Wait after the cancel before the final status query (default of CANCEL_SETTLE_MS)
+pub const CANCEL_SETTLE: Duration = Duration::from_millis(300);
+
+/// How an entry is followed: each wait is cut short when the private stream reports the order done
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollTiming {
+    pub interval: Duration,
+    pub max_polls: u32,
+    pub cancel_settle: Duration,
+}
+
+impl Default for PollTiming {
+    fn default() -> Self {
+        Self {
+            interval: POLL_INTERVAL,
+            max_polls: MAX_POLLS,
+            cancel_settle: CANCEL_SETTLE,
+        }
+    }
+}
+
+impl PollTiming {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            interval: Duration::from_millis(config.order_poll_interval_ms),
+            max_polls: config.entry_max_polls,
+            cancel_settle: Duration::from_millis(config.cancel_settle_ms),
+        }
+    }
+
+    /// How long an entry rests before it is cancelled
+    pub fn window(&self) -> Duration {
+        self.interval * self.max_polls
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleState {
+    /// Place request not answered yet
+    Placing,
+    /// Accepted by the exchange, `attempt` status polls done
+    Polling { order_id: String, attempt: u32 },
+    /// Still open after every poll, cancel requested
+    Cancelling { order_id: String },
+    /// Cancel sent, the final status decides the outcome
+    Verifying { order_id: String },
+    Done,
+}
+
+/// What the actor does next
+#[derive(Debug)]
+pub enum Action {
+    /// Wait `after`, then query the order status and call `polled`
+    Poll { after: Duration },
+    /// Cancel the order and call `cancel_sent`
+    Cancel,
+    /// Wait `after`, then query the order status and call `verified`
+    Verify { after: Duration },
+    /// Report the outcome; the lifecycle is over
+    Finish(Outcome),
+}
+
+#[derive(Debug)]
+pub enum Outcome {
+    /// Fully filled (`during_cancel` = the fill raced the timeout cancel)
+    Filled { status: Box<OrderStatusResponse>, during_cancel: bool },
+    /// Cancelled after a partial fill: a (smaller) position exists
+    PartiallyFilled(Box<OrderStatusResponse>),
+    /// Nothing was filled
+    Failed { reason: String, retryable: bool },
+    /// The final status couldn't be read; only the position can tell
+    Unknown { reason: String, retryable: bool },
+    /// Final status (or event) with no transition; logged, nothing reported
+    Unrecognized(String),
+}
+
+/// One entry order from placement to a verified outcome
+#[derive(Debug)]
+pub struct OrderLifecycle {
+    state: LifecycleState,
+    timing: PollTiming,
+}
+
+impl OrderLifecycle {
+    pub fn new(timing: PollTiming) -> Self {
+        Self {
+            state: LifecycleState::Placing,
+            timing: PollTiming { max_polls: timing.max_polls.max(1), ..timing },
+        }
+    }
+
+    pub fn state(&self) -> &LifecycleState {
+        &self.state
+    }
+
+    /// Exchange order ID once the order was accepted
+    pub fn order_id(&self) -> Option<&str> {
+        match &self.state {
+            LifecycleState::Polling { order_id, .. }
+            | LifecycleState::Cancelling { order_id }
+            | LifecycleState::Verifying { order_id } => Some(order_id),
+            LifecycleState::Placing | LifecycleState::Done => None,
+        }
+    }
+
+    /// The exchange accepted the order
+    pub fn placed(&mut self, order_id: String) -> Action {
+        match self.state {
+            LifecycleState::Placing => {
+                self.state = LifecycleState::Polling { order_id, attempt: 0 };
+                Action::Poll { after: self.timing.interval }
+            }
+            _ => self.unexpected("placed"),
+        }
+    }
+
+    /// The place request failed: nothing exists on the exchange
+    pub fn place_failed(&mut self, reason: String, retryable: bool) -> Action {
+        match self.state {
+            LifecycleState::Placing => self.finish(Outcome::Failed { reason, retryable }),
+            _ => self.unexpected("place_failed"),
+        }
+    }
+
+    /// Result of a status poll
+    pub fn polled(&mut self, result: Result<OrderStatusResponse, BybitError>) -> Action {
+        let LifecycleState::Polling { order_id, attempt } = &mut self.state else {
+            return self.unexpected("polled");
+        };
+        *attempt += 1;
+        let attempt = *attempt;
+
+        match result {
+            Ok(status) => {
+                info!(
+                    "📊 Order {} status: {} (attempt {}/{})",
+                    order_id, status.order_status, attempt, self.timing.max_polls
+                );
+                match status.order_status.as_str() {
+                    "Filled" => {
+                        return self.finish(Outcome::Filled { status: Box::new(status), during_cancel: false })
+                    }
+                    "Cancelled" | "Rejected" => {
+                        let reason = format!("Order {} {}", order_id, status.order_status);
+                        return self.finish(Outcome::Failed { reason, retryable: false });
+                    }
+                    // Keep polling
+                    "PartiallyFilled" | "New" => {}
+                    other => warn!("Unknown order status: {}", other),
+                }
+            }
+            Err(e) => warn!(
+                "Failed to query order status (attempt {}/{}): {}",
+                attempt, self.timing.max_polls, e
+            ),
+        }
+
+        if attempt < self.timing.max_polls {
+            return Action::Poll { after: self.timing.interval };
+        }
+        let order_id = std::mem::take(order_id);
+        self.state = LifecycleState::Cancelling { order_id };
+        Action::Cancel
+    }
+
+    /// Give up on a resting order before the polls run out (to reprice it)
+    pub fn withdraw(&mut self) -> Action {
+        match &mut self.state {
+            LifecycleState::Polling { order_id, .. } => {
+                let order_id = std::mem::take(order_id);
+                self.state = LifecycleState::Cancelling { order_id };
+                Action::Cancel
+            }
+            _ => self.unexpected("withdraw"),
+        }
+    }
+
+    /// The cancel request went out (whether or not the exchange accepted it)
+    pub fn cancel_sent(&mut self) -> Action {
+        match &mut self.state {
+            LifecycleState::Cancelling { order_id } => {
+                let order_id = std::mem::take(order_id);
+                self.state = LifecycleState::Verifying { order_id };
+                Action::Verify { after: self.timing.cancel_settle }
+            }
+            _ => self.unexpected("cancel_sent"),
+        }
+    }
+
+    /// Final status after the cancel
+    pub fn verified(&mut self, result: Result<OrderStatusResponse, BybitError>) -> Action {
+        let LifecycleState::Verifying { order_id } = &self.state else {
+            return self.unexpected("verified");
+        };
+        let outcome = match result {
+            Ok(status) => match status.order_status.as_str() {
+                // The order filled DURING the cancel call
+                "Filled" => Outcome::Filled { status: Box::new(status), during_cancel: true },
+                "PartiallyFilled" => Outcome::PartiallyFilled(Box::new(status)),
+                "Cancelled" | "Rejected" => Outcome::Failed {
+                    reason: format!("Order {} {} after timeout", order_id, status.order_status),
+                    retryable: false,
+                },
+                _ => Outcome::Unrecognized(status.order_status),
+            },
+            Err(e) => Outcome::Unknown {
+                reason: format!("Order {} cancel attempted, final state unknown ({})", order_id, e),
+                retryable: e.is_retryable(),
+            },
+        };
+        self.finish(outcome)
+    }
+
+    fn finish(&mut self, outcome: Outcome) -> Action {
+        self.state = LifecycleState::Done;
+        Action::Finish(outcome)
+    }
+
+    /// A call out of order is a bug in the caller; end the lifecycle rather than panic in the actor
+    fn unexpected(&mut self, event: &str) -> Action {
+        let message = format!("{} in state {:?}", event, self.state);
+        self.finish(Outcome::Unrecognized(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(order_status: &str, cum_exec_qty: &str) -> OrderStatusResponse {
+        OrderStatusResponse {
+            order_id: "o-1".to_string(),
+            order_link_id: String::new(),
+            symbol: "BTCUSDT".to_string(),
+            order_status: order_status.to_string(),
+            order_type: "Limit".to_string(),
+            side: "Buy".to_string(),
+            price: "100".to_string(),
+            qty: "2".to_string(),
+            cum_exec_qty: cum_exec_qty.to_string(),
+            cum_exec_value: "0".to_string(),
+            cum_exec_fee: "0".to_string(),
+            avg_price: "100".to_string(),
+            reduce_only: false,
+            updated_time: String::new(),
+        }
+    }
+
+    /// Placed, then open through every poll: the lifecycle is waiting for the final status
+    fn timed_out(max_polls: u32) -> OrderLifecycle {
+        let mut lifecycle = OrderLifecycle::new(PollTiming { max_polls, ..PollTiming::default() });
+        assert!(matches!(lifecycle.placed("o-1".to_string()), Action::Poll { .. }));
+        for attempt in 1..max_polls {
+            let result = if attempt % 2 == 0 { Ok(status("New", "0")) } else { Err(BybitError::NotFound("o-1".into())) };
+            assert!(matches!(lifecycle.polled(result), Action::Poll { .. }));
+        }
+        assert!(matches!(lifecycle.polled(Ok(status("PartiallyFilled", "1"))), Action::Cancel));
+        assert!(matches!(lifecycle.cancel_sent(), Action::Verify { after } if after == CANCEL_SETTLE));
+        assert_eq!(lifecycle.state(), &LifecycleState::Verifying { order_id: "o-1".to_string() });
+        lifecycle
+    }
+
+    #[test]
+    fn test_configured_timing_drives_the_waits() {
+        let timing = PollTiming {
+            interval: Duration::from_millis(200),
+            max_polls: 2,
+            cancel_settle: Duration::from_millis(100),
+        };
+        assert_eq!(timing.window(), Duration::from_millis(400));
+        let mut lifecycle = OrderLifecycle::new(timing);
+        assert!(matches!(lifecycle.placed("o-1".to_string()), Action::Poll { after } if after == timing.interval));
+        assert!(matches!(lifecycle.polled(Ok(status("New", "0"))), Action::Poll { .. }));
+        assert!(matches!(lifecycle.polled(Ok(status("New", "0"))), Action::Cancel));
+        assert!(matches!(lifecycle.cancel_sent(), Action::Verify { after } if after == timing.cancel_settle));
+    }
+
+    #[test]
+    fn test_polling_ends_on_fill_or_rejection() {
+        let mut lifecycle = OrderLifecycle::new(PollTiming::default());
+        lifecycle.placed("o-1".to_string());
+        assert!(matches!(lifecycle.polled(Ok(status("New", "0"))), Action::Poll { .. }));
+        assert!(matches!(
+            lifecycle.polled(Ok(status("Filled", "2"))),
+            Action::Finish(Outcome::Filled { during_cancel: false, .. })
+        ));
+        assert_eq!(lifecycle.state(), &LifecycleState::Done);
+
+        let mut lifecycle = OrderLifecycle::new(PollTiming::default());
+        lifecycle.placed("o-1".to_string());
+        assert!(matches!(
+            lifecycle.polled(Ok(status("Rejected", "0"))),
+            Action::Finish(Outcome::Failed { retryable: false, .. })
+        ));
+
+        let mut lifecycle = OrderLifecycle::new(PollTiming::default());
+        assert!(matches!(
+            lifecycle.place_failed("rate limited".to_string(), true),
+            Action::Finish(Outcome::Failed { retryable: true, .. })
+        ));
+        // Out of order calls end the lifecycle instead of panicking
+        assert!(matches!(lifecycle.cancel_sent(), Action::Finish(Outcome::Unrecognized(_))));
+    }
+
+    #[test]
+    fn test_final_status_after_cancel() {
+        // BUG #20: filled while the cancel was in flight
+        assert!(matches!(
+            timed_out(3).verified(Ok(status("Filled", "2"))),
+            Action::Finish(Outcome::Filled { during_cancel: true, .. })
+        ));
+        // BUG #21: cancelled half filled, a position exists
+        assert!(matches!(
+            timed_out(3).verified(Ok(status("PartiallyFilled", "1"))),
+            Action::Finish(Outcome::PartiallyFilled(s)) if s.cum_exec_qty == "1"
+        ));
+        assert!(matches!(
+            timed_out(1).verified(Ok(status("Cancelled", "0"))),
+            Action::Finish(Outcome::Failed { retryable: false, .. })
+        ));
+        assert!(matches!(
+            timed_out(2).verified(Err(BybitError::ServerError { status: 502, msg: "bad gateway".into() })),
+            Action::Finish(Outcome::Unknown { retryable: true, .. })
+        ));
+        assert!(matches!(
+            timed_out(2).verified(Ok(status("New", "0"))),
+            Action::Finish(Outcome::Unrecognized(s)) if s == "New"
+        ));
+    }
+
+    #[test]
+    fn test_withdraw_cancels_and_verifies_like_a_timeout() {
+        let mut lifecycle = OrderLifecycle::new(PollTiming::default());
+        lifecycle.placed("o-1".to_string());
+        assert!(matches!(lifecycle.polled(Ok(status("New", "0"))), Action::Poll { .. }));
+        assert!(matches!(lifecycle.withdraw(), Action::Cancel));
+        assert!(matches!(lifecycle.cancel_sent(), Action::Verify { .. }));
+        assert!(matches!(
+            lifecycle.verified(Ok(status("Filled", "2"))),
+            Action::Finish(Outcome::Filled { during_cancel: true, .. })
+        ));
+        assert!(matches!(lifecycle.withdraw(), Action::Finish(Outcome::Unrecognized(_))));
+    }
+}
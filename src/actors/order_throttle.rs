@@ -0,0 +1,147 @@
+//! Order Rate Throttle
+//!
+//! Last backstop against a strategy gone haywire (a signal loop, a state
+//! machine that keeps re-entering): at most `MAX_ORDERS_PER_MINUTE` and
+//! `MAX_ORDERS_PER_HOUR` entry orders reach the exchange in any rolling
+//! window. An order over a cap is refused before any API call, and the first
+//! refusal of a burst raises a critical alert. Closes are never throttled.
+
+use std::collections::VecDeque;
+use std::fmt;
+use tokio::time::{Duration, Instant};
+
+/// Rolling windows: (length, name, config key)
+const WINDOWS: [(Duration, &str, &str); 2] = [
+    (Duration::from_secs(60), "minute", "MAX_ORDERS_PER_MINUTE"),
+    (Duration::from_secs(3600), "hour", "MAX_ORDERS_PER_HOUR"),
+];
+
+/// Why an order was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Throttled {
+    pub limit: u32,
+    pub window: &'static str,
+    pub key: &'static str,
+    /// Until the oldest order in the window ages out
+    pub retry_in: Duration,
+    /// First refusal since the last admitted order (the one to alert on)
+    pub first: bool,
+}
+
+impl fmt::Display for Throttled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Order rate limit: {} orders in the last {} ({}), next slot in {}s",
+            self.limit,
+            self.window,
+            self.key,
+            self.retry_in.as_secs().max(1)
+        )
+    }
+}
+
+pub struct OrderThrottle {
+    /// Cap per entry of `WINDOWS` (0 = no cap)
+    limits: [u32; 2],
+    /// Admitted orders within the longest window, oldest first
+    sent: VecDeque<Instant>,
+    tripped: bool,
+}
+
+impl OrderThrottle {
+    pub fn new(per_minute: u32, per_hour: u32) -> Self {
+        Self {
+            limits: [per_minute, per_hour],
+            sent: VecDeque::new(),
+            tripped: false,
+        }
+    }
+
+    /// Count an order sent at `now`, or refuse it if any window is full
+    pub fn admit(&mut self, now: Instant) -> Result<(), Throttled> {
+        let longest = WINDOWS[WINDOWS.len() - 1].0;
+        while self.sent.front().is_some_and(|at| now.duration_since(*at) >= longest) {
+            self.sent.pop_front();
+        }
+
+        for ((window, name, key), limit) in WINDOWS.into_iter().zip(self.limits) {
+            if limit == 0 {
+                continue;
+            }
+            let in_window: Vec<&Instant> = self.sent.iter().filter(|at| now.duration_since(**at) < window).collect();
+            if in_window.len() >= limit as usize {
+                // The slot frees when the order `limit` places back leaves the window
+                let oldest = *in_window[in_window.len() - limit as usize];
+                let first = !std::mem::replace(&mut self.tripped, true);
+                return Err(Throttled {
+                    limit,
+                    window: name,
+                    key,
+                    retry_in: window.saturating_sub(now.duration_since(oldest)),
+                    first,
+                });
+            }
+        }
+
+        self.sent.push_back(now);
+        self.tripped = false;
+        Ok(())
+    }
+
+    /// Give back a slot admitted at `at` for an order that was never sent
+    pub fn refund(&mut self, at: Instant) {
+        if let Some(i) = self.sent.iter().rposition(|sent| *sent == at) {
+            self.sent.remove(i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caps_block_until_the_window_frees() {
+        let start = Instant::now();
+        let mut throttle = OrderThrottle::new(3, 5);
+        for i in 0..3 {
+            assert!(throttle.admit(start + Duration::from_secs(i)).is_ok());
+        }
+
+        // A runaway loop: only the first refusal is flagged for the alert
+        let blocked = throttle.admit(start + Duration::from_secs(10)).unwrap_err();
+        assert_eq!((blocked.window, blocked.first, blocked.retry_in), ("minute", true, Duration::from_secs(50)));
+        assert!(!throttle.admit(start + Duration::from_secs(11)).unwrap_err().first);
+
+        // The first order ages out of the minute
+        assert!(throttle.admit(start + Duration::from_secs(60)).is_ok());
+        assert!(throttle.admit(start + Duration::from_secs(121)).is_ok());
+
+        // 5 per hour reached even though the minute is quiet again
+        let blocked = throttle.admit(start + Duration::from_secs(300)).unwrap_err();
+        assert_eq!((blocked.key, blocked.first), ("MAX_ORDERS_PER_HOUR", true));
+        assert_eq!(blocked.retry_in, Duration::from_secs(3300));
+        assert!(throttle.admit(start + Duration::from_secs(3600)).is_ok());
+    }
+
+    #[test]
+    fn test_refunded_slot_can_be_used_again() {
+        let start = Instant::now();
+        let mut throttle = OrderThrottle::new(2, 0);
+        assert!(throttle.admit(start).is_ok());
+        assert!(throttle.admit(start).is_ok());
+        assert!(throttle.admit(start).is_err());
+
+        throttle.refund(start);
+        assert!(throttle.admit(start).is_ok());
+        assert!(throttle.admit(start).is_err());
+    }
+
+    #[test]
+    fn test_zero_disables_a_cap() {
+        let start = Instant::now();
+        let mut throttle = OrderThrottle::new(0, 0);
+        assert!((0..1000).all(|_| throttle.admit(start).is_ok()));
+    }
+}
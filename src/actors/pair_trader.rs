@@ -0,0 +1,717 @@
+//! Pair Trader
+//!
+//! Spread trading on two correlated symbols (`PAIR_SYMBOLS=A/B`), running
+//! next to the strategy and independent of it. Once a second the log price
+//! ratio ln(A/B) is sampled from both mid prices, and its z-score over the
+//! last `PAIR_LOOKBACK` samples says how far the pair has stretched. At
+//! `PAIR_ENTRY_Z` the rich leg is shorted and the cheap one bought,
+//! `PAIR_NOTIONAL_USD` each; the pair is closed once the spread is back
+//! inside `PAIR_EXIT_Z`, or when it stretches on to `PAIR_STOP_Z`.
+//! Execution enters and exits both legs together and never leaves one
+//! standing alone: a leg that fails unwinds the other, and a leg that can't
+//! be closed engages the kill switch (and halts the pair trader).
+//!
+//! Entries obey the strategy's risk state (kill switch, pauses, loss streak,
+//! daily trade limit) and, with shards, hold one `PositionGate` slot. The
+//! open pair is kept in `DATA_DIR/pair_trade.json`: after a restart it is
+//! held on if both legs are still there, and flattened if only one is.
+
+use crate::actors::execution::ExecutionSender;
+use crate::actors::messages::{
+    ExecutionMessage, ExecutionResponse, MarketDataMessage, NotifierMessage, PairOrder, PairReport, StrategyMessage,
+};
+use crate::actors::position_monitor::MarkPrice;
+use crate::channel::MeteredSender;
+use crate::config::Config;
+use crate::exchange::{BybitClient, SpecsCache};
+use crate::health::ActorHeartbeat;
+use crate::models::{Fill, Order, OrderSide, OrderType, Position, Symbol, TimeInForce};
+use crate::notifications::{format_duration, Alert};
+use crate::persist::{self, StateFile};
+use crate::risk_limits::PositionGate;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::{oneshot, watch};
+use tokio::time::{interval, timeout, Duration};
+use tracing::{debug, error, info, warn};
+
+/// Spread sampling period
+pub const PAIR_SAMPLE_SECS: u64 = 1;
+
+/// Expected heartbeat period: a pair entry that has to be unwound holds the loop this long
+pub const PAIR_LIVENESS_SECS: u64 = 30;
+
+/// How long an entry waits for the strategy's risk state
+const RISK_STATUS_TIMEOUT_SECS: u64 = 2;
+
+/// Which way the pair is held
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PairSide {
+    /// Long A, short B: A was cheap (z at or below -entry)
+    LongA,
+    /// Short A, long B: A was rich (z at or above entry)
+    ShortA,
+}
+
+/// Rolling z-score of the log price ratio ln(A/B)
+pub struct Spread {
+    samples: VecDeque<f64>,
+    lookback: usize,
+}
+
+impl Spread {
+    pub fn new(lookback: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(lookback),
+            lookback: lookback.max(2),
+        }
+    }
+
+    /// Add the prices of A and B; the z-score of this sample once the window is full
+    /// (None while warming up or when the spread hasn't moved at all)
+    pub fn push(&mut self, a: f64, b: f64) -> Option<f64> {
+        if a <= 0.0 || b <= 0.0 {
+            return None;
+        }
+        let value = (a / b).ln();
+        if self.samples.len() == self.lookback {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(value);
+        if self.samples.len() < self.lookback {
+            return None;
+        }
+
+        let n = self.samples.len() as f64;
+        let mean = self.samples.iter().sum::<f64>() / n;
+        let variance = self.samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+        let std_dev = variance.sqrt();
+        (std_dev > f64::EPSILON).then(|| (value - mean) / std_dev)
+    }
+}
+
+/// Entry and exit thresholds on the spread z-score
+#[derive(Debug, Clone, Copy)]
+pub struct PairRules {
+    pub entry_z: f64,
+    pub exit_z: f64,
+    /// Zero = no stop
+    pub stop_z: f64,
+}
+
+impl PairRules {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            entry_z: config.pair_entry_z,
+            exit_z: config.pair_exit_z,
+            stop_z: config.pair_stop_z,
+        }
+    }
+
+    /// Side to enter at `z` (None = not stretched enough)
+    pub fn entry(&self, z: f64) -> Option<PairSide> {
+        if z >= self.entry_z {
+            Some(PairSide::ShortA)
+        } else if z <= -self.entry_z {
+            Some(PairSide::LongA)
+        } else {
+            None
+        }
+    }
+
+    /// Why a pair held `side` should be closed at `z` (None = hold)
+    pub fn exit(&self, side: PairSide, z: f64) -> Option<&'static str> {
+        // Distance the spread still has to revert, positive while it's stretched our way
+        let stretch = match side {
+            PairSide::ShortA => z,
+            PairSide::LongA => -z,
+        };
+        if stretch <= self.exit_z {
+            Some("spread reverted")
+        } else if self.stop_z > 0.0 && stretch >= self.stop_z {
+            Some("spread stop")
+        } else {
+            None
+        }
+    }
+}
+
+/// The pair while both legs are held (persisted)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OpenPair {
+    side: PairSide,
+    long: Fill,
+    short: Fill,
+    entry_z: f64,
+    opened_at: DateTime<Utc>,
+}
+
+/// PairTrader - spread entries and exits on two symbols, both legs at once
+pub struct PairTrader {
+    config: Arc<Config>,
+    a: Symbol,
+    b: Symbol,
+    prices_a: watch::Receiver<Option<MarkPrice>>,
+    prices_b: watch::Receiver<Option<MarkPrice>>,
+    client: BybitClient,
+    specs_cache: SpecsCache,
+    market_data_tx: MeteredSender<MarketDataMessage>,
+    execution_tx: ExecutionSender,
+    strategy_tx: MeteredSender<StrategyMessage>,
+    notifier_tx: MeteredSender<NotifierMessage>,
+    /// The shards' shared gate (None without shards); a pair holds one slot
+    position_gate: Option<PositionGate>,
+    heartbeat: ActorHeartbeat,
+    spread: Spread,
+    rules: PairRules,
+    open: Option<OpenPair>,
+    state: StateFile,
+    /// The pair carried over from the last run has been checked against the exchange
+    reconciled: bool,
+    /// A leg was stranded: no more pair trades until restart
+    halted: bool,
+}
+
+impl PairTrader {
+    /// None unless PAIR_SYMBOLS is set
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_config(
+        config: Arc<Config>,
+        mut prices: impl FnMut(Symbol) -> watch::Receiver<Option<MarkPrice>>,
+        client: BybitClient,
+        specs_cache: SpecsCache,
+        market_data_tx: MeteredSender<MarketDataMessage>,
+        execution_tx: ExecutionSender,
+        strategy_tx: MeteredSender<StrategyMessage>,
+        notifier_tx: MeteredSender<NotifierMessage>,
+        position_gate: Option<PositionGate>,
+        heartbeat: ActorHeartbeat,
+    ) -> Option<Self> {
+        let (a, b) = config.pair_symbols.clone()?;
+        let (a, b) = (Symbol::from(a.as_str()), Symbol::from(b.as_str()));
+        let state = StateFile::new(config.data_path("pair_trade.json"));
+        let open = match persist::load_json::<Option<OpenPair>>(state.path()) {
+            Ok(open) => open.flatten(),
+            Err(e) => {
+                warn!("⚠️ Ignoring pair trade file: {:#}", e);
+                None
+            }
+        };
+        Some(Self {
+            prices_a: prices(a.clone()),
+            prices_b: prices(b.clone()),
+            a,
+            b,
+            client,
+            specs_cache,
+            market_data_tx,
+            execution_tx,
+            strategy_tx,
+            notifier_tx,
+            position_gate,
+            heartbeat,
+            spread: Spread::new(config.pair_lookback),
+            rules: PairRules::from_config(&config),
+            open,
+            state,
+            reconciled: false,
+            halted: false,
+            config,
+        })
+    }
+
+    pub async fn run(mut self) {
+        info!(
+            "🔗 PairTrader started: {} / {} (entry ±{}σ, exit {}σ, stop {}σ, ${} per leg, {} samples)",
+            self.a, self.b, self.rules.entry_z, self.rules.exit_z, self.rules.stop_z,
+            self.config.pair_notional_usd, self.config.pair_lookback
+        );
+
+        for symbol in [self.a.clone(), self.b.clone()] {
+            if let Err(e) = self.market_data_tx.send(MarketDataMessage::Subscribe(symbol.clone())).await {
+                error!("Failed to subscribe to pair leg {}: {}", symbol, e);
+            }
+        }
+
+        let mut sample_interval = interval(Duration::from_secs(PAIR_SAMPLE_SECS));
+
+        loop {
+            self.heartbeat.beat();
+            sample_interval.tick().await;
+            if self.halted {
+                continue;
+            }
+            let z = self.sample();
+            if !self.reconciled {
+                self.reconcile().await;
+                continue;
+            }
+            let Some(z) = z else {
+                continue;
+            };
+            match self.open.as_ref().map(|open| open.side) {
+                None => {
+                    if let Some(side) = self.rules.entry(z) {
+                        self.open_pair(side, z).await;
+                    }
+                }
+                Some(side) => {
+                    if let Some(why) = self.rules.exit(side, z) {
+                        self.close_pair(why, z).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Spread z-score at the current mid prices (None until both legs are priced and the window is full)
+    fn sample(&mut self) -> Option<f64> {
+        let a = self.prices_a.borrow().as_ref()?.price.to_f64()?;
+        let b = self.prices_b.borrow().as_ref()?.price.to_f64()?;
+        self.spread.push(a, b)
+    }
+
+    async fn open_pair(&mut self, side: PairSide, z: f64) {
+        if let Some(reason) = self.entry_block().await {
+            debug!("🔗 Spread at {:.2}σ, no pair entry: {}", z, reason);
+            return;
+        }
+        let (long, short) = match side {
+            PairSide::LongA => (self.a.clone(), self.b.clone()),
+            PairSide::ShortA => (self.b.clone(), self.a.clone()),
+        };
+        let (Some(long), Some(short)) = (self.leg(&long, OrderSide::Buy).await, self.leg(&short, OrderSide::Sell).await) else {
+            return;
+        };
+        if !self.legs_flat().await {
+            return;
+        }
+        if let Some(ref gate) = self.position_gate {
+            if !gate.try_acquire(&self.gate_key()) {
+                debug!("🔗 Spread at {:.2}σ, no pair entry: {} positions already open", z, gate.max_open());
+                return;
+            }
+        }
+        info!("🔗 Spread at {:.2}σ: long {} / short {}", z, long.symbol, short.symbol);
+
+        match self.execute(|reply| ExecutionMessage::PlacePair { legs: PairOrder { long, short }, reply }).await {
+            Some(PairReport::Opened { long, short }) => {
+                self.notify(Alert::info(format!(
+                    "🔗 <b>Pair opened</b> at {:.2}σ\nLong {} {} @ {}\nShort {} {} @ {}",
+                    z, long.qty, long.symbol, long.avg_price, short.qty, short.symbol, short.avg_price
+                )));
+                self.open = Some(OpenPair { side, long, short, entry_z: z, opened_at: Utc::now() });
+                self.save();
+            }
+            Some(PairReport::Failed(reason)) => {
+                warn!("🔗 Pair entry failed, nothing held: {}", reason);
+                self.notify(Alert::warning(format!("🔗 <b>Pair entry failed</b>\n{}\nBoth legs flat", reason)));
+                self.release_gate();
+            }
+            Some(PairReport::Stranded(reason)) => self.halt(&reason),
+            other => {
+                // Execution gone or busy: whether anything is held is unknown, the slot stays taken
+                warn!("🔗 Unexpected pair entry report: {:?}", other);
+            }
+        }
+    }
+
+    async fn close_pair(&mut self, why: &str, z: f64) {
+        let Some(open) = self.open.as_ref() else {
+            return;
+        };
+        info!("🔗 Closing pair at {:.2}σ ({})", z, why);
+        let (long, short) = (open.long.symbol.clone(), open.short.symbol.clone());
+
+        match self.execute(|reply| ExecutionMessage::ClosePair { long, short, reply }).await {
+            Some(PairReport::Closed { long: long_exit, short: short_exit }) => {
+                let Some(open) = self.open.take() else {
+                    return;
+                };
+                self.save();
+                self.release_gate();
+                let pnl = leg_pnl(&open.long, long_exit.as_ref()) + leg_pnl(&open.short, short_exit.as_ref());
+                info!("🔗 Pair closed ({}): net PnL {:.4} USDT", why, pnl);
+                let text = format!(
+                    "🔗 <b>Pair closed</b> ({})\n{:.2}σ → {:.2}σ in {}\nNet PnL: <b>{:.2} USDT</b>",
+                    why,
+                    open.entry_z,
+                    z,
+                    format_duration((Utc::now() - open.opened_at).num_seconds().max(0) as u64),
+                    pnl
+                );
+                self.notify(if pnl >= Decimal::ZERO { Alert::success(text) } else { Alert::warning(text) });
+            }
+            Some(PairReport::Stranded(reason)) => self.halt(&reason),
+            // Execution gone or busy: the next sample tries again
+            other => warn!("🔗 Pair exit not confirmed: {:?}", other),
+        }
+    }
+
+    /// Market order for one leg, `PAIR_NOTIONAL_USD` at the current mid (None if it can't be sized)
+    async fn leg(&self, symbol: &Symbol, side: OrderSide) -> Option<Order> {
+        let price = if *symbol == self.a { &self.prices_a } else { &self.prices_b };
+        let price = price.borrow().as_ref()?.price;
+        let specs = self.specs_cache.get_or_fetch(&self.client, &symbol.0).await;
+        let notional = Decimal::from_f64(self.config.pair_notional_usd)?;
+        let qty = specs.round_qty(notional / price);
+        if qty < specs.min_order_qty {
+            warn!("🔗 PAIR_NOTIONAL_USD={} is below the minimum order of {} ({})", notional, symbol, specs.min_order_qty);
+            return None;
+        }
+        Some(Order {
+            symbol: symbol.clone(),
+            side,
+            order_type: OrderType::Market,
+            qty,
+            price: None,
+            time_in_force: TimeInForce::IOC,
+            reduce_only: false,
+            qty_step: Some(specs.qty_step),
+            tick_size: None,
+            ref_price: Some(price),
+            allow_duplicate: false,
+            hard_stop_percent: None,
+            position_idx: 0,
+        })
+    }
+
+    /// Why the strategy's risk state keeps entries out right now (None = clear).
+    /// Unknown state blocks too
+    async fn entry_block(&self) -> Option<String> {
+        let (reply, status) = oneshot::channel();
+        let asked = timeout(Duration::from_secs(RISK_STATUS_TIMEOUT_SECS), async {
+            self.strategy_tx.send(StrategyMessage::Status(reply)).await.ok()?;
+            status.await.ok()
+        })
+        .await;
+        match asked {
+            Ok(Some(status)) => status.entry_block(),
+            _ => Some("strategy risk state unavailable".to_string()),
+        }
+    }
+
+    /// Neither leg is held on the exchange, so an entry can't stack on a position left from earlier
+    async fn legs_flat(&self) -> bool {
+        for symbol in [self.a.clone(), self.b.clone()] {
+            match self.position(&symbol).await {
+                Ok(None) => {}
+                Ok(Some(position)) => {
+                    warn!("🔗 {} already holds {:?} {}, no pair entry", symbol, position.side, position.size);
+                    return false;
+                }
+                Err(e) => {
+                    warn!("🔗 {} position unknown, no pair entry: {}", symbol, e);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// The exchange position of `symbol` (None = flat)
+    async fn position(&self, symbol: &Symbol) -> Result<Option<Position>, String> {
+        let response = self
+            .execution_tx
+            .request(|reply| ExecutionMessage::GetPositionWithReply { symbol: symbol.clone(), reply })
+            .await;
+        match response {
+            Some(ExecutionResponse::CurrentPosition(position)) => Ok(position),
+            other => Err(format!("{:?}", other)),
+        }
+    }
+
+    /// The pair the last run left open: held on if both legs are still there, dropped if
+    /// both are gone, flattened if only one is. Retried on the next sample until the
+    /// positions are known
+    async fn reconcile(&mut self) {
+        let Some(open) = self.open.as_ref() else {
+            self.reconciled = true;
+            return;
+        };
+        let (long, short) = (open.long.symbol.clone(), open.short.symbol.clone());
+        let held = match (self.position(&long).await, self.position(&short).await) {
+            (Ok(long_position), Ok(short_position)) => (long_position.is_some(), short_position.is_some()),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("🔗 Pair {} / {} from the last run, positions unknown: {}", long, short, e);
+                return;
+            }
+        };
+        match held {
+            (true, true) => {
+                info!("🔗 Pair long {} / short {} carried over from the last run", long, short);
+                if let Some(ref gate) = self.position_gate {
+                    gate.try_acquire(&self.gate_key());
+                }
+            }
+            (false, false) => {
+                info!("🔗 Pair {} / {} from the last run was closed meanwhile", long, short);
+                self.open = None;
+                self.save();
+            }
+            _ => {
+                warn!("🔗 Only one leg of the pair {} / {} is left from the last run, flattening it", long, short);
+                match self.execute(|reply| ExecutionMessage::ClosePair { long, short, reply }).await {
+                    Some(PairReport::Closed { .. }) => {
+                        self.notify(Alert::warning(
+                            "🔗 <b>Pair half-open after restart</b>\nThe remaining leg was closed".to_string(),
+                        ));
+                        self.open = None;
+                        self.save();
+                    }
+                    Some(PairReport::Stranded(reason)) => self.halt(&reason),
+                    other => {
+                        warn!("🔗 Flattening the leftover leg not confirmed: {:?}", other);
+                        return;
+                    }
+                }
+            }
+        }
+        self.reconciled = true;
+    }
+
+    /// Send a pair message and wait for execution's report
+    async fn execute(&self, msg: impl FnOnce(oneshot::Sender<PairReport>) -> ExecutionMessage) -> Option<PairReport> {
+        self.execution_tx.request(msg).await
+    }
+
+    /// Execution already alerted and engaged the kill switch; stay out until restart.
+    /// The pair file and the gate slot are kept: a leg is still open
+    fn halt(&mut self, reason: &str) {
+        error!("🔗 Pair leg stranded, pair trading halted until restart: {}", reason);
+        self.halted = true;
+    }
+
+    fn save(&self) {
+        self.state.save(&self.open, "pair trade");
+    }
+
+    fn gate_key(&self) -> String {
+        format!("{}/{}", self.a, self.b)
+    }
+
+    fn release_gate(&self) {
+        if let Some(ref gate) = self.position_gate {
+            gate.release(&self.gate_key());
+        }
+    }
+
+    fn notify(&self, alert: Alert) {
+        if let Err(e) = self.notifier_tx.try_send(NotifierMessage::Alert(alert)) {
+            debug!("Dropped alert: {}", e);
+        }
+    }
+}
+
+/// Realized PnL of one leg after fees (no exit fill = nothing was left to close)
+fn leg_pnl(entry: &Fill, exit: Option<&Fill>) -> Decimal {
+    let Some(exit) = exit else {
+        return -entry.fee;
+    };
+    let qty = exit.qty.min(entry.qty);
+    let gross = match entry.side {
+        OrderSide::Buy => (exit.avg_price - entry.avg_price) * qty,
+        OrderSide::Sell => (entry.avg_price - exit.avg_price) * qty,
+    };
+    gross - entry.fee - exit.fee
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actors::messages::Traced;
+    use crate::channel::{metered_channel, MeteredReceiver, OverflowPolicy};
+    use crate::health::HealthRegistry;
+    use crate::metrics::Metrics;
+    use crate::models::PositionSide;
+
+    fn fill(symbol: &str, side: OrderSide) -> Fill {
+        Fill {
+            symbol: Symbol::from(symbol),
+            order_id: "1".to_string(),
+            side,
+            qty: Decimal::from(2),
+            avg_price: Decimal::from(100),
+            fee: Decimal::ZERO,
+        }
+    }
+
+    /// Execution's priority lane: `held` symbols have a position, ClosePair flattens both
+    async fn fake_execution(mut priority_rx: MeteredReceiver<Traced<ExecutionMessage>>, held: Vec<&'static str>) {
+        while let Some(Traced { msg, .. }) = priority_rx.recv().await {
+            match msg {
+                ExecutionMessage::GetPositionWithReply { symbol, reply } => {
+                    let position = held.iter().any(|held| symbol == Symbol::from(*held)).then(|| Position {
+                        symbol: symbol.clone(),
+                        side: PositionSide::Long,
+                        size: Decimal::from(2),
+                        entry_price: Decimal::from(100),
+                        current_price: Decimal::from(100),
+                        unrealized_pnl: Decimal::ZERO,
+                        stop_loss: None,
+                        leverage: Decimal::ONE,
+                        liq_price: None,
+                        realized_pnl: Decimal::ZERO,
+                        cum_realized_pnl: Decimal::ZERO,
+                    });
+                    let _ = reply.send(ExecutionResponse::CurrentPosition(position));
+                }
+                ExecutionMessage::ClosePair { reply, .. } => {
+                    let _ = reply.send(PairReport::Closed { long: None, short: None });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_half_open_pair_from_the_last_run_is_flattened() {
+        std::env::set_var("BYBIT_API_KEY", "test");
+        std::env::set_var("BYBIT_API_SECRET", "test");
+        let dir = std::env::temp_dir().join(format!("pair-trader-test-{}", std::process::id()));
+        let mut config = Config::from_env().unwrap();
+        config.data_dir = dir.to_string_lossy().into_owned();
+        config.pair_symbols = Some(("BTCUSDT".to_string(), "ETHUSDT".to_string()));
+        let left_open = OpenPair {
+            side: PairSide::LongA,
+            long: fill("BTCUSDT", OrderSide::Buy),
+            short: fill("ETHUSDT", OrderSide::Sell),
+            entry_z: -2.5,
+            opened_at: Utc::now(),
+        };
+        persist::save_json(&config.data_path("pair_trade.json"), &Some(left_open)).unwrap();
+
+        let metrics = Metrics::new();
+        let policy = OverflowPolicy::Block { timeout: None };
+        let (normal_tx, _normal_rx) = metered_channel("execution", 10, policy, &metrics);
+        let (priority_tx, priority_rx) = metered_channel("execution_priority", 10, policy, &metrics);
+        let (market_data_tx, _market_data_rx) = metered_channel("market_data", 10, policy, &metrics);
+        let (strategy_tx, _strategy_rx) = metered_channel("strategy", 10, policy, &metrics);
+        let (notifier_tx, _notifier_rx) = metered_channel("notifier", 10, policy, &metrics);
+        let gate = PositionGate::new(1);
+        let mut trader = PairTrader::from_config(
+            Arc::new(config),
+            |_| watch::channel(None).1,
+            BybitClient::new("test".to_string(), "test".to_string(), "http://127.0.0.1:9".to_string()),
+            SpecsCache::new(),
+            market_data_tx,
+            ExecutionSender::new(normal_tx, priority_tx),
+            strategy_tx,
+            notifier_tx,
+            Some(gate.clone()),
+            HealthRegistry::new().register("pair_trader", Duration::from_secs(PAIR_LIVENESS_SECS)),
+        )
+        .unwrap();
+        assert!(trader.open.is_some());
+
+        // Only the long leg survived the restart: it is closed and the pair forgotten
+        tokio::spawn(fake_execution(priority_rx, vec!["BTCUSDT"]));
+        trader.reconcile().await;
+        assert!(trader.reconciled);
+        assert!(trader.open.is_none());
+        assert!(!trader.halted);
+        // Nothing held: the slot is free for the strategy
+        assert!(gate.try_acquire("SOLUSDT"));
+
+        let mut saved = None;
+        for _ in 0..100 {
+            saved = persist::load_json::<Option<OpenPair>>(trader.state.path()).unwrap();
+            if matches!(saved, Some(None)) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(matches!(saved, Some(None)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_entries_wait_for_the_strategy_risk_state() {
+        std::env::set_var("BYBIT_API_KEY", "test");
+        std::env::set_var("BYBIT_API_SECRET", "test");
+        let mut config = Config::from_env().unwrap();
+        config.data_dir = std::env::temp_dir().join(format!("pair-risk-test-{}", std::process::id())).to_string_lossy().into_owned();
+        config.pair_symbols = Some(("BTCUSDT".to_string(), "ETHUSDT".to_string()));
+
+        let metrics = Metrics::new();
+        let policy = OverflowPolicy::Block { timeout: None };
+        let (normal_tx, _normal_rx) = metered_channel("execution", 10, policy, &metrics);
+        let (priority_tx, _priority_rx) = metered_channel("execution_priority", 10, policy, &metrics);
+        let (market_data_tx, _market_data_rx) = metered_channel("market_data", 10, policy, &metrics);
+        let (strategy_tx, strategy_rx) = metered_channel("strategy", 10, policy, &metrics);
+        let (notifier_tx, _notifier_rx) = metered_channel("notifier", 10, policy, &metrics);
+        let trader = PairTrader::from_config(
+            Arc::new(config),
+            |_| watch::channel(None).1,
+            BybitClient::new("test".to_string(), "test".to_string(), "http://127.0.0.1:9".to_string()),
+            SpecsCache::new(),
+            market_data_tx,
+            ExecutionSender::new(normal_tx, priority_tx),
+            strategy_tx,
+            notifier_tx,
+            None,
+            HealthRegistry::new().register("pair_trader", Duration::from_secs(PAIR_LIVENESS_SECS)),
+        )
+        .unwrap();
+
+        // A strategy that never answers blocks entries rather than letting them through
+        let block = trader.entry_block().await;
+        assert_eq!(block.as_deref(), Some("strategy risk state unavailable"));
+        drop(strategy_rx);
+    }
+
+    #[test]
+    fn test_spread_zscore_needs_a_full_window() {
+        let mut spread = Spread::new(4);
+        assert_eq!(spread.push(100.0, 50.0), None);
+        assert_eq!(spread.push(100.0, 50.0), None);
+        assert_eq!(spread.push(100.0, 50.0), None);
+        // Full, but a spread that never moved has no z-score
+        assert_eq!(spread.push(100.0, 50.0), None);
+
+        // A rallies against B: positive z, and the oldest samples roll out
+        let z = spread.push(110.0, 50.0).unwrap();
+        assert!((z - 3.0f64.sqrt()).abs() < 1e-9, "{}", z);
+        assert!(spread.push(90.0, 50.0).unwrap() < 0.0);
+    }
+
+    #[test]
+    fn test_rules_enter_against_the_stretch_and_exit_on_reversion_or_stop() {
+        let rules = PairRules { entry_z: 2.0, exit_z: 0.5, stop_z: 4.0 };
+        assert_eq!(rules.entry(2.1), Some(PairSide::ShortA));
+        assert_eq!(rules.entry(-2.0), Some(PairSide::LongA));
+        assert_eq!(rules.entry(1.9), None);
+
+        assert_eq!(rules.exit(PairSide::ShortA, 1.5), None);
+        assert_eq!(rules.exit(PairSide::ShortA, 0.4), Some("spread reverted"));
+        assert_eq!(rules.exit(PairSide::ShortA, -1.0), Some("spread reverted"));
+        assert_eq!(rules.exit(PairSide::ShortA, 4.2), Some("spread stop"));
+        assert_eq!(rules.exit(PairSide::LongA, -3.0), None);
+        assert_eq!(rules.exit(PairSide::LongA, -4.0), Some("spread stop"));
+
+        let no_stop = PairRules { stop_z: 0.0, ..rules };
+        assert_eq!(no_stop.exit(PairSide::LongA, -10.0), None);
+    }
+
+    #[test]
+    fn test_leg_pnl_nets_fees() {
+        let fill = |side, price: i64, fee: &str| Fill {
+            symbol: Symbol::from("ETHUSDT"),
+            order_id: "1".to_string(),
+            side,
+            qty: Decimal::from(2),
+            avg_price: Decimal::from(price),
+            fee: fee.parse().unwrap(),
+        };
+        // Long 2 @ 100 → 105, short 2 @ 50 → 52: +10 and -4, minus 4 × 0.1 fees
+        let long = leg_pnl(&fill(OrderSide::Buy, 100, "0.1"), Some(&fill(OrderSide::Sell, 105, "0.1")));
+        let short = leg_pnl(&fill(OrderSide::Sell, 50, "0.1"), Some(&fill(OrderSide::Buy, 52, "0.1")));
+        assert_eq!(long + short, "5.6".parse::<Decimal>().unwrap());
+        assert_eq!(leg_pnl(&fill(OrderSide::Buy, 100, "0.1"), None), "-0.1".parse::<Decimal>().unwrap());
+    }
+}
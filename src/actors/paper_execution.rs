@@ -0,0 +1,317 @@
+//! Paper Execution
+//!
+//! Takes the place of `ExecutionActor` behind a shadow strategy: orders never
+//! reach the exchange. An entry fills at once at its limit price (or the
+//! current mid for market orders), a close at the current mid, with Bybit's
+//! base-tier fees. The strategy gets the same feedback real execution sends:
+//! the fill, then the position; the close fill, then flat.
+
+use crate::actors::execution::EXECUTION_LIVENESS_SECS;
+use crate::actors::messages::{ExecutionMessage, ExecutionResponse, PairReport, StrategyMessage, Traced};
+use crate::actors::position_monitor::MarkPrice;
+use crate::channel::{MeteredReceiver, MeteredSender};
+use crate::config::Config;
+use crate::health::ActorHeartbeat;
+use crate::models::*;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::time::{interval, Duration};
+use tracing::{debug, error, info, warn};
+
+/// Bybit linear perpetuals, base tier: 0.055% taker, 0.02% maker
+const TAKER_FEE_RATE: Decimal = Decimal::from_parts(55, 0, 0, false, 5);
+const MAKER_FEE_RATE: Decimal = Decimal::from_parts(2, 0, 0, false, 4);
+
+/// PaperExecutionActor - simulated fills for a shadow strategy
+pub struct PaperExecutionActor {
+    config: Arc<Config>,
+    message_rx: MeteredReceiver<Traced<ExecutionMessage>>,
+    priority_rx: MeteredReceiver<Traced<ExecutionMessage>>,
+    strategy_tx: MeteredSender<StrategyMessage>,
+    /// Mid price of the traded symbol (the live feed's)
+    prices: watch::Receiver<Option<MarkPrice>>,
+    positions: HashMap<Symbol, Position>,
+    order_seq: u64,
+    heartbeat: ActorHeartbeat,
+}
+
+impl PaperExecutionActor {
+    pub fn new(
+        config: Arc<Config>,
+        message_rx: MeteredReceiver<Traced<ExecutionMessage>>,
+        priority_rx: MeteredReceiver<Traced<ExecutionMessage>>,
+        strategy_tx: MeteredSender<StrategyMessage>,
+        prices: watch::Receiver<Option<MarkPrice>>,
+        heartbeat: ActorHeartbeat,
+    ) -> Self {
+        Self {
+            config,
+            message_rx,
+            priority_rx,
+            strategy_tx,
+            prices,
+            positions: HashMap::new(),
+            order_seq: 0,
+            heartbeat,
+        }
+    }
+
+    pub async fn run(mut self) {
+        info!("📝 PaperExecutionActor started");
+
+        let mut liveness_interval = interval(Duration::from_secs(EXECUTION_LIVENESS_SECS));
+
+        loop {
+            self.heartbeat.beat();
+            let msg = tokio::select! {
+                biased;
+                Some(Traced { msg, .. }) = self.priority_rx.recv() => msg,
+                Some(Traced { msg, .. }) = self.message_rx.recv() => msg,
+                _ = liveness_interval.tick() => continue,
+                else => break,
+            };
+            if !self.handle(msg).await {
+                break;
+            }
+        }
+    }
+
+    /// Returns false on Shutdown
+    async fn handle(&mut self, msg: ExecutionMessage) -> bool {
+        match msg {
+            ExecutionMessage::PlaceOrder(order) => {
+                self.fill_entry(order).await;
+            }
+            ExecutionMessage::PlaceOrderWithReply { order, reply } => {
+                let _ = reply.send(self.fill_entry(order).await);
+            }
+            ExecutionMessage::ClosePosition { symbol, reason, .. } => self.fill_close(symbol, reason).await,
+            ExecutionMessage::GetPosition { symbol, query } => {
+                let position = self.marked_position(&symbol);
+                self.send(StrategyMessage::PositionUpdate { symbol, position, query: Some(query) }).await;
+            }
+            ExecutionMessage::GetPositionWithReply { symbol, reply } => {
+                let _ = reply.send(ExecutionResponse::CurrentPosition(self.marked_position(&symbol)));
+            }
+            ExecutionMessage::Reconcile(symbol) => {
+                // Paper orders fill at once: nothing is ever left working
+                let position = self.positions.get(&symbol).cloned();
+                self.send(StrategyMessage::Reconciled { symbol, position }).await;
+            }
+            ExecutionMessage::PlacePair { reply, .. } => {
+                // Shadows run the single-symbol strategy only
+                let _ = reply.send(PairReport::Failed("pair trading is not simulated".to_string()));
+            }
+            ExecutionMessage::ClosePair { reply, .. } => {
+                let _ = reply.send(PairReport::Closed { long: None, short: None });
+            }
+            ExecutionMessage::Shutdown => {
+                info!("PaperExecutionActor shutting down");
+                return false;
+            }
+        }
+        true
+    }
+
+    async fn fill_entry(&mut self, order: Order) -> ExecutionResponse {
+        let Some(price) = order.price.or_else(|| self.mark(&order.symbol)).or(order.ref_price) else {
+            let reason = format!("No price to fill {} at", order.symbol);
+            warn!("📝 {}", reason);
+            self.send(StrategyMessage::OrderFailed { symbol: order.symbol, reason: reason.clone(), retryable: false }).await;
+            return ExecutionResponse::OrderFailed { reason, retryable: false };
+        };
+        let fee_rate = match order.order_type {
+            OrderType::Limit => MAKER_FEE_RATE,
+            OrderType::Market => TAKER_FEE_RATE,
+        };
+        let fill = self.fill(&order.symbol, order.side, order.qty, price, fee_rate);
+        info!("📝 Paper fill: {:?} {} {} @ {}", fill.side, fill.qty, fill.symbol, fill.avg_price);
+
+        let side = match order.side {
+            OrderSide::Buy => PositionSide::Long,
+            OrderSide::Sell => PositionSide::Short,
+        };
+        let position = match self.positions.remove(&order.symbol) {
+            // Adding to the position: size-weighted entry
+            Some(open) if open.side == side => {
+                let size = open.size + order.qty;
+                Position {
+                    entry_price: (open.entry_price * open.size + price * order.qty) / size,
+                    size,
+                    current_price: price,
+                    ..open
+                }
+            }
+            _ => self.new_position(&order.symbol, side, order.qty, price),
+        };
+        self.positions.insert(order.symbol.clone(), position.clone());
+
+        self.send(StrategyMessage::OrderFilled(fill.clone())).await;
+        self.send(StrategyMessage::PositionUpdate { symbol: order.symbol, position: Some(position), query: None }).await;
+        ExecutionResponse::OrderFilled { fill, partial: false }
+    }
+
+    async fn fill_close(&mut self, symbol: Symbol, reason: ExitReason) {
+        let Some(position) = self.positions.remove(&symbol) else {
+            debug!("📝 No paper position for {}", symbol);
+            self.send(StrategyMessage::PositionUpdate { symbol, position: None, query: None }).await;
+            return;
+        };
+        let price = self.mark(&symbol).unwrap_or(position.current_price);
+        let side = match position.side {
+            PositionSide::Long => OrderSide::Sell,
+            PositionSide::Short => OrderSide::Buy,
+        };
+        let fill = self.fill(&symbol, side, position.size, price, TAKER_FEE_RATE);
+        info!("📝 Paper close: {} {} @ {} ({})", symbol, position.size, price, reason);
+
+        self.send(StrategyMessage::CloseFilled { fill, reason }).await;
+        self.send(StrategyMessage::PositionUpdate { symbol, position: None, query: None }).await;
+    }
+
+    fn fill(&mut self, symbol: &Symbol, side: OrderSide, qty: Decimal, price: Decimal, fee_rate: Decimal) -> Fill {
+        self.order_seq += 1;
+        Fill {
+            symbol: symbol.clone(),
+            order_id: format!("paper-{}", self.order_seq),
+            side,
+            qty,
+            avg_price: price,
+            fee: qty * price * fee_rate,
+        }
+    }
+
+    /// Position as `ExecutionActor` reports it (config stop loss, configured leverage)
+    fn new_position(&self, symbol: &Symbol, side: PositionSide, size: Decimal, entry_price: Decimal) -> Position {
+        let sl_fraction = Decimal::from_f64(self.config.stop_loss_percent / 100.0).unwrap_or_default();
+        let stop_loss = match side {
+            PositionSide::Long => entry_price * (Decimal::ONE - sl_fraction),
+            PositionSide::Short => entry_price * (Decimal::ONE + sl_fraction),
+        };
+        Position {
+            symbol: symbol.clone(),
+            side,
+            size,
+            entry_price,
+            current_price: entry_price,
+            unrealized_pnl: Decimal::ZERO,
+            stop_loss: Some(stop_loss),
+            leverage: self
+                .config
+                .leverage
+                .and_then(Decimal::from_f64)
+                .filter(|l| *l >= Decimal::ONE)
+                .unwrap_or(Decimal::ONE),
+            liq_price: None,
+            realized_pnl: Decimal::ZERO,
+            cum_realized_pnl: Decimal::ZERO,
+        }
+    }
+
+    /// Open paper position on `symbol`, marked at the current mid
+    fn marked_position(&self, symbol: &Symbol) -> Option<Position> {
+        self.positions.get(symbol).cloned().map(|mut position| {
+            if let Some(price) = self.mark(symbol) {
+                position.current_price = price;
+            }
+            position
+        })
+    }
+
+    /// Current mid of `symbol`, if the feed is on it
+    fn mark(&self, symbol: &Symbol) -> Option<Decimal> {
+        self.prices.borrow().as_ref().filter(|mark| &mark.symbol == symbol).map(|mark| mark.price)
+    }
+
+    async fn send(&self, msg: StrategyMessage) {
+        if let Err(e) = self.strategy_tx.send(msg).await {
+            error!("Failed to send paper execution feedback: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actors::execution::ExecutionSender;
+    use crate::channel::{metered_channel, OverflowPolicy};
+    use crate::health::HealthRegistry;
+    use crate::metrics::Metrics;
+
+    #[tokio::test]
+    async fn test_paper_fills_report_like_execution() {
+        std::env::set_var("BYBIT_API_KEY", "paper");
+        std::env::set_var("BYBIT_API_SECRET", "paper");
+        let config = Arc::new(Config::from_env().unwrap());
+        let metrics = Metrics::new();
+        let policy = OverflowPolicy::Block { timeout: None };
+        let (normal_tx, normal_rx) = metered_channel("execution", 10, policy, &metrics);
+        let (priority_tx, priority_rx) = metered_channel("execution_priority", 10, policy, &metrics);
+        let (strategy_tx, mut strategy_rx) = metered_channel("strategy", 10, policy, &metrics);
+        let (price_tx, prices) = watch::channel(None);
+        let symbol = Symbol::from("BTCUSDT");
+        let mark = |price: i64| Some(MarkPrice { symbol: Symbol::from("BTCUSDT"), price: Decimal::from(price) });
+
+        let actor = PaperExecutionActor::new(
+            config,
+            normal_rx,
+            priority_rx,
+            strategy_tx,
+            prices,
+            HealthRegistry::new().register("paper_execution", Duration::from_secs(EXECUTION_LIVENESS_SECS)),
+        );
+        let handle = tokio::spawn(actor.run());
+        let execution = ExecutionSender::new(normal_tx, priority_tx);
+
+        price_tx.send_replace(mark(100));
+        let order = Order {
+            symbol: symbol.clone(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Market,
+            qty: Decimal::from(2),
+            price: None,
+            time_in_force: TimeInForce::IOC,
+            reduce_only: false,
+            qty_step: None,
+            tick_size: None,
+            ref_price: Some(Decimal::from(99)),
+            allow_duplicate: false,
+            hard_stop_percent: None,
+            position_idx: 0,
+        };
+        execution.send(ExecutionMessage::PlaceOrder(order.clone())).await.unwrap();
+        // Market entry at the mid, taker fee: 2 × 100 × 0.055%
+        assert!(matches!(strategy_rx.recv().await, Some(StrategyMessage::OrderFilled(fill))
+            if fill.avg_price == Decimal::from(100) && fill.fee == Decimal::new(11, 2)));
+        assert!(matches!(strategy_rx.recv().await, Some(StrategyMessage::PositionUpdate { position: Some(p), .. })
+            if p.side == PositionSide::Long && p.size == Decimal::from(2)));
+
+        price_tx.send_replace(mark(110));
+        execution
+            .send(ExecutionMessage::ClosePosition { symbol: symbol.clone(), position_side: PositionSide::Long, reason: ExitReason::TakeProfit })
+            .await
+            .unwrap();
+        assert!(matches!(strategy_rx.recv().await, Some(StrategyMessage::CloseFilled { fill, reason: ExitReason::TakeProfit })
+            if fill.side == OrderSide::Sell && fill.avg_price == Decimal::from(110)));
+        assert!(matches!(strategy_rx.recv().await, Some(StrategyMessage::PositionUpdate { position: None, .. })));
+
+        // Flat again
+        execution.send(ExecutionMessage::GetPosition { symbol: symbol.clone(), query: 1 }).await.unwrap();
+        assert!(matches!(strategy_rx.recv().await, Some(StrategyMessage::PositionUpdate { position: None, .. })));
+
+        // A caller waiting on the answer gets it directly, the strategy still hears of the fill
+        let response = execution.request(|reply| ExecutionMessage::PlaceOrderWithReply { order, reply }).await;
+        assert!(matches!(response, Some(ExecutionResponse::OrderFilled { fill, partial: false }) if fill.avg_price == Decimal::from(110)));
+        assert!(matches!(strategy_rx.recv().await, Some(StrategyMessage::OrderFilled(_))));
+        assert!(matches!(strategy_rx.recv().await, Some(StrategyMessage::PositionUpdate { position: Some(_), .. })));
+        let response = execution.request(|reply| ExecutionMessage::GetPositionWithReply { symbol, reply }).await;
+        assert!(matches!(response, Some(ExecutionResponse::CurrentPosition(Some(p))) if p.size == Decimal::from(2)));
+        assert!(strategy_rx.try_recv().is_none());
+
+        execution.send(ExecutionMessage::Shutdown).await.unwrap();
+        handle.await.unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+//! Position Drift Detection
+//!
+//! The periodic position verification compares what the strategy holds with
+//! what the exchange reports. Outside an order or a close nothing should
+//! touch the position, so a different side, size or entry price (leftovers of
+//! a partial close, a manual trade in the Bybit app), or a position showing up
+//! while the strategy is flat, is drift: it is alerted, then either adopted as
+//! the new truth or closed, per `POSITION_DRIFT_ACTION`. A vanished position
+//! stays the strategy's liquidation path. Entry prices within
+//! `ENTRY_PRICE_TOLERANCE` of each other count as the same price.
+
+use crate::actors::strategy_state::StrategyState;
+use crate::models::{Position, Symbol};
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// Relative entry price difference that isn't drift (1 bp: the exchange's average
+/// entry is recomputed at its own precision)
+const ENTRY_PRICE_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 4);
+
+/// Disagreement between the strategy and the exchange
+#[derive(Debug, Clone)]
+pub struct Drift {
+    pub symbol: Symbol,
+    /// What the strategy held (None = flat)
+    pub expected: Option<Position>,
+    pub actual: Position,
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let held = |p: &Position| format!("{:?} {} @ {}", p.side, p.size, p.entry_price);
+        write!(
+            f,
+            "{}: strategy holds {}, exchange reports {}",
+            self.symbol,
+            self.expected.as_ref().map_or("nothing".to_string(), held),
+            held(&self.actual)
+        )
+    }
+}
+
+/// Drift between `expected` and the exchange's `actual` position, judged in `state`
+/// (while an order or close is in flight the position is supposed to change)
+pub fn detect(state: StrategyState, expected: Option<&Position>, actual: Option<&Position>) -> Option<Drift> {
+    let actual = actual?;
+    let drifted = match (state, expected) {
+        (StrategyState::Idle, _) => true,
+        (StrategyState::PositionOpen, Some(expected)) => {
+            expected.side != actual.side
+                || expected.size != actual.size
+                || (expected.entry_price - actual.entry_price).abs()
+                    > expected.entry_price.abs() * ENTRY_PRICE_TOLERANCE
+        }
+        _ => false,
+    };
+    drifted.then(|| Drift {
+        symbol: actual.symbol.clone(),
+        expected: expected.filter(|_| state == StrategyState::PositionOpen).cloned(),
+        actual: actual.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PositionSide;
+    use std::str::FromStr;
+
+    fn position(side: PositionSide, size: i64, entry: i64) -> Position {
+        Position {
+            symbol: Symbol::from("BTCUSDT"),
+            side,
+            size: Decimal::from(size),
+            entry_price: Decimal::from(entry),
+            current_price: Decimal::from(entry),
+            unrealized_pnl: Decimal::ZERO,
+            stop_loss: None,
+            leverage: Decimal::ONE,
+            liq_price: None,
+            realized_pnl: Decimal::ZERO,
+            cum_realized_pnl: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_open_position_drift() {
+        let held = position(PositionSide::Long, 10, 100);
+        let open = StrategyState::PositionOpen;
+        assert!(detect(open, Some(&held), Some(&held)).is_none());
+        // A moving mark price is not drift
+        let marked = Position { current_price: Decimal::from(105), ..held.clone() };
+        assert!(detect(open, Some(&held), Some(&marked)).is_none());
+        // Nor an entry price rounded differently
+        let rounded = Position { entry_price: Decimal::from_str("100.005").unwrap(), ..held.clone() };
+        assert!(detect(open, Some(&held), Some(&rounded)).is_none());
+
+        // Partial close leftover
+        let drift = detect(open, Some(&held), Some(&position(PositionSide::Long, 4, 100))).unwrap();
+        assert_eq!(drift.to_string(), "BTCUSDT: strategy holds Long 10 @ 100, exchange reports Long 4 @ 100");
+        // Flipped or averaged in by hand
+        assert!(detect(open, Some(&held), Some(&position(PositionSide::Short, 10, 100))).is_some());
+        assert!(detect(open, Some(&held), Some(&position(PositionSide::Long, 10, 98))).is_some());
+
+        // Gone: the strategy's liquidation path, not drift
+        assert!(detect(open, Some(&held), None).is_none());
+    }
+
+    #[test]
+    fn test_position_while_flat_is_drift_but_not_while_waiting() {
+        let found = position(PositionSide::Short, 3, 50);
+        let drift = detect(StrategyState::Idle, None, Some(&found)).unwrap();
+        assert_eq!(drift.to_string(), "BTCUSDT: strategy holds nothing, exchange reports Short 3 @ 50");
+
+        for state in [StrategyState::OrderPending, StrategyState::ClosingPosition, StrategyState::SwitchingSymbol] {
+            assert!(detect(state, None, Some(&found)).is_none());
+        }
+    }
+}
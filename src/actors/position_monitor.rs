@@ -0,0 +1,466 @@
+//! Position Monitor
+//!
+//! Stop loss, take profit, trailing stop, breakeven and the liquidation guard
+//! run in their own task instead of the strategy's orderbook handler, so exits
+//! react to every price even while the strategy is busy with indicator work.
+//! The market-data actor writes the traded symbol's mid price into a `watch`
+//! cell, and the strategy arms the monitor with an `ExitPlan` while a position
+//! is open (and disarms it otherwise). A triggered exit goes straight to the
+//! execution priority lane; the strategy learns about it from
+//! `StrategyMessage::MonitorExit`.
+
+use crate::actors::execution::ExecutionSender;
+use crate::actors::messages::{ExecutionMessage, StrategyMessage};
+use crate::channel::MeteredSender;
+use crate::health::ActorHeartbeat;
+use crate::models::{ExitReason, Position, PositionSide, Symbol};
+use rust_decimal::Decimal;
+use tokio::sync::watch;
+use tokio::time::{interval, Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Heartbeat period (also bounds how long the loop sits idle without prices)
+pub const MONITOR_BEAT_SECS: u64 = 1;
+
+/// Minimum spacing of close attempts
+const CLOSE_RETRY_SECS: u64 = 2;
+
+/// A close that left the plan armed this long is presumed lost and sent again
+/// (longer than the default close ladder, so a slow close isn't doubled)
+const REFIRE_SECS: u64 = 30;
+
+/// Trailing stop for momentum trades: armed once the peak passes ACTIVATION,
+/// fires on a DISTANCE drop from the peak. Price moves, ROE = move × leverage
+/// (0.2% is ~2% ROE at 10x, wider gave back too much on scalps)
+const TRAILING_ACTIVATION: f64 = 0.3;
+pub const TRAILING_DISTANCE: f64 = 0.2;
+
+/// Breakeven: a trade that was ever above ARM never turns into a loss,
+/// it's closed once it falls back under FLOOR (covers fees)
+const BREAKEVEN_ARM: f64 = 0.5;
+const BREAKEVEN_FLOOR: f64 = 0.1;
+
+/// Latest mid price of the traded symbol
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkPrice {
+    pub symbol: Symbol,
+    pub price: Decimal,
+}
+
+/// What the monitor needs to manage the open position
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExitPlan {
+    pub symbol: Symbol,
+    pub side: PositionSide,
+    pub entry_price: Decimal,
+    pub leverage: Decimal,
+    pub liq_price: Option<Decimal>,
+    pub stop_loss_percent: f64,
+    pub take_profit_percent: f64,
+    /// Momentum trade: trailing stop instead of the fixed TP
+    pub trailing: bool,
+    /// SL/TP are the trade's dynamic targets rather than the config ones (logs only)
+    pub dynamic: bool,
+    /// Liquidation guard: close within this distance of the liquidation price, % (0 = off)
+    pub liq_buffer_percent: f64,
+}
+
+impl ExitPlan {
+    /// The planned position marked at `price`
+    fn position_at(&self, price: Decimal) -> Position {
+        Position {
+            symbol: self.symbol.clone(),
+            side: self.side,
+            size: Decimal::ZERO,
+            entry_price: self.entry_price,
+            current_price: price,
+            unrealized_pnl: Decimal::ZERO,
+            stop_loss: None,
+            leverage: self.leverage,
+            liq_price: self.liq_price,
+            realized_pnl: Decimal::ZERO,
+            cum_realized_pnl: Decimal::ZERO,
+        }
+    }
+
+    /// Same position as `other` (a re-arm rather than a new trade)
+    fn same_position(&self, other: &ExitPlan) -> bool {
+        self.symbol == other.symbol && self.side == other.side && self.entry_price == other.entry_price
+    }
+}
+
+/// Exit rules applied to the price stream of one position
+#[derive(Debug, Default)]
+struct ExitRules {
+    /// Best PnL seen, % (for trailing and breakeven)
+    peak_pnl_percent: f64,
+}
+
+impl ExitRules {
+    fn check(&mut self, plan: &ExitPlan, price: Decimal) -> Option<ExitReason> {
+        let position = plan.position_at(price);
+        let pnl_pct = position.pnl_percent();
+        let roe_pct = position.roe_percent();
+        let tag = if plan.dynamic { "[Dynamic]" } else { "[Static]" };
+
+        // ✅ LIQUIDATION GUARD: Independent of SL/TP - a wide SL at high leverage
+        // (or a gap through it) must never reach the exchange's liquidation engine
+        if let Some(distance) = position.liquidation_distance_percent() {
+            if plan.liq_buffer_percent > 0.0 && distance <= plan.liq_buffer_percent {
+                error!(
+                    "🚨 LIQUIDATION GUARD for {} | Price: {} | Liq: {} | Distance: {:.2}% (buffer {:.2}%) | ROE: {:.2}%",
+                    plan.symbol, price, plan.liq_price.unwrap_or_default(), distance,
+                    plan.liq_buffer_percent, roe_pct
+                );
+                return Some(ExitReason::LiquidationGuard);
+            }
+        }
+
+        if pnl_pct > self.peak_pnl_percent {
+            self.peak_pnl_percent = pnl_pct;
+        }
+        let peak = self.peak_pnl_percent;
+
+        if plan.trailing && peak > TRAILING_ACTIVATION {
+            let drop_from_peak = peak - pnl_pct;
+            if drop_from_peak >= TRAILING_DISTANCE {
+                info!(
+                    "📉 TRAILING STOP triggered for {} | Peak: {:.2}% | Now: {:.2}% (ROE {:.2}%) | Drop: {:.2}%",
+                    plan.symbol, peak, pnl_pct, roe_pct, drop_from_peak
+                );
+                return Some(ExitReason::TrailingStop);
+            }
+        }
+
+        if peak > BREAKEVEN_ARM && pnl_pct < BREAKEVEN_FLOOR {
+            info!(
+                "🛡️  BREAKEVEN PROTECT triggered for {} | Peak was: {:.2}% | Now: {:.2}% (ROE {:.2}%) | Securing profit!",
+                plan.symbol, peak, pnl_pct, roe_pct
+            );
+            return Some(ExitReason::Breakeven);
+        }
+
+        if pnl_pct <= -plan.stop_loss_percent {
+            warn!(
+                "🛑 STOP LOSS triggered for {} at {} (PnL: {:.2}% | Target: -{:.2}% {})",
+                plan.symbol, price, pnl_pct, plan.stop_loss_percent, tag
+            );
+            return Some(ExitReason::StopLoss);
+        }
+
+        // Momentum trades ignore the fixed TP and let the trailing stop take profit
+        if !plan.trailing && pnl_pct >= plan.take_profit_percent {
+            info!(
+                "💰 TAKE PROFIT hit for {} (PnL: {:.2}% | Target: {:.2}% {})",
+                plan.symbol, pnl_pct, plan.take_profit_percent, tag
+            );
+            return Some(ExitReason::TakeProfit);
+        }
+
+        None
+    }
+}
+
+/// PositionMonitor - exit checks on every price, off the strategy task
+pub struct PositionMonitor {
+    prices: watch::Receiver<Option<MarkPrice>>,
+    plans: watch::Receiver<Option<ExitPlan>>,
+    execution_tx: ExecutionSender,
+    strategy_tx: MeteredSender<StrategyMessage>,
+    heartbeat: ActorHeartbeat,
+
+    /// Armed plan (None = flat or the strategy is busy with the position)
+    plan: Option<ExitPlan>,
+    /// Position the rules' peak belongs to; kept while disarmed, so a re-arm continues it
+    tracked: Option<ExitPlan>,
+    rules: ExitRules,
+    /// Close sent for the armed plan; quiet until the strategy re-arms or REFIRE_SECS pass
+    fired: Option<Instant>,
+    last_close_attempt: Option<Instant>,
+}
+
+impl PositionMonitor {
+    pub fn new(
+        prices: watch::Receiver<Option<MarkPrice>>,
+        plans: watch::Receiver<Option<ExitPlan>>,
+        execution_tx: ExecutionSender,
+        strategy_tx: MeteredSender<StrategyMessage>,
+        heartbeat: ActorHeartbeat,
+    ) -> Self {
+        Self {
+            prices,
+            plans,
+            execution_tx,
+            strategy_tx,
+            heartbeat,
+            plan: None,
+            tracked: None,
+            rules: ExitRules::default(),
+            fired: None,
+            last_close_attempt: None,
+        }
+    }
+
+    pub async fn run(mut self) {
+        info!("🎯 PositionMonitor started");
+
+        let mut beat_interval = interval(Duration::from_secs(MONITOR_BEAT_SECS));
+
+        loop {
+            self.heartbeat.beat();
+            tokio::select! {
+                changed = self.plans.changed() => {
+                    if changed.is_err() {
+                        info!("PositionMonitor plan channel closed, shutting down");
+                        break;
+                    }
+                    let plan = self.plans.borrow_and_update().clone();
+                    self.arm(plan);
+                    // The position may already be past a level at the last known price
+                    let price = self.prices.borrow().clone();
+                    if let Some(price) = price {
+                        self.on_price(price).await;
+                    }
+                }
+
+                changed = self.prices.changed() => {
+                    if changed.is_err() {
+                        info!("PositionMonitor price feed closed, shutting down");
+                        break;
+                    }
+                    let price = self.prices.borrow_and_update().clone();
+                    if let Some(price) = price {
+                        self.on_price(price).await;
+                    }
+                }
+
+                _ = beat_interval.tick() => {}
+            }
+        }
+    }
+
+    fn arm(&mut self, plan: Option<ExitPlan>) {
+        if let Some(ref new) = plan {
+            // Re-armed after a failed close: keep the peak for trailing/breakeven
+            if !self.tracked.as_ref().is_some_and(|old| old.same_position(new)) {
+                debug!("🎯 Monitoring {} {:?} from {}", new.symbol, new.side, new.entry_price);
+                self.rules = ExitRules::default();
+            }
+            self.tracked = Some(new.clone());
+            self.fired = None;
+        }
+        self.plan = plan;
+    }
+
+    async fn on_price(&mut self, mark: MarkPrice) {
+        let Some(ref plan) = self.plan else {
+            return;
+        };
+        if plan.symbol != mark.symbol {
+            return;
+        }
+        if self.fired.is_some_and(|at| at.elapsed() < Duration::from_secs(REFIRE_SECS)) {
+            return;
+        }
+        let Some(reason) = self.rules.check(plan, mark.price) else {
+            return;
+        };
+
+        // ✅ FIX RATE LIMIT: Don't spam close requests
+        if self.last_close_attempt.is_some_and(|t| t.elapsed() < Duration::from_secs(CLOSE_RETRY_SECS)) {
+            debug!("⏳ Rate limit: Close attempt throttled (< {}s since last)", CLOSE_RETRY_SECS);
+            return;
+        }
+        self.last_close_attempt = Some(Instant::now());
+        if self.fired.is_some() {
+            warn!("⚠️ {} close for {} not confirmed after {}s, sending it again", reason, plan.symbol, REFIRE_SECS);
+        }
+
+        let symbol = plan.symbol.clone();
+        let send_result = tokio::time::timeout(
+            Duration::from_secs(5),
+            self.execution_tx.send(ExecutionMessage::ClosePosition {
+                symbol: symbol.clone(),
+                position_side: plan.side,
+                reason,
+            }),
+        )
+        .await;
+
+        match send_result {
+            Ok(Ok(_)) => {
+                self.fired = Some(Instant::now());
+                let exit = StrategyMessage::MonitorExit { symbol, reason, price: mark.price };
+                if let Err(e) = self.strategy_tx.send(exit).await {
+                    warn!("Failed to report {} to the strategy: {}", reason, e);
+                }
+            }
+            Ok(Err(e)) => {
+                warn!("Failed to send ClosePosition for {}: {}", reason, e);
+            }
+            Err(_) => {
+                warn!("⚠️  CRITICAL: ExecutionActor timeout on {}! Retrying on the next price.", reason);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::{metered_channel, OverflowPolicy};
+    use crate::health::HealthRegistry;
+    use crate::metrics::Metrics;
+
+    fn plan(trailing: bool) -> ExitPlan {
+        ExitPlan {
+            symbol: Symbol::from("BTCUSDT"),
+            side: PositionSide::Long,
+            entry_price: Decimal::from(100),
+            leverage: Decimal::from(10),
+            liq_price: Some(Decimal::from(91)),
+            stop_loss_percent: 1.0,
+            take_profit_percent: 2.0,
+            trailing,
+            dynamic: false,
+            liq_buffer_percent: 2.0,
+        }
+    }
+
+    fn price(p: &str) -> Decimal {
+        p.parse().unwrap()
+    }
+
+    #[test]
+    fn test_exit_rules() {
+        let mut rules = ExitRules::default();
+        assert_eq!(rules.check(&plan(false), price("100.5")), None);
+        assert_eq!(rules.check(&plan(false), price("102")), Some(ExitReason::TakeProfit));
+
+        let mut rules = ExitRules::default();
+        assert_eq!(rules.check(&plan(false), price("99")), Some(ExitReason::StopLoss));
+
+        // Momentum: no fixed TP, trailing fires 0.2% below the peak
+        let mut rules = ExitRules::default();
+        assert_eq!(rules.check(&plan(true), price("102")), None);
+        assert_eq!(rules.check(&plan(true), price("101.85")), None);
+        assert_eq!(rules.check(&plan(true), price("101.7")), Some(ExitReason::TrailingStop));
+
+        // Breakeven: was above +0.5%, back under +0.1%
+        let mut rules = ExitRules::default();
+        assert_eq!(rules.check(&plan(false), price("100.6")), None);
+        assert_eq!(rules.check(&plan(false), price("100.05")), Some(ExitReason::Breakeven));
+
+        // Liquidation guard comes first, whatever the SL says
+        let mut wide = plan(false);
+        wide.stop_loss_percent = 20.0;
+        let mut rules = ExitRules::default();
+        assert_eq!(rules.check(&wide, price("92.5")), Some(ExitReason::LiquidationGuard));
+    }
+
+    #[tokio::test]
+    async fn test_monitor_closes_and_reports_once() {
+        let metrics = Metrics::new();
+        let (normal_tx, _normal_rx) = metered_channel("execution", 10, OverflowPolicy::Block { timeout: None }, &metrics);
+        let (priority_tx, mut priority_rx) =
+            metered_channel("execution_priority", 10, OverflowPolicy::Block { timeout: None }, &metrics);
+        let (strategy_tx, mut strategy_rx) = metered_channel("strategy", 10, OverflowPolicy::Block { timeout: None }, &metrics);
+        let (price_tx, price_rx) = watch::channel(None);
+        let (plan_tx, plan_rx) = watch::channel(None);
+
+        let monitor = PositionMonitor::new(
+            price_rx,
+            plan_rx,
+            ExecutionSender::new(normal_tx, priority_tx),
+            strategy_tx,
+            HealthRegistry::new().register("position_monitor", Duration::from_secs(MONITOR_BEAT_SECS)),
+        );
+        let handle = tokio::spawn(monitor.run());
+
+        let symbol = Symbol::from("BTCUSDT");
+        plan_tx.send_replace(Some(plan(false)));
+        price_tx.send_replace(Some(MarkPrice { symbol: symbol.clone(), price: price("98.9") }));
+
+        match priority_rx.recv().await.map(|traced| traced.msg) {
+            Some(ExecutionMessage::ClosePosition { reason, .. }) => assert_eq!(reason, ExitReason::StopLoss),
+            other => panic!("expected ClosePosition, got {:?}", other),
+        }
+        match strategy_rx.recv().await {
+            Some(StrategyMessage::MonitorExit { reason, price: at, .. }) => {
+                assert_eq!(reason, ExitReason::StopLoss);
+                assert_eq!(at, price("98.9"));
+            }
+            other => panic!("expected MonitorExit, got {:?}", other),
+        }
+
+        // Fired once: further prices don't repeat the close until re-armed
+        price_tx.send_replace(Some(MarkPrice { symbol, price: price("98.5") }));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(priority_rx.try_recv().is_none());
+
+        drop(plan_tx);
+        handle.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_monitor_fires_again_when_the_close_is_lost() {
+        let metrics = Metrics::new();
+        let policy = OverflowPolicy::Block { timeout: None };
+        let (normal_tx, _normal_rx) = metered_channel("execution", 10, policy, &metrics);
+        let (priority_tx, mut priority_rx) = metered_channel("execution_priority", 10, policy, &metrics);
+        let (strategy_tx, _strategy_rx) = metered_channel("strategy", 10, policy, &metrics);
+        let (_price_tx, price_rx) = watch::channel(None);
+        let (_plan_tx, plan_rx) = watch::channel(None);
+        let mut monitor = PositionMonitor::new(
+            price_rx,
+            plan_rx,
+            ExecutionSender::new(normal_tx, priority_tx),
+            strategy_tx,
+            HealthRegistry::new().register("position_monitor", Duration::from_secs(MONITOR_BEAT_SECS)),
+        );
+        let mark = MarkPrice { symbol: Symbol::from("BTCUSDT"), price: price("98.9") };
+
+        monitor.arm(Some(plan(false)));
+        monitor.on_price(mark.clone()).await;
+        assert!(priority_rx.try_recv().is_some());
+
+        // The strategy never answered: quiet for a while, then the close goes out again
+        tokio::time::advance(Duration::from_secs(REFIRE_SECS - 1)).await;
+        monitor.on_price(mark.clone()).await;
+        assert!(priority_rx.try_recv().is_none());
+        tokio::time::advance(Duration::from_secs(1)).await;
+        monitor.on_price(mark).await;
+        assert!(priority_rx.try_recv().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rearming_the_same_position_keeps_the_peak() {
+        let metrics = Metrics::new();
+        let policy = OverflowPolicy::Block { timeout: None };
+        let (normal_tx, _normal_rx) = metered_channel("execution", 10, policy, &metrics);
+        let (priority_tx, _priority_rx) = metered_channel("execution_priority", 10, policy, &metrics);
+        let (strategy_tx, _strategy_rx) = metered_channel("strategy", 10, policy, &metrics);
+        let (_price_tx, price_rx) = watch::channel(None);
+        let (_plan_tx, plan_rx) = watch::channel(None);
+        let mut monitor = PositionMonitor::new(
+            price_rx,
+            plan_rx,
+            ExecutionSender::new(normal_tx, priority_tx),
+            strategy_tx,
+            HealthRegistry::new().register("position_monitor", Duration::from_secs(MONITOR_BEAT_SECS)),
+        );
+
+        monitor.arm(Some(plan(true)));
+        assert_eq!(monitor.rules.check(&plan(true), price("102")), None);
+        // Disarmed while a close was tried, then re-armed after it failed
+        monitor.arm(None);
+        monitor.arm(Some(plan(true)));
+        assert_eq!(monitor.rules.check(&plan(true), price("101.7")), Some(ExitReason::TrailingStop));
+
+        // A new position starts from scratch
+        let mut other = plan(true);
+        other.entry_price = Decimal::from(101);
+        monitor.arm(Some(other.clone()));
+        assert_eq!(monitor.rules.check(&other, price("101.7")), None);
+    }
+}
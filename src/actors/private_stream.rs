@@ -0,0 +1,620 @@
+//! Private WebSocket Stream
+//!
+//! Authenticated connection to Bybit's private stream (`position`, `order`,
+//! `execution`, `wallet`). It runs as its own task with its own reconnect
+//! loop, so an auth failure or a dropped private socket never touches the
+//! public market-data feed. Updates land in `PrivateState`, which other
+//! actors read instead of polling REST; orders reaching a final status are
+//! also announced (`subscribe_finished`), so execution can stop waiting for
+//! its next status poll. After every (re)connect the state is resynced from
+//! REST, so positions and orders that changed while the socket was down don't
+//! stay stale until their next push.
+
+use crate::actors::websocket::{jitter, ReconnectBackoff, HEALTHY_CONNECTION};
+use crate::config::Config;
+use crate::exchange::{BybitClient, FaultScenario, OrderStatusResponse, PositionInfo, WalletBalance};
+use crate::health::ActorHeartbeat;
+use anyhow::{bail, Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use hmac::{Hmac, Mac};
+use parking_lot::RwLock;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::time::{interval, timeout, Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, error, info, warn};
+
+/// Bybit drops idle private connections after 10 minutes; the docs ask for a ping every 20s
+pub const PRIVATE_PING_INTERVAL_SECS: u64 = 20;
+
+/// Auth signature validity
+const AUTH_EXPIRES_MS: i64 = 10_000;
+
+/// Time allowed for the auth reply
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Recent orders/executions kept in `PrivateState`
+const MAX_RECENT: usize = 200;
+
+/// Final-status announcements a slow subscriber may fall behind by
+const FINISHED_CAPACITY: usize = 64;
+
+/// Order statuses after which nothing changes anymore
+const FINAL_STATUSES: [&str; 5] = ["Filled", "Cancelled", "Rejected", "PartiallyFilledCanceled", "Deactivated"];
+
+const TOPICS: [&str; 4] = ["position.linear", "order.linear", "execution.linear", "wallet"];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivatePosition {
+    pub symbol: String,
+    /// "Buy", "Sell" or "" when flat
+    pub side: String,
+    pub size: String,
+    #[serde(default)]
+    pub entry_price: String,
+    #[serde(default)]
+    pub mark_price: String,
+    #[serde(default)]
+    pub leverage: String,
+    #[serde(default)]
+    pub liq_price: String,
+    #[serde(default)]
+    pub unrealised_pnl: String,
+    #[serde(default)]
+    pub cur_realised_pnl: String,
+    #[serde(default)]
+    pub updated_time: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivateOrder {
+    pub order_id: String,
+    #[serde(default)]
+    pub order_link_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub order_status: String,
+    #[serde(default)]
+    pub qty: String,
+    #[serde(default)]
+    pub cum_exec_qty: String,
+    #[serde(default)]
+    pub avg_price: String,
+    #[serde(default)]
+    pub reject_reason: String,
+    #[serde(default)]
+    pub updated_time: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivateExecution {
+    pub exec_id: String,
+    pub order_id: String,
+    #[serde(default)]
+    pub order_link_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub exec_price: String,
+    pub exec_qty: String,
+    #[serde(default)]
+    pub exec_fee: String,
+    #[serde(default)]
+    pub exec_type: String,
+    #[serde(default)]
+    pub exec_time: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivateWallet {
+    #[serde(default)]
+    pub account_type: String,
+    #[serde(default)]
+    pub total_equity: String,
+    #[serde(default)]
+    pub total_wallet_balance: String,
+    #[serde(default)]
+    pub total_margin_balance: String,
+    #[serde(default)]
+    pub total_available_balance: String,
+    /// Initial margin rate of the account
+    #[serde(rename = "accountIMRate", default)]
+    pub account_im_rate: String,
+    /// Maintenance margin rate (liquidation at 1.0)
+    #[serde(rename = "accountMMRate", default)]
+    pub account_mm_rate: String,
+}
+
+impl From<PositionInfo> for PrivatePosition {
+    fn from(pos: PositionInfo) -> Self {
+        Self {
+            symbol: pos.symbol,
+            side: pos.side,
+            size: pos.size,
+            entry_price: pos.avg_price,
+            mark_price: String::new(),
+            leverage: pos.leverage,
+            liq_price: pos.liq_price,
+            unrealised_pnl: pos.unrealised_pnl,
+            cur_realised_pnl: pos.cur_realised_pnl,
+            updated_time: String::new(),
+        }
+    }
+}
+
+impl From<OrderStatusResponse> for PrivateOrder {
+    fn from(order: OrderStatusResponse) -> Self {
+        Self {
+            order_id: order.order_id,
+            order_link_id: order.order_link_id,
+            symbol: order.symbol,
+            side: order.side,
+            order_status: order.order_status,
+            qty: order.qty,
+            cum_exec_qty: order.cum_exec_qty,
+            avg_price: order.avg_price,
+            reject_reason: String::new(),
+            updated_time: order.updated_time,
+        }
+    }
+}
+
+impl From<WalletBalance> for PrivateWallet {
+    fn from(wallet: WalletBalance) -> Self {
+        Self {
+            account_type: wallet.account_type,
+            total_equity: wallet.total_equity,
+            total_margin_balance: String::new(),
+            total_wallet_balance: wallet.total_wallet_balance,
+            total_available_balance: wallet.total_available_balance,
+            account_im_rate: wallet.account_im_rate,
+            account_mm_rate: wallet.account_mm_rate,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PrivateFrame {
+    topic: Option<String>,
+    op: Option<String>,
+    success: Option<bool>,
+    #[serde(default)]
+    ret_msg: String,
+    data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    /// By symbol; flat positions are removed
+    positions: HashMap<String, PrivatePosition>,
+    wallet: Option<PrivateWallet>,
+    recent_orders: VecDeque<PrivateOrder>,
+    recent_executions: VecDeque<PrivateExecution>,
+    last_update: Option<Instant>,
+}
+
+/// Latest account state pushed by the private stream (cheap to clone, shared)
+#[derive(Clone)]
+pub struct PrivateState {
+    inner: Arc<RwLock<Snapshot>>,
+    /// IDs of orders as they reach a final status
+    finished: broadcast::Sender<String>,
+}
+
+impl Default for PrivateState {
+    fn default() -> Self {
+        Self {
+            inner: Arc::default(),
+            finished: broadcast::channel(FINISHED_CAPACITY).0,
+        }
+    }
+}
+
+impl PrivateState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// IDs of orders the stream reports filled, cancelled or rejected from now on
+    pub fn subscribe_finished(&self) -> broadcast::Receiver<String> {
+        self.finished.subscribe()
+    }
+
+    pub fn position(&self, symbol: &str) -> Option<PrivatePosition> {
+        self.inner.read().positions.get(symbol).cloned()
+    }
+
+    pub fn positions(&self) -> Vec<PrivatePosition> {
+        self.inner.read().positions.values().cloned().collect()
+    }
+
+    pub fn wallet(&self) -> Option<PrivateWallet> {
+        self.inner.read().wallet.clone()
+    }
+
+    /// Latest update for an order, newest first
+    pub fn order(&self, order_id: &str) -> Option<PrivateOrder> {
+        self.inner
+            .read()
+            .recent_orders
+            .iter()
+            .rev()
+            .find(|o| o.order_id == order_id)
+            .cloned()
+    }
+
+    /// Executions (fills) of an order, oldest first
+    pub fn executions(&self, order_id: &str) -> Vec<PrivateExecution> {
+        self.inner
+            .read()
+            .recent_executions
+            .iter()
+            .filter(|e| e.order_id == order_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Time since the last private update (None = nothing received yet)
+    pub fn last_update_age(&self) -> Option<Duration> {
+        self.inner.read().last_update.map(|t| t.elapsed())
+    }
+
+    /// Apply one topic frame; returns false for frames without a known topic
+    fn apply(&self, topic: &str, data: serde_json::Value) -> Result<bool> {
+        let mut snapshot = self.inner.write();
+        match topic {
+            t if t.starts_with("position") => {
+                for pos in serde_json::from_value::<Vec<PrivatePosition>>(data)? {
+                    if pos.size.parse::<f64>().unwrap_or(0.0) == 0.0 {
+                        snapshot.positions.remove(&pos.symbol);
+                    } else {
+                        snapshot.positions.insert(pos.symbol.clone(), pos);
+                    }
+                }
+            }
+            t if t.starts_with("order") => {
+                for order in serde_json::from_value::<Vec<PrivateOrder>>(data)? {
+                    self.push_order(&mut snapshot, order);
+                }
+            }
+            t if t.starts_with("execution") => {
+                for exec in serde_json::from_value::<Vec<PrivateExecution>>(data)? {
+                    push_bounded(&mut snapshot.recent_executions, exec);
+                }
+            }
+            "wallet" => {
+                if let Some(wallet) = serde_json::from_value::<Vec<PrivateWallet>>(data)?.into_iter().next() {
+                    snapshot.wallet = Some(wallet);
+                }
+            }
+            _ => return Ok(false),
+        }
+        snapshot.last_update = Some(Instant::now());
+        Ok(true)
+    }
+
+    /// Take a REST snapshot after a (re)connect: `positions` replaces the held ones,
+    /// `orders` (oldest first) land like pushed updates. None = that part couldn't be fetched
+    fn resync(&self, positions: Option<Vec<PrivatePosition>>, orders: Vec<PrivateOrder>, wallet: Option<PrivateWallet>) {
+        let mut snapshot = self.inner.write();
+        if let Some(positions) = positions {
+            snapshot.positions = positions
+                .into_iter()
+                .filter(|pos| pos.size.parse::<f64>().unwrap_or(0.0) != 0.0)
+                .map(|pos| (pos.symbol.clone(), pos))
+                .collect();
+        }
+        for order in orders {
+            self.push_order(&mut snapshot, order);
+        }
+        if wallet.is_some() {
+            snapshot.wallet = wallet;
+        }
+    }
+
+    fn push_order(&self, snapshot: &mut Snapshot, order: PrivateOrder) {
+        if FINAL_STATUSES.contains(&order.order_status.as_str()) {
+            // Nobody waiting is fine
+            let _ = self.finished.send(order.order_id.clone());
+        }
+        push_bounded(&mut snapshot.recent_orders, order);
+    }
+}
+
+fn push_bounded<T>(queue: &mut VecDeque<T>, item: T) {
+    if queue.len() >= MAX_RECENT {
+        queue.pop_front();
+    }
+    queue.push_back(item);
+}
+
+/// `hex(HMAC_SHA256(secret, "GET/realtime" + expires))`
+pub fn auth_signature(api_secret: &str, expires: i64) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(api_secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("GET/realtime{}", expires).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// PrivateStreamActor - authenticated account stream, independent of the public feed
+pub struct PrivateStreamActor {
+    ws_url: String,
+    api_key: String,
+    api_secret: String,
+    /// REST snapshot after each (re)connect
+    client: BybitClient,
+    state: PrivateState,
+    /// ✅ CHAOS: Simulated delivery latency (FAULT_SCENARIO ws_latency_ms)
+    fault_scenario: Option<FaultScenario>,
+    heartbeat: ActorHeartbeat,
+}
+
+impl PrivateStreamActor {
+    pub fn new(config: Arc<Config>, client: BybitClient, state: PrivateState, heartbeat: ActorHeartbeat) -> Self {
+        Self {
+            ws_url: config.private_ws_url(),
+            api_key: config.bybit_api_key.clone(),
+            api_secret: config.bybit_api_secret.clone(),
+            client,
+            state,
+            fault_scenario: config.fault_scenario,
+            heartbeat,
+        }
+    }
+
+    pub async fn run(self) {
+        info!("🔐 PrivateStreamActor started");
+
+        let mut backoff = ReconnectBackoff::default();
+        // Wall clock (ms) of the last drop: orders that finished since then are fetched on reconnect
+        let mut dropped_at = None;
+
+        loop {
+            self.heartbeat.beat();
+            let connected_at = Instant::now();
+            let result = self.connect_and_stream(dropped_at).await;
+            dropped_at = Some(chrono::Utc::now().timestamp_millis());
+
+            if connected_at.elapsed() >= HEALTHY_CONNECTION {
+                backoff.reset();
+            }
+            let delay = backoff.next_delay(jitter());
+
+            match result {
+                Ok(_) => warn!("⚠️  Private WebSocket closed, reconnecting in {:.1}s...", delay.as_secs_f64()),
+                Err(e) => error!("Private WebSocket error: {}. Reconnecting in {:.1}s...", e, delay.as_secs_f64()),
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn connect_and_stream(&self, dropped_at: Option<i64>) -> Result<()> {
+        let (ws_stream, _) = connect_async(&self.ws_url)
+            .await
+            .context("Failed to connect to private WebSocket")?;
+        let (mut write, mut read) = ws_stream.split();
+
+        // Auth must succeed before any subscription is accepted
+        let expires = chrono::Utc::now().timestamp_millis() + AUTH_EXPIRES_MS;
+        let auth = json!({
+            "op": "auth",
+            "args": [self.api_key, expires, auth_signature(&self.api_secret, expires)],
+        });
+        write.send(Message::Text(auth.to_string())).await?;
+
+        let authed = timeout(AUTH_TIMEOUT, async {
+            while let Some(msg) = read.next().await {
+                if let Message::Text(text) = msg? {
+                    let frame: PrivateFrame = serde_json::from_str(&text)?;
+                    if frame.op.as_deref() == Some("auth") {
+                        if frame.success == Some(true) {
+                            return Ok(());
+                        }
+                        bail!("auth rejected: {}", frame.ret_msg);
+                    }
+                }
+            }
+            bail!("connection closed during auth")
+        })
+        .await
+        .context("auth timed out")?;
+        authed?;
+
+        let subscribe = json!({ "op": "subscribe", "args": TOPICS });
+        write.send(Message::Text(subscribe.to_string())).await?;
+        info!("✅ Private WebSocket authenticated, subscribed to {}", TOPICS.join(", "));
+        // Subscribed first: pushes from here on are read after the snapshot and win over it
+        self.resync(dropped_at).await;
+
+        let mut ping_interval = interval(Duration::from_secs(PRIVATE_PING_INTERVAL_SECS));
+        let mut delayed = self.fault_scenario.and_then(|scenario| scenario.ws_delay::<String>());
+
+        loop {
+            self.heartbeat.beat();
+            let delayed_due = delayed.as_ref().and_then(|line| line.next_due());
+            tokio::select! {
+                msg = read.next() => {
+                    match msg {
+                        Some(Ok(Message::Text(text))) => match delayed.as_mut() {
+                            Some(line) => line.push(text),
+                            None => {
+                                if let Err(e) = self.handle_message(&text) {
+                                    warn!("Failed to handle private message: {}", e);
+                                }
+                            }
+                        },
+                        Some(Ok(Message::Close(_))) | None => {
+                            info!("Private WebSocket closed by server");
+                            return Ok(());
+                        }
+                        Some(Err(e)) => bail!("read error: {}", e),
+                        Some(Ok(_)) => {}
+                    }
+                }
+
+                _ = tokio::time::sleep_until(delayed_due.unwrap_or_else(Instant::now)), if delayed_due.is_some() => {
+                    while let Some(text) = delayed.as_mut().and_then(|line| line.pop_due()) {
+                        if let Err(e) = self.handle_message(&text) {
+                            warn!("Failed to handle private message: {}", e);
+                        }
+                    }
+                }
+
+                _ = ping_interval.tick() => {
+                    write.send(Message::Text(json!({ "op": "ping" }).to_string())).await?;
+                }
+            }
+        }
+    }
+
+    /// Positions, open orders (plus those finished since `dropped_at`) and the wallet from REST
+    async fn resync(&self, dropped_at: Option<i64>) {
+        let positions = match self.client.get_open_positions().await {
+            Ok(positions) => Some(positions.into_iter().map(PrivatePosition::from).collect()),
+            Err(e) => {
+                warn!("🔐 Position resync failed, positions may be stale until their next update: {}", e);
+                None
+            }
+        };
+
+        let mut orders = Vec::new();
+        if let Some(since) = dropped_at {
+            match self.client.get_order_history(since).await {
+                // Newest first
+                Ok(finished) => orders.extend(finished.into_iter().rev().map(PrivateOrder::from)),
+                Err(e) => warn!("🔐 Order history resync failed: {}", e),
+            }
+        }
+        match self.client.get_open_orders().await {
+            Ok(open) => orders.extend(open.into_iter().map(PrivateOrder::from)),
+            Err(e) => warn!("🔐 Open order resync failed: {}", e),
+        }
+
+        let wallet = match self.client.get_wallet_balance().await {
+            Ok(wallet) => Some(PrivateWallet::from(wallet)),
+            Err(e) => {
+                warn!("🔐 Wallet resync failed: {}", e);
+                None
+            }
+        };
+
+        let held = positions.as_ref().map(Vec::len);
+        self.state.resync(positions, orders, wallet);
+        info!("🔐 Private state resynced from REST ({:?} positions held)", held);
+    }
+
+    fn handle_message(&self, text: &str) -> Result<()> {
+        let frame: PrivateFrame = serde_json::from_str(text)?;
+        match (frame.topic, frame.data) {
+            (Some(topic), Some(data)) => {
+                if self.state.apply(&topic, data)? {
+                    debug!("🔐 Private update: {}", topic);
+                }
+            }
+            _ => {
+                if frame.op.as_deref() == Some("subscribe") && frame.success == Some(false) {
+                    error!("Private subscribe failed: {}", frame.ret_msg);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_signature_is_hex_hmac() {
+        let sig = auth_signature("secret", 1_700_000_010_000);
+        assert_eq!(sig.len(), 64);
+        assert_eq!(sig, auth_signature("secret", 1_700_000_010_000));
+        assert_ne!(sig, auth_signature("secret", 1_700_000_010_001));
+    }
+
+    #[test]
+    fn test_state_tracks_positions_orders_and_wallet() {
+        let state = PrivateState::new();
+        let open = serde_json::json!([{
+            "symbol": "BTCUSDT", "side": "Buy", "size": "0.01", "entryPrice": "50000",
+            "markPrice": "50100", "leverage": "10", "liqPrice": "45000", "unrealisedPnl": "1"
+        }]);
+        assert!(state.apply("position.linear", open).unwrap());
+        assert_eq!(state.position("BTCUSDT").unwrap().size, "0.01");
+
+        let flat = serde_json::json!([{ "symbol": "BTCUSDT", "side": "", "size": "0" }]);
+        state.apply("position.linear", flat).unwrap();
+        assert!(state.position("BTCUSDT").is_none());
+
+        let mut finished = state.subscribe_finished();
+        let working = serde_json::json!([{
+            "orderId": "abc", "symbol": "BTCUSDT", "side": "Buy", "orderStatus": "PartiallyFilled"
+        }]);
+        state.apply("order.linear", working).unwrap();
+        let order = serde_json::json!([{
+            "orderId": "abc", "symbol": "BTCUSDT", "side": "Buy", "orderStatus": "Filled"
+        }]);
+        state.apply("order.linear", order).unwrap();
+        assert_eq!(state.order("abc").unwrap().order_status, "Filled");
+        // Only the final status is announced
+        assert_eq!(finished.try_recv().unwrap(), "abc");
+        assert!(finished.try_recv().is_err());
+
+        let wallet = serde_json::json!([{ "accountType": "UNIFIED", "totalEquity": "1000", "accountMMRate": "0.01" }]);
+        state.apply("wallet", wallet).unwrap();
+        assert_eq!(state.wallet().unwrap().account_mm_rate, "0.01");
+
+        assert!(!state.apply("greeks", serde_json::json!([])).unwrap());
+    }
+
+    #[test]
+    fn test_resync_replaces_positions_and_announces_missed_fills() {
+        let state = PrivateState::new();
+        let held = serde_json::json!([
+            { "symbol": "BTCUSDT", "side": "Buy", "size": "0.01" },
+            { "symbol": "ETHUSDT", "side": "Sell", "size": "0.5" }
+        ]);
+        state.apply("position.linear", held).unwrap();
+        let mut finished = state.subscribe_finished();
+
+        // ETH was closed and SOL opened while the socket was down
+        let position = |symbol: &str, size: &str| PrivatePosition {
+            symbol: symbol.to_string(),
+            side: "Buy".to_string(),
+            size: size.to_string(),
+            entry_price: String::new(),
+            mark_price: String::new(),
+            leverage: String::new(),
+            liq_price: String::new(),
+            unrealised_pnl: String::new(),
+            cur_realised_pnl: String::new(),
+            updated_time: String::new(),
+        };
+        let filled: PrivateOrder = serde_json::from_value(serde_json::json!({
+            "orderId": "abc", "symbol": "ETHUSDT", "side": "Buy", "orderStatus": "Filled"
+        }))
+        .unwrap();
+        state.resync(
+            Some(vec![position("BTCUSDT", "0.01"), position("ETHUSDT", "0"), position("SOLUSDT", "3")]),
+            vec![filled],
+            None,
+        );
+
+        assert!(state.position("ETHUSDT").is_none());
+        assert_eq!(state.position("SOLUSDT").unwrap().size, "3");
+        assert_eq!(state.positions().len(), 2);
+        assert_eq!(state.order("abc").unwrap().order_status, "Filled");
+        assert_eq!(finished.try_recv().unwrap(), "abc");
+
+        // A failed position query leaves the held ones alone
+        state.resync(None, Vec::new(), None);
+        assert_eq!(state.positions().len(), 2);
+    }
+}
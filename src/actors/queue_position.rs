@@ -0,0 +1,259 @@
+//! Queue Position of Resting Entries
+//!
+//! A PostOnly entry joins the back of the queue at its price. Its place is
+//! estimated from the book: the visible size at our level when it went in is
+//! ahead of us, cancels can only shrink that (never below what is still
+//! shown), and taker trades hitting our level eat it away. A trade through our
+//! price means the queue is gone. From that, each status poll decides:
+//!
+//! - the touch moved away from our price: chase it (resting behind the market
+//!   only fills when price comes back against the signal);
+//! - after `QUEUE_MIN_WAIT`, the queue drains too slowly to reach us before
+//!   the order times out: step one tick inside the spread if there is room;
+//! - otherwise keep our place.
+//!
+//! `ExecutionActor` reprices an entry (cancel, verify, place anew) up to
+//! `ENTRY_MAX_REPRICES` times; the market data actor feeds books and trades
+//! of symbols with a tracked order into the shared `QueueBoard`.
+
+use crate::models::{OrderBookSnapshot, OrderSide, Symbol, TradeSide, TradeTick};
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+/// Time in the queue before its drain rate is trusted
+pub const QUEUE_MIN_WAIT: Duration = Duration::from_secs(2);
+
+/// What to do with the resting order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueCall {
+    Wait,
+    /// Cancel and re-place at this price
+    Reprice(Decimal),
+}
+
+/// Estimated place of one resting order
+#[derive(Debug, Clone)]
+pub struct QueueEstimate {
+    side: OrderSide,
+    price: Decimal,
+    qty: Decimal,
+    placed_at: Instant,
+    /// Visible size ahead of us (None until the first book after placement)
+    ahead: Option<Decimal>,
+    /// Taker volume that traded at our level since placement
+    traded: Decimal,
+    book: Option<OrderBookSnapshot>,
+}
+
+impl QueueEstimate {
+    pub fn new(side: OrderSide, price: Decimal, qty: Decimal, placed_at: Instant) -> Self {
+        Self {
+            side,
+            price,
+            qty,
+            placed_at,
+            ahead: None,
+            traded: Decimal::ZERO,
+            book: None,
+        }
+    }
+
+    pub fn ahead(&self) -> Option<Decimal> {
+        self.ahead
+    }
+
+    /// Shown size at our level, less our own order, caps what can be ahead
+    pub fn on_book(&mut self, book: &OrderBookSnapshot) {
+        let levels = match self.side {
+            OrderSide::Buy => &book.bids,
+            OrderSide::Sell => &book.asks,
+        };
+        let shown = levels
+            .iter()
+            .find(|(price, _)| *price == self.price)
+            .map_or(Decimal::ZERO, |(_, size)| *size);
+        let others = (shown - self.qty).max(Decimal::ZERO);
+        self.ahead = Some(self.ahead.map_or(others, |ahead| ahead.min(others)));
+        self.book = Some(book.clone());
+    }
+
+    /// Takers selling into our bid (buying from our ask) work through the queue
+    pub fn on_trade(&mut self, tick: &TradeTick) {
+        let hits_us = match self.side {
+            OrderSide::Buy => tick.side == TradeSide::Sell && tick.price <= self.price,
+            OrderSide::Sell => tick.side == TradeSide::Buy && tick.price >= self.price,
+        };
+        if !hits_us {
+            return;
+        }
+        if tick.price == self.price {
+            self.traded += tick.size;
+            self.ahead = self.ahead.map(|ahead| (ahead - tick.size).max(Decimal::ZERO));
+        } else {
+            // Traded through: everything at our price went first
+            self.ahead = Some(Decimal::ZERO);
+        }
+    }
+
+    /// Reprice or wait, with the order cancelled at `deadline` if still resting
+    pub fn assess(&self, tick_size: Option<Decimal>, now: Instant, deadline: Instant) -> QueueCall {
+        let (Some(book), Some(ahead)) = (&self.book, self.ahead) else {
+            return QueueCall::Wait;
+        };
+        let (touch, away) = match self.side {
+            OrderSide::Buy => (book.best_bid, book.best_bid > self.price),
+            OrderSide::Sell => (book.best_ask, book.best_ask < self.price),
+        };
+        if away {
+            return QueueCall::Reprice(touch);
+        }
+
+        let waited = now.duration_since(self.placed_at);
+        if ahead.is_zero() || waited < QUEUE_MIN_WAIT {
+            return QueueCall::Wait;
+        }
+        // Volume per second through our level; none at all = the queue isn't moving
+        let rate = self.traded / Decimal::from_f64_retain(waited.as_secs_f64()).unwrap_or(Decimal::ONE);
+        let left = Decimal::from_f64_retain(deadline.saturating_duration_since(now).as_secs_f64()).unwrap_or_default();
+        if !rate.is_zero() && ahead / rate <= left {
+            return QueueCall::Wait;
+        }
+
+        let Some(tick) = tick_size.filter(|tick| *tick > Decimal::ZERO) else {
+            return QueueCall::Wait;
+        };
+        let inside = match self.side {
+            OrderSide::Buy => self.price + tick,
+            OrderSide::Sell => self.price - tick,
+        };
+        // One tick inside must stay a maker price
+        if inside > book.best_bid && inside < book.best_ask {
+            QueueCall::Reprice(inside)
+        } else {
+            QueueCall::Wait
+        }
+    }
+}
+
+/// Resting entries being tracked, by symbol (cheap to clone, shared)
+#[derive(Clone, Default)]
+pub struct QueueBoard {
+    tracked: Arc<Mutex<HashMap<Symbol, QueueEstimate>>>,
+}
+
+impl QueueBoard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track(&self, symbol: Symbol, estimate: QueueEstimate) {
+        self.tracked.lock().insert(symbol, estimate);
+    }
+
+    pub fn untrack(&self, symbol: &Symbol) {
+        self.tracked.lock().remove(symbol);
+    }
+
+    pub fn observe_book(&self, book: &OrderBookSnapshot) {
+        if let Some(estimate) = self.tracked.lock().get_mut(&book.symbol) {
+            estimate.on_book(book);
+        }
+    }
+
+    pub fn observe_trade(&self, tick: &TradeTick) {
+        if let Some(estimate) = self.tracked.lock().get_mut(&tick.symbol) {
+            estimate.on_trade(tick);
+        }
+    }
+
+    /// Call for `symbol`'s order and the size estimated ahead of it (None = not tracked)
+    pub fn assess(
+        &self,
+        symbol: &Symbol,
+        tick_size: Option<Decimal>,
+        now: Instant,
+        deadline: Instant,
+    ) -> Option<(QueueCall, Option<Decimal>)> {
+        let tracked = self.tracked.lock();
+        let estimate = tracked.get(symbol)?;
+        Some((estimate.assess(tick_size, now, deadline), estimate.ahead()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn book(bids: &[(&str, &str)], asks: &[(&str, &str)]) -> OrderBookSnapshot {
+        let levels = |levels: &[(&str, &str)]| levels.iter().map(|(p, s)| (d(p), d(s))).collect::<Vec<_>>();
+        let (bids, asks) = (levels(bids), levels(asks));
+        OrderBookSnapshot::new(Symbol::from("BTCUSDT"), 0, bids[0].0, asks[0].0, bids[0].1, asks[0].1).with_depth(bids, asks)
+    }
+
+    fn sell(price: &str, size: &str) -> TradeTick {
+        TradeTick {
+            symbol: Symbol::from("BTCUSDT"),
+            price: d(price),
+            size: d(size),
+            timestamp: 0,
+            side: TradeSide::Sell,
+        }
+    }
+
+    #[test]
+    fn test_queue_drains_with_trades_and_cancels() {
+        let start = Instant::now();
+        // Our 1 joined 10 others at the bid
+        let mut queue = QueueEstimate::new(OrderSide::Buy, d("100.00"), d("1"), start);
+        queue.on_book(&book(&[("100.00", "11")], &[("100.10", "5")]));
+        assert_eq!(queue.ahead(), Some(d("10")));
+
+        queue.on_trade(&sell("100.00", "4"));
+        assert_eq!(queue.ahead(), Some(d("6")));
+        // Size added behind us doesn't push us back; cancels shrink what's ahead
+        queue.on_book(&book(&[("100.00", "20")], &[("100.10", "5")]));
+        assert_eq!(queue.ahead(), Some(d("6")));
+        queue.on_book(&book(&[("100.00", "3")], &[("100.10", "5")]));
+        assert_eq!(queue.ahead(), Some(d("2")));
+        // Buys and trades above our bid don't touch the queue; a trade through it clears it
+        queue.on_trade(&TradeTick { side: TradeSide::Buy, ..sell("100.00", "5") });
+        assert_eq!(queue.ahead(), Some(d("2")));
+        queue.on_trade(&sell("99.99", "1"));
+        assert_eq!(queue.ahead(), Some(d("0")));
+    }
+
+    #[test]
+    fn test_chase_the_touch_or_step_inside_a_stuck_queue() {
+        let start = Instant::now();
+        let deadline = start + Duration::from_secs(10);
+        let tick = Some(d("0.01"));
+
+        // Bid moved up without us
+        let mut queue = QueueEstimate::new(OrderSide::Buy, d("100.00"), d("1"), start);
+        queue.on_book(&book(&[("100.02", "3"), ("100.00", "11")], &[("100.10", "5")]));
+        assert_eq!(queue.assess(tick, start, deadline), QueueCall::Reprice(d("100.02")));
+
+        // At the touch: trust the drain rate only after QUEUE_MIN_WAIT
+        let mut queue = QueueEstimate::new(OrderSide::Buy, d("100.00"), d("1"), start);
+        queue.on_book(&book(&[("100.00", "51")], &[("100.10", "5")]));
+        assert_eq!(queue.assess(tick, start + Duration::from_secs(1), deadline), QueueCall::Wait);
+        // 2 traded in 2s with 48 ahead and 8s left: step one tick inside
+        queue.on_trade(&sell("100.00", "2"));
+        assert_eq!(queue.assess(tick, start + Duration::from_secs(2), deadline), QueueCall::Reprice(d("100.01")));
+        // Fast enough queue: keep the place
+        queue.on_trade(&sell("100.00", "40"));
+        assert_eq!(queue.assess(tick, start + Duration::from_secs(2), deadline), QueueCall::Wait);
+
+        // No room inside a one-tick spread
+        let mut queue = QueueEstimate::new(OrderSide::Sell, d("100.01"), d("1"), start);
+        queue.on_book(&book(&[("100.00", "5")], &[("100.01", "50")]));
+        assert_eq!(queue.assess(tick, start + Duration::from_secs(3), deadline), QueueCall::Wait);
+    }
+}
@@ -0,0 +1,254 @@
+//! Binance Reference Feed
+//!
+//! Binance futures often move a few hundred milliseconds before Bybit on alts.
+//! With `BINANCE_REFERENCE=true` this actor follows the Binance `bookTicker`
+//! of every symbol the strategy watches (same name, e.g. SOLUSDT) on its own
+//! connection and reconnect loop, and on each Binance update samples both
+//! mids into `ReferencePrices`. The strategy reads two features from it: the
+//! premium of Binance over Bybit, and the lead (Binance's return over
+//! `REFERENCE_LEAD_MS` minus Bybit's). A symbol Binance doesn't list simply
+//! has no features.
+
+use crate::actors::position_monitor::MarkPrice;
+use crate::actors::websocket::{jitter, ReconnectBackoff, HEALTHY_CONNECTION};
+use crate::health::ActorHeartbeat;
+use crate::models::Symbol;
+use anyhow::{bail, Context, Result};
+use dashmap::DashMap;
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::prelude::ToPrimitive;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::time::{interval, Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, error, info, warn};
+
+pub const REFERENCE_WS_URL: &str = "wss://fstream.binance.com/ws";
+
+/// Watched symbols are re-read (and subscriptions adjusted) this often
+pub const REFERENCE_RESUBSCRIBE_SECS: u64 = 1;
+
+/// A handshake slower than this is retried (connect_async has no timeout of its own)
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Features of a feed quieter than this are stale (connection down, symbol not on Binance)
+const STALE_AFTER: Duration = Duration::from_secs(2);
+
+/// Binance futures `bookTicker` event (subscription replies don't parse as one)
+#[derive(Debug, Deserialize)]
+struct BookTicker {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "b")]
+    bid: String,
+    #[serde(rename = "a")]
+    ask: String,
+}
+
+/// `(symbol, mid)` of a `bookTicker` frame
+fn parse_book_ticker(text: &str) -> Option<(Symbol, f64)> {
+    let ticker: BookTicker = serde_json::from_str(text).ok()?;
+    let bid: f64 = ticker.bid.parse().ok()?;
+    let ask: f64 = ticker.ask.parse().ok()?;
+    (bid > 0.0 && ask > 0.0).then(|| (Symbol::from(ticker.symbol.as_str()), (bid + ask) / 2.0))
+}
+
+/// Lead-lag features of one symbol
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceFeatures {
+    /// Binance mid over Bybit mid, bps
+    pub premium_bps: f64,
+    /// Binance return minus Bybit return over the lead window, bps (positive = Binance moved up first)
+    pub lead_bps: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    binance: f64,
+    bybit: f64,
+}
+
+/// Recent Binance/Bybit mid pairs per symbol (cheap to clone, shared)
+#[derive(Clone)]
+pub struct ReferencePrices {
+    lead: Duration,
+    samples: Arc<DashMap<Symbol, VecDeque<Sample>>>,
+}
+
+impl ReferencePrices {
+    pub fn new(lead_ms: u64) -> Self {
+        Self {
+            lead: Duration::from_millis(lead_ms),
+            samples: Arc::new(DashMap::new()),
+        }
+    }
+
+    pub fn record(&self, symbol: &Symbol, binance: f64, bybit: f64, now: Instant) {
+        let mut samples = self.samples.entry(symbol.clone()).or_default();
+        samples.push_back(Sample { at: now, binance, bybit });
+        // Keep the newest sample at least one lead window old as the base
+        while samples.get(1).is_some_and(|s| now.duration_since(s.at) >= self.lead) {
+            samples.pop_front();
+        }
+    }
+
+    /// None until a lead window of history, or when the feed went quiet
+    pub fn features(&self, symbol: &Symbol, now: Instant) -> Option<ReferenceFeatures> {
+        let samples = self.samples.get(symbol)?;
+        let (base, last) = (samples.front()?, samples.back()?);
+        if now.duration_since(last.at) > STALE_AFTER || last.at.duration_since(base.at) < self.lead {
+            return None;
+        }
+        Some(ReferenceFeatures {
+            premium_bps: (last.binance / last.bybit - 1.0) * 10_000.0,
+            lead_bps: (last.binance / base.binance - last.bybit / base.bybit) * 10_000.0,
+        })
+    }
+}
+
+/// ReferenceFeedActor - Binance futures book tops next to the Bybit mids
+pub struct ReferenceFeedActor {
+    /// Bybit mids of the watched symbols: the current one, or one per shard
+    bybit: Vec<watch::Receiver<Option<MarkPrice>>>,
+    reference: ReferencePrices,
+    heartbeat: ActorHeartbeat,
+}
+
+impl ReferenceFeedActor {
+    pub fn new(bybit: Vec<watch::Receiver<Option<MarkPrice>>>, reference: ReferencePrices, heartbeat: ActorHeartbeat) -> Self {
+        Self { bybit, reference, heartbeat }
+    }
+
+    pub async fn run(self) {
+        info!("🛰️  ReferenceFeedActor started");
+
+        let mut backoff = ReconnectBackoff::default();
+
+        loop {
+            self.heartbeat.beat();
+            let connected_at = Instant::now();
+            let result = self.connect_and_stream().await;
+
+            if connected_at.elapsed() >= HEALTHY_CONNECTION {
+                backoff.reset();
+            }
+            let delay = backoff.next_delay(jitter());
+
+            match result {
+                Ok(_) => warn!("⚠️  Binance reference feed closed, reconnecting in {:.1}s...", delay.as_secs_f64()),
+                Err(e) => error!("Binance reference feed error: {}. Reconnecting in {:.1}s...", e, delay.as_secs_f64()),
+            }
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn connect_and_stream(&self) -> Result<()> {
+        let (ws_stream, _) = tokio::time::timeout(CONNECT_TIMEOUT, connect_async(REFERENCE_WS_URL))
+            .await
+            .context("Timed out connecting to Binance WebSocket")?
+            .context("Failed to connect to Binance WebSocket")?;
+        let (mut write, mut read) = ws_stream.split();
+        info!("✅ Binance reference feed connected");
+
+        let mut subscribed = HashSet::new();
+        let mut request_id = 0u64;
+        let mut resubscribe = interval(Duration::from_secs(REFERENCE_RESUBSCRIBE_SECS));
+
+        loop {
+            self.heartbeat.beat();
+            tokio::select! {
+                msg = read.next() => {
+                    match msg {
+                        Some(Ok(Message::Text(text))) => self.handle_message(&text),
+                        Some(Ok(Message::Close(_))) | None => {
+                            info!("Binance reference feed closed by server");
+                            return Ok(());
+                        }
+                        Some(Err(e)) => bail!("read error: {}", e),
+                        // Tungstenite answers pings itself
+                        Some(Ok(_)) => {}
+                    }
+                }
+
+                _ = resubscribe.tick() => {
+                    let watched = self.watched();
+                    for (method, symbols) in [
+                        ("UNSUBSCRIBE", subscribed.difference(&watched).cloned().collect::<Vec<Symbol>>()),
+                        ("SUBSCRIBE", watched.difference(&subscribed).cloned().collect()),
+                    ] {
+                        if symbols.is_empty() {
+                            continue;
+                        }
+                        request_id += 1;
+                        let params: Vec<String> = symbols.iter().map(|s| format!("{}@bookTicker", s.0.to_lowercase())).collect();
+                        debug!("🛰️  Binance {} {}", method, params.join(", "));
+                        write
+                            .send(Message::Text(json!({ "method": method, "params": params, "id": request_id }).to_string()))
+                            .await?;
+                    }
+                    subscribed = watched;
+                }
+            }
+        }
+    }
+
+    /// Symbols with a Bybit mid to compare against
+    fn watched(&self) -> HashSet<Symbol> {
+        self.bybit
+            .iter()
+            .filter_map(|rx| rx.borrow().as_ref().map(|mark| mark.symbol.clone()))
+            .collect()
+    }
+
+    fn handle_message(&self, text: &str) {
+        let Some((symbol, binance)) = parse_book_ticker(text) else {
+            return;
+        };
+        let bybit = self.bybit.iter().find_map(|rx| {
+            rx.borrow()
+                .as_ref()
+                .filter(|mark| mark.symbol == symbol)
+                .and_then(|mark| mark.price.to_f64())
+        });
+        if let Some(bybit) = bybit.filter(|price| *price > 0.0) {
+            self.reference.record(&symbol, binance, bybit, Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_book_ticker() {
+        let frame = r#"{"e":"bookTicker","u":400900217,"E":1568014460893,"T":1568014460891,
+            "s":"SOLUSDT","b":"149.90","B":"31.21","a":"150.10","A":"40.66"}"#;
+        assert_eq!(parse_book_ticker(frame), Some((Symbol::from("SOLUSDT"), 150.0)));
+        assert_eq!(parse_book_ticker(r#"{"result":null,"id":1}"#), None);
+    }
+
+    #[test]
+    fn test_lead_and_premium() {
+        let start = Instant::now();
+        let reference = ReferencePrices::new(500);
+        let sol = Symbol::from("SOLUSDT");
+        reference.record(&sol, 100.0, 100.0, start);
+        // Not a lead window of history yet
+        assert!(reference.features(&sol, start + Duration::from_millis(100)).is_none());
+
+        // Binance up 20 bps, Bybit only 5 bps
+        reference.record(&sol, 100.2, 100.05, start + Duration::from_millis(600));
+        let features = reference.features(&sol, start + Duration::from_millis(600)).unwrap();
+        assert!((features.lead_bps - 15.0).abs() < 1e-6);
+        assert!((features.premium_bps - (100.2 / 100.05 - 1.0) * 10_000.0).abs() < 1e-9);
+
+        // Feed went quiet
+        assert!(reference.features(&sol, start + Duration::from_secs(5)).is_none());
+        assert!(reference.features(&Symbol::from("ETHUSDT"), start).is_none());
+    }
+}
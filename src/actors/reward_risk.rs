@@ -0,0 +1,92 @@
+//! Reward:Risk Pre-Check
+//!
+//! The fixed SL/TP pair reads 1:2, but fees, the spread and slippage come off
+//! the reward and add to the risk, and a target beyond the 200-tick VWAP is
+//! rarely reached in one go (price stalls there). With `MIN_REWARD_RISK` set,
+//! an entry is skipped (reason `reward_risk`) when
+//! `(reward − costs) / (risk + costs)` falls below it: reward is the distance
+//! to the take profit or to the VWAP when that lies in between, risk the stop
+//! distance, costs the round-trip taker fees, the spread and
+//! `EXPECTED_SLIPPAGE_BPS` on both fills.
+
+use crate::models::OrderSide;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// One planned trade, all distances in % of the entry price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardRisk {
+    pub reward_percent: f64,
+    pub risk_percent: f64,
+    pub cost_percent: f64,
+}
+
+impl RewardRisk {
+    /// Before costs
+    pub fn new(side: OrderSide, entry: Decimal, vwap: Option<Decimal>, target_percent: f64, stop_percent: f64) -> Self {
+        // VWAP on the profit side, short of the target: that's as far as the move is counted on
+        let to_vwap = vwap
+            .filter(|_| !entry.is_zero())
+            .and_then(|vwap| {
+                let distance = match side {
+                    OrderSide::Buy => vwap - entry,
+                    OrderSide::Sell => entry - vwap,
+                };
+                (distance / entry * Decimal::from(100)).to_f64()
+            })
+            .filter(|distance| *distance > 0.0);
+        Self {
+            reward_percent: to_vwap.map_or(target_percent, |distance| distance.min(target_percent)),
+            risk_percent: stop_percent,
+            cost_percent: 0.0,
+        }
+    }
+
+    /// `fee_percent` covers entry and exit, `slippage_bps` one fill
+    pub fn with_costs(self, fee_percent: f64, spread_bps: f64, slippage_bps: f64) -> Self {
+        Self {
+            // Half the spread on each fill, plus slippage on each
+            cost_percent: fee_percent + (spread_bps + 2.0 * slippage_bps) / 100.0,
+            ..self
+        }
+    }
+
+    /// Net reward per unit of net risk (negative once costs exceed the reward)
+    pub fn ratio(&self) -> f64 {
+        let risk = self.risk_percent + self.cost_percent;
+        if risk <= 0.0 {
+            return 0.0;
+        }
+        (self.reward_percent - self.cost_percent) / risk
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_costs_come_off_the_reward_and_add_to_the_risk() {
+        let plan = RewardRisk::new(OrderSide::Buy, Decimal::from(100), None, 0.70, 0.35).with_costs(0.11, 2.0, 1.0);
+        assert!((plan.cost_percent - 0.15).abs() < 1e-9);
+        // (0.70 − 0.15) / (0.35 + 0.15) = 1.1, not the 2.0 SL/TP suggests
+        assert!((plan.ratio() - 1.1).abs() < 1e-9);
+
+        // Costs above the reward: a losing trade even when it works
+        let tiny = RewardRisk::new(OrderSide::Buy, Decimal::from(100), None, 0.10, 0.35).with_costs(0.11, 2.0, 1.0);
+        assert!(tiny.ratio() < 0.0);
+    }
+
+    #[test]
+    fn test_vwap_ahead_caps_the_reward() {
+        let entry = Decimal::from(100);
+        // Long with the VWAP 0.3% above: the move is counted up to it
+        let long = RewardRisk::new(OrderSide::Buy, entry, Some(Decimal::new(1003, 1)), 0.70, 0.35);
+        assert!((long.reward_percent - 0.3).abs() < 1e-9);
+        // Behind the trade, or beyond the target: the target counts
+        let behind = RewardRisk::new(OrderSide::Sell, entry, Some(Decimal::new(1003, 1)), 0.70, 0.35);
+        assert_eq!(behind.reward_percent, 0.70);
+        let beyond = RewardRisk::new(OrderSide::Sell, entry, Some(Decimal::from(99)), 0.70, 0.35);
+        assert_eq!(beyond.reward_percent, 0.70);
+    }
+}
@@ -0,0 +1,175 @@
+//! Strategy Router
+//!
+//! With `TRADING_SYMBOLS` every symbol gets its own strategy shard (engine
+//! task + inbox), and this actor takes the place of the single engine on the
+//! strategy channel: execution feedback and warm-ups go to the shard of their
+//! symbol, operator commands and parameter patches go to every shard. Market
+//! data skips the router (each shard has its own ring from MarketDataActor).
+//! Entries from all shards share one `PositionGate` before execution.
+
+use crate::actors::messages::{StrategyMessage, StrategyStatus};
+use crate::channel::{MeteredReceiver, MeteredSender};
+use crate::health::ActorHeartbeat;
+use crate::models::Symbol;
+use std::collections::HashMap;
+use tokio::sync::oneshot;
+use tokio::time::{interval, Duration};
+use tracing::{debug, info, warn};
+
+/// Heartbeat period (also bounds how long the loop sits idle)
+pub const ROUTER_BEAT_SECS: u64 = 1;
+
+/// StrategyRouter - fans the strategy channel out to per-symbol shards
+pub struct StrategyRouter {
+    inbox: MeteredReceiver<StrategyMessage>,
+    shards: HashMap<Symbol, MeteredSender<StrategyMessage>>,
+    /// Shard order for status queries (first = reported when nobody holds a position)
+    order: Vec<Symbol>,
+    heartbeat: ActorHeartbeat,
+}
+
+impl StrategyRouter {
+    pub fn new(inbox: MeteredReceiver<StrategyMessage>, heartbeat: ActorHeartbeat) -> Self {
+        Self {
+            inbox,
+            shards: HashMap::new(),
+            order: Vec::new(),
+            heartbeat,
+        }
+    }
+
+    /// Send `symbol`'s messages to `tx`
+    pub fn add_shard(&mut self, symbol: Symbol, tx: MeteredSender<StrategyMessage>) {
+        if self.shards.insert(symbol.clone(), tx).is_none() {
+            self.order.push(symbol);
+        }
+    }
+
+    pub async fn run(mut self) {
+        info!("🔀 StrategyRouter started with {} shards: {:?}", self.order.len(), self.order);
+
+        let mut beat_interval = interval(Duration::from_secs(ROUTER_BEAT_SECS));
+
+        loop {
+            self.heartbeat.beat();
+            tokio::select! {
+                msg = self.inbox.recv() => match msg {
+                    Some(msg) => self.dispatch(msg).await,
+                    None => {
+                        info!("StrategyRouter channel closed, shutting down");
+                        break;
+                    }
+                },
+
+                _ = beat_interval.tick() => {}
+            }
+        }
+    }
+
+    async fn dispatch(&self, msg: StrategyMessage) {
+        match msg {
+            StrategyMessage::Control(command) => {
+                for tx in self.shards.values() {
+                    if let Err(e) = tx.send(StrategyMessage::Control(command)).await {
+                        warn!("Failed to forward {} to a shard: {}", command, e);
+                    }
+                }
+            }
+            StrategyMessage::PatchParams(patch) => {
+                for tx in self.shards.values() {
+                    if let Err(e) = tx.send(StrategyMessage::PatchParams(patch)).await {
+                        warn!("Failed to forward a parameter patch to a shard: {}", e);
+                    }
+                }
+            }
+            StrategyMessage::Status(reply) => {
+                // Answered off the routing loop: a busy shard mustn't hold up market data
+                let shards: Vec<_> = self.order.iter().filter_map(|s| self.shards.get(s).cloned()).collect();
+                tokio::spawn(async move {
+                    if let Some(status) = collect_status(shards).await {
+                        let _ = reply.send(status);
+                    }
+                });
+            }
+            msg => {
+                let Some(tx) = msg.symbol().and_then(|symbol| self.shards.get(symbol)) else {
+                    debug!("No shard for {:?}, dropped", msg.symbol());
+                    return;
+                };
+                if let Err(e) = tx.send(msg).await {
+                    debug!("Failed to forward to shard: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Status of the shard holding a position, or of the first shard
+async fn collect_status(shards: Vec<MeteredSender<StrategyMessage>>) -> Option<StrategyStatus> {
+    let mut first = None;
+    for tx in shards {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        if tx.send(StrategyMessage::Status(reply_tx)).await.is_err() {
+            continue;
+        }
+        let Ok(status) = reply_rx.await else {
+            continue;
+        };
+        if status.position.is_some() {
+            return Some(status);
+        }
+        first.get_or_insert(status);
+    }
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actors::messages::ControlCommand;
+    use crate::channel::{metered_channel, OverflowPolicy};
+    use crate::health::HealthRegistry;
+    use crate::metrics::Metrics;
+    use crate::models::{TradeSide, TradeTick};
+    use rust_decimal::Decimal;
+
+    fn trade(symbol: &str) -> StrategyMessage {
+        StrategyMessage::Trade(TradeTick {
+            symbol: Symbol::from(symbol),
+            price: Decimal::from(100),
+            size: Decimal::ONE,
+            timestamp: 0,
+            side: TradeSide::Buy,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_router_routes_by_symbol_and_broadcasts_control() {
+        let metrics = Metrics::new();
+        let policy = OverflowPolicy::Block { timeout: None };
+        let (inbox_tx, inbox_rx) = metered_channel("strategy", 10, policy, &metrics);
+        let (btc_tx, mut btc_rx) = metered_channel("strategy_btc", 10, policy, &metrics);
+        let (eth_tx, mut eth_rx) = metered_channel("strategy_eth", 10, policy, &metrics);
+
+        let mut router = StrategyRouter::new(
+            inbox_rx,
+            HealthRegistry::new().register("strategy_router", Duration::from_secs(ROUTER_BEAT_SECS)),
+        );
+        router.add_shard(Symbol::from("BTCUSDT"), btc_tx);
+        router.add_shard(Symbol::from("ETHUSDT"), eth_tx);
+        let handle = tokio::spawn(router.run());
+
+        inbox_tx.send(trade("ETHUSDT")).await.unwrap();
+        inbox_tx.send(trade("SOLUSDT")).await.unwrap();
+        inbox_tx.send(StrategyMessage::Control(ControlCommand::Pause)).await.unwrap();
+
+        assert!(matches!(eth_rx.recv().await, Some(StrategyMessage::Trade(t)) if t.symbol.as_str() == "ETHUSDT"));
+        assert!(matches!(eth_rx.recv().await, Some(StrategyMessage::Control(ControlCommand::Pause))));
+        // Nothing for BTC but the broadcast; unknown symbols are dropped
+        assert!(matches!(btc_rx.recv().await, Some(StrategyMessage::Control(ControlCommand::Pause))));
+        assert!(btc_rx.try_recv().is_none());
+
+        drop(inbox_tx);
+        handle.await.unwrap();
+    }
+}
@@ -1,48 +1,92 @@
-use crate::actors::messages::{MarketDataMessage, StrategyMessage};
+use crate::actors::messages::{MarketDataMessage, ScannerMessage, StrategyMessage};
+use crate::channel::{MeteredReceiver, MeteredSender};
 use crate::config::Config;
-use crate::exchange::{BybitClient, SpecsCache, SymbolSpecs};
-use crate::models::Symbol;
+use crate::exchange::{BybitClient, Kline, RecentTrade, SpecsCache};
+use crate::health::ActorHeartbeat;
+use crate::risk_limits::TempBlacklist;
+use crate::symbol_lists::SymbolLists;
+use crate::models::{Candle, Symbol, TradeSide, TradeTick};
 use anyhow::Result;
+use rust_decimal::Decimal;
+use std::str::FromStr;
 use std::sync::Arc;
-use tokio::sync::mpsc;
 use tokio::time::{interval, Duration, Instant};
 use tracing::{debug, error, info, warn};
 
-/// Minimum time to hold a symbol before switching (prevents frequent switches)
-const MIN_SYMBOL_HOLD_TIME_SECS: u64 = 300; // 5 minutes
+/// ✅ HYSTERESIS: The coin out-scoring the current one, and for how many scans in a row
+#[derive(Debug, Default)]
+struct Challenger {
+    symbol: Option<String>,
+    scans: u32,
+}
+
+impl Challenger {
+    /// This scan's coin past the threshold (None = the current one holds); returns its streak
+    fn observe(&mut self, winner: Option<&str>) -> u32 {
+        match winner {
+            Some(symbol) if self.symbol.as_deref() == Some(symbol) => self.scans += 1,
+            Some(symbol) => {
+                self.symbol = Some(symbol.to_string());
+                self.scans = 1;
+            }
+            None => *self = Self::default(),
+        }
+        self.scans
+    }
+}
 
 /// The "Predator" Scanner - hunts for high-volatility coins
 pub struct ScannerActor {
     client: BybitClient,
     config: Arc<Config>,
-    market_data_tx: mpsc::Sender<MarketDataMessage>,
-    strategy_tx: mpsc::Sender<StrategyMessage>,
+    market_data_tx: MeteredSender<MarketDataMessage>,
+    strategy_tx: MeteredSender<StrategyMessage>,
+    command_rx: MeteredReceiver<ScannerMessage>,
     specs_cache: SpecsCache,
+    temp_blacklist: TempBlacklist,
+    /// Black/whitelist with runtime edits (Telegram, control API)
+    symbols: SymbolLists,
+    /// Operator override (control API), takes precedence over scanning and TRADING_SYMBOL
+    pinned_symbol: Option<String>,
+    /// The pin holds an adopted position and lifts once the exchange shows it closed
+    pinned_until_flat: bool,
     current_symbol: Option<Symbol>,
     current_score: f64,
-    // ✅ FIX RECONNECT: Track first scan to ensure subscription after restart
-    first_scan: bool,
     // ✅ STABILITY: Track last symbol switch time
     last_symbol_switch: Option<Instant>,
+    challenger: Challenger,
+    heartbeat: ActorHeartbeat,
 }
 
 impl ScannerActor {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         client: BybitClient,
         config: Arc<Config>,
-        market_data_tx: mpsc::Sender<MarketDataMessage>,
-        strategy_tx: mpsc::Sender<StrategyMessage>,
+        market_data_tx: MeteredSender<MarketDataMessage>,
+        strategy_tx: MeteredSender<StrategyMessage>,
+        command_rx: MeteredReceiver<ScannerMessage>,
+        specs_cache: SpecsCache,
+        temp_blacklist: TempBlacklist,
+        symbols: SymbolLists,
+        heartbeat: ActorHeartbeat,
     ) -> Self {
         Self {
             client,
             config,
             market_data_tx,
             strategy_tx,
-            specs_cache: SpecsCache::new(),
+            command_rx,
+            specs_cache,
+            temp_blacklist,
+            symbols,
+            pinned_symbol: None,
+            pinned_until_flat: false,
             current_symbol: None,
             current_score: 0.0,
-            first_scan: true, // ✅ FIX RECONNECT: Ensure first scan always sends messages
             last_symbol_switch: None,
+            challenger: Challenger::default(),
+            heartbeat,
         }
     }
 
@@ -57,7 +101,12 @@ impl ScannerActor {
         }
 
         loop {
-            scan_interval.tick().await;
+            self.heartbeat.beat();
+            tokio::select! {
+                _ = scan_interval.tick() => {}
+                Some(command) = self.command_rx.recv() => self.handle_command(command),
+            }
+            self.heartbeat.beat();
 
             if let Err(e) = self.scan_and_select().await {
                 error!("Scan failed: {}", e);
@@ -67,16 +116,65 @@ impl ScannerActor {
         }
     }
 
+    /// Commands are applied by the scan that follows right after
+    fn handle_command(&mut self, command: ScannerMessage) {
+        match command {
+            ScannerMessage::PinSymbol(Some(symbol)) | ScannerMessage::AdoptSymbol(symbol)
+                if !self.config.trading_symbols.is_empty() =>
+            {
+                warn!("📌 Pin {} ignored: TRADING_SYMBOLS fixes the traded symbols", symbol);
+            }
+            ScannerMessage::PinSymbol(Some(symbol)) => {
+                info!("📌 Operator pinned {}", symbol);
+                self.pinned_symbol = Some(symbol.0.to_string());
+                self.pinned_until_flat = false;
+            }
+            ScannerMessage::AdoptSymbol(symbol) => {
+                info!("📌 Pinned {} until its adopted position is closed", symbol);
+                self.pinned_symbol = Some(symbol.0.to_string());
+                self.pinned_until_flat = true;
+            }
+            ScannerMessage::PinSymbol(None) => {
+                info!("📌 Operator unpinned {:?}, back to scanning", self.pinned_symbol);
+                self.pinned_symbol = None;
+                self.pinned_until_flat = false;
+            }
+            // A current symbol no longer allowed is switched away from by the scan below
+            ScannerMessage::SymbolListsChanged => {
+                if let Some(reason) = self.pinned_symbol.as_deref().and_then(|s| self.symbols.rejects(s)) {
+                    warn!("📌 Pinned {:?} is {}, back to scanning", self.pinned_symbol, reason);
+                    self.pinned_symbol = None;
+                    self.pinned_until_flat = false;
+                }
+                let fixed = self.config.trading_symbol.iter().chain(&self.config.trading_symbols);
+                for (symbol, reason) in fixed.filter_map(|s| Some((s, self.symbols.rejects(s)?))) {
+                    warn!("⛔ {} is {} but fixed by TRADING_SYMBOL(S): entries stay blocked, no switch", symbol, reason);
+                }
+            }
+            ScannerMessage::NewCoinDetected { .. } => {}
+        }
+    }
+
     async fn scan_and_select(&mut self) -> Result<()> {
+        self.refresh_expired_specs().await;
+
+        // ✅ SHARDS: Fixed symbol set, one strategy shard each (no scanning, no pinning)
+        if !self.config.trading_symbols.is_empty() {
+            return self.use_shard_symbols().await;
+        }
+
+        self.release_closed_adoption().await;
+
         // ✅ MEAN REVERSION: If fixed symbol is set, use it directly (no scanning)
-        if let Some(ref fixed_symbol) = self.config.trading_symbol {
-            return self.use_fixed_symbol(fixed_symbol.clone()).await;
+        if let Some(fixed_symbol) = self.pinned_symbol.clone().or_else(|| self.config.trading_symbol.clone()) {
+            return self.use_fixed_symbol(fixed_symbol).await;
         }
 
         info!("🎯 Starting market scan...");
 
         // Fetch all tickers
         let tickers = self.client.get_tickers("linear").await?;
+        let now = chrono::Utc::now();
 
         // Filter and score coins
         let mut candidates: Vec<ScoredCoin> = tickers
@@ -116,8 +214,20 @@ impl ScannerActor {
                 }
 
                 // ✅ FIX BUG #30: Check blacklist BEFORE selecting symbol
-                if self.config.blacklist_symbols.contains(&symbol.to_uppercase()) {
-                    debug!("⛔ Symbol {} is blacklisted, excluding from scan", symbol);
+                if let Some(reason) = self.symbols.rejects(&symbol) {
+                    debug!("⛔ Symbol {} is {}, excluding from scan", symbol, reason);
+                    return None;
+                }
+
+                // ✅ PAIR TRADING: The pair trader's legs are not the strategy's to trade
+                if self.config.is_pair_symbol(&symbol) {
+                    debug!("🔗 Symbol {} is a pair leg, excluding from scan", symbol);
+                    return None;
+                }
+
+                // ✅ AUTO-BLACKLIST: Symbols that lost too much recently
+                if self.temp_blacklist.is_blocked(&symbol, now) {
+                    debug!("🚫 Symbol {} is temporarily blacklisted, excluding from scan", symbol);
                     return None;
                 }
 
@@ -135,10 +245,8 @@ impl ScannerActor {
                     // 2. Must not move more than 30% (otherwise it's a dangerous pump)
                     let abs_change = price_change_24h.abs();
                     
-                    if abs_change < 0.015 {
-                         0.0 // Too stable (Dead)
-                    } else if abs_change > 0.30 {
-                         0.0 // Too volatile (Dangerous Pump)
+                    if !(0.015..=0.30).contains(&abs_change) {
+                         0.0 // Too stable (Dead) or too volatile (Dangerous Pump)
                     } else {
                          // ✅ FIXED: Bell curve formula
                          // Peak at 9% volatility (ideal for momentum trading)
@@ -193,13 +301,10 @@ impl ScannerActor {
             );
 
             // ✅ FIXED: Update current score from live candidates (Solve Zombie Bug)
-            let mut current_score_live = 0.0;
-            
             if let Some(ref current) = self.current_symbol {
-                if let Some(current_candidate) = candidates.iter().find(|c| c.symbol == current.0) {
-                    current_score_live = current_candidate.score;
+                if let Some(current_candidate) = candidates.iter().find(|c| c.symbol == current.as_str()) {
                     // Update internal state to match reality
-                    self.current_score = current_score_live;
+                    self.current_score = current_candidate.score;
                 } else {
                     // Current symbol dropped out of filter (volume crash?) -> Score 0 to force switch
                     self.current_score = 0.0;
@@ -208,94 +313,81 @@ impl ScannerActor {
 
             // Check if we should switch
             let should_switch = if let Some(ref current) = self.current_symbol {
-                // ✅ STABILITY: Don't switch if we just switched recently
-                let hold_time_ok = self.last_symbol_switch
-                    .map(|t| t.elapsed().as_secs() >= MIN_SYMBOL_HOLD_TIME_SECS)
+                // ✅ SYMBOL LISTS: The operator fenced off the current coin, or it lost its way onto
+                // the auto-blacklist (it dropped out of the candidates, score 0) - leave it without
+                // waiting out the dwell time
+                let fenced = self.symbols.rejects(current.as_str())
+                    .or_else(|| self.temp_blacklist.is_blocked(current.as_str(), now).then_some("temporarily blacklisted"));
+                if let Some(reason) = fenced {
+                    warn!("⛔ Current coin {} is {}, switching now", current, reason);
+                }
+
+                // ✅ DWELL TIME: Don't switch if we just switched recently (MIN_SYMBOL_DWELL_SECS)
+                let dwell_secs = self.config.min_symbol_dwell_secs;
+                let dwell_ok = fenced.is_some() || self.last_symbol_switch
+                    .map(|t| t.elapsed().as_secs() >= dwell_secs)
                     .unwrap_or(true); // No previous switch = OK to switch
-                
-                if !hold_time_ok {
+
+                let beats = top_coin.score > self.current_score * self.config.score_threshold_multiplier
+                    && top_coin.symbol != current.as_str();
+                // ✅ HYSTERESIS: One scan's turnover spike is not enough (SWITCH_CONFIRM_SCANS)
+                let streak = self.challenger.observe(beats.then_some(top_coin.symbol.as_str()));
+                let confirm_scans = self.config.switch_confirm_scans;
+
+                if !dwell_ok {
                     if let Some(last_switch) = self.last_symbol_switch {
                         debug!(
-                            "⏳ Symbol hold time: {}s / {}s remaining",
+                            "⏳ Symbol dwell time: {}s / {}s",
                             last_switch.elapsed().as_secs(),
-                            MIN_SYMBOL_HOLD_TIME_SECS - last_switch.elapsed().as_secs()
+                            dwell_secs
                         );
                     }
                     false
+                } else if beats && fenced.is_none() && streak < confirm_scans {
+                    info!(
+                        "⏳ {} out-scores {} ({}/{} scans in a row)",
+                        top_coin.symbol, current, streak, confirm_scans
+                    );
+                    false
                 } else {
-                    // Hold time OK, check score threshold
-                    top_coin.score > self.current_score * self.config.score_threshold_multiplier
-                        && top_coin.symbol != current.0
+                    beats
                 }
             } else {
                 // No current symbol, switch to top
                 true
             };
 
-            // ✅ FIX RECONNECT: Always send messages on first scan (even if same symbol)
-            // This ensures WebSocket resubscribes after reconnect
-            let should_notify = should_switch || self.first_scan;
+            // WebSocket reconnects resubscribe on their own (MarketDataActor remembers the symbol)
+            if should_switch {
+                // Fetch instrument specs if not cached (or expired)
+                let specs = self.specs_cache.get_or_fetch(&self.client, &top_coin.symbol).await;
 
-            if should_notify {
-                // Fetch instrument specs if not cached
-                let specs = if let Some(cached) = self.specs_cache.get(&top_coin.symbol) {
-                    cached
-                } else {
-                    match self.client.get_instrument_info(&top_coin.symbol).await {
-                        Ok(info) => {
-                            let specs = SymbolSpecs::from(info);
-                            self.specs_cache.insert(specs.clone());
-                            specs
-                        }
-                        Err(e) => {
-                            warn!("⚠️ Failed to fetch specs for {}: {}, using defaults", top_coin.symbol, e);
-                            self.specs_cache.get_or_default(&top_coin.symbol)
-                        }
-                    }
-                };
+                info!(
+                    "🔄 Switching to new coin: {} (score: {:.2e} -> {:.2e})",
+                    top_coin.symbol, self.current_score, top_coin.score
+                );
 
-                if should_switch {
-                    info!(
-                        "🔄 Switching to new coin: {} (score: {:.2e} -> {:.2e})",
-                        top_coin.symbol, self.current_score, top_coin.score
-                    );
-
-                    self.current_symbol = Some(Symbol(top_coin.symbol.clone()));
-                    self.current_score = top_coin.score;
-                    self.last_symbol_switch = Some(Instant::now()); // ✅ Track switch time
-
-                    // Send switch command to MarketDataActor (only on actual switch)
-                    if let Err(e) = self
-                        .market_data_tx
-                        .send(MarketDataMessage::SwitchSymbol(Symbol(
-                            top_coin.symbol.clone(),
-                        )))
-                        .await
-                    {
-                        error!("Failed to send symbol switch message: {}", e);
-                    }
-                } else if self.first_scan {
-                    // First scan after start - same symbol but need to notify
-                    info!("✅ Current coin {} confirmed optimal (first scan after start)",
-                        self.current_symbol.as_ref().unwrap());
+                self.current_symbol = Some(Symbol::from(top_coin.symbol.as_str()));
+                self.current_score = top_coin.score;
+                self.last_symbol_switch = Some(Instant::now()); // ✅ Track switch time
+                self.challenger = Challenger::default();
 
-                    // Still send SwitchSymbol to ensure WebSocket subscribes
-                    if let Err(e) = self
-                        .market_data_tx
-                        .send(MarketDataMessage::SwitchSymbol(Symbol(
-                            top_coin.symbol.clone(),
-                        )))
-                        .await
-                    {
-                        error!("Failed to send symbol switch message: {}", e);
-                    }
+                // Send switch command to MarketDataActor (only on actual switch)
+                if let Err(e) = self
+                    .market_data_tx
+                    .send(MarketDataMessage::SwitchSymbol(Symbol::from(
+                        top_coin.symbol.as_str(),
+                    )))
+                    .await
+                {
+                    error!("Failed to send symbol switch message: {}", e);
                 }
 
-                // Always send specs to StrategyEngine when notifying
+                // Always send specs to StrategyEngine on a switch
                 if let Err(e) = self
                     .strategy_tx
                     .send(StrategyMessage::SymbolChanged {
-                        symbol: Symbol(top_coin.symbol.clone()),
+                        symbol: Symbol::from(top_coin.symbol.as_str()),
                         specs,
                         price_change_24h: top_coin.price_change_24h, // Pass 24h change for trend protection
                     })
@@ -304,8 +396,8 @@ impl ScannerActor {
                     error!("Failed to send symbol specs to strategy: {}", e);
                 }
 
-                // Clear first_scan flag
-                self.first_scan = false;
+                self.send_warmup_ticks(&top_coin.symbol).await;
+                self.send_warmup_candles(&top_coin.symbol).await;
             } else {
                 info!("✅ Current coin {} still optimal", self.current_symbol.as_ref().unwrap());
                 
@@ -334,10 +426,26 @@ impl ScannerActor {
     }
 
     /// ✅ MEAN REVERSION: Use fixed trading symbol (skip scanning)
+    /// ✅ ORDER RECOVERY: Lift the pin on an adopted position once it is closed
+    /// (a failed query keeps it: only a confirmed flat position unpins)
+    async fn release_closed_adoption(&mut self) {
+        let Some(symbol) = self.pinned_symbol.clone().filter(|_| self.pinned_until_flat) else {
+            return;
+        };
+        match self.client.get_position(&symbol).await {
+            Ok(positions) if positions.iter().all(|p| Decimal::from_str(&p.size).is_ok_and(|size| size.is_zero())) => {
+                info!("📌 Adopted {} position closed, unpinned, back to scanning", symbol);
+                self.pinned_symbol = None;
+                self.pinned_until_flat = false;
+            }
+            Ok(_) => debug!("📌 Adopted {} position still open, staying pinned", symbol),
+            Err(e) => warn!("📌 Couldn't check the adopted {} position, staying pinned: {}", symbol, e),
+        }
+    }
+
     async fn use_fixed_symbol(&mut self, symbol: String) -> Result<()> {
-        // Only send on first scan or if symbol changed
-        let should_notify = self.first_scan 
-            || self.current_symbol.as_ref().map(|s| &s.0) != Some(&symbol);
+        // Only send on startup or if symbol changed
+        let should_notify = self.current_symbol.as_ref().map(Symbol::as_str) != Some(symbol.as_str());
 
         if !should_notify {
             debug!("📌 Fixed symbol {} already active", symbol);
@@ -347,17 +455,7 @@ impl ScannerActor {
         info!("📌 Using fixed trading symbol: {}", symbol);
 
         // Fetch instrument specs
-        let specs = match self.client.get_instrument_info(&symbol).await {
-            Ok(info) => {
-                let specs = SymbolSpecs::from(info);
-                self.specs_cache.insert(specs.clone());
-                specs
-            }
-            Err(e) => {
-                error!("Failed to fetch specs for {}: {}", symbol, e);
-                self.specs_cache.get_or_default(&symbol)
-            }
-        };
+        let specs = self.specs_cache.get_or_fetch(&self.client, &symbol).await;
 
         // Get 24h price change (default to 0 for neutral)
         let price_change_24h = self.client.get_tickers("linear").await
@@ -369,7 +467,7 @@ impl ScannerActor {
 
         // Send switch command to MarketDataActor
         if let Err(e) = self.market_data_tx
-            .send(MarketDataMessage::SwitchSymbol(Symbol(symbol.clone())))
+            .send(MarketDataMessage::SwitchSymbol(Symbol::from(symbol.as_str())))
             .await
         {
             error!("Failed to send symbol switch: {}", e);
@@ -378,7 +476,7 @@ impl ScannerActor {
         // Send to StrategyEngine
         if let Err(e) = self.strategy_tx
             .send(StrategyMessage::SymbolChanged {
-                symbol: Symbol(symbol.clone()),
+                symbol: Symbol::from(symbol.as_str()),
                 specs,
                 price_change_24h,
             })
@@ -387,10 +485,192 @@ impl ScannerActor {
             error!("Failed to send symbol specs: {}", e);
         }
 
-        self.current_symbol = Some(Symbol(symbol));
-        self.first_scan = false;
+        self.send_warmup_ticks(&symbol).await;
+        self.send_warmup_candles(&symbol).await;
+
+        self.current_symbol = Some(Symbol::from(symbol));
+        Ok(())
+    }
+}
+
+impl ScannerActor {
+    /// ✅ SHARDS: Stream every TRADING_SYMBOLS symbol and give its shard specs and
+    /// warm-up once; later scans only keep the 24h change fresh
+    async fn use_shard_symbols(&mut self) -> Result<()> {
+        let config = self.config.clone();
+        let tickers = self.client.get_tickers("linear").await.ok();
+        let price_change_24h = |symbol: &str| {
+            tickers
+                .as_ref()
+                .and_then(|t| t.list.iter().find(|ticker| ticker.symbol == symbol))
+                .and_then(|ticker| ticker.price_24h_pcnt.parse::<f64>().ok())
+                .unwrap_or(0.0)
+        };
+
+        if self.current_symbol.is_some() {
+            for symbol in &config.trading_symbols {
+                if let Err(e) = self
+                    .strategy_tx
+                    .send(StrategyMessage::UpdateMarketStats {
+                        symbol: Symbol::from(symbol.as_str()),
+                        price_change_24h: price_change_24h(symbol),
+                    })
+                    .await
+                {
+                    debug!("Failed to send market stats update: {}", e);
+                }
+            }
+            return Ok(());
+        }
+
+        info!("🔀 Sharded symbols: {}", config.trading_symbols.join(", "));
+        for (i, symbol) in config.trading_symbols.iter().enumerate() {
+            let specs = self.specs_cache.get_or_fetch(&self.client, symbol).await;
+
+            // The first symbol is the "traded" one, the rest stream alongside (each watched for a stale feed)
+            let command = if i == 0 {
+                MarketDataMessage::SwitchSymbol(Symbol::from(symbol.as_str()))
+            } else {
+                MarketDataMessage::Subscribe(Symbol::from(symbol.as_str()))
+            };
+            if let Err(e) = self.market_data_tx.send(command).await {
+                error!("Failed to subscribe to {}: {}", symbol, e);
+            }
+
+            if let Err(e) = self
+                .strategy_tx
+                .send(StrategyMessage::SymbolChanged {
+                    symbol: Symbol::from(symbol.as_str()),
+                    specs,
+                    price_change_24h: price_change_24h(symbol),
+                })
+                .await
+            {
+                error!("Failed to send symbol specs: {}", e);
+            }
+
+            self.send_warmup_ticks(symbol).await;
+            self.send_warmup_candles(symbol).await;
+        }
+
+        self.current_symbol = config.trading_symbols.first().map(|s| Symbol::from(s.as_str()));
         Ok(())
     }
+
+    /// ✅ SPECS: Refetch the traded symbols' specs once their TTL runs out and
+    /// push them to the strategy if Bybit changed tick/lot sizes
+    async fn refresh_expired_specs(&self) {
+        let Some(current) = self.current_symbol.as_ref() else {
+            return;
+        };
+        // Sharded: every shard's symbol, otherwise just the traded one
+        let symbols: Vec<Symbol> = if self.config.trading_symbols.is_empty() {
+            vec![current.clone()]
+        } else {
+            self.config.trading_symbols.iter().map(|s| Symbol::from(s.as_str())).collect()
+        };
+
+        for symbol in &symbols {
+            if self.specs_cache.get(&symbol.0).is_some() {
+                continue;
+            }
+
+            let previous = self.specs_cache.get_stale(&symbol.0);
+            match self.specs_cache.refresh(&self.client, &symbol.0).await {
+                Ok(specs) if previous.as_ref() != Some(&specs) => {
+                    warn!("📏 Specs changed for {}: {:?} -> {:?}", symbol, previous, specs);
+                    if let Err(e) = self.strategy_tx.send(StrategyMessage::SpecsRefreshed(specs)).await {
+                        error!("Failed to send refreshed specs: {}", e);
+                    }
+                }
+                Ok(_) => debug!("📏 Specs for {} unchanged after refresh", symbol),
+                Err(e) => warn!("⚠️ Failed to refresh specs for {}: {}", symbol, e),
+            }
+        }
+    }
+
+    /// ✅ WARM-UP: Pre-fill the strategy's tick buffer from recent public trades,
+    /// so slow coins don't sit blind for minutes collecting 200 live ticks
+    async fn send_warmup_ticks(&self, symbol: &str) {
+        if self.config.warmup_trades == 0 {
+            return;
+        }
+
+        let trades = match self.client.get_recent_trades(symbol, self.config.warmup_trades).await {
+            Ok(trades) => trades,
+            Err(e) => {
+                warn!("⚠️ Failed to fetch recent trades for {} warm-up: {}", symbol, e);
+                return;
+            }
+        };
+
+        // Bybit returns newest first
+        let ticks: Vec<TradeTick> = trades.iter().rev().filter_map(warmup_tick).collect();
+        info!("🔥 Warm-up: {} recent trades for {}", ticks.len(), symbol);
+
+        if let Err(e) = self
+            .strategy_tx
+            .send(StrategyMessage::WarmupTicks { symbol: Symbol::from(symbol), ticks })
+            .await
+        {
+            error!("Failed to send warm-up ticks: {}", e);
+        }
+    }
+
+    /// ✅ WARM-UP: Recent 1m klines so ATR/EMA/regime are valid right after a switch
+    async fn send_warmup_candles(&self, symbol: &str) {
+        if self.config.warmup_klines == 0 {
+            return;
+        }
+
+        let klines = match self.client.get_klines(symbol, "1", self.config.warmup_klines).await {
+            Ok(klines) => klines,
+            Err(e) => {
+                warn!("⚠️ Failed to fetch klines for {} warm-up: {}", symbol, e);
+                return;
+            }
+        };
+
+        // Bybit returns newest first
+        let candles: Vec<Candle> = klines.iter().rev().filter_map(warmup_candle).collect();
+        info!("🔥 Warm-up: {} 1m klines for {}", candles.len(), symbol);
+
+        if let Err(e) = self
+            .strategy_tx
+            .send(StrategyMessage::WarmupCandles { symbol: Symbol::from(symbol), candles })
+            .await
+        {
+            error!("Failed to send warm-up candles: {}", e);
+        }
+    }
+}
+
+fn warmup_candle(kline: &Kline) -> Option<Candle> {
+    Some(Candle {
+        start: kline.start_time.parse().ok()?,
+        open: Decimal::from_str(&kline.open).ok()?,
+        high: Decimal::from_str(&kline.high).ok()?,
+        low: Decimal::from_str(&kline.low).ok()?,
+        close: Decimal::from_str(&kline.close).ok().filter(|c| *c > Decimal::ZERO)?,
+        volume: Decimal::from_str(&kline.volume).ok()?,
+    })
+}
+
+fn warmup_tick(trade: &RecentTrade) -> Option<TradeTick> {
+    let price = Decimal::from_str(&trade.price).ok().filter(|p| *p > Decimal::ZERO)?;
+    let size = Decimal::from_str(&trade.size).ok().filter(|s| *s > Decimal::ZERO)?;
+    let side = match trade.side.as_str() {
+        "Buy" => TradeSide::Buy,
+        "Sell" => TradeSide::Sell,
+        _ => return None,
+    };
+    Some(TradeTick {
+        symbol: Symbol::from(trade.symbol.as_str()),
+        price,
+        size,
+        timestamp: trade.time.parse().ok()?,
+        side,
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -400,3 +680,70 @@ struct ScoredCoin {
     turnover_24h: f64,
     price_change_24h: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::{metered_channel, OverflowPolicy};
+    use crate::health::HealthRegistry;
+    use crate::metrics::Metrics;
+    use serde_json::json;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn position(size: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({ "retCode": 0, "retMsg": "OK", "result": { "list": [
+            { "symbol": "SOLUSDT", "side": "Buy", "size": size, "avgPrice": "100", "unrealisedPnl": "0" }
+        ] } }))
+    }
+
+    #[tokio::test]
+    async fn test_adopted_pin_lifts_once_the_position_is_closed() {
+        let server = MockServer::start().await;
+        Mock::given(path("/v5/position/list")).respond_with(position("1")).up_to_n_times(1).mount(&server).await;
+        Mock::given(path("/v5/position/list")).respond_with(position("0")).mount(&server).await;
+
+        std::env::set_var("BYBIT_API_KEY", "test");
+        std::env::set_var("BYBIT_API_SECRET", "test");
+        let config = Config::from_env().unwrap();
+        let metrics = Metrics::new();
+        let policy = OverflowPolicy::Block { timeout: None };
+        let (market_data_tx, _market_data_rx) = metered_channel("market_data", 1, policy, &metrics);
+        let (strategy_tx, _strategy_rx) = metered_channel("strategy", 1, policy, &metrics);
+        let (_, command_rx) = metered_channel("scanner_cmd", 1, policy, &metrics);
+        let mut scanner = ScannerActor::new(
+            BybitClient::new("test".to_string(), "test".to_string(), server.uri()),
+            Arc::new(config.clone()),
+            market_data_tx,
+            strategy_tx,
+            command_rx,
+            SpecsCache::new(),
+            TempBlacklist::new(),
+            SymbolLists::fixed(&config),
+            HealthRegistry::new().register("scanner", Duration::from_secs(1)),
+        );
+
+        scanner.handle_command(ScannerMessage::AdoptSymbol(Symbol::from("SOLUSDT")));
+        scanner.release_closed_adoption().await;
+        assert_eq!(scanner.pinned_symbol.as_deref(), Some("SOLUSDT"));
+        scanner.release_closed_adoption().await;
+        assert_eq!(scanner.pinned_symbol, None);
+
+        // An operator pin stays put whatever the position
+        scanner.handle_command(ScannerMessage::PinSymbol(Some(Symbol::from("SOLUSDT"))));
+        scanner.release_closed_adoption().await;
+        assert_eq!(scanner.pinned_symbol.as_deref(), Some("SOLUSDT"));
+    }
+
+    #[test]
+    fn test_challenger_streak_resets_when_it_loses_the_top() {
+        let mut challenger = Challenger::default();
+        assert_eq!(challenger.observe(Some("SOLUSDT")), 1);
+        assert_eq!(challenger.observe(Some("SOLUSDT")), 2);
+        // Another coin on top starts over
+        assert_eq!(challenger.observe(Some("SUIUSDT")), 1);
+        // A scan where the current coin holds breaks the streak
+        assert_eq!(challenger.observe(None), 0);
+        assert_eq!(challenger.observe(Some("SUIUSDT")), 1);
+    }
+}
@@ -0,0 +1,138 @@
+//! Spread Gate in Ticks
+//!
+//! A fixed `MAX_SPREAD_BPS` means very different things per coin: on a $0.002
+//! coin a single tick can be 50 bps, so even the tightest book never passes,
+//! while on SOL 20 bps is a book falling apart. With `MAX_SPREAD_TICKS` set the
+//! limit is counted in the symbol's ticks instead: an entry needs a spread of
+//! at most that many ticks, or of `SPREAD_TYPICAL_MULTIPLE` times the symbol's
+//! typical spread (an EWMA over its orderbooks) when the coin normally trades
+//! wider than that. `MAX_SPREAD_BPS` then no longer applies. Once warmed up,
+//! books wider than the current limit don't feed the typical spread, so a
+//! blowout can't widen its own allowance.
+
+use crate::models::OrderBookSnapshot;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// EWMA span of the typical spread, and the books needed before it counts
+pub const TYPICAL_SPREAD_BOOKS: u32 = 200;
+
+/// Spread limit of the traded symbol, in ticks
+#[derive(Debug)]
+pub struct SpreadGate {
+    max_ticks: f64,
+    typical_multiple: f64,
+    /// EWMA of the spread in ticks
+    typical: Option<f64>,
+    books: u32,
+}
+
+impl SpreadGate {
+    pub fn new(max_ticks: u32, typical_multiple: f64) -> Self {
+        Self {
+            max_ticks: f64::from(max_ticks),
+            typical_multiple,
+            typical: None,
+            books: 0,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_ticks > 0.0
+    }
+
+    pub fn observe(&mut self, book: &OrderBookSnapshot, tick_size: Decimal) {
+        if !self.enabled() || tick_size <= Decimal::ZERO {
+            return;
+        }
+        let Some(ticks) = (book.spread() / tick_size).to_f64() else {
+            return;
+        };
+        // A spread we'd reject is abnormal, not typical
+        if self.typical_ticks().is_some() && ticks > self.limit_ticks() {
+            return;
+        }
+        let alpha = 2.0 / (f64::from(TYPICAL_SPREAD_BOOKS) + 1.0);
+        self.typical = Some(self.typical.map_or(ticks, |typical| typical + alpha * (ticks - typical)));
+        self.books = self.books.saturating_add(1);
+    }
+
+    /// Forget the previous symbol's spreads
+    pub fn reset(&mut self) {
+        self.typical = None;
+        self.books = 0;
+    }
+
+    /// Typical spread in ticks (None until `TYPICAL_SPREAD_BOOKS` books)
+    pub fn typical_ticks(&self) -> Option<f64> {
+        self.typical.filter(|_| self.books >= TYPICAL_SPREAD_BOOKS)
+    }
+
+    /// Widest spread an entry accepts, ticks
+    pub fn limit_ticks(&self) -> f64 {
+        let typical = self.typical_ticks().map_or(0.0, |typical| typical * self.typical_multiple);
+        self.max_ticks.max(typical)
+    }
+
+    /// The limit in bps at `book`'s mid (None = off, or no usable tick size)
+    pub fn limit_bps(&self, book: &OrderBookSnapshot, tick_size: Decimal) -> Option<f64> {
+        if !self.enabled() || tick_size <= Decimal::ZERO || book.mid_price.is_zero() {
+            return None;
+        }
+        let tick_bps = (tick_size / book.mid_price * Decimal::from(10_000)).to_f64()?;
+        Some(self.limit_ticks() * tick_bps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn book(bid: &str, ask: &str) -> OrderBookSnapshot {
+        OrderBookSnapshot::new(Symbol::from("XUSDT"), 0, d(bid), d(ask), d("100"), d("100"))
+    }
+
+    #[test]
+    fn test_limit_scales_with_tick_size() {
+        let gate = SpreadGate::new(3, 2.0);
+        // One tick on a $0.002 coin is 50 bps, on a $150 coin under 1 bps
+        let cheap = gate.limit_bps(&book("0.00199", "0.00201"), d("0.00001")).unwrap();
+        assert!((cheap - 150.0).abs() < 1e-9);
+        let sol = gate.limit_bps(&book("149.99", "150.01"), d("0.01")).unwrap();
+        assert!((sol - 2.0).abs() < 1e-9);
+
+        assert_eq!(SpreadGate::new(0, 2.0).limit_bps(&book("149.99", "150.01"), d("0.01")), None);
+        assert_eq!(gate.limit_bps(&book("149.99", "150.01"), Decimal::ZERO), None);
+    }
+
+    #[test]
+    fn test_typical_spread_widens_the_limit_after_warmup() {
+        let mut gate = SpreadGate::new(3, 2.0);
+        for _ in 0..TYPICAL_SPREAD_BOOKS - 1 {
+            gate.observe(&book("100.00", "100.05"), d("0.01"));
+        }
+        // Not warmed up: the tick floor alone
+        assert_eq!(gate.limit_ticks(), 3.0);
+
+        gate.observe(&book("100.00", "100.05"), d("0.01"));
+        assert!((gate.limit_ticks() - 10.0).abs() < 1e-9);
+
+        // A blowout past the limit leaves the typical spread alone
+        for _ in 0..TYPICAL_SPREAD_BOOKS {
+            gate.observe(&book("100.00", "101.00"), d("0.01"));
+        }
+        assert!((gate.limit_ticks() - 10.0).abs() < 1e-9);
+        // Spreads within the limit still move it
+        gate.observe(&book("100.00", "100.08"), d("0.01"));
+        assert!(gate.limit_ticks() > 10.0);
+
+        gate.reset();
+        assert_eq!(gate.typical_ticks(), None);
+        assert_eq!(gate.limit_ticks(), 3.0);
+    }
+}
@@ -1,29 +1,79 @@
-use crate::actors::messages::{ExecutionMessage, StrategyMessage};
-use crate::config::Config;
+use crate::actors::entry_pricing::EntryPricing;
+use crate::actors::execution::ExecutionSender;
+use crate::actors::index_divergence::IndexDivergence;
+use crate::actors::messages::{
+    ControlCommand, ExecutionMessage, NotifierMessage, ParamPatch, StrategyMessage, StrategyParams, StrategyStatus,
+};
+use crate::actors::position_drift;
+use crate::actors::position_monitor::ExitPlan;
+use crate::actors::reference_feed::ReferencePrices;
+use crate::actors::strategy_state::{StateMachine, StrategyState};
+use crate::actors::spread_gate::SpreadGate;
+use crate::actors::reward_risk::RewardRisk;
+use crate::actors::switch_ticks::SwitchTicks;
+use crate::actors::taker_flow::TakerFlow;
+use crate::channel::{MeteredReceiver, MeteredSender};
+use crate::clock::Clock;
+use crate::config::{Config, DriftAction, ROUND_TRIP_FEE_PERCENT};
+use crate::dataset::{SignalDataset, SignalFeatures, SignalOutcome, SignalSample};
+use crate::decision_log::{DecisionEntry, DecisionLine, DecisionLog};
+use crate::events::{EventBus, EventKind};
 use crate::exchange::SymbolSpecs;
+use crate::health::ActorHeartbeat;
+use crate::journal::{Journal, JournalEntry};
+use crate::metrics::StateMachineStats;
+use crate::model::SignalModel;
 use crate::models::*;
+use crate::notifications::{escape_html, format_duration, Alert, AlertLevel};
+use crate::risk_limits::{DailyTradeLimit, LossStreak, PositionGate, StreakAction, TempBlacklist, TradeCooldown};
+use crate::symbol_lists::SymbolLists;
+use crate::spsc::{RingReceiver, RingSender};
+use crate::webhook::ExternalSignal;
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio::time::{interval, Duration, Instant};
-use tracing::{debug, error, info, warn};
-
-/// ✅ FIXED: Proper state machine for order lifecycle
-#[derive(Debug, Clone, PartialEq)]
-enum StrategyState {
-    Idle,                 // No position, no order
-    OrderPending,         // Order sent, waiting for confirmation
-    PositionOpen,         // Position confirmed by exchange
-    ClosingPosition,      // Close order sent, waiting for confirmation
-    SwitchingSymbol,      // ✅ FIX BUG #1: Closing position before symbol switch
+use tracing::{debug, error, info, info_span, warn, Instrument, Span};
+
+/// Position verification period (also bounds how long the loop sits idle)
+pub const POSITION_VERIFY_INTERVAL_SECS: u64 = 10;
+
+/// Stop loss and take profit of every momentum entry, % from entry (1:2 R/R).
+/// The hard stop sits HARD_STOP_GAP_PERCENT beyond this stop, not STOP_LOSS_PERCENT
+pub const ENTRY_STOP_LOSS_PERCENT: f64 = 0.35;
+const ENTRY_TAKE_PROFIT_PERCENT: f64 = 0.70;
+
+/// Minimum spacing of position_pnl events
+const PNL_EVENT_INTERVAL_MS: u64 = 1000;
+
+/// 1m candles for ATR/EMA/regime (matches the "1" kline interval used for warm-up)
+const CANDLE_INTERVAL_MS: i64 = 60_000;
+const CANDLE_CAPACITY: usize = 200;
+
+/// ✅ DUPLICATE SIGNAL GUARD: Direction + VWAP level a trade was entered on
+#[derive(Debug, Clone, Copy)]
+struct SignalFingerprint {
+    bullish: bool,
+    vwap: Decimal,
+}
+
+/// Entry fill of the trade currently open (for the round-trip trade record)
+#[derive(Debug, Clone)]
+struct OpenTrade {
+    entry: Fill,
+    opened_at: chrono::DateTime<chrono::Utc>,
+    started: Instant,
 }
 
 /// StrategyEngine - Impulse/Momentum Scalping with Smart Order Routing
 pub struct StrategyEngine {
     config: Arc<Config>,
-    message_rx: mpsc::Receiver<StrategyMessage>,
-    execution_tx: mpsc::Sender<ExecutionMessage>,
+    message_rx: MeteredReceiver<StrategyMessage>,
+    /// Orderbooks and trades from MarketDataActor (everything else uses `message_rx`)
+    market_rx: RingReceiver<StrategyMessage>,
+    execution_tx: ExecutionSender,
+    notifier_tx: MeteredSender<NotifierMessage>,
 
     // State
     current_symbol: Option<Symbol>,
@@ -33,16 +83,23 @@ pub struct StrategyEngine {
 
     // Tick buffer for momentum calculation (expanded for better trend detection)
     tick_buffer: RingBuffer<TradeTick>,
+    /// f64 mirror of tick_buffer for indicator math (Some with FAST_MATH)
+    fast_ticks: Option<RingBuffer<FastTick>>,
+
+    // ✅ INDICATORS: 1m candles for ATR/EMA/regime (warm-started from REST klines)
+    candles: CandleSeries,
 
     // Entry conditions
     momentum_threshold: f64,
+    /// Join / improve / cross per entry
+    pricing: EntryPricing,
 
     // ✅ PUMP PROTECTION: 24h price change for global trend filter
     /// Stores 24h price change percentage (e.g., 0.25 = +25%, -0.15 = -15%)
     price_change_24h: Option<f64>,
 
     // ✅ FIXED: Proper state machine replaces simple boolean
-    state: StrategyState,
+    state: StateMachine,
 
     // ✅ FIX BUG #1: Store pending symbol change until position is closed
     pending_symbol_change: Option<(Symbol, SymbolSpecs, f64)>, // (symbol, specs, price_change_24h)
@@ -56,6 +113,8 @@ pub struct StrategyEngine {
     // ✅ IMPROVEMENT #3: Trade cooldown - prevent revenge trading
     /// When the last trade was closed
     last_trade_time: Option<Instant>,
+    /// The same in wall-clock time, persisted so a restart keeps the cooldown
+    cooldown: TradeCooldown,
     /// Cooldown duration in seconds (configurable)
     trade_cooldown_secs: u64,
 
@@ -97,36 +156,200 @@ pub struct StrategyEngine {
 
     // ⚡ PHASE 3: DYNAMIC BLACKLIST - Prevent revenge trading
     /// Track consecutive losses per symbol for temporary blacklist
+    #[allow(dead_code)]
     symbol_consecutive_losses: std::collections::HashMap<String, u32>,
-    /// Temporarily blacklisted symbols with blacklist start time
-    temp_blacklist: std::collections::HashMap<String, Instant>,
+    /// Temporarily blacklisted symbols (shared with the scanner)
+    temp_blacklist: TempBlacklist,
+    /// BLACKLIST_SYMBOLS/WHITELIST_SYMBOLS, with runtime edits once `set_symbol_lists` shares the live lists
+    symbols: SymbolLists,
+    /// ✅ AUTO-BLACKLIST: Realized net PnL per symbol since start / its last blacklisting
+    symbol_realized_pnl: std::collections::HashMap<String, Decimal>,
+
+    // ✅ DUPLICATE SIGNAL GUARD: Don't re-enter the level that just stopped us out
+    /// Signal the current trade was entered on
+    entry_signal: Option<SignalFingerprint>,
+    /// Signal of the last closed trade and when it closed
+    last_exit_signal: Option<(SignalFingerprint, Instant)>,
+
+    // ✅ PERFORMANCE ATTRIBUTION: Confirmations / 24h change / ATR the open trade was entered with
+    entry_context: Option<EntryContext>,
+
+    // ✅ SIGNAL DATASET: Evaluated signals + outcomes for offline research
+    dataset: SignalDataset,
+    /// Features of the signal being confirmed, as first seen
+    pending_signal_sample: Option<SignalSample>,
+
+    // ✅ DECISION LOG: Every input and decision, for after-the-fact trade reconstruction
+    decisions: DecisionLog,
+
+    // ✅ SIGNAL MODEL: Confirmed signals must score above SIGNAL_MODEL_THRESHOLD
+    signal_model: Option<SignalModel>,
+
+    // ✅ EVENTS: Signals, orders, fills, closed trades and state changes for outbound integrations
+    events: EventBus,
+    /// tick_counter at the last tick_stats event
+    stats_tick_mark: usize,
+    /// Throttles position_pnl events
+    last_pnl_event: Option<Instant>,
+    /// Throttles the periodic PnL debug log (per engine, shards run concurrently)
+    last_pnl_log: Option<Instant>,
+
+    // ✅ TRADE NOTIFICATIONS: Entry fill of the open trade
+    open_trade: Option<OpenTrade>,
+    /// Closed trades are appended here
+    journal: Journal,
+
+    // ✅ KILL SWITCH: Operator halted trading, no new entries until restart
+    kill_switch_engaged: bool,
+    /// Operator pause: no new entries until resumed, the open position is still managed
+    manual_pause: bool,
+
+    // ✅ DAILY TRADE LIMIT: Entries per UTC day (persisted)
+    daily_trades: DailyTradeLimit,
+
+    /// Latest position verification request; a fill moves it on, so answers asked before it are stale
+    position_query: u64,
+
+    // ✅ LOSS STREAK: Pause, then halt, after consecutive losses (persisted)
+    loss_streak: LossStreak,
+
+    // ✅ SHARED RISK GATE: Position slots shared with the other shards (None = single engine)
+    position_gate: Option<PositionGate>,
+    /// This shard holds a slot (entry sent, not flat yet)
+    holds_position_slot: bool,
+
+    // ✅ WATCHDOG: Progress reporting
+    heartbeat: ActorHeartbeat,
+
+    // ✅ POSITION MONITOR: Exit plan of the open position (None = nothing to watch)
+    exit_plan_tx: watch::Sender<Option<ExitPlan>>,
+
+    // ✅ HEARTBEAT: Liveness info for the periodic status alert
+    started_at: Instant,
+    last_tick_at: Option<Instant>,
+
+    // ✅ TRACING: Correlation span of the trade in flight (entry → fills → close)
+    active_trade_span: Option<Span>,
+    /// Correlation ID of `active_trade_span` (decision log lines)
+    trade_cid: Option<String>,
+    trade_seq: u64,
+
+    // ✅ REPLAY: Calendar time (recorded time during a replay)
+    clock: Clock,
+
+    // ✅ SHADOW: Copy of every market input for each shadow twin (shadow and arena profiles)
+    shadow_txs: Vec<RingSender<StrategyMessage>>,
+
+    // ✅ REFERENCE FEED: Binance lead-lag features (None = BINANCE_REFERENCE off)
+    reference: Option<ReferencePrices>,
+
+    // ✅ INDEX DIVERGENCE: Last/mark vs. index of the current symbol (tickers stream)
+    index_prices: IndexDivergence,
+
+    // ✅ TAKER FLOW: Rolling taker buy/sell volume of the current symbol
+    taker_flow: TakerFlow,
+
+    // ✅ SPREAD IN TICKS: Spread limit from the tick size and typical spread (MAX_SPREAD_TICKS)
+    spread_gate: SpreadGate,
+
+    // ✅ SWITCH TICKS: Ticks of other symbols, replayed if one of them becomes the traded symbol
+    switch_ticks: SwitchTicks,
+}
+
+/// Last trade of the previous run as an `Instant`, so the cooldown check carries on from it
+fn resumed_cooldown(cooldown: &TradeCooldown) -> Option<Instant> {
+    let since = cooldown.since_last_trade(chrono::Utc::now())?;
+    Instant::now().checked_sub(since)
+}
+
+/// Decision log line in the strategy's current context (free so it can be
+/// built while the open position is borrowed)
+fn decision_line(
+    at: chrono::DateTime<chrono::Utc>,
+    state: &StateMachine,
+    cid: Option<&String>,
+    symbol: Option<Symbol>,
+    entry: DecisionEntry,
+) -> DecisionLine {
+    DecisionLine {
+        at,
+        symbol,
+        state: format!("{:?}", state),
+        cid: cid.cloned(),
+        entry,
+    }
 }
 
 impl StrategyEngine {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         config: Arc<Config>,
-        message_rx: mpsc::Receiver<StrategyMessage>,
-        execution_tx: mpsc::Sender<ExecutionMessage>,
+        message_rx: MeteredReceiver<StrategyMessage>,
+        market_rx: RingReceiver<StrategyMessage>,
+        execution_tx: ExecutionSender,
+        notifier_tx: MeteredSender<NotifierMessage>,
+        journal: Journal,
+        temp_blacklist: TempBlacklist,
+        signal_model: Option<SignalModel>,
+        events: EventBus,
+        heartbeat: ActorHeartbeat,
     ) -> Self {
         let momentum_threshold = config.momentum_threshold / 100.0; // Convert percentage to decimal
+        let pricing = EntryPricing::from_config(&config);
+        let mut state = StateMachine::new();
+        state.set_stuck_after(Duration::from_secs(config.stuck_state_timeout_secs));
+        let daily_trades = DailyTradeLimit::load(
+            config.data_path("daily_trades.json"),
+            config.max_trades_per_day,
+            chrono::Utc::now(),
+        );
+        let cooldown = TradeCooldown::load(config.data_path("trade_cooldown.json"));
+        let last_trade_time = resumed_cooldown(&cooldown);
+        let fast_ticks = config.fast_math.then(|| RingBuffer::new(300));
+        let taker_flow = TakerFlow::new(config.taker_flow_window_secs);
+        let spread_gate = SpreadGate::new(config.max_spread_ticks, config.spread_typical_multiple);
+        let symbols = SymbolLists::fixed(&config);
+        let dataset = SignalDataset::new(
+            config.signal_dataset.then(|| config.data_path("signals.jsonl")),
+            Duration::from_secs(config.signal_outcome_horizon_secs),
+        );
+        let decisions = DecisionLog::new(config.decision_log.then(|| config.data_path("decisions.jsonl")));
+        let loss_streak = LossStreak::load(
+            config.data_path("loss_streak.json"),
+            config.loss_streak_pause_after,
+            chrono::Duration::minutes(config.loss_streak_pause_mins as i64),
+            config.loss_streak_halt_after,
+        );
+        if loss_streak.is_halted() {
+            warn!(
+                "🛑 Loss streak halt carried over from the last run ({} losses), send /resume to trade",
+                loss_streak.consecutive_losses()
+            );
+        }
         Self {
             config,
             message_rx,
+            market_rx,
             execution_tx,
+            notifier_tx,
             current_symbol: None,
             current_position: None,
             last_orderbook: None,
             current_specs: None,
             tick_buffer: RingBuffer::new(300), // ✅ EXPANDED: 300 ticks for better trend detection
+            fast_ticks,
+            candles: CandleSeries::new(CANDLE_INTERVAL_MS, CANDLE_CAPACITY),
             momentum_threshold, // ✅ CONFIGURABLE: Read from env MOMENTUM_THRESHOLD (default 0.1%)
-            state: StrategyState::Idle,
+            pricing,
+            state,
             pending_symbol_change: None,
             price_change_24h: None, // ✅ PUMP PROTECTION: Will be set on symbol change
             // ✅ IMPROVEMENT #1: Confirmation delay
             pending_signal: None,
             confirmation_count: 0,
             // ✅ IMPROVEMENT #3: Trade cooldown (30 seconds)
-            last_trade_time: None,
+            last_trade_time,
+            cooldown,
             trade_cooldown_secs: 30,
             // ✅ FIX INFINITE CLOSE LOOP: Initialize rate limit
             last_close_attempt: None,
@@ -146,150 +369,128 @@ impl StrategyEngine {
             last_api_error_time: None,
             is_paused: false,
             symbol_consecutive_losses: std::collections::HashMap::new(),
-            temp_blacklist: std::collections::HashMap::new(),
+            temp_blacklist,
+            symbols,
+            symbol_realized_pnl: std::collections::HashMap::new(),
+            entry_signal: None,
+            last_exit_signal: None,
+            entry_context: None,
+            dataset,
+            decisions,
+            pending_signal_sample: None,
+            signal_model,
+            events,
+            stats_tick_mark: 0,
+            last_pnl_event: None,
+            last_pnl_log: None,
+            open_trade: None,
+            journal,
+            kill_switch_engaged: false,
+            manual_pause: false,
+            daily_trades,
+            position_query: 0,
+            loss_streak,
+            position_gate: None,
+            holds_position_slot: false,
+            heartbeat,
+            exit_plan_tx: watch::channel(None).0,
+            started_at: Instant::now(),
+            last_tick_at: None,
+            active_trade_span: None,
+            trade_cid: None,
+            trade_seq: 0,
+            clock: Clock::default(),
+            shadow_txs: Vec::new(),
+            reference: None,
+            index_prices: IndexDivergence::default(),
+            taker_flow,
+            spread_gate,
+            switch_ticks: SwitchTicks::default(),
         }
     }
 
+    /// ✅ SHARDS: Run as the `symbol` shard: entries need a slot from `gate` and count
+    /// against the shared `daily_trades`; the loss streak and decision log are kept per symbol
+    pub fn set_shard(&mut self, symbol: &Symbol, gate: PositionGate, daily_trades: DailyTradeLimit) {
+        self.position_gate = Some(gate);
+        self.daily_trades = daily_trades;
+        if self.config.decision_log {
+            self.decisions = DecisionLog::new(Some(self.config.data_path(&format!("decisions_{}.jsonl", symbol))));
+        }
+        self.loss_streak = LossStreak::load(
+            self.config.data_path(&format!("loss_streak_{}.json", symbol)),
+            self.config.loss_streak_pause_after,
+            chrono::Duration::minutes(self.config.loss_streak_pause_mins as i64),
+            self.config.loss_streak_halt_after,
+        );
+        self.cooldown = TradeCooldown::load(self.config.data_path(&format!("trade_cooldown_{}.json", symbol)));
+        self.last_trade_time = resumed_cooldown(&self.cooldown);
+    }
+
+    /// ✅ STATE MACHINE: Count transitions and stuck states into `stats` (see `Metrics::register_state_machine`)
+    pub fn set_state_stats(&mut self, stats: Arc<StateMachineStats>) {
+        self.state.set_stats(stats);
+    }
+
+    /// ✅ SYMBOL LISTS: Live black/whitelist shared with the scanner, Telegram and the control API
+    pub fn set_symbol_lists(&mut self, symbols: SymbolLists) {
+        self.symbols = symbols;
+    }
+
+    /// ✅ REFERENCE FEED: Binance lead-lag features for the dataset and the entry filter
+    pub fn set_reference(&mut self, reference: ReferencePrices) {
+        self.reference = Some(reference);
+    }
+
+    /// ✅ SHADOW: Also pass a copy of every market input to `tx` (a shadow twin's ring)
+    pub fn add_shadow(&mut self, tx: RingSender<StrategyMessage>) {
+        self.shadow_txs.push(tx);
+    }
+
+    /// Exit plans for the position monitor
+    pub fn exit_plans(&self) -> watch::Receiver<Option<ExitPlan>> {
+        self.exit_plan_tx.subscribe()
+    }
+
     pub async fn run(mut self) {
         info!("⚡ StrategyEngine started");
+        self.log_decision(DecisionEntry::Started);
 
         // ✅ HFT OPTIMIZATION: Position verification every 10 seconds (was 60)
         // Faster detection of API desync, flash crashes, unexpected liquidations
-        let mut position_verify_interval = interval(Duration::from_secs(10));
+        let mut position_verify_interval = interval(Duration::from_secs(POSITION_VERIFY_INTERVAL_SECS));
+
+        // ✅ HEARTBEAT: Periodic "still alive" status (0 = disabled)
+        let heartbeat_enabled = self.config.heartbeat_interval_secs > 0;
+        let mut heartbeat_interval = interval(Duration::from_secs(self.config.heartbeat_interval_secs.max(1)));
+        heartbeat_interval.tick().await; // First tick fires immediately, skip it
 
         loop {
+            self.heartbeat.beat();
             tokio::select! {
                 // Handle incoming messages
-                Some(msg) = self.message_rx.recv() => {
-                    match msg {
-                        StrategyMessage::OrderBook(snapshot) => {
-                            self.handle_orderbook(snapshot).await;
-                        }
-                        StrategyMessage::Trade(tick) => {
-                            self.handle_trade(tick).await;
-                        }
-                        StrategyMessage::PositionUpdate(position) => {
-                            self.current_position = position.clone();
-                            // ✅ FIXED: Update state machine based on position
-                            if position.is_some() {
-                                info!("📍 Position confirmed, transitioning to PositionOpen");
-                                self.state = StrategyState::PositionOpen;
-                                // ✅ TIME-BASED EXIT: helper
-                                if self.position_start_time.is_none() {
-                                    self.position_start_time = Some(Instant::now());
-                                }
-                            } else if self.state == StrategyState::ClosingPosition {
-                                info!("✅ Position closed, transitioning to Idle");
-                                // ✅ IMPROVEMENT #3: Start trade cooldown
-                                self.last_trade_time = Some(Instant::now());
-                                // ✅ FIX MEMORY LOSS BUG: Clear dynamic risk when position closes
-                                self.active_dynamic_risk = None;
-                                // ✅ FIX BUG #18: Clear close attempt timestamp
-                                self.last_close_attempt = None;
-                                // ✅ Reset time tracker
-                                self.position_start_time = None;
-                                // ✅ CLEANUP: Reset trailing stop state
-                                self.is_momentum_trade = false;
-                                self.peak_pnl_percent = 0.0;
-                                self.state = StrategyState::Idle;
-                            } else if self.state == StrategyState::SwitchingSymbol {
-                                // ✅ FIX BUG #1: Now complete the pending symbol change
-                                info!("✅ Position closed during symbol switch, completing switch...");
-                                // ✅ IMPROVEMENT #3: Start trade cooldown
-                                self.last_trade_time = Some(Instant::now());
-                                // ✅ FIX MEMORY LOSS BUG: Clear dynamic risk when position closes
-                                self.active_dynamic_risk = None;
-                                // ✅ FIX BUG #18: Clear close attempt timestamp
-                                self.last_close_attempt = None;
-                                // ✅ CLEANUP: Reset trailing stop state
-                                self.is_momentum_trade = false;
-                                self.peak_pnl_percent = 0.0;
-                                if let Some((new_symbol, specs, price_change_24h)) = self.pending_symbol_change.take() {
-                                    self.complete_symbol_switch(new_symbol, specs, price_change_24h);
-                                } else {
-                                    warn!("SwitchingSymbol state but no pending change!");
-                                    self.state = StrategyState::Idle;
-                                }
-                            } else if position.is_none() && matches!(self.state, StrategyState::PositionOpen | StrategyState::SwitchingSymbol) {
-                                // ✅ FIX BUG #16 (CRITICAL): Only reset if position disappeared in states where we HAVE a position
-                                // CRITICAL STATES TO CHECK:
-                                // - PositionOpen: Position should exist, if None = liquidation/margin call
-                                // - SwitchingSymbol: We're closing position, if None = position closed
-                                //
-                                // DO NOT CHECK in these states:
-                                // - Idle: No position expected (normal)
-                                // - OrderPending: Position doesn't exist yet (order not filled)
-                                // - ClosingPosition: Position disappearing is EXPECTED
-                                warn!(
-                                    "⚠️  Position disappeared unexpectedly in state {:?} (liquidation? margin call?). Resetting to Idle.",
-                                    self.state
-                                );
-                                self.state = StrategyState::Idle;
-                                self.active_dynamic_risk = None;
-                                self.last_trade_time = Some(Instant::now());
-                            }
-                        }
-                        StrategyMessage::SymbolChanged { symbol: new_symbol, specs, price_change_24h } => {
-                            self.handle_symbol_change(new_symbol, specs, price_change_24h).await;
-                        }
-                        // ✅ CRITICAL: Feedback from execution with state transitions
-                        StrategyMessage::OrderFilled(symbol) => {
-                            info!("✅ Order filled for {}, transitioning state", symbol);
-                            match self.state {
-                                StrategyState::OrderPending => {
-                                    // Entry order filled - wait for PositionUpdate
-                                    debug!("Entry order filled, waiting for PositionUpdate");
-                                }
-                                StrategyState::ClosingPosition => {
-                                    // Close order filled
-                                    info!("Close order filled, transitioning to Idle");
-                                    // ✅ Start cooldown timer
-                                    self.last_trade_time = Some(Instant::now());
-                                    // ✅ FIX MEMORY LOSS BUG: Clear dynamic risk when position closes
-                                    self.active_dynamic_risk = None;
-                                    self.state = StrategyState::Idle;
-                                    self.current_position = None;
-                                }
-                                _ => {
-                                    warn!("Received OrderFilled in unexpected state: {:?}", self.state);
-                                }
-                            }
-                        }
-                        StrategyMessage::OrderFailed(error) => {
-                            warn!("❌ Order failed: {}, transitioning to Idle", error);
-                            self.state = StrategyState::Idle;
-                            self.current_position = None;
-                            // ✅ FIX MEMORY LEAK: Clear dynamic risk on order failure
-                            self.active_dynamic_risk = None;
-                            // Reset confirmation state to avoid stale signals
-                            self.pending_signal = None;
-                            self.confirmation_count = 0;
-                        }
-                        // ✅ HARMONY: Handle live market stats update
-                        StrategyMessage::UpdateMarketStats { symbol, price_change_24h } => {
-                            // Only update if it matches current symbol
-                            if let Some(ref current) = self.current_symbol {
-                                if *current == symbol {
-                                    // Log only if change is significant (to avoid log spam)
-                                    let old_change = self.price_change_24h.unwrap_or(0.0);
-                                    if (old_change - price_change_24h).abs() > 0.05 {
-                                        info!("📊 Market Update for {}: 24h change {:.2}% -> {:.2}%", 
-                                              symbol, old_change * 100.0, price_change_24h * 100.0);
-                                    }
-                                    self.price_change_24h = Some(price_change_24h);
-                                }
-                            }
-                        }
-                    }
+                // ✅ CONFLATION: A backlog of orderbooks is skipped down to the latest per symbol
+                Some(msg) = self.message_rx.recv_conflated(StrategyMessage::conflation_key) => {
+                    self.process(msg).await;
+                }
+
+                // ✅ SPSC: Orderbooks and trades arrive on the lock-free market data ring
+                Some(msg) = self.market_rx.recv_conflated(StrategyMessage::conflation_key) => {
+                    self.process(msg).await;
                 }
 
                 // ✅ FIXED: Periodic position verification (prevents desync)
                 _ = position_verify_interval.tick() => {
-                    if let Some(ref symbol) = self.current_symbol {
+                    self.publish_tick_stats();
+                    self.check_stuck_state().await;
+                    self.decisions.flush();
+                    if let Some(symbol) = self.current_symbol.clone() {
                         debug!("🔍 Verifying position for {}", symbol);
+                        self.position_query += 1;
                         if let Err(e) = self
                             .execution_tx
-                            .send(ExecutionMessage::GetPosition(symbol.clone()))
+                            .send(ExecutionMessage::GetPosition { symbol, query: self.position_query })
+                            .instrument(self.trade_span())
                             .await
                         {
                             warn!("Failed to request position verification: {}", e);
@@ -297,6 +498,10 @@ impl StrategyEngine {
                     }
                 }
 
+                _ = heartbeat_interval.tick(), if heartbeat_enabled => {
+                    self.send_heartbeat();
+                }
+
                 // Channel closed
                 else => {
                     info!("StrategyEngine message channel closed, shutting down");
@@ -306,6 +511,218 @@ impl StrategyEngine {
         }
     }
 
+    /// ✅ REPLAY: Calendar time from `clock`, decision log kept in memory
+    /// (`replay` drives `process` directly instead of `run`)
+    #[cfg(any(test, feature = "replay"))]
+    pub(crate) fn set_replay(&mut self, clock: Clock) {
+        self.clock = clock;
+        self.decisions = DecisionLog::in_memory();
+    }
+
+    /// Decision log lines since the last call (replay only)
+    #[cfg(any(test, feature = "replay"))]
+    pub(crate) fn take_decision_lines(&mut self) -> Vec<DecisionLine> {
+        self.decisions.take_lines()
+    }
+
+    /// One message plus the bookkeeping every message gets (span, state events, exit plan)
+    pub(crate) async fn process(&mut self, msg: StrategyMessage) {
+        // ✅ TRACING: Everything a trade triggers logs under its trade{cid=...} span
+        let span = self.trade_span();
+        let previous_state = self.state.get();
+        if self.is_stale_position_update(&msg) {
+            return;
+        }
+        self.log_input(&msg);
+        for shadow_tx in &mut self.shadow_txs {
+            if let Some(copy) = msg.shadow_copy() {
+                // A shadow that can't keep up loses data, the live engine never waits for it
+                if let Err(e) = shadow_tx.try_send(copy) {
+                    debug!("Dropped shadow input: {}", e);
+                }
+            }
+        }
+        self.handle_message(msg).instrument(span).await;
+        if self.state != previous_state {
+            let (from, to) = (format!("{:?}", previous_state), format!("{:?}", self.state));
+            self.log_decision(DecisionEntry::StateChanged { from: from.clone(), to: to.clone() });
+            self.events.publish(EventKind::StateChanged {
+                symbol: self.current_symbol.clone(),
+                from,
+                to,
+            });
+        }
+        if self.state == StrategyState::Idle {
+            self.active_trade_span = None;
+            self.trade_cid = None;
+            self.release_position_slot();
+        }
+        self.sync_exit_plan();
+    }
+
+    async fn handle_message(&mut self, msg: StrategyMessage) {
+        match msg {
+            StrategyMessage::OrderBook(snapshot) => {
+                self.handle_orderbook(snapshot).await;
+            }
+            StrategyMessage::Trade(tick) => {
+                self.handle_trade(tick).await;
+            }
+            StrategyMessage::Ticker(ticker) => {
+                if self.current_symbol.as_ref() == Some(&ticker.symbol) {
+                    self.index_prices.apply(&ticker, Instant::now());
+                }
+            }
+            StrategyMessage::WarmupTicks { symbol, ticks } => {
+                self.apply_warmup_ticks(symbol, ticks);
+            }
+            StrategyMessage::WarmupCandles { symbol, candles } => {
+                self.apply_warmup_candles(symbol, candles);
+            }
+            StrategyMessage::PositionUpdate { position, .. } => {
+                let drifted = self.check_position_drift(position.as_ref());
+                self.handle_position_update(position);
+                if drifted && self.config.position_drift_action == DriftAction::Flatten {
+                    self.close_open_position(ExitReason::PositionDrift).await;
+                }
+            }
+            StrategyMessage::SymbolChanged { symbol: new_symbol, specs, price_change_24h } => {
+                self.handle_symbol_change(new_symbol, specs, price_change_24h).await;
+            }
+            StrategyMessage::SpecsRefreshed(specs) => {
+                if self.current_symbol.as_ref().is_some_and(|s| s.as_str() == specs.symbol) {
+                    info!("📏 Specs updated for {}: qty_step={}, tick_size={}",
+                          specs.symbol, specs.qty_step, specs.tick_size);
+                    self.current_specs = Some(specs);
+                } else if let Some((symbol, pending_specs, _)) = self.pending_symbol_change.as_mut() {
+                    if symbol.as_str() == specs.symbol {
+                        *pending_specs = specs;
+                    }
+                }
+            }
+            // ✅ CRITICAL: Feedback from execution with state transitions
+            StrategyMessage::OrderFilled(fill) => {
+                info!("✅ Order filled for {}, transitioning state", fill.symbol);
+                self.position_query += 1;
+                match self.state.get() {
+                    StrategyState::OrderPending => {
+                        // Entry order filled - wait for PositionUpdate
+                        debug!("Entry order filled, waiting for PositionUpdate");
+                        self.reset_api_errors();
+                        self.notify(Alert::trade_opened(&fill, self.config.trading_mode));
+                        self.events.publish(EventKind::EntryFilled { fill: fill.clone() });
+                        self.open_trade = Some(OpenTrade {
+                            entry: fill,
+                            opened_at: self.clock.now(),
+                            started: Instant::now(),
+                        });
+                        // ✅ DAILY TRADE LIMIT: Count the entry, say so once the allowance is used up
+                        if self.daily_trades.record_entry(self.clock.now()) {
+                            let max = self.daily_trades.max_per_day();
+                            info!("📅 Daily trade limit reached ({} entries), no new positions until 00:00 UTC", max);
+                            self.notify(Alert::info(format!(
+                                "📅 <b>Daily trade limit reached</b>\n{} entries today, no new positions until 00:00 UTC",
+                                max
+                            )));
+                        }
+                    }
+                    StrategyState::ClosingPosition => {
+                        // Close order filled
+                        info!("Close order filled, transitioning to Idle");
+                        // ✅ Start cooldown timer
+                        self.start_cooldown();
+                        self.record_exit_signal();
+                        // ✅ FIX MEMORY LOSS BUG: Clear dynamic risk when position closes
+                        self.active_dynamic_risk = None;
+                        self.state.transition(StrategyState::Idle, "close order filled");
+                        self.current_position = None;
+                    }
+                    _ => {
+                        warn!("Received OrderFilled in unexpected state: {:?}", self.state);
+                    }
+                }
+            }
+            StrategyMessage::CloseFilled { fill, reason } => {
+                self.position_query += 1;
+                self.handle_close_filled(fill, reason);
+            }
+            StrategyMessage::MonitorExit { symbol, reason, price } => {
+                self.handle_monitor_exit(symbol, reason, price);
+            }
+            StrategyMessage::OrderFailed { reason, retryable, .. } => {
+                self.handle_order_failed(reason, retryable);
+            }
+            StrategyMessage::Reconciled { symbol, position } => {
+                self.handle_reconciled(symbol, position);
+            }
+            // ✅ HARMONY: Handle live market stats update
+            StrategyMessage::UpdateMarketStats { symbol, price_change_24h } => {
+                // Only update if it matches current symbol
+                if let Some(ref current) = self.current_symbol {
+                    if *current == symbol {
+                        // Log only if change is significant (to avoid log spam)
+                        let old_change = self.price_change_24h.unwrap_or(0.0);
+                        if (old_change - price_change_24h).abs() > 0.05 {
+                            info!("📊 Market Update for {}: 24h change {:.2}% -> {:.2}%", 
+                                  symbol, old_change * 100.0, price_change_24h * 100.0);
+                        }
+                        self.price_change_24h = Some(price_change_24h);
+                    }
+                }
+            }
+            StrategyMessage::Control(command) => {
+                self.handle_control(command).await;
+            }
+            StrategyMessage::ExternalSignal(signal) => {
+                self.handle_external_signal(signal).await;
+            }
+            StrategyMessage::Status(reply) => {
+                let _ = reply.send(self.status());
+            }
+            StrategyMessage::PatchParams(patch) => {
+                self.apply_params(patch);
+            }
+        }
+    }
+
+    /// Span of the trade in flight, or a disabled span when flat
+    fn trade_span(&self) -> Span {
+        self.active_trade_span.clone().unwrap_or_else(Span::none)
+    }
+
+    /// Fresh `trade{cid=...}` span for a confirmed signal
+    fn new_trade_span(&mut self) -> Span {
+        self.trade_seq += 1;
+        let cid = format!("{:x}-{}", self.clock.now().timestamp_millis(), self.trade_seq);
+        let symbol = self.current_symbol.as_ref().map(Symbol::as_str).unwrap_or("-");
+        let span = info_span!("trade", cid = %cid, symbol = %symbol);
+        self.trade_cid = Some(cid);
+        span
+    }
+
+    /// ✅ DECISION LOG: What came in, before it is handled
+    fn log_input(&mut self, msg: &StrategyMessage) {
+        if !self.decisions.is_enabled() {
+            return;
+        }
+        if let Some(entry) = DecisionEntry::input(msg) {
+            let symbol = msg.symbol().map(Symbol::from).or_else(|| self.current_symbol.clone());
+            self.write_decision_line(symbol, entry);
+        }
+    }
+
+    /// ✅ DECISION LOG: What the strategy decided
+    fn log_decision(&mut self, entry: DecisionEntry) {
+        if self.decisions.is_enabled() {
+            self.write_decision_line(self.current_symbol.clone(), entry);
+        }
+    }
+
+    fn write_decision_line(&mut self, symbol: Option<Symbol>, entry: DecisionEntry) {
+        let line = decision_line(self.clock.now(), &self.state, self.trade_cid.as_ref(), symbol, entry);
+        self.decisions.record(line);
+    }
+
     async fn handle_symbol_change(&mut self, new_symbol: Symbol, specs: SymbolSpecs, price_change_24h: f64) {
         info!("🔄 Symbol change requested: {} (qty_step: {}, tick_size: {}, 24h: {:.2}%)",
               new_symbol, specs.qty_step, specs.tick_size, price_change_24h * 100.0);
@@ -316,7 +733,15 @@ impl StrategyEngine {
 
             // Store pending symbol change - will be applied after close confirmation
             self.pending_symbol_change = Some((new_symbol, specs, price_change_24h));
-            self.state = StrategyState::SwitchingSymbol;
+            if self.decisions.is_enabled() {
+                let exit = DecisionEntry::Exit {
+                    reason: ExitReason::SymbolSwitch,
+                    price: Some(position.current_price),
+                    pnl_percent: Some(position.pnl_percent()),
+                };
+                self.decisions.record(decision_line(self.clock.now(), &self.state, self.trade_cid.as_ref(), Some(position.symbol.clone()), exit));
+            }
+            self.state.transition(StrategyState::SwitchingSymbol, "closing for symbol switch");
 
             // ✅ FIX BUG #17 (CRITICAL): Use timeout to prevent blocking if ExecutionActor hangs
             let send_result = tokio::time::timeout(
@@ -324,6 +749,7 @@ impl StrategyEngine {
                 self.execution_tx.send(ExecutionMessage::ClosePosition {
                     symbol: position.symbol.clone(),
                     position_side: position.side,
+                    reason: ExitReason::SymbolSwitch,
                 })
             ).await;
 
@@ -343,7 +769,7 @@ impl StrategyEngine {
                     }
                 }
             }
-            // DON'T switch yet - wait for PositionUpdate(None)
+            // DON'T switch yet - wait for the flat PositionUpdate
             return;
         }
 
@@ -354,26 +780,60 @@ impl StrategyEngine {
     /// Complete the symbol switch after position is closed
     fn complete_symbol_switch(&mut self, new_symbol: Symbol, specs: SymbolSpecs, price_change_24h: f64) {
         info!("✅ Completing symbol switch to: {} (24h: {:.2}%)", new_symbol, price_change_24h * 100.0);
+        if let Some(old_symbol) = self.current_symbol.take() {
+            self.dataset.flush_symbol(&old_symbol);
+        }
+        self.pending_signal_sample = None;
         self.current_symbol = Some(new_symbol);
         self.current_position = None;
         self.last_orderbook = None;
+        self.index_prices.reset();
+        self.taker_flow.reset();
+        self.spread_gate.reset();
         self.current_specs = Some(specs);
         self.tick_buffer = RingBuffer::new(300); // ✅ EXPANDED buffer
+        self.fast_ticks = self.config.fast_math.then(|| RingBuffer::new(300));
+        self.candles = CandleSeries::new(CANDLE_INTERVAL_MS, CANDLE_CAPACITY);
         self.price_change_24h = Some(price_change_24h); // ✅ Store 24h change for trend protection
         self.pending_symbol_change = None;
         // ✅ Reset confirmation state for new symbol
         self.pending_signal = None;
         self.confirmation_count = 0;
-        self.state = StrategyState::Idle;
+        self.state.transition(StrategyState::Idle, "symbol switched");
         // ✅ FIX CRITICAL BUG: Clear VWAP cache on symbol switch
         // CRITICAL: Old symbol's VWAP would cause completely wrong calculations!
         self.cached_vwap_short = None;
         self.cached_vwap_long = None;
         self.tick_counter = 0;
         self.last_cache_update = 0;
+        self.stats_tick_mark = 0;
+        // VWAP levels of the old symbol mean nothing here
+        self.entry_signal = None;
+        self.last_exit_signal = None;
+        self.open_trade = None;
+        self.entry_context = None;
+        self.replay_switch_ticks();
+    }
+
+    /// ✅ SWITCH TICKS: The new symbol's ticks that arrived before the switch go into its
+    /// buffers (no signals off them)
+    fn replay_switch_ticks(&mut self) {
+        let Some(symbol) = self.current_symbol.clone() else {
+            return;
+        };
+        let ticks = self.switch_ticks.take(&symbol);
+        if ticks.is_empty() {
+            return;
+        }
+        info!("🔁 Replaying {} ticks of {} that arrived before the switch", ticks.len(), symbol);
+        for tick in &ticks {
+            self.absorb_tick(tick);
+        }
     }
 
     async fn handle_orderbook(&mut self, snapshot: OrderBookSnapshot) {
+        let price = snapshot.price(self.config.price_source);
+        self.dataset.observe_price(&snapshot.symbol, price, std::time::Instant::now());
         // ✅ FIXED: Prevent race condition - ignore messages from old symbol
         if let Some(ref current_symbol) = self.current_symbol {
             if snapshot.symbol != *current_symbol {
@@ -384,6 +844,9 @@ impl StrategyEngine {
                 return;
             }
         }
+        if let Some(ref specs) = self.current_specs {
+            self.spread_gate.observe(&snapshot, specs.tick_size);
+        }
 
         // ✅ FIX INFINITE CLOSE LOOP: Don't process exit logic if already closing or ordering
         // CRITICAL: orderbook updates come faster than state transitions, causing spam!
@@ -393,32 +856,40 @@ impl StrategyEngine {
 
         // Update current price if we have a position
         if let Some(ref mut position) = self.current_position {
-            position.current_price = snapshot.mid_price;
+            position.current_price = price;
 
             // ✅ FIX CRITICAL BUG #8: Use active dynamic risk for BOTH SL and TP, not position.stop_loss!
             let (sl_target, tp_target) = self.active_dynamic_risk
                 .unwrap_or((self.config.stop_loss_percent, self.config.take_profit_percent));
 
             let pnl_pct = position.pnl_percent();
+            let roe_pct = position.roe_percent();
 
-            // ✅ TRAILING STOP: Update peak PnL for momentum trades
-            // ✅ NOW: Update for ALL trades (needed for Breakeven protection)
+            // ✅ TRAILING STOP: Peak PnL for the status (the position monitor tracks its own)
             if pnl_pct > self.peak_pnl_percent {
                 self.peak_pnl_percent = pnl_pct;
             }
 
+            // ✅ EVENTS: Live PnL for dashboards, throttled
+            if self.events.has_subscribers()
+                && self.last_pnl_event.is_none_or(|t| t.elapsed() >= Duration::from_millis(PNL_EVENT_INTERVAL_MS))
+            {
+                self.last_pnl_event = Some(Instant::now());
+                self.events.publish(EventKind::PositionPnl {
+                    symbol: position.symbol.clone(),
+                    side: position.side,
+                    size: position.size,
+                    entry_price: position.entry_price,
+                    mark_price: position.current_price,
+                    pnl_percent: pnl_pct,
+                    roe_percent: roe_pct,
+                });
+            }
+
             // ✅ DEBUG: Log PnL every 5 seconds to catch missed TP/SL
-            static mut LAST_PNL_LOG: Option<std::time::Instant> = None;
-            let should_log = unsafe {
-                match LAST_PNL_LOG {
-                    Some(last) if last.elapsed().as_secs() < 5 => false,
-                    _ => {
-                        LAST_PNL_LOG = Some(std::time::Instant::now());
-                        true
-                    }
-                }
-            };
+            let should_log = self.last_pnl_log.is_none_or(|last| last.elapsed().as_secs() >= 5);
             if should_log {
+                self.last_pnl_log = Some(Instant::now());
                 let mode = if self.is_momentum_trade { "MOMENTUM" } else { "REVERSION" };
                 let trailing_info = if self.is_momentum_trade {
                     format!(" | Peak: {:.2}%", self.peak_pnl_percent)
@@ -426,166 +897,349 @@ impl StrategyEngine {
                     String::new()
                 };
                 info!(
-                    "📊 {} {} | Entry: {} | Current: {} | PnL: {:.2}% | TP: {:.2}% | SL: -{:.2}%{}",
+                    "📊 {} {} | Entry: {} | Current: {} | PnL: {:.2}% (ROE {:.2}% @ {}x) | TP: {:.2}% | SL: -{:.2}%{}",
                     mode, position.symbol, position.entry_price, position.current_price,
-                    pnl_pct, tp_target, sl_target, trailing_info
+                    pnl_pct, roe_pct, position.leverage, tp_target, sl_target, trailing_info
                 );
             }
 
-            // ✅ TRAILING STOP: For momentum trades, check if price dropped from peak
-            // FIX: Distance 1.5% was too wide for scalping (1.5% price = 15% ROE)
-            // New distance: 0.2% price (~2% ROE) - secures profit quickly
-            const TRAILING_DISTANCE: f64 = 0.2; 
-            if self.is_momentum_trade && self.peak_pnl_percent > 0.3 {
-                // Only activate trailing after 0.3% profit
-                let drop_from_peak = self.peak_pnl_percent - pnl_pct;
-                if drop_from_peak >= TRAILING_DISTANCE {
-                    info!(
-                        "📉 TRAILING STOP triggered for {} | Peak: {:.2}% | Now: {:.2}% | Drop: {:.2}%",
-                        position.symbol, self.peak_pnl_percent, pnl_pct, drop_from_peak
-                    );
-                    
-                    self.state = StrategyState::ClosingPosition;
-                    self.last_close_attempt = Some(Instant::now());
-                    
-                    let _ = tokio::time::timeout(
-                        Duration::from_secs(5),
-                        self.execution_tx.send(ExecutionMessage::ClosePosition {
-                            symbol: position.symbol.clone(),
-                            position_side: position.side,
-                        })
-                    ).await;
-                    return;
-                }
-            }
+            // SL/TP, trailing, breakeven and the liquidation guard live in the position monitor
+        }
 
-            // ✅ BREAKEVEN / SECURE PROFIT:
-            // If trade was ever > +0.5% (5% ROE), NEVER let it lose money.
-            // Trigger close if it drops back to +0.1% (covers fees).
-            // This applies to BOTH Momentum and Mean Reversion trades.
-            if self.peak_pnl_percent > 0.5 && pnl_pct < 0.1 {
-                 info!(
-                    "🛡️  BREAKEVEN PROTECT triggered for {} | Peak was: {:.2}% | Now: {:.2}% | Securing profit!",
-                    position.symbol, self.peak_pnl_percent, pnl_pct
+        self.last_orderbook = Some(snapshot);
+    }
+
+    /// ✅ POSITION MONITOR: An exit fired in the monitor, its close is already on the way
+    fn handle_monitor_exit(&mut self, symbol: Symbol, reason: ExitReason, price: Decimal) {
+        if self.state != StrategyState::PositionOpen || self.current_symbol.as_ref() != Some(&symbol) {
+            debug!("Ignoring {} from the position monitor in state {:?}", reason, self.state);
+            return;
+        }
+        self.state.transition(StrategyState::ClosingPosition, "position monitor exit");
+        self.last_close_attempt = Some(Instant::now());
+
+        if reason == ExitReason::LiquidationGuard {
+            if let Some(ref mut position) = self.current_position {
+                position.current_price = price;
+                let text = format!(
+                    "🚨 <b>LIQUIDATION GUARD {}</b>\nPrice {} is {:.2}% from liquidation at {}\nROE: {:.2}% @ {}x — closing position",
+                    escape_html(position.symbol.as_str()), price,
+                    position.liquidation_distance_percent().unwrap_or_default(),
+                    position.liq_price.unwrap_or_default(), position.roe_percent(), position.leverage
                 );
-                
-                self.state = StrategyState::ClosingPosition;
-                self.last_close_attempt = Some(Instant::now());
-                
-                let _ = tokio::time::timeout(
-                    Duration::from_secs(5),
-                    self.execution_tx.send(ExecutionMessage::ClosePosition {
-                        symbol: position.symbol.clone(),
-                        position_side: position.side,
-                    })
-                ).await;
-                return;
+                self.notify(Alert::critical(text));
             }
+        }
+    }
 
-            // Check stop loss using dynamic SL target
-            if pnl_pct <= -sl_target {
-                // ✅ FIX RATE LIMIT: Don't spam close requests
-                if let Some(last_attempt) = self.last_close_attempt {
-                    if last_attempt.elapsed().as_secs() < 2 {
-                        debug!("⏳ Rate limit: Close attempt throttled (< 2s since last)");
-                        return;
-                    }
-                }
+    /// Position from execution: confirms an entry, completes a close or a switch, or reveals a vanished position
+    fn handle_position_update(&mut self, position: Option<Position>) {
+        self.current_position = position.clone();
+        // ✅ FIXED: Update state machine based on position
+        if let Some(ref p) = position {
+            info!(
+                "📍 Position confirmed (unrealized {:+} USD, realized {:+} USD), transitioning to PositionOpen",
+                p.unrealized_pnl, p.realized_pnl
+            );
+            self.state.transition(StrategyState::PositionOpen, "position confirmed");
+            // ✅ TIME-BASED EXIT: helper
+            if self.position_start_time.is_none() {
+                self.position_start_time = Some(Instant::now());
+            }
+        } else if self.state == StrategyState::ClosingPosition {
+            info!("✅ Position closed, transitioning to Idle");
+            // ✅ IMPROVEMENT #3: Start trade cooldown
+            self.start_cooldown();
+            self.record_exit_signal();
+            // ✅ FIX MEMORY LOSS BUG: Clear dynamic risk when position closes
+            self.active_dynamic_risk = None;
+            // ✅ FIX BUG #18: Clear close attempt timestamp
+            self.last_close_attempt = None;
+            // ✅ Reset time tracker
+            self.position_start_time = None;
+            // ✅ CLEANUP: Reset trailing stop state
+            self.is_momentum_trade = false;
+            self.peak_pnl_percent = 0.0;
+            self.state.transition(StrategyState::Idle, "position closed");
+        } else if self.state == StrategyState::SwitchingSymbol {
+            // ✅ FIX BUG #1: Now complete the pending symbol change
+            info!("✅ Position closed during symbol switch, completing switch...");
+            // ✅ IMPROVEMENT #3: Start trade cooldown
+            self.start_cooldown();
+            // ✅ FIX MEMORY LOSS BUG: Clear dynamic risk when position closes
+            self.active_dynamic_risk = None;
+            // ✅ FIX BUG #18: Clear close attempt timestamp
+            self.last_close_attempt = None;
+            // ✅ CLEANUP: Reset trailing stop state
+            self.is_momentum_trade = false;
+            self.peak_pnl_percent = 0.0;
+            if let Some((new_symbol, specs, price_change_24h)) = self.pending_symbol_change.take() {
+                self.complete_symbol_switch(new_symbol, specs, price_change_24h);
+            } else {
+                warn!("SwitchingSymbol state but no pending change!");
+                self.state.transition(StrategyState::Idle, "switch without pending symbol");
+            }
+        } else if position.is_none() && matches!(self.state.get(), StrategyState::PositionOpen | StrategyState::SwitchingSymbol) {
+            // ✅ FIX BUG #16 (CRITICAL): Only reset if position disappeared in states where we HAVE a position
+            // CRITICAL STATES TO CHECK:
+            // - PositionOpen: Position should exist, if None = liquidation/margin call
+            // - SwitchingSymbol: We're closing position, if None = position closed
+            //
+            // DO NOT CHECK in these states:
+            // - Idle: No position expected (normal)
+            // - OrderPending: Position doesn't exist yet (order not filled)
+            // - ClosingPosition: Position disappearing is EXPECTED
+            warn!(
+                "⚠️  Position disappeared unexpectedly in state {:?} (liquidation? margin call?). Resetting to Idle.",
+                self.state
+            );
+            self.state.transition(StrategyState::Idle, "position disappeared");
+            self.active_dynamic_risk = None;
+            self.start_cooldown();
+            self.record_exit_signal();
+            self.open_trade = None;
+            self.entry_context = None;
+        }
+    }
 
-                warn!(
-                    "🛑 STOP LOSS triggered for {} at {} (PnL: {:.2}% | Target: -{:.2}% {})",
-                    position.symbol,
-                    position.current_price,
-                    pnl_pct,
-                    sl_target,
-                    if self.active_dynamic_risk.is_some() { "[Dynamic]" } else { "[Static]" }
-                );
+    /// ✅ STALE UPDATES: Another symbol's position, or the answer to a verification asked before
+    /// the last fill, says nothing about what is held now (it would read as drift)
+    fn is_stale_position_update(&self, msg: &StrategyMessage) -> bool {
+        let StrategyMessage::PositionUpdate { symbol, query, .. } = msg else {
+            return false;
+        };
+        let stale = self.current_symbol.as_ref() != Some(symbol) || query.is_some_and(|query| query != self.position_query);
+        if stale {
+            debug!("Dropped stale position update for {} (query {:?}, latest {})", symbol, query, self.position_query);
+        }
+        stale
+    }
 
-                // ✅ FIXED: Transition to ClosingPosition state
-                self.state = StrategyState::ClosingPosition;
-                self.last_close_attempt = Some(Instant::now());
+    /// ✅ POSITION DRIFT: Alert when the exchange holds something other than what the strategy
+    /// thinks it holds. Returns true on drift; `handle_position_update` adopts the exchange's
+    /// position either way, POSITION_DRIFT_ACTION=flatten then closes it
+    fn check_position_drift(&mut self, position: Option<&Position>) -> bool {
+        let Some(drift) = position_drift::detect(self.state.get(), self.current_position.as_ref(), position) else {
+            return false;
+        };
+        let action = match self.config.position_drift_action {
+            DriftAction::Adopt => "adopting the exchange position",
+            DriftAction::Flatten => "closing it",
+        };
+        warn!("⚠️  Position drift on {}, {}", drift, action);
+        self.notify(Alert::warning(format!(
+            "⚠️ <b>Position drift</b>\n{}\nAction: {}",
+            escape_html(&drift.to_string()),
+            action
+        )));
+        true
+    }
 
-                // ✅ FIX BUG #17 (CRITICAL): Use timeout to prevent blocking
-                let send_result = tokio::time::timeout(
-                    Duration::from_secs(5),
-                    self.execution_tx.send(ExecutionMessage::ClosePosition {
-                        symbol: position.symbol.clone(),
-                        position_side: position.side,
-                    })
-                ).await;
+    /// The entry didn't make it: back to Idle with nothing left of it
+    fn handle_order_failed(&mut self, reason: String, retryable: bool) {
+        warn!("❌ Order failed: {}, transitioning to Idle", reason);
+        // ⚡ PHASE 3: Transient exchange trouble feeds the circuit breaker
+        if retryable {
+            self.handle_api_error();
+        }
+        self.state.transition(StrategyState::Idle, "order failed");
+        self.current_position = None;
+        // ✅ FIX MEMORY LEAK: Clear dynamic risk on order failure
+        self.active_dynamic_risk = None;
+        self.entry_signal = None;
+        self.entry_context = None;
+        self.open_trade = None;
+        self.dataset.entry_failed(&reason);
+        // Reset confirmation state to avoid stale signals
+        self.pending_signal = None;
+        self.confirmation_count = 0;
+    }
 
-                match send_result {
-                    Ok(Ok(_)) => { /* SL close sent successfully */ }
-                    Ok(Err(e)) => {
-                        warn!("Failed to send ClosePosition for stop loss: {}", e);
-                        self.state = StrategyState::PositionOpen; // Revert state on failure
-                    }
-                    Err(_) => {
-                        warn!("⚠️  CRITICAL: ExecutionActor timeout on SL! Reverting state.");
-                        self.state = StrategyState::PositionOpen;
-                    }
-                }
+    /// ✅ STATE WATCHDOG: An order or close the exchange never answered would leave the strategy
+    /// waiting forever. Past STUCK_STATE_TIMEOUT_SECS, say so (repeated with a backoff while it
+    /// lasts) and ask execution where things really stand; `handle_reconciled` derives the state from the answer
+    async fn check_stuck_state(&mut self) {
+        let Some((state, held)) = self.state.check_stuck() else {
+            return;
+        };
+        let symbol = self.current_symbol.clone();
+        let label = symbol.as_ref().map(Symbol::as_str).unwrap_or("-");
+        error!("⏳ Strategy stuck in {:?} for {}s on {}, reconciling with the exchange", state, held.as_secs(), label);
+        self.notify(Alert::warning(format!(
+            "⏳ <b>Strategy stuck</b>\n{:?} for {} on {}, reconciling with the exchange",
+            state,
+            format_duration(held.as_secs()),
+            escape_html(label)
+        )));
+        let Some(symbol) = symbol else {
+            return;
+        };
+        if let Err(e) = self
+            .execution_tx
+            .send(ExecutionMessage::Reconcile(symbol))
+            .instrument(self.trade_span())
+            .await
+        {
+            warn!("Failed to request reconciliation: {}", e);
+        }
+    }
 
-                return;
+    /// ✅ RECONCILE: Nothing is working on the exchange any more, so its position decides
+    fn handle_reconciled(&mut self, symbol: Symbol, position: Option<Position>) {
+        let state = self.state.get();
+        if !state.is_waiting() {
+            debug!("Reconciliation for {} arrived in {:?}, already resolved", symbol, state);
+            return;
+        }
+        info!(
+            "🔄 Reconciled {} in {:?}: {}",
+            symbol,
+            state,
+            position.as_ref().map_or("flat".to_string(), |p| format!("{:?} {} @ {}", p.side, p.size, p.entry_price))
+        );
+        match (state, position) {
+            (StrategyState::OrderPending, None) => {
+                self.handle_order_failed("no position after reconciliation".to_string(), false);
             }
+            (StrategyState::OrderPending, Some(position)) => {
+                warn!("⚠️  Entry fill was never reported, adopting the exchange position");
+                self.handle_position_update(Some(position));
+            }
+            // A close or switch that never finished: open again (the monitor retries) or done
+            (_, position) => self.handle_position_update(position),
+        }
+    }
 
-            // Check take profit using dynamic TP target
-            // ✅ TRAILING STOP: For momentum trades, ignore fixed TP and let profit run!
-            if !self.is_momentum_trade && pnl_pct >= tp_target {
-                // ✅ FIX RATE LIMIT: Don't spam close requests
-                if let Some(last_attempt) = self.last_close_attempt {
-                    if last_attempt.elapsed().as_secs() < 2 {
-                        debug!("⏳ Rate limit: Close attempt throttled (< 2s since last)");
-                        return;
-                    }
-                }
-
-                info!(
-                    "💰 TAKE PROFIT hit for {} (PnL: {:.2}% | Target: {:.2}% {})",
-                    position.symbol,
-                    pnl_pct,
-                    tp_target,
-                    if self.active_dynamic_risk.is_some() { "[Dynamic]" } else { "[Static]" }
-                );
+    /// ✅ SHARED RISK GATE: Flat again, let another shard have the slot
+    fn release_position_slot(&mut self) {
+        if !self.holds_position_slot {
+            return;
+        }
+        self.holds_position_slot = false;
+        if let (Some(gate), Some(symbol)) = (&self.position_gate, &self.current_symbol) {
+            gate.release(symbol.as_str());
+        }
+    }
 
-                // ✅ FIXED: Transition to ClosingPosition state
-                self.state = StrategyState::ClosingPosition;
-                self.last_close_attempt = Some(Instant::now());
+    /// What the position monitor should watch right now: the open position,
+    /// unless it's already being closed or replaced
+    fn exit_plan(&self) -> Option<ExitPlan> {
+        if self.state != StrategyState::PositionOpen {
+            return None;
+        }
+        let position = self.current_position.as_ref()?;
+        let (stop_loss_percent, take_profit_percent) = self.active_dynamic_risk
+            .unwrap_or((self.config.stop_loss_percent, self.config.take_profit_percent));
+        Some(ExitPlan {
+            symbol: position.symbol.clone(),
+            side: position.side,
+            entry_price: position.entry_price,
+            leverage: position.leverage,
+            liq_price: position.liq_price,
+            stop_loss_percent,
+            take_profit_percent,
+            trailing: self.is_momentum_trade,
+            dynamic: self.active_dynamic_risk.is_some(),
+            liq_buffer_percent: self.config.liq_buffer_percent,
+        })
+    }
 
-                // ✅ FIX BUG #17 (CRITICAL): Use timeout to prevent blocking
-                let send_result = tokio::time::timeout(
-                    Duration::from_secs(5),
-                    self.execution_tx.send(ExecutionMessage::ClosePosition {
-                        symbol: position.symbol.clone(),
-                        position_side: position.side,
-                    })
-                ).await;
+    /// Re-arm or disarm the position monitor when the plan changed
+    fn sync_exit_plan(&self) {
+        let plan = self.exit_plan();
+        self.exit_plan_tx.send_if_modified(|current| {
+            if *current == plan {
+                return false;
+            }
+            *current = plan;
+            true
+        });
+    }
 
-                match send_result {
-                    Ok(Ok(_)) => { /* TP close sent successfully */ }
-                    Ok(Err(e)) => {
-                        warn!("Failed to send ClosePosition for take profit: {}", e);
-                        self.state = StrategyState::PositionOpen; // Revert state on failure
-                    }
-                    Err(_) => {
-                        warn!("⚠️  CRITICAL: ExecutionActor timeout on TP! Reverting state.");
-                        self.state = StrategyState::PositionOpen;
-                    }
-                }
+    /// ✅ WARM-UP: Put REST history in front of whatever live ticks already arrived
+    fn apply_warmup_ticks(&mut self, symbol: Symbol, ticks: Vec<TradeTick>) {
+        if self.current_symbol.as_ref() != Some(&symbol) {
+            debug!("Ignoring warm-up ticks for {} (current: {:?})", symbol, self.current_symbol);
+            return;
+        }
 
-                return;
+        // Live ticks win: history only fills the time before the first live tick
+        let first_live = self.tick_buffer.iter().next().map(|t| t.timestamp).unwrap_or(i64::MAX);
+        let mut buffer = RingBuffer::new(300);
+        let mut added = 0;
+        for tick in ticks.into_iter().filter(|t| t.timestamp < first_live) {
+            buffer.push(tick);
+            added += 1;
+        }
+        for tick in self.tick_buffer.iter() {
+            buffer.push(tick.clone());
+        }
+        self.tick_buffer = buffer;
+        if let Some(ref mut fast) = self.fast_ticks {
+            *fast = RingBuffer::new(300);
+            for tick in self.tick_buffer.iter() {
+                fast.push(FastTick::from(tick));
             }
         }
 
-        self.last_orderbook = Some(snapshot);
+        // Same invalidation as a live tick
+        self.tick_counter += added;
+        self.cached_vwap_short = None;
+        self.cached_vwap_long = None;
+        self.last_cache_update = self.tick_counter;
+
+        info!("🔥 Warm-up: {} historical ticks for {}, buffer {}/200", added, symbol, self.tick_buffer.len());
+    }
+
+    /// Add a tick to the buffers, candles and taker flow
+    fn absorb_tick(&mut self, tick: &TradeTick) {
+        self.candles.push_trade(tick.price, tick.size, tick.timestamp);
+        self.tick_buffer.push(tick.clone());
+        if let Some(ref mut fast) = self.fast_ticks {
+            fast.push(FastTick::from(tick));
+        }
+        self.taker_flow.push(tick);
+
+        // ✅ PERFORMANCE: Invalidate VWAP cache on new tick
+        // CRITICAL FIX: Use tick_counter instead of buffer.len()!
+        // RingBuffer.len() stays constant when full (300), so len-based
+        // invalidation would STOP working after 300 ticks!
+        self.tick_counter += 1;
+        if self.tick_counter != self.last_cache_update {
+            self.cached_vwap_short = None;
+            self.cached_vwap_long = None;
+            self.last_cache_update = self.tick_counter;
+        }
+    }
+
+    /// ✅ WARM-UP: Put REST klines in front of the candles built from live ticks
+    fn apply_warmup_candles(&mut self, symbol: Symbol, candles: Vec<Candle>) {
+        if self.current_symbol.as_ref() != Some(&symbol) {
+            debug!("Ignoring warm-up candles for {} (current: {:?})", symbol, self.current_symbol);
+            return;
+        }
+
+        let added = self.candles.warm_start(candles);
+        info!(
+            "🔥 Warm-up: {} historical candles for {} | ATR: {} | Regime: {}",
+            added,
+            symbol,
+            self.candles.atr_percent().map(|a| format!("{:.3}%", a)).unwrap_or_else(|| "-".to_string()),
+            self.candles.regime().map(|r| r.to_string()).unwrap_or_else(|| "-".to_string())
+        );
     }
 
     async fn handle_trade(&mut self, tick: TradeTick) {
+        // ✅ HEARTBEAT: Feed liveness is tracked before any filtering
+        self.last_tick_at = Some(Instant::now());
 
         // ⚡ PHASE 3: CIRCUIT BREAKER - Check if trading is paused
         self.check_pause_status();
         if self.is_paused { return; }
 
+        // ✅ KILL SWITCH: Operator stopped trading
+        if self.kill_switch_engaged { return; }
+
         // ⚡ PHASE 3: Check temporary blacklist
         if let Some(ref symbol) = self.current_symbol {
             if self.is_temp_blacklisted(&symbol.0) { return; }
@@ -595,32 +1249,21 @@ impl StrategyEngine {
         if let Some(ref current_symbol) = self.current_symbol {
             if tick.symbol != *current_symbol {
                 debug!(
-                    "Ignoring trade tick from old symbol {} (current: {})",
+                    "Buffering trade tick from other symbol {} (current: {})",
                     tick.symbol, current_symbol
                 );
+                self.switch_ticks.push(tick);
                 return;
             }
         }
 
         // ✅ PUMP PROTECTION: Check blacklist
-        if self.config.blacklist_symbols.contains(&tick.symbol.0.to_uppercase()) {
-            debug!("⛔ Symbol {} is blacklisted, ignoring tick", tick.symbol);
+        if let Some(reason) = self.symbols.rejects(&tick.symbol.0) {
+            debug!("⛔ Symbol {} is {}, ignoring tick", tick.symbol, reason);
             return;
         }
 
-        // Add to buffer
-        self.tick_buffer.push(tick.clone());
-
-        // ✅ PERFORMANCE: Invalidate VWAP cache on new tick
-        // CRITICAL FIX: Use tick_counter instead of buffer.len()!
-        // RingBuffer.len() stays constant when full (300), so len-based
-        // invalidation would STOP working after 300 ticks!
-        self.tick_counter += 1;
-        if self.tick_counter != self.last_cache_update {
-            self.cached_vwap_short = None;
-            self.cached_vwap_long = None;
-            self.last_cache_update = self.tick_counter;
-        }
+        self.absorb_tick(&tick);
 
 
 
@@ -669,7 +1312,15 @@ impl StrategyEngine {
                     pnl_pct, position.symbol
                 );
 
-                self.state = StrategyState::ClosingPosition;
+                if self.decisions.is_enabled() {
+                    let exit = DecisionEntry::Exit {
+                        reason: ExitReason::FlashCrash,
+                        price: Some(last_price),
+                        pnl_percent: Some(pnl_pct),
+                    };
+                    self.decisions.record(decision_line(self.clock.now(), &self.state, self.trade_cid.as_ref(), Some(position.symbol.clone()), exit));
+                }
+                self.state.transition(StrategyState::ClosingPosition, "flash crash exit");
                 self.last_close_attempt = Some(Instant::now());
 
                 // ✅ FIX BUG #17 (CRITICAL): Use timeout to prevent blocking
@@ -678,6 +1329,7 @@ impl StrategyEngine {
                     self.execution_tx.send(ExecutionMessage::ClosePosition {
                         symbol: position.symbol.clone(),
                         position_side: position.side,
+                        reason: ExitReason::FlashCrash,
                     })
                 ).await;
 
@@ -685,11 +1337,11 @@ impl StrategyEngine {
                     Ok(Ok(_)) => { /* Flash crash emergency close sent */ }
                     Ok(Err(e)) => {
                         warn!("Failed to send emergency ClosePosition: {}", e);
-                        self.state = StrategyState::PositionOpen;
+                        self.state.transition(StrategyState::PositionOpen, "close send failed");
                     }
                     Err(_) => {
                         warn!("⚠️  CRITICAL: ExecutionActor timeout on flash crash exit! Reverting state.");
-                        self.state = StrategyState::PositionOpen;
+                        self.state.transition(StrategyState::PositionOpen, "close send timed out");
                     }
                 }
 
@@ -700,17 +1352,27 @@ impl StrategyEngine {
             if let Some(start_time) = self.position_start_time {
                 let duration = start_time.elapsed();
                 // If position > 15 mins and PnL < 0.2% (stalled), kill it to free capital
-                if duration.as_secs() > 900 && pnl_pct < 0.2 {
-                    if self.last_close_attempt.map(|t| t.elapsed().as_secs() > 5).unwrap_or(true) {
-                         info!("⏰ Time-based Exit: Trade stalled ({:?}, PnL {:.2}%), closing.", duration, pnl_pct);
-                         self.state = StrategyState::ClosingPosition;
-                         self.last_close_attempt = Some(Instant::now());
-                         let _ = self.execution_tx.send(ExecutionMessage::ClosePosition {
-                             symbol: position.symbol.clone(),
-                             position_side: position.side,
-                         }).await;
-                         return;
+                if duration.as_secs() > 900
+                    && pnl_pct < 0.2
+                    && self.last_close_attempt.map(|t| t.elapsed().as_secs() > 5).unwrap_or(true)
+                {
+                    info!("⏰ Time-based Exit: Trade stalled ({:?}, PnL {:.2}%), closing.", duration, pnl_pct);
+                    if self.decisions.is_enabled() {
+                        let exit = DecisionEntry::Exit {
+                            reason: ExitReason::TimeExit,
+                            price: Some(last_price),
+                            pnl_percent: Some(pnl_pct),
+                        };
+                        self.decisions.record(decision_line(self.clock.now(), &self.state, self.trade_cid.as_ref(), Some(position.symbol.clone()), exit));
                     }
+                    self.state.transition(StrategyState::ClosingPosition, "time-based exit");
+                    self.last_close_attempt = Some(Instant::now());
+                    let _ = self.execution_tx.send(ExecutionMessage::ClosePosition {
+                        symbol: position.symbol.clone(),
+                        position_side: position.side,
+                        reason: ExitReason::TimeExit,
+                    }).await;
+                    return;
                 }
             }
             }
@@ -723,7 +1385,7 @@ impl StrategyEngine {
         if buffer_len < 200 {
             // ✅ FIX BUG #15: Show buffering progress at INFO level (every 20 ticks + milestones)
             // User needs to see the bot is working and accumulating data
-            if buffer_len % 20 == 0 || buffer_len == 50 || buffer_len == 100 || buffer_len == 150 || buffer_len == 199 {
+            if buffer_len.is_multiple_of(20) || buffer_len == 50 || buffer_len == 100 || buffer_len == 150 || buffer_len == 199 {
                 info!("📊 Buffering ticks: {}/200 ({}% ready)", buffer_len, buffer_len * 100 / 200);
             }
             return;
@@ -750,9 +1412,27 @@ impl StrategyEngine {
             }
         }
 
+        // ✅ MANUAL PAUSE: Operator stopped entries (exits above keep running)
+        if self.manual_pause {
+            debug!("⏸️  Paused by operator, skipping entry signals");
+            return;
+        }
+
+        // ✅ DAILY TRADE LIMIT: Overtrading guard, lifts at the next UTC day
+        if self.daily_trades.is_reached(self.clock.now()) {
+            debug!("📅 Daily trade limit ({}) reached, skipping entry signals", self.daily_trades.max_per_day());
+            return;
+        }
+
+        // ✅ LOSS STREAK: Timed pause or halt until /resume
+        if self.loss_streak.blocks_entry(self.clock.now()) {
+            debug!("🧊 Loss streak pause ({} losses), skipping entry signals", self.loss_streak.consecutive_losses());
+            return;
+        }
+
         // ✅ FIX BUG #15: Periodic status report (every 50 ticks after buffer full)
         // Show user what's happening even if no strong signals
-        if self.tick_counter % 50 == 0 && self.tick_counter > 200 {
+        if self.tick_counter.is_multiple_of(50) && self.tick_counter > 200 {
             if let Some(momentum) = self.calculate_momentum() {
                 let trend_str = match self.calculate_trend() {
                     Some(true) => "BULLISH",
@@ -811,13 +1491,17 @@ impl StrategyEngine {
                         if self.confirmation_count >= 3 {
                             if let Some(ref orderbook) = self.last_orderbook {
                                 // Check spread is reasonable
-                                if orderbook.spread_bps > self.config.max_spread_bps {
+                                let max_spread_bps = self.max_spread_bps(orderbook);
+                                if orderbook.spread_bps > max_spread_bps {
                                     warn!(
                                         "⚠️  Entry blocked: Spread too wide {:.2} bps (max: {:.2}). Resetting confirmation.",
-                                        orderbook.spread_bps, self.config.max_spread_bps
+                                        orderbook.spread_bps, max_spread_bps
                                     );
                                     // ✅ FIX: Reset confirmation state when spread too wide
                                     // CRITICAL: Market conditions changed, signal may be invalid
+                                    let orderbook_clone = orderbook.clone();
+                                    self.record_signal(momentum, &orderbook_clone, Err("spread"));
+                                    self.publish_signal(momentum, &orderbook_clone, "momentum", Err("spread"));
                                     self.pending_signal = None;
                                     self.confirmation_count = 0;
                                     return;
@@ -825,18 +1509,44 @@ impl StrategyEngine {
 
                                 // ✅ Signal confirmed - execute entry!
                                 info!("✅ Signal CONFIRMED after {} ticks", self.confirmation_count);
+                                let confirmations = self.confirmation_count;
                                 self.pending_signal = None;
                                 self.confirmation_count = 0;
                                 
                                 let orderbook_clone = orderbook.clone();
-                                self.execute_entry(momentum, &orderbook_clone).await;
+                                let mut sample = (self.wants_signal_samples() || self.signal_model.is_some())
+                                    .then(|| self.signal_sample(momentum, confirmations, &orderbook_clone));
+                                let result = match self
+                                    .reference_gate(momentum > 0.0, &orderbook_clone)
+                                    .and_then(|()| self.index_gate(momentum > 0.0))
+                                    .and_then(|()| self.taker_flow_gate(momentum > 0.0))
+                                    .and_then(|()| self.model_gate(sample.as_mut()))
+                                {
+                                    Ok(()) => {
+                                        let span = self.new_trade_span();
+                                        let result = self.execute_entry(momentum, confirmations, &orderbook_clone)
+                                            .instrument(span.clone())
+                                            .await;
+                                        if self.state == StrategyState::OrderPending {
+                                            self.active_trade_span = Some(span);
+                                        }
+                                        result
+                                    }
+                                    Err(reason) => Err(reason),
+                                };
+                                if let Some(sample) = sample {
+                                    self.push_signal_sample(sample, result);
+                                }
+                                self.publish_signal(momentum, &orderbook_clone, "momentum", result);
                             }
                         }
                     } else {
                         // Direction changed - reset
                         debug!("🔄 Signal direction changed, resetting confirmation");
+                        self.finish_unconfirmed_signal();
                         self.pending_signal = Some(signal_is_bullish);
                         self.confirmation_count = 1;
+                        self.start_signal_sample(momentum);
                     }
                 } else {
                     // First time seeing this signal - start confirmation
@@ -845,11 +1555,13 @@ impl StrategyEngine {
                     );
                     self.pending_signal = Some(signal_is_bullish);
                     self.confirmation_count = 1;
+                    self.start_signal_sample(momentum);
                 }
             } else {
                 // Momentum below threshold - reset pending signal
                 if self.pending_signal.is_some() {
                     debug!("📉 Momentum dropped below threshold, resetting confirmation");
+                    self.finish_unconfirmed_signal();
                     self.pending_signal = None;
                     self.confirmation_count = 0;
                 }
@@ -857,62 +1569,132 @@ impl StrategyEngine {
         }
     }
 
-    /// ✅ PERFORMANCE: Get cached 50-tick VWAP or calculate if needed
-    fn get_vwap_short(&mut self) -> Option<Decimal> {
-        // Return cached value if available
-        if let Some(cached) = self.cached_vwap_short {
-            return Some(cached);
+    /// Why an external signal can't be entered right now (same gates as `handle_trade`)
+    fn external_signal_block(&mut self, signal: &ExternalSignal) -> Option<String> {
+        let now = self.clock.now();
+        self.check_pause_status();
+        if self.is_paused {
+            return Some("circuit breaker pause".to_string());
         }
-
-        // Calculate and cache
-        // ✅ OPTIMIZATION: Use zero-allocation iter_rev()
-        if self.tick_buffer.len() < 50 {
-            return None;
+        if self.kill_switch_engaged {
+            return Some("kill switch engaged".to_string());
+        }
+        if self.manual_pause {
+            return Some("paused by operator".to_string());
+        }
+        let Some(current) = self.current_symbol.as_ref() else {
+            return Some("no symbol selected yet".to_string());
+        };
+        if !current.0.eq_ignore_ascii_case(signal.symbol.trim()) {
+            return Some(format!("trading {}, not {}", current, signal.symbol));
+        }
+        let symbol = current.0.to_uppercase();
+        if let Some(reason) = self.symbols.rejects(&symbol) {
+            return Some(format!("{} is {}", symbol, reason));
+        }
+        if self.is_temp_blacklisted(&symbol) {
+            return Some(format!("{} is blacklisted", symbol));
+        }
+        if self.state != StrategyState::Idle {
+            return Some(format!("state {:?}", self.state));
+        }
+        if let Some(last_trade) = self.last_trade_time {
+            let elapsed = last_trade.elapsed().as_secs();
+            if elapsed < self.trade_cooldown_secs {
+                return Some(format!("cooldown ({}s left)", self.trade_cooldown_secs - elapsed));
+            }
+        }
+        if self.daily_trades.is_reached(now) {
+            return Some(format!("daily trade limit ({}) reached", self.daily_trades.max_per_day()));
+        }
+        if self.loss_streak.blocks_entry(now) {
+            return Some(format!("loss streak pause ({} losses)", self.loss_streak.consecutive_losses()));
+        }
+        match self.last_orderbook.as_ref() {
+            None => Some("no orderbook yet".to_string()),
+            Some(orderbook) if orderbook.spread_bps > self.max_spread_bps(orderbook) => Some(format!(
+                "spread {:.2} bps > {:.2}",
+                orderbook.spread_bps,
+                self.max_spread_bps(orderbook)
+            )),
+            Some(_) => None,
         }
+    }
 
-        let mut total_value = Decimal::ZERO;
-        let mut total_volume = Decimal::ZERO;
-        for tick in self.tick_buffer.iter_rev().take(50) {
-            total_value += tick.price * tick.size;
-            total_volume += tick.size;
+    /// ✅ EXTERNAL SIGNAL: Webhook entry, sized and protected like an own signal.
+    /// The direction comes from the sender, the momentum size from our own VWAP.
+    async fn handle_external_signal(&mut self, signal: ExternalSignal) {
+        let Some(bullish) = signal.is_bullish() else {
+            return;
+        };
+        let action = if bullish { "LONG" } else { "SHORT" };
+        let source = escape_html(signal.source());
+
+        if let Some(reason) = self.external_signal_block(&signal) {
+            info!("📨 External {} {} from {} ignored: {}", action, signal.symbol, signal.source(), reason);
+            self.notify(Alert::info(format!(
+                "📨 External {} <b>{}</b> from {} ignored: {}",
+                action,
+                escape_html(&signal.symbol),
+                source,
+                escape_html(&reason)
+            )));
+            return;
         }
+        let Some(orderbook) = self.last_orderbook.clone() else {
+            return;
+        };
 
-        if total_volume == Decimal::ZERO {
-            return None;
+        let magnitude = self.calculate_momentum().map(f64::abs).unwrap_or(self.momentum_threshold);
+        let momentum = if bullish { magnitude } else { -magnitude };
+        info!("📨 External {} signal from {} on {}", action, signal.source(), orderbook.symbol);
+
+        // An own signal still confirming would enter on top of this one
+        self.finish_unconfirmed_signal();
+        self.pending_signal = None;
+        self.confirmation_count = 0;
+
+        let span = self.new_trade_span();
+        let result = self.execute_entry(momentum, 0, &orderbook).instrument(span.clone()).await;
+        if self.state == StrategyState::OrderPending {
+            self.active_trade_span = Some(span);
         }
+        self.record_signal(momentum, &orderbook, result);
+        self.publish_signal(momentum, &orderbook, signal.source(), result);
+
+        let text = match result {
+            Ok(()) => format!("📨 External {} <b>{}</b> from {}: entry sent", action, orderbook.symbol, source),
+            Err(reason) => format!("📨 External {} <b>{}</b> from {} rejected: {}", action, orderbook.symbol, source, reason),
+        };
+        self.notify(Alert::info(text));
+    }
 
-        let vwap = total_value / total_volume;
-        self.cached_vwap_short = Some(vwap);
-        Some(vwap)
+    /// ✅ PERFORMANCE: Get cached 50-tick VWAP or calculate if needed
+    fn get_vwap_short(&mut self) -> Option<Decimal> {
+        if self.cached_vwap_short.is_none() {
+            self.cached_vwap_short = self.vwap(50);
+        }
+        self.cached_vwap_short
     }
 
     /// ✅ PERFORMANCE: Get cached 200-tick VWAP or calculate if needed
     fn get_vwap_long(&mut self) -> Option<Decimal> {
-        // Return cached value if available
-        if let Some(cached) = self.cached_vwap_long {
-            return Some(cached);
+        if self.cached_vwap_long.is_none() {
+            self.cached_vwap_long = self.vwap(200);
         }
+        self.cached_vwap_long
+    }
 
-        // Calculate and cache
-        // ✅ OPTIMIZATION: Use zero-allocation iter_rev()
-        if self.tick_buffer.len() < 200 {
+    /// VWAP of the newest `window` ticks (None until the buffer holds that many)
+    fn vwap(&self, window: usize) -> Option<Decimal> {
+        if self.tick_buffer.len() < window {
             return None;
         }
-
-        let mut total_value = Decimal::ZERO;
-        let mut total_volume = Decimal::ZERO;
-        for tick in self.tick_buffer.iter_rev().take(200) {
-            total_value += tick.price * tick.size;
-            total_volume += tick.size;
-        }
-
-        if total_volume == Decimal::ZERO {
-            return None;
+        // ✅ FAST MATH: f64 sums, one conversion back per computation
+        match self.fast_ticks {
+            Some(ref fast) => vwap_fast(fast.iter_rev().take(window)).and_then(Decimal::from_f64),
+            None => vwap(self.tick_buffer.iter_rev().take(window)),
         }
-
-        let vwap = total_value / total_volume;
-        self.cached_vwap_long = Some(vwap);
-        Some(vwap)
     }
 
     /// ✅ PUMP PROTECTION: Calculate trend using short vs long VWAP (CACHED)
@@ -1005,6 +1787,7 @@ impl StrategyEngine {
     }
 
     /// Record a losing trade for dynamic blacklist
+    #[allow(dead_code)]
     fn record_loss(&mut self, symbol: &str) {
         *self.symbol_consecutive_losses.entry(symbol.to_string()).or_insert(0) += 1;
         
@@ -1016,11 +1799,12 @@ impl StrategyEngine {
                 "⚠️  TEMP BLACKLIST: {} ({} consecutive losses) - paused for 2 hours",
                 symbol, losses
             );
-            self.temp_blacklist.insert(symbol.to_string(), Instant::now());
+            self.temp_blacklist.add(symbol, self.clock.now() + chrono::Duration::hours(2));
         }
     }
 
     /// Reset loss counter on winning trade
+    #[allow(dead_code)]
     fn reset_losses(&mut self, symbol: &str) {
         if self.symbol_consecutive_losses.contains_key(symbol) {
             debug!("✅ Win! Resetting loss counter for {}", symbol);
@@ -1028,25 +1812,397 @@ impl StrategyEngine {
         }
     }
 
+    /// ✅ IMPROVEMENT #3: The trade cooldown starts now (persisted across restarts)
+    fn start_cooldown(&mut self) {
+        self.last_trade_time = Some(Instant::now());
+        self.cooldown.record_trade(self.clock.now());
+    }
+
     /// Check if symbol is temporarily blacklisted
     fn is_temp_blacklisted(&self, symbol: &str) -> bool {
-        const BLACKLIST_DURATION_HOURS: u64 = 2;
-
-        if let Some(blacklisted_at) = self.temp_blacklist.get(symbol) {
-            let elapsed_secs = blacklisted_at.elapsed().as_secs();
-            let is_still_blacklisted = elapsed_secs < (BLACKLIST_DURATION_HOURS * 3600);
-            
-            if is_still_blacklisted {
-                let remaining_hours = (BLACKLIST_DURATION_HOURS * 3600 - elapsed_secs) / 3600;
-                debug!("🚫 {} is blacklisted ({} hours remaining)", symbol, remaining_hours);
-            }
-            
-            is_still_blacklisted
+        let now = self.clock.now();
+        if let Some(until) = self.temp_blacklist.blocked_until(symbol, now) {
+            let remaining_mins = (until - now).num_minutes();
+            debug!("🚫 {} is blacklisted ({}h {}m remaining)", symbol, remaining_mins / 60, remaining_mins % 60);
+            true
         } else {
             false
         }
     }
 
+    /// ✅ AUTO-BLACKLIST: Block a symbol once its realized loss reaches SYMBOL_MAX_LOSS_USD
+    fn apply_symbol_loss_limit(&mut self, symbol: &str, net_pnl: Decimal) {
+        let max_loss = self.config.symbol_max_loss_usd;
+        if max_loss <= 0.0 {
+            return;
+        }
+        let realized = self.symbol_realized_pnl.entry(symbol.to_string()).or_insert(Decimal::ZERO);
+        *realized += net_pnl;
+        if realized.to_f64().unwrap_or(0.0) > -max_loss {
+            return;
+        }
+
+        let loss = -*realized;
+        self.symbol_realized_pnl.remove(symbol);
+        let until = self.clock.now() + chrono::Duration::hours(self.config.symbol_blacklist_hours as i64);
+        self.temp_blacklist.add(symbol, until);
+        warn!(
+            "🚫 AUTO-BLACKLIST: {} lost ${:.4} (limit ${:.2}), excluded until {}",
+            symbol, loss, max_loss, until.format("%Y-%m-%d %H:%M UTC")
+        );
+        self.notify(Alert::warning(format!(
+            "🚫 <b>{} blacklisted</b>\nRealized loss ${:.4} (limit ${:.2})\nExcluded until {}",
+            escape_html(symbol),
+            loss,
+            max_loss,
+            until.format("%Y-%m-%d %H:%M UTC")
+        )));
+    }
+
+    /// ✅ TRADE NOTIFICATIONS: Build the round-trip record from entry + close fills
+    fn handle_close_filled(&mut self, fill: Fill, reason: ExitReason) {
+        let open_trade = self.open_trade.take();
+        let entry_context = self.entry_context.take();
+
+        // Fall back to the exchange position if we never saw the entry fill
+        // (e.g. position adopted after restart)
+        let (side, entry_price, entry_fee, opened_at, hold_secs) = match (&open_trade, &self.current_position) {
+            (Some(open), _) => (
+                if open.entry.side == OrderSide::Buy { PositionSide::Long } else { PositionSide::Short },
+                open.entry.avg_price,
+                open.entry.fee,
+                open.opened_at,
+                open.started.elapsed().as_secs(),
+            ),
+            (None, Some(position)) => (
+                position.side,
+                position.entry_price,
+                Decimal::ZERO,
+                self.clock.now(),
+                self.position_start_time.map(|t| t.elapsed().as_secs()).unwrap_or(0),
+            ),
+            (None, None) => {
+                warn!("CloseFilled for {} without a known entry, skipping trade record", fill.symbol);
+                return;
+            }
+        };
+
+        let gross_pnl = TradeRecord::gross_pnl(side, fill.qty, entry_price, fill.avg_price);
+        let fees = entry_fee + fill.fee;
+        // CloseFilled arrives before the flat PositionUpdate, so the position still carries the leverage
+        let leverage = self
+            .current_position
+            .as_ref()
+            .map(|p| p.leverage)
+            .or_else(|| self.config.leverage.and_then(Decimal::from_f64))
+            .unwrap_or(Decimal::ONE);
+        let (pnl_percent, roe_percent) =
+            TradeRecord::returns(gross_pnl, gross_pnl - fees, fill.qty, entry_price, leverage);
+        let entry_slippage_bps = match (&open_trade, entry_context.and_then(|c| c.ref_price)) {
+            (Some(open), Some(ref_price)) => Some(TradeRecord::slippage_bps(open.entry.side, ref_price, open.entry.avg_price)),
+            _ => None,
+        };
+        let trade = TradeRecord {
+            symbol: fill.symbol.clone(),
+            side,
+            qty: fill.qty,
+            entry_price,
+            exit_price: fill.avg_price,
+            gross_pnl,
+            fees,
+            net_pnl: gross_pnl - fees,
+            opened_at,
+            closed_at: self.clock.now(),
+            hold_secs,
+            mode: self.config.trading_mode,
+            exit_reason: reason,
+            leverage,
+            pnl_percent,
+            roe_percent,
+            confirmations: entry_context.map(|c| c.confirmations),
+            price_change_24h: entry_context.and_then(|c| c.price_change_24h),
+            entry_atr_percent: entry_context.and_then(|c| c.atr_percent),
+            entry_slippage_bps,
+        };
+
+        info!(
+            "🧾 Trade closed: {} {:?} {} | {} → {} | Net PnL: ${:.4} (fees ${:.4}) | Move {:+.2}% | ROE {:+.2}% @ {}x | {}",
+            trade.symbol, trade.side, trade.qty, trade.entry_price, trade.exit_price,
+            trade.net_pnl, trade.fees, trade.pnl_percent, trade.roe_percent, trade.leverage, trade.exit_reason
+        );
+        self.notify(Alert::trade_closed(&trade));
+        self.dataset.trade_closed(&trade);
+        self.apply_loss_streak(trade.net_pnl);
+        self.apply_symbol_loss_limit(&trade.symbol.0, trade.net_pnl);
+        self.events.publish(EventKind::TradeClosed { trade: trade.clone() });
+        self.journal.record(JournalEntry::Trade(trade));
+    }
+
+    /// ✅ LOSS STREAK: Escalate the cooldown on consecutive losses
+    fn apply_loss_streak(&mut self, net_pnl: Decimal) {
+        let losses_before = self.loss_streak.consecutive_losses();
+        match self.loss_streak.record_trade(net_pnl, self.clock.now()) {
+            StreakAction::None => {
+                if losses_before > 0 && self.loss_streak.consecutive_losses() == 0 {
+                    debug!("✅ Loss streak of {} ended", losses_before);
+                }
+            }
+            StreakAction::Paused(until) => {
+                let losses = self.loss_streak.consecutive_losses();
+                warn!("🧊 {} consecutive losses, entries paused until {}", losses, until.format("%H:%M UTC"));
+                self.notify(Alert::warning(format!(
+                    "🧊 <b>Loss streak: {} in a row</b>\nNo new entries until {}",
+                    losses,
+                    until.format("%H:%M UTC")
+                )));
+            }
+            StreakAction::Halted => {
+                let losses = self.loss_streak.consecutive_losses();
+                error!("🛑 {} consecutive losses, entries halted until /resume", losses);
+                self.notify(Alert::critical(format!(
+                    "🛑 <b>Loss streak: {} in a row</b>\nTrading halted — send /resume to continue",
+                    losses
+                )));
+            }
+        }
+    }
+
+    /// ✅ OPERATOR CONTROL: Commands confirmed via Telegram
+    async fn handle_control(&mut self, command: ControlCommand) {
+        warn!("🕹️  Operator command received: {}", command);
+        self.events.publish(EventKind::ControlCommand { command: command.to_string() });
+
+        match command {
+            ControlCommand::Pause => {
+                self.manual_pause = true;
+                self.pending_signal = None;
+                self.confirmation_count = 0;
+                warn!("⏸️ Paused by operator: no new entries until resumed");
+                self.notify(Alert::warning(
+                    "⏸️ <b>Paused</b>\nNo new entries until resumed, the open position is still managed".to_string(),
+                ));
+            }
+            ControlCommand::Resume => {
+                let losses = self.loss_streak.consecutive_losses();
+                self.loss_streak.resume();
+                self.manual_pause = false;
+                info!("▶️ Loss streak reset ({} losses), entries resumed", losses);
+                let mut text = format!("▶️ <b>Resumed</b>\nLoss streak of {} cleared", losses);
+                if self.kill_switch_engaged {
+                    text.push_str("\n🛑 Kill switch still engaged until restart");
+                }
+                self.notify(Alert::success(text));
+            }
+            ControlCommand::KillSwitch => {
+                self.kill_switch_engaged = true;
+                self.pending_signal = None;
+                self.confirmation_count = 0;
+                error!("🛑 KILL SWITCH ENGAGED: No new entries until restart");
+                let text = if self.close_open_position(ExitReason::Manual).await {
+                    "🛑 <b>KILL SWITCH ENGAGED</b>\nClosing open position, no new entries until restart"
+                } else {
+                    "🛑 <b>KILL SWITCH ENGAGED</b>\nNo open position, no new entries until restart"
+                };
+                self.notify(Alert::critical(text.to_string()));
+            }
+            ControlCommand::ClosePosition => {
+                let text = if self.close_open_position(ExitReason::Manual).await {
+                    "Manual close requested".to_string()
+                } else {
+                    format!("Nothing to close (state: {:?})", self.state)
+                };
+                self.notify(Alert::warning(text));
+            }
+        }
+    }
+
+    /// Send a ClosePosition for the open position outside the exit rules (manual, kill switch,
+    /// drift). Returns true if the close is in flight.
+    async fn close_open_position(&mut self, reason: ExitReason) -> bool {
+        if self.state == StrategyState::ClosingPosition {
+            info!("Close already in progress");
+            return true;
+        }
+        if self.state != StrategyState::PositionOpen {
+            return false;
+        }
+        let Some(position) = self.current_position.clone() else {
+            return false;
+        };
+
+        self.log_decision(DecisionEntry::Exit {
+            reason,
+            price: Some(position.current_price),
+            pnl_percent: Some(position.pnl_percent()),
+        });
+        self.state.transition(StrategyState::ClosingPosition, "close outside the exit rules");
+        self.last_close_attempt = Some(Instant::now());
+
+        // ✅ FIX BUG #17 (CRITICAL): Use timeout to prevent blocking
+        let send_result = tokio::time::timeout(
+            Duration::from_secs(5),
+            self.execution_tx.send(ExecutionMessage::ClosePosition {
+                symbol: position.symbol.clone(),
+                position_side: position.side,
+                reason,
+            })
+        ).await;
+
+        match send_result {
+            Ok(Ok(_)) => true,
+            Ok(Err(e)) => {
+                warn!("Failed to send {} ClosePosition: {}", reason, e);
+                self.state.transition(StrategyState::PositionOpen, "close send failed");
+                false
+            }
+            Err(_) => {
+                warn!("⚠️  CRITICAL: ExecutionActor timeout on {} close! Reverting state.", reason);
+                self.state.transition(StrategyState::PositionOpen, "close send timed out");
+                false
+            }
+        }
+    }
+
+    /// ✅ HEARTBEAT: Uptime, symbol, state and last tick age
+    fn send_heartbeat(&self) {
+        let tick_age = self.last_tick_at.map(|t| t.elapsed().as_secs());
+        let feed_stalled = tick_age
+            .map(|age| age >= self.config.heartbeat_stale_tick_secs)
+            .unwrap_or(self.current_symbol.is_some());
+
+        let mut text = format!(
+            "<b>Alive</b> — uptime {}\n\
+             Symbol: {}\n\
+             State: {:?}\n\
+             Last tick: {}{}",
+            format_duration(self.started_at.elapsed().as_secs()),
+            self.current_symbol.as_ref().map(Symbol::as_str).unwrap_or("-"),
+            self.state,
+            tick_age.map(|a| format!("{} ago", format_duration(a))).unwrap_or_else(|| "never".to_string()),
+            if feed_stalled { " ⚠️ FEED STALLED" } else { "" }
+        );
+        if let (Some(atr), Some(regime)) = (self.candles.atr_percent(), self.candles.regime()) {
+            text.push_str(&format!("\nATR(1m): {:.3}% | Regime: {}", atr, regime));
+        }
+        if self.daily_trades.max_per_day() > 0 {
+            text.push_str(&format!(
+                "\nTrades today: {}/{}",
+                self.daily_trades.count(self.clock.now()),
+                self.daily_trades.max_per_day()
+            ));
+        }
+        if self.kill_switch_engaged {
+            text.push_str("\n🛑 Kill switch engaged");
+        } else if self.loss_streak.is_halted() {
+            text.push_str(&format!(
+                "\n🛑 Halted after {} losses — /resume",
+                self.loss_streak.consecutive_losses()
+            ));
+        } else if let Some(until) = self.loss_streak.paused_until(self.clock.now()) {
+            text.push_str(&format!("\n🧊 Loss streak pause until {}", until.format("%H:%M UTC")));
+        } else if self.manual_pause {
+            text.push_str("\n⏸️ Paused by operator — /resume");
+        } else if self.is_paused {
+            text.push_str("\n⏸️ Circuit breaker paused");
+        }
+
+        debug!("💓 Heartbeat sent");
+        self.notify(Alert::new(AlertLevel::Heartbeat, text));
+    }
+
+    fn params(&self) -> StrategyParams {
+        StrategyParams {
+            momentum_threshold: self.momentum_threshold * 100.0,
+            trade_cooldown_secs: self.trade_cooldown_secs,
+            max_trades_per_day: self.daily_trades.max_per_day(),
+        }
+    }
+
+    fn status(&mut self) -> StrategyStatus {
+        let now = self.clock.now();
+        StrategyStatus {
+            symbol: self.current_symbol.clone(),
+            state: format!("{:?}", self.state),
+            position: self.current_position.clone(),
+            peak_pnl_percent: self.peak_pnl_percent,
+            orderbook: self.last_orderbook.clone(),
+            last_price: self.tick_buffer.last().map(|t| t.price),
+            ticks_buffered: self.tick_buffer.len(),
+            momentum_percent: self.calculate_momentum().map(|m| m * 100.0),
+            atr_percent: self.candles.atr_percent(),
+            regime: self.candles.regime().map(|r| r.to_string()),
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            last_tick_age_secs: self.last_tick_at.map(|t| t.elapsed().as_secs()),
+            paused: self.manual_pause,
+            kill_switch: self.kill_switch_engaged,
+            circuit_breaker: self.is_paused,
+            loss_streak_halted: self.loss_streak.is_halted(),
+            loss_streak_paused_until: self.loss_streak.paused_until(now),
+            consecutive_losses: self.loss_streak.consecutive_losses(),
+            trades_today: self.daily_trades.count(now),
+            params: self.params(),
+        }
+    }
+
+    /// ✅ LIVE PARAMS: Operator tuning without a restart (not persisted)
+    fn apply_params(&mut self, patch: ParamPatch) {
+        let before = self.params();
+        if let Some(threshold) = patch.momentum_threshold {
+            self.momentum_threshold = threshold / 100.0;
+        }
+        if let Some(cooldown) = patch.trade_cooldown_secs {
+            self.trade_cooldown_secs = cooldown;
+        }
+        if let Some(max) = patch.max_trades_per_day {
+            self.daily_trades.set_max_per_day(max);
+        }
+        let after = self.params();
+        warn!("🎛️ Parameters changed: {:?} -> {:?}", before, after);
+        self.notify(Alert::info(format!(
+            "🎛️ <b>Parameters changed</b>\nMomentum threshold: {:.3}% → {:.3}%\nCooldown: {}s → {}s\nMax trades/day: {} → {}",
+            before.momentum_threshold,
+            after.momentum_threshold,
+            before.trade_cooldown_secs,
+            after.trade_cooldown_secs,
+            before.max_trades_per_day,
+            after.max_trades_per_day
+        )));
+    }
+
+    /// Queue an alert without ever blocking the hot path
+    fn notify(&self, alert: Alert) {
+        if let Err(e) = self.notifier_tx.try_send(NotifierMessage::Alert(alert)) {
+            debug!("Dropped alert: {}", e);
+        }
+    }
+
+    /// Remember the signal of the trade that just closed (duplicate-signal guard)
+    fn record_exit_signal(&mut self) {
+        if let Some(signal) = self.entry_signal.take() {
+            self.last_exit_signal = Some((signal, Instant::now()));
+        }
+    }
+
+    /// ✅ DUPLICATE SIGNAL GUARD: Same direction + same VWAP band within the window after an exit
+    fn is_duplicate_signal(&self, bullish: bool, vwap: Decimal) -> bool {
+        let Some((last, closed_at)) = self.last_exit_signal else {
+            return false;
+        };
+
+        if last.bullish != bullish
+            || closed_at.elapsed().as_secs() >= self.config.duplicate_signal_window_secs
+            || last.vwap.is_zero()
+        {
+            return false;
+        }
+
+        let distance_bps = ((vwap - last.vwap) / last.vwap * Decimal::from(10000))
+            .abs()
+            .to_f64()
+            .unwrap_or(f64::MAX);
+        distance_bps <= self.config.duplicate_signal_band_bps
+    }
+
     // ⚡ PHASE 2: Removed calculate_volatility() and calculate_dynamic_risk()
     // These functions are no longer used after Phase 1 fixed SL/TP (0.35%/0.70%)
     // Keeping this comment for history - they're in git if needed
@@ -1067,47 +2223,217 @@ impl StrategyEngine {
         Some(distance)
     }
 
-    async fn execute_entry(&mut self, momentum: f64, orderbook: &OrderBookSnapshot) {
+    /// ✅ SIGNAL DATASET: Feature vector of the signal being evaluated
+    fn signal_sample(&mut self, momentum: f64, confirmations: u8, orderbook: &OrderBookSnapshot) -> SignalSample {
+        let reference = self.reference.as_ref().and_then(|r| r.features(&orderbook.symbol, Instant::now()));
+        let divergence = self.index_prices.divergence(Instant::now());
+        SignalSample {
+            at: self.clock.now(),
+            symbol: orderbook.symbol.clone(),
+            bullish: momentum > 0.0,
+            price: orderbook.price(self.config.price_source),
+            features: SignalFeatures {
+                momentum_percent: momentum * 100.0,
+                vwap_distance_percent: self.calculate_vwap_distance().map(|d| d * 100.0),
+                trend_bullish: self.calculate_trend(),
+                atr_percent: self.candles.atr_percent(),
+                regime: self.candles.regime().map(|r| r.to_string()),
+                spread_bps: orderbook.spread_bps,
+                imbalance: orderbook.imbalance(),
+                price_change_24h: self.price_change_24h,
+                confirmations,
+                reference_premium_bps: reference.map(|r| r.premium_bps),
+                reference_lead_bps: reference.map(|r| r.lead_bps),
+                mark_index_bps: divergence.and_then(|d| d.mark_index_bps),
+                last_index_bps: divergence.and_then(|d| d.last_index_bps),
+                taker_buy_ratio: self.taker_flow.buy_ratio(),
+            },
+            taken: false,
+            reject_reason: None,
+            model_score: None,
+            outcome: SignalOutcome::default(),
+        }
+    }
+
+    /// ✅ REFERENCE FEED: Binance already moving against the signal blocks the entry
+    /// (no fresh reference data lets it through)
+    fn reference_gate(&self, bullish: bool, orderbook: &OrderBookSnapshot) -> Result<(), &'static str> {
+        let max_adverse = self.config.reference_max_adverse_bps;
+        let Some(reference) = self.reference.as_ref().filter(|_| max_adverse > 0.0) else {
+            return Ok(());
+        };
+        let Some(features) = reference.features(&orderbook.symbol, Instant::now()) else {
+            return Ok(());
+        };
+        let adverse = if bullish { -features.lead_bps } else { features.lead_bps };
+        if adverse > max_adverse {
+            info!(
+                "🛰️  Entry blocked: Binance leads {:+.1} bps against the {} signal (max: {:.1})",
+                features.lead_bps,
+                if bullish { "LONG" } else { "SHORT" },
+                max_adverse
+            );
+            return Err("reference");
+        }
+        Ok(())
+    }
+
+    /// ✅ INDEX DIVERGENCE: Don't chase a perp already stretched from its index in the signal's direction
+    fn index_gate(&self, bullish: bool) -> Result<(), &'static str> {
+        let max_bps = self.config.max_index_divergence_bps;
+        match self.index_prices.blocks_entry(bullish, max_bps, Instant::now()) {
+            Some(stretch) => {
+                info!("📐 Entry blocked: Last price {:+.1} bps from index (max: {:.1})", stretch, max_bps);
+                Err("index_divergence")
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Widest spread an entry accepts: in ticks with MAX_SPREAD_TICKS, else MAX_SPREAD_BPS
+    fn max_spread_bps(&self, orderbook: &OrderBookSnapshot) -> f64 {
+        self.current_specs
+            .as_ref()
+            .and_then(|specs| self.spread_gate.limit_bps(orderbook, specs.tick_size))
+            .unwrap_or(self.config.max_spread_bps)
+    }
+
+    /// ✅ TAKER FLOW: A move without aggressive takers on its side is drift, not accumulation
+    fn taker_flow_gate(&self, bullish: bool) -> Result<(), &'static str> {
+        let min_share = self.config.min_taker_flow;
+        match self.taker_flow.blocks_entry(bullish, min_share) {
+            Some(share) => {
+                info!(
+                    "🌊 Entry blocked: Taker {} share {:.0}% (min: {:.0}%)",
+                    if bullish { "buy" } else { "sell" },
+                    share * 100.0,
+                    min_share * 100.0
+                );
+                Err("taker_flow")
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// ✅ SIGNAL MODEL: Score the confirmed signal; Err(reason) blocks the entry.
+    /// Scoring errors block too (a broken model must not trade unfiltered).
+    fn model_gate(&mut self, sample: Option<&mut SignalSample>) -> Result<(), &'static str> {
+        let (Some(model), Some(sample)) = (self.signal_model.as_mut(), sample) else {
+            return Ok(());
+        };
+        let threshold = self.config.signal_model_threshold;
+        match model.score(&sample.features) {
+            Ok(score) => {
+                sample.model_score = Some(score);
+                if f64::from(score) < threshold {
+                    info!("🤖 Entry blocked: Model score {:.3} < {:.3}", score, threshold);
+                    Err("model")
+                } else {
+                    info!("🤖 Model score {:.3} ≥ {:.3}", score, threshold);
+                    Ok(())
+                }
+            }
+            Err(e) => {
+                warn!("🤖 Entry blocked: Model scoring failed: {:#}", e);
+                Err("model_error")
+            }
+        }
+    }
+
+    fn push_signal_sample(&mut self, mut sample: SignalSample, result: Result<(), &'static str>) {
+        sample.taken = result.is_ok();
+        sample.reject_reason = result.err().map(str::to_string);
+        if self.decisions.is_enabled() {
+            self.log_decision(DecisionEntry::Signal { sample: Box::new(sample.clone()) });
+        }
+        self.dataset.record(sample, std::time::Instant::now());
+    }
+
+    /// Signal features are needed by the dataset and/or the decision log
+    fn wants_signal_samples(&self) -> bool {
+        self.dataset.is_enabled() || self.decisions.is_enabled()
+    }
+
+    fn record_signal(&mut self, momentum: f64, orderbook: &OrderBookSnapshot, result: Result<(), &'static str>) {
+        if self.wants_signal_samples() {
+            let sample = self.signal_sample(momentum, self.confirmation_count, orderbook);
+            self.push_signal_sample(sample, result);
+        }
+    }
+
+    /// Feed summary since the last call (the counter restarts on a symbol switch)
+    fn publish_tick_stats(&mut self) {
+        let Some(symbol) = self.current_symbol.clone() else {
+            return;
+        };
+        let ticks = self.tick_counter.saturating_sub(self.stats_tick_mark);
+        self.stats_tick_mark = self.tick_counter;
+        if !self.events.has_subscribers() {
+            return;
+        }
+        let momentum_percent = self.calculate_momentum().map(|m| m * 100.0);
+        self.events.publish(EventKind::TickStats {
+            symbol,
+            ticks: ticks as u64,
+            last_price: self.tick_buffer.last().map(|t| t.price),
+            spread_bps: self.last_orderbook.as_ref().map(|o| o.spread_bps),
+            momentum_percent,
+        });
+    }
+
+    fn publish_signal(&self, momentum: f64, orderbook: &OrderBookSnapshot, source: &str, result: Result<(), &'static str>) {
+        self.events.publish(EventKind::Signal {
+            symbol: orderbook.symbol.clone(),
+            bullish: momentum > 0.0,
+            price: orderbook.price(self.config.price_source),
+            source: source.to_string(),
+            taken: result.is_ok(),
+            reject_reason: result.err().map(str::to_string),
+        });
+    }
+
+    /// Remember how a new signal looked before confirmation
+    fn start_signal_sample(&mut self, momentum: f64) {
+        self.pending_signal_sample = None;
+        if !self.wants_signal_samples() {
+            return;
+        }
+        if let Some(orderbook) = self.last_orderbook.clone() {
+            self.pending_signal_sample = Some(self.signal_sample(momentum, 0, &orderbook));
+        }
+    }
+
+    /// A signal that faded or flipped before confirming
+    fn finish_unconfirmed_signal(&mut self) {
+        if let Some(mut sample) = self.pending_signal_sample.take() {
+            sample.features.confirmations = self.confirmation_count;
+            self.push_signal_sample(sample, Err("unconfirmed"));
+        }
+    }
+
+    /// Sends the entry order; Err(reason) when the signal was rejected instead
+    async fn execute_entry(&mut self, momentum: f64, confirmations: u8, orderbook: &OrderBookSnapshot) -> Result<(), &'static str> {
         // ⚡ PHASE 1: FIXED RISK - Predictable and simple
         // Problem: Dynamic SL (0.7-3.0%) made risk uncontrollable
         // Solution: Fixed tight SL for Momentum scalping
         
-        let (sl_percent, tp_percent) = (0.35, 0.70); // 1:2 R/R ratio
-        info!("🎯 MOMENTUM: Fixed SL={:.2}% TP={:.2}% (1:2 R/R)", sl_percent, tp_percent);
-        
-        // ⚡ PHASE 1: Basic liquidity check via bid/ask sizes
-        // OrderBookSnapshot has bid_size and ask_size
-        let bid_volume_usd = (orderbook.bid_size * orderbook.best_bid).to_f64().unwrap_or(0.0);
-        let ask_volume_usd = (orderbook.ask_size * orderbook.best_ask).to_f64().unwrap_or(0.0);
-        
-        const MIN_SIZE_USD: f64 = 1000.0; // $1k minimum on best level
-        
-       if bid_volume_usd < MIN_SIZE_USD || ask_volume_usd < MIN_SIZE_USD {
-            warn!(
-                "❌ Entry blocked: Low liquidity | Bid: ${:.0} | Ask: ${:.0}",
-                bid_volume_usd, ask_volume_usd
+        // ✅ DUPLICATE SIGNAL GUARD: Block re-entry on the level that just closed us out
+        let signal_is_bullish = momentum > 0.0;
+        let signal_vwap = self.get_vwap_short().unwrap_or(orderbook.mid_price);
+        if self.is_duplicate_signal(signal_is_bullish, signal_vwap) {
+            info!(
+                "🔁 Entry blocked: Duplicate {} signal at VWAP {} (same band as last exit, window {}s)",
+                if signal_is_bullish { "LONG" } else { "SHORT" },
+                signal_vwap,
+                self.config.duplicate_signal_window_secs
             );
             self.pending_signal = None;
             self.confirmation_count = 0;
-            return;
+            return Err("duplicate");
         }
-        
-        // ✅ FIX MEMORY LOSS BUG: Store dynamic risk for this trade
-        // CRITICAL: handle_orderbook must use these values, not config!
-        self.active_dynamic_risk = Some((sl_percent, tp_percent));
 
-        info!(
-            "🎯 ENTRY SIGNAL: {} momentum={:.4}% spread={:.2}bps | Dynamic SL={:.2}% TP={:.2}%",
-            orderbook.symbol,
-            momentum * 100.0,
-            orderbook.spread_bps,
-            sl_percent,
-            tp_percent
-        );
-        
-        // ✅ TRAILING STOP: Activate for momentum trades
-        self.is_momentum_trade = true; // Always true in Momentum-only mode
-        self.peak_pnl_percent = 0.0;
+        let (sl_percent, tp_percent) = (ENTRY_STOP_LOSS_PERCENT, ENTRY_TAKE_PROFIT_PERCENT);
+        info!("🎯 MOMENTUM: Fixed SL={:.2}% TP={:.2}% (1:2 R/R)", sl_percent, tp_percent);
         
         // ⚡ MOMENTUM: Trade WITH the trend (simple and clear)
         let side = if momentum > 0.0 {
@@ -1116,6 +2442,21 @@ impl StrategyEngine {
             OrderSide::Sell // Price < VWAP → SHORT
         };
 
+        // ✅ REWARD:RISK: Skip trades whose edge the costs eat
+        if self.config.min_reward_risk > 0.0 {
+            let plan = RewardRisk::new(side, orderbook.mid_price, self.get_vwap_long(), tp_percent, sl_percent)
+                .with_costs(ROUND_TRIP_FEE_PERCENT, orderbook.spread_bps, self.config.expected_slippage_bps);
+            if plan.ratio() < self.config.min_reward_risk {
+                info!(
+                    "⚖️ Entry blocked: R:R {:.2} after costs (min: {:.2}) | reward {:.3}%, risk {:.3}%, costs {:.3}%",
+                    plan.ratio(), self.config.min_reward_risk, plan.reward_percent, plan.risk_percent, plan.cost_percent
+                );
+                self.pending_signal = None;
+                self.confirmation_count = 0;
+                return Err("reward_risk");
+            }
+        }
+
         // ✅ RISK-ADJUSTED POSITION SIZING (FIXED DOLLAR RISK)
         // Goal: Lose exactly $X regardless of SL size or volatility
         // Formula: Position_Size = Risk_Amount / (SL_Percent / 100)
@@ -1130,7 +2471,7 @@ impl StrategyEngine {
             error!("⚠️  Cannot calculate position size with zero/negative SL, aborting entry");
             self.pending_signal = None;
             self.confirmation_count = 0;
-            return;
+            return Err("invalid_sl");
         }
 
         let sl_decimal = sl_percent / 100.0; // Convert to decimal (e.g., 0.35% -> 0.0035)
@@ -1157,10 +2498,59 @@ impl StrategyEngine {
                    position_value / orderbook.mid_price, qty, specs.qty_step);
         }
 
-        // ⚡ PHASE 1: MOMENTUM ONLY - Market IOC for speed
-        // Speed is king in HFT scalping
-        info!("🚀 MOMENTUM: Using MARKET IOC for speed (Taker Fee)");
-        let (order_type, price, time_in_force) = (OrderType::Market, None, TimeInForce::IOC);
+        // ✅ LIQUIDITY GATE: Visible depth on the side we consume must cover our size
+        // (replaces fixed $1k-on-best-level check which ignored order size)
+        if !orderbook.is_liquid(
+            side,
+            qty,
+            self.config.liquidity_spread_multiple,
+            self.config.liquidity_depth_factor,
+        ) {
+            let depth = orderbook.depth_within(
+                side,
+                orderbook.spread()
+                    * Decimal::from_f64(self.config.liquidity_spread_multiple).unwrap_or(Decimal::ONE),
+            );
+            warn!(
+                "❌ Entry blocked: Low liquidity | Need {} × {:.1} within {:.1} spreads | Visible: {}",
+                qty, self.config.liquidity_depth_factor, self.config.liquidity_spread_multiple, depth
+            );
+            self.pending_signal = None;
+            self.confirmation_count = 0;
+            return Err("low_liquidity");
+        }
+
+        // ✅ SHARED RISK GATE: The other shards may already hold every position slot
+        if let Some(ref gate) = self.position_gate {
+            if !gate.try_acquire(orderbook.symbol.as_str()) {
+                info!(
+                    "🚧 Entry blocked: {} position(s) already open across shards (MAX_OPEN_POSITIONS)",
+                    gate.max_open()
+                );
+                self.pending_signal = None;
+                self.confirmation_count = 0;
+                return Err("position_limit");
+            }
+            self.holds_position_slot = true;
+        }
+
+        // ✅ FIX MEMORY LOSS BUG: Store dynamic risk for this trade
+        // CRITICAL: the position monitor must use these values, not config!
+        self.active_dynamic_risk = Some((sl_percent, tp_percent));
+
+        info!(
+            "🎯 ENTRY SIGNAL: {} momentum={:.4}% spread={:.2}bps | Dynamic SL={:.2}% TP={:.2}% | Regime: {}",
+            orderbook.symbol,
+            momentum * 100.0,
+            orderbook.spread_bps,
+            sl_percent,
+            tp_percent,
+            self.candles.regime().map(|r| r.to_string()).unwrap_or_else(|| "-".to_string())
+        );
+
+        // ✅ TRAILING STOP: Activate for momentum trades
+        self.is_momentum_trade = true; // Always true in Momentum-only mode
+        self.peak_pnl_percent = 0.0;
 
         // ✅ Pass symbol specs to order for precision validation
         let (qty_step, tick_size) = if let Some(ref specs) = self.current_specs {
@@ -1169,6 +2559,15 @@ impl StrategyEngine {
             (None, None)
         };
 
+        // ✅ PRICING: Strong moves cross the spread, weaker ones wait as a maker (join or improve by a tick)
+        let urgency = momentum.abs() / self.momentum_threshold;
+        let terms = self.pricing.terms(side, orderbook, tick_size, urgency);
+        let (order_type, price, time_in_force) = (terms.order_type, terms.price, terms.time_in_force);
+        info!(
+            "🚀 Pricing: {} ({:?} {:?} @ {:?}) | urgency {:.2}x threshold, spread {:.2}bps",
+            terms.pricing, order_type, time_in_force, price, urgency, orderbook.spread_bps
+        );
+
         let order = Order {
             symbol: orderbook.symbol.clone(),
             side,
@@ -1179,15 +2578,32 @@ impl StrategyEngine {
             reduce_only: false,
             qty_step,
             tick_size,
+            ref_price: Some(orderbook.mid_price),
+            allow_duplicate: false,
+            // ✅ HARD STOP: Catastrophe cover behind this trade's stop, placed on the exchange once filled
+            hard_stop_percent: (self.config.hard_stop_gap_percent > 0.0)
+                .then_some(sl_percent + self.config.hard_stop_gap_percent),
+            position_idx: 0,
         };
 
         // ✅ FIXED: Don't set position optimistically - wait for exchange confirmation
         // Position will be set via PositionUpdate message from ExecutionActor
 
         // ✅ FIXED: Transition to OrderPending state
-        self.state = StrategyState::OrderPending;
+        self.state.transition(StrategyState::OrderPending, "entry order sent");
+        self.entry_signal = Some(SignalFingerprint {
+            bullish: signal_is_bullish,
+            vwap: signal_vwap,
+        });
+        self.entry_context = Some(EntryContext {
+            confirmations,
+            price_change_24h: self.price_change_24h,
+            atr_percent: self.candles.atr_percent(),
+            ref_price: Some(orderbook.mid_price),
+        });
 
         // Send order to execution
+        let submitted = (self.events.has_subscribers() || self.decisions.is_enabled()).then(|| order.clone());
         if let Err(e) = self
             .execution_tx
             .send(ExecutionMessage::PlaceOrder(order))
@@ -1196,9 +2612,17 @@ impl StrategyEngine {
             warn!("Failed to send PlaceOrder to execution: {}", e);
             // ✅ FIX MEMORY LEAK: Clear dynamic risk if order send failed
             self.active_dynamic_risk = None;
+            self.entry_signal = None;
+            self.entry_context = None;
             // Revert state if send failed
-            self.state = StrategyState::Idle;
+            self.state.transition(StrategyState::Idle, "entry send failed");
+            return Err("send_failed");
+        }
+        if let Some(order) = submitted {
+            self.log_decision(DecisionEntry::OrderSent { order: order.clone() });
+            self.events.publish(EventKind::OrderSubmitted { order });
         }
+        Ok(())
     }
 
 }
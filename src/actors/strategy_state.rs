@@ -0,0 +1,224 @@
+//! Strategy State Machine
+//!
+//! The strategy's order/position state with its legal transitions in one
+//! table. A transition outside the table still happens (the handlers know
+//! more about the exchange than the table does) but is logged and counted, so
+//! a desync shows up in `/metrics` instead of only as a missed trade. States
+//! that should resolve on their own (an order or close waiting for the
+//! exchange) are watched: one that outlives `STUCK_STATE_TIMEOUT_SECS` is
+//! reported, and the strategy reconciles it with the exchange. A reconcile
+//! that fails or goes unanswered leaves the state as it was, so the report
+//! repeats with a doubling backoff until the state moves.
+
+use crate::metrics::StateMachineStats;
+use std::fmt;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// ✅ FIXED: Proper state machine for order lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrategyState {
+    Idle,                 // No position, no order
+    OrderPending,         // Order sent, waiting for confirmation
+    PositionOpen,         // Position confirmed by exchange
+    ClosingPosition,      // Close order sent, waiting for confirmation
+    SwitchingSymbol,      // ✅ FIX BUG #1: Closing position before symbol switch
+}
+
+impl StrategyState {
+    /// Transition table (staying in the same state is always fine)
+    pub fn can_transition_to(self, to: StrategyState) -> bool {
+        use StrategyState::*;
+        self == to
+            || matches!(
+                (self, to),
+                // Entry sent / position found by the periodic verification
+                (Idle, OrderPending | PositionOpen)
+                    | (OrderPending, PositionOpen | Idle)
+                    // Exit, symbol switch, or the position vanished (liquidation)
+                    | (PositionOpen, ClosingPosition | SwitchingSymbol | Idle)
+                    // Closed, close failed (position still there), or a switch while closing
+                    | (ClosingPosition, Idle | PositionOpen | SwitchingSymbol)
+                    | (SwitchingSymbol, Idle | PositionOpen)
+            )
+    }
+
+    /// Waiting on the exchange: watched by the stuck-state timer (the others may last as long as they like)
+    pub fn is_waiting(self) -> bool {
+        matches!(
+            self,
+            StrategyState::OrderPending | StrategyState::ClosingPosition | StrategyState::SwitchingSymbol
+        )
+    }
+}
+
+/// Default `STUCK_STATE_TIMEOUT_SECS`
+pub const STUCK_AFTER: Duration = Duration::from_secs(60);
+
+/// Longest gap between repeated reports of the same stay
+pub const STUCK_RECHECK_MAX: Duration = Duration::from_secs(600);
+
+/// Current state plus when it was entered
+pub struct StateMachine {
+    state: StrategyState,
+    entered_at: Instant,
+    /// How long a waiting state may last
+    stuck_after: Duration,
+    /// The current stay was last reported as stuck at this time
+    stuck_reported_at: Option<Instant>,
+    /// Wait before reporting the same stay again (doubles per report)
+    stuck_recheck: Duration,
+    stats: Arc<StateMachineStats>,
+}
+
+impl StateMachine {
+    pub fn new() -> Self {
+        Self {
+            state: StrategyState::Idle,
+            entered_at: Instant::now(),
+            stuck_after: STUCK_AFTER,
+            stuck_reported_at: None,
+            stuck_recheck: STUCK_AFTER,
+            stats: Arc::new(StateMachineStats::default()),
+        }
+    }
+
+    /// Count into `stats` (registered with `Metrics`) from now on
+    pub fn set_stats(&mut self, stats: Arc<StateMachineStats>) {
+        self.stats = stats;
+    }
+
+    /// Execution gives up on an entry after ~11s (poll, cancel, verify): keep this well above
+    pub fn set_stuck_after(&mut self, stuck_after: Duration) {
+        self.stuck_after = stuck_after;
+    }
+
+    pub fn get(&self) -> StrategyState {
+        self.state
+    }
+
+    /// Move to `to`; `cause` names the event for the illegal-transition log
+    pub fn transition(&mut self, to: StrategyState, cause: &'static str) {
+        let from = self.state;
+        if from == to {
+            return;
+        }
+        if from.can_transition_to(to) {
+            debug!("Strategy state {:?} → {:?} ({})", from, to, cause);
+        } else {
+            warn!("⚠️  Illegal strategy transition {:?} → {:?} ({})", from, to, cause);
+            self.stats.record_illegal();
+        }
+        self.stats.record_transition();
+        self.state = to;
+        self.entered_at = Instant::now();
+        self.stuck_reported_at = None;
+    }
+
+    /// The current state and how long it's been held, when that's over its limit.
+    /// While the stay lasts it's reported again after `stuck_after`, 2×, 4×... (capped at
+    /// `STUCK_RECHECK_MAX`), so a failed or unanswered reconcile is retried
+    pub fn check_stuck(&mut self) -> Option<(StrategyState, Duration)> {
+        let held = self.entered_at.elapsed();
+        if !self.state.is_waiting() || held < self.stuck_after {
+            return None;
+        }
+        match self.stuck_reported_at {
+            None => self.stuck_recheck = self.stuck_after,
+            Some(at) if at.elapsed() < self.stuck_recheck => return None,
+            Some(_) => self.stuck_recheck = (self.stuck_recheck * 2).min(STUCK_RECHECK_MAX.max(self.stuck_after)),
+        }
+        self.stuck_reported_at = Some(Instant::now());
+        self.stats.record_stuck();
+        Some((self.state, held))
+    }
+}
+
+impl Default for StateMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq<StrategyState> for StateMachine {
+    fn eq(&self, other: &StrategyState) -> bool {
+        self.state == *other
+    }
+}
+
+impl fmt::Debug for StateMachine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.state, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_illegal_transitions_are_counted_but_applied() {
+        let stats = Arc::new(StateMachineStats::default());
+        let mut machine = StateMachine::new();
+        machine.set_stats(stats.clone());
+
+        machine.transition(StrategyState::OrderPending, "entry");
+        machine.transition(StrategyState::PositionOpen, "position confirmed");
+        machine.transition(StrategyState::PositionOpen, "position verified");
+        machine.transition(StrategyState::ClosingPosition, "exit");
+        machine.transition(StrategyState::Idle, "position closed");
+        assert_eq!((stats.transitions(), stats.illegal()), (4, 0));
+
+        // Closing something that was never open
+        machine.transition(StrategyState::ClosingPosition, "exit");
+        assert_eq!(machine, StrategyState::ClosingPosition);
+        assert_eq!((stats.transitions(), stats.illegal()), (5, 1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stuck_state_reported_per_stay_with_backoff() {
+        let mut machine = StateMachine::new();
+        tokio::time::advance(Duration::from_secs(3600)).await;
+        assert!(machine.check_stuck().is_none(), "Idle may last forever");
+
+        machine.transition(StrategyState::OrderPending, "entry");
+        machine.transition(StrategyState::PositionOpen, "position confirmed");
+        machine.transition(StrategyState::ClosingPosition, "exit");
+        tokio::time::advance(Duration::from_secs(59)).await;
+        assert!(machine.check_stuck().is_none());
+        tokio::time::advance(Duration::from_secs(2)).await;
+        assert!(matches!(machine.check_stuck(), Some((StrategyState::ClosingPosition, held)) if held >= Duration::from_secs(60)));
+        assert!(machine.check_stuck().is_none());
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert!(machine.check_stuck().is_none(), "not before the backoff ran out");
+
+        // A new stay starts a new clock
+        machine.transition(StrategyState::PositionOpen, "close failed");
+        machine.transition(StrategyState::ClosingPosition, "exit");
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(machine.check_stuck().is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stuck_state_rearms_after_failed_reconcile() {
+        let mut machine = StateMachine::new();
+        machine.transition(StrategyState::OrderPending, "entry");
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(machine.check_stuck().is_some());
+
+        // The reconcile failed: nothing moved the state, so it's asked again after 60s, then 120s
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(matches!(machine.check_stuck(), Some((StrategyState::OrderPending, held)) if held >= Duration::from_secs(120)));
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(machine.check_stuck().is_none());
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(machine.check_stuck().is_some());
+
+        // The backoff tops out at STUCK_RECHECK_MAX
+        for _ in 0..5 {
+            tokio::time::advance(STUCK_RECHECK_MAX).await;
+            assert!(machine.check_stuck().is_some());
+        }
+    }
+}
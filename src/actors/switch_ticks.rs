@@ -0,0 +1,86 @@
+//! Tick Buffering Across Symbol Switches
+//!
+//! On a hot-swap the market data actor starts streaming the new symbol before
+//! `SymbolChanged` reaches the strategy (different channels), and a switch
+//! that first closes a position takes longer still. The symbol-mismatch guard
+//! used to drop those first ticks; now the latest ticks of every symbol other
+//! than the traded one are kept (at most `SWITCH_BUFFER_TICKS`, spanning at
+//! most `SWITCH_BUFFER_SECS` of trade time) and the new symbol's are replayed
+//! into its buffers once the switch completes, so warm-up starts sooner.
+//! Replayed ticks fill buffers only, they never trigger a signal.
+
+use crate::models::{Symbol, TradeTick};
+use std::collections::{HashMap, VecDeque};
+
+/// Ticks kept per foreign symbol (the strategy's tick buffer holds as many)
+pub const SWITCH_BUFFER_TICKS: usize = 300;
+/// Trade-time span kept per foreign symbol
+pub const SWITCH_BUFFER_SECS: i64 = 30;
+
+/// Latest ticks of the symbols not (yet) traded
+#[derive(Debug, Default)]
+pub struct SwitchTicks {
+    by_symbol: HashMap<Symbol, VecDeque<TradeTick>>,
+}
+
+impl SwitchTicks {
+    pub fn push(&mut self, tick: TradeTick) {
+        let ticks = self.by_symbol.entry(tick.symbol.clone()).or_default();
+        let cutoff = tick.timestamp - SWITCH_BUFFER_SECS * 1000;
+        ticks.push_back(tick);
+        while ticks.len() > SWITCH_BUFFER_TICKS || ticks.front().is_some_and(|t| t.timestamp < cutoff) {
+            ticks.pop_front();
+        }
+    }
+
+    /// `symbol`'s ticks, oldest first; everything else buffered is dropped
+    pub fn take(&mut self, symbol: &Symbol) -> Vec<TradeTick> {
+        let ticks = self.by_symbol.remove(symbol).map(Vec::from).unwrap_or_default();
+        self.by_symbol.clear();
+        ticks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TradeSide;
+    use rust_decimal::Decimal;
+
+    fn tick(symbol: &str, timestamp: i64) -> TradeTick {
+        TradeTick {
+            symbol: Symbol::from(symbol),
+            price: Decimal::from(100),
+            size: Decimal::ONE,
+            timestamp,
+            side: TradeSide::Buy,
+        }
+    }
+
+    #[test]
+    fn test_keeps_the_latest_ticks_per_symbol() {
+        let mut buffer = SwitchTicks::default();
+        for i in 0..SWITCH_BUFFER_TICKS as i64 + 5 {
+            buffer.push(tick("SOLUSDT", i));
+        }
+        buffer.push(tick("ETHUSDT", 0));
+        buffer.push(tick("ETHUSDT", 40_000));
+
+        let sol = buffer.take(&Symbol::from("SOLUSDT"));
+        assert_eq!(sol.len(), SWITCH_BUFFER_TICKS);
+        assert_eq!(sol[0].timestamp, 5);
+        // The switch is done: the other symbols' ticks go too
+        assert!(buffer.take(&Symbol::from("ETHUSDT")).is_empty());
+    }
+
+    #[test]
+    fn test_ticks_older_than_the_span_are_dropped() {
+        let mut buffer = SwitchTicks::default();
+        buffer.push(tick("SOLUSDT", 0));
+        buffer.push(tick("SOLUSDT", 20_000));
+        buffer.push(tick("SOLUSDT", 35_000));
+
+        let timestamps: Vec<i64> = buffer.take(&Symbol::from("SOLUSDT")).iter().map(|t| t.timestamp).collect();
+        assert_eq!(timestamps, vec![20_000, 35_000]);
+    }
+}
@@ -0,0 +1,123 @@
+//! Taker Flow
+//!
+//! The same VWAP deviation can come from aggressive buyers lifting the offer
+//! or from price drifting through a thin book. The share of taker buy volume
+//! over the last `TAKER_FLOW_WINDOW_SECS` of trades tells them apart: it goes
+//! into the signal dataset, and with `MIN_TAKER_FLOW` set a long needs at least
+//! that share of the volume bought by takers (a short, sold). Windows are in
+//! exchange trade time, so replays see the same flow as the live run.
+
+use crate::models::{TradeSide, TradeTick};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+
+/// Rolling taker buy/sell volume of the traded symbol
+#[derive(Debug)]
+pub struct TakerFlow {
+    window_ms: i64,
+    trades: VecDeque<(i64, TradeSide, Decimal)>,
+    buy: Decimal,
+    sell: Decimal,
+}
+
+impl TakerFlow {
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window_ms: window_secs as i64 * 1000,
+            trades: VecDeque::new(),
+            buy: Decimal::ZERO,
+            sell: Decimal::ZERO,
+        }
+    }
+
+    pub fn push(&mut self, tick: &TradeTick) {
+        *self.side_mut(tick.side) += tick.size;
+        self.trades.push_back((tick.timestamp, tick.side, tick.size));
+        let cutoff = tick.timestamp - self.window_ms;
+        while let Some(&(at, side, size)) = self.trades.front() {
+            if at > cutoff {
+                break;
+            }
+            *self.side_mut(side) -= size;
+            self.trades.pop_front();
+        }
+    }
+
+    /// Forget the previous symbol's trades
+    pub fn reset(&mut self) {
+        self.trades.clear();
+        self.buy = Decimal::ZERO;
+        self.sell = Decimal::ZERO;
+    }
+
+    /// Taker buy volume / total volume in the window, 0..1 (None = no trades)
+    pub fn buy_ratio(&self) -> Option<f64> {
+        let total = self.buy + self.sell;
+        if total.is_zero() {
+            return None;
+        }
+        (self.buy / total).to_f64()
+    }
+
+    /// Takers on the signal's side below `min_share` of the volume (0 = never)
+    pub fn blocks_entry(&self, bullish: bool, min_share: f64) -> Option<f64> {
+        if min_share <= 0.0 {
+            return None;
+        }
+        let buy_ratio = self.buy_ratio()?;
+        let share = if bullish { buy_ratio } else { 1.0 - buy_ratio };
+        (share < min_share).then_some(share)
+    }
+
+    fn side_mut(&mut self, side: TradeSide) -> &mut Decimal {
+        match side {
+            TradeSide::Buy => &mut self.buy,
+            TradeSide::Sell => &mut self.sell,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Symbol;
+
+    fn tick(timestamp: i64, side: TradeSide, size: i64) -> TradeTick {
+        TradeTick {
+            symbol: Symbol::from("BTCUSDT"),
+            price: Decimal::from(100),
+            size: Decimal::from(size),
+            timestamp,
+            side,
+        }
+    }
+
+    #[test]
+    fn test_ratio_rolls_with_the_window() {
+        let mut flow = TakerFlow::new(10);
+        assert_eq!(flow.buy_ratio(), None);
+
+        flow.push(&tick(0, TradeSide::Buy, 3));
+        flow.push(&tick(5_000, TradeSide::Sell, 1));
+        assert_eq!(flow.buy_ratio(), Some(0.75));
+
+        // The first buy leaves the window
+        flow.push(&tick(10_000, TradeSide::Sell, 1));
+        assert_eq!(flow.buy_ratio(), Some(0.0));
+
+        flow.reset();
+        assert_eq!(flow.buy_ratio(), None);
+    }
+
+    #[test]
+    fn test_entries_need_takers_on_their_side() {
+        let mut flow = TakerFlow::new(30);
+        flow.push(&tick(0, TradeSide::Buy, 7));
+        flow.push(&tick(1, TradeSide::Sell, 3));
+
+        assert_eq!(flow.blocks_entry(true, 0.6), None);
+        assert!(flow.blocks_entry(false, 0.6).is_some_and(|share| (share - 0.3).abs() < 1e-9));
+        assert_eq!(flow.blocks_entry(false, 0.0), None);
+    }
+}
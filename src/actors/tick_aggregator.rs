@@ -0,0 +1,212 @@
+//! Trade Pre-Aggregation
+//!
+//! On very busy symbols per-trade processing in the strategy is mostly wasted
+//! work, so trades can be bundled before they reach it (`TICK_AGGREGATION`):
+//! per time bucket (`100ms`) or per traded size (`volume:2.5`). A bucket
+//! becomes one tick at its VWAP carrying the summed size, so VWAP over
+//! aggregated ticks equals VWAP over the raw trades; the side is the one with
+//! more volume. IO-free like `ws_decoder`: the market-data actor feeds it and
+//! flushes it on a timer.
+
+use crate::config::TickAggregation;
+use crate::models::{Symbol, TradeSide, TradeTick};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use tokio::time::Duration;
+
+/// A volume bucket that hasn't filled up by then is sent anyway, so quiet
+/// markets don't starve the strategy
+const VOLUME_BUCKET_MAX_AGE_MS: i64 = 1_000;
+
+/// Timer period for volume buckets
+const VOLUME_FLUSH_INTERVAL_MS: u64 = 100;
+
+#[derive(Debug)]
+struct Bucket {
+    /// Time bucket index (timestamp / millis); unused for volume buckets
+    index: i64,
+    first_ts: i64,
+    last_ts: i64,
+    notional: Decimal,
+    buy_size: Decimal,
+    sell_size: Decimal,
+}
+
+impl Bucket {
+    fn new(index: i64, timestamp: i64) -> Self {
+        Self {
+            index,
+            first_ts: timestamp,
+            last_ts: timestamp,
+            notional: Decimal::ZERO,
+            buy_size: Decimal::ZERO,
+            sell_size: Decimal::ZERO,
+        }
+    }
+
+    fn add(&mut self, tick: &TradeTick) {
+        self.last_ts = self.last_ts.max(tick.timestamp);
+        self.notional += tick.price * tick.size;
+        match tick.side {
+            TradeSide::Buy => self.buy_size += tick.size,
+            TradeSide::Sell => self.sell_size += tick.size,
+        }
+    }
+
+    fn size(&self) -> Decimal {
+        self.buy_size + self.sell_size
+    }
+
+    fn into_tick(self, symbol: Symbol) -> TradeTick {
+        let size = self.size();
+        TradeTick {
+            symbol,
+            price: self.notional / size,
+            size,
+            timestamp: self.last_ts,
+            side: if self.buy_size >= self.sell_size { TradeSide::Buy } else { TradeSide::Sell },
+        }
+    }
+}
+
+pub struct TickAggregator {
+    mode: TickAggregation,
+    /// Open bucket per streamed symbol
+    buckets: HashMap<Symbol, Bucket>,
+}
+
+impl TickAggregator {
+    pub fn new(mode: TickAggregation) -> Self {
+        Self {
+            mode,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// How often `flush_due` should run (None = aggregation off)
+    pub fn flush_interval(&self) -> Option<Duration> {
+        match self.mode {
+            TickAggregation::Off => None,
+            TickAggregation::Time { millis } => Some(Duration::from_millis(millis as u64)),
+            TickAggregation::Volume { .. } => Some(Duration::from_millis(VOLUME_FLUSH_INTERVAL_MS)),
+        }
+    }
+
+    /// Add a trade; ticks that are ready for the strategy go to `out`
+    pub fn push(&mut self, tick: TradeTick, out: &mut Vec<TradeTick>) {
+        // Zero-size trades can't be weighted (and would divide by zero)
+        if tick.size <= Decimal::ZERO {
+            return;
+        }
+        match self.mode {
+            TickAggregation::Off => out.push(tick),
+            TickAggregation::Time { millis } => {
+                let index = tick.timestamp.div_euclid(millis);
+                let next = Bucket::new(index, tick.timestamp);
+                match self.buckets.get_mut(&tick.symbol) {
+                    Some(bucket) if bucket.index >= index => bucket.add(&tick),
+                    Some(bucket) => {
+                        let done = std::mem::replace(bucket, next);
+                        bucket.add(&tick);
+                        out.push(done.into_tick(tick.symbol));
+                    }
+                    None => {
+                        let mut bucket = next;
+                        bucket.add(&tick);
+                        self.buckets.insert(tick.symbol, bucket);
+                    }
+                }
+            }
+            TickAggregation::Volume { size } => {
+                let bucket = self
+                    .buckets
+                    .entry(tick.symbol.clone())
+                    .or_insert_with(|| Bucket::new(0, tick.timestamp));
+                bucket.add(&tick);
+                if bucket.size() >= size {
+                    if let Some(done) = self.buckets.remove(&tick.symbol) {
+                        out.push(done.into_tick(tick.symbol));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Close buckets whose time is up at `now_ms` (wall clock)
+    pub fn flush_due(&mut self, now_ms: i64, out: &mut Vec<TradeTick>) {
+        let due: Vec<Symbol> = self
+            .buckets
+            .iter()
+            .filter(|(_, bucket)| match self.mode {
+                TickAggregation::Off => true,
+                TickAggregation::Time { millis } => (bucket.index + 1).saturating_mul(millis) <= now_ms,
+                TickAggregation::Volume { .. } => now_ms.saturating_sub(bucket.first_ts) >= VOLUME_BUCKET_MAX_AGE_MS,
+            })
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        for symbol in due {
+            if let Some(bucket) = self.buckets.remove(&symbol) {
+                out.push(bucket.into_tick(symbol));
+            }
+        }
+    }
+
+    /// Drop the open bucket of a symbol that is no longer streamed
+    pub fn remove(&mut self, symbol: &Symbol) {
+        self.buckets.remove(symbol);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(price: i64, size: i64, timestamp: i64, side: TradeSide) -> TradeTick {
+        TradeTick {
+            symbol: Symbol::from("BTCUSDT"),
+            price: Decimal::from(price),
+            size: Decimal::from(size),
+            timestamp,
+            side,
+        }
+    }
+
+    #[test]
+    fn test_time_buckets_keep_vwap_and_size() {
+        let mut aggregator = TickAggregator::new(TickAggregation::Time { millis: 100 });
+        let mut out = Vec::new();
+        aggregator.push(tick(100, 1, 1_000, TradeSide::Buy), &mut out);
+        aggregator.push(tick(110, 3, 1_050, TradeSide::Sell), &mut out);
+        assert!(out.is_empty());
+
+        // Next bucket closes the first one
+        aggregator.push(tick(120, 1, 1_100, TradeSide::Buy), &mut out);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].price, Decimal::new(1075, 1)); // (100 + 330) / 4
+        assert_eq!(out[0].size, Decimal::from(4));
+        assert_eq!(out[0].side, TradeSide::Sell);
+        assert_eq!(out[0].timestamp, 1_050);
+
+        aggregator.flush_due(1_150, &mut out);
+        assert_eq!(out.len(), 1);
+        aggregator.flush_due(1_200, &mut out);
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[1].price, Decimal::from(120));
+    }
+
+    #[test]
+    fn test_volume_buckets_fill_or_expire() {
+        let mut aggregator = TickAggregator::new(TickAggregation::Volume { size: Decimal::from(5) });
+        let mut out = Vec::new();
+        aggregator.push(tick(100, 2, 0, TradeSide::Buy), &mut out);
+        aggregator.push(tick(100, 3, 10, TradeSide::Buy), &mut out);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].size, Decimal::from(5));
+
+        aggregator.push(tick(101, 1, 20, TradeSide::Sell), &mut out);
+        aggregator.flush_due(500, &mut out);
+        assert_eq!(out.len(), 1);
+        aggregator.flush_due(20 + VOLUME_BUCKET_MAX_AGE_MS, &mut out);
+        assert_eq!(out.len(), 2);
+    }
+}
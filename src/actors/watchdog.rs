@@ -0,0 +1,211 @@
+use crate::actors::messages::NotifierMessage;
+use crate::channel::MeteredSender;
+use crate::config::Config;
+use crate::health::HealthRegistry;
+use crate::metrics::Metrics;
+use crate::notifications::{format_duration, Alert};
+use crate::systemd::SystemdNotifier;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::time::{interval, sleep, Duration, Instant};
+use tracing::{error, info, warn};
+
+const CHECK_INTERVAL_SECS: u64 = 5;
+
+/// Ongoing run of consecutive checks that saw drops on one channel
+struct DropStreak {
+    started: Instant,
+    dropped_at_start: u64,
+    alerted: bool,
+}
+
+/// WatchdogActor - Alerts when an actor stops making progress or a channel keeps dropping
+pub struct WatchdogActor {
+    config: Arc<Config>,
+    health: Arc<HealthRegistry>,
+    metrics: Arc<Metrics>,
+    notifier_tx: MeteredSender<NotifierMessage>,
+    /// Actors currently reported as stalled (alert once per episode)
+    stalled: HashSet<&'static str>,
+    /// Dropped counter per channel at the previous check
+    last_dropped: HashMap<&'static str, u64>,
+    drop_streaks: HashMap<&'static str, DropStreak>,
+    /// (park count, busy time) per runtime worker at the previous check
+    worker_samples: Vec<(u64, Duration)>,
+    /// ✅ SYSTEMD: Petted only while every actor is healthy
+    systemd: Option<SystemdNotifier>,
+}
+
+impl WatchdogActor {
+    pub fn new(
+        config: Arc<Config>,
+        health: Arc<HealthRegistry>,
+        metrics: Arc<Metrics>,
+        notifier_tx: MeteredSender<NotifierMessage>,
+        systemd: Option<SystemdNotifier>,
+    ) -> Self {
+        Self {
+            config,
+            health,
+            metrics,
+            notifier_tx,
+            stalled: HashSet::new(),
+            last_dropped: HashMap::new(),
+            drop_streaks: HashMap::new(),
+            worker_samples: Vec::new(),
+            systemd: systemd.filter(|s| s.watchdog_timeout().is_some()),
+        }
+    }
+
+    pub async fn run(mut self) {
+        info!("🐕 WatchdogActor started (stall grace: {}s)", self.config.actor_stall_secs);
+        if let Some(timeout) = self.systemd.as_ref().and_then(|s| s.watchdog_timeout()) {
+            info!("🐕 systemd watchdog: {}s, petted every {}s while actors are healthy", timeout.as_secs(), CHECK_INTERVAL_SECS);
+            if timeout < Duration::from_secs(2 * CHECK_INTERVAL_SECS) {
+                warn!("⚠️ WatchdogSec is below {}s, systemd may restart a healthy bot", 2 * CHECK_INTERVAL_SECS);
+            }
+        }
+
+        let mut check_interval = interval(Duration::from_secs(CHECK_INTERVAL_SECS));
+
+        loop {
+            check_interval.tick().await;
+            self.check_actors().await;
+            self.check_channels().await;
+            self.check_runtime().await;
+        }
+    }
+
+    async fn check_actors(&mut self) {
+        let stalled = self.health.stalled(Duration::from_secs(self.config.actor_stall_secs));
+        let now_stalled: HashSet<&'static str> = stalled.iter().map(|s| s.name).collect();
+
+        for status in &stalled {
+            if self.stalled.contains(status.name) {
+                continue;
+            }
+            error!(
+                "🐕 WATCHDOG: {} has not progressed for {}s",
+                status.name,
+                status.silent_for.as_secs()
+            );
+            self.notify(Alert::critical(format!(
+                "<b>Actor stalled: {}</b>\nNo progress for {}",
+                status.name,
+                format_duration(status.silent_for.as_secs())
+            )))
+            .await;
+        }
+
+        if let Some(ref systemd) = self.systemd {
+            if stalled.is_empty() {
+                systemd.watchdog();
+            } else if self.stalled.is_empty() {
+                error!("🐕 WATCHDOG: Not petting systemd while actors are stalled, it will restart the bot");
+            }
+        }
+
+        for name in self.stalled.difference(&now_stalled) {
+            info!("🐕 WATCHDOG: {} recovered", name);
+            self.notify(Alert::success(format!("Actor recovered: {}", name))).await;
+        }
+
+        if !stalled.is_empty() && self.config.watchdog_exit_on_stall {
+            error!("🐕 WATCHDOG: Exiting so the supervisor can restart the bot");
+            // Give the notifier a moment to deliver the alert
+            sleep(Duration::from_secs(3)).await;
+            std::process::exit(1);
+        }
+
+        self.stalled = now_stalled;
+    }
+
+    /// ✅ BACKPRESSURE: Alert when a channel drops messages on every check for the whole window
+    async fn check_channels(&mut self) {
+        let window = Duration::from_secs(self.config.channel_drop_alert_secs);
+
+        for channel in self.metrics.channels() {
+            let dropped = channel.dropped();
+            let previous = self.last_dropped.insert(channel.name, dropped).unwrap_or(0);
+
+            if dropped == previous {
+                if let Some(streak) = self.drop_streaks.remove(channel.name) {
+                    if streak.alerted {
+                        info!("📉 Channel '{}' stopped dropping messages", channel.name);
+                    }
+                }
+                continue;
+            }
+
+            let streak = self.drop_streaks.entry(channel.name).or_insert(DropStreak {
+                started: Instant::now(),
+                dropped_at_start: previous,
+                alerted: false,
+            });
+
+            if !streak.alerted && streak.started.elapsed() >= window {
+                streak.alerted = true;
+                let lost = dropped - streak.dropped_at_start;
+                warn!(
+                    "📈 Channel '{}' dropping messages: {} in {}s (depth {}/{})",
+                    channel.name,
+                    lost,
+                    streak.started.elapsed().as_secs(),
+                    channel.depth(),
+                    channel.capacity
+                );
+                let alert = Alert::warning(format!(
+                    "<b>Channel backpressure: {}</b>\n{} messages dropped in {}\nDepth: {}/{}",
+                    channel.name,
+                    lost,
+                    format_duration(streak.started.elapsed().as_secs()),
+                    channel.depth(),
+                    channel.capacity
+                ));
+                self.notify(alert).await;
+            }
+        }
+    }
+
+    /// ✅ DIAGNOSTICS: A worker that was busy the whole interval without parking is
+    /// saturated or blocked by synchronous code on the runtime
+    async fn check_runtime(&mut self) {
+        let rt = tokio::runtime::Handle::current().metrics();
+        let samples: Vec<(u64, Duration)> = (0..rt.num_workers())
+            .map(|w| (rt.worker_park_count(w), rt.worker_total_busy_duration(w)))
+            .collect();
+
+        if self.worker_samples.len() == samples.len() {
+            let threshold = Duration::from_millis(CHECK_INTERVAL_SECS * 1000 * 9 / 10);
+            let blocked: Vec<usize> = samples
+                .iter()
+                .zip(&self.worker_samples)
+                .enumerate()
+                .filter(|(_, ((park, busy), (prev_park, prev_busy)))| {
+                    park == prev_park && busy.saturating_sub(*prev_busy) >= threshold
+                })
+                .map(|(worker, _)| worker)
+                .collect();
+
+            if !blocked.is_empty() {
+                warn!(
+                    "🧵 Tokio workers {:?} busy for {}s without parking (blocking code on the runtime?)",
+                    blocked, CHECK_INTERVAL_SECS
+                );
+                self.notify(Alert::warning(format!(
+                    "<b>Runtime workers blocked</b>\nWorkers {:?} busy {}s without parking",
+                    blocked, CHECK_INTERVAL_SECS
+                )))
+                .await;
+            }
+        }
+
+        self.worker_samples = samples;
+    }
+
+    async fn notify(&self, alert: Alert) {
+        if let Err(e) = self.notifier_tx.send(NotifierMessage::Alert(alert)).await {
+            warn!("Watchdog failed to queue alert: {}", e);
+        }
+    }
+}
@@ -1,62 +1,177 @@
-use crate::actors::messages::{MarketDataMessage, StrategyMessage};
-use crate::config::Config;
-use crate::models::{OrderBookSnapshot, Symbol, TradeSide, TradeTick};
-use anyhow::{Context, Result};
+use crate::actors::messages::{MarketDataMessage, NotifierMessage, StrategyMessage};
+use crate::actors::position_monitor::MarkPrice;
+use crate::actors::queue_position::QueueBoard;
+use crate::actors::tick_aggregator::TickAggregator;
+use crate::actors::ws_decoder::{Decoded, FrameDecoder};
+use crate::channel::{MeteredReceiver, MeteredSender, SendError};
+use crate::config::{Config, PriceSource};
+use crate::exchange::FaultScenario;
+use crate::health::ActorHeartbeat;
+use crate::market_feed::{FeedMessage, MarketFeed};
+use crate::models::{Symbol, TradeTick};
+use crate::notifications::Alert;
+use crate::spsc::RingSender;
+use anyhow::{bail, Context, Result};
 use futures_util::{SinkExt, StreamExt};
-use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::net::TcpStream;
-use tokio::sync::mpsc;
-use tokio::time::{interval, Duration};
+use tokio::sync::watch;
+use tokio::time::{interval, Duration, Instant};
 use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 use tracing::{debug, error, info, warn};
 
 type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 
+/// Keep-alive ping interval (also bounds how long the loop sits idle)
+pub const PING_INTERVAL_SECS: u64 = 20;
+
+/// Orderbook depth subscribed to (Bybit supports 1, 50, 200, 500 for linear)
+const ORDERBOOK_DEPTH: u32 = 50;
+
+/// How often the stale-feed check runs
+const STALE_FEED_CHECK_SECS: u64 = 5;
+
+/// First reconnect delay; doubles with every consecutive failed connection
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Kept below the watchdog stall limit (ping interval + ACTOR_STALL_SECS)
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// A connection that stayed up this long resets the backoff
+pub(crate) const HEALTHY_CONNECTION: Duration = Duration::from_secs(60);
+
 /// MarketDataActor - maintains WebSocket connection with Hot-Swap capability
 pub struct MarketDataActor {
-    config: Arc<Config>,
     ws_url: String,
-    strategy_tx: mpsc::Sender<StrategyMessage>,
-    command_rx: mpsc::Receiver<MarketDataMessage>,
+    /// ✅ SPSC: Default destination for market data (lock-free ring into the strategy)
+    market_tx: Option<RingSender<StrategyMessage>>,
+    /// Per-symbol destinations overriding `market_tx` (one ring per strategy shard)
+    routes: HashMap<Symbol, RingSender<StrategyMessage>>,
+    /// ✅ FAN-OUT: Copy of every tick/orderbook for local consumers (ZeroMQ)
+    feed: Option<MarketFeed>,
+    /// ✅ QUEUE POSITION: Books and raw trades for resting entry estimates (ENTRY_MAX_REPRICES)
+    queue: Option<QueueBoard>,
+    /// ✅ POSITION MONITOR: Mid price of the traded symbol, written on every orderbook
+    prices: watch::Sender<Option<MarkPrice>>,
+    /// Per-symbol price cells (one per strategy shard)
+    symbol_prices: HashMap<Symbol, watch::Sender<Option<MarkPrice>>>,
+    command_rx: MeteredReceiver<MarketDataMessage>,
+    notifier_tx: MeteredSender<NotifierMessage>,
+    /// Traded symbol; survives reconnects and is resubscribed on every new socket
     current_symbol: Option<Symbol>,
+    /// Extra symbols from `Subscribe`, streamed alongside `current_symbol` (also resubscribed)
+    watchlist: HashSet<Symbol>,
+    /// Frame parsing + local L2 books (IO-free, fuzzed separately)
+    decoder: FrameDecoder,
+    /// Optional trade bucketing before the strategy (TICK_AGGREGATION)
+    aggregator: TickAggregator,
+    /// Scratch buffer for ticks leaving the aggregator
+    aggregated: Vec<TradeTick>,
+    /// Last orderbook/trade per traded symbol (or (re)subscribe time)
+    last_data_at: HashMap<Symbol, Instant>,
+    /// Zero = stale-feed reconnect disabled
+    stale_feed_after: Duration,
+    /// ✅ CHAOS: Simulated delivery latency (FAULT_SCENARIO ws_latency_ms)
+    fault_scenario: Option<FaultScenario>,
+    /// ✅ MICROPRICE: Book price published to the position monitors
+    price_source: PriceSource,
+    heartbeat: ActorHeartbeat,
 }
 
 impl MarketDataActor {
     pub fn new(
         config: Arc<Config>,
-        strategy_tx: mpsc::Sender<StrategyMessage>,
-        command_rx: mpsc::Receiver<MarketDataMessage>,
+        command_rx: MeteredReceiver<MarketDataMessage>,
+        notifier_tx: MeteredSender<NotifierMessage>,
+        heartbeat: ActorHeartbeat,
     ) -> Self {
         let ws_url = config.ws_url().to_string();
 
         Self {
-            config,
+            decoder: FrameDecoder::new(config.stale_data_threshold_ms, ORDERBOOK_DEPTH as usize),
+            aggregator: TickAggregator::new(config.tick_aggregation),
+            aggregated: Vec::new(),
             ws_url,
-            strategy_tx,
+            market_tx: None,
+            routes: HashMap::new(),
+            feed: None,
+            queue: None,
+            prices: watch::channel(None).0,
+            symbol_prices: HashMap::new(),
             command_rx,
+            notifier_tx,
             current_symbol: None,
+            watchlist: HashSet::new(),
+            last_data_at: HashMap::new(),
+            stale_feed_after: Duration::from_secs(config.ws_stale_feed_secs),
+            fault_scenario: config.fault_scenario,
+            price_source: config.price_source,
+            heartbeat,
         }
     }
 
+    /// Send orderbooks and trades of symbols without their own route to `tx`
+    pub fn set_market_ring(&mut self, tx: RingSender<StrategyMessage>) {
+        self.market_tx = Some(tx);
+    }
+
+    /// Send `symbol`'s orderbooks and trades to `tx` instead of the default ring
+    pub fn add_route(&mut self, symbol: Symbol, tx: RingSender<StrategyMessage>) {
+        self.routes.insert(symbol, tx);
+    }
+
+    /// Also publish every tick and orderbook (all streamed symbols) to `feed`
+    pub fn set_feed(&mut self, feed: MarketFeed) {
+        self.feed = Some(feed);
+    }
+
+    /// Feed books and trades (before aggregation) of symbols with a resting entry to `queue`
+    pub fn set_queue_board(&mut self, queue: QueueBoard) {
+        self.queue = Some(queue);
+    }
+
+    /// Mid prices of the traded symbol for the position monitor
+    pub fn prices(&self) -> watch::Receiver<Option<MarkPrice>> {
+        self.prices.subscribe()
+    }
+
+    /// Mid prices of `symbol` whether or not it's the traded one (sharded strategies)
+    pub fn prices_for(&mut self, symbol: Symbol) -> watch::Receiver<Option<MarkPrice>> {
+        self.symbol_prices
+            .entry(symbol)
+            .or_insert_with(|| watch::channel(None).0)
+            .subscribe()
+    }
+
     pub async fn run(mut self) {
         info!("📡 MarketDataActor started");
 
+        // ✅ BACKOFF: Don't hammer the exchange during a prolonged outage
+        let mut backoff = ReconnectBackoff::default();
+
         loop {
-            match self.connect_and_stream().await {
+            self.heartbeat.beat();
+            let connected_at = Instant::now();
+            let result = self.connect_and_stream().await;
+
+            if connected_at.elapsed() >= HEALTHY_CONNECTION {
+                backoff.reset();
+            }
+            let delay = backoff.next_delay(jitter());
+
+            match result {
                 Ok(_) => {
                     // ✅ FIX BUG #31: Reconnect after graceful close (e.g., error 104)
-                    warn!("⚠️  WebSocket connection closed, reconnecting in 3s...");
-                    tokio::time::sleep(Duration::from_secs(3)).await;
+                    warn!("⚠️  WebSocket connection closed, reconnecting in {:.1}s...", delay.as_secs_f64());
                     // Continue loop to reconnect instead of breaking
                 }
                 Err(e) => {
-                    error!("WebSocket error: {}. Reconnecting in 5s...", e);
-                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    error!("WebSocket error: {}. Reconnecting in {:.1}s...", e, delay.as_secs_f64());
                 }
             }
+            tokio::time::sleep(delay).await;
         }
     }
 
@@ -70,27 +185,45 @@ impl MarketDataActor {
 
         let (mut write, mut read) = ws_stream.split();
 
-        // ✅ FIX BUG #4: Re-subscribe to current symbol after reconnect
-        if let Some(ref symbol) = self.current_symbol {
+        // Books from the previous connection are stale - a fresh snapshot follows subscribe
+        self.decoder.reset();
+        self.last_data_at.clear();
+
+        // ✅ FIX BUG #4: Re-subscribe to current symbols after reconnect
+        // Done here rather than waiting for the scanner: a socket without subscriptions
+        // looks healthy but starves the strategy until the next symbol switch
+        let streamed: HashSet<Symbol> = self.watchlist.iter().chain(&self.current_symbol).cloned().collect();
+        for symbol in streamed {
             info!("🔄 Re-subscribing to {} after reconnect", symbol);
-            if let Err(e) = self.subscribe(&mut write, symbol).await {
-                error!("Failed to re-subscribe to {}: {}", symbol, e);
-            }
+            self.subscribe(&mut write, &symbol)
+                .await
+                .with_context(|| format!("Failed to re-subscribe to {}", symbol))?;
         }
 
         // Ping interval to keep connection alive
-        let mut ping_interval = interval(Duration::from_secs(20));
+        let mut ping_interval = interval(Duration::from_secs(PING_INTERVAL_SECS));
+        let mut stale_check = interval(Duration::from_secs(STALE_FEED_CHECK_SECS));
+        // ✅ TICK AGGREGATION: Closes time buckets (and expires volume buckets) without waiting for the next trade
+        let aggregating = self.aggregator.flush_interval().is_some();
+        let mut aggregation_flush = interval(self.aggregator.flush_interval().unwrap_or(Duration::from_secs(1)));
+        // Frames of a dropped connection die with it, like the books they'd update
+        let mut delayed = self.fault_scenario.and_then(|scenario| scenario.ws_delay::<String>());
 
         loop {
+            self.heartbeat.beat();
+            let delayed_due = delayed.as_ref().and_then(|line| line.next_due());
             tokio::select! {
                 // Handle incoming WebSocket messages
                 Some(msg) = read.next() => {
                     match msg {
-                        Ok(Message::Text(text)) => {
-                            if let Err(e) = self.handle_message(&text).await {
-                                warn!("Failed to handle message: {}", e);
+                        Ok(Message::Text(text)) => match delayed.as_mut() {
+                            Some(line) => line.push(text),
+                            None => {
+                                if let Err(e) = self.handle_message(text).await {
+                                    warn!("Failed to handle message: {}", e);
+                                }
                             }
-                        }
+                        },
                         Ok(Message::Ping(_)) => {
                             // Tungstenite handles pong automatically
                         }
@@ -111,21 +244,48 @@ impl MarketDataActor {
                     match cmd {
                         MarketDataMessage::SwitchSymbol(new_symbol) => {
                             info!("🔄 Hot-swapping to symbol: {}", new_symbol);
-
-                            // Unsubscribe from old symbol
-                            if let Some(ref old_symbol) = self.current_symbol {
-                                if let Err(e) = self.unsubscribe(&mut write, old_symbol).await {
-                                    error!("Failed to unsubscribe from {}: {}", old_symbol, e);
+                            let streamed = self.current_symbol.as_ref() == Some(&new_symbol)
+                                || self.watchlist.contains(&new_symbol);
+
+                            // Unsubscribe from old symbol (unless the watchlist still wants it)
+                            if let Some(old_symbol) = self.current_symbol.take() {
+                                if old_symbol != new_symbol && !self.watchlist.contains(&old_symbol) {
+                                    if let Err(e) = self.unsubscribe(&mut write, &old_symbol).await {
+                                        error!("Failed to unsubscribe from {}: {}", old_symbol, e);
+                                    }
                                 }
 
                                 // ✅ Notify strategy is handled by Scanner now (sends SymbolChanged with specs)
                             }
 
+                            // Remember the target first: if the subscribe write fails the
+                            // socket is gone, and the reconnect subscribes to it instead
+                            self.current_symbol = Some(new_symbol.clone());
+                            self.last_data_at.insert(new_symbol.clone(), Instant::now());
+
                             // Subscribe to new symbol
-                            if let Err(e) = self.subscribe(&mut write, &new_symbol).await {
-                                error!("Failed to subscribe to {}: {}", new_symbol, e);
-                            } else {
-                                self.current_symbol = Some(new_symbol);
+                            if !streamed {
+                                self.subscribe(&mut write, &new_symbol)
+                                    .await
+                                    .with_context(|| format!("Failed to subscribe to {}", new_symbol))?;
+                            }
+                        }
+                        MarketDataMessage::Subscribe(symbol) => {
+                            let streamed = self.current_symbol.as_ref() == Some(&symbol)
+                                || self.watchlist.contains(&symbol);
+                            self.watchlist.insert(symbol.clone());
+                            if !streamed {
+                                self.subscribe(&mut write, &symbol)
+                                    .await
+                                    .with_context(|| format!("Failed to subscribe to {}", symbol))?;
+                            }
+                        }
+                        MarketDataMessage::Unsubscribe(symbol) => {
+                            // The traded symbol stays until the scanner switches away from it
+                            if self.watchlist.remove(&symbol) && self.current_symbol.as_ref() != Some(&symbol) {
+                                if let Err(e) = self.unsubscribe(&mut write, &symbol).await {
+                                    error!("Failed to unsubscribe from {}: {}", symbol, e);
+                                }
                             }
                         }
                         MarketDataMessage::Shutdown => {
@@ -142,45 +302,113 @@ impl MarketDataActor {
                         break;
                     }
                 }
+
+                // ✅ STALE FEED: An "open" socket that stopped delivering data is a zombie
+                _ = stale_check.tick() => {
+                    self.check_stale_feed()?;
+                }
+
+                _ = tokio::time::sleep_until(delayed_due.unwrap_or_else(Instant::now)), if delayed_due.is_some() => {
+                    while let Some(text) = delayed.as_mut().and_then(|line| line.pop_due()) {
+                        if let Err(e) = self.handle_message(text).await {
+                            warn!("Failed to handle message: {}", e);
+                        }
+                    }
+                }
+
+                _ = aggregation_flush.tick(), if aggregating => {
+                    self.aggregator.flush_due(chrono::Utc::now().timestamp_millis(), &mut self.aggregated);
+                    self.forward_aggregated().await;
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Symbols whose silence means a dead feed: the traded one and every streamed shard
+    /// symbol (other watchlist coins may be quiet)
+    fn traded(&self) -> impl Iterator<Item = &Symbol> {
+        self.current_symbol
+            .iter()
+            .chain(self.routes.keys().filter(|symbol| self.watchlist.contains(*symbol)))
+    }
+
+    /// Err (forcing a reconnect) if a traded symbol has been silent too long
+    fn check_stale_feed(&mut self) -> Result<()> {
+        if self.stale_feed_after.is_zero() {
+            return Ok(());
+        }
+        // Silence counts from the first check after (re)subscribing
+        let now = Instant::now();
+        let traded: Vec<Symbol> = self.traded().cloned().collect();
+        let Some((symbol, silent)) = traded
+            .into_iter()
+            .map(|symbol| {
+                let since = *self.last_data_at.entry(symbol.clone()).or_insert(now);
+                (symbol, now.duration_since(since))
+            })
+            .max_by_key(|(_, silent)| *silent)
+        else {
+            return Ok(());
+        };
+        if silent < self.stale_feed_after {
+            return Ok(());
+        }
+
+        warn!("🧟 No market data for {} in {}s, forcing WebSocket reconnect", symbol, silent.as_secs());
+        let alert = Alert::warning(format!(
+            "🧟 <b>STALE FEED</b>\nNo data for {} in {}s — reconnecting WebSocket",
+            symbol,
+            silent.as_secs()
+        ));
+        if let Err(e) = self.notifier_tx.try_send(NotifierMessage::Alert(alert)) {
+            warn!("Failed to send stale feed alert: {}", e);
+        }
+        bail!("stale feed for {} ({}s without data)", symbol, silent.as_secs());
+    }
+
     async fn subscribe(
-        &self,
+        &mut self,
         write: &mut futures_util::stream::SplitSink<WsStream, Message>,
         symbol: &Symbol,
     ) -> Result<()> {
         let subscribe_msg = SubscribeMessage {
             op: "subscribe".to_string(),
             args: vec![
-                format!("orderbook.1.{}", symbol.0),
+                format!("orderbook.{}.{}", ORDERBOOK_DEPTH, symbol.0),
                 format!("publicTrade.{}", symbol.0),
+                format!("tickers.{}", symbol.0),
             ],
         };
 
+        // Accept frames before the first snapshot can arrive
+        self.decoder.subscribe(symbol);
+
         let msg_text = serde_json::to_string(&subscribe_msg)?;
         write.send(Message::Text(msg_text)).await?;
 
-        info!("📥 Subscribed to {} orderbook and trades", symbol);
+        info!("📥 Subscribed to {} orderbook, trades and ticker", symbol);
         Ok(())
     }
 
     async fn unsubscribe(
-        &self,
+        &mut self,
         write: &mut futures_util::stream::SplitSink<WsStream, Message>,
         symbol: &Symbol,
     ) -> Result<()> {
         let unsubscribe_msg = SubscribeMessage {
             op: "unsubscribe".to_string(),
             args: vec![
-                format!("orderbook.1.{}", symbol.0),
+                format!("orderbook.{}.{}", ORDERBOOK_DEPTH, symbol.0),
                 format!("publicTrade.{}", symbol.0),
+                format!("tickers.{}", symbol.0),
             ],
         };
 
+        self.decoder.unsubscribe(symbol);
+        self.aggregator.remove(symbol);
+
         let msg_text = serde_json::to_string(&unsubscribe_msg)?;
         write.send(Message::Text(msg_text)).await?;
 
@@ -188,160 +416,163 @@ impl MarketDataActor {
         Ok(())
     }
 
-    async fn handle_message(&self, text: &str) -> Result<()> {
-        // Try to parse as WebSocket response
-        let ws_msg: WsMessage = serde_json::from_str(text)?;
-
-        // Handle different topics
-        if let Some(ref topic) = ws_msg.topic {
-            if topic.starts_with("orderbook") {
-                self.handle_orderbook(ws_msg)?;
-            } else if topic.starts_with("publicTrade") {
-                self.handle_trade(ws_msg).await?;
+    async fn handle_message(&mut self, text: String) -> Result<()> {
+        let now = chrono::Utc::now().timestamp_millis();
+        match self.decoder.handle_text(text, now)? {
+            Decoded::OrderBook(snapshot) => {
+                self.mark_data(&snapshot.symbol);
+                let mark = MarkPrice {
+                    symbol: snapshot.symbol.clone(),
+                    price: snapshot.price(self.price_source),
+                };
+                if let Some(cell) = self.symbol_prices.get(&snapshot.symbol) {
+                    cell.send_replace(Some(mark.clone()));
+                }
+                if self.current_symbol.as_ref() == Some(&snapshot.symbol) {
+                    self.prices.send_replace(Some(mark));
+                }
+                if let Some(ref feed) = self.feed {
+                    feed.publish(FeedMessage::OrderBook(snapshot.clone()));
+                }
+                if let Some(ref queue) = self.queue {
+                    queue.observe_book(&snapshot);
+                }
+                // ✅ FIXED: Use try_send to avoid task explosion (100x faster)
+                if let Some(tx) = self.route(&snapshot.symbol) {
+                    if let Err(e) = tx.try_send(StrategyMessage::OrderBook(snapshot)) {
+                         // It's normal to drop packets in HFT if consumer is slow
+                         debug!("Dropped orderbook snapshot: {}", e);
+                    }
+                }
+            }
+            Decoded::Trades(mut ticks) => {
+                if let Some(tick) = ticks.first() {
+                    self.mark_data(&tick.symbol);
+                }
+                for tick in ticks.drain(..) {
+                    if let Some(ref feed) = self.feed {
+                        feed.publish(FeedMessage::Trade(tick.clone()));
+                    }
+                    if let Some(ref queue) = self.queue {
+                        queue.observe_trade(&tick);
+                    }
+                    self.aggregator.push(tick, &mut self.aggregated);
+                }
+                self.decoder.recycle(ticks);
+                self.forward_aggregated().await;
             }
+            Decoded::Ticker(ticker) => {
+                // Index moves on every push, so a dropped update is superseded within 100ms
+                if let Some(tx) = self.route(&ticker.symbol) {
+                    if let Err(e) = tx.try_send(StrategyMessage::Ticker(ticker)) {
+                        debug!("Dropped ticker update: {}", e);
+                    }
+                }
+            }
+            Decoded::Nothing => {}
         }
 
         Ok(())
     }
 
-    fn handle_orderbook(&self, msg: WsMessage) -> Result<()> {
-        if let Some(data) = msg.data {
-            if let Some(symbol_str) = data.get("s").and_then(|v| v.as_str()) {
-                let symbol = Symbol::from(symbol_str);
-
-                // Get best bid/ask
-                let bids = data.get("b").and_then(|v| v.as_array());
-                let asks = data.get("a").and_then(|v| v.as_array());
-
-                if let (Some(bids), Some(asks)) = (bids, asks) {
-                    if let (Some(best_bid), Some(best_ask)) = (bids.first(), asks.first()) {
-                        let bid_price = best_bid[0].as_str().unwrap_or("0");
-                        let bid_size = best_bid[1].as_str().unwrap_or("0");
-                        let ask_price = best_ask[0].as_str().unwrap_or("0");
-                        let ask_size = best_ask[1].as_str().unwrap_or("0");
-
-                        let timestamp = data
-                            .get("ts")
-                            .and_then(|v| v.as_i64())
-                            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
-
-                        // Check for stale data
-                        let now = chrono::Utc::now().timestamp_millis();
-                        if now - timestamp > self.config.stale_data_threshold_ms {
-                            debug!("Ignoring stale orderbook data (age: {}ms)", now - timestamp);
-                            return Ok(());
-                        }
-
-                        let snapshot = OrderBookSnapshot::new(
-                            symbol.clone(),
-                            timestamp,
-                            Decimal::from_str(bid_price).unwrap_or(Decimal::ZERO),
-                            Decimal::from_str(ask_price).unwrap_or(Decimal::ZERO),
-                            Decimal::from_str(bid_size).unwrap_or(Decimal::ZERO),
-                            Decimal::from_str(ask_size).unwrap_or(Decimal::ZERO),
-                        );
-
-                        // ✅ FIXED: Use try_send to avoid task explosion (100x faster)
-                        if let Err(e) = self.strategy_tx.try_send(StrategyMessage::OrderBook(snapshot)) {
-                             // It's normal to drop packets in HFT if consumer is slow
-                             debug!("Dropped orderbook snapshot: {}", e);
-                        }
-                    }
-                }
-            }
+    /// Send whatever the aggregator has ready (every tick when aggregation is off)
+    async fn forward_aggregated(&mut self) {
+        let mut ready = std::mem::take(&mut self.aggregated);
+        for tick in ready.drain(..) {
+            self.forward_trade(tick).await;
         }
+        self.aggregated = ready;
+    }
 
-        Ok(())
+    /// Destination for `symbol`'s market data (None = nobody trades it)
+    fn route(&mut self, symbol: &Symbol) -> Option<&mut RingSender<StrategyMessage>> {
+        match self.routes.get_mut(symbol) {
+            Some(tx) => Some(tx),
+            None => self.market_tx.as_mut(),
+        }
     }
 
-    async fn handle_trade(&self, msg: WsMessage) -> Result<()> {
-        if let Some(data_array) = msg.data {
-            if let Some(trades) = data_array.as_array() {
-                for trade_data in trades {
-                    if let Some(symbol_str) = trade_data.get("s").and_then(|v| v.as_str()) {
-                        let symbol = Symbol::from(symbol_str);
-                        let price = trade_data
-                            .get("p")
-                            .and_then(|v| v.as_str())
-                            .and_then(|s| Decimal::from_str(s).ok())
-                            .unwrap_or(Decimal::ZERO);
-
-                        let size = trade_data
-                            .get("v")
-                            .and_then(|v| v.as_str())
-                            .and_then(|s| Decimal::from_str(s).ok())
-                            .unwrap_or(Decimal::ZERO);
-
-                        let timestamp = trade_data
-                            .get("T")
-                            .and_then(|v| v.as_i64())
-                            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
-
-                        // Check for stale data
-                        let now = chrono::Utc::now().timestamp_millis();
-                        if now - timestamp > self.config.stale_data_threshold_ms {
-                            continue;
-                        }
+    /// Stale-feed tracking follows the traded symbols only (watchlist coins may be quiet)
+    fn mark_data(&mut self, symbol: &Symbol) {
+        if self.current_symbol.as_ref() == Some(symbol) || self.routes.contains_key(symbol) {
+            self.last_data_at.insert(symbol.clone(), Instant::now());
+        }
+    }
 
-                        let side = trade_data
-                            .get("S")
-                            .and_then(|v| v.as_str())
-                            .map(|s| {
-                                if s == "Buy" {
-                                    TradeSide::Buy
-                                } else {
-                                    TradeSide::Sell
-                                }
-                            })
-                            .unwrap_or(TradeSide::Buy);
-
-                        let tick = TradeTick {
-                            symbol: symbol.clone(),
-                            price,
-                            size,
-                            timestamp,
-                            side,
-                        };
-
-                        // ✅ FIX BUG #32 (HIGH): Trade ticks are CRITICAL for VWAP!
-                        // CANNOT use try_send - dropped ticks = incomplete VWAP = wrong signals!
-                        // Use send with timeout to detect if Strategy is slow (shouldn't happen)
-                        match tokio::time::timeout(
-                            tokio::time::Duration::from_millis(100),
-                            self.strategy_tx.send(StrategyMessage::Trade(tick))
-                        ).await {
-                            Ok(Ok(_)) => {
-                                // Tick sent successfully
-                            }
-                            Ok(Err(e)) => {
-                                error!("⚠️  BUG #32: Failed to send trade tick (channel closed?): {}", e);
-                            }
-                            Err(_) => {
-                                // Timeout - Strategy is too slow! This should NEVER happen
-                                error!("⚠️  BUG #32: Trade tick send TIMEOUT! Strategy actor is blocking!");
-                                error!("⚠️  CRITICAL: VWAP data will be incomplete, signals unreliable!");
-                            }
-                        }
-                    }
-                }
+    async fn forward_trade(&mut self, tick: TradeTick) {
+        let Some(tx) = self.route(&tick.symbol) else {
+            return;
+        };
+        // ✅ FIX BUG #32 (HIGH): Trade ticks are CRITICAL for VWAP!
+        // CANNOT use try_send - dropped ticks = incomplete VWAP = wrong signals!
+        // Use send with timeout to detect if Strategy is slow (shouldn't happen)
+        match tx
+            .send_timeout(StrategyMessage::Trade(tick), Duration::from_millis(100))
+            .await
+        {
+            Ok(_) => {
+                // Tick sent successfully
+            }
+            Err(SendError::Closed(_)) => {
+                error!("⚠️  BUG #32: Failed to send trade tick (channel closed)");
+            }
+            Err(SendError::Timeout(_)) | Err(SendError::Full(_)) => {
+                // Timeout - Strategy is too slow! This should NEVER happen
+                error!("⚠️  BUG #32: Trade tick send TIMEOUT! Strategy actor is blocking!");
+                error!("⚠️  CRITICAL: VWAP data will be incomplete, signals unreliable!");
             }
         }
+    }
+}
 
-        Ok(())
+/// Capped exponential reconnect delay
+#[derive(Debug, Default)]
+pub(crate) struct ReconnectBackoff {
+    /// Consecutive reconnects since the last healthy connection
+    attempt: u32,
+}
+
+impl ReconnectBackoff {
+    /// `jitter` in [0, 1) scales the delay into [50%, 100%) so clients don't reconnect in lockstep
+    pub(crate) fn next_delay(&mut self, jitter: f64) -> Duration {
+        let exp = RECONNECT_BASE_DELAY.saturating_mul(2u32.saturating_pow(self.attempt));
+        self.attempt = self.attempt.saturating_add(1);
+        exp.min(RECONNECT_MAX_DELAY).mul_f64(0.5 + jitter.clamp(0.0, 1.0) / 2.0)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.attempt = 0;
     }
 }
 
+/// Cheap [0, 1) jitter from the clock (no RNG dependency needed for this)
+pub(crate) fn jitter() -> f64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    f64::from(nanos % 1_000_000) / 1_000_000.0
+}
+
 #[derive(Debug, Serialize)]
 struct SubscribeMessage {
     op: String,
     args: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct WsMessage {
-    topic: Option<String>,
-    #[serde(rename = "type")]
-    #[allow(dead_code)]
-    msg_type: Option<String>,
-    data: Option<serde_json::Value>,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_caps_and_resets() {
+        let mut backoff = ReconnectBackoff::default();
+        let delays: Vec<u64> = (0..8).map(|_| backoff.next_delay(0.999_999).as_secs_f64().round() as u64).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 30, 30, 30]);
+
+        // No jitter = half the nominal delay, never zero
+        backoff.reset();
+        assert_eq!(backoff.next_delay(0.0), RECONNECT_BASE_DELAY / 2);
+        assert_eq!(backoff.next_delay(0.0), RECONNECT_BASE_DELAY);
+    }
 }
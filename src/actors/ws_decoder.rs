@@ -0,0 +1,632 @@
+//! WebSocket Frame Decoding
+//!
+//! The IO-free half of the market-data actor: turns raw Bybit public frames
+//! into orderbook snapshots, trade ticks and ticker updates. Kept apart from the socket so it
+//! can be fuzzed (see `fuzz/`): a garbage payload yields an error or nothing,
+//! never a panic, a poisoned book or a zero-price tick.
+//!
+//! Built with the `simd` feature, frames owned by the socket (`handle_text`)
+//! are parsed in place by simd-json instead of serde_json; both parsers feed
+//! the same book and validation code.
+
+use crate::models::{LocalOrderBook, OrderBookSnapshot, Symbol, TickerUpdate, TradeSide, TradeTick};
+use anyhow::{bail, Result};
+use rust_decimal::Decimal;
+use serde::de::{Deserializer as _, SeqAccess, Visitor};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use tracing::debug;
+
+/// Frames larger than this are dropped unparsed (Bybit frames are a few KB)
+pub const MAX_FRAME_BYTES: usize = 1 << 20;
+
+/// Levels read from a single frame; the rest is ignored
+const MAX_LEVELS_PER_FRAME: usize = 1000;
+
+/// Prices/sizes above this are garbage and would overflow Decimal math downstream
+const MAX_SANE_VALUE: i64 = 1_000_000_000_000_000;
+
+/// Public topic frame. `data` stays raw until the topic says what it is,
+/// and strings borrow from the socket buffer instead of allocating.
+#[derive(Debug, Deserialize)]
+pub struct WsFrame<'a> {
+    #[serde(borrow)]
+    pub topic: Option<Cow<'a, str>>,
+    #[serde(rename = "type")]
+    pub frame_type: Option<FrameType>,
+    pub ts: Option<i64>,
+    #[serde(borrow)]
+    pub data: Option<&'a RawValue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameType {
+    Snapshot,
+    Delta,
+    #[serde(other)]
+    Unknown,
+}
+
+/// `orderbook.{depth}.{symbol}` payload
+#[derive(Debug, Deserialize)]
+pub struct OrderBookData<'a> {
+    #[serde(rename = "s", borrow)]
+    pub symbol: Cow<'a, str>,
+    #[serde(rename = "b", borrow, default)]
+    pub bids: Vec<&'a RawValue>,
+    #[serde(rename = "a", borrow, default)]
+    pub asks: Vec<&'a RawValue>,
+    /// Update id; 1 means the stream restarted
+    #[serde(rename = "u")]
+    pub update_id: Option<i64>,
+}
+
+/// `["price", "size"]`
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Level(
+    #[serde(with = "rust_decimal::serde::str")] pub Decimal,
+    #[serde(with = "rust_decimal::serde::str")] pub Decimal,
+);
+
+/// One entry of a `publicTrade.{symbol}` payload
+#[derive(Debug, Deserialize)]
+pub struct TradeData<'a> {
+    #[serde(rename = "s", borrow)]
+    pub symbol: Cow<'a, str>,
+    #[serde(rename = "p", with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+    #[serde(rename = "v", with = "rust_decimal::serde::str")]
+    pub size: Decimal,
+    #[serde(rename = "S")]
+    pub side: TradeSide,
+    #[serde(rename = "T")]
+    pub timestamp: Option<i64>,
+}
+
+/// `tickers.{symbol}` payload (prices the delta didn't change are missing)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TickerData<'a> {
+    #[serde(borrow)]
+    pub symbol: Cow<'a, str>,
+    #[serde(with = "rust_decimal::serde::str_option", default)]
+    pub last_price: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option", default)]
+    pub mark_price: Option<Decimal>,
+    #[serde(with = "rust_decimal::serde::str_option", default)]
+    pub index_price: Option<Decimal>,
+}
+
+/// What one frame produced
+#[derive(Debug)]
+pub enum Decoded {
+    Nothing,
+    OrderBook(OrderBookSnapshot),
+    Trades(Vec<TradeTick>),
+    Ticker(TickerUpdate),
+}
+
+/// Local books + validation for the subscribed symbols
+pub struct FrameDecoder {
+    /// Interned: ticks and snapshots share these instead of allocating a symbol each
+    symbols: HashSet<Symbol>,
+    /// Local L2 books rebuilt from snapshot + delta frames
+    order_books: HashMap<Symbol, LocalOrderBook>,
+    stale_threshold_ms: i64,
+    /// Levels per side kept in the book and forwarded to the strategy
+    depth: usize,
+    /// Scratch level buffers, reused by every orderbook frame
+    bids: Vec<(Decimal, Decimal)>,
+    asks: Vec<(Decimal, Decimal)>,
+    /// Tick buffer handed back through `recycle`, reused by the next trade frame
+    spare_ticks: Vec<TradeTick>,
+}
+
+impl FrameDecoder {
+    pub fn new(stale_threshold_ms: i64, depth: usize) -> Self {
+        Self {
+            symbols: HashSet::new(),
+            order_books: HashMap::new(),
+            stale_threshold_ms,
+            depth,
+            bids: Vec::new(),
+            asks: Vec::new(),
+            spare_ticks: Vec::new(),
+        }
+    }
+
+    /// Start accepting frames for this symbol
+    pub fn subscribe(&mut self, symbol: &Symbol) {
+        self.symbols.insert(symbol.clone());
+    }
+
+    /// Stop accepting frames for this symbol and drop its book
+    pub fn unsubscribe(&mut self, symbol: &Symbol) {
+        self.symbols.remove(symbol);
+        self.order_books.remove(symbol);
+    }
+
+    /// Give back a drained `Decoded::Trades` vector so the next frame reuses its capacity
+    pub fn recycle(&mut self, mut ticks: Vec<TradeTick>) {
+        ticks.clear();
+        if ticks.capacity() > self.spare_ticks.capacity() {
+            self.spare_ticks = ticks;
+        }
+    }
+
+    /// Forget all books (after a reconnect a fresh snapshot follows subscribe)
+    pub fn reset(&mut self) {
+        self.order_books.clear();
+    }
+
+    /// Frame straight off the socket: with the `simd` feature the buffer is
+    /// parsed in place by simd-json, otherwise same as `handle_message`
+    pub fn handle_text(&mut self, text: String, now_ms: i64) -> Result<Decoded> {
+        #[cfg(feature = "simd")]
+        {
+            if text.len() > MAX_FRAME_BYTES {
+                bail!("Frame too large ({} bytes)", text.len());
+            }
+            simd::decode(self, text.into_bytes(), now_ms)
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            self.handle_message(&text, now_ms)
+        }
+    }
+
+    pub fn handle_message(&mut self, text: &str, now_ms: i64) -> Result<Decoded> {
+        if text.len() > MAX_FRAME_BYTES {
+            bail!("Frame too large ({} bytes)", text.len());
+        }
+
+        // Subscribe acks / pongs have no topic and fall through to Nothing
+        let frame: WsFrame = serde_json::from_str(text)?;
+
+        // Handle different topics
+        let decoded = match frame.topic.as_deref() {
+            Some(topic) if topic.starts_with("orderbook") => self
+                .handle_orderbook(frame, now_ms)?
+                .map_or(Decoded::Nothing, Decoded::OrderBook),
+            Some(topic) if topic.starts_with("publicTrade") => {
+                Decoded::Trades(self.handle_trade(frame, now_ms)?)
+            }
+            Some(topic) if topic.starts_with("tickers") => self
+                .handle_ticker(frame)?
+                .map_or(Decoded::Nothing, Decoded::Ticker),
+            _ => Decoded::Nothing,
+        };
+        Ok(decoded)
+    }
+
+    /// Apply a snapshot/delta frame; returns the strategy snapshot unless stale or unusable
+    pub fn handle_orderbook(&mut self, frame: WsFrame, now_ms: i64) -> Result<Option<OrderBookSnapshot>> {
+        // Bybit puts the book timestamp on the envelope, not inside data
+        let timestamp = frame.ts.unwrap_or(now_ms);
+        let Some(raw) = frame.data else {
+            return Ok(None);
+        };
+
+        let data: OrderBookData = serde_json::from_str(raw.get())?;
+        let Some(symbol) = self.subscribed(&data.symbol) else {
+            debug!("Ignoring orderbook frame for unsubscribed {}", data.symbol);
+            return Ok(None);
+        };
+
+        parse_levels(&data.bids, &mut self.bids);
+        parse_levels(&data.asks, &mut self.asks);
+        // u == 1 means Bybit restarted the stream: treat as snapshot
+        let reset = frame.frame_type == Some(FrameType::Snapshot) || data.update_id == Some(1);
+
+        Ok(self.apply_book(symbol, reset, timestamp, now_ms))
+    }
+
+    /// Apply the levels in the scratch buffers; the strategy snapshot unless stale or unusable
+    fn apply_book(&mut self, symbol: Symbol, reset: bool, timestamp: i64, now_ms: i64) -> Option<OrderBookSnapshot> {
+        let book = self.order_books.entry(symbol.clone()).or_default();
+        if reset {
+            book.apply_snapshot(&self.bids, &self.asks);
+        } else {
+            book.apply_delta(&self.bids, &self.asks);
+        }
+        book.trim(self.depth);
+
+        // Check for stale data
+        let age = now_ms.saturating_sub(timestamp);
+        if age > self.stale_threshold_ms {
+            debug!("Ignoring stale orderbook data (age: {}ms)", age);
+            return None;
+        }
+
+        book.to_snapshot(symbol, timestamp, self.depth)
+    }
+
+    /// Valid, fresh ticks from a publicTrade frame; malformed entries are skipped
+    pub fn handle_trade(&mut self, frame: WsFrame, now_ms: i64) -> Result<Vec<TradeTick>> {
+        let mut ticks = std::mem::take(&mut self.spare_ticks);
+        let Some(raw) = frame.data else {
+            return Ok(ticks);
+        };
+
+        // Entries are visited one by one, no intermediate Vec per frame
+        let mut de = serde_json::Deserializer::from_str(raw.get());
+        de.deserialize_seq(TradeVisitor {
+            decoder: self,
+            ticks: &mut ticks,
+            now_ms,
+        })?;
+        de.end()?;
+        Ok(ticks)
+    }
+
+    /// Ticker prices of a subscribed symbol; insane values are dropped like missing ones
+    pub fn handle_ticker(&self, frame: WsFrame) -> Result<Option<TickerUpdate>> {
+        let Some(raw) = frame.data else {
+            return Ok(None);
+        };
+        let data: TickerData = serde_json::from_str(raw.get())?;
+        Ok(self.accept_ticker(data))
+    }
+
+    fn accept_ticker(&self, ticker: TickerData) -> Option<TickerUpdate> {
+        let update = TickerUpdate {
+            symbol: self.subscribed(&ticker.symbol)?,
+            last_price: ticker.last_price.filter(|p| is_sane(*p)),
+            mark_price: ticker.mark_price.filter(|p| is_sane(*p)),
+            index_price: ticker.index_price.filter(|p| is_sane(*p)),
+        };
+        (update.last_price.is_some() || update.mark_price.is_some() || update.index_price.is_some()).then_some(update)
+    }
+
+    /// The tick for a subscribed, sane and fresh trade
+    fn accept_trade(&self, trade: TradeData, now_ms: i64) -> Option<TradeTick> {
+        let timestamp = trade.timestamp.unwrap_or(now_ms);
+        let valid = is_sane(trade.price)
+            && is_sane(trade.size)
+            // Check for stale data
+            && now_ms.saturating_sub(timestamp) <= self.stale_threshold_ms;
+        if !valid {
+            return None;
+        }
+        Some(TradeTick {
+            symbol: self.subscribed(&trade.symbol)?,
+            price: trade.price,
+            size: trade.size,
+            timestamp,
+            side: trade.side,
+        })
+    }
+
+    /// The interned symbol (a refcount bump, not an allocation) if subscribed
+    fn subscribed(&self, symbol: &str) -> Option<Symbol> {
+        self.symbols.get(symbol).cloned()
+    }
+}
+
+/// Turns the `publicTrade` data array into ticks as it is read
+struct TradeVisitor<'d> {
+    decoder: &'d FrameDecoder,
+    ticks: &'d mut Vec<TradeTick>,
+    now_ms: i64,
+}
+
+impl<'de> Visitor<'de> for TradeVisitor<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of trades")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut seen = 0;
+        while let Some(entry) = seq.next_element::<&RawValue>()? {
+            seen += 1;
+            if seen > MAX_LEVELS_PER_FRAME {
+                continue;
+            }
+            if let Some(tick) = serde_json::from_str::<TradeData>(entry.get())
+                .ok()
+                .and_then(|trade| self.decoder.accept_trade(trade, self.now_ms))
+            {
+                self.ticks.push(tick);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// In (0, MAX_SANE_VALUE]
+fn is_sane(value: Decimal) -> bool {
+    value > Decimal::ZERO && value <= Decimal::from(MAX_SANE_VALUE)
+}
+
+/// Typed `[price, size]` levels, skipping malformed entries.
+/// Size 0 is kept (it deletes the level); negative or absurd values are not.
+fn parse_levels(levels: &[&RawValue], out: &mut Vec<(Decimal, Decimal)>) {
+    out.clear();
+    out.extend(
+        levels
+            .iter()
+            .take(MAX_LEVELS_PER_FRAME)
+            .filter_map(|raw| serde_json::from_str::<Level>(raw.get()).ok())
+            .map(|Level(price, size)| (price, size))
+            .filter(is_sane_level),
+    );
+}
+
+fn is_sane_level(&(price, size): &(Decimal, Decimal)) -> bool {
+    is_sane(price) && (size.is_zero() || is_sane(size))
+}
+
+/// simd-json parse of a whole frame into a borrowed tape, then the same
+/// field-by-field rules as the serde structs above
+#[cfg(feature = "simd")]
+mod simd {
+    use super::*;
+    use simd_json::prelude::*;
+    use simd_json::BorrowedValue;
+    use std::str::FromStr;
+
+    pub(super) fn decode(decoder: &mut FrameDecoder, mut bytes: Vec<u8>, now_ms: i64) -> Result<Decoded> {
+        let frame = simd_json::to_borrowed_value(&mut bytes)?;
+        // Subscribe acks / pongs have no topic and fall through to Nothing
+        let Some(topic) = frame.get_str("topic") else {
+            return Ok(Decoded::Nothing);
+        };
+        let Some(data) = frame.get("data").filter(|data| !data.is_null()) else {
+            return Ok(match topic {
+                t if t.starts_with("publicTrade") => Decoded::Trades(Vec::new()),
+                _ => Decoded::Nothing,
+            });
+        };
+
+        if topic.starts_with("orderbook") {
+            // Bybit puts the book timestamp on the envelope, not inside data
+            let timestamp = frame.get_i64("ts").unwrap_or(now_ms);
+            let Some(name) = data.get_str("s") else {
+                bail!("orderbook data without a symbol");
+            };
+            let Some(symbol) = decoder.subscribed(name) else {
+                debug!("Ignoring orderbook frame for unsubscribed {}", name);
+                return Ok(Decoded::Nothing);
+            };
+            levels(data.get("b"), &mut decoder.bids)?;
+            levels(data.get("a"), &mut decoder.asks)?;
+            // u == 1 means Bybit restarted the stream: treat as snapshot
+            let reset = frame.get_str("type") == Some("snapshot") || data.get_i64("u") == Some(1);
+            let snapshot = decoder.apply_book(symbol, reset, timestamp, now_ms);
+            Ok(snapshot.map_or(Decoded::Nothing, Decoded::OrderBook))
+        } else if topic.starts_with("publicTrade") {
+            let Some(entries) = data.as_array() else {
+                bail!("publicTrade data is not an array");
+            };
+            let mut ticks = std::mem::take(&mut decoder.spare_ticks);
+            ticks.extend(
+                entries
+                    .iter()
+                    .take(MAX_LEVELS_PER_FRAME)
+                    .filter_map(trade)
+                    .filter_map(|trade| decoder.accept_trade(trade, now_ms)),
+            );
+            Ok(Decoded::Trades(ticks))
+        } else if topic.starts_with("tickers") {
+            let Some(name) = data.get_str("symbol") else {
+                bail!("ticker data without a symbol");
+            };
+            let price = |key| data.get(key).and_then(decimal);
+            let ticker = TickerData {
+                symbol: Cow::Borrowed(name),
+                last_price: price("lastPrice"),
+                mark_price: price("markPrice"),
+                index_price: price("indexPrice"),
+            };
+            Ok(decoder.accept_ticker(ticker).map_or(Decoded::Nothing, Decoded::Ticker))
+        } else {
+            Ok(Decoded::Nothing)
+        }
+    }
+
+    /// `[["price", "size"], ...]`, skipping malformed entries; missing = empty
+    fn levels(value: Option<&BorrowedValue>, out: &mut Vec<(Decimal, Decimal)>) -> Result<()> {
+        out.clear();
+        let Some(value) = value else {
+            return Ok(());
+        };
+        let Some(levels) = value.as_array() else {
+            bail!("orderbook levels are not an array");
+        };
+        out.extend(
+            levels
+                .iter()
+                .take(MAX_LEVELS_PER_FRAME)
+                .filter_map(|level| match level.as_array()?.as_slice() {
+                    [price, size] => Some((decimal(price)?, decimal(size)?)),
+                    _ => None,
+                })
+                .filter(is_sane_level),
+        );
+        Ok(())
+    }
+
+    fn trade<'a>(entry: &'a BorrowedValue) -> Option<TradeData<'a>> {
+        let side = match entry.get_str("S")? {
+            "Buy" => TradeSide::Buy,
+            "Sell" => TradeSide::Sell,
+            _ => return None,
+        };
+        let timestamp = match entry.get("T") {
+            None => None,
+            Some(t) if t.is_null() => None,
+            Some(t) => Some(t.as_i64()?),
+        };
+        Some(TradeData {
+            symbol: Cow::Borrowed(entry.get_str("s")?),
+            price: decimal(entry.get("p")?)?,
+            size: decimal(entry.get("v")?)?,
+            side,
+            timestamp,
+        })
+    }
+
+    /// Same rules as `rust_decimal::serde::str`
+    fn decimal(value: &BorrowedValue) -> Option<Decimal> {
+        let s = value.as_str()?;
+        Decimal::from_str(s).or_else(|_| Decimal::from_scientific(s)).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn decoder() -> FrameDecoder {
+        let mut decoder = FrameDecoder::new(1000, 50);
+        decoder.subscribe(&Symbol::from("BTCUSDT"));
+        decoder
+    }
+
+    #[test]
+    fn test_garbage_frames_never_panic() {
+        let mut decoder = decoder();
+        let frames = [
+            "",
+            "null",
+            "[]",
+            "{\"topic\":42}",
+            "{\"topic\":\"orderbook.50.BTCUSDT\",\"data\":[]}",
+            "{\"topic\":\"orderbook.50.BTCUSDT\",\"ts\":-9223372036854775808,\"data\":{\"s\":\"BTCUSDT\",\"b\":[[\"1\",\"1\"]],\"a\":[[\"2\",\"1\"]]}}",
+            "{\"topic\":\"orderbook.50.BTCUSDT\",\"type\":\"snapshot\",\"data\":{\"s\":\"BTCUSDT\",\"b\":[[\"79228162514264337593543950335\",\"1\"]],\"a\":[[\"79228162514264337593543950335\",\"1\"]]}}",
+            "{\"topic\":\"publicTrade.BTCUSDT\",\"data\":{\"s\":\"BTCUSDT\"}}",
+            "{\"topic\":\"publicTrade.BTCUSDT\",\"data\":[{\"s\":\"BTCUSDT\",\"p\":\"0\",\"v\":\"1\",\"S\":\"Buy\",\"T\":-9223372036854775808}]}",
+        ];
+        for frame in frames {
+            let _ = decoder.handle_message(frame, NOW);
+        }
+    }
+
+    #[test]
+    fn test_malformed_trades_are_skipped() {
+        let mut decoder = decoder();
+        let text = format!(
+            r#"{{"topic":"publicTrade.BTCUSDT","data":[
+                {{"s":"BTCUSDT","p":"100","v":"1","S":"Buy","T":{now}}},
+                {{"s":"BTCUSDT","p":"abc","v":"1","S":"Buy","T":{now}}},
+                {{"s":"BTCUSDT","p":"-5","v":"1","S":"Sell","T":{now}}},
+                {{"s":"BTCUSDT","p":"100","v":"1","S":"Sideways","T":{now}}},
+                {{"s":"ETHUSDT","p":"100","v":"1","S":"Buy","T":{now}}}
+            ]}}"#,
+            now = NOW
+        );
+        let frame: WsFrame = serde_json::from_str(&text).unwrap();
+
+        let ticks = decoder.handle_trade(frame, NOW).unwrap();
+        assert_eq!(ticks.len(), 1);
+        assert_eq!(ticks[0].price, Decimal::from(100));
+    }
+
+    #[test]
+    fn test_orderbook_for_other_symbol_is_ignored() {
+        let mut decoder = decoder();
+        let frame = format!(
+            r#"{{"topic":"orderbook.50.ETHUSDT","type":"snapshot","ts":{},"data":{{"s":"ETHUSDT","b":[["1","1"]],"a":[["2","1"]]}}}}"#,
+            NOW
+        );
+        assert!(matches!(decoder.handle_message(&frame, NOW).unwrap(), Decoded::Nothing));
+        assert!(decoder.order_books.is_empty());
+    }
+
+    #[test]
+    fn test_ticker_delta_carries_only_changed_prices() {
+        let mut decoder = decoder();
+        let frame = r#"{"topic":"tickers.BTCUSDT","type":"delta","data":{"symbol":"BTCUSDT","markPrice":"100.5","indexPrice":"100.1","fundingRate":"0.0001"}}"#;
+        let Decoded::Ticker(ticker) = decoder.handle_message(frame, NOW).unwrap() else {
+            panic!("expected ticker");
+        };
+        assert_eq!(ticker.last_price, None);
+        assert_eq!((ticker.mark_price, ticker.index_price), (Some(Decimal::new(1005, 1)), Some(Decimal::new(1001, 1))));
+
+        // Nothing priced, or not subscribed
+        let funding_only = r#"{"topic":"tickers.BTCUSDT","data":{"symbol":"BTCUSDT","fundingRate":"0.0001"}}"#;
+        assert!(matches!(decoder.handle_message(funding_only, NOW).unwrap(), Decoded::Nothing));
+        let other = r#"{"topic":"tickers.ETHUSDT","data":{"symbol":"ETHUSDT","lastPrice":"2000"}}"#;
+        assert!(matches!(decoder.handle_message(other, NOW).unwrap(), Decoded::Nothing));
+    }
+
+    #[test]
+    fn test_snapshot_then_delta() {
+        let mut decoder = decoder();
+        let snapshot = format!(
+            r#"{{"topic":"orderbook.50.BTCUSDT","type":"snapshot","ts":{},"data":{{"s":"BTCUSDT","b":[["100","1"],["99","2"]],"a":[["101","1"]],"u":5}}}}"#,
+            NOW
+        );
+        let delta = format!(
+            r#"{{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":{},"data":{{"s":"BTCUSDT","b":[["100","0"]],"a":[["100.5","3"]],"u":6}}}}"#,
+            NOW
+        );
+        decoder.handle_message(&snapshot, NOW).unwrap();
+        let Decoded::OrderBook(book) = decoder.handle_message(&delta, NOW).unwrap() else {
+            panic!("expected orderbook");
+        };
+        assert_eq!(book.best_bid, Decimal::from(99));
+        assert_eq!(book.best_ask, Decimal::new(1005, 1));
+    }
+
+    #[test]
+    fn test_unsubscribe_drops_book() {
+        let mut decoder = decoder();
+        let eth = Symbol::from("ETHUSDT");
+        decoder.subscribe(&eth);
+        let frame = format!(
+            r#"{{"topic":"orderbook.50.ETHUSDT","type":"snapshot","ts":{},"data":{{"s":"ETHUSDT","b":[["1","1"]],"a":[["2","1"]]}}}}"#,
+            NOW
+        );
+        assert!(matches!(decoder.handle_message(&frame, NOW).unwrap(), Decoded::OrderBook(_)));
+
+        decoder.unsubscribe(&eth);
+        assert!(decoder.order_books.is_empty());
+        assert!(matches!(decoder.handle_message(&frame, NOW).unwrap(), Decoded::Nothing));
+    }
+
+    #[test]
+    fn test_ticks_share_the_symbol_and_reuse_the_buffer() {
+        let mut decoder = decoder();
+        let frame = format!(
+            r#"{{"topic":"publicTrade.BTCUSDT","data":[{{"s":"BTCUSDT","p":"100","v":"1","S":"Buy","T":{now}}},{{"s":"BTCUSDT","p":"101","v":"1","S":"Sell","T":{now}}}]}}"#,
+            now = NOW
+        );
+        let Decoded::Trades(ticks) = decoder.handle_message(&frame, NOW).unwrap() else {
+            panic!("expected trades");
+        };
+        assert!(std::sync::Arc::ptr_eq(&ticks[0].symbol.0, &ticks[1].symbol.0));
+
+        let buffer = ticks.as_ptr();
+        decoder.recycle(ticks);
+        let Decoded::Trades(ticks) = decoder.handle_message(&frame, NOW).unwrap() else {
+            panic!("expected trades");
+        };
+        assert_eq!(ticks.as_ptr(), buffer);
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn test_simd_path_matches_serde() {
+        let frames = [
+            format!(r#"{{"topic":"orderbook.50.BTCUSDT","type":"snapshot","ts":{},"data":{{"s":"BTCUSDT","b":[["100","1"],["bad","1"],["99","2","x"]],"a":[["101","1e0"]],"u":5}}}}"#, NOW),
+            format!(r#"{{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":{},"data":{{"s":"BTCUSDT","b":[["100","0"]],"a":[["100.5","3"]],"u":6}}}}"#, NOW),
+            format!(r#"{{"topic":"publicTrade.BTCUSDT","data":[{{"s":"BTCUSDT","p":"100","v":"1","S":"Buy","T":{}}},{{"s":"BTCUSDT","p":"-1","v":"1","S":"Sell"}},{{"s":"BTCUSDT","p":"100.5","v":"2","S":"Sell","T":null}}]}}"#, NOW),
+            r#"{"success":true,"op":"subscribe"}"#.to_string(),
+        ];
+        let (mut serde, mut simd) = (decoder(), decoder());
+        for frame in frames {
+            let expected = format!("{:?}", serde.handle_message(&frame, NOW).unwrap());
+            let actual = format!("{:?}", simd.handle_text(frame, NOW).unwrap());
+            assert_eq!(actual, expected);
+        }
+    }
+}
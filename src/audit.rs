@@ -0,0 +1,343 @@
+//! Order Audit Log
+//!
+//! Evidence of what the bot asked the exchange and what it answered, kept apart
+//! from the normal logs (`ORDER_AUDIT`, `DATA_DIR/order_audit.jsonl`): every
+//! order create, cancel and cancel-all request exactly as it was signed and
+//! sent (each retry is its own request), and the response to it (HTTP status
+//! and raw body, rejects included) or the transport failure in its place.
+//!
+//! The file is only ever appended to. Each line carries the hash of the line
+//! before it and its own SHA-256 over both, so an edited, removed or reordered
+//! line breaks the chain at that point (`--verify-audit <file>` checks it).
+//! Every line is flushed and synced before the request goes out, on a blocking
+//! thread. A line that fails to write keeps its seq and place in the chain and
+//! is written ahead of the next one; whatever it left half-written is cut off.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{error, warn};
+
+/// `prev_hash` of the first line
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Lines kept for a retry while the file can't be written; past it the oldest is dropped
+const MAX_PENDING_LINES: usize = 10_000;
+
+/// First read back from the end of the file when `open` looks for the last line
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// One line, without its hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    pub prev_hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditRecord {
+    /// Signed request body, as sent to `endpoint`
+    Request { endpoint: String, body: String },
+    /// The exchange's answer to line `request_seq`
+    Response { request_seq: u64, status: u16, body: String },
+    /// No answer to line `request_seq` (timeout, connection error)
+    Failed { request_seq: u64, error: String },
+}
+
+/// Appends to the audit file; cloned into every client clone
+#[derive(Clone)]
+pub struct OrderAudit {
+    inner: Arc<Mutex<AuditWriter>>,
+}
+
+struct AuditWriter {
+    path: PathBuf,
+    file: Option<File>,
+    /// File length up to the last line written in full
+    committed_len: u64,
+    seq: u64,
+    last_hash: String,
+    /// Chained lines not written yet, oldest first
+    pending: VecDeque<String>,
+}
+
+impl OrderAudit {
+    /// Continue the chain at the end of `path` (a new file starts it)
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let committed_len = match std::fs::metadata(&path) {
+            Ok(meta) => meta.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+        let (seq, last_hash) = match last_line(&path)? {
+            None => (0, GENESIS_HASH.to_string()),
+            Some(line) => match split_line(&line).and_then(|(entry, hash)| Ok((parse(entry)?.seq, hash))) {
+                Ok((seq, hash)) => (seq, hash.to_string()),
+                Err(e) => {
+                    // Never rewritten: the break stays visible to verification
+                    warn!("⚠️ Order audit {} ends in a broken line ({:#}), chain restarts", path.display(), e);
+                    (0, GENESIS_HASH.to_string())
+                }
+            },
+        };
+        Ok(Self {
+            inner: Arc::new(Mutex::new(AuditWriter {
+                path,
+                file: None,
+                committed_len,
+                seq,
+                last_hash,
+                pending: VecDeque::new(),
+            })),
+        })
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.inner.lock().path.clone()
+    }
+
+    /// Record a request before it is sent; the returned seq ties the response to it
+    pub async fn request(&self, endpoint: &str, body: &str) -> u64 {
+        self.append(AuditRecord::Request {
+            endpoint: endpoint.to_string(),
+            body: body.to_string(),
+        })
+        .await
+    }
+
+    pub async fn response(&self, request_seq: u64, status: u16, body: &str) {
+        self.append(AuditRecord::Response {
+            request_seq,
+            status,
+            body: body.to_string(),
+        })
+        .await;
+    }
+
+    pub async fn failed(&self, request_seq: u64, error: &str) {
+        self.append(AuditRecord::Failed {
+            request_seq,
+            error: error.to_string(),
+        })
+        .await;
+    }
+
+    /// Write failures never hold up an order, they are only logged
+    async fn append(&self, record: AuditRecord) -> u64 {
+        let inner = self.inner.clone();
+        // The write and fsync block: off the runtime's workers
+        match tokio::task::spawn_blocking(move || inner.lock().append(record)).await {
+            Ok(seq) => seq,
+            Err(e) => {
+                error!("Order audit writer panicked: {}", e);
+                0
+            }
+        }
+    }
+}
+
+impl AuditWriter {
+    /// Chain `record` and write it after any lines still pending; returns its seq
+    fn append(&mut self, record: AuditRecord) -> u64 {
+        self.seq += 1;
+        let entry = AuditEntry {
+            seq: self.seq,
+            at: Utc::now(),
+            record,
+            prev_hash: self.last_hash.clone(),
+        };
+        // Plain strings and numbers: serializing can't fail
+        let json = serde_json::to_string(&entry).expect("audit entry serializes");
+        self.last_hash = chain_hash(&entry.prev_hash, &json);
+        // `{...}` + `,"hash":"..."}`: the hashed bytes stay verbatim in the line
+        self.pending
+            .push_back(format!("{},\"hash\":\"{}\"}}\n", &json[..json.len() - 1], self.last_hash));
+        if self.pending.len() > MAX_PENDING_LINES {
+            self.pending.pop_front();
+            error!("🛑 Order audit {} can't be written, dropped a line: the chain breaks here", self.path.display());
+        }
+
+        if let Err(e) = self.flush() {
+            warn!("⚠️ Order audit write failed ({} lines pending): {:#}", self.pending.len(), e);
+            // Reopen on the next line
+            self.file = None;
+        }
+        entry.seq
+    }
+
+    /// Write the pending lines and sync them
+    fn flush(&mut self) -> Result<()> {
+        let file = match self.file.as_mut() {
+            Some(file) => file,
+            None => {
+                let file = open(&self.path)?;
+                // Cut off whatever a failed write left half-written
+                file.set_len(self.committed_len)
+                    .with_context(|| format!("failed to truncate {}", self.path.display()))?;
+                self.file.insert(file)
+            }
+        };
+        while let Some(line) = self.pending.front() {
+            file.write_all(line.as_bytes())
+                .with_context(|| format!("failed to append to {}", self.path.display()))?;
+            self.committed_len += line.len() as u64;
+            self.pending.pop_front();
+        }
+        file.sync_data().with_context(|| format!("failed to sync {}", self.path.display()))
+    }
+}
+
+fn chain_hash(prev_hash: &str, entry_json: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(entry_json.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Line → (hashed entry JSON, hash)
+fn split_line(line: &str) -> Result<(String, &str)> {
+    const SUFFIX_LEN: usize = ",\"hash\":\"\"}".len() + 64;
+    let split = line.len().checked_sub(SUFFIX_LEN).filter(|at| line.is_char_boundary(*at));
+    let Some((entry, suffix)) = split.map(|at| line.split_at(at)) else {
+        bail!("line too short");
+    };
+    let Some(hash) = suffix.strip_prefix(",\"hash\":\"").and_then(|s| s.strip_suffix("\"}")) else {
+        bail!("no hash at the end of the line");
+    };
+    Ok((format!("{}}}", entry), hash))
+}
+
+fn parse(entry_json: String) -> Result<AuditEntry> {
+    serde_json::from_str(&entry_json).context("not an audit entry")
+}
+
+/// Last non-empty line, read back from the end of the file in growing chunks
+fn last_line(path: &Path) -> Result<Option<String>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    let len = file.metadata().with_context(|| format!("failed to read {}", path.display()))?.len();
+    let mut chunk = TAIL_CHUNK;
+    loop {
+        let start = len.saturating_sub(chunk);
+        let mut tail = Vec::new();
+        file.seek(SeekFrom::Start(start))
+            .and_then(|_| (&mut file).take(len - start).read_to_end(&mut tail))
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        // A cut character can only be at the start, before the newline that matters
+        let tail = String::from_utf8_lossy(&tail);
+        let tail = tail.trim_end();
+        match tail.rfind('\n') {
+            Some(at) => return Ok(Some(tail[at + 1..].to_string())),
+            None if start == 0 => return Ok((!tail.is_empty()).then(|| tail.to_string())),
+            None => chunk *= 2,
+        }
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))
+}
+
+/// Check every hash and link of the file; Ok = number of lines
+pub fn verify(path: &Path) -> Result<u64> {
+    let file = File::open(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut prev_seq = 0;
+    let mut count = 0;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let number = index + 1;
+        let line = line.with_context(|| format!("failed to read {}", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (json, hash) = split_line(&line).with_context(|| format!("line {}", number))?;
+        let entry = parse(json.clone()).with_context(|| format!("line {}", number))?;
+        if entry.prev_hash != prev_hash {
+            bail!("line {} (seq {}): does not follow the line before it (removed or reordered lines)", number, entry.seq);
+        }
+        if chain_hash(&entry.prev_hash, &json) != hash {
+            bail!("line {} (seq {}): hash mismatch (line edited)", number, entry.seq);
+        }
+        if entry.seq != prev_seq + 1 {
+            bail!("line {}: seq {} after {}", number, entry.seq, prev_seq);
+        }
+        prev_hash = hash.to_string();
+        prev_seq = entry.seq;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chain_survives_reopen_and_catches_edits() {
+        let path = std::env::temp_dir().join(format!("order-audit-test-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let audit = OrderAudit::open(path.clone()).unwrap();
+        let seq = audit.request("/v5/order/create", r#"{"symbol":"BTCUSDT","qty":"0.01"}"#).await;
+        audit.response(seq, 200, r#"{"retCode":110007,"retMsg":"insufficient balance"}"#).await;
+        drop(audit);
+
+        // A restart continues the same chain
+        let audit = OrderAudit::open(path.clone()).unwrap();
+        let seq = audit.request("/v5/order/cancel", r#"{"orderId":"abc"}"#).await;
+        assert_eq!(seq, 3);
+        audit.failed(seq, "timeout").await;
+        assert_eq!(verify(&path).unwrap(), 4);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, content.replace("110007", "0")).unwrap();
+        assert!(verify(&path).unwrap_err().to_string().contains("line 2 (seq 2): hash mismatch"));
+
+        let lines: Vec<&str> = content.lines().collect();
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert!(verify(&path).unwrap_err().to_string().contains("does not follow"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_keeps_its_place_in_the_chain() {
+        let path = std::env::temp_dir().join(format!("order-audit-failed-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let audit = OrderAudit::open(path.clone()).unwrap();
+        let seq = audit.request("/v5/order/create", r#"{"symbol":"BTCUSDT"}"#).await;
+        // A handle that can't write: the response waits for the next line
+        audit.inner.lock().file = Some(File::open(&path).unwrap());
+        audit.response(seq, 200, r#"{"retCode":0}"#).await;
+        assert_eq!(verify(&path).unwrap(), 1);
+
+        assert_eq!(audit.request("/v5/order/cancel", r#"{"orderId":"abc"}"#).await, 3);
+        assert_eq!(verify(&path).unwrap(), 3);
+        assert_eq!(last_line(&path).unwrap().map(|line| line.contains("\"seq\":3")), Some(true));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,450 @@
+//! Metered Channels with Overflow Policy
+//!
+//! Bounded MPSC queue used between actors. Each channel picks what happens
+//! when it is full, so the market-data path can shed stale data while
+//! control messages are never dropped:
+//! - `DropNewest`: reject the incoming message
+//! - `DropOldest`: evict the oldest queued message to make room
+//! - `Block`: wait for space (optionally up to a timeout, then drop)
+//!
+//! The receiver can also conflate (`recv_conflated`): skip a message when a
+//! newer one with the same key is already queued, so a busy consumer jumps
+//! straight to the latest orderbook instead of replaying a stale backlog.
+//!
+//! A channel built with `metered_channel_keeping` never drops the messages
+//! its `keep` rule marks (e.g. critical alerts): overflow evicts or rejects
+//! the others, and a kept message is queued past capacity if it must.
+//!
+//! Sends, drops, conflations and queue depth are reported to the metrics registry.
+
+use crate::metrics::{ChannelStats, Metrics};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::time::{Duration, Instant};
+
+/// What `send` does when the channel is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    DropNewest,
+    DropOldest,
+    /// Wait for space; `None` waits forever
+    Block { timeout: Option<Duration> },
+}
+
+/// Send failure; the undelivered message is handed back
+pub enum SendError<T> {
+    /// Channel full (DropNewest policy or `try_send`)
+    Full(T),
+    /// Still full after the Block timeout
+    Timeout(T),
+    /// Receiver dropped
+    Closed(T),
+}
+
+impl<T> SendError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            SendError::Full(v) | SendError::Timeout(v) | SendError::Closed(v) => v,
+        }
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendError::Full(_) => write!(f, "channel full"),
+            SendError::Timeout(_) => write!(f, "channel full (send timed out)"),
+            SendError::Closed(_) => write!(f, "channel closed"),
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SendError({})", self)
+    }
+}
+
+struct Shared<T> {
+    queue: Mutex<VecDeque<T>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    /// Messages overflow must never drop
+    keep: Option<fn(&T) -> bool>,
+    stats: Arc<ChannelStats>,
+    senders: AtomicUsize,
+    receiver_closed: AtomicBool,
+    /// Wakes the receiver when a message arrives or the last sender leaves
+    item_ready: Notify,
+    /// Wakes blocked senders when space frees up or the receiver leaves
+    space_ready: Notify,
+}
+
+pub struct MeteredSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct MeteredReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Bounded channel with `policy`, reporting to `metrics` under `name`
+pub fn metered_channel<T>(
+    name: &'static str,
+    capacity: usize,
+    policy: OverflowPolicy,
+    metrics: &Metrics,
+) -> (MeteredSender<T>, MeteredReceiver<T>) {
+    build_channel(name, capacity, policy, None, metrics)
+}
+
+/// Like `metered_channel`, but a message `keep` returns true for is never dropped
+/// on overflow: DropOldest evicts the oldest message that isn't kept instead, and
+/// a kept message goes in past capacity when nothing else can make room
+pub fn metered_channel_keeping<T>(
+    name: &'static str,
+    capacity: usize,
+    policy: OverflowPolicy,
+    keep: fn(&T) -> bool,
+    metrics: &Metrics,
+) -> (MeteredSender<T>, MeteredReceiver<T>) {
+    build_channel(name, capacity, policy, Some(keep), metrics)
+}
+
+fn build_channel<T>(
+    name: &'static str,
+    capacity: usize,
+    policy: OverflowPolicy,
+    keep: Option<fn(&T) -> bool>,
+    metrics: &Metrics,
+) -> (MeteredSender<T>, MeteredReceiver<T>) {
+    assert!(capacity > 0, "channel capacity must be > 0");
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        capacity,
+        policy,
+        keep,
+        stats: metrics.register_channel(name, capacity),
+        senders: AtomicUsize::new(1),
+        receiver_closed: AtomicBool::new(false),
+        item_ready: Notify::new(),
+        space_ready: Notify::new(),
+    });
+    (
+        MeteredSender { shared: shared.clone() },
+        MeteredReceiver { shared },
+    )
+}
+
+impl<T> MeteredSender<T> {
+    /// Send according to the channel's overflow policy
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.shared.policy {
+            OverflowPolicy::DropNewest => self.push_or_reject(value),
+            OverflowPolicy::DropOldest => self.push_evicting(value),
+            OverflowPolicy::Block { timeout: None } => self.push_blocking(value, None).await,
+            OverflowPolicy::Block { timeout: Some(t) } => {
+                self.push_blocking(value, Some(Instant::now() + t)).await
+            }
+        }
+    }
+
+    /// Never waits: DropOldest channels still evict, everything else rejects when full
+    pub fn try_send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.shared.policy {
+            OverflowPolicy::DropOldest => self.push_evicting(value),
+            _ => self.push_or_reject(value),
+        }
+    }
+
+    /// Wait up to `timeout` for space, whatever the policy
+    pub async fn send_timeout(&self, value: T, timeout: Duration) -> Result<(), SendError<T>> {
+        self.push_blocking(value, Some(Instant::now() + timeout)).await
+    }
+
+    /// Messages currently queued
+    pub fn depth(&self) -> usize {
+        self.shared.queue.lock().len()
+    }
+
+    pub fn stats(&self) -> &Arc<ChannelStats> {
+        &self.shared.stats
+    }
+
+    fn push_or_reject(&self, value: T) -> Result<(), SendError<T>> {
+        let shared = &self.shared;
+        if shared.receiver_closed.load(Ordering::Acquire) {
+            shared.stats.record_dropped(self.depth());
+            return Err(SendError::Closed(value));
+        }
+
+        let mut queue = shared.queue.lock();
+        if queue.len() >= shared.capacity && !self.is_kept(&value) {
+            shared.stats.record_dropped(queue.len());
+            return Err(SendError::Full(value));
+        }
+        queue.push_back(value);
+        shared.stats.record_sent(queue.len());
+        drop(queue);
+
+        shared.item_ready.notify_one();
+        Ok(())
+    }
+
+    fn push_evicting(&self, value: T) -> Result<(), SendError<T>> {
+        let shared = &self.shared;
+        if shared.receiver_closed.load(Ordering::Acquire) {
+            shared.stats.record_dropped(self.depth());
+            return Err(SendError::Closed(value));
+        }
+
+        let mut queue = shared.queue.lock();
+        if queue.len() >= shared.capacity {
+            let evictable = match shared.keep {
+                Some(keep) => queue.iter().position(|queued| !keep(queued)),
+                None => Some(0),
+            };
+            match evictable {
+                Some(index) => {
+                    queue.remove(index);
+                    shared.stats.record_dropped(queue.len());
+                }
+                // Only kept messages queued: the newcomer makes way, unless it's kept too
+                None if !self.is_kept(&value) => {
+                    shared.stats.record_dropped(queue.len());
+                    return Err(SendError::Full(value));
+                }
+                None => {}
+            }
+        }
+        queue.push_back(value);
+        shared.stats.record_sent(queue.len());
+        drop(queue);
+
+        shared.item_ready.notify_one();
+        Ok(())
+    }
+
+    fn is_kept(&self, value: &T) -> bool {
+        self.shared.keep.is_some_and(|keep| keep(value))
+    }
+
+    async fn push_blocking(&self, mut value: T, deadline: Option<Instant>) -> Result<(), SendError<T>> {
+        loop {
+            // Register interest before checking so a wakeup between check and await isn't lost
+            let notified = self.shared.space_ready.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            match self.push_or_reject(value) {
+                Err(SendError::Full(v)) => value = v,
+                other => return other,
+            }
+            // push_or_reject counted the Full as a drop; we are still trying
+            self.shared.stats.undo_dropped();
+
+            match deadline {
+                None => notified.await,
+                Some(deadline) => {
+                    if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                        self.shared.stats.record_dropped(self.depth());
+                        return Err(SendError::Timeout(value));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T> Clone for MeteredSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::AcqRel);
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for MeteredSender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // Last sender gone: let the receiver drain and observe the close
+            self.shared.item_ready.notify_one();
+        }
+    }
+}
+
+impl<T> MeteredReceiver<T> {
+    /// Next message; None once all senders are gone and the queue is drained
+    pub async fn recv(&mut self) -> Option<T> {
+        let shared = self.shared.clone();
+        loop {
+            let notified = shared.item_ready.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(value) = self.try_recv() {
+                return Some(value);
+            }
+            if shared.senders.load(Ordering::Acquire) == 0 {
+                return self.try_recv();
+            }
+
+            notified.await;
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<T> {
+        let value = self.shared.queue.lock().pop_front();
+        if value.is_some() {
+            self.shared.space_ready.notify_waiters();
+        }
+        value
+    }
+
+    /// Like `recv`, but a keyed message is skipped while a newer message with
+    /// the same key is still queued. Unkeyed messages keep their order and are
+    /// never skipped.
+    pub async fn recv_conflated<K>(&mut self, key: impl Fn(&T) -> Option<&K>) -> Option<T>
+    where
+        K: PartialEq + ?Sized,
+    {
+        loop {
+            let value = self.recv().await?;
+            let Some(value_key) = key(&value) else {
+                return Some(value);
+            };
+            let superseded = self
+                .shared
+                .queue
+                .lock()
+                .iter()
+                .any(|queued| key(queued) == Some(value_key));
+            if !superseded {
+                return Some(value);
+            }
+            self.shared.stats.record_conflated();
+        }
+    }
+}
+
+impl<T> Drop for MeteredReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_closed.store(true, Ordering::Release);
+        self.shared.space_ready.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drops_and_depth_are_counted() {
+        let metrics = Metrics::new();
+        let (tx, mut rx) = metered_channel::<u32>("test", 2, OverflowPolicy::DropNewest, &metrics);
+
+        assert!(tx.send(1).await.is_ok());
+        assert!(tx.send(2).await.is_ok());
+        assert!(matches!(tx.send(3).await, Err(SendError::Full(3))));
+        assert_eq!(tx.stats().sent(), 2);
+        assert_eq!(tx.stats().dropped(), 1);
+        assert_eq!(tx.stats().depth(), 2);
+
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(tx.depth(), 1);
+        assert!(metrics.render_prometheus().contains("channel_dropped_total{channel=\"test\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_keeps_latest() {
+        let metrics = Metrics::new();
+        let (tx, mut rx) = metered_channel::<u32>("conflated", 2, OverflowPolicy::DropOldest, &metrics);
+
+        for i in 1..=4 {
+            assert!(tx.send(i).await.is_ok());
+        }
+        assert_eq!(rx.recv().await, Some(3));
+        assert_eq!(rx.recv().await, Some(4));
+        assert_eq!(tx.stats().dropped(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_kept_messages_survive_overflow() {
+        let metrics = Metrics::new();
+        // Even numbers stand in for critical alerts
+        let keep: fn(&u32) -> bool = |v| v % 2 == 0;
+        let (tx, mut rx) = metered_channel_keeping("alerts", 2, OverflowPolicy::DropOldest, keep, &metrics);
+
+        tx.send(2).await.unwrap();
+        tx.send(1).await.unwrap();
+        // Full: 3 evicts 1, not the older kept 2
+        tx.send(3).await.unwrap();
+        tx.send(5).await.unwrap();
+        tx.try_send(4).unwrap();
+        assert_eq!(tx.depth(), 2);
+        // Only kept messages queued: an unkept one is rejected, a kept one goes in past capacity
+        assert!(matches!(tx.send(7).await, Err(SendError::Full(7))));
+        tx.send(6).await.unwrap();
+
+        drop(tx);
+        let mut received = Vec::new();
+        while let Some(v) = rx.recv().await {
+            received.push(v);
+        }
+        assert_eq!(received, vec![2, 4, 6]);
+        assert_eq!(metrics.channels()[0].dropped(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_recv_conflated_keeps_latest_per_key() {
+        let metrics = Metrics::new();
+        let (tx, mut rx) = metered_channel::<(&str, u32)>("books", 8, OverflowPolicy::DropNewest, &metrics);
+        for message in [("btc", 1), ("trade", 2), ("eth", 3), ("btc", 4), ("trade", 5), ("btc", 6)] {
+            tx.send(message).await.unwrap();
+        }
+        fn key<'a>(message: &'a (&str, u32)) -> Option<&'a str> {
+            (message.0 != "trade").then_some(message.0)
+        }
+
+        drop(tx);
+        let mut received = Vec::new();
+        while let Some(message) = rx.recv_conflated(key).await {
+            received.push(message.1);
+        }
+        assert_eq!(received, vec![2, 3, 5, 6]);
+        assert_eq!(metrics.channels()[0].conflated(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_block_waits_for_space_then_times_out() {
+        let metrics = Metrics::new();
+        let policy = OverflowPolicy::Block { timeout: Some(Duration::from_millis(100)) };
+        let (tx, mut rx) = metered_channel::<u32>("control", 1, policy, &metrics);
+
+        tx.send(1).await.unwrap();
+        let sender = tx.clone();
+        let blocked = tokio::spawn(async move { sender.send(2).await.is_ok() });
+        tokio::task::yield_now().await;
+        assert_eq!(rx.recv().await, Some(1));
+        assert!(blocked.await.unwrap());
+        assert_eq!(tx.stats().dropped(), 0);
+
+        assert!(matches!(tx.send(3).await, Err(SendError::Timeout(3))));
+        assert_eq!(tx.stats().dropped(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_recv_returns_none_after_senders_drop() {
+        let metrics = Metrics::new();
+        let (tx, mut rx) = metered_channel::<u32>("closing", 4, OverflowPolicy::DropNewest, &metrics);
+        tx.send(7).await.unwrap();
+        drop(tx);
+        assert_eq!(rx.recv().await, Some(7));
+        assert_eq!(rx.recv().await, None);
+    }
+}
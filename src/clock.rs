@@ -0,0 +1,50 @@
+//! Strategy Wall Clock
+//!
+//! Calendar time for the strategy (daily trade limit, loss-streak pauses,
+//! blacklists, trade records). Live it is the system clock. A replay anchors
+//! it to the recorded start and lets it follow tokio's clock, which the replay
+//! keeps paused and advances to each recorded line: the strategy then sees the
+//! same dates and the same elapsed times as it did live.
+
+use chrono::{DateTime, Utc};
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Clock {
+    #[default]
+    System,
+    /// `start` at tokio instant `anchor`, moving with tokio time from there
+    Replay { start: DateTime<Utc>, anchor: Instant },
+}
+
+impl Clock {
+    /// Reads `start` now (tokio time should be paused, see `replay`)
+    pub fn replay(start: DateTime<Utc>) -> Self {
+        Clock::Replay { start, anchor: Instant::now() }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        match self {
+            Clock::System => Utc::now(),
+            Clock::Replay { start, anchor } => {
+                *start + chrono::Duration::from_std(anchor.elapsed()).unwrap_or(chrono::Duration::MAX)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_replay_clock_follows_tokio_time() {
+        let start = DateTime::parse_from_rfc3339("2026-03-01T03:00:00Z").unwrap().with_timezone(&Utc);
+        let clock = Clock::replay(start);
+        assert_eq!(clock.now(), start);
+
+        tokio::time::advance(Duration::from_millis(90_500)).await;
+        assert_eq!(clock.now(), start + chrono::Duration::milliseconds(90_500));
+    }
+}
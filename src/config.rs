@@ -1,10 +1,20 @@
+use crate::exchange::FaultScenario;
+use crate::notifications::{parse_alert_routes, AlertLevel, QuietHours, QuietHoursMode};
+use crate::actors::execution::CLOSE_LADDER_RUNGS;
+use crate::actors::position_monitor::TRAILING_DISTANCE;
+use crate::actors::strategy::ENTRY_STOP_LOSS_PERCENT;
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 /// Trading strategy mode
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TradingMode {
     /// Momentum: Trade WITH the trend (pump coins, strong directional moves)
     Momentum,
@@ -12,8 +22,19 @@ pub enum TradingMode {
     MeanReversion,
 }
 
-impl TradingMode {
-    pub fn from_str(s: &str) -> Result<Self> {
+impl std::fmt::Display for TradingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TradingMode::Momentum => write!(f, "MOMENTUM"),
+            TradingMode::MeanReversion => write!(f, "MEAN_REVERSION"),
+        }
+    }
+}
+
+impl FromStr for TradingMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
         match s.to_uppercase().as_str() {
             "MOMENTUM" => Ok(TradingMode::Momentum),
             "MEAN_REVERSION" | "MEANREVERSION" | "REVERSION" => Ok(TradingMode::MeanReversion),
@@ -25,15 +46,118 @@ impl TradingMode {
     }
 }
 
+/// Trade pre-aggregation between the market-data actor and the strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TickAggregation {
+    /// Every trade reaches the strategy
+    Off,
+    /// One tick per time bucket of this many milliseconds
+    Time { millis: i64 },
+    /// One tick per this much traded size (base coin)
+    Volume { size: Decimal },
+}
+
+impl FromStr for TickAggregation {
+    type Err = anyhow::Error;
+
+    /// `off`, `100ms` or `volume:2.5`
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_lowercase();
+        let invalid = || anyhow::anyhow!("Invalid TICK_AGGREGATION: '{}'. Use 'off', e.g. '100ms' or e.g. 'volume:2.5'", s);
+        if s.is_empty() || s == "off" {
+            return Ok(TickAggregation::Off);
+        }
+        if let Some(size) = s.strip_prefix("volume:") {
+            let size = Decimal::from_str(size.trim()).map_err(|_| invalid())?;
+            return match size > Decimal::ZERO {
+                true => Ok(TickAggregation::Volume { size }),
+                false => Err(invalid()),
+            };
+        }
+        match s.strip_suffix("ms").map(|ms| ms.trim().parse::<i64>()) {
+            Some(Ok(millis)) if millis > 0 => Ok(TickAggregation::Time { millis }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// What to do when the exchange position differs from the strategy's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DriftAction {
+    /// Take the exchange's position as the truth and keep managing it
+    Adopt,
+    /// Close whatever the exchange holds
+    Flatten,
+}
+
+impl FromStr for DriftAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "adopt" => Ok(DriftAction::Adopt),
+            "flatten" | "force_flat" | "flat" => Ok(DriftAction::Flatten),
+            _ => Err(anyhow::anyhow!(
+                "Invalid POSITION_DRIFT_ACTION: '{}'. Must be 'adopt' or 'flatten'",
+                s
+            )),
+        }
+    }
+}
+
+/// Which book price marks positions and signals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PriceSource {
+    /// (bid + ask) / 2
+    Mid,
+    /// Touch prices weighted by the opposite side's size (leans toward the thinner side)
+    Microprice,
+}
+
+impl FromStr for PriceSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "mid" => Ok(PriceSource::Mid),
+            "microprice" | "micro" => Ok(PriceSource::Microprice),
+            _ => Err(anyhow::anyhow!("Invalid PRICE_SOURCE: '{}'. Must be 'mid' or 'microprice'", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub bybit_api_key: String,
     pub bybit_api_secret: String,
     pub testnet: bool,
+    /// Bybit Demo Trading (mainnet account, virtual funds)
+    pub demo_trading: bool,
+    /// Demo only: top the demo wallet up by this many USDT at startup (0 = off)
+    pub demo_topup_usdt: f64,
 
     // ✅ NEW: Custom URLs for Demo Trading / Custom Endpoints
     pub custom_rest_url: Option<String>,
     pub custom_ws_url: Option<String>,
+    pub custom_private_ws_url: Option<String>,
+    // ✅ ENDPOINT PROBE: Time the mainnet REST/WebSocket candidates at startup and use the fastest
+    pub endpoint_probe: bool,
+    /// Re-probe period, reported only (0 = startup only)
+    pub endpoint_probe_interval_secs: u64,
+    /// Candidate REST base URLs / WebSocket hosts (`wss://host`, paths are added)
+    pub rest_endpoints: Vec<String>,
+    pub ws_endpoints: Vec<String>,
+    /// Fastest candidates found at startup (None = the default domain)
+    pub probed_rest_url: Option<String>,
+    pub probed_ws_host: Option<String>,
+    /// Run the authenticated private stream (positions/orders/executions/wallet)
+    pub private_ws_enabled: bool,
+    /// Leverage applied to each traded symbol before its first entry (None = keep the exchange setting)
+    pub leverage: Option<f64>,
+    /// Directory for state files that survive restarts (specs cache, journal, ...)
+    pub data_dir: String,
+    /// Periodic wallet equity snapshot into the journal (0 = off; startup/close snapshots always run)
+    pub equity_snapshot_interval_secs: u64,
 
     // Trading parameters
     pub max_position_size_usd: f64,
@@ -44,103 +168,544 @@ pub struct Config {
     pub scan_interval_secs: u64,
     pub min_turnover_24h_usd: f64,
     pub score_threshold_multiplier: f64,
+    /// Minimum time on a scanned symbol before the next switch, unless the current one is
+    /// fenced off (black/whitelist, auto-blacklist)
+    pub min_symbol_dwell_secs: u64,
+    /// Consecutive scans a challenger must beat the threshold before the switch (1 = first scan)
+    pub switch_confirm_scans: u32,
+    /// Instrument specs (tick/lot size) older than this are refetched
+    pub specs_ttl_secs: u64,
 
     // Risk management
     pub max_spread_bps: f64,
+    // ✅ SPREAD IN TICKS: Max spread in ticks, widened to this multiple of the symbol's typical
+    // spread; replaces MAX_SPREAD_BPS (0 = off)
+    pub max_spread_ticks: u32,
+    pub spread_typical_multiple: f64,
+    /// Force-close when price is within this % of the liquidation price (0 = off)
+    pub liq_buffer_percent: f64,
+    /// How often the account's margin rate is polled (0 = off)
+    pub margin_check_interval_secs: u64,
+    /// Maintenance margin rate (MM / equity) that triggers a warning / critical alert
+    pub margin_warn_ratio: f64,
+    pub margin_critical_ratio: f64,
+    /// At the critical level, close the bot's open position
+    pub margin_auto_reduce: bool,
+    /// Engage the kill switch when equity falls this % below its persisted peak (0 = off)
+    pub max_drawdown_percent: f64,
     pub stale_data_threshold_ms: i64,
+    /// Reconnect the WebSocket if the subscribed symbol sends nothing for this long (0 = off)
+    pub ws_stale_feed_secs: u64,
+    /// Recent trades fetched via REST to pre-fill the tick buffer on symbol switch (0 = off)
+    pub warmup_trades: usize,
+    /// 1m klines fetched on a symbol switch to warm up ATR/EMA/regime (0 = off)
+    pub warmup_klines: usize,
+
+    // ✅ LIQUIDITY GATE: Visible depth must cover order size
+    /// Required depth / order size ratio on the consumed side
+    pub liquidity_depth_factor: f64,
+    /// How far from the touch (in spreads) depth is counted
+    pub liquidity_spread_multiple: f64,
 
     // Strategy parameters
     pub momentum_threshold: f64,
+    // ✅ ENTRY PRICING: Cross the spread at this multiple of the momentum threshold (0 = always
+    // Market IOC), else a PostOnly limit one tick inside a spread this wide (bps), or at the touch
+    pub entry_cross_urgency: f64,
+    pub entry_improve_spread_bps: f64,
+    // ✅ QUEUE POSITION: Withdraw and re-place a resting PostOnly entry this many times when its
+    // queue won't reach it before the timeout or the touch moved away (0 = never), chasing the
+    // touch at most this far from the first placement's price (bps)
+    pub entry_max_reprices: u32,
+    pub entry_max_reprice_bps: f64,
+    // ✅ ESCALATING CLOSE: TP/trailing exits rest as a limit at the touch this long before market (0 = market)
+    pub close_limit_window_ms: u64,
+    // ✅ FILL CONFIRMATION: Order status poll cadence, polls before a resting entry is cancelled, polls
+    // of a close order, and the wait after a cancel. A private stream update ends each wait early
+    pub order_poll_interval_ms: u64,
+    pub entry_max_polls: u32,
+    pub close_max_polls: u32,
+    pub cancel_settle_ms: u64,
+    // ✅ HARD STOP: Exchange-side stop-loss this far beyond the bot's own stop, % (0 = none). With it
+    // in place the soft (in-process) stop also tries a limit at the touch first
+    pub hard_stop_gap_percent: f64,
+    /// VWAP/momentum math in f64 instead of Decimal (orders stay Decimal)
+    pub fast_math: bool,
+    /// Bundle trades into time/volume buckets before the strategy (TICK_AGGREGATION)
+    pub tick_aggregation: TickAggregation,
     pub min_trend_strength: f64,
 
+    // ✅ DUPLICATE SIGNAL GUARD: No re-entry on the same direction + VWAP band after exit
+    pub duplicate_signal_window_secs: u64,
+    pub duplicate_signal_band_bps: f64,
+
+    // ✅ DAILY TRADE LIMIT: Max entries per UTC day (0 = unlimited)
+    pub max_trades_per_day: u32,
+
+    // ✅ ORDER THROTTLE: Entry orders sent to the exchange per rolling minute/hour (0 = no cap)
+    pub max_orders_per_minute: u32,
+    pub max_orders_per_hour: u32,
+
+    // ✅ DUPLICATE ORDER GUARD: Same symbol/side/~qty entry within N seconds is refused (0 = off)
+    pub duplicate_order_window_secs: u64,
+
+    // ✅ STUCK STATE: Reconcile with the exchange when an order/close/switch waits this long
+    pub stuck_state_timeout_secs: u64,
+
+    // ✅ POSITION DRIFT: Exchange position differs from the strategy's (adopt it or flatten)
+    pub position_drift_action: DriftAction,
+
+    // ✅ LOSS STREAK: Timed pause after N losses in a row, halt until /resume after M (0 = off)
+    pub loss_streak_pause_after: u32,
+    pub loss_streak_pause_mins: u64,
+    pub loss_streak_halt_after: u32,
+
+    // ✅ AUTO-BLACKLIST: Symbol excluded for N hours once its realized loss reaches $X (0 = off)
+    pub symbol_max_loss_usd: f64,
+    pub symbol_blacklist_hours: u64,
+
+    // ✅ SIGNAL DATASET: Features + outcome of every evaluated signal → DATA_DIR/signals.jsonl
+    pub signal_dataset: bool,
+    pub signal_outcome_horizon_secs: u64,
+
+    // ✅ DECISION LOG: Every strategy input and decision → DATA_DIR/decisions.jsonl
+    pub decision_log: bool,
+
+    // ✅ AUDIT: Hash-chained order requests + exchange responses → DATA_DIR/order_audit.jsonl
+    pub order_audit: bool,
+
+    // ✅ SIGNAL MODEL: ONNX model scoring confirmed signals (needs the `onnx` feature)
+    pub signal_model_path: Option<PathBuf>,
+    pub signal_model_threshold: f64,
+
     // ✅ Fixed dollar risk per trade
     pub risk_amount_usd: f64,
 
     // ✅ PUMP PROTECTION: Blacklist specific symbols
     pub blacklist_symbols: Vec<String>,
+    // ✅ WHITELIST: Only these symbols are scanned and traded (empty = all not blacklisted)
+    pub whitelist_symbols: Vec<String>,
 
     // ✅ MEAN REVERSION: Fixed trading symbol (empty = auto-scan)
     pub trading_symbol: Option<String>,
 
+    // ✅ SHARDS: One strategy engine per symbol behind a router (empty = single engine)
+    pub trading_symbols: Vec<String>,
+    /// Positions open at the same time across all shards
+    pub max_open_positions: usize,
+
+    // ✅ PAIR TRADING: Spread trader on two correlated symbols, long one leg and short the other (None = off)
+    pub pair_symbols: Option<(String, String)>,
+    /// Notional per leg, USD
+    pub pair_notional_usd: f64,
+    /// Spread samples (one a second) behind the z-score
+    pub pair_lookback: usize,
+    pub pair_entry_z: f64,
+    pub pair_exit_z: f64,
+    /// Spread stretched this far against the pair: close it (0 = off)
+    pub pair_stop_z: f64,
+
+    // ✅ REFERENCE FEED: Binance futures book of the traded symbols as a lead-lag signal
+    pub binance_reference: bool,
+    /// Lead window: Binance return minus Bybit return over it, ms
+    pub reference_lead_ms: u64,
+    /// Binance leading against the signal by this much blocks the entry, bps (0 = off)
+    pub reference_max_adverse_bps: f64,
+
+    // ✅ MICROPRICE: Book price for PnL, exits and signal prices (mid lags on imbalanced books)
+    pub price_source: PriceSource,
+
+    // ✅ INDEX DIVERGENCE: Last price this far from the index in the signal's direction blocks the entry, bps (0 = off)
+    pub max_index_divergence_bps: f64,
+
+    // ✅ TAKER FLOW: Window of the taker buy/sell ratio, and the share of takers on the signal's side an entry needs (0 = off)
+    pub taker_flow_window_secs: u64,
+    pub min_taker_flow: f64,
+
+    // ✅ REWARD:RISK: Net reward / net risk an entry needs after fees, spread and slippage (0 = off),
+    // and the slippage expected on each fill, bps
+    pub min_reward_risk: f64,
+    pub expected_slippage_bps: f64,
+
     // ✅ SCANNER MODE: "STABLE" (default) or "VOLATILE" (Find Mid-Caps)
     pub scanner_mode: String,
 
     // ✅ NEW: Trading strategy mode (cannot change during runtime!)
     pub trading_mode: TradingMode,
+
+    // ✅ ALERTS: Telegram notifications (both required, otherwise alerts are only logged)
+    pub telegram_bot_token: Option<String>,
+    pub telegram_chat_id: Option<String>,
+    /// Per-level chat routing; unrouted levels go to telegram_chat_id
+    pub telegram_routes: HashMap<AlertLevel, Vec<String>>,
+    /// Telegram user IDs allowed to issue commands (empty = commands disabled)
+    pub telegram_allowed_users: Vec<i64>,
+    /// Identical alerts within this window collapse into one (0 = off)
+    pub alert_dedup_window_secs: u64,
+    /// Info/Success alerts are batched into a digest this often (0 = send immediately)
+    pub alert_digest_interval_secs: u64,
+    /// UTC window when Info/Success alerts are held back (None = off)
+    pub quiet_hours: Option<QuietHours>,
+    pub quiet_hours_mode: QuietHoursMode,
+    /// How often to send the "still alive" status (0 = off)
+    pub heartbeat_interval_secs: u64,
+    /// Heartbeat flags the feed as stalled if the last tick is older than this
+    pub heartbeat_stale_tick_secs: u64,
+    /// Actor counts as stalled after its normal loop interval plus this grace
+    pub actor_stall_secs: u64,
+    /// Exit the process on a stall so systemd/docker restarts it
+    pub watchdog_exit_on_stall: bool,
+    /// On a crashed actor, market-close the positions this run opened before exiting (opt-in)
+    pub crash_flatten_positions: bool,
+    /// Alert if a channel keeps dropping messages for this long
+    pub channel_drop_alert_secs: u64,
+    /// Prometheus exporter bind address, e.g. "0.0.0.0:9100" (None = off)
+    pub metrics_addr: Option<String>,
+    /// External signal webhook bind address, e.g. "0.0.0.0:8088" (None = off)
+    pub webhook_addr: Option<String>,
+    /// Shared secret the webhook expects with every signal
+    pub webhook_token: String,
+    /// Redis for event publishing and control commands (None = off)
+    pub redis_url: Option<String>,
+    /// Channel prefix: events on `{prefix}:{type}`, commands on `{prefix}:control`
+    pub redis_channel_prefix: String,
+    /// Identifies this bot in published events (several bots on one bus)
+    pub bot_id: String,
+    /// NATS server for domain events (None = off)
+    pub nats_url: Option<String>,
+    /// Kafka bootstrap servers for domain events (None = off)
+    pub kafka_brokers: Option<String>,
+    /// NATS subject / Kafka topic prefix: events go to `{prefix}.{type}`
+    pub event_topic_prefix: String,
+    /// ZeroMQ PUB endpoint for the tick/orderbook fan-out, e.g. "tcp://127.0.0.1:5556" (None = off)
+    pub zmq_pub_addr: Option<String>,
+    /// Remote control HTTP API bind address, e.g. "127.0.0.1:8089" (None = off)
+    pub control_api_addr: Option<String>,
+    /// Bearer token the control API expects with every request
+    pub control_api_token: String,
+    /// Live event WebSocket bind address, e.g. "0.0.0.0:8090" (None = off)
+    pub events_ws_addr: Option<String>,
+    /// Clients must pass `?token=` when set (None = open, keep it on a private network)
+    pub events_ws_token: Option<String>,
+
+    // ✅ CHAOS: Injected exchange faults and latency for soak tests (testnet/demo only, None = off)
+    pub fault_scenario: Option<FaultScenario>,
+
+    // ✅ SHADOW: Overrides for a second strategy run on simulated fills (None = off, see `shadow`)
+    pub shadow_config: Option<PathBuf>,
+    // ✅ ARENA: Named override files that all paper-trade side by side (empty = off, see `paper_profiles`)
+    pub paper_profiles: Vec<(String, PathBuf)>,
+}
+
+/// Bybit linear taker fee, entry plus exit (base tier), %
+pub const ROUND_TRIP_FEE_PERCENT: f64 = 0.11;
+/// Bybit's minimum order value on USDT perpetuals
+const MIN_NOTIONAL_USD: f64 = 5.0;
+/// Execution needs up to ~11s to place, poll, cancel and verify an entry (default cadence)
+const MIN_STUCK_STATE_TIMEOUT_SECS: u64 = 15;
+/// Stuck-state timeout headroom over the time an entry takes to resolve
+const STUCK_STATE_MARGIN_SECS: u64 = 4;
+/// Fewer spread samples make a z-score out of noise
+const MIN_PAIR_LOOKBACK: usize = 30;
+
+/// Default mainnet REST base and WebSocket host
+const MAINNET_REST_URL: &str = "https://api.bybit.com";
+const MAINNET_WS_HOST: &str = "wss://stream.bybit.com";
+
+/// Comma-separated URLs without trailing '/', `default` when unset or empty
+fn endpoint_list(raw: Option<String>, default: &[&str]) -> Vec<String> {
+    let urls: Vec<String> = raw
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().trim_end_matches('/').to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if urls.is_empty() {
+        default.iter().map(|s| s.to_string()).collect()
+    } else {
+        urls
+    }
+}
+
+/// Reads variables for `from_vars`, collecting every problem instead of stopping at the first
+struct Vars<'a> {
+    var: &'a dyn Fn(&str) -> Result<String, env::VarError>,
+    problems: Vec<String>,
+}
+
+impl Vars<'_> {
+    /// `key` parsed, `default` when unset or empty (or invalid, recorded as a problem)
+    fn get<T: FromStr>(&mut self, key: &str, default: T) -> T
+    where
+        T::Err: Display,
+    {
+        self.opt(key).unwrap_or(default)
+    }
+
+    /// `key` parsed, None when unset or empty (or invalid, recorded as a problem)
+    fn opt<T: FromStr>(&mut self, key: &str) -> Option<T>
+    where
+        T::Err: Display,
+    {
+        let raw = (self.var)(key).ok()?;
+        let value = raw.trim();
+        if value.is_empty() {
+            return None;
+        }
+        match value.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                let expected = match std::any::type_name::<T>() {
+                    "f64" => "expected a number: ",
+                    "bool" => "expected true or false: ",
+                    "u8" | "u16" | "u32" | "u64" | "usize" => "expected a whole number >= 0: ",
+                    _ => "",
+                };
+                self.problem(format!("{}={:?}: {}{}", key, value, expected, e));
+                None
+            }
+        }
+    }
+
+    fn problem(&mut self, problem: impl Into<String>) {
+        self.problems.push(problem.into());
+    }
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
         dotenvy::dotenv().ok();
+        Self::from_vars(|key| env::var(key))
+    }
+
+    /// ✅ SHADOW: The shadow strategy's config: this one with the variables from
+    /// SHADOW_CONFIG (a .env-style file) on top, its state files under DATA_DIR/shadow
+    pub fn shadow(&self) -> Result<Option<Config>> {
+        let Some(ref path) = self.shadow_config else {
+            return Ok(None);
+        };
+        self.overlay(path, self.data_path("shadow")).map(Some)
+    }
+
+    /// ✅ ARENA: Config of every PAPER_PROFILES entry, state files under DATA_DIR/arena/<name>
+    pub fn paper_profiles(&self) -> Result<Vec<(String, Config)>> {
+        self.paper_profiles
+            .iter()
+            .map(|(name, path)| Ok((name.clone(), self.overlay(path, self.data_path("arena").join(name))?)))
+            .collect()
+    }
+
+    /// This config with the variables of the .env-style file at `path` on top
+    fn overlay(&self, path: &Path, data_dir: PathBuf) -> Result<Config> {
+        let overrides = dotenvy::from_path_iter(path)
+            .and_then(|vars| vars.collect::<Result<HashMap<String, String>, _>>())
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut config = Self::from_vars(|key| overrides.get(key).cloned().ok_or(env::VarError::NotPresent).or_else(|_| env::var(key)))
+            .with_context(|| format!("Invalid config overrides {}", path.display()))?;
+        config.shadow_config = None;
+        config.paper_profiles = Vec::new();
+        config.data_dir = data_dir.to_string_lossy().into_owned();
+        Ok(config)
+    }
+
+    /// Build from `var` (the environment, or the environment under overrides).
+    /// Every invalid value and broken rule is reported, all in one error
+    fn from_vars(var: impl Fn(&str) -> Result<String, env::VarError>) -> Result<Self> {
+        let mut vars = Vars { var: &var, problems: Vec::new() };
+
+        let testnet = vars.get("BYBIT_TESTNET", false);
+        let demo_trading = vars.get("DEMO_TRADING", false);
+        if testnet && demo_trading {
+            vars.problem("BYBIT_TESTNET and DEMO_TRADING are mutually exclusive");
+        }
+
+        let webhook_addr = var("WEBHOOK_ADDR").ok().filter(|s| !s.trim().is_empty());
+        let webhook_token = var("WEBHOOK_TOKEN").unwrap_or_default().trim().to_string();
+        if webhook_addr.is_some() && webhook_token.is_empty() {
+            vars.problem("WEBHOOK_ADDR is set but WEBHOOK_TOKEN is empty");
+        }
+        let trading_symbols: Vec<String> = var("TRADING_SYMBOLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_uppercase())
+            .filter(|s| !s.is_empty())
+            .fold(Vec::new(), |mut symbols, s| {
+                if !symbols.contains(&s) {
+                    symbols.push(s);
+                }
+                symbols
+            });
+        if !trading_symbols.is_empty() && var("TRADING_SYMBOL").is_ok_and(|s| !s.trim().is_empty()) {
+            vars.problem("TRADING_SYMBOL and TRADING_SYMBOLS are mutually exclusive");
+        }
+
+        // "ETHUSDT/BTCUSDT"
+        let pair_symbols = var("PAIR_SYMBOLS").ok().filter(|s| !s.trim().is_empty()).and_then(|s| {
+            let pair = s
+                .split_once('/')
+                .map(|(a, b)| (a.trim().to_uppercase(), b.trim().to_uppercase()))
+                .filter(|(a, b)| !a.is_empty() && !b.is_empty() && a != b);
+            if pair.is_none() {
+                vars.problem(format!("PAIR_SYMBOLS={}: expected two different symbols as A/B, e.g. ETHUSDT/BTCUSDT", s.trim()));
+            }
+            pair
+        });
+        let traded = |symbol: &String| {
+            trading_symbols.contains(symbol) || var("TRADING_SYMBOL").is_ok_and(|s| s.trim().eq_ignore_ascii_case(symbol))
+        };
+        if let Some((a, b)) = pair_symbols.as_ref().filter(|(a, b)| traded(a) || traded(b)) {
+            vars.problem(format!("PAIR_SYMBOLS={}/{}: a pair leg can't also be traded by the strategy (TRADING_SYMBOL/S)", a, b));
+        }
+
+        let fault_scenario: Option<FaultScenario> = vars.opt("FAULT_SCENARIO");
+        if fault_scenario.is_some() && !testnet && !demo_trading {
+            vars.problem("FAULT_SCENARIO only runs against testnet or demo trading");
+        }
+
+        // "tight=profiles/tight.env,loose=profiles/loose.env"
+        let mut paper_profiles: Vec<(String, PathBuf)> = Vec::new();
+        for entry in var("PAPER_PROFILES").unwrap_or_default().split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let Some((name, path)) = entry.split_once('=').map(|(n, p)| (n.trim().to_lowercase(), p.trim())) else {
+                vars.problem(format!("PAPER_PROFILES entry '{}' is not name=path", entry));
+                continue;
+            };
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') || path.is_empty() {
+                vars.problem(format!("PAPER_PROFILES entry '{}': name must be [a-z0-9_], path non-empty", entry));
+                continue;
+            }
+            if name == "live" || paper_profiles.iter().any(|(n, _)| *n == name) {
+                vars.problem(format!("PAPER_PROFILES has '{}' twice (or named 'live')", name));
+                continue;
+            }
+            paper_profiles.push((name, PathBuf::from(path)));
+        }
+
+        let control_api_addr = var("CONTROL_API_ADDR").ok().filter(|s| !s.trim().is_empty());
+        let control_api_token = var("CONTROL_API_TOKEN").unwrap_or_default().trim().to_string();
+        if control_api_addr.is_some() && control_api_token.is_empty() {
+            vars.problem("CONTROL_API_ADDR is set but CONTROL_API_TOKEN is empty");
+        }
+        for key in ["BYBIT_API_KEY", "BYBIT_API_SECRET"] {
+            if var(key).is_err() {
+                vars.problem(format!("{} not found in environment", key));
+            }
+        }
+        let leverage: Option<f64> = vars.opt("LEVERAGE");
+        if leverage.is_some_and(|l| !(1.0..=100.0).contains(&l)) {
+            vars.problem(format!("LEVERAGE={}: expected 1 to 100 (or unset to keep the exchange setting)", leverage.unwrap_or_default()));
+        }
+        let max_open_positions = vars.get("MAX_OPEN_POSITIONS", 1);
+        if max_open_positions == 0 {
+            vars.problem("MAX_OPEN_POSITIONS=0: expected at least 1");
+        }
 
-        Ok(Self {
-            bybit_api_key: env::var("BYBIT_API_KEY")
-                .context("BYBIT_API_KEY not found in environment")?,
-            bybit_api_secret: env::var("BYBIT_API_SECRET")
-                .context("BYBIT_API_SECRET not found in environment")?,
-            testnet: env::var("BYBIT_TESTNET")
-                .unwrap_or_else(|_| "false".to_string())
-                .parse()
-                .unwrap_or(false),
+        let config = Self {
+            bybit_api_key: var("BYBIT_API_KEY").unwrap_or_default(),
+            bybit_api_secret: var("BYBIT_API_SECRET").unwrap_or_default(),
+            testnet,
+            demo_trading,
+            demo_topup_usdt: vars.get("DEMO_TOPUP_USDT", 0.0),
 
             // ✅ NEW: Load custom URLs if provided
-            custom_rest_url: env::var("BYBIT_REST_URL").ok(),
-            custom_ws_url: env::var("BYBIT_WS_URL").ok(),
-
-            max_position_size_usd: env::var("MAX_POSITION_SIZE_USD")
-                .unwrap_or_else(|_| "1000.0".to_string())
-                .parse()
-                .unwrap_or(1000.0),
-            stop_loss_percent: env::var("STOP_LOSS_PERCENT")
-                .unwrap_or_else(|_| "0.5".to_string())
-                .parse()
-                .unwrap_or(0.5),
-            take_profit_percent: env::var("TAKE_PROFIT_PERCENT")
-                .unwrap_or_else(|_| "1.0".to_string())
-                .parse()
-                .unwrap_or(1.0),
-
-            scan_interval_secs: env::var("SCAN_INTERVAL_SECS")
-                .unwrap_or_else(|_| "60".to_string())
-                .parse()
-                .unwrap_or(60),
-            min_turnover_24h_usd: env::var("MIN_TURNOVER_24H_USD")
-                .unwrap_or_else(|_| "10000000.0".to_string())
-                .parse()
-                .unwrap_or(10_000_000.0),
-            score_threshold_multiplier: env::var("SCORE_THRESHOLD_MULTIPLIER")
-                .unwrap_or_else(|_| "1.2".to_string())
-                .parse()
-                .unwrap_or(1.2),
-
-            max_spread_bps: env::var("MAX_SPREAD_BPS")
-                .unwrap_or_else(|_| "20.0".to_string())
-                .parse()
-                .unwrap_or(20.0),
-            stale_data_threshold_ms: env::var("STALE_DATA_THRESHOLD_MS")
-                .unwrap_or_else(|_| "500".to_string())
-                .parse()
-                .unwrap_or(500),
-
-            momentum_threshold: env::var("MOMENTUM_THRESHOLD")
-                .unwrap_or_else(|_| "0.15".to_string())
-                .parse()
-                .unwrap_or(0.15),
-
-            min_trend_strength: env::var("MIN_TREND_STRENGTH")
-                .unwrap_or_else(|_| "0.1".to_string())
-                .parse::<f64>()
-                .unwrap_or(0.1)
-                / 100.0, // Convert percentage to decimal (0.1 → 0.001)
+            custom_rest_url: var("BYBIT_REST_URL").ok(),
+            custom_ws_url: var("BYBIT_WS_URL").ok(),
+            custom_private_ws_url: var("BYBIT_PRIVATE_WS_URL").ok(),
+            endpoint_probe: vars.get("ENDPOINT_PROBE", false),
+            endpoint_probe_interval_secs: vars.get("ENDPOINT_PROBE_INTERVAL_SECS", 3600),
+            rest_endpoints: endpoint_list(var("REST_ENDPOINTS").ok(), &[MAINNET_REST_URL, "https://api.bytick.com"]),
+            ws_endpoints: endpoint_list(var("WS_ENDPOINTS").ok(), &[MAINNET_WS_HOST]),
+            probed_rest_url: None,
+            probed_ws_host: None,
+            private_ws_enabled: vars.get("PRIVATE_WS_ENABLED", false),
+            leverage: leverage.filter(|l| *l >= 1.0),
+            data_dir: var("DATA_DIR").unwrap_or_else(|_| "data".to_string()),
+            equity_snapshot_interval_secs: vars.get("EQUITY_SNAPSHOT_INTERVAL_SECS", 3600),
+
+            max_position_size_usd: vars.get("MAX_POSITION_SIZE_USD", 1000.0),
+            stop_loss_percent: vars.get("STOP_LOSS_PERCENT", 0.5),
+            take_profit_percent: vars.get("TAKE_PROFIT_PERCENT", 1.0),
+
+            scan_interval_secs: vars.get("SCAN_INTERVAL_SECS", 60),
+            min_turnover_24h_usd: vars.get("MIN_TURNOVER_24H_USD", 10_000_000.0),
+            specs_ttl_secs: vars.get("SPECS_TTL_SECS", 3600),
+            score_threshold_multiplier: vars.get("SCORE_THRESHOLD_MULTIPLIER", 1.2),
+            min_symbol_dwell_secs: vars.get("MIN_SYMBOL_DWELL_SECS", 300),
+            switch_confirm_scans: vars.get("SWITCH_CONFIRM_SCANS", 1),
+
+            max_spread_bps: vars.get("MAX_SPREAD_BPS", 20.0),
+            max_spread_ticks: vars.get("MAX_SPREAD_TICKS", 0),
+            spread_typical_multiple: vars.get("SPREAD_TYPICAL_MULTIPLE", 2.0),
+            liq_buffer_percent: vars.get("LIQ_BUFFER_PERCENT", 1.0),
+            margin_check_interval_secs: vars.get("MARGIN_CHECK_INTERVAL_SECS", 60),
+            margin_warn_ratio: vars.get("MARGIN_WARN_RATIO", 0.5),
+            margin_critical_ratio: vars.get("MARGIN_CRITICAL_RATIO", 0.8),
+            margin_auto_reduce: vars.get("MARGIN_AUTO_REDUCE", false),
+            max_drawdown_percent: vars.get("MAX_DRAWDOWN_PERCENT", 0.0),
+            stale_data_threshold_ms: vars.get("STALE_DATA_THRESHOLD_MS", 500),
+            ws_stale_feed_secs: vars.get("WS_STALE_FEED_SECS", 30),
+            warmup_trades: vars.get("WARMUP_TRADES", 300),
+            warmup_klines: vars.get("WARMUP_KLINES", 200),
+
+            liquidity_depth_factor: vars.get("LIQUIDITY_DEPTH_FACTOR", 3.0),
+            liquidity_spread_multiple: vars.get("LIQUIDITY_SPREAD_MULTIPLE", 5.0),
+
+            momentum_threshold: vars.get("MOMENTUM_THRESHOLD", 0.15),
+            entry_cross_urgency: vars.get("ENTRY_CROSS_URGENCY", 0.0),
+            entry_improve_spread_bps: vars.get("ENTRY_IMPROVE_SPREAD_BPS", 5.0),
+            entry_max_reprices: vars.get("ENTRY_MAX_REPRICES", 0),
+            entry_max_reprice_bps: vars.get("ENTRY_MAX_REPRICE_BPS", 10.0),
+            close_limit_window_ms: vars.get("CLOSE_LIMIT_WINDOW_MS", 0),
+            order_poll_interval_ms: vars.get("ORDER_POLL_INTERVAL_MS", 500),
+            entry_max_polls: vars.get("ENTRY_MAX_POLLS", 20),
+            close_max_polls: vars.get("CLOSE_MAX_POLLS", 10),
+            cancel_settle_ms: vars.get("CANCEL_SETTLE_MS", 300),
+            hard_stop_gap_percent: vars.get("HARD_STOP_GAP_PERCENT", 0.0),
+            fast_math: vars.get("FAST_MATH", false),
+            tick_aggregation: vars.get("TICK_AGGREGATION", TickAggregation::Off),
+
+            min_trend_strength: vars.get("MIN_TREND_STRENGTH", 0.1) / 100.0, // Convert percentage to decimal (0.1 → 0.001)
+
+            duplicate_signal_window_secs: vars.get("DUPLICATE_SIGNAL_WINDOW_SECS", 300),
+            duplicate_signal_band_bps: vars.get("DUPLICATE_SIGNAL_BAND_BPS", 10.0),
+
+            max_trades_per_day: vars.get("MAX_TRADES_PER_DAY", 0),
+
+            max_orders_per_minute: vars.get("MAX_ORDERS_PER_MINUTE", 10),
+            max_orders_per_hour: vars.get("MAX_ORDERS_PER_HOUR", 120),
+            duplicate_order_window_secs: vars.get("DUPLICATE_ORDER_WINDOW_SECS", 10),
+            stuck_state_timeout_secs: vars.get("STUCK_STATE_TIMEOUT_SECS", 60),
+            position_drift_action: vars.get("POSITION_DRIFT_ACTION", DriftAction::Adopt),
+
+            loss_streak_pause_after: vars.get("LOSS_STREAK_PAUSE_AFTER", 3),
+            loss_streak_pause_mins: vars.get("LOSS_STREAK_PAUSE_MINS", 15),
+            loss_streak_halt_after: vars.get("LOSS_STREAK_HALT_AFTER", 5),
+
+            symbol_max_loss_usd: vars.get("SYMBOL_MAX_LOSS_USD", 0.0),
+            symbol_blacklist_hours: vars.get("SYMBOL_BLACKLIST_HOURS", 24),
+
+            signal_dataset: vars.get("SIGNAL_DATASET", false),
+            signal_outcome_horizon_secs: vars.get("SIGNAL_OUTCOME_HORIZON_SECS", 300),
+
+            decision_log: vars.get("DECISION_LOG", false),
+            order_audit: vars.get("ORDER_AUDIT", true),
+
+            signal_model_path: var("SIGNAL_MODEL_PATH")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| PathBuf::from(s.trim())),
+            signal_model_threshold: vars.get("SIGNAL_MODEL_THRESHOLD", 0.5),
 
             // ✅ Fixed dollar risk per trade (default $0.30)
-            risk_amount_usd: env::var("RISK_AMOUNT_USD")
-                .unwrap_or_else(|_| "0.30".to_string())
-                .parse()
-                .unwrap_or(0.30),
+            risk_amount_usd: vars.get("RISK_AMOUNT_USD", 0.30),
 
             // ✅ PUMP PROTECTION: Parse blacklist (comma-separated symbols)
-            blacklist_symbols: env::var("BLACKLIST_SYMBOLS")
+            blacklist_symbols: var("BLACKLIST_SYMBOLS")
+                .unwrap_or_else(|_| "".to_string())
+                .split(',')
+                .map(|s| s.trim().to_uppercase())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            whitelist_symbols: var("WHITELIST_SYMBOLS")
                 .unwrap_or_else(|_| "".to_string())
                 .split(',')
                 .map(|s| s.trim().to_uppercase())
@@ -148,13 +713,37 @@ impl Config {
                 .collect(),
 
             // ✅ MEAN REVERSION: Fixed symbol (e.g., BTCUSDT). Empty = auto-scan
-            trading_symbol: env::var("TRADING_SYMBOL")
+            trading_symbol: var("TRADING_SYMBOL")
                 .ok()
                 .filter(|s| !s.trim().is_empty())
                 .map(|s| s.trim().to_uppercase()),
 
+            trading_symbols,
+            max_open_positions: max_open_positions.max(1),
+
+            pair_symbols,
+            pair_notional_usd: vars.get("PAIR_NOTIONAL_USD", 100.0),
+            pair_lookback: vars.get("PAIR_LOOKBACK", 300),
+            pair_entry_z: vars.get("PAIR_ENTRY_Z", 2.0),
+            pair_exit_z: vars.get("PAIR_EXIT_Z", 0.5),
+            pair_stop_z: vars.get("PAIR_STOP_Z", 4.0),
+
+            binance_reference: vars.get("BINANCE_REFERENCE", false),
+            reference_lead_ms: vars.get("REFERENCE_LEAD_MS", 500),
+            reference_max_adverse_bps: vars.get("REFERENCE_MAX_ADVERSE_BPS", 0.0),
+
+            price_source: vars.get("PRICE_SOURCE", PriceSource::Mid),
+
+            max_index_divergence_bps: vars.get("MAX_INDEX_DIVERGENCE_BPS", 0.0),
+
+            taker_flow_window_secs: vars.get("TAKER_FLOW_WINDOW_SECS", 30),
+            min_taker_flow: vars.get("MIN_TAKER_FLOW", 0.0),
+
+            min_reward_risk: vars.get("MIN_REWARD_RISK", 0.0),
+            expected_slippage_bps: vars.get("EXPECTED_SLIPPAGE_BPS", 1.0),
+
             // ✅ SCANNER MODE: "STABLE" or "VOLATILE"
-            scanner_mode: env::var("SCANNER_MODE")
+            scanner_mode: var("SCANNER_MODE")
                 .map(|s| s.trim().to_string()) // Trim whitespace
                 .ok() // Convert Result to Option
                 .filter(|s| !s.is_empty()) // Filter out empty strings
@@ -162,40 +751,467 @@ impl Config {
                 .to_uppercase(),
 
             // ✅ TRADING MODE: MOMENTUM or MEAN_REVERSION (default: MOMENTUM)
-            trading_mode: env::var("TRADING_MODE")
+            trading_mode: vars.get("TRADING_MODE", TradingMode::Momentum),
+
+            telegram_bot_token: var("TELEGRAM_BOT_TOKEN")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            telegram_chat_id: var("TELEGRAM_CHAT_ID")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            telegram_routes: parse_alert_routes(&var("TELEGRAM_ROUTES").unwrap_or_default())
+                .unwrap_or_else(|e| {
+                    vars.problem(format!("Invalid TELEGRAM_ROUTES: {:#}", e));
+                    HashMap::new()
+                }),
+            telegram_allowed_users: var("TELEGRAM_ALLOWED_USERS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .filter_map(|s| match s.parse::<i64>() {
+                    Ok(id) => Some(id),
+                    Err(_) => {
+                        vars.problem(format!("Invalid TELEGRAM_ALLOWED_USERS entry: '{}' (expected a numeric user id)", s));
+                        None
+                    }
+                })
+                .collect(),
+            alert_dedup_window_secs: vars.get("ALERT_DEDUP_WINDOW_SECS", 60),
+            alert_digest_interval_secs: vars.get("ALERT_DIGEST_INTERVAL_SECS", 0),
+            quiet_hours: vars.opt("QUIET_HOURS"),
+            quiet_hours_mode: vars.get("QUIET_HOURS_MODE", QuietHoursMode::Digest),
+            heartbeat_interval_secs: vars.get("HEARTBEAT_INTERVAL_SECS", 1800),
+            heartbeat_stale_tick_secs: vars.get("HEARTBEAT_STALE_TICK_SECS", 120),
+            actor_stall_secs: vars.get("ACTOR_STALL_SECS", 30),
+            watchdog_exit_on_stall: vars.get("WATCHDOG_EXIT_ON_STALL", false),
+            crash_flatten_positions: vars.get("CRASH_FLATTEN_POSITIONS", false),
+            channel_drop_alert_secs: vars.get("CHANNEL_DROP_ALERT_SECS", 30),
+            metrics_addr: var("METRICS_ADDR")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            webhook_addr,
+            webhook_token,
+            redis_url: var("REDIS_URL")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            redis_channel_prefix: var("REDIS_CHANNEL_PREFIX")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| "scalper".to_string()),
+            bot_id: var("BOT_ID")
                 .ok()
-                .and_then(|s| TradingMode::from_str(&s).ok())
-                .unwrap_or(TradingMode::Momentum),
-        })
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| "scalper".to_string()),
+            nats_url: var("NATS_URL")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            kafka_brokers: var("KAFKA_BROKERS")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            event_topic_prefix: var("EVENT_TOPIC_PREFIX")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| "scalper".to_string()),
+            zmq_pub_addr: var("ZMQ_PUB_ADDR")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            control_api_addr,
+            control_api_token,
+            events_ws_addr: var("EVENTS_WS_ADDR")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            events_ws_token: var("EVENTS_WS_TOKEN")
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty()),
+            fault_scenario,
+            shadow_config: var("SHADOW_CONFIG")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .map(PathBuf::from),
+            paper_profiles,
+        };
+
+        let mut problems = vars.problems;
+        problems.extend(config.validate());
+        if !problems.is_empty() {
+            anyhow::bail!(
+                "Invalid configuration ({} problem{}):\n  - {}",
+                problems.len(),
+                if problems.len() == 1 { "" } else { "s" },
+                problems.join("\n  - ")
+            );
+        }
+        Ok(config)
+    }
+
+    /// Cross-field rules, one line per broken rule with the range expected
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, problem: String| {
+            if !ok {
+                problems.push(problem);
+            }
+        };
+
+        check(
+            self.take_profit_percent > ROUND_TRIP_FEE_PERCENT,
+            format!(
+                "TAKE_PROFIT_PERCENT={}: must exceed the round-trip taker fees ({}%), or every take profit loses money",
+                self.take_profit_percent, ROUND_TRIP_FEE_PERCENT
+            ),
+        );
+        let max_stop_loss = 100.0 / self.leverage.unwrap_or(1.0);
+        check(
+            self.stop_loss_percent >= TRAILING_DISTANCE && self.stop_loss_percent < max_stop_loss,
+            format!(
+                "STOP_LOSS_PERCENT={}: expected at least the trailing stop distance ({}%) and under {}% (liquidation at LEVERAGE {})",
+                self.stop_loss_percent,
+                TRAILING_DISTANCE,
+                max_stop_loss,
+                self.leverage.unwrap_or(1.0)
+            ),
+        );
+        // Entries place their hard stop behind the fixed entry stop, not STOP_LOSS_PERCENT
+        check(
+            self.hard_stop_gap_percent >= 0.0 && ENTRY_STOP_LOSS_PERCENT + self.hard_stop_gap_percent < max_stop_loss,
+            format!(
+                "HARD_STOP_GAP_PERCENT={}: expected 0 or more, with the hard stop ({}% from entry) under {}% (liquidation at LEVERAGE {})",
+                self.hard_stop_gap_percent,
+                ENTRY_STOP_LOSS_PERCENT + self.hard_stop_gap_percent,
+                max_stop_loss,
+                self.leverage.unwrap_or(1.0)
+            ),
+        );
+        check(
+            self.max_position_size_usd >= MIN_NOTIONAL_USD,
+            format!(
+                "MAX_POSITION_SIZE_USD={}: expected at least the exchange minimum order value (${})",
+                self.max_position_size_usd, MIN_NOTIONAL_USD
+            ),
+        );
+        check(
+            self.risk_amount_usd > 0.0 && self.risk_amount_usd <= self.max_position_size_usd,
+            format!(
+                "RISK_AMOUNT_USD={}: expected above 0 and at most MAX_POSITION_SIZE_USD ({})",
+                self.risk_amount_usd, self.max_position_size_usd
+            ),
+        );
+        check(
+            self.switch_confirm_scans >= 1,
+            format!("SWITCH_CONFIRM_SCANS={}: expected at least 1", self.switch_confirm_scans),
+        );
+        check(
+            self.momentum_threshold > 0.0,
+            format!("MOMENTUM_THRESHOLD={}: expected above 0 (%)", self.momentum_threshold),
+        );
+        check(
+            0.0 < self.margin_warn_ratio && self.margin_warn_ratio < self.margin_critical_ratio && self.margin_critical_ratio <= 1.0,
+            format!(
+                "MARGIN_WARN_RATIO={} / MARGIN_CRITICAL_RATIO={}: expected 0 < warn < critical <= 1",
+                self.margin_warn_ratio, self.margin_critical_ratio
+            ),
+        );
+        check(
+            (0.0..100.0).contains(&self.max_drawdown_percent),
+            format!("MAX_DRAWDOWN_PERCENT={}: expected 0 (off) to under 100", self.max_drawdown_percent),
+        );
+        // The breaker runs on the account monitor's equity polls
+        check(
+            self.max_drawdown_percent == 0.0 || self.margin_check_interval_secs > 0,
+            format!(
+                "MAX_DRAWDOWN_PERCENT={} needs MARGIN_CHECK_INTERVAL_SECS above 0 (the drawdown breaker checks equity on that poll)",
+                self.max_drawdown_percent
+            ),
+        );
+        check(
+            (0.0..=1.0).contains(&self.signal_model_threshold),
+            format!("SIGNAL_MODEL_THRESHOLD={}: expected a probability, 0 to 1", self.signal_model_threshold),
+        );
+        check(
+            self.max_spread_bps > 0.0,
+            format!("MAX_SPREAD_BPS={}: expected above 0", self.max_spread_bps),
+        );
+        check(
+            self.spread_typical_multiple >= 1.0,
+            format!("SPREAD_TYPICAL_MULTIPLE={}: expected at least 1", self.spread_typical_multiple),
+        );
+        check(
+            self.order_poll_interval_ms > 0 && self.entry_max_polls > 0 && self.close_max_polls > 0,
+            format!(
+                "ORDER_POLL_INTERVAL_MS={}, ENTRY_MAX_POLLS={}, CLOSE_MAX_POLLS={}: expected above 0",
+                self.order_poll_interval_ms, self.entry_max_polls, self.close_max_polls
+            ),
+        );
+        // Polling, cancelling and verifying an entry, once per placement (ENTRY_MAX_REPRICES re-places it)
+        let window_ms = self.order_poll_interval_ms * u64::from(self.entry_max_polls) + self.cancel_settle_ms;
+        let entry_secs = (window_ms * (u64::from(self.entry_max_reprices) + 1)).div_ceil(1000);
+        let min_stuck_state_timeout = MIN_STUCK_STATE_TIMEOUT_SECS.max(entry_secs + STUCK_STATE_MARGIN_SECS);
+        check(
+            self.stuck_state_timeout_secs >= min_stuck_state_timeout,
+            format!(
+                "STUCK_STATE_TIMEOUT_SECS={}: expected at least {} (an entry takes up to ~{}s to resolve)",
+                self.stuck_state_timeout_secs, min_stuck_state_timeout, entry_secs
+            ),
+        );
+        // A lane waiting on an order is not stalled: one entry placement (each beats the
+        // heartbeat) or a whole close ladder has to fit inside ACTOR_STALL_SECS
+        let close_ms = self.close_limit_window_ms
+            + CLOSE_LADDER_RUNGS as u64 * self.order_poll_interval_ms * u64::from(self.close_max_polls);
+        let poll_loop_secs = window_ms.max(close_ms).div_ceil(1000);
+        check(
+            self.actor_stall_secs >= poll_loop_secs + STUCK_STATE_MARGIN_SECS,
+            format!(
+                "ACTOR_STALL_SECS={}: expected at least {} (an order poll loop holds execution up to ~{}s)",
+                self.actor_stall_secs,
+                poll_loop_secs + STUCK_STATE_MARGIN_SECS,
+                poll_loop_secs
+            ),
+        );
+        check(
+            self.entry_max_reprice_bps > 0.0,
+            format!("ENTRY_MAX_REPRICE_BPS={}: expected above 0", self.entry_max_reprice_bps),
+        );
+        check(
+            self.entry_cross_urgency >= 0.0,
+            format!("ENTRY_CROSS_URGENCY={}: expected 0 (always cross) or above", self.entry_cross_urgency),
+        );
+        check(
+            self.reference_lead_ms > 0,
+            format!("REFERENCE_LEAD_MS={}: expected above 0", self.reference_lead_ms),
+        );
+        check(
+            self.reference_max_adverse_bps >= 0.0,
+            format!("REFERENCE_MAX_ADVERSE_BPS={}: expected 0 (off) or above", self.reference_max_adverse_bps),
+        );
+        check(
+            self.max_index_divergence_bps >= 0.0,
+            format!("MAX_INDEX_DIVERGENCE_BPS={}: expected 0 (off) or above", self.max_index_divergence_bps),
+        );
+        check(
+            self.taker_flow_window_secs > 0,
+            format!("TAKER_FLOW_WINDOW_SECS={}: expected above 0", self.taker_flow_window_secs),
+        );
+        check(
+            (0.0..1.0).contains(&self.min_taker_flow),
+            format!("MIN_TAKER_FLOW={}: expected a share in [0, 1) (0 = off)", self.min_taker_flow),
+        );
+        check(
+            self.min_reward_risk >= 0.0,
+            format!("MIN_REWARD_RISK={}: expected 0 (off) or above", self.min_reward_risk),
+        );
+        check(
+            self.expected_slippage_bps >= 0.0,
+            format!("EXPECTED_SLIPPAGE_BPS={}: expected 0 or above", self.expected_slippage_bps),
+        );
+        if self.pair_symbols.is_some() {
+            check(
+                self.pair_notional_usd >= MIN_NOTIONAL_USD,
+                format!(
+                    "PAIR_NOTIONAL_USD={}: expected at least the exchange minimum order value (${})",
+                    self.pair_notional_usd, MIN_NOTIONAL_USD
+                ),
+            );
+            check(
+                self.pair_lookback >= MIN_PAIR_LOOKBACK,
+                format!("PAIR_LOOKBACK={}: expected at least {} samples", self.pair_lookback, MIN_PAIR_LOOKBACK),
+            );
+            check(
+                0.0 <= self.pair_exit_z
+                    && self.pair_exit_z < self.pair_entry_z
+                    && (self.pair_stop_z == 0.0 || self.pair_stop_z > self.pair_entry_z),
+                format!(
+                    "PAIR_EXIT_Z={} / PAIR_ENTRY_Z={} / PAIR_STOP_Z={}: expected 0 <= exit < entry < stop (stop 0 = off)",
+                    self.pair_exit_z, self.pair_entry_z, self.pair_stop_z
+                ),
+            );
+        }
+        problems
     }
 
     /// Get REST API URL
     /// Priority: 1. Custom URL (BYBIT_REST_URL)
-    ///           2. Testnet URL
-    ///           3. Mainnet URL (default)
+    ///           2. Demo Trading URL
+    ///           3. Testnet URL
+    ///           4. Mainnet URL (default)
     pub fn rest_api_url(&self) -> String {
         if let Some(ref custom_url) = self.custom_rest_url {
-            // Custom URL takes highest priority (for Demo Trading)
+            // Custom URL takes highest priority
             custom_url.clone()
+        } else if self.demo_trading {
+            "https://api-demo.bybit.com".to_string()
         } else if self.testnet {
             "https://api-testnet.bybit.com".to_string()
         } else {
-            "https://api.bybit.com".to_string()
+            self.probed_rest_url.clone().unwrap_or_else(|| MAINNET_REST_URL.to_string())
         }
     }
 
+    /// ✅ ENDPOINT PROBE: The REST base is a mainnet default the probe may replace
+    pub fn probes_rest(&self) -> bool {
+        self.endpoint_probe && self.custom_rest_url.is_none() && !self.demo_trading && !self.testnet
+    }
+
+    /// ✅ ENDPOINT PROBE: The public WebSocket is a mainnet default the probe may replace (demo included)
+    pub fn probes_ws(&self) -> bool {
+        self.endpoint_probe && self.custom_ws_url.is_none() && !self.testnet
+    }
+
     /// Get WebSocket URL
     /// Priority: 1. Custom URL (BYBIT_WS_URL)
     ///           2. Testnet URL
-    ///           3. Mainnet URL (default)
+    ///           3. Mainnet URL (default, also for Demo Trading: demo has no public stream)
     pub fn ws_url(&self) -> String {
         if let Some(ref custom_url) = self.custom_ws_url {
-            // Custom URL takes highest priority (for Demo Trading)
+            // Custom URL takes highest priority
             custom_url.clone()
         } else if self.testnet {
             "wss://stream-testnet.bybit.com/v5/public/linear".to_string()
         } else {
-            "wss://stream.bybit.com/v5/public/linear".to_string()
+            format!("{}/v5/public/linear", self.probed_ws_host.as_deref().unwrap_or(MAINNET_WS_HOST))
+        }
+    }
+
+    /// A leg of the pair trader (PAIR_SYMBOLS)
+    pub fn is_pair_symbol(&self, symbol: &str) -> bool {
+        self.pair_symbols
+            .as_ref()
+            .is_some_and(|(a, b)| a.eq_ignore_ascii_case(symbol) || b.eq_ignore_ascii_case(symbol))
+    }
+
+    /// Path of a state file inside `DATA_DIR`
+    pub fn data_path(&self, file_name: &str) -> PathBuf {
+        PathBuf::from(&self.data_dir).join(file_name)
+    }
+
+    /// Human-readable trading environment for startup logs
+    pub fn environment(&self) -> &'static str {
+        if self.demo_trading {
+            "DEMO"
+        } else if self.testnet {
+            "TESTNET"
+        } else if self.custom_rest_url.is_some() {
+            "CUSTOM"
+        } else {
+            "MAINNET"
+        }
+    }
+
+    /// Get private (authenticated) WebSocket URL
+    /// Priority: 1. Custom URL (BYBIT_PRIVATE_WS_URL)
+    ///           2. Demo Trading URL
+    ///           3. Testnet URL
+    ///           4. Mainnet URL (default)
+    pub fn private_ws_url(&self) -> String {
+        if let Some(ref custom_url) = self.custom_private_ws_url {
+            custom_url.clone()
+        } else if self.demo_trading {
+            "wss://stream-demo.bybit.com/v5/private".to_string()
+        } else if self.testnet {
+            "wss://stream-testnet.bybit.com/v5/private".to_string()
+        } else {
+            format!("{}/v5/private", self.probed_ws_host.as_deref().unwrap_or(MAINNET_WS_HOST))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_map(vars: &HashMap<String, String>) -> Result<Config> {
+        Config::from_vars(|key| vars.get(key).cloned().ok_or(env::VarError::NotPresent))
+    }
+
+    fn base() -> HashMap<String, String> {
+        [("BYBIT_API_KEY", "key"), ("BYBIT_API_SECRET", "secret")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_env_example_is_valid() {
+        let mut vars: HashMap<String, String> = dotenvy::from_path_iter(".env.example")
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        vars.extend(base());
+        from_map(&vars).unwrap();
+    }
+
+    #[test]
+    fn test_every_problem_is_reported_at_once() {
+        let mut vars = base();
+        for (key, value) in [
+            ("MAX_POSITION_SIZE_USD", "lots"),
+            ("DUPLICATE_SIGNAL_WINDOW_SECS", "-5"),
+            ("TAKE_PROFIT_PERCENT", "0.05"),
+            ("STOP_LOSS_PERCENT", "0.1"),
+            ("TRADING_MODE", "sideways"),
+        ] {
+            vars.insert(key.to_string(), value.to_string());
+        }
+        vars.remove("BYBIT_API_SECRET");
+
+        let error = from_map(&vars).unwrap_err().to_string();
+        assert!(error.starts_with("Invalid configuration (6 problems)"), "{}", error);
+        assert!(error.contains("MAX_POSITION_SIZE_USD=\"lots\": expected a number"), "{}", error);
+        assert!(error.contains("DUPLICATE_SIGNAL_WINDOW_SECS=\"-5\": expected a whole number >= 0"), "{}", error);
+        assert!(error.contains("TAKE_PROFIT_PERCENT=0.05: must exceed the round-trip taker fees (0.11%)"), "{}", error);
+        assert!(error.contains("STOP_LOSS_PERCENT=0.1: expected at least the trailing stop distance (0.2%)"), "{}", error);
+        assert!(error.contains("Invalid TRADING_MODE: 'sideways'"), "{}", error);
+        assert!(error.contains("BYBIT_API_SECRET not found"), "{}", error);
+    }
+
+    #[test]
+    fn test_drawdown_breaker_needs_the_margin_poll() {
+        let mut vars = base();
+        vars.insert("MAX_DRAWDOWN_PERCENT".to_string(), "10".to_string());
+        vars.insert("MARGIN_CHECK_INTERVAL_SECS".to_string(), "0".to_string());
+        let error = from_map(&vars).unwrap_err().to_string();
+        assert!(error.contains("MAX_DRAWDOWN_PERCENT=10 needs MARGIN_CHECK_INTERVAL_SECS above 0"), "{}", error);
+
+        vars.insert("MAX_DRAWDOWN_PERCENT".to_string(), "0".to_string());
+        from_map(&vars).unwrap();
+    }
+
+    #[test]
+    fn test_stuck_timeout_covers_every_entry_reprice() {
+        let mut vars = base();
+        vars.insert("ENTRY_MAX_REPRICES".to_string(), "5".to_string());
+        // Six placements of 20 × 500ms polls + 300ms settle: ~62s, past the default 60s
+        let error = from_map(&vars).unwrap_err().to_string();
+        assert!(error.contains("STUCK_STATE_TIMEOUT_SECS=60: expected at least 66 (an entry takes up to ~62s"), "{}", error);
+
+        vars.insert("STUCK_STATE_TIMEOUT_SECS".to_string(), "66".to_string());
+        from_map(&vars).unwrap();
+    }
+
+    #[test]
+    fn test_hard_stop_sits_behind_the_entry_stop() {
+        let mut vars = base();
+        // Liquidation 2% away; the entry stop is ENTRY_STOP_LOSS_PERCENT, whatever STOP_LOSS_PERCENT says
+        vars.insert("LEVERAGE".to_string(), "50".to_string());
+        vars.insert("STOP_LOSS_PERCENT".to_string(), "1.9".to_string());
+        vars.insert("HARD_STOP_GAP_PERCENT".to_string(), "1.5".to_string());
+        from_map(&vars).unwrap();
+
+        vars.insert("HARD_STOP_GAP_PERCENT".to_string(), "1.7".to_string());
+        let error = from_map(&vars).unwrap_err().to_string();
+        assert!(error.contains("HARD_STOP_GAP_PERCENT=1.7"), "{}", error);
+    }
+
+    #[test]
+    fn test_stall_limit_covers_the_order_poll_loops() {
+        let mut vars = base();
+        // Three close rungs of 20 × 500ms polls: ~30s, past the default 30s
+        vars.insert("CLOSE_MAX_POLLS".to_string(), "20".to_string());
+        let error = from_map(&vars).unwrap_err().to_string();
+        assert!(error.contains("ACTOR_STALL_SECS=30: expected at least 34 (an order poll loop holds execution up to ~30s"), "{}", error);
+
+        vars.insert("ACTOR_STALL_SECS".to_string(), "34".to_string());
+        from_map(&vars).unwrap();
+    }
+}
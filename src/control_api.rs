@@ -0,0 +1,348 @@
+//! Remote Control API
+//!
+//! Token-protected HTTP endpoints for scripting the bot without Telegram
+//! (`Authorization: Bearer <CONTROL_API_TOKEN>` on every request):
+//!
+//! - `GET /status` — strategy snapshot (JSON)
+//! - `POST /pause`, `POST /resume` — stop / allow new entries
+//! - `POST /close` — market-close the open position
+//! - `POST /symbol` `{"symbol": "ETHUSDT"}` — trade this symbol (`null` = back to scanning)
+//! - `PATCH /params` `{"momentum_threshold": 0.15, ...}` — live parameter change
+//! - `POST /promote` `{"profile": "tight"}` — live engine takes a paper profile's parameters
+//! - `GET /blacklist`, `GET /whitelist` — current lists
+//! - `POST` / `DELETE` `/blacklist` or `/whitelist` `{"symbol": "PEPEUSDT"}` — add / remove
+//!   (persisted and audited, see `symbol_lists`)
+//!
+//! Unlike Telegram there is no confirmation step: the token is the authorization.
+
+use crate::actors::messages::{ControlCommand, ParamPatch, ScannerMessage, StrategyMessage};
+use crate::channel::MeteredSender;
+use crate::config::Config;
+use crate::http::{self, token_matches, Request};
+use crate::models::Symbol;
+use crate::shadow::Arena;
+use crate::symbol_lists::{Edit, ListKind, SymbolLists};
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::time::{timeout, Duration};
+use tracing::{debug, info, warn};
+
+/// How long a request waits for the strategy/scanner before giving up
+const ACTOR_TIMEOUT_SECS: u64 = 3;
+
+type Response = (&'static str, serde_json::Value);
+
+#[derive(Debug, Deserialize)]
+struct SymbolRequest {
+    symbol: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListEditRequest {
+    symbol: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PromoteRequest {
+    profile: String,
+}
+
+pub struct ControlApi {
+    token: String,
+    /// Symbols the lists don't allow can't be pinned
+    symbols: SymbolLists,
+    strategy_tx: MeteredSender<StrategyMessage>,
+    scanner_tx: MeteredSender<ScannerMessage>,
+    arena: Arena,
+}
+
+impl ControlApi {
+    pub fn new(
+        config: &Config,
+        strategy_tx: MeteredSender<StrategyMessage>,
+        scanner_tx: MeteredSender<ScannerMessage>,
+        symbols: SymbolLists,
+        arena: Arena,
+    ) -> Self {
+        Self {
+            token: config.control_api_token.clone(),
+            symbols,
+            strategy_tx,
+            scanner_tx,
+            arena,
+        }
+    }
+
+    /// Serve until the listener fails
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        info!("🎛️ Control API listening on http://{}", addr);
+        let api = Arc::new(self);
+
+        loop {
+            let (mut stream, peer) = listener.accept().await?;
+            let api = api.clone();
+
+            tokio::spawn(async move {
+                let (status, body) = match http::read_request(&mut stream).await {
+                    Ok(request) => api.handle(&request).await,
+                    Err(status) => {
+                        debug!("Bad control API request from {}: {}", peer, status);
+                        (status, json!({ "error": status }))
+                    }
+                };
+                http::respond(&mut stream, status, "application/json", &body.to_string()).await;
+            });
+        }
+    }
+
+    async fn handle(&self, request: &Request) -> Response {
+        if !token_matches(&self.token, request.bearer_token().unwrap_or("")) {
+            warn!("🚫 Control API request with a bad token: {} {}", request.method, request.route());
+            return ("401 Unauthorized", json!({ "error": "unauthorized" }));
+        }
+
+        match (request.method.as_str(), request.route()) {
+            ("GET", "/status") => self.status().await,
+            ("POST", "/pause") => self.control(ControlCommand::Pause).await,
+            ("POST", "/resume") => self.control(ControlCommand::Resume).await,
+            ("POST", "/close") => self.control(ControlCommand::ClosePosition).await,
+            ("POST", "/symbol") => self.switch_symbol(&request.body).await,
+            ("PATCH", "/params") => self.patch_params(&request.body).await,
+            ("POST", "/promote") => self.promote(&request.body).await,
+            ("GET", "/blacklist") => self.list(ListKind::Blacklist),
+            ("GET", "/whitelist") => self.list(ListKind::Whitelist),
+            ("POST", "/blacklist") => self.edit_list(ListKind::Blacklist, true, &request.body).await,
+            ("POST", "/whitelist") => self.edit_list(ListKind::Whitelist, true, &request.body).await,
+            ("DELETE", "/blacklist") => self.edit_list(ListKind::Blacklist, false, &request.body).await,
+            ("DELETE", "/whitelist") => self.edit_list(ListKind::Whitelist, false, &request.body).await,
+            (_, "/status" | "/pause" | "/resume" | "/close" | "/symbol" | "/params" | "/promote" | "/blacklist" | "/whitelist") => {
+                ("405 Method Not Allowed", json!({ "error": "method not allowed" }))
+            }
+            _ => ("404 Not Found", json!({ "error": "not found" })),
+        }
+    }
+
+    async fn status(&self) -> Response {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        if let Err(response) = self.send_strategy(StrategyMessage::Status(reply_tx)).await {
+            return response;
+        }
+        match timeout(Duration::from_secs(ACTOR_TIMEOUT_SECS), reply_rx).await {
+            Ok(Ok(status)) => ("200 OK", json!(status)),
+            _ => ("503 Service Unavailable", json!({ "error": "strategy did not answer" })),
+        }
+    }
+
+    async fn control(&self, command: ControlCommand) -> Response {
+        info!("🎛️ Control API: {}", command);
+        match self.send_strategy(StrategyMessage::Control(command)).await {
+            Ok(()) => ("202 Accepted", json!({ "accepted": command.to_string() })),
+            Err(response) => response,
+        }
+    }
+
+    async fn switch_symbol(&self, body: &[u8]) -> Response {
+        let request: SymbolRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => return ("400 Bad Request", json!({ "error": format!("invalid JSON: {}", e) })),
+        };
+        let symbol = match request.symbol.map(|s| s.trim().to_uppercase()) {
+            Some(s) if s.is_empty() || !s.chars().all(|c| c.is_ascii_alphanumeric()) => {
+                return ("400 Bad Request", json!({ "error": format!("invalid symbol '{}'", s) }));
+            }
+            Some(s) if !self.symbols.allows(&s) => {
+                let reason = self.symbols.rejects(&s).unwrap_or_default();
+                return ("409 Conflict", json!({ "error": format!("{} is {}", s, reason) }));
+            }
+            other => other.map(Symbol::from),
+        };
+
+        info!("🎛️ Control API: pin symbol {:?}", symbol);
+        let pinned = json!({ "pinned": symbol.as_ref().map(|s| s.0.clone()) });
+        match timeout(
+            Duration::from_secs(ACTOR_TIMEOUT_SECS),
+            self.scanner_tx.send(ScannerMessage::PinSymbol(symbol)),
+        )
+        .await
+        {
+            Ok(Ok(())) => ("202 Accepted", pinned),
+            _ => ("503 Service Unavailable", json!({ "error": "scanner unavailable" })),
+        }
+    }
+
+    async fn patch_params(&self, body: &[u8]) -> Response {
+        let patch: ParamPatch = match serde_json::from_slice(body) {
+            Ok(patch) => patch,
+            Err(e) => return ("400 Bad Request", json!({ "error": format!("invalid params: {}", e) })),
+        };
+        if patch.is_empty() {
+            return ("400 Bad Request", json!({ "error": "nothing to change" }));
+        }
+        if let Err(e) = patch.validate() {
+            return ("400 Bad Request", json!({ "error": e }));
+        }
+        info!("🎛️ Control API: params {:?}", patch);
+        match self.send_strategy(StrategyMessage::PatchParams(patch)).await {
+            Ok(()) => ("202 Accepted", json!({ "accepted": "params" })),
+            Err(response) => response,
+        }
+    }
+
+    async fn promote(&self, body: &[u8]) -> Response {
+        let request: PromoteRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => return ("400 Bad Request", json!({ "error": format!("invalid JSON: {}", e) })),
+        };
+        let profile = request.profile.trim().to_lowercase();
+        let patch = match self.arena.promotion(&profile) {
+            Ok(patch) => patch,
+            Err(e) => return ("404 Not Found", json!({ "error": e })),
+        };
+        info!("🎛️ Control API: promote paper profile '{}' {:?}", profile, patch);
+        match self.send_strategy(StrategyMessage::PatchParams(patch)).await {
+            Ok(()) => ("202 Accepted", json!({ "promoted": profile })),
+            Err(response) => response,
+        }
+    }
+
+    fn list(&self, kind: ListKind) -> Response {
+        ("200 OK", json!({ "list": kind, "symbols": self.symbols.list(kind) }))
+    }
+
+    /// Add (or remove) a symbol; a change makes the scanner leave a current symbol no longer allowed
+    async fn edit_list(&self, kind: ListKind, add: bool, body: &[u8]) -> Response {
+        let request: ListEditRequest = match serde_json::from_slice(body) {
+            Ok(request) => request,
+            Err(e) => return ("400 Bad Request", json!({ "error": format!("invalid JSON: {}", e) })),
+        };
+        let symbol = request.symbol.trim().to_uppercase();
+        if symbol.is_empty() || !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return ("400 Bad Request", json!({ "error": format!("invalid symbol '{}'", symbol) }));
+        }
+
+        let edit = if add {
+            self.symbols.add(kind, &symbol, "control_api").await
+        } else {
+            self.symbols.remove(kind, &symbol, "control_api").await
+        };
+        if edit == Edit::LastEntry {
+            return (
+                "409 Conflict",
+                json!({ "error": format!("{} is the last whitelisted symbol: an empty whitelist would allow every symbol", symbol) }),
+            );
+        }
+        let changed = edit == Edit::Changed;
+        if changed {
+            let rescan = self.scanner_tx.send(ScannerMessage::SymbolListsChanged);
+            if !matches!(timeout(Duration::from_secs(ACTOR_TIMEOUT_SECS), rescan).await, Ok(Ok(()))) {
+                warn!("🎛️ Control API: scanner didn't take the {} change", kind);
+            }
+        }
+        (
+            "200 OK",
+            json!({ "list": kind, "symbol": symbol, "changed": changed, "symbols": self.symbols.list(kind) }),
+        )
+    }
+
+    async fn send_strategy(&self, message: StrategyMessage) -> Result<(), Response> {
+        match timeout(Duration::from_secs(ACTOR_TIMEOUT_SECS), self.strategy_tx.send(message)).await {
+            Ok(Ok(())) => Ok(()),
+            _ => Err(("503 Service Unavailable", json!({ "error": "strategy unavailable" }))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::{metered_channel, OverflowPolicy};
+    use crate::metrics::Metrics;
+    use crate::symbol_lists::SymbolList;
+
+    fn request(method: &str, path: &str, token: &str, body: &str) -> Request {
+        let raw = format!(
+            "{} {} HTTP/1.1\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            token,
+            body.len(),
+            body
+        );
+        http::parse_request(raw.as_bytes()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_commands_need_the_token_and_reach_the_actors() {
+        let metrics = Metrics::new();
+        let (strategy_tx, mut strategy_rx) = metered_channel("strategy", 8, OverflowPolicy::Block { timeout: None }, &metrics);
+        let (scanner_tx, mut scanner_rx) = metered_channel("scanner", 8, OverflowPolicy::Block { timeout: None }, &metrics);
+        let api = ControlApi {
+            token: "s3cret".to_string(),
+            symbols: SymbolLists::new(SymbolList::fixed(&["LUNAUSDT".to_string()]), SymbolList::fixed(&[]), None),
+            strategy_tx,
+            scanner_tx,
+            arena: Arena::default(),
+        };
+
+        assert_eq!(api.handle(&request("POST", "/pause", "wrong", "")).await.0, "401 Unauthorized");
+        assert!(strategy_rx.try_recv().is_none());
+
+        assert_eq!(api.handle(&request("POST", "/pause", "s3cret", "")).await.0, "202 Accepted");
+        assert!(matches!(strategy_rx.try_recv(), Some(StrategyMessage::Control(ControlCommand::Pause))));
+
+        let (status, _) = api.handle(&request("POST", "/symbol", "s3cret", r#"{"symbol":"ethusdt"}"#)).await;
+        assert_eq!(status, "202 Accepted");
+        assert!(matches!(scanner_rx.try_recv(), Some(ScannerMessage::PinSymbol(Some(s))) if s.as_str() == "ETHUSDT"));
+        let (status, _) = api.handle(&request("POST", "/symbol", "s3cret", r#"{"symbol":"LUNAUSDT"}"#)).await;
+        assert_eq!(status, "409 Conflict");
+
+        let (status, _) = api.handle(&request("PATCH", "/params", "s3cret", r#"{"momentum_threshold":-1}"#)).await;
+        assert_eq!(status, "400 Bad Request");
+        let (status, _) = api.handle(&request("PATCH", "/params", "s3cret", r#"{"stop_loss":1}"#)).await;
+        assert_eq!(status, "400 Bad Request");
+        let (status, _) = api.handle(&request("POST", "/promote", "s3cret", r#"{"profile":"tight"}"#)).await;
+        assert_eq!(status, "404 Not Found");
+        assert_eq!(api.handle(&request("GET", "/close", "s3cret", "")).await.0, "405 Method Not Allowed");
+    }
+
+    #[tokio::test]
+    async fn test_list_edits_fence_symbols_and_trigger_a_rescan() {
+        let metrics = Metrics::new();
+        let (strategy_tx, _strategy_rx) = metered_channel("strategy", 8, OverflowPolicy::Block { timeout: None }, &metrics);
+        let (scanner_tx, mut scanner_rx) = metered_channel("scanner", 8, OverflowPolicy::Block { timeout: None }, &metrics);
+        let api = ControlApi {
+            token: "s3cret".to_string(),
+            symbols: SymbolLists::new(SymbolList::fixed(&[]), SymbolList::fixed(&[]), None),
+            strategy_tx,
+            scanner_tx,
+            arena: Arena::default(),
+        };
+
+        let (status, body) = api.handle(&request("POST", "/whitelist", "s3cret", r#"{"symbol":"solusdt"}"#)).await;
+        assert_eq!(status, "200 OK");
+        assert_eq!(body["changed"], json!(true));
+        assert!(matches!(scanner_rx.try_recv(), Some(ScannerMessage::SymbolListsChanged)));
+        // Off the whitelist = can't be pinned
+        let (status, _) = api.handle(&request("POST", "/symbol", "s3cret", r#"{"symbol":"ETHUSDT"}"#)).await;
+        assert_eq!(status, "409 Conflict");
+
+        let (_, body) = api.handle(&request("POST", "/whitelist", "s3cret", r#"{"symbol":"SOLUSDT"}"#)).await;
+        assert_eq!(body["changed"], json!(false));
+        assert!(scanner_rx.try_recv().is_none());
+
+        api.handle(&request("POST", "/whitelist", "s3cret", r#"{"symbol":"ADAUSDT"}"#)).await;
+        let (status, body) = api.handle(&request("DELETE", "/whitelist", "s3cret", r#"{"symbol":"SOLUSDT"}"#)).await;
+        assert_eq!((status, body["symbols"].clone()), ("200 OK", json!(["ADAUSDT"])));
+        // The last one stays: an empty whitelist would let everything in
+        let (status, _) = api.handle(&request("DELETE", "/whitelist", "s3cret", r#"{"symbol":"ADAUSDT"}"#)).await;
+        assert_eq!(status, "409 Conflict");
+        assert_eq!(api.handle(&request("POST", "/blacklist", "s3cret", r#"{"symbol":"a-b"}"#)).await.0, "400 Bad Request");
+        assert_eq!(api.handle(&request("GET", "/blacklist", "s3cret", "")).await.0, "200 OK");
+        assert_eq!(api.handle(&request("PATCH", "/blacklist", "s3cret", "")).await.0, "405 Method Not Allowed");
+    }
+}
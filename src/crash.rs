@@ -0,0 +1,131 @@
+//! Crash Handling
+//!
+//! A panic or a dead actor must never leave a silent naked position:
+//! - the panic hook pushes a critical alert (message + location) to the notifier
+//! - `emergency_flatten` market-closes the positions this process opened straight
+//!   through the REST client, without relying on the (possibly dead) strategy/execution
+//!   actors. Positions the bot didn't open (manual trades, other bots) are left alone
+
+use crate::actors::messages::NotifierMessage;
+use crate::channel::MeteredSender;
+use crate::exchange::BybitClient;
+use crate::models::*;
+use crate::notifications::{escape_html, Alert};
+use anyhow::Result;
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::panic::PanicHookInfo;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Alert on every panic (tokio catches task panics, but the hook still runs first)
+pub fn install_panic_hook(notifier_tx: MeteredSender<NotifierMessage>) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let text = format!(
+            "💥 <b>PANIC</b> in thread '{}'\n<code>{}</code>",
+            escape_html(thread.name().unwrap_or("unnamed")),
+            escape_html(&panic_message(info))
+        );
+        error!("💥 PANIC: {}", panic_message(info));
+        // try_send never blocks, safe inside the hook
+        let _ = notifier_tx.try_send(NotifierMessage::Alert(Alert::critical(text)));
+        previous(info);
+    }));
+}
+
+/// "message at file:line"
+pub fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".to_string());
+
+    match info.location() {
+        Some(location) => format!("{} at {}:{}", message, location.file(), location.line()),
+        None => message,
+    }
+}
+
+/// Symbols execution sent an entry on and hasn't seen flat since (cheap to clone, shared)
+#[derive(Clone, Default)]
+pub struct OwnedPositions(Arc<Mutex<HashSet<Symbol>>>);
+
+impl OwnedPositions {
+    /// Before the entry goes out: it may fill even if its outcome is never confirmed
+    pub fn opening(&self, symbol: &Symbol) {
+        self.0.lock().insert(symbol.clone());
+    }
+
+    pub fn closed(&self, symbol: &Symbol) {
+        self.0.lock().remove(symbol);
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.0.lock().iter().any(|owned| owned.as_str() == symbol)
+    }
+}
+
+/// Reduce-only market close of every open linear position in `owned`. Returns one report line per position.
+pub async fn emergency_flatten(client: &BybitClient, owned: &OwnedPositions) -> Result<Vec<String>> {
+    let positions = client.get_open_positions().await?;
+    let mut report = Vec::new();
+
+    for pos_info in positions {
+        let size = Decimal::from_str(&pos_info.size).unwrap_or(Decimal::ZERO);
+        if size.is_zero() {
+            continue;
+        }
+        if !owned.contains(&pos_info.symbol) {
+            info!("🚨 Leaving {} {} {} open: not opened by this bot", pos_info.symbol, pos_info.side, size);
+            continue;
+        }
+
+        let close_side = if pos_info.side == "Buy" {
+            OrderSide::Sell
+        } else {
+            OrderSide::Buy
+        };
+        let order = Order {
+            symbol: Symbol::from(pos_info.symbol.as_str()),
+            side: close_side,
+            order_type: OrderType::Market,
+            qty: size,
+            price: None,
+            time_in_force: TimeInForce::IOC,
+            reduce_only: true,
+            qty_step: None,
+            tick_size: None,
+            ref_price: None,
+            allow_duplicate: false,
+            hard_stop_percent: None,
+            // Hedge mode holds each side under its own index
+            position_idx: pos_info.position_idx,
+        };
+
+        info!("🚨 Emergency close: {} {:?} {}", pos_info.symbol, close_side, size);
+        match client.place_order(&order).await {
+            Ok(response) => report.push(format!(
+                "✅ {} {} {} → close order {}",
+                pos_info.symbol, pos_info.side, size, response.order_id
+            )),
+            Err(e) => {
+                error!("Emergency close of {} failed: {}", pos_info.symbol, e);
+                report.push(format!(
+                    "❌ {} {} {}: {}",
+                    pos_info.symbol,
+                    pos_info.side,
+                    size,
+                    escape_html(&e.to_string())
+                ));
+            }
+        }
+    }
+
+    Ok(report)
+}
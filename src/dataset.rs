@@ -0,0 +1,331 @@
+//! Signal Dataset
+//!
+//! Every evaluated entry signal, taken or not, with its feature vector and
+//! what happened next: the price move over a fixed horizon and, for taken
+//! signals, the trade result. One JSON line per signal in `DATA_DIR`, written
+//! once the outcome is known, as training data for model-driven filters.
+
+use crate::models::{ExitReason, Symbol, TradeRecord};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Inputs the strategy had when it evaluated the signal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignalFeatures {
+    /// Price vs. 50-tick VWAP, % (sign = direction)
+    pub momentum_percent: f64,
+    /// Price vs. 200-tick VWAP, %
+    pub vwap_distance_percent: Option<f64>,
+    /// 50- vs. 200-tick VWAP: true = bullish
+    pub trend_bullish: Option<bool>,
+    /// 1m ATR, % of price
+    pub atr_percent: Option<f64>,
+    pub regime: Option<String>,
+    pub spread_bps: f64,
+    /// Visible book imbalance, -1 (all asks) .. 1 (all bids)
+    pub imbalance: f64,
+    /// 24h price change (0.25 = +25%)
+    pub price_change_24h: Option<f64>,
+    pub confirmations: u8,
+    /// Binance mid over Bybit mid, bps (dataset only; None = no reference feed)
+    #[serde(default)]
+    pub reference_premium_bps: Option<f64>,
+    /// Binance return minus Bybit return over `REFERENCE_LEAD_MS`, bps
+    #[serde(default)]
+    pub reference_lead_bps: Option<f64>,
+    /// Mark price vs. index, bps (dataset only)
+    #[serde(default)]
+    pub mark_index_bps: Option<f64>,
+    /// Last price vs. index, bps
+    #[serde(default)]
+    pub last_index_bps: Option<f64>,
+    /// Taker buy volume / total over `TAKER_FLOW_WINDOW_SECS`, 0..1 (dataset only)
+    #[serde(default)]
+    pub taker_buy_ratio: Option<f64>,
+}
+
+impl SignalFeatures {
+    /// Column names of `vector()`, in order (model input layout)
+    pub const NAMES: [&'static str; 9] = [
+        "momentum_percent",
+        "vwap_distance_percent",
+        "trend",
+        "atr_percent",
+        "trending",
+        "spread_bps",
+        "imbalance",
+        "price_change_24h",
+        "confirmations",
+    ];
+
+    /// Flat float vector for model scoring: missing values are 0, trend is +1/-1,
+    /// regime is 1 when trending
+    pub fn vector(&self) -> [f32; 9] {
+        [
+            self.momentum_percent as f32,
+            self.vwap_distance_percent.unwrap_or(0.0) as f32,
+            match self.trend_bullish {
+                Some(true) => 1.0,
+                Some(false) => -1.0,
+                None => 0.0,
+            },
+            self.atr_percent.unwrap_or(0.0) as f32,
+            if self.regime.as_deref() == Some("TRENDING") { 1.0 } else { 0.0 },
+            self.spread_bps as f32,
+            self.imbalance as f32,
+            self.price_change_24h.unwrap_or(0.0) as f32,
+            f32::from(self.confirmations),
+        ]
+    }
+}
+
+/// What happened after the signal
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SignalOutcome {
+    /// Mid-price move in the signal's direction after the horizon, bps
+    pub horizon_return_bps: Option<f64>,
+    /// Taken signals: the trade's result
+    pub trade_net_pnl: Option<Decimal>,
+    pub trade_roe_percent: Option<f64>,
+    pub exit_reason: Option<ExitReason>,
+}
+
+/// One dataset line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignalSample {
+    pub at: DateTime<Utc>,
+    pub symbol: Symbol,
+    pub bullish: bool,
+    pub price: Decimal,
+    pub features: SignalFeatures,
+    pub taken: bool,
+    /// Why a signal was not taken ("spread", "duplicate", "unconfirmed", ...)
+    pub reject_reason: Option<String>,
+    /// Win probability from the signal model, if one is configured
+    #[serde(default)]
+    pub model_score: Option<f32>,
+    pub outcome: SignalOutcome,
+}
+
+struct PendingSample {
+    sample: SignalSample,
+    recorded: Instant,
+    horizon_done: bool,
+    /// Taken and the trade hasn't closed yet
+    awaiting_trade: bool,
+}
+
+impl PendingSample {
+    fn is_complete(&self) -> bool {
+        self.horizon_done && !self.awaiting_trade
+    }
+}
+
+/// Signals waiting for their outcome, written to disk once complete
+pub struct SignalDataset {
+    path: Option<PathBuf>,
+    horizon: Duration,
+    pending: Vec<PendingSample>,
+}
+
+impl SignalDataset {
+    /// `path` None = dataset off (every call is a no-op)
+    pub fn new(path: Option<PathBuf>, horizon: Duration) -> Self {
+        Self {
+            path,
+            horizon,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn record(&mut self, sample: SignalSample, now: Instant) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.pending.push(PendingSample {
+            awaiting_trade: sample.taken,
+            sample,
+            recorded: now,
+            horizon_done: false,
+        });
+    }
+
+    /// Fill in horizon returns that are due and write out finished samples
+    pub fn observe_price(&mut self, symbol: &Symbol, price: Decimal, now: Instant) {
+        for pending in self.pending.iter_mut() {
+            if pending.horizon_done
+                || pending.sample.symbol != *symbol
+                || now.duration_since(pending.recorded) < self.horizon
+            {
+                continue;
+            }
+            pending.sample.outcome.horizon_return_bps = Some(directional_bps(&pending.sample, price));
+            pending.horizon_done = true;
+        }
+        self.flush_complete();
+    }
+
+    /// Attach a closed trade to the taken signal it came from
+    pub fn trade_closed(&mut self, trade: &TradeRecord) {
+        if let Some(pending) = self
+            .pending
+            .iter_mut()
+            .rev()
+            .find(|p| p.awaiting_trade && p.sample.symbol == trade.symbol)
+        {
+            let outcome = &mut pending.sample.outcome;
+            outcome.trade_net_pnl = Some(trade.net_pnl);
+            outcome.trade_roe_percent = Some(trade.roe_percent);
+            outcome.exit_reason = Some(trade.exit_reason);
+            pending.awaiting_trade = false;
+        }
+        self.flush_complete();
+    }
+
+    /// The entry order for the last taken signal never filled
+    pub fn entry_failed(&mut self, reason: &str) {
+        if let Some(pending) = self.pending.iter_mut().rev().find(|p| p.awaiting_trade) {
+            pending.awaiting_trade = false;
+            pending.sample.taken = false;
+            pending.sample.reject_reason = Some(format!("order_failed: {}", reason));
+        }
+        self.flush_complete();
+    }
+
+    /// Symbol switch: no more prices for the old symbol, write what is known
+    pub fn flush_symbol(&mut self, symbol: &Symbol) {
+        for pending in self.pending.iter_mut().filter(|p| p.sample.symbol == *symbol) {
+            pending.horizon_done = true;
+        }
+        self.flush_complete();
+    }
+
+    fn flush_complete(&mut self) {
+        if !self.pending.iter().any(PendingSample::is_complete) {
+            return;
+        }
+        let (done, pending): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.pending).into_iter().partition(PendingSample::is_complete);
+        self.pending = pending;
+        if let Err(e) = self.append(done.iter().map(|p| &p.sample)) {
+            warn!("⚠️ Signal dataset write failed: {:#}", e);
+        }
+    }
+
+    fn append<'a>(&self, samples: impl Iterator<Item = &'a SignalSample>) -> Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        let mut lines = String::new();
+        for sample in samples {
+            lines.push_str(&serde_json::to_string(sample)?);
+            lines.push('\n');
+        }
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open {}", path.display()))?
+            .write_all(lines.as_bytes())
+            .with_context(|| format!("failed to append to {}", path.display()))?;
+        debug!("🧪 {} signal samples written", lines.lines().count());
+        Ok(())
+    }
+}
+
+/// Move from the signal price to `price`, bps, positive when it went the signal's way
+fn directional_bps(sample: &SignalSample, price: Decimal) -> f64 {
+    if sample.price.is_zero() {
+        return 0.0;
+    }
+    let change = (price - sample.price) / sample.price * Decimal::from(10_000);
+    let change = change.to_f64().unwrap_or(0.0);
+    if sample.bullish {
+        change
+    } else {
+        -change
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(symbol: &str, taken: bool) -> SignalSample {
+        SignalSample {
+            at: Utc::now(),
+            symbol: Symbol::from(symbol),
+            bullish: false,
+            price: Decimal::from(100),
+            features: SignalFeatures {
+                momentum_percent: -0.2,
+                vwap_distance_percent: Some(-0.3),
+                trend_bullish: Some(false),
+                atr_percent: Some(0.1),
+                regime: Some("TRENDING".to_string()),
+                spread_bps: 1.5,
+                imbalance: -0.4,
+                price_change_24h: Some(0.05),
+                confirmations: 3,
+                reference_premium_bps: None,
+                reference_lead_bps: None,
+                mark_index_bps: None,
+                last_index_bps: None,
+                taker_buy_ratio: None,
+            },
+            taken,
+            reject_reason: (!taken).then(|| "spread".to_string()),
+            model_score: None,
+            outcome: SignalOutcome::default(),
+        }
+    }
+
+    #[test]
+    fn test_samples_are_written_once_the_outcome_is_known() {
+        let path = std::env::temp_dir().join(format!("signals-test-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let horizon = Duration::from_secs(60);
+        let mut dataset = SignalDataset::new(Some(path.clone()), horizon);
+        let start = Instant::now();
+        let btc = Symbol::from("BTCUSDT");
+
+        dataset.record(sample("BTCUSDT", false), start);
+        dataset.record(sample("BTCUSDT", true), start);
+        dataset.observe_price(&btc, Decimal::from(99), start + Duration::from_secs(30));
+        assert!(!path.exists());
+
+        // Rejected short: price fell 1% over the horizon → +100 bps in its favour
+        dataset.observe_price(&btc, Decimal::from(99), start + horizon);
+        let lines: Vec<SignalSample> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+        assert!(!lines[0].taken);
+        assert!((lines[0].outcome.horizon_return_bps.unwrap() - 100.0).abs() < 1e-9);
+
+        // The taken one waits for its trade
+        dataset.entry_failed("rejected");
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.contains("order_failed: rejected"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
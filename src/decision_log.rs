@@ -0,0 +1,404 @@
+//! Decision Log
+//!
+//! Append-only record of the strategy (`DECISION_LOG`): every message it
+//! handles and every decision it takes, one JSON line each in
+//! `DATA_DIR/decisions.jsonl`, in the order they happened. Inputs are logged
+//! whole (orderbooks with their depth, warm-ups with every tick), signals with
+//! the full feature vector and model score, so a trade can be replayed from
+//! the inputs the strategy saw (`replay`) and explained by the numbers it
+//! decided on. Lines written while a trade is in flight carry its `cid` (the
+//! same ID as the `trade{cid=...}` log span).
+//!
+//! Market data lines are buffered; anything else is flushed right away, so a
+//! crash loses at most a few ticks, never a decision.
+
+use crate::actors::messages::{ControlCommand, ParamPatch, StrategyMessage};
+use crate::dataset::SignalSample;
+use crate::exchange::SymbolSpecs;
+use crate::models::{Candle, ExitReason, Fill, Order, OrderBookSnapshot, Position, Symbol, TickerUpdate, TradeSide, TradeTick};
+use crate::webhook::ExternalSignal;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// What happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DecisionEntry {
+    /// The strategy started (a replay starts over from a clean strategy here)
+    Started,
+
+    // Inputs (the symbol of symbol-less variants is the line's)
+    OrderBook {
+        snapshot: OrderBookSnapshot,
+    },
+    Trade {
+        price: Decimal,
+        size: Decimal,
+        side: TradeSide,
+        timestamp: i64,
+    },
+    Ticker {
+        ticker: TickerUpdate,
+    },
+    WarmupTicks {
+        ticks: Vec<TradeTick>,
+    },
+    WarmupCandles {
+        candles: Vec<Candle>,
+    },
+    PositionUpdate {
+        position: Option<Position>,
+    },
+    SymbolChanged {
+        specs: SymbolSpecs,
+        price_change_24h: f64,
+    },
+    SpecsRefreshed {
+        specs: SymbolSpecs,
+    },
+    MarketStats {
+        price_change_24h: f64,
+    },
+    OrderFilled {
+        fill: Fill,
+    },
+    CloseFilled {
+        fill: Fill,
+        reason: ExitReason,
+    },
+    OrderFailed {
+        reason: String,
+        retryable: bool,
+    },
+    MonitorExit {
+        reason: ExitReason,
+        price: Decimal,
+    },
+    Reconciled {
+        position: Option<Position>,
+    },
+    Control {
+        command: ControlCommand,
+    },
+    ExternalSignal {
+        signal: ExternalSignal,
+    },
+    ParamsPatched {
+        momentum_threshold: Option<f64>,
+        trade_cooldown_secs: Option<u64>,
+        max_trades_per_day: Option<u32>,
+    },
+
+    // Decisions
+    /// Entry signal evaluated: features, model score, taken or why not
+    Signal { sample: Box<SignalSample> },
+    /// Entry order handed to execution
+    OrderSent { order: Order },
+    /// Close decided by the strategy itself (monitor exits are logged as inputs)
+    Exit {
+        reason: ExitReason,
+        price: Option<Decimal>,
+        pnl_percent: Option<f64>,
+    },
+    StateChanged { from: String, to: String },
+}
+
+impl DecisionEntry {
+    /// The input side of a strategy message (None for queries that change nothing)
+    pub fn input(msg: &StrategyMessage) -> Option<Self> {
+        let entry = match msg {
+            StrategyMessage::OrderBook(snapshot) => DecisionEntry::OrderBook { snapshot: snapshot.clone() },
+            StrategyMessage::Trade(tick) => DecisionEntry::Trade {
+                price: tick.price,
+                size: tick.size,
+                side: tick.side,
+                timestamp: tick.timestamp,
+            },
+            StrategyMessage::Ticker(ticker) => DecisionEntry::Ticker { ticker: ticker.clone() },
+            StrategyMessage::WarmupTicks { ticks, .. } => DecisionEntry::WarmupTicks { ticks: ticks.clone() },
+            StrategyMessage::WarmupCandles { candles, .. } => DecisionEntry::WarmupCandles { candles: candles.clone() },
+            StrategyMessage::PositionUpdate { position, .. } => DecisionEntry::PositionUpdate { position: position.clone() },
+            StrategyMessage::SymbolChanged { specs, price_change_24h, .. } => DecisionEntry::SymbolChanged {
+                specs: specs.clone(),
+                price_change_24h: *price_change_24h,
+            },
+            StrategyMessage::SpecsRefreshed(specs) => DecisionEntry::SpecsRefreshed { specs: specs.clone() },
+            StrategyMessage::UpdateMarketStats { price_change_24h, .. } => {
+                DecisionEntry::MarketStats { price_change_24h: *price_change_24h }
+            }
+            StrategyMessage::OrderFilled(fill) => DecisionEntry::OrderFilled { fill: fill.clone() },
+            StrategyMessage::CloseFilled { fill, reason } => DecisionEntry::CloseFilled { fill: fill.clone(), reason: *reason },
+            StrategyMessage::OrderFailed { reason, retryable, .. } => DecisionEntry::OrderFailed {
+                reason: reason.clone(),
+                retryable: *retryable,
+            },
+            StrategyMessage::MonitorExit { reason, price, .. } => DecisionEntry::MonitorExit { reason: *reason, price: *price },
+            StrategyMessage::Reconciled { position, .. } => DecisionEntry::Reconciled { position: position.clone() },
+            StrategyMessage::Control(command) => DecisionEntry::Control { command: *command },
+            StrategyMessage::ExternalSignal(signal) => DecisionEntry::ExternalSignal { signal: signal.clone() },
+            StrategyMessage::PatchParams(patch) => DecisionEntry::ParamsPatched {
+                momentum_threshold: patch.momentum_threshold,
+                trade_cooldown_secs: patch.trade_cooldown_secs,
+                max_trades_per_day: patch.max_trades_per_day,
+            },
+            StrategyMessage::Status(_) => return None,
+        };
+        Some(entry)
+    }
+
+    /// The strategy message an input line was written for (None for decisions,
+    /// markers, and inputs that need a symbol when the line has none)
+    pub fn into_message(self, symbol: Option<&Symbol>) -> Option<StrategyMessage> {
+        let symbol = || symbol.cloned();
+        let msg = match self {
+            DecisionEntry::OrderBook { snapshot } => StrategyMessage::OrderBook(snapshot),
+            DecisionEntry::Trade { price, size, side, timestamp } => StrategyMessage::Trade(TradeTick {
+                symbol: symbol()?,
+                price,
+                size,
+                timestamp,
+                side,
+            }),
+            DecisionEntry::Ticker { ticker } => StrategyMessage::Ticker(ticker),
+            DecisionEntry::WarmupTicks { ticks } => StrategyMessage::WarmupTicks { symbol: symbol()?, ticks },
+            DecisionEntry::WarmupCandles { candles } => StrategyMessage::WarmupCandles { symbol: symbol()?, candles },
+            DecisionEntry::PositionUpdate { position } => StrategyMessage::PositionUpdate { symbol: symbol()?, position, query: None },
+            DecisionEntry::SymbolChanged { specs, price_change_24h } => StrategyMessage::SymbolChanged {
+                symbol: symbol()?,
+                specs,
+                price_change_24h,
+            },
+            DecisionEntry::SpecsRefreshed { specs } => StrategyMessage::SpecsRefreshed(specs),
+            DecisionEntry::MarketStats { price_change_24h } => {
+                StrategyMessage::UpdateMarketStats { symbol: symbol()?, price_change_24h }
+            }
+            DecisionEntry::OrderFilled { fill } => StrategyMessage::OrderFilled(fill),
+            DecisionEntry::CloseFilled { fill, reason } => StrategyMessage::CloseFilled { fill, reason },
+            DecisionEntry::OrderFailed { reason, retryable } => StrategyMessage::OrderFailed {
+                symbol: symbol()?,
+                reason,
+                retryable,
+            },
+            DecisionEntry::MonitorExit { reason, price } => StrategyMessage::MonitorExit { symbol: symbol()?, reason, price },
+            DecisionEntry::Reconciled { position } => StrategyMessage::Reconciled { symbol: symbol()?, position },
+            DecisionEntry::Control { command } => StrategyMessage::Control(command),
+            DecisionEntry::ExternalSignal { signal } => StrategyMessage::ExternalSignal(signal),
+            DecisionEntry::ParamsPatched { momentum_threshold, trade_cooldown_secs, max_trades_per_day } => {
+                StrategyMessage::PatchParams(ParamPatch { momentum_threshold, trade_cooldown_secs, max_trades_per_day })
+            }
+            DecisionEntry::Started
+            | DecisionEntry::Signal { .. }
+            | DecisionEntry::OrderSent { .. }
+            | DecisionEntry::Exit { .. }
+            | DecisionEntry::StateChanged { .. } => return None,
+        };
+        Some(msg)
+    }
+
+    /// Something the strategy decided (as opposed to an input or a marker)
+    pub fn is_decision(&self) -> bool {
+        matches!(
+            self,
+            DecisionEntry::Signal { .. }
+                | DecisionEntry::OrderSent { .. }
+                | DecisionEntry::Exit { .. }
+                | DecisionEntry::StateChanged { .. }
+        )
+    }
+
+    fn is_market_data(&self) -> bool {
+        matches!(self, DecisionEntry::OrderBook { .. } | DecisionEntry::Trade { .. })
+    }
+}
+
+/// One line of the log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionLine {
+    pub at: DateTime<Utc>,
+    pub symbol: Option<Symbol>,
+    /// Strategy state when the line was written (before the input was handled)
+    pub state: String,
+    /// Trade in flight, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cid: Option<String>,
+    #[serde(flatten)]
+    pub entry: DecisionEntry,
+}
+
+/// Writer owned by the strategy; with no path every call is a no-op
+pub struct DecisionLog {
+    path: Option<PathBuf>,
+    writer: Option<BufWriter<File>>,
+    /// Lines kept for `take_lines` instead of being written (replay)
+    memory: Option<Vec<DecisionLine>>,
+}
+
+impl DecisionLog {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self { path, writer: None, memory: None }
+    }
+
+    /// Keeps the lines until `take_lines` (replay compares them with the recorded ones)
+    pub fn in_memory() -> Self {
+        Self { path: None, writer: None, memory: Some(Vec::new()) }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some() || self.memory.is_some()
+    }
+
+    /// Lines recorded since the last call (always empty for a file log)
+    pub fn take_lines(&mut self) -> Vec<DecisionLine> {
+        self.memory.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Log failures never interrupt trading, they are only logged
+    pub fn record(&mut self, line: DecisionLine) {
+        if let Some(lines) = self.memory.as_mut() {
+            lines.push(line);
+            return;
+        }
+        if !self.is_enabled() {
+            return;
+        }
+        let flush = !line.entry.is_market_data();
+        if let Err(e) = self.append(&line, flush) {
+            warn!("⚠️ Decision log write failed: {:#}", e);
+            // Reopen on the next line
+            self.writer = None;
+        }
+    }
+
+    /// Push buffered market data lines to disk
+    pub fn flush(&mut self) {
+        if let Some(writer) = self.writer.as_mut() {
+            if let Err(e) = writer.flush() {
+                warn!("⚠️ Decision log flush failed: {}", e);
+            }
+        }
+    }
+
+    fn append(&mut self, line: &DecisionLine, flush: bool) -> Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => self.writer.insert(BufWriter::new(open(path)?)),
+        };
+        serde_json::to_writer(&mut *writer, line)?;
+        writer.write_all(b"\n")?;
+        if flush {
+            writer.flush().with_context(|| format!("failed to append to {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DecisionLog {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))
+}
+
+/// Every line in the file, skipping lines that don't parse (e.g. a torn last write)
+pub fn read_all(path: &Path) -> Result<Vec<DecisionLine>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The lines of trade `cid`: from the signal that opened it until the strategy
+/// was Idle again, with every input it saw in between
+pub fn trade_lines<'a>(lines: &'a [DecisionLine], cid: &str) -> &'a [DecisionLine] {
+    let Some(start) = lines.iter().position(|line| line.cid.as_deref() == Some(cid)) else {
+        return &[];
+    };
+    let end = lines[start..]
+        .iter()
+        .position(|line| matches!(&line.entry, DecisionEntry::StateChanged { to, .. } if to == "Idle"))
+        .map_or(lines.len(), |offset| start + offset + 1);
+    &lines[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(cid: Option<&str>, entry: DecisionEntry) -> DecisionLine {
+        DecisionLine {
+            at: Utc::now(),
+            symbol: Some(Symbol::from("BTCUSDT")),
+            state: "Idle".to_string(),
+            cid: cid.map(str::to_string),
+            entry,
+        }
+    }
+
+    fn trade(price: i64) -> DecisionEntry {
+        DecisionEntry::Trade {
+            price: Decimal::from(price),
+            size: Decimal::ONE,
+            side: TradeSide::Buy,
+            timestamp: 0,
+        }
+    }
+
+    fn state(from: &str, to: &str) -> DecisionEntry {
+        DecisionEntry::StateChanged { from: from.to_string(), to: to.to_string() }
+    }
+
+    #[test]
+    fn test_log_round_trips_and_picks_out_a_trade() {
+        let path = std::env::temp_dir().join(format!("decisions-test-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut log = DecisionLog::new(Some(path.clone()));
+
+        let written = vec![
+            line(None, trade(99)),
+            line(Some("a-1"), DecisionEntry::Exit { reason: ExitReason::Manual, price: None, pnl_percent: None }),
+            line(Some("a-1"), state("OrderPending", "PositionOpen")),
+            line(Some("a-1"), trade(101)),
+            line(Some("a-1"), state("ClosingPosition", "Idle")),
+            line(None, trade(102)),
+        ];
+        for l in written.clone() {
+            log.record(l);
+        }
+        // Market data stays buffered until something else (or a flush) comes along
+        log.flush();
+
+        let lines = read_all(&path).unwrap();
+        let json = |lines: &[DecisionLine]| serde_json::to_value(lines).unwrap();
+        assert_eq!(json(&lines), json(&written));
+        assert_eq!(json(trade_lines(&lines, "a-1")), json(&written[1..5]));
+        assert!(trade_lines(&lines, "b-2").is_empty());
+
+        drop(log);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,99 @@
+//! Equity High-Water Mark
+//!
+//! Peak account equity for the drawdown breaker, persisted in `DATA_DIR` so
+//! a restart cannot reset drawdown-based risk limits. The file only ever
+//! moves up; deleting it is the explicit way to start a new peak.
+
+use crate::persist;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct HwmState {
+    high_water_mark: Decimal,
+    updated_at: DateTime<Utc>,
+}
+
+pub struct HighWaterMark {
+    path: PathBuf,
+    state: Option<HwmState>,
+}
+
+impl HighWaterMark {
+    /// Load the stored peak (a missing file starts fresh, an unreadable one is reported)
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let state = match persist::load_json::<HwmState>(&path) {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("⚠️ Ignoring equity high-water mark file: {:#}", e);
+                None
+            }
+        };
+        if let Some(ref state) = state {
+            info!("📈 Equity high-water mark {} (since {})", state.high_water_mark, state.updated_at);
+        }
+        Self { path, state }
+    }
+
+    pub fn value(&self) -> Option<Decimal> {
+        self.state.as_ref().map(|s| s.high_water_mark)
+    }
+
+    /// Raise the peak if `equity` exceeds it (persisted right away); returns the current peak
+    pub fn observe(&mut self, equity: Decimal) -> Decimal {
+        if self.value().is_some_and(|hwm| equity <= hwm) {
+            return self.value().unwrap_or(equity);
+        }
+        let state = HwmState {
+            high_water_mark: equity,
+            updated_at: Utc::now(),
+        };
+        if let Err(e) = persist::save_json(&self.path, &state) {
+            warn!("⚠️ Failed to persist equity high-water mark: {:#}", e);
+        }
+        self.state = Some(state);
+        equity
+    }
+
+    /// Drop of `equity` below the peak, % of the peak (0 at or above it)
+    pub fn drawdown_percent(&self, equity: Decimal) -> f64 {
+        match self.value() {
+            Some(hwm) if hwm > Decimal::ZERO && equity < hwm => {
+                ((hwm - equity) / hwm * Decimal::from(100)).to_f64().unwrap_or(0.0)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peak_survives_restart_and_only_moves_up() {
+        let path = std::env::temp_dir().join(format!("hwm-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut hwm = HighWaterMark::load(&path);
+        assert_eq!(hwm.value(), None);
+        assert_eq!(hwm.observe(Decimal::from(1000)), Decimal::from(1000));
+        assert_eq!(hwm.observe(Decimal::from(900)), Decimal::from(1000));
+        assert!((hwm.drawdown_percent(Decimal::from(900)) - 10.0).abs() < 1e-9);
+
+        // Restart at the lower equity: drawdown is still measured from the old peak
+        let mut restarted = HighWaterMark::load(&path);
+        assert_eq!(restarted.observe(Decimal::from(850)), Decimal::from(1000));
+        assert!((restarted.drawdown_percent(Decimal::from(850)) - 15.0).abs() < 1e-9);
+
+        assert_eq!(restarted.observe(Decimal::from(1100)), Decimal::from(1100));
+        assert_eq!(HighWaterMark::load(&path).value(), Some(Decimal::from(1100)));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+//! Endpoint Latency Probing
+//!
+//! Bybit's mainnet API answers on more than one domain, and a few tens of ms
+//! of round trip decide whether a maker entry is early in the queue or taker
+//! flow ran over it first. With `ENDPOINT_PROBE=true` the candidates
+//! (`REST_ENDPOINTS`, `WS_ENDPOINTS`) are timed before anything connects: REST
+//! as the best of `PROBE_SAMPLES` `GET /v5/market/time`, WebSocket hosts as the
+//! best TCP connect. The fastest of each is used for the whole run; every
+//! result is logged and exported (`endpoint_rtt_seconds`, `endpoint_in_use`).
+//! Every `ENDPOINT_PROBE_INTERVAL_SECS` the candidates are timed again and one
+//! clearly faster than the endpoint in use is reported: clients keep their
+//! endpoint until the next restart.
+
+use crate::config::Config;
+use crate::metrics::Metrics;
+use std::fmt;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::time::{interval, Duration, Instant};
+use tracing::{info, warn};
+
+/// Timings per candidate, the best one counts
+pub const PROBE_SAMPLES: u32 = 3;
+/// A sample slower than this counts as unreachable
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+/// A candidate is reported once it needs less than this share of the RTT in use
+const REPORT_RATIO: f64 = 0.8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointKind {
+    Rest,
+    Ws,
+}
+
+impl EndpointKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EndpointKind::Rest => "rest",
+            EndpointKind::Ws => "ws",
+        }
+    }
+}
+
+impl fmt::Display for EndpointKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One timed candidate
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub kind: EndpointKind,
+    pub url: String,
+    /// None = unreachable
+    pub rtt: Option<Duration>,
+}
+
+/// The reachable candidate of `kind` with the lowest RTT
+pub fn fastest(probes: &[Probe], kind: EndpointKind) -> Option<&Probe> {
+    probes
+        .iter()
+        .filter(|p| p.kind == kind && p.rtt.is_some())
+        .min_by_key(|p| p.rtt)
+}
+
+/// A candidate clearly faster than `current` (an unreachable endpoint in use loses to any)
+pub fn better_than<'a>(probes: &'a [Probe], kind: EndpointKind, current: &str) -> Option<&'a Probe> {
+    let best = fastest(probes, kind).filter(|p| p.url != current)?;
+    let current_rtt = probes.iter().find(|p| p.kind == kind && p.url == current).and_then(|p| p.rtt);
+    match (best.rtt, current_rtt) {
+        (Some(best_rtt), Some(current_rtt)) => {
+            (best_rtt.as_secs_f64() < current_rtt.as_secs_f64() * REPORT_RATIO).then_some(best)
+        }
+        _ => Some(best),
+    }
+}
+
+/// "host:port" of a URL, the port defaulting by scheme
+fn host_port(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split('/').next().filter(|a| !a.is_empty())?;
+    if authority.contains(':') {
+        return Some(authority.to_string());
+    }
+    let port = match scheme {
+        "https" | "wss" => 443,
+        "http" | "ws" => 80,
+        _ => return None,
+    };
+    Some(format!("{}:{}", authority, port))
+}
+
+/// Best of `PROBE_SAMPLES` timed `attempt`s (None = every one failed)
+async fn best_of<F, Fut>(mut attempt: F) -> Option<Duration>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    let mut best: Option<Duration> = None;
+    for _ in 0..PROBE_SAMPLES {
+        let start = Instant::now();
+        if let Ok(true) = tokio::time::timeout(PROBE_TIMEOUT, attempt()).await {
+            let rtt = start.elapsed();
+            best = Some(best.map_or(rtt, |best| best.min(rtt)));
+        }
+    }
+    best
+}
+
+/// Server time request; the connection is reused, so after the first sample this is one round trip
+pub async fn probe_rest(http: &reqwest::Client, base_url: &str) -> Option<Duration> {
+    let url = format!("{}/v5/market/time", base_url);
+    best_of(|| async { http.get(&url).send().await.is_ok_and(|r| r.status().is_success()) }).await
+}
+
+/// TCP connect to the WebSocket host: one round trip
+pub async fn probe_ws(url: &str) -> Option<Duration> {
+    let addr = host_port(url)?;
+    best_of(|| async { TcpStream::connect(&addr).await.is_ok() }).await
+}
+
+/// Time every candidate `config` lets the probe replace
+pub async fn probe_all(config: &Config) -> Vec<Probe> {
+    let mut probes = Vec::new();
+    if config.probes_rest() {
+        let http = reqwest::Client::new();
+        for url in &config.rest_endpoints {
+            let rtt = probe_rest(&http, url).await;
+            probes.push(Probe { kind: EndpointKind::Rest, url: url.clone(), rtt });
+        }
+    }
+    if config.probes_ws() {
+        for url in &config.ws_endpoints {
+            let rtt = probe_ws(url).await;
+            probes.push(Probe { kind: EndpointKind::Ws, url: url.clone(), rtt });
+        }
+    }
+    for probe in &probes {
+        match probe.rtt {
+            Some(rtt) => info!("📡 {} endpoint {}: {:.1} ms", probe.kind, probe.url, rtt.as_secs_f64() * 1000.0),
+            None => warn!("📡 {} endpoint {}: unreachable", probe.kind, probe.url),
+        }
+    }
+    probes
+}
+
+/// Startup: point `config` at the fastest candidates (no probe configured = nothing changes)
+pub async fn select(config: &mut Config) -> Vec<Probe> {
+    let probes = probe_all(config).await;
+    if let Some(best) = fastest(&probes, EndpointKind::Rest) {
+        info!("📡 Using REST endpoint {}", best.url);
+        config.probed_rest_url = Some(best.url.clone());
+    }
+    if let Some(best) = fastest(&probes, EndpointKind::Ws) {
+        info!("📡 Using WebSocket host {}", best.url);
+        config.probed_ws_host = Some(best.url.clone());
+    }
+    probes
+}
+
+/// Endpoint of `kind` the clients use
+fn in_use(config: &Config, kind: EndpointKind) -> String {
+    match kind {
+        EndpointKind::Rest => config.rest_api_url(),
+        EndpointKind::Ws => config.probed_ws_host.clone().unwrap_or_default(),
+    }
+}
+
+pub fn record(metrics: &Metrics, config: &Config, probes: &[Probe]) {
+    for probe in probes {
+        let used = in_use(config, probe.kind) == probe.url;
+        metrics.record_endpoint(probe.kind.as_str(), &probe.url, probe.rtt, used);
+    }
+}
+
+/// Re-probe every ENDPOINT_PROBE_INTERVAL_SECS, report a clearly faster candidate
+pub async fn monitor(config: Arc<Config>, metrics: Arc<Metrics>) {
+    let mut ticker = interval(Duration::from_secs(config.endpoint_probe_interval_secs.max(1)));
+    ticker.tick().await; // The startup probe just ran
+    loop {
+        ticker.tick().await;
+        let probes = probe_all(&config).await;
+        record(&metrics, &config, &probes);
+        for kind in [EndpointKind::Rest, EndpointKind::Ws] {
+            let current = in_use(&config, kind);
+            if let Some(better) = better_than(&probes, kind, &current) {
+                warn!(
+                    "📡 {} endpoint {} ({:?}) is now faster than {} in use: restart to switch",
+                    kind, better.url, better.rtt, current
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(kind: EndpointKind, url: &str, ms: Option<u64>) -> Probe {
+        Probe {
+            kind,
+            url: url.to_string(),
+            rtt: ms.map(Duration::from_millis),
+        }
+    }
+
+    #[test]
+    fn test_fastest_reachable_wins_and_only_a_clear_gain_is_reported() {
+        let probes = vec![
+            probe(EndpointKind::Rest, "https://api.bybit.com", Some(80)),
+            probe(EndpointKind::Rest, "https://api.bytick.com", Some(70)),
+            probe(EndpointKind::Rest, "https://down.example", None),
+            probe(EndpointKind::Ws, "wss://stream.bybit.com", Some(90)),
+        ];
+        assert_eq!(fastest(&probes, EndpointKind::Rest).unwrap().url, "https://api.bytick.com");
+        assert_eq!(fastest(&probes, EndpointKind::Ws).unwrap().url, "wss://stream.bybit.com");
+
+        // 70 vs 80 ms is noise, 70 vs an unreachable endpoint is not
+        assert!(better_than(&probes, EndpointKind::Rest, "https://api.bybit.com").is_none());
+        assert_eq!(better_than(&probes, EndpointKind::Rest, "https://down.example").unwrap().url, "https://api.bytick.com");
+        assert!(better_than(&probes, EndpointKind::Rest, "https://api.bytick.com").is_none());
+    }
+
+    #[test]
+    fn test_host_port_defaults_by_scheme() {
+        assert_eq!(host_port("wss://stream.bybit.com").as_deref(), Some("stream.bybit.com:443"));
+        assert_eq!(host_port("ws://localhost:8080/v5/public").as_deref(), Some("localhost:8080"));
+        assert_eq!(host_port("stream.bybit.com"), None);
+    }
+
+    #[tokio::test]
+    async fn test_ws_probe_times_a_connect() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        assert!(probe_ws(&url).await.is_some());
+
+        drop(listener);
+        assert!(probe_ws(&url).await.is_none());
+    }
+}
@@ -0,0 +1,209 @@
+//! Event Publisher
+//!
+//! Forwards every domain event (`events::BotEvent`, JSON) to NATS subjects
+//! and/or Kafka topics named `{prefix}.{type}`, e.g. `scalper.trade_closed`.
+//! Kafka messages are keyed by `bot_id`, so one bot's events stay ordered.
+//! Needs the `nats` / `kafka` build features.
+
+use crate::config::Config;
+use crate::events::{BotEvent, EventBus};
+use anyhow::Result;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{info, warn};
+
+#[cfg(feature = "nats")]
+mod nats {
+    use super::*;
+    use anyhow::Context;
+
+    pub struct NatsSink {
+        client: async_nats::Client,
+    }
+
+    impl NatsSink {
+        /// Keeps retrying in the background if the server isn't up yet
+        pub async fn connect(url: &str) -> Result<Self> {
+            let client = async_nats::ConnectOptions::new()
+                .retry_on_initial_connect()
+                .connect(url)
+                .await
+                .with_context(|| format!("invalid NATS_URL {}", url))?;
+            Ok(Self { client })
+        }
+
+        pub async fn publish(&self, subject: &str, _key: &str, payload: Vec<u8>) -> Result<()> {
+            self.client.publish(subject.to_string(), payload.into()).await?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(feature = "nats"))]
+mod nats {
+    use super::*;
+
+    pub enum NatsSink {}
+
+    impl NatsSink {
+        pub async fn connect(url: &str) -> Result<Self> {
+            anyhow::bail!("NATS_URL={} needs a build with `--features nats`", url)
+        }
+
+        pub async fn publish(&self, _subject: &str, _key: &str, _payload: Vec<u8>) -> Result<()> {
+            match *self {}
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+mod kafka {
+    use super::*;
+    use anyhow::Context;
+    use rdkafka::producer::{FutureProducer, FutureRecord};
+    use rdkafka::ClientConfig;
+
+    pub struct KafkaSink {
+        producer: FutureProducer,
+    }
+
+    impl KafkaSink {
+        pub fn connect(brokers: &str) -> Result<Self> {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .set("message.timeout.ms", "10000")
+                .create()
+                .context("failed to create Kafka producer")?;
+            Ok(Self { producer })
+        }
+
+        /// Queued in librdkafka, delivery isn't awaited
+        pub async fn publish(&self, topic: &str, key: &str, payload: Vec<u8>) -> Result<()> {
+            self.producer
+                .send_result(FutureRecord::to(topic).key(key).payload(&payload))
+                .map(drop)
+                .map_err(|(e, _)| e.into())
+        }
+    }
+}
+
+#[cfg(not(feature = "kafka"))]
+mod kafka {
+    use super::*;
+
+    pub enum KafkaSink {}
+
+    impl KafkaSink {
+        pub fn connect(brokers: &str) -> Result<Self> {
+            anyhow::bail!("KAFKA_BROKERS={} needs a build with `--features kafka`", brokers)
+        }
+
+        pub async fn publish(&self, _topic: &str, _key: &str, _payload: Vec<u8>) -> Result<()> {
+            match *self {}
+        }
+    }
+}
+
+enum Sink {
+    Nats(nats::NatsSink),
+    Kafka(kafka::KafkaSink),
+}
+
+impl Sink {
+    fn name(&self) -> &'static str {
+        match self {
+            Sink::Nats(_) => "NATS",
+            Sink::Kafka(_) => "Kafka",
+        }
+    }
+
+    async fn publish(&self, subject: &str, key: &str, payload: Vec<u8>) -> Result<()> {
+        match self {
+            Sink::Nats(sink) => sink.publish(subject, key, payload).await,
+            Sink::Kafka(sink) => sink.publish(subject, key, payload).await,
+        }
+    }
+}
+
+/// One outbound connection, fed from the event bus
+pub struct EventPublisher {
+    sink: Sink,
+    prefix: String,
+    events: broadcast::Receiver<BotEvent>,
+}
+
+impl EventPublisher {
+    /// Publishers for NATS_URL / KAFKA_BROKERS (empty when neither is set)
+    pub async fn from_config(config: &Config, events: &EventBus) -> Result<Vec<Self>> {
+        let mut sinks = Vec::new();
+        if let Some(ref url) = config.nats_url {
+            sinks.push(Sink::Nats(nats::NatsSink::connect(url).await?));
+        }
+        if let Some(ref brokers) = config.kafka_brokers {
+            sinks.push(Sink::Kafka(kafka::KafkaSink::connect(brokers)?));
+        }
+        Ok(sinks
+            .into_iter()
+            .map(|sink| Self {
+                sink,
+                prefix: config.event_topic_prefix.clone(),
+                events: events.subscribe(),
+            })
+            .collect())
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.sink.name()
+    }
+
+    /// Task monitor label
+    pub fn task_name(&self) -> &'static str {
+        match self.sink {
+            Sink::Nats(_) => "nats_publisher",
+            Sink::Kafka(_) => "kafka_publisher",
+        }
+    }
+
+    pub async fn run(mut self) {
+        info!("📡 {} event publisher started ({}.<type>)", self.sink.name(), self.prefix);
+        loop {
+            let event = match self.events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("⚠️ {} publisher fell behind, {} events dropped", self.sink.name(), missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            let payload = match event.to_json() {
+                Ok(payload) => payload.into_bytes(),
+                Err(e) => {
+                    warn!("Failed to serialize {} event: {}", event.kind.name(), e);
+                    continue;
+                }
+            };
+            let subject = subject(&self.prefix, &event);
+            if let Err(e) = self.sink.publish(&subject, &event.bot_id, payload).await {
+                warn!("⚠️ {} publish to {} failed: {:#}", self.sink.name(), subject, e);
+            }
+        }
+    }
+}
+
+/// `{prefix}.{type}`
+fn subject(prefix: &str, event: &BotEvent) -> String {
+    format!("{}.{}", prefix, event.kind.name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventKind;
+
+    #[test]
+    fn test_subject_per_event_type() {
+        let bus = EventBus::new("bot-a");
+        let mut rx = bus.subscribe();
+        bus.publish(EventKind::ControlCommand { command: "RESUME".to_string() });
+        assert_eq!(subject("scalper", &rx.try_recv().unwrap()), "scalper.control_command");
+    }
+}
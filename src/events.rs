@@ -0,0 +1,184 @@
+//! Domain Events
+//!
+//! What the strategy does (tick stats, signals, orders, fills, position PnL,
+//! closed trades, state changes), broadcast to optional outbound integrations.
+//! Publishing never blocks the strategy: with no subscriber it is a no-op, and
+//! a subscriber that falls behind loses the oldest events instead of slowing
+//! the hot path.
+//!
+//! The JSON is a public schema (README "События"): fields may be added, but a
+//! rename, removal or type change bumps `EVENT_SCHEMA_VERSION`.
+
+use crate::models::{Fill, Order, PositionSide, Symbol, TradeRecord};
+use crate::redact::redact;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before it starts losing them
+const EVENT_BUFFER: usize = 1024;
+
+/// `schema` field of every event
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    /// Market feed summary for the traded symbol, every few seconds
+    TickStats {
+        symbol: Symbol,
+        /// Trade ticks since the previous `tick_stats`
+        ticks: u64,
+        last_price: Option<Decimal>,
+        spread_bps: Option<f64>,
+        momentum_percent: Option<f64>,
+    },
+    /// Entry signal evaluated (own confirmed signal or external)
+    Signal {
+        symbol: Symbol,
+        bullish: bool,
+        price: Decimal,
+        /// "momentum" or the external sender
+        source: String,
+        taken: bool,
+        reject_reason: Option<String>,
+    },
+    /// Entry order handed to execution
+    OrderSubmitted { order: Order },
+    /// Entry order filled
+    EntryFilled { fill: Fill },
+    /// Open position marked to the mid price, at most once per second
+    PositionPnl {
+        symbol: Symbol,
+        side: PositionSide,
+        size: Decimal,
+        entry_price: Decimal,
+        mark_price: Decimal,
+        pnl_percent: f64,
+        roe_percent: f64,
+    },
+    /// Position closed, with the journal's trade record
+    TradeClosed { trade: TradeRecord },
+    /// Strategy state machine moved (Idle, OrderPending, PositionOpen, ...)
+    StateChanged {
+        symbol: Option<Symbol>,
+        from: String,
+        to: String,
+    },
+    /// Operator command applied ("KILL SWITCH", "RESUME", ...)
+    ControlCommand { command: String },
+}
+
+impl EventKind {
+    /// The `type` tag, e.g. "trade_closed"
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::TickStats { .. } => "tick_stats",
+            EventKind::Signal { .. } => "signal",
+            EventKind::OrderSubmitted { .. } => "order_submitted",
+            EventKind::EntryFilled { .. } => "entry_filled",
+            EventKind::PositionPnl { .. } => "position_pnl",
+            EventKind::TradeClosed { .. } => "trade_closed",
+            EventKind::StateChanged { .. } => "state_changed",
+            EventKind::ControlCommand { .. } => "control_command",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotEvent {
+    pub schema: u32,
+    /// Which bot sent it (BOT_ID), for several bots on one bus
+    pub bot_id: String,
+    pub at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+impl BotEvent {
+    /// The JSON payload for outbound integrations, credentials redacted
+    /// (reject reasons and control commands can echo one)
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self).map(|json| redact(&json).into_owned())
+    }
+}
+
+/// Broadcast handle; clones share the same bus
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<BotEvent>,
+    bot_id: String,
+}
+
+impl EventBus {
+    pub fn new(bot_id: impl Into<String>) -> Self {
+        let (tx, _) = broadcast::channel(EVENT_BUFFER);
+        Self {
+            tx,
+            bot_id: bot_id.into(),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<BotEvent> {
+        self.tx.subscribe()
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 0
+    }
+
+    pub fn publish(&self, kind: EventKind) {
+        if self.has_subscribers() {
+            let _ = self.tx.send(BotEvent {
+                schema: EVENT_SCHEMA_VERSION,
+                bot_id: self.bot_id.clone(),
+                at: Utc::now(),
+                kind,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_are_tagged_json_and_skip_without_subscribers() {
+        let bus = EventBus::new("bot-a");
+        bus.publish(EventKind::ControlCommand { command: "PAUSE".to_string() });
+
+        let mut rx = bus.subscribe();
+        bus.publish(EventKind::StateChanged {
+            symbol: Some(Symbol::from("BTCUSDT")),
+            from: "Idle".to_string(),
+            to: "OrderPending".to_string(),
+        });
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.kind.name(), "state_changed");
+        let json: serde_json::Value = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "state_changed");
+        assert_eq!(json["to"], "OrderPending");
+        assert!(json["at"].is_string());
+        // Envelope is part of the published schema
+        assert_eq!(json["schema"], EVENT_SCHEMA_VERSION);
+        assert_eq!(json["bot_id"], "bot-a");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_event_payloads_are_redacted() {
+        crate::redact::register("evtS3cretToken");
+        let bus = EventBus::new("bot-a");
+        let mut rx = bus.subscribe();
+        bus.publish(EventKind::ControlCommand {
+            command: "LOGIN evtS3cretToken".to_string(),
+        });
+
+        let json = rx.try_recv().unwrap().to_json().unwrap();
+        assert!(json.contains("LOGIN [REDACTED]"), "{}", json);
+        assert!(!json.contains("evtS3cretToken"));
+    }
+}
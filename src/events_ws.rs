@@ -0,0 +1,158 @@
+//! Live Event WebSocket
+//!
+//! Streams every domain event (`events::BotEvent`, same JSON as Redis/NATS/Kafka)
+//! to WebSocket clients such as dashboards and mobile apps, one text frame per
+//! event. Query parameters:
+//!
+//! - `token` — required when EVENTS_WS_TOKEN is set (browsers can't set headers)
+//! - `types` — comma-separated event types to receive, e.g. `types=signal,trade_closed`
+//!
+//! Read-only: messages from clients are ignored.
+
+use crate::events::{BotEvent, EventBus};
+use crate::http::token_matches;
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{interval, Duration};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+/// Keeps idle connections alive through proxies
+const PING_INTERVAL_SECS: u64 = 30;
+
+/// What a client asked for in the connection URL
+#[derive(Debug, Default, PartialEq)]
+struct ClientParams {
+    token: Option<String>,
+    /// None = every event type
+    types: Option<HashSet<String>>,
+}
+
+impl ClientParams {
+    fn from_query(query: Option<&str>) -> Self {
+        let mut params = Self::default();
+        for pair in query.unwrap_or("").split('&') {
+            match pair.split_once('=') {
+                Some(("token", value)) => params.token = Some(value.to_string()),
+                Some(("types", value)) => {
+                    params.types = Some(
+                        value
+                            .split(',')
+                            .map(|t| t.trim().to_lowercase())
+                            .filter(|t| !t.is_empty())
+                            .collect(),
+                    )
+                }
+                _ => {}
+            }
+        }
+        params
+    }
+
+    fn wants(&self, event: &BotEvent) -> bool {
+        self.types.as_ref().is_none_or(|types| types.contains(event.kind.name()))
+    }
+}
+
+/// Serve the event stream until the listener fails
+pub async fn serve(addr: SocketAddr, token: Option<Arc<str>>, events: EventBus) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("🛰️ Event WebSocket listening on ws://{}", addr);
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let token = token.clone();
+        let events = events.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(stream, peer, token, events).await {
+                debug!("Event WebSocket client {} ended: {}", peer, e);
+            }
+        });
+    }
+}
+
+async fn handle_client(stream: TcpStream, peer: SocketAddr, token: Option<Arc<str>>, events: EventBus) -> Result<()> {
+    let mut params = ClientParams::default();
+    // The Err type is fixed by tungstenite's handshake callback
+    #[allow(clippy::result_large_err)]
+    let callback = |request: &Request, response: Response| {
+        params = ClientParams::from_query(request.uri().query());
+        let authorized = match token.as_deref() {
+            Some(expected) => token_matches(expected, params.token.as_deref().unwrap_or("")),
+            None => true,
+        };
+        if authorized {
+            Ok(response)
+        } else {
+            let mut error = ErrorResponse::new(Some("unauthorized".to_string()));
+            *error.status_mut() = StatusCode::UNAUTHORIZED;
+            Err(error)
+        }
+    };
+    let ws = match tokio_tungstenite::accept_hdr_async(stream, callback).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            warn!("🚫 Event WebSocket handshake from {} rejected: {}", peer, e);
+            return Ok(());
+        }
+    };
+
+    // Subscribe only after the handshake so rejected clients never hold the bus
+    let mut rx = events.subscribe();
+    let (mut write, mut read) = ws.split();
+    let mut ping = interval(Duration::from_secs(PING_INTERVAL_SECS));
+    info!("🛰️ Event WebSocket client {} connected (types: {:?})", peer, params.types);
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(event) if params.wants(&event) => {
+                    write.send(Message::Text(event.to_json()?)).await?;
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("⚠️ Event WebSocket client {} fell behind, {} events dropped", peer, missed);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = read.next() => match message {
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+            _ = ping.tick() => {
+                write.send(Message::Ping(Vec::new())).await?;
+            }
+        }
+    }
+
+    info!("🛰️ Event WebSocket client {} disconnected", peer);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventKind;
+
+    #[test]
+    fn test_query_selects_token_and_event_types() {
+        let params = ClientParams::from_query(Some("token=s3cret&types=signal, Trade_Closed"));
+        assert_eq!(params.token.as_deref(), Some("s3cret"));
+
+        let bus = EventBus::new("bot-a");
+        let mut rx = bus.subscribe();
+        bus.publish(EventKind::ControlCommand { command: "PAUSE".to_string() });
+        let event = rx.try_recv().unwrap();
+        assert!(!params.wants(&event));
+        assert!(ClientParams::from_query(None).wants(&event));
+        assert!(ClientParams::from_query(Some("types=control_command")).wants(&event));
+    }
+}
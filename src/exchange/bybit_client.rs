@@ -1,16 +1,29 @@
-use anyhow::{Context, Result};
+use crate::audit::OrderAudit;
+use crate::exchange::error::{BybitError, BybitResult};
+use crate::exchange::faults::{FaultInjector, FaultScenario, RequestFault};
 use hmac::{Hmac, Mac};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, error, warn};
 
 type HmacSha256 = Hmac<Sha256>;
 
 const RECV_WINDOW: &str = "5000";
 
+/// retCode for an orderLinkId that was already used (an earlier attempt reached Bybit)
+const DUPLICATE_ORDER_LINK_ID: i32 = 110072;
+
+/// Most pages of 50 read from an order list (a runaway cursor can't loop forever)
+const MAX_ORDER_PAGES: usize = 20;
+
 /// Round a value to the nearest step (e.g., round 4.977 to step 0.1 = 4.9)
 fn round_to_step(value: Decimal, step: Decimal) -> Decimal {
     if step.is_zero() {
@@ -25,15 +38,23 @@ pub struct BybitClient {
     api_key: String,
     api_secret: String,
     base_url: String,
+    /// Backoff before retry N is `retry_base_delay * 2^N`
+    retry_base_delay: Duration,
+    /// ✅ CHAOS: Faults answered in place of the exchange (FAULT_SCENARIO, None = off)
+    faults: Option<Arc<FaultInjector>>,
+    /// ✅ AUDIT: Order requests and responses (ORDER_AUDIT, None = off)
+    audit: Option<OrderAudit>,
+    /// ✅ ORDER LINKS: orderLinkId prefix and counter (None = orders go out untagged)
+    order_links: Option<(Arc<str>, Arc<AtomicU64>)>,
 }
 
 impl BybitClient {
     pub fn new(api_key: String, api_secret: String, base_url: String) -> Self {
         // HFT-optimized HTTP client
         let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
+            .timeout(Duration::from_secs(10))
             .tcp_nodelay(true) // Disable Nagle's algorithm for low latency
-            .pool_idle_timeout(std::time::Duration::from_secs(90))
+            .pool_idle_timeout(Duration::from_secs(90))
             .pool_max_idle_per_host(10) // Connection pooling
             .build()
             .expect("Failed to create HTTP client");
@@ -43,9 +64,103 @@ impl BybitClient {
             api_key,
             api_secret,
             base_url,
+            retry_base_delay: Duration::from_secs(1),
+            faults: None,
+            audit: None,
+            order_links: None,
         }
     }
 
+    /// Override the retry backoff base (tests use a few milliseconds)
+    pub fn with_retry_base_delay(mut self, delay: Duration) -> Self {
+        self.retry_base_delay = delay;
+        self
+    }
+
+    /// Inject faults from `scenario` into every request (soak tests on testnet/demo)
+    pub fn with_faults(mut self, scenario: FaultScenario) -> Self {
+        self.faults = Some(Arc::new(FaultInjector::new(scenario)));
+        self
+    }
+
+    /// ✅ AUDIT: Record every order request and the exchange's answer (ORDER_AUDIT)
+    pub fn with_audit(mut self, audit: OrderAudit) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// ✅ ORDER LINKS: Tag every order `<prefix><e|c><unix ms>-<seq>` (entry / reduce-only close),
+    /// so a restart can find the orders it sent (see `order_recovery`)
+    pub fn with_order_links(mut self, prefix: impl Into<String>) -> Self {
+        self.order_links = Some((Arc::from(prefix.into()), Arc::new(AtomicU64::new(0))));
+        self
+    }
+
+    fn next_order_link_id(&self, reduce_only: bool) -> Option<String> {
+        let (prefix, seq) = self.order_links.as_ref()?;
+        let kind = if reduce_only { 'c' } else { 'e' };
+        let seq = seq.fetch_add(1, Ordering::Relaxed);
+        Some(format!("{}{}{}-{}", prefix, kind, chrono::Utc::now().timestamp_millis(), seq))
+    }
+
+    /// `send` + `read_api` for order requests, with both ends in the audit log
+    async fn send_order<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        endpoint: &str,
+        body: &str,
+        what: &str,
+    ) -> BybitResult<ApiResponse<T>> {
+        let Some(ref audit) = self.audit else {
+            let response = self.send(request, what).await?;
+            return Self::read_api(response, what).await;
+        };
+        let seq = audit.request(endpoint, body).await;
+        let read = match self.send(request, what).await {
+            Ok(response) => Self::read_body(response).await,
+            Err(e) => Err(e),
+        };
+        match read {
+            Ok((status, raw_body)) => {
+                audit.response(seq, status, &raw_body).await;
+                Self::decode_api(status, raw_body, what)
+            }
+            Err(e) => {
+                audit.failed(seq, &e.to_string()).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Send `request`, unless the fault injector answers in place of the exchange
+    async fn send(&self, request: RequestBuilder, what: &str) -> BybitResult<reqwest::Response> {
+        let Some(ref faults) = self.faults else {
+            return Ok(request.send().await?);
+        };
+        // ✅ LATENCY: Half the simulated round trip on the way out, half on the way back
+        let latency = faults.latency();
+        tokio::time::sleep(latency / 2).await;
+        let result = match faults.roll_request(what) {
+            None => request.send().await.map_err(BybitError::from),
+            Some(RequestFault::ServerError) => Err(BybitError::ServerError {
+                status: 503,
+                msg: format!("injected fault ({})", what),
+            }),
+            Some(RequestFault::Timeout { sent }) => {
+                if sent {
+                    let _ = request.send().await;
+                }
+                tokio::time::sleep(faults.hang()).await;
+                Err(BybitError::Timeout {
+                    msg: format!("injected fault ({})", what),
+                    sent,
+                })
+            }
+        };
+        tokio::time::sleep(latency - latency / 2).await;
+        result
+    }
+
     /// Generate Bybit V5 API signature
     /// Formula: timestamp + api_key + recv_window + params
     fn sign(&self, timestamp: i64, recv_window: &str, params: &str) -> String {
@@ -60,99 +175,149 @@ impl BybitClient {
         hex::encode(mac.finalize().into_bytes())
     }
 
+    /// Check HTTP status, decode the V5 envelope and classify a non-zero retCode
+    async fn read_api<T: DeserializeOwned>(response: reqwest::Response, what: &str) -> BybitResult<ApiResponse<T>> {
+        let (status, body) = Self::read_body(response).await?;
+        Self::decode_api(status, body, what)
+    }
+
+    /// Status and body (an error page whose body can't be read counts as empty)
+    async fn read_body(response: reqwest::Response) -> BybitResult<(u16, String)> {
+        let status = response.status();
+        match response.text().await {
+            Ok(body) => Ok((status.as_u16(), body)),
+            Err(_) if !status.is_success() => Ok((status.as_u16(), String::new())),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn decode_api<T: DeserializeOwned>(status: u16, raw_body: String, what: &str) -> BybitResult<ApiResponse<T>> {
+        if !(200..300).contains(&status) {
+            return Err(BybitError::from_http(status, raw_body));
+        }
+
+        let decode_err = |e: serde_json::Error| BybitError::Decode(format!("{} response: {} ({})", what, e, raw_body));
+
+        // Error responses carry `"result": {}`, so check retCode before decoding the payload
+        let data: ApiResponse<serde_json::Value> = serde_json::from_str(&raw_body).map_err(decode_err)?;
+        if data.ret_code != 0 {
+            return Err(BybitError::from_ret_code(data.ret_code, data.ret_msg));
+        }
+
+        Ok(ApiResponse {
+            ret_code: data.ret_code,
+            ret_msg: data.ret_msg,
+            result: serde_json::from_value(data.result).map_err(decode_err)?,
+        })
+    }
+
     /// Public endpoint - no authentication required
-    pub async fn get_tickers(&self, category: &str) -> Result<TickersResponse> {
+    pub async fn get_tickers(&self, category: &str) -> BybitResult<TickersResponse> {
         let url = format!("{}/v5/market/tickers", self.base_url);
 
         let mut retries = 0;
         let max_retries = 3;
 
         loop {
-            match self
+            let request = self
                 .client
                 .get(&url)
-                .query(&[("category", category)])
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        let data: ApiResponse<TickersResponse> = response
-                            .json()
-                            .await
-                            .context("Failed to parse tickers response")?;
-
-                        if data.ret_code == 0 {
-                            return Ok(data.result);
-                        } else {
-                            anyhow::bail!("API error: {} - {}", data.ret_code, data.ret_msg);
-                        }
-                    } else if response.status().as_u16() >= 500 && retries < max_retries {
-                        retries += 1;
-                        error!(
-                            "Server error {}, retry {}/{}",
-                            response.status(),
-                            retries,
-                            max_retries
-                        );
-                        tokio::time::sleep(tokio::time::Duration::from_secs(2u64.pow(retries)))
-                            .await;
-                        continue;
-                    } else {
-                        let status = response.status();
-                        let body = response.text().await.unwrap_or_default();
-                        anyhow::bail!("HTTP error {}: {}", status, body);
-                    }
-                }
-                Err(e) if retries < max_retries => {
+                .query(&[("category", category)]);
+            let result = match self.send(request, "tickers").await {
+                Ok(response) => Self::read_api::<TickersResponse>(response, "tickers").await,
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(data) => return Ok(data.result),
+                Err(e) if e.is_retryable() && retries < max_retries => {
                     retries += 1;
-                    error!("Request error: {}, retry {}/{}", e, retries, max_retries);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2u64.pow(retries))).await;
+                    error!("Tickers request failed: {}, retry {}/{}", e, retries, max_retries);
+                    tokio::time::sleep(self.retry_base_delay * 2u32.pow(retries)).await;
                 }
-                Err(e) => return Err(e.into()),
+                Err(e) => return Err(e),
             }
         }
     }
 
+    /// GET /v5/market/recent-trade
+    /// Latest public trades, newest first (linear allows up to 1000)
+    pub async fn get_recent_trades(&self, symbol: &str, limit: usize) -> BybitResult<Vec<RecentTrade>> {
+        let url = format!("{}/v5/market/recent-trade", self.base_url);
+        let limit = limit.clamp(1, 1000).to_string();
+
+        let request = self
+            .client
+            .get(&url)
+            .query(&[("category", "linear"), ("symbol", symbol), ("limit", limit.as_str())]);
+        let response = self.send(request, "recent-trade").await?;
+
+        let data = Self::read_api::<RecentTradesResponse>(response, "recent-trade").await?;
+        Ok(data.result.list)
+    }
+
+    /// GET /v5/market/orderbook
+    /// Best bid and ask right now (limit = 1 level per side)
+    pub async fn get_top_of_book(&self, symbol: &str) -> BybitResult<(Decimal, Decimal)> {
+        let url = format!("{}/v5/market/orderbook", self.base_url);
+
+        let request = self
+            .client
+            .get(&url)
+            .query(&[("category", "linear"), ("symbol", symbol), ("limit", "1")]);
+        let response = self.send(request, "orderbook").await?;
+
+        let data = Self::read_api::<OrderBookResponse>(response, "orderbook").await?;
+        let best = |levels: &[[String; 2]]| levels.first().and_then(|[price, _]| price.parse::<Decimal>().ok());
+        match (best(&data.result.b), best(&data.result.a)) {
+            (Some(bid), Some(ask)) => Ok((bid, ask)),
+            _ => Err(BybitError::NotFound(format!("top of book for {}", symbol))),
+        }
+    }
+
+    /// GET /v5/market/kline
+    /// Latest klines, newest first (`interval` in Bybit notation: "1", "5", "60", "D", ...; max 1000)
+    pub async fn get_klines(&self, symbol: &str, interval: &str, limit: usize) -> BybitResult<Vec<Kline>> {
+        let url = format!("{}/v5/market/kline", self.base_url);
+        let limit = limit.clamp(1, 1000).to_string();
+
+        let request = self
+            .client
+            .get(&url)
+            .query(&[
+                ("category", "linear"),
+                ("symbol", symbol),
+                ("interval", interval),
+                ("limit", limit.as_str()),
+            ]);
+        let response = self.send(request, "kline").await?;
+
+        let data = Self::read_api::<KlinesResponse>(response, "kline").await?;
+        Ok(data.result.list)
+    }
+
     /// GET /v5/market/instruments-info
     /// Fetch instrument specifications (qtyStep, tickSize, minOrderQty)
-    pub async fn get_instrument_info(&self, symbol: &str) -> Result<InstrumentInfo> {
+    pub async fn get_instrument_info(&self, symbol: &str) -> BybitResult<InstrumentInfo> {
         let url = format!("{}/v5/market/instruments-info", self.base_url);
 
-        let response = self
+        let request = self
             .client
             .get(&url)
-            .query(&[("category", "linear"), ("symbol", symbol)])
-            .send()
-            .await
-            .context("Failed to send instruments-info request")?;
-
-        if response.status().is_success() {
-            let data: ApiResponse<InstrumentsResponse> = response
-                .json()
-                .await
-                .context("Failed to parse instruments-info response")?;
-
-            if data.ret_code == 0 {
-                if let Some(instrument) = data.result.list.into_iter().next() {
-                    return Ok(instrument);
-                } else {
-                    anyhow::bail!("No instrument info found for {}", symbol);
-                }
-            } else {
-                anyhow::bail!("API error: {} - {}", data.ret_code, data.ret_msg);
-            }
-        } else {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("HTTP error {}: {}", status, body);
-        }
+            .query(&[("category", "linear"), ("symbol", symbol)]);
+        let response = self.send(request, "instruments-info").await?;
+
+        let data = Self::read_api::<InstrumentsResponse>(response, "instruments-info").await?;
+        data.result
+            .list
+            .into_iter()
+            .next()
+            .ok_or_else(|| BybitError::NotFound(format!("instrument info for {}", symbol)))
     }
 
     /// POST /v5/order/create
     /// CRITICAL: For POST requests, the signature MUST be calculated on the EXACT JSON body sent
-    pub async fn place_order(&self, order: &crate::models::Order) -> Result<PlaceOrderResponse> {
-        let timestamp = chrono::Utc::now().timestamp_millis();
+    pub async fn place_order(&self, order: &crate::models::Order) -> BybitResult<PlaceOrderResponse> {
         let url = format!("{}/v5/order/create", self.base_url);
 
         // Round qty based on instrument's qtyStep, fallback to 2 decimals
@@ -170,6 +335,7 @@ impl BybitClient {
             "orderType": format!("{:?}", order.order_type),
             "qty": qty_rounded.to_string(),
             "timeInForce": format!("{:?}", order.time_in_force),
+            "positionIdx": order.position_idx,
         });
 
         // Add optional fields - round price based on instrument's tickSize
@@ -186,24 +352,30 @@ impl BybitClient {
             payload["reduceOnly"] = json!(true);
         }
 
-        // Serialize to string ONCE - this exact string will be signed and sent
-        let payload_str = serde_json::to_string(&payload)
-            .context("Failed to serialize order payload")?;
+        // Same id on every retry: Bybit refuses a second order under it
+        let link_id = self.next_order_link_id(order.reduce_only);
+        if let Some(ref link_id) = link_id {
+            payload["orderLinkId"] = json!(link_id);
+        }
 
-        // Generate signature on the EXACT payload
-        let signature = self.sign(timestamp, RECV_WINDOW, &payload_str);
+        // Serialize to string ONCE - this exact string will be signed and sent
+        let payload_str = payload.to_string();
 
         debug!(
             "Placing order: {:?} {} {} @ {:?}",
             order.side, order.qty, order.symbol, order.price
         );
 
-        // Send request with exponential backoff retry
+        // Send request with exponential backoff retry (transient errors only)
         let mut retries = 0;
         let max_retries = 3;
 
         loop {
-            let response = self
+            // Fresh timestamp per attempt: backoff sleeps would push a stale one past recv_window
+            let timestamp = chrono::Utc::now().timestamp_millis();
+            let signature = self.sign(timestamp, RECV_WINDOW, &payload_str);
+
+            let request = self
                 .client
                 .post(&url)
                 .header("X-BAPI-API-KEY", &self.api_key)
@@ -211,53 +383,34 @@ impl BybitClient {
                 .header("X-BAPI-SIGN", &signature)
                 .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
                 .header("Content-Type", "application/json")
-                .body(payload_str.clone()) // Send the EXACT signed string
-                .send()
+                .body(payload_str.clone()); // Send the EXACT signed string
+            let result = self
+                .send_order::<PlaceOrderResponse>(request, "/v5/order/create", &payload_str, "order")
                 .await;
 
-            match response {
-                Ok(resp) if resp.status().is_success() => {
-                    let raw_body = resp.text().await.context("Failed to read response body")?;
-                    debug!("Raw order response: {}", raw_body);
-                    
-                    let data: ApiResponse<PlaceOrderResponse> = serde_json::from_str(&raw_body)
-                        .context(format!("Failed to parse order response: {}", raw_body))?;
-
-                    if data.ret_code == 0 {
-                        debug!("Order placed successfully: {}", data.result.order_id);
-                        return Ok(data.result);
-                    } else {
-                        anyhow::bail!(
-                            "Order placement failed: {} - {}",
-                            data.ret_code,
-                            data.ret_msg
-                        );
-                    }
+            match result {
+                Ok(data) => {
+                    debug!("Order placed successfully: {}", data.result.order_id);
+                    return Ok(data.result);
                 }
-                Ok(resp) if resp.status().as_u16() >= 500 && retries < max_retries => {
+                // Without an orderLinkId Bybit can't refuse a repeat, so only what it never
+                // acted on is resent
+                Err(e) if retries < max_retries && if link_id.is_some() { e.is_retryable() } else { e.is_unsent() } => {
                     retries += 1;
-                    warn!(
-                        "Server error {}, retry {}/{}",
-                        resp.status(),
-                        retries,
-                        max_retries
-                    );
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2u64.pow(retries))).await;
-                    continue;
+                    warn!("Order request failed: {}, retry {}/{}", e, retries, max_retries);
+                    tokio::time::sleep(self.retry_base_delay * 2u32.pow(retries)).await;
                 }
-                Ok(resp) => {
-                    let status = resp.status();
-                    let body = resp.text().await.unwrap_or_default();
-                    anyhow::bail!("Order failed with HTTP {}: {}", status, body);
-                }
-                Err(e) if retries < max_retries => {
-                    retries += 1;
-                    warn!("Request error: {}, retry {}/{}", e, retries, max_retries);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2u64.pow(retries))).await;
-                }
-                Err(e) => {
-                    return Err(e).context("Failed to send order request");
+                // An attempt that looked failed did place the order: answer with that one
+                Err(e) if retries > 0 && e.ret_code() == Some(DUPLICATE_ORDER_LINK_ID) => {
+                    let link_id = link_id.as_deref().unwrap_or_default();
+                    warn!("Order {} was placed by an earlier attempt, looking it up", link_id);
+                    let placed = self.get_order_by_link_id(&order.symbol.0, link_id).await?;
+                    return Ok(PlaceOrderResponse {
+                        order_id: placed.order_id,
+                        order_link_id: placed.order_link_id,
+                    });
                 }
+                Err(e) => return Err(e),
             }
         }
     }
@@ -265,7 +418,7 @@ impl BybitClient {
     /// GET /v5/position/list
     /// CRITICAL: For GET requests, the signature MUST be calculated on the QUERY STRING
     /// Format: category=linear&symbol=BTCUSDT (NOT JSON!)
-    pub async fn get_position(&self, symbol: &str) -> Result<Vec<PositionInfo>> {
+    pub async fn get_position(&self, symbol: &str) -> BybitResult<Vec<PositionInfo>> {
         let timestamp = chrono::Utc::now().timestamp_millis();
         let url = format!("{}/v5/position/list", self.base_url);
 
@@ -278,53 +431,184 @@ impl BybitClient {
 
         debug!("Getting position for {}", symbol);
 
-        let response = self
+        let request = self
             .client
             .get(&url)
             .header("X-BAPI-API-KEY", &self.api_key)
             .header("X-BAPI-TIMESTAMP", timestamp.to_string())
             .header("X-BAPI-SIGN", &signature)
             .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
-            .query(&[("category", "linear"), ("symbol", symbol)]) // Same query params as signature
-            .send()
-            .await;
-
-        match response {
-            Ok(resp) if resp.status().is_success() => {
-                let data: ApiResponse<PositionListResponse> = resp
-                    .json()
-                    .await
-                    .context("Failed to parse position response")?;
-
-                if data.ret_code == 0 {
-                    debug!("Got {} positions for {}", data.result.list.len(), symbol);
-                    Ok(data.result.list)
-                } else {
-                    // Not an error - just no position
-                    debug!(
-                        "Get position returned: {} - {}",
-                        data.ret_code, data.ret_msg
-                    );
-                    Ok(vec![])
-                }
-            }
-            Ok(resp) => {
-                let status = resp.status();
-                let body = resp.text().await.unwrap_or_default();
-                warn!("Get position failed with HTTP {}: {}", status, body);
-                Ok(vec![]) // Return empty instead of error
+            .query(&[("category", "linear"), ("symbol", symbol)]); // Same query params as signature
+        let response = self.send(request, "position").await;
+
+        let result = match response {
+            Ok(resp) => Self::read_api::<PositionListResponse>(resp, "position").await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(data) => {
+                debug!("Got {} positions for {}", data.result.list.len(), symbol);
+                Ok(match self.faults.as_ref() {
+                    Some(faults) => faults.positions(symbol, data.result.list),
+                    None => data.result.list,
+                })
             }
+            // Not "no position": callers must not close or adopt on a failed query
             Err(e) => {
-                warn!("Get position request failed: {}", e);
-                Ok(vec![]) // Return empty instead of error
+                warn!("Get position failed: {}", e);
+                Err(e)
             }
         }
     }
 
+    /// GET /v5/position/list for every USDT-settled symbol
+    /// Failures are errors: callers must not read "no positions" into them
+    pub async fn get_open_positions(&self) -> BybitResult<Vec<PositionInfo>> {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let url = format!("{}/v5/position/list", self.base_url);
+
+        let query_string = "category=linear&settleCoin=USDT";
+        let signature = self.sign(timestamp, RECV_WINDOW, query_string);
+
+        let request = self
+            .client
+            .get(&url)
+            .header("X-BAPI-API-KEY", &self.api_key)
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-SIGN", &signature)
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
+            .query(&[("category", "linear"), ("settleCoin", "USDT")]);
+        let response = self.send(request, "position").await?;
+
+        let data = Self::read_api::<PositionListResponse>(response, "position").await?;
+        Ok(match self.faults.as_ref() {
+            Some(faults) => faults.positions("settleCoin=USDT", data.result.list),
+            None => data.result.list,
+        })
+    }
+
+    /// GET /v5/account/wallet-balance (Unified Trading Account totals)
+    pub async fn get_wallet_balance(&self) -> BybitResult<WalletBalance> {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let url = format!("{}/v5/account/wallet-balance", self.base_url);
+
+        let query_string = "accountType=UNIFIED";
+        let signature = self.sign(timestamp, RECV_WINDOW, query_string);
+
+        let request = self
+            .client
+            .get(&url)
+            .header("X-BAPI-API-KEY", &self.api_key)
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-SIGN", &signature)
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
+            .query(&[("accountType", "UNIFIED")]);
+        let response = self.send(request, "wallet-balance").await?;
+
+        let data = Self::read_api::<WalletBalanceResponse>(response, "wallet-balance").await?;
+        data.result
+            .list
+            .into_iter()
+            .next()
+            .ok_or_else(|| BybitError::NotFound("unified wallet balance".to_string()))
+    }
+
+    /// POST /v5/position/set-leverage (same leverage for both sides)
+    /// "Leverage not modified" (110043) counts as success
+    pub async fn set_leverage(&self, symbol: &str, leverage: &str) -> BybitResult<()> {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let url = format!("{}/v5/position/set-leverage", self.base_url);
+
+        let payload = json!({
+            "category": "linear",
+            "symbol": symbol,
+            "buyLeverage": leverage,
+            "sellLeverage": leverage,
+        });
+
+        let payload_str = payload.to_string();
+        let signature = self.sign(timestamp, RECV_WINDOW, &payload_str);
+
+        let request = self
+            .client
+            .post(&url)
+            .header("X-BAPI-API-KEY", &self.api_key)
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-SIGN", &signature)
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
+            .header("Content-Type", "application/json")
+            .body(payload_str);
+        let response = self.send(request, "set leverage").await?;
+
+        match Self::read_api::<serde_json::Value>(response, "set leverage").await {
+            Ok(_) => Ok(()),
+            Err(e) if e.ret_code() == Some(110043) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// POST /v5/position/trading-stop
+    /// Stop-loss on the whole position, triggered by mark price and closed at market.
+    /// "Not modified" (34040) counts as success
+    pub async fn set_stop_loss(&self, symbol: &str, stop_price: Decimal) -> BybitResult<()> {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let url = format!("{}/v5/position/trading-stop", self.base_url);
+
+        let payload = json!({
+            "category": "linear",
+            "symbol": symbol,
+            "tpslMode": "Full",
+            "stopLoss": stop_price.normalize().to_string(),
+            "slTriggerBy": "MarkPrice",
+            "positionIdx": 0,
+        });
+
+        let payload_str = payload.to_string();
+        let signature = self.sign(timestamp, RECV_WINDOW, &payload_str);
+
+        let request = self
+            .client
+            .post(&url)
+            .header("X-BAPI-API-KEY", &self.api_key)
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-SIGN", &signature)
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
+            .header("Content-Type", "application/json")
+            .body(payload_str);
+        let response = self.send(request, "set stop loss").await?;
+
+        match Self::read_api::<serde_json::Value>(response, "set stop loss").await {
+            Ok(_) => Ok(()),
+            Err(e) if e.ret_code() == Some(34040) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// GET /v5/user/query-api
+    /// Permissions, expiry and account type of the API key in use
+    pub async fn get_api_key_info(&self) -> BybitResult<ApiKeyInfo> {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let url = format!("{}/v5/user/query-api", self.base_url);
+        let signature = self.sign(timestamp, RECV_WINDOW, "");
+
+        let request = self
+            .client
+            .get(&url)
+            .header("X-BAPI-API-KEY", &self.api_key)
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-SIGN", &signature)
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW);
+        let response = self.send(request, "query-api").await?;
+
+        let data = Self::read_api::<ApiKeyInfo>(response, "query-api").await?;
+        Ok(data.result)
+    }
+
     /// GET /v5/order/realtime
     /// Query order status by order ID
     /// Returns order details including status: "New", "PartiallyFilled", "Filled", "Cancelled", "Rejected"
-    pub async fn get_order_status(&self, symbol: &str, order_id: &str) -> Result<OrderStatusResponse> {
+    pub async fn get_order_status(&self, symbol: &str, order_id: &str) -> BybitResult<OrderStatusResponse> {
         let timestamp = chrono::Utc::now().timestamp_millis();
         let url = format!("{}/v5/order/realtime", self.base_url);
 
@@ -336,7 +620,7 @@ impl BybitClient {
 
         debug!("Querying order status for {}", order_id);
 
-        let response = self
+        let request = self
             .client
             .get(&url)
             .header("X-BAPI-API-KEY", &self.api_key)
@@ -347,31 +631,89 @@ impl BybitClient {
                 ("category", "linear"),
                 ("symbol", symbol),
                 ("orderId", order_id),
-            ])
-            .send()
-            .await?;
+            ]);
+        let response = self.send(request, "order status").await?;
+
+        let data = Self::read_api::<OrderStatusListResponse>(response, "order status").await?;
+        let status = data.result
+            .list
+            .into_iter()
+            .next()
+            .ok_or_else(|| BybitError::NotFound(format!("order {}", order_id)))?;
+        Ok(match self.faults.as_ref() {
+            Some(faults) => faults.order_status(status),
+            None => status,
+        })
+    }
 
-        if response.status().is_success() {
-            let data: ApiResponse<OrderStatusListResponse> = response
-                .json()
-                .await
-                .context("Failed to parse order status response")?;
+    /// GET /v5/order/realtime by orderLinkId
+    pub async fn get_order_by_link_id(&self, symbol: &str, link_id: &str) -> BybitResult<OrderStatusResponse> {
+        let query = [("category", "linear"), ("symbol", symbol), ("orderLinkId", link_id)];
+        self.get_orders("/v5/order/realtime", &query, "order status")
+            .await?
+            .list
+            .into_iter()
+            .next()
+            .ok_or_else(|| BybitError::NotFound(format!("order {}", link_id)))
+    }
 
-            if data.ret_code == 0 && !data.result.list.is_empty() {
-                Ok(data.result.list[0].clone())
-            } else {
-                anyhow::bail!("Order not found or API error: {} - {}", data.ret_code, data.ret_msg);
+    /// GET /v5/order/realtime: orders still open on every USDT-settled symbol (all pages)
+    pub async fn get_open_orders(&self) -> BybitResult<Vec<OrderStatusResponse>> {
+        let query = [("category", "linear"), ("settleCoin", "USDT"), ("limit", "50")];
+        self.get_order_pages("/v5/order/realtime", &query, "open orders").await
+    }
+
+    /// GET /v5/order/history: orders that reached a final status (filled, cancelled, ...)
+    /// since `start_ms`, newest first (all pages)
+    pub async fn get_order_history(&self, start_ms: i64) -> BybitResult<Vec<OrderStatusResponse>> {
+        let start = start_ms.to_string();
+        let query = [("category", "linear"), ("settleCoin", "USDT"), ("startTime", start.as_str()), ("limit", "50")];
+        self.get_order_pages("/v5/order/history", &query, "order history").await
+    }
+
+    /// `get_orders` following `nextPageCursor` until the last page (at most MAX_ORDER_PAGES)
+    async fn get_order_pages(&self, endpoint: &str, query: &[(&str, &str)], what: &str) -> BybitResult<Vec<OrderStatusResponse>> {
+        let mut orders = Vec::new();
+        let mut cursor = String::new();
+        for _ in 0..MAX_ORDER_PAGES {
+            let mut page_query = query.to_vec();
+            if !cursor.is_empty() {
+                page_query.push(("cursor", cursor.as_str()));
             }
-        } else {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("Get order status failed: {} - {}", status, body);
+            let page = self.get_orders(endpoint, &page_query, what).await?;
+            orders.extend(page.list);
+            if page.next_page_cursor.is_empty() {
+                return Ok(orders);
+            }
+            cursor = page.next_page_cursor;
         }
+        warn!("{}: stopped after {} pages ({} orders)", what, MAX_ORDER_PAGES, orders.len());
+        Ok(orders)
+    }
+
+    /// Signed GET of an order list; `query` is signed and sent as given
+    /// (Bybit's cursor comes back URL-encoded and must not be encoded again)
+    async fn get_orders(&self, endpoint: &str, query: &[(&str, &str)], what: &str) -> BybitResult<OrderStatusListResponse> {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let query_string = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+        let url = format!("{}{}?{}", self.base_url, endpoint, query_string);
+        let signature = self.sign(timestamp, RECV_WINDOW, &query_string);
+
+        let request = self
+            .client
+            .get(&url)
+            .header("X-BAPI-API-KEY", &self.api_key)
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-SIGN", &signature)
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW);
+        let response = self.send(request, what).await?;
+
+        Ok(Self::read_api::<OrderStatusListResponse>(response, what).await?.result)
     }
 
     /// Cancel a single order by order ID
     /// POST /v5/order/cancel
-    pub async fn cancel_order(&self, symbol: &str, order_id: &str) -> Result<()> {
+    pub async fn cancel_order(&self, symbol: &str, order_id: &str) -> BybitResult<()> {
         let timestamp = chrono::Utc::now().timestamp_millis();
         let url = format!("{}/v5/order/cancel", self.base_url);
 
@@ -381,10 +723,10 @@ impl BybitClient {
             "orderId": order_id,
         });
 
-        let payload_str = serde_json::to_string(&payload)?;
+        let payload_str = payload.to_string();
         let signature = self.sign(timestamp, RECV_WINDOW, &payload_str);
 
-        let response = self
+        let request = self
             .client
             .post(&url)
             .header("X-BAPI-API-KEY", &self.api_key)
@@ -392,30 +734,28 @@ impl BybitClient {
             .header("X-BAPI-SIGN", &signature)
             .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
             .header("Content-Type", "application/json")
-            .body(payload_str)
-            .send()
-            .await?;
+            .body(payload_str.clone());
 
-        if response.status().is_success() {
-            let data: ApiResponse<serde_json::Value> = response.json().await?;
-            if data.ret_code == 0 {
+        match self
+            .send_order::<serde_json::Value>(request, "/v5/order/cancel", &payload_str, "cancel order")
+            .await
+        {
+            Ok(_) => {
                 debug!("Cancelled order {} for {}", order_id, symbol);
                 Ok(())
-            } else {
-                // Order might already be filled/cancelled - not an error
-                warn!("Cancel order response: {} - {}", data.ret_code, data.ret_msg);
+            }
+            // Order might already be filled/cancelled - not an error
+            Err(e) if e.ret_code().is_some() => {
+                warn!("Cancel order response: {}", e);
                 Ok(())
             }
-        } else {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("Cancel order failed: {} - {}", status, body);
+            Err(e) => Err(e),
         }
     }
 
     /// Cancel all orders for a symbol (useful for emergency stops)
     #[allow(dead_code)]
-    pub async fn cancel_all_orders(&self, symbol: &str) -> Result<()> {
+    pub async fn cancel_all_orders(&self, symbol: &str) -> BybitResult<()> {
         let timestamp = chrono::Utc::now().timestamp_millis();
         let url = format!("{}/v5/order/cancel-all", self.base_url);
 
@@ -424,10 +764,10 @@ impl BybitClient {
             "symbol": symbol,
         });
 
-        let payload_str = serde_json::to_string(&payload)?;
+        let payload_str = payload.to_string();
         let signature = self.sign(timestamp, RECV_WINDOW, &payload_str);
 
-        let response = self
+        let request = self
             .client
             .post(&url)
             .header("X-BAPI-API-KEY", &self.api_key)
@@ -435,18 +775,90 @@ impl BybitClient {
             .header("X-BAPI-SIGN", &signature)
             .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
             .header("Content-Type", "application/json")
-            .body(payload_str)
-            .send()
+            .body(payload_str.clone());
+
+        self.send_order::<serde_json::Value>(request, "/v5/order/cancel-all", &payload_str, "cancel all orders")
             .await?;
+        debug!("Cancelled all orders for {}", symbol);
+        Ok(())
+    }
 
-        if response.status().is_success() {
-            debug!("Cancelled all orders for {}", symbol);
-            Ok(())
-        } else {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            anyhow::bail!("Cancel all orders failed: {} - {}", status, body);
+    /// Cancel the plain open orders this client placed on `symbol` (tagged with its
+    /// orderLinkId prefix); conditional orders and the position's TP/SL, like the hard
+    /// stop, stay. Untagged clients can't tell their orders apart: every plain order goes
+    pub async fn cancel_own_orders(&self, symbol: &str) -> BybitResult<()> {
+        let Some((ref prefix, _)) = self.order_links else {
+            return self.cancel_orders_filtered(symbol).await;
+        };
+        let query = [("category", "linear"), ("symbol", symbol), ("orderFilter", "Order"), ("limit", "50")];
+        let open = self.get_order_pages("/v5/order/realtime", &query, "open orders").await?;
+        let mut result = Ok(());
+        for order in open.iter().filter(|order| order.order_link_id.starts_with(&**prefix)) {
+            if let Err(e) = self.cancel_order(symbol, &order.order_id).await {
+                warn!("Failed to cancel {}: {}", order.order_link_id, e);
+                result = Err(e);
+            }
         }
+        result
+    }
+
+    /// POST /v5/order/cancel-all limited to plain orders (orderFilter=Order)
+    async fn cancel_orders_filtered(&self, symbol: &str) -> BybitResult<()> {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let url = format!("{}/v5/order/cancel-all", self.base_url);
+
+        let payload = json!({
+            "category": "linear",
+            "symbol": symbol,
+            "orderFilter": "Order",
+        });
+
+        let payload_str = payload.to_string();
+        let signature = self.sign(timestamp, RECV_WINDOW, &payload_str);
+
+        let request = self
+            .client
+            .post(&url)
+            .header("X-BAPI-API-KEY", &self.api_key)
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-SIGN", &signature)
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
+            .header("Content-Type", "application/json")
+            .body(payload_str.clone());
+
+        self.send_order::<serde_json::Value>(request, "/v5/order/cancel-all", &payload_str, "cancel plain orders")
+            .await?;
+        debug!("Cancelled plain orders for {}", symbol);
+        Ok(())
+    }
+
+    /// POST /v5/account/demo-apply-money
+    /// Demo Trading only: add virtual funds to the demo UTA wallet
+    pub async fn demo_apply_money(&self, coin: &str, amount: &str) -> BybitResult<()> {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let url = format!("{}/v5/account/demo-apply-money", self.base_url);
+
+        let payload = json!({
+            "adjustType": 0,
+            "utaDemoApplyMoney": [{ "coin": coin, "amountStr": amount }],
+        });
+
+        let payload_str = payload.to_string();
+        let signature = self.sign(timestamp, RECV_WINDOW, &payload_str);
+
+        let request = self
+            .client
+            .post(&url)
+            .header("X-BAPI-API-KEY", &self.api_key)
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-SIGN", &signature)
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
+            .header("Content-Type", "application/json")
+            .body(payload_str);
+        let response = self.send(request, "demo apply money").await?;
+
+        Self::read_api::<serde_json::Value>(response, "demo apply money").await?;
+        Ok(())
     }
 }
 
@@ -479,6 +891,73 @@ pub struct TickerInfo {
     pub ask1_size: String,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyInfo {
+    #[serde(default)]
+    pub note: String,
+    /// 0 = read-write, 1 = read-only
+    pub read_only: i32,
+    /// e.g. {"ContractTrade": ["Order", "Position"], "Wallet": ["AccountTransfer"]}
+    #[serde(default)]
+    pub permissions: HashMap<String, Vec<String>>,
+    /// IP whitelist; keys without one expire after 90 days
+    #[serde(default)]
+    pub ips: Vec<String>,
+    /// Days until expiry (only for keys that expire)
+    #[serde(default)]
+    pub deadline_day: i64,
+    /// Expiry timestamp, empty for non-expiring keys
+    #[serde(default)]
+    pub expired_at: String,
+    /// 1 = Unified Trading Account
+    #[serde(default)]
+    pub uta: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecentTradesResponse {
+    pub list: Vec<RecentTrade>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentTrade {
+    pub exec_id: String,
+    pub symbol: String,
+    pub price: String,
+    pub size: String,
+    /// "Buy" / "Sell" (taker side)
+    pub side: String,
+    /// Trade time, ms
+    pub time: String,
+}
+
+/// Levels as [price, size], best first
+#[derive(Debug, Deserialize)]
+pub struct OrderBookResponse {
+    pub b: Vec<[String; 2]>,
+    pub a: Vec<[String; 2]>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KlinesResponse {
+    pub list: Vec<Kline>,
+}
+
+/// One kline; Bybit sends it as an array of strings in this field order
+#[derive(Debug, Deserialize, Clone)]
+pub struct Kline {
+    /// Bar open time, ms
+    pub start_time: String,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: String,
+    pub turnover: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PlaceOrderResponse {
@@ -491,7 +970,7 @@ pub struct PositionListResponse {
     pub list: Vec<PositionInfo>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PositionInfo {
     pub symbol: String,
@@ -499,6 +978,43 @@ pub struct PositionInfo {
     pub size: String,
     pub avg_price: String,
     pub unrealised_pnl: String,
+    /// Leverage set for the symbol (reported even when flat)
+    #[serde(default)]
+    pub leverage: String,
+    /// Estimated liquidation price ("" when there is none)
+    #[serde(default)]
+    pub liq_price: String,
+    /// Realized PnL of the current holding (fees and partial closes)
+    #[serde(default)]
+    pub cur_realised_pnl: String,
+    /// Realized PnL of every position held on the symbol
+    #[serde(default)]
+    pub cum_realised_pnl: String,
+    /// 0 in one-way mode, 1 (long) / 2 (short) in hedge mode
+    #[serde(default)]
+    pub position_idx: u8,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletBalanceResponse {
+    pub list: Vec<WalletBalance>,
+}
+
+/// Account-level totals of the unified wallet, in USD
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletBalance {
+    pub account_type: String,
+    pub total_equity: String,
+    pub total_wallet_balance: String,
+    /// Balance free for new initial margin
+    pub total_available_balance: String,
+    pub total_initial_margin: String,
+    pub total_maintenance_margin: String,
+    #[serde(rename = "accountIMRate")]
+    pub account_im_rate: String,
+    #[serde(rename = "accountMMRate")]
+    pub account_mm_rate: String,
 }
 
 // ✅ Symbol specification types (for dynamic precision)
@@ -531,8 +1047,12 @@ pub struct PriceFilter {
 
 // ✅ Order status types (for order confirmation polling)
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct OrderStatusListResponse {
     pub list: Vec<OrderStatusResponse>,
+    /// Empty on the last page
+    #[serde(default)]
+    pub next_page_cursor: String,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -548,12 +1068,39 @@ pub struct OrderStatusResponse {
     pub qty: String,
     pub cum_exec_qty: String, // Cumulative executed quantity
     pub cum_exec_value: String,
+    #[serde(default)]
+    pub cum_exec_fee: String, // Cumulative fee paid
     pub avg_price: String, // Average fill price
+    #[serde(default)]
+    pub reduce_only: bool,
+    /// Unix ms of the last change (fill, cancel, ...)
+    #[serde(default)]
+    pub updated_time: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        /// Rounded value is on the step grid, never above the input, and less than one step below it
+        #[test]
+        fn prop_round_to_step_stays_on_grid(
+            mantissa in 0i64..1_000_000_000_000,
+            scale in 0u32..9,
+            step_mantissa in 1i64..1000,
+            step_scale in 0u32..9,
+        ) {
+            let value = Decimal::new(mantissa, scale);
+            let step = Decimal::new(step_mantissa, step_scale);
+            let rounded = round_to_step(value, step);
+
+            prop_assert!(rounded <= value);
+            prop_assert!(value - rounded < step);
+            prop_assert!((rounded % step).is_zero());
+        }
+    }
 
     #[test]
     fn test_signature_generation() {
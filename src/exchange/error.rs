@@ -0,0 +1,175 @@
+//! Bybit Error Taxonomy
+//!
+//! Every `BybitClient` failure is classified once, here, from the HTTP status
+//! or the V5 `retCode`, so callers branch on the class (`is_retryable`,
+//! `matches!(e, BybitError::InsufficientBalance { .. })`) instead of parsing
+//! error strings.
+
+use crate::redact::redact;
+use thiserror::Error;
+
+pub type BybitResult<T> = std::result::Result<T, BybitError>;
+
+#[derive(Debug, Error)]
+pub enum BybitError {
+    /// Bad key/signature, missing permission, IP not whitelisted, expired key
+    #[error("authentication failed ({code}): {msg}")]
+    Auth { code: i32, msg: String },
+
+    /// retCode rate limit or HTTP 403/429 (`code` is None for HTTP-level limits)
+    #[error("rate limited ({}): {msg}", code.map_or_else(|| "HTTP".to_string(), |c| c.to_string()))]
+    RateLimited { code: Option<i32>, msg: String },
+
+    #[error("insufficient balance ({code}): {msg}")]
+    InsufficientBalance { code: i32, msg: String },
+
+    /// Exchange rejected the parameters (qty/price out of range, reduce-only on a flat position, ...)
+    #[error("invalid order ({code}): {msg}")]
+    InvalidOrder { code: i32, msg: String },
+
+    /// Request timestamp outside recv_window (local clock drift or a slow request)
+    #[error("timestamp out of recv_window ({code}): {msg}")]
+    Timestamp { code: i32, msg: String },
+
+    /// HTTP 5xx or an internal-error retCode
+    #[error("server error ({status}): {msg}")]
+    ServerError { status: u16, msg: String },
+
+    /// Any other non-zero retCode
+    #[error("API error ({code}): {msg}")]
+    Api { code: i32, msg: String },
+
+    /// Any other non-success HTTP status
+    #[error("HTTP {status}: {body}")]
+    Http { status: u16, body: String },
+
+    /// Connect/timeout/transport failure (request may or may not have reached Bybit)
+    #[error("network error: {0}")]
+    Network(#[from] reqwest::Error),
+
+    /// No answer in time from outside reqwest (injected faults); `sent` = the request reached Bybit
+    #[error("request timed out{}: {msg}", if *sent { " after it was sent" } else { "" })]
+    Timeout { msg: String, sent: bool },
+
+    #[error("failed to decode response: {0}")]
+    Decode(String),
+
+    /// Request succeeded but the requested object does not exist
+    #[error("not found: {0}")]
+    NotFound(String),
+}
+
+impl BybitError {
+    /// Classify a non-zero V5 retCode
+    pub fn from_ret_code(code: i32, msg: impl Into<String>) -> Self {
+        let msg = msg.into();
+        match code {
+            10003 | 10004 | 10005 | 10007 | 10009 | 10010 | 33004 => BybitError::Auth { code, msg },
+            10006 | 10018 => BybitError::RateLimited { code: Some(code), msg },
+            10002 => BybitError::Timestamp { code, msg },
+            10000 | 10016 => BybitError::ServerError { status: 200, msg: format!("{} - {}", code, msg) },
+            110004 | 110007 | 110012 | 110044 | 110045 | 110052 => {
+                BybitError::InsufficientBalance { code, msg }
+            }
+            10001 | 110003 | 110013 | 110017 | 110094 => BybitError::InvalidOrder { code, msg },
+            _ => BybitError::Api { code, msg },
+        }
+    }
+
+    /// Classify a non-success HTTP status
+    pub fn from_http(status: u16, body: impl Into<String>) -> Self {
+        // Error pages of proxies and gateways may echo the request back
+        let body = redact(&body.into()).into_owned();
+        match status {
+            // Bybit answers IP-level rate limits with 403 "access too frequent"
+            403 | 429 => BybitError::RateLimited { code: None, msg: body },
+            401 => BybitError::Auth { code: 401, msg: body },
+            500..=599 => BybitError::ServerError { status, msg: body },
+            _ => BybitError::Http { status, body },
+        }
+    }
+
+    /// Transient failure: the same request may succeed if retried after a backoff
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            BybitError::RateLimited { .. }
+            | BybitError::Timestamp { .. }
+            | BybitError::ServerError { .. } => true,
+            // Bybit may have acted on it: a resend could place the order twice
+            BybitError::Timeout { sent, .. } => !sent,
+            BybitError::Network(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            _ => false,
+        }
+    }
+
+    /// Refused before Bybit acted on the request: resending can't place an order twice.
+    /// Timeouts and server errors may come after the order was taken
+    pub fn is_unsent(&self) -> bool {
+        match self {
+            BybitError::RateLimited { .. } | BybitError::Timestamp { .. } => true,
+            BybitError::Network(e) => e.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Rejected because qty/price do not match the instrument's lot/tick size or limits
+    /// (stale cached specs) — refetch instrument info before the next order.
+    /// 10001 is the generic parameter error an off-step qty comes back with
+    pub fn is_precision_rejection(&self) -> bool {
+        matches!(self.ret_code(), Some(10001 | 110003 | 110094 | 170134 | 170137))
+    }
+
+    /// Bybit retCode, if the error came from one
+    pub fn ret_code(&self) -> Option<i32> {
+        match self {
+            BybitError::Auth { code, .. }
+            | BybitError::InsufficientBalance { code, .. }
+            | BybitError::InvalidOrder { code, .. }
+            | BybitError::Timestamp { code, .. }
+            | BybitError::Api { code, .. } => Some(*code),
+            BybitError::RateLimited { code, .. } => *code,
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ret_code_classification() {
+        assert!(matches!(BybitError::from_ret_code(10004, "sign error"), BybitError::Auth { .. }));
+        assert!(matches!(BybitError::from_ret_code(110007, "ab not enough"), BybitError::InsufficientBalance { .. }));
+        assert!(matches!(BybitError::from_ret_code(110017, "reduce-only"), BybitError::InvalidOrder { .. }));
+        assert!(matches!(BybitError::from_ret_code(12345, "?"), BybitError::Api { code: 12345, .. }));
+
+        assert!(BybitError::from_ret_code(10006, "too many visits").is_retryable());
+        assert!(BybitError::from_ret_code(10002, "recv_window").is_retryable());
+        assert!(!BybitError::from_ret_code(110007, "ab not enough").is_retryable());
+        assert!(BybitError::from_ret_code(10006, "too many visits").is_unsent());
+        assert!(!BybitError::from_ret_code(10016, "server error").is_unsent());
+        let timeout = |sent| BybitError::Timeout { msg: "no answer".to_string(), sent };
+        assert!(!timeout(false).is_unsent());
+        assert!(timeout(false).is_retryable());
+        assert!(!timeout(true).is_retryable());
+        assert_eq!(BybitError::from_ret_code(110007, "").ret_code(), Some(110007));
+
+        assert!(BybitError::from_ret_code(10001, "Qty invalid").is_precision_rejection());
+        assert!(BybitError::from_ret_code(170134, "Order price has too many decimals.").is_precision_rejection());
+        assert!(!BybitError::from_ret_code(110017, "reduce-only rule not satisfied").is_precision_rejection());
+        assert!(!BybitError::from_ret_code(110007, "qty exceeds balance").is_precision_rejection());
+        // The code decides, whatever the message says
+        assert!(BybitError::from_ret_code(110094, "Order does not meet minimum order value").is_precision_rejection());
+        assert!(!BybitError::from_ret_code(110001, "order price not found").is_precision_rejection());
+        assert!(!BybitError::from_http(400, "qty step").is_precision_rejection());
+    }
+
+    #[test]
+    fn test_http_classification() {
+        assert!(BybitError::from_http(503, "unavailable").is_retryable());
+        assert!(BybitError::from_http(403, "access too frequent").is_retryable());
+        assert!(!BybitError::from_http(404, "").is_retryable());
+        assert_eq!(BybitError::from_http(429, "").ret_code(), None);
+    }
+}
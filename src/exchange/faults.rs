@@ -0,0 +1,475 @@
+//! Fault Injection
+//!
+//! Chaos mode for soak tests (`FAULT_SCENARIO`, testnet/demo only). Every
+//! `BybitClient` request asks the injector first, which rolls the scenario's
+//! dice and may answer in place of the exchange:
+//!
+//! - `timeout`: the request hangs for `hang_ms`, then fails as a timeout. Half
+//!   the time it was sent anyway, like a real timeout that leaves an order behind
+//!   (that one is not retried: a resend would be the duplicate to look for).
+//! - `5xx`: HTTP 503 instead of the response.
+//! - `stale_position`: a position query gets the previous answer again (a lagging replica).
+//! - `out_of_order`: an order status query gets an older status of the order
+//!   after a newer one was already seen.
+//!
+//! Latency is simulated the same way, so a strategy tuned against instant
+//! answers meets realistic round trips before it meets mainnet:
+//!
+//! - `latency_ms`: every REST call takes this long (`100`, or `50-200` for a
+//!   uniform range), half on the way out and half on the way back.
+//! - `ws_latency_ms`: public and private WebSocket frames are handed on this
+//!   much later than they arrived, still in arrival order.
+//!
+//! The dice are a seeded PRNG, so a run that broke something can be repeated
+//! with the seed from the startup log.
+
+use crate::exchange::{OrderStatusResponse, PositionInfo};
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use tokio::time::{Duration, Instant};
+use tracing::warn;
+
+/// Queries whose last position answer is kept for stale replies
+const MAX_POSITION_QUERIES: usize = 256;
+
+/// Orders whose statuses are kept for out-of-order replies
+const MAX_TRACKED_ORDERS: usize = 1_000;
+
+/// Statuses kept per order (an order rarely goes through more)
+const MAX_STATUSES_PER_ORDER: usize = 8;
+
+/// Fault probabilities per request (0.0 ..= 1.0)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FaultScenario {
+    pub timeout: f64,
+    pub server_error: f64,
+    pub stale_position: f64,
+    pub out_of_order: f64,
+    /// How long an injected timeout hangs before it fails
+    pub hang_ms: u64,
+    /// REST round trip
+    pub latency: Latency,
+    /// WebSocket frame delivery
+    pub ws_latency: Latency,
+    pub seed: u64,
+}
+
+impl FromStr for FaultScenario {
+    type Err = anyhow::Error;
+
+    /// `timeout=0.05,5xx=0.1,stale_position=0.2,out_of_order=0.1[,hang_ms=10000][,latency_ms=50-200][,ws_latency_ms=50-200][,seed=42]`
+    fn from_str(s: &str) -> Result<Self> {
+        let mut scenario = FaultScenario {
+            timeout: 0.0,
+            server_error: 0.0,
+            stale_position: 0.0,
+            out_of_order: 0.0,
+            hang_ms: 10_000,
+            latency: Latency::default(),
+            ws_latency: Latency::default(),
+            seed: time_seed(),
+        };
+        for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .with_context(|| format!("Invalid FAULT_SCENARIO entry '{}', expected key=value", part))?;
+            let value = value.trim();
+            let probability = || -> Result<f64> {
+                let p: f64 = value.parse().with_context(|| format!("Invalid probability '{}' for {}", value, key))?;
+                anyhow::ensure!((0.0..=1.0).contains(&p), "{} must be between 0 and 1, got {}", key, p);
+                Ok(p)
+            };
+            match key.trim().to_lowercase().as_str() {
+                "timeout" => scenario.timeout = probability()?,
+                "5xx" => scenario.server_error = probability()?,
+                "stale_position" => scenario.stale_position = probability()?,
+                "out_of_order" => scenario.out_of_order = probability()?,
+                "hang_ms" => scenario.hang_ms = value.parse().with_context(|| format!("Invalid hang_ms '{}'", value))?,
+                "latency_ms" => scenario.latency = value.parse()?,
+                "ws_latency_ms" => scenario.ws_latency = value.parse()?,
+                "seed" => scenario.seed = value.parse().with_context(|| format!("Invalid seed '{}'", value))?,
+                other => anyhow::bail!(
+                    "Unknown FAULT_SCENARIO fault '{}' (timeout, 5xx, stale_position, out_of_order, hang_ms, latency_ms, ws_latency_ms, seed)",
+                    other
+                ),
+            }
+        }
+        anyhow::ensure!(
+            scenario.timeout + scenario.server_error <= 1.0,
+            "FAULT_SCENARIO timeout + 5xx must not exceed 1"
+        );
+        Ok(scenario)
+    }
+}
+
+impl fmt::Display for FaultScenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "timeout={},5xx={},stale_position={},out_of_order={},hang_ms={},latency_ms={},ws_latency_ms={},seed={}",
+            self.timeout,
+            self.server_error,
+            self.stale_position,
+            self.out_of_order,
+            self.hang_ms,
+            self.latency,
+            self.ws_latency,
+            self.seed
+        )
+    }
+}
+
+impl FaultScenario {
+    /// Holding line for a WebSocket connection's frames (None = delivered as they arrive)
+    pub fn ws_delay<T>(&self) -> Option<DelayLine<T>> {
+        (!self.ws_latency.is_zero()).then(|| DelayLine::new(self.ws_latency, self.seed.wrapping_add(1)))
+    }
+}
+
+/// Simulated network delay, uniform in `min_ms ..= max_ms`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Latency {
+    pub min_ms: u64,
+    pub max_ms: u64,
+}
+
+impl Latency {
+    pub fn is_zero(&self) -> bool {
+        self.max_ms == 0
+    }
+
+    fn sample(&self, rng: &mut SplitMix64) -> Duration {
+        if self.min_ms == self.max_ms {
+            return Duration::from_millis(self.min_ms);
+        }
+        Duration::from_millis(self.min_ms + rng.next_u64() % (self.max_ms - self.min_ms + 1))
+    }
+}
+
+impl FromStr for Latency {
+    type Err = anyhow::Error;
+
+    /// `100` or `50-200`
+    fn from_str(s: &str) -> Result<Self> {
+        let (min, max) = s.split_once('-').unwrap_or((s, s));
+        let parse = |ms: &str| ms.trim().parse::<u64>().with_context(|| format!("Invalid latency '{}', expected ms or min-max", s));
+        let latency = Latency { min_ms: parse(min)?, max_ms: parse(max)? };
+        anyhow::ensure!(latency.min_ms <= latency.max_ms, "Latency range '{}' has min above max", s);
+        Ok(latency)
+    }
+}
+
+impl fmt::Display for Latency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.min_ms == self.max_ms {
+            write!(f, "{}", self.min_ms)
+        } else {
+            write!(f, "{}-{}", self.min_ms, self.max_ms)
+        }
+    }
+}
+
+fn time_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// What a request gets instead of the exchange's answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestFault {
+    /// Hang, then fail as a timeout; `sent` = the request went out first
+    Timeout { sent: bool },
+    ServerError,
+}
+
+/// The scenario's dice plus what was answered before (for stale/out-of-order replies)
+pub struct FaultInjector {
+    scenario: FaultScenario,
+    rng: Mutex<SplitMix64>,
+    /// Last position answer per query
+    positions: Mutex<Bounded<Vec<PositionInfo>>>,
+    /// Statuses seen per order, oldest first
+    orders: Mutex<Bounded<Vec<OrderStatusResponse>>>,
+}
+
+impl FaultInjector {
+    pub fn new(scenario: FaultScenario) -> Self {
+        Self {
+            scenario,
+            rng: Mutex::new(SplitMix64(scenario.seed)),
+            positions: Mutex::new(Bounded::new(MAX_POSITION_QUERIES)),
+            orders: Mutex::new(Bounded::new(MAX_TRACKED_ORDERS)),
+        }
+    }
+
+    pub fn hang(&self) -> Duration {
+        Duration::from_millis(self.scenario.hang_ms)
+    }
+
+    /// Round trip for the next REST call
+    pub fn latency(&self) -> Duration {
+        self.scenario.latency.sample(&mut self.rng.lock())
+    }
+
+    /// Fault for the next request to `endpoint`, if any
+    pub fn roll_request(&self, endpoint: &str) -> Option<RequestFault> {
+        let (roll, coin) = {
+            let mut rng = self.rng.lock();
+            (rng.next_f64(), rng.next_f64())
+        };
+        let fault = if roll < self.scenario.timeout {
+            RequestFault::Timeout { sent: coin < 0.5 }
+        } else if roll < self.scenario.timeout + self.scenario.server_error {
+            RequestFault::ServerError
+        } else {
+            return None;
+        };
+        warn!("🧪 Injected fault on {}: {:?}", endpoint, fault);
+        Some(fault)
+    }
+
+    /// `fresh`, or the previous answer to the same query when the dice say stale
+    pub fn positions(&self, query: &str, fresh: Vec<PositionInfo>) -> Vec<PositionInfo> {
+        let stale = self.rng.lock().next_f64() < self.scenario.stale_position;
+        let mut positions = self.positions.lock();
+        match positions.get(query) {
+            // The stale answer stays the remembered one: a replica lags until it catches up
+            Some(previous) if stale => {
+                warn!("🧪 Injected stale position answer for {}", query);
+                previous.clone()
+            }
+            _ => {
+                *positions.entry(query) = fresh.clone();
+                fresh
+            }
+        }
+    }
+
+    /// `fresh`, or an older status of the same order when the dice say out of order
+    pub fn order_status(&self, fresh: OrderStatusResponse) -> OrderStatusResponse {
+        let roll = {
+            let mut rng = self.rng.lock();
+            (rng.next_f64() < self.scenario.out_of_order).then(|| rng.next_u64())
+        };
+        let mut orders = self.orders.lock();
+        let seen = orders.entry(&fresh.order_id);
+        let older = seen.iter().filter(|status| status.order_status != fresh.order_status).count();
+        if seen.len() == MAX_STATUSES_PER_ORDER {
+            seen.remove(0);
+        }
+        seen.push(fresh.clone());
+        match roll {
+            Some(pick) if older > 0 => {
+                let stale = seen
+                    .iter()
+                    .filter(|status| status.order_status != fresh.order_status)
+                    .nth((pick % older as u64) as usize)
+                    .cloned()
+                    .expect("counted above");
+                warn!(
+                    "🧪 Injected out-of-order status for {}: {} after {}",
+                    fresh.order_id, stale.order_status, fresh.order_status
+                );
+                stale
+            }
+            _ => fresh,
+        }
+    }
+}
+
+/// Map that forgets its oldest key past `capacity`
+struct Bounded<V> {
+    map: HashMap<String, V>,
+    /// Keys, oldest first
+    keys: VecDeque<String>,
+    capacity: usize,
+}
+
+impl<V: Default> Bounded<V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            map: HashMap::new(),
+            keys: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&V> {
+        self.map.get(key)
+    }
+
+    fn entry(&mut self, key: &str) -> &mut V {
+        if !self.map.contains_key(key) {
+            if self.keys.len() == self.capacity {
+                if let Some(oldest) = self.keys.pop_front() {
+                    self.map.remove(&oldest);
+                }
+            }
+            self.keys.push_back(key.to_string());
+        }
+        self.map.entry(key.to_string()).or_default()
+    }
+}
+
+/// WebSocket frames held back by a simulated delivery latency
+pub struct DelayLine<T> {
+    latency: Latency,
+    rng: SplitMix64,
+    /// (due, frame), in arrival order
+    queue: VecDeque<(Instant, T)>,
+}
+
+impl<T> DelayLine<T> {
+    pub fn new(latency: Latency, seed: u64) -> Self {
+        Self {
+            latency,
+            rng: SplitMix64(seed),
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, frame: T) {
+        let mut due = Instant::now() + self.latency.sample(&mut self.rng);
+        // One TCP stream: a frame never overtakes the one before it
+        if let Some((previous, _)) = self.queue.back() {
+            due = due.max(*previous);
+        }
+        self.queue.push_back((due, frame));
+    }
+
+    /// When the oldest held frame is due
+    pub fn next_due(&self) -> Option<Instant> {
+        self.queue.front().map(|(due, _)| *due)
+    }
+
+    /// The oldest frame, once it's due
+    pub fn pop_due(&mut self) -> Option<T> {
+        match self.queue.front() {
+            Some((due, _)) if *due <= Instant::now() => self.queue.pop_front().map(|(_, frame)| frame),
+            _ => None,
+        }
+    }
+}
+
+/// Small seeded PRNG (SplitMix64); the dice don't need more
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(order_status: &str) -> OrderStatusResponse {
+        serde_json::from_value(serde_json::json!({
+            "orderId": "o-1", "orderLinkId": "", "symbol": "BTCUSDT", "orderStatus": order_status,
+            "orderType": "Market", "side": "Buy", "price": "0", "qty": "1", "cumExecQty": "0",
+            "cumExecValue": "0", "cumExecFee": "0", "avgPrice": "0"
+        }))
+        .unwrap()
+    }
+
+    fn position(size: &str) -> PositionInfo {
+        serde_json::from_value(serde_json::json!({
+            "symbol": "BTCUSDT", "side": "Buy", "size": size, "avgPrice": "100", "unrealisedPnl": "0"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_scenario_parses_and_rejects_bad_entries() {
+        let scenario: FaultScenario = "timeout=0.05, 5xx=0.1,stale_position=0.2,out_of_order=1,hang_ms=50,seed=7".parse().unwrap();
+        assert_eq!((scenario.timeout, scenario.server_error), (0.05, 0.1));
+        assert_eq!((scenario.stale_position, scenario.out_of_order), (0.2, 1.0));
+        assert_eq!((scenario.hang_ms, scenario.seed), (50, 7));
+        assert_eq!(scenario.to_string().parse::<FaultScenario>().unwrap(), scenario);
+
+        assert!("timeout=1.5".parse::<FaultScenario>().is_err());
+        assert!("timeout=0.6,5xx=0.6".parse::<FaultScenario>().is_err());
+        assert!("latency=0.1".parse::<FaultScenario>().is_err());
+        assert!("timeout".parse::<FaultScenario>().is_err());
+
+        let latency: FaultScenario = "latency_ms=50-200,ws_latency_ms=100".parse().unwrap();
+        assert_eq!(latency.latency, Latency { min_ms: 50, max_ms: 200 });
+        assert_eq!(latency.ws_latency, Latency { min_ms: 100, max_ms: 100 });
+        assert_eq!(latency.to_string().parse::<FaultScenario>().unwrap(), latency);
+        assert!("latency_ms=200-50".parse::<FaultScenario>().is_err());
+    }
+
+    #[test]
+    fn test_injector_replays_old_answers_and_is_seeded() {
+        let always: FaultScenario = "stale_position=1,out_of_order=1,5xx=0.5,seed=42".parse().unwrap();
+        let injector = FaultInjector::new(always);
+
+        // Nothing older to return yet: the fresh answer goes through and is remembered
+        assert_eq!(injector.positions("BTCUSDT", vec![position("1")])[0].size, "1");
+        assert_eq!(injector.positions("BTCUSDT", vec![])[0].size, "1");
+        assert_eq!(injector.order_status(status("New")).order_status, "New");
+        assert_eq!(injector.order_status(status("Filled")).order_status, "New");
+
+        // Same seed, same dice
+        let rolls = |injector: &FaultInjector| (0..32).map(|_| injector.roll_request("order")).collect::<Vec<_>>();
+        let first = rolls(&FaultInjector::new(always));
+        assert_eq!(first, rolls(&FaultInjector::new(always)));
+        assert!(first.contains(&Some(RequestFault::ServerError)) && first.contains(&None));
+    }
+
+    #[test]
+    fn test_injector_memory_is_bounded() {
+        let mut orders = Bounded::<Vec<u32>>::new(2);
+        orders.entry("a").push(1);
+        orders.entry("b").push(2);
+        orders.entry("a").push(3);
+        orders.entry("c").push(4);
+        // "a" was the oldest key, whatever was added to it later
+        assert!(orders.get("a").is_none());
+        assert_eq!(orders.get("b"), Some(&vec![2]));
+        assert_eq!(orders.map.len(), 2);
+
+        let injector = FaultInjector::new("out_of_order=0".parse().unwrap());
+        for _ in 0..20 {
+            injector.order_status(status("New"));
+        }
+        assert_eq!(injector.orders.lock().get("o-1").unwrap().len(), MAX_STATUSES_PER_ORDER);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_delay_line_holds_frames_in_order() {
+        let mut line = DelayLine::new(Latency { min_ms: 50, max_ms: 200 }, 7);
+        for frame in 0..20 {
+            line.push(frame);
+        }
+        assert_eq!(line.pop_due(), None);
+
+        let start = Instant::now();
+        let mut delivered = Vec::new();
+        while let Some(due) = line.next_due() {
+            tokio::time::sleep_until(due).await;
+            while let Some(frame) = line.pop_due() {
+                delivered.push(frame);
+            }
+        }
+        assert_eq!(delivered, (0..20).collect::<Vec<_>>());
+        let waited = start.elapsed();
+        assert!(waited >= Duration::from_millis(50) && waited <= Duration::from_millis(200), "{:?}", waited);
+    }
+}
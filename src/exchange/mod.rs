@@ -1,5 +1,9 @@
 pub mod bybit_client;
+pub mod error;
+pub mod faults;
 pub mod specs;
 
 pub use bybit_client::*;
+pub use error::*;
+pub use faults::*;
 pub use specs::*;
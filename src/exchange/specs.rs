@@ -1,18 +1,29 @@
 //! Instrument Specifications Module
 //! 
 //! Fetches and caches qtyStep/tickSize for each trading pair from Bybit API.
-//! Automatically loads specs when a new symbol is selected.
+//! Automatically loads specs when a new symbol is selected. Entries expire
+//! after a TTL (Bybit occasionally changes tick/lot sizes) and can be
+//! invalidated explicitly when an order is rejected for precision.
+//! With a backing file the cache survives restarts: no burst of
+//! instruments-info calls on startup, and last known specs as a fallback
+//! while the endpoint is unavailable.
 
-use crate::exchange::bybit_client::InstrumentInfo;
-use anyhow::Result;
+use crate::exchange::bybit_client::{BybitClient, InstrumentInfo};
+use crate::exchange::error::BybitResult;
+use crate::persist;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
-use tracing::{debug, error, info, warn};
+use std::time::Duration;
+use tracing::{info, warn};
 
 /// Cached precision specs for a symbol
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SymbolSpecs {
     pub symbol: String,
     pub qty_step: Decimal,
@@ -49,6 +60,16 @@ impl SymbolSpecs {
             rounded
         }
     }
+
+    /// Largest valid qty (at most `qty`) whose notional at `price` fits in `max_notional`;
+    /// None if not even the minimum order fits
+    pub fn fit_notional(&self, qty: Decimal, price: Decimal, max_notional: Decimal) -> Option<Decimal> {
+        if price <= Decimal::ZERO || max_notional <= Decimal::ZERO {
+            return None;
+        }
+        let fitted = self.round_qty(qty.min(max_notional / price));
+        (fitted >= self.min_order_qty && !fitted.is_zero()).then_some(fitted)
+    }
 }
 
 impl From<InstrumentInfo> for SymbolSpecs {
@@ -63,29 +84,131 @@ impl From<InstrumentInfo> for SymbolSpecs {
     }
 }
 
-/// Thread-safe specs cache with automatic fetching
+/// Specs plus the wall-clock time they were fetched (persisted as-is)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSpecs {
+    #[serde(flatten)]
+    specs: SymbolSpecs,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Thread-safe specs cache with automatic fetching (clones share the same entries)
 #[derive(Clone)]
 pub struct SpecsCache {
-    cache: Arc<DashMap<String, SymbolSpecs>>,
+    cache: Arc<DashMap<String, CachedSpecs>>,
+    /// Entries older than this are refetched (None = never expire)
+    ttl: Option<Duration>,
+    /// JSON file rewritten on every change (None = memory only)
+    path: Option<Arc<PathBuf>>,
 }
 
 impl SpecsCache {
     pub fn new() -> Self {
         Self {
             cache: Arc::new(DashMap::new()),
+            ttl: None,
+            path: None,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Load previously persisted specs from `path` and keep it updated.
+    /// A missing or unreadable file just starts the cache empty.
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        match persist::load_json::<BTreeMap<String, CachedSpecs>>(&path) {
+            Ok(Some(entries)) => {
+                info!("📏 Loaded specs for {} symbols from {}", entries.len(), path.display());
+                for (symbol, entry) in entries {
+                    self.cache.insert(symbol, entry);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("⚠️ Ignoring specs cache file: {:#}", e),
+        }
+        self.path = Some(Arc::new(path));
+        self
+    }
+
+    fn is_fresh(&self, entry: &CachedSpecs) -> bool {
+        self.ttl.is_none_or(|ttl| {
+            // A fetch time in the future (clock jump) counts as fresh
+            (Utc::now() - entry.fetched_at).to_std().ok().is_none_or(|age| age < ttl)
+        })
+    }
+
+    fn persist(&self) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        let entries: BTreeMap<String, CachedSpecs> = self
+            .cache
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        if let Err(e) = persist::save_json(path, &entries) {
+            warn!("⚠️ Failed to persist specs cache: {:#}", e);
         }
     }
     
-    /// Get specs for a symbol (returns None if not cached)
+    /// Get fresh specs for a symbol (returns None if not cached or expired)
     pub fn get(&self, symbol: &str) -> Option<SymbolSpecs> {
-        self.cache.get(symbol).map(|v| v.clone())
+        self.cache
+            .get(symbol)
+            .filter(|entry| self.is_fresh(entry))
+            .map(|entry| entry.specs.clone())
+    }
+
+    /// Get specs even if expired (fallback when the endpoint is unavailable)
+    pub fn get_stale(&self, symbol: &str) -> Option<SymbolSpecs> {
+        self.cache.get(symbol).map(|entry| entry.specs.clone())
     }
     
     /// Store specs for a symbol
     pub fn insert(&self, specs: SymbolSpecs) {
         info!("📏 Cached specs for {}: qty_step={}, tick_size={}", 
               specs.symbol, specs.qty_step, specs.tick_size);
-        self.cache.insert(specs.symbol.clone(), specs);
+        self.cache.insert(
+            specs.symbol.clone(),
+            CachedSpecs {
+                specs,
+                fetched_at: Utc::now(),
+            },
+        );
+        self.persist();
+    }
+
+    /// Drop a symbol's specs so the next lookup refetches them
+    pub fn invalidate(&self, symbol: &str) {
+        if self.cache.remove(symbol).is_some() {
+            info!("📏 Invalidated specs for {}", symbol);
+            self.persist();
+        }
+    }
+
+    /// Fetch instrument info from the exchange and cache it
+    pub async fn refresh(&self, client: &BybitClient, symbol: &str) -> BybitResult<SymbolSpecs> {
+        let specs = SymbolSpecs::from(client.get_instrument_info(symbol).await?);
+        self.insert(specs.clone());
+        Ok(specs)
+    }
+
+    /// Fresh cached specs, else fetched, else stale/default
+    pub async fn get_or_fetch(&self, client: &BybitClient, symbol: &str) -> SymbolSpecs {
+        if let Some(specs) = self.get(symbol) {
+            return specs;
+        }
+        match self.refresh(client, symbol).await {
+            Ok(specs) => specs,
+            Err(e) => {
+                warn!("⚠️ Failed to fetch specs for {}: {}", symbol, e);
+                self.get_or_default(symbol)
+            }
+        }
     }
     
     /// Check if symbol is cached
@@ -93,9 +216,9 @@ impl SpecsCache {
         self.cache.contains_key(symbol)
     }
     
-    /// Get fallback specs if not cached (conservative defaults)
+    /// Get fallback specs if not cached (last known, else conservative defaults)
     pub fn get_or_default(&self, symbol: &str) -> SymbolSpecs {
-        self.get(symbol).unwrap_or_else(|| {
+        self.get_stale(symbol).unwrap_or_else(|| {
             warn!("⚠️ Using fallback specs for {} (not cached)", symbol);
             SymbolSpecs {
                 symbol: symbol.to_string(),
@@ -113,3 +236,111 @@ impl Default for SpecsCache {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Specs with min/max on the qty grid, like Bybit returns them
+    fn specs() -> impl Strategy<Value = SymbolSpecs> {
+        (1i64..1000, 0u32..8, 1i64..100, 1i64..100_000, 1i64..1000, 0u32..8).prop_map(
+            |(step, step_scale, min_steps, extra_steps, tick, tick_scale)| {
+                let qty_step = Decimal::new(step, step_scale);
+                SymbolSpecs {
+                    symbol: "TESTUSDT".to_string(),
+                    qty_step,
+                    min_order_qty: qty_step * Decimal::from(min_steps),
+                    max_order_qty: qty_step * Decimal::from(min_steps + extra_steps),
+                    tick_size: Decimal::new(tick, tick_scale),
+                }
+            },
+        )
+    }
+
+    fn btc_specs() -> SymbolSpecs {
+        SymbolSpecs {
+            symbol: "BTCUSDT".to_string(),
+            qty_step: Decimal::new(1, 3),
+            min_order_qty: Decimal::new(1, 3),
+            max_order_qty: Decimal::from(100),
+            tick_size: Decimal::new(1, 1),
+        }
+    }
+
+    #[test]
+    fn test_expired_specs_fall_back_until_invalidated() {
+        let cache = SpecsCache::new().with_ttl(Duration::ZERO);
+        cache.insert(btc_specs());
+        assert!(cache.get("BTCUSDT").is_none());
+        assert_eq!(cache.get_or_default("BTCUSDT"), btc_specs());
+
+        cache.invalidate("BTCUSDT");
+        assert!(!cache.contains("BTCUSDT"));
+        assert_eq!(cache.get_or_default("BTCUSDT").qty_step, Decimal::new(1, 2));
+
+        let fresh = SpecsCache::new().with_ttl(Duration::from_secs(60));
+        fresh.insert(btc_specs());
+        assert_eq!(fresh.get("BTCUSDT"), Some(btc_specs()));
+    }
+
+    #[test]
+    fn test_specs_survive_restart_via_file() {
+        let path = std::env::temp_dir().join(format!("specs-cache-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let cache = SpecsCache::new().with_file(&path);
+        cache.insert(btc_specs());
+        drop(cache);
+
+        let reloaded = SpecsCache::new().with_ttl(Duration::from_secs(60)).with_file(&path);
+        assert_eq!(reloaded.get("BTCUSDT"), Some(btc_specs()));
+
+        // Past the TTL the persisted entry is still a fallback
+        let expired = SpecsCache::new().with_ttl(Duration::ZERO).with_file(&path);
+        assert!(expired.get("BTCUSDT").is_none());
+        assert_eq!(expired.get_or_default("BTCUSDT"), btc_specs());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_fit_notional_sizes_down_or_skips() {
+        let specs = btc_specs();
+        let price = Decimal::from(50_000);
+        // Fits as is
+        assert_eq!(specs.fit_notional(Decimal::new(10, 3), price, Decimal::from(1_000)), Some(Decimal::new(10, 3)));
+        // $300 of room → 0.006 BTC
+        assert_eq!(specs.fit_notional(Decimal::new(10, 3), price, Decimal::from(300)), Some(Decimal::new(6, 3)));
+        // Less than one min order (0.001 BTC = $50)
+        assert_eq!(specs.fit_notional(Decimal::new(10, 3), price, Decimal::from(40)), None);
+        assert_eq!(specs.fit_notional(Decimal::new(10, 3), price, Decimal::ZERO), None);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_clamp_qty_within_bounds_and_on_grid(
+            specs in specs(),
+            mantissa in 0i64..10_000_000_000_000,
+            scale in 0u32..10,
+        ) {
+            let qty = specs.clamp_qty(Decimal::new(mantissa, scale));
+            prop_assert!(qty >= specs.min_order_qty);
+            prop_assert!(qty <= specs.max_order_qty);
+            prop_assert!((qty % specs.qty_step).is_zero());
+        }
+
+        #[test]
+        fn prop_round_price_on_tick_grid_and_not_above(
+            specs in specs(),
+            mantissa in 1i64..10_000_000_000_000,
+            scale in 0u32..10,
+        ) {
+            let price = Decimal::new(mantissa, scale);
+            let rounded = specs.round_price(price);
+            prop_assert!(rounded <= price);
+            prop_assert!(price - rounded < specs.tick_size);
+            prop_assert!((rounded % specs.tick_size).is_zero());
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Actor Health Registry
+//!
+//! Each actor registers once and calls `beat()` on every loop iteration.
+//! The watchdog reads the registry and flags actors that stopped progressing,
+//! e.g. ExecutionActor stuck in an order poll loop while messages pile up.
+
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+struct ActorSlot {
+    name: &'static str,
+    /// How often the actor's loop normally comes around (idle tick / scan interval)
+    expected_interval: Duration,
+    /// Millis since registry start at the last beat
+    last_beat_ms: AtomicU64,
+}
+
+/// Handle an actor uses to report progress (cheap to clone)
+#[derive(Clone)]
+pub struct ActorHeartbeat {
+    slot: Arc<ActorSlot>,
+    started: Instant,
+}
+
+impl ActorHeartbeat {
+    pub fn beat(&self) {
+        let now_ms = self.started.elapsed().as_millis() as u64;
+        self.slot.last_beat_ms.store(now_ms, Ordering::Relaxed);
+    }
+}
+
+/// Liveness of one actor at the time of the check
+#[derive(Debug, Clone)]
+pub struct ActorStatus {
+    pub name: &'static str,
+    pub silent_for: Duration,
+    pub expected_interval: Duration,
+}
+
+pub struct HealthRegistry {
+    started: Instant,
+    actors: Mutex<Vec<Arc<ActorSlot>>>,
+}
+
+impl HealthRegistry {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            started: Instant::now(),
+            actors: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Register an actor; it counts as alive from this moment
+    pub fn register(&self, name: &'static str, expected_interval: Duration) -> ActorHeartbeat {
+        let slot = Arc::new(ActorSlot {
+            name,
+            expected_interval,
+            last_beat_ms: AtomicU64::new(self.started.elapsed().as_millis() as u64),
+        });
+        self.actors.lock().push(slot.clone());
+        ActorHeartbeat {
+            slot,
+            started: self.started,
+        }
+    }
+
+    pub fn statuses(&self) -> Vec<ActorStatus> {
+        let now_ms = self.started.elapsed().as_millis() as u64;
+        self.actors
+            .lock()
+            .iter()
+            .map(|slot| ActorStatus {
+                name: slot.name,
+                silent_for: Duration::from_millis(now_ms.saturating_sub(slot.last_beat_ms.load(Ordering::Relaxed))),
+                expected_interval: slot.expected_interval,
+            })
+            .collect()
+    }
+
+    /// Actors silent for longer than their loop interval plus `grace`
+    pub fn stalled(&self, grace: Duration) -> Vec<ActorStatus> {
+        self.statuses()
+            .into_iter()
+            .filter(|s| s.silent_for > s.expected_interval + grace)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stalled_actor_is_reported() {
+        let registry = HealthRegistry::new();
+        let fast = registry.register("fast", Duration::from_secs(1));
+        let _slow = registry.register("slow", Duration::from_secs(60));
+
+        tokio::time::advance(Duration::from_secs(20)).await;
+        fast.beat();
+        assert!(registry.stalled(Duration::from_secs(10)).is_empty());
+
+        tokio::time::advance(Duration::from_secs(15)).await;
+        let stalled: Vec<_> = registry
+            .stalled(Duration::from_secs(10))
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(stalled, vec!["fast"]);
+    }
+}
@@ -0,0 +1,108 @@
+//! Minimal HTTP/1.1 Plumbing
+//!
+//! Just enough request parsing for the small token-protected endpoints
+//! (signal webhook, control API): one request per connection, body by
+//! Content-Length, response with `Connection: close`.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+
+/// Requests bigger than this are rejected (commands and alerts are tiny)
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+const READ_TIMEOUT_SECS: u64 = 5;
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Path without the query string
+    pub fn route(&self) -> &str {
+        self.path.split('?').next().unwrap_or("")
+    }
+
+    /// `Authorization: Bearer <token>`
+    pub fn bearer_token(&self) -> Option<&str> {
+        self.header("authorization")
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(str::trim)
+    }
+}
+
+/// Parse a complete request (None while headers or body are still incomplete)
+pub fn parse_request(buf: &[u8]) -> Option<Request> {
+    let header_end = buf.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&buf[..header_end]).ok()?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let body = buf.get(header_end + 4..header_end + 4 + content_length)?.to_vec();
+
+    Some(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// Read one request; Err(status line) when it can't be answered normally
+pub async fn read_request(stream: &mut TcpStream) -> Result<Request, &'static str> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 2048];
+    let read = timeout(Duration::from_secs(READ_TIMEOUT_SECS), async {
+        loop {
+            if let Some(request) = parse_request(&buf) {
+                return Ok(request);
+            }
+            if buf.len() > MAX_REQUEST_BYTES {
+                return Err("413 Payload Too Large");
+            }
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return Err("400 Bad Request"),
+                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            }
+        }
+    })
+    .await;
+    read.unwrap_or(Err("408 Request Timeout"))
+}
+
+pub async fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+/// Compare without leaking the match length through timing
+pub fn token_matches(expected: &str, given: &str) -> bool {
+    let (a, b) = (expected.as_bytes(), given.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,188 @@
+//! Trade Journal
+//!
+//! Append-only JSON Lines file in `DATA_DIR`: every closed trade plus wallet
+//! equity snapshots (startup, after each close, periodically), so the equity
+//! curve can be rebuilt from disk even across crashes and restarts.
+
+use crate::exchange::{BybitClient, WalletBalance};
+use crate::models::TradeRecord;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use tracing::{debug, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotReason {
+    Startup,
+    TradeClose,
+    Periodic,
+}
+
+/// Wallet totals at a point in time (USD)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquitySnapshot {
+    pub at: DateTime<Utc>,
+    pub reason: SnapshotReason,
+    pub total_equity: Decimal,
+    pub wallet_balance: Decimal,
+    pub available_balance: Decimal,
+}
+
+impl EquitySnapshot {
+    pub fn from_wallet(wallet: &WalletBalance, reason: SnapshotReason) -> Self {
+        let parse = |s: &str| Decimal::from_str(s).unwrap_or(Decimal::ZERO);
+        Self {
+            at: Utc::now(),
+            reason,
+            total_equity: parse(&wallet.total_equity),
+            wallet_balance: parse(&wallet.total_wallet_balance),
+            available_balance: parse(&wallet.total_available_balance),
+        }
+    }
+}
+
+/// One journal line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JournalEntry {
+    Trade(TradeRecord),
+    Equity(EquitySnapshot),
+}
+
+/// Shared handle to the journal file (clones append to the same file)
+#[derive(Clone)]
+pub struct Journal {
+    path: Arc<PathBuf>,
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl Journal {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Arc::new(path.into()),
+            write_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, entry: &JournalEntry) -> Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock();
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path.as_ref())
+            .with_context(|| format!("failed to open {}", self.path.display()))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("failed to append to {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Journal failures never interrupt trading, they are only logged
+    pub fn record(&self, entry: JournalEntry) {
+        if let Err(e) = self.append(&entry) {
+            warn!("⚠️ Journal write failed: {:#}", e);
+        }
+    }
+
+    /// Fetch the wallet and journal an equity snapshot
+    pub async fn snapshot_equity(&self, client: &BybitClient, reason: SnapshotReason) -> Option<EquitySnapshot> {
+        match client.get_wallet_balance().await {
+            Ok(wallet) => {
+                let snapshot = EquitySnapshot::from_wallet(&wallet, reason);
+                debug!("📒 Equity snapshot ({:?}): {}", reason, snapshot.total_equity);
+                self.record(JournalEntry::Equity(snapshot.clone()));
+                Some(snapshot)
+            }
+            Err(e) => {
+                warn!("⚠️ Equity snapshot ({:?}) failed: {}", reason, e);
+                None
+            }
+        }
+    }
+
+    /// Closed trades only, oldest first
+    pub fn trades(&self) -> Result<Vec<TradeRecord>> {
+        Ok(self
+            .read_all()?
+            .into_iter()
+            .filter_map(|entry| match entry {
+                JournalEntry::Trade(trade) => Some(trade),
+                JournalEntry::Equity(_) => None,
+            })
+            .collect())
+    }
+
+    /// Every entry in the file, skipping lines that don't parse (e.g. a torn last write)
+    pub fn read_all(&self) -> Result<Vec<JournalEntry>> {
+        let content = match std::fs::read_to_string(self.path.as_ref()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", self.path.display())),
+        };
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+/// Periodic equity snapshots (the first tick fires after one full period)
+pub async fn run_equity_snapshots(journal: Journal, client: BybitClient, period: Duration) {
+    let mut ticker = interval(period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        journal.snapshot_equity(&client, SnapshotReason::Periodic).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_roundtrip_and_torn_lines_are_skipped() {
+        let path = std::env::temp_dir().join(format!("journal-test-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let journal = Journal::new(&path);
+
+        let snapshot = EquitySnapshot {
+            at: Utc::now(),
+            reason: SnapshotReason::Startup,
+            total_equity: Decimal::new(100_050, 2),
+            wallet_balance: Decimal::from(1000),
+            available_balance: Decimal::from(900),
+        };
+        journal.append(&JournalEntry::Equity(snapshot.clone())).unwrap();
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"type\":\"equ").unwrap();
+
+        let entries = journal.read_all().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(matches!(&entries[0], JournalEntry::Equity(s) if *s == snapshot));
+
+        let line = std::fs::read_to_string(&path).unwrap();
+        assert!(line.starts_with("{\"type\":\"equity\",\"at\":"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,4 +1,39 @@
 pub mod actors;
+pub mod audit;
+pub mod channel;
+pub mod clock;
 pub mod config;
+pub mod control_api;
+pub mod crash;
+pub mod dataset;
+pub mod decision_log;
+pub mod drawdown;
+pub mod endpoints;
+pub mod event_publisher;
+pub mod events;
+pub mod events_ws;
 pub mod exchange;
+pub mod health;
+pub mod http;
+pub mod journal;
+pub mod market_feed;
+pub mod metrics;
+pub mod model;
 pub mod models;
+pub mod notifications;
+pub mod order_recovery;
+pub mod persist;
+pub mod preflight;
+pub mod redact;
+pub mod redis_bridge;
+#[cfg(any(test, feature = "replay"))]
+pub mod replay;
+pub mod risk_limits;
+pub mod runtime;
+pub mod shadow;
+pub mod spsc;
+pub mod stats;
+pub mod symbol_lists;
+pub mod systemd;
+pub mod tui;
+pub mod webhook;
@@ -1,130 +1,925 @@
 use anyhow::Result;
 use bybit_scalper_bot::actors::*;
+use bybit_scalper_bot::actors::private_stream::{PrivateState, PrivateStreamActor, PRIVATE_PING_INTERVAL_SECS};
+use bybit_scalper_bot::audit::{self, OrderAudit};
 use bybit_scalper_bot::config::Config;
-use bybit_scalper_bot::exchange::BybitClient;
+use bybit_scalper_bot::control_api::ControlApi;
+use bybit_scalper_bot::channel::{metered_channel, metered_channel_keeping, MeteredSender, OverflowPolicy};
+use bybit_scalper_bot::crash;
+use bybit_scalper_bot::endpoints;
+use bybit_scalper_bot::event_publisher::EventPublisher;
+use bybit_scalper_bot::events::EventBus;
+use bybit_scalper_bot::events_ws;
+use bybit_scalper_bot::exchange::{BybitClient, SpecsCache};
+use bybit_scalper_bot::health::HealthRegistry;
+use bybit_scalper_bot::journal::{self, Journal, SnapshotReason};
+use bybit_scalper_bot::market_feed::MarketFeed;
+use bybit_scalper_bot::metrics::{self, Metrics};
+use bybit_scalper_bot::model::SignalModel;
+use bybit_scalper_bot::notifications::Alert;
+use bybit_scalper_bot::order_recovery;
+use bybit_scalper_bot::preflight;
+use bybit_scalper_bot::redis_bridge;
+use bybit_scalper_bot::redact::{self, RedactingMakeWriter};
+#[cfg(feature = "replay")]
+use bybit_scalper_bot::replay;
+use bybit_scalper_bot::models::Symbol;
+use bybit_scalper_bot::risk_limits::{DailyTradeLimit, PositionGate, TempBlacklist};
+use bybit_scalper_bot::symbol_lists::SymbolLists;
+use bybit_scalper_bot::runtime::RuntimeConfig;
+use bybit_scalper_bot::shadow::{Arena, Shadow};
+use bybit_scalper_bot::spsc;
+use bybit_scalper_bot::systemd::SystemdNotifier;
+use bybit_scalper_bot::tui;
+use bybit_scalper_bot::webhook;
+use futures_util::future::select_all;
 use std::sync::Arc;
-use tokio::sync::mpsc;
-use tracing::{error, info};
+use tokio::time::Duration;
+use tokio::task::{JoinError, JoinHandle};
+use tracing::{error, info, info_span, warn};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::{fmt, EnvFilter};
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> std::process::ExitCode {
+    // ✅ REDACTION: Credentials are known before the first log line, and the
+    // startup error (its whole chain) is printed redacted
+    redact::register_env();
+    match start() {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", redact::redact(&format!("{:?}", e)));
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
+
+fn start() -> Result<()> {
+    // ✅ REPLAY: `--replay <decisions.jsonl>` re-runs a decision log offline and exits
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(i) = args.iter().position(|arg| arg == "--replay") {
+        let path = args.get(i + 1).ok_or_else(|| anyhow::anyhow!("--replay needs a decision log file"))?;
+        return run_replay(std::path::Path::new(path));
+    }
+    // ✅ AUDIT: `--verify-audit <order_audit.jsonl>` checks the hash chain and exits
+    if let Some(i) = args.iter().position(|arg| arg == "--verify-audit") {
+        let path = args.get(i + 1).ok_or_else(|| anyhow::anyhow!("--verify-audit needs an audit file"))?;
+        let lines = audit::verify(std::path::Path::new(path))?;
+        println!("🔏 {}: {} lines, hash chain intact", path, lines);
+        return Ok(());
+    }
+
+    // ✅ RUNTIME: Worker count / core pinning must be known before the runtime exists
+    let runtime_config = RuntimeConfig::from_env()?;
+    let runtime = runtime_config.build_main()?;
+    let result = runtime.block_on(run(runtime_config));
+    // Don't hang on a blocking task (file write) still running
+    runtime.shutdown_timeout(std::time::Duration::from_secs(5));
+    result
+}
+
+/// Replay a decision log through the strategy and fail on the first changed decision
+#[cfg(feature = "replay")]
+fn run_replay(path: &std::path::Path) -> Result<()> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+    tracing_subscriber::registry()
+        .with(
+            fmt::layer()
+                .with_writer(RedactingMakeWriter::new(std::io::stdout))
+                .with_target(false)
+                .compact()
+                .with_filter(filter),
+        )
+        .init();
+
+    let config = Config::from_env()?;
+    let report = replay::replay_file(&config, path)?;
+    println!(
+        "🔁 Replayed {}: {} sessions, {} inputs, {} decisions reproduced",
+        path.display(),
+        report.sessions,
+        report.inputs,
+        report.decisions
+    );
+    match report.divergence {
+        Some(divergence) => anyhow::bail!("decisions diverge {}", divergence),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(feature = "replay"))]
+fn run_replay(_path: &std::path::Path) -> Result<()> {
+    anyhow::bail!("--replay needs a build with `--features replay`")
+}
+
+async fn run(runtime_config: RuntimeConfig) -> Result<()> {
+    // ✅ TUI: `--tui` draws the dashboard on the terminal, logs go to a file
+    let tui_mode = std::env::args().skip(1).any(|arg| arg == "--tui");
+    if tui_mode && !tui::AVAILABLE {
+        anyhow::bail!("--tui needs a build with `--features tui`");
+    }
+    let log_writer = if tui_mode {
+        let dir = std::path::PathBuf::from(std::env::var("DATA_DIR").unwrap_or_else(|_| "data".to_string()));
+        std::fs::create_dir_all(&dir)?;
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(dir.join("tui.log"))?;
+        BoxMakeWriter::new(RedactingMakeWriter::new(std::sync::Mutex::new(file)))
+    } else {
+        BoxMakeWriter::new(RedactingMakeWriter::new(std::io::stdout))
+    };
+
     // Initialize structured logging
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
 
-    fmt()
-        .with_env_filter(filter)
+    let fmt_layer = fmt::layer()
+        .with_writer(log_writer)
+        .with_ansi(!tui_mode)
         .with_target(false)
         .with_thread_ids(false)
         .with_file(false)
         .with_line_number(false)
         .compact()
-        .init();
+        .with_filter(filter);
+    let registry = tracing_subscriber::registry().with(fmt_layer);
+
+    // ✅ DIAGNOSTICS: tokio-console (needs RUSTFLAGS="--cfg tokio_unstable")
+    #[cfg(feature = "tokio-console")]
+    let registry = registry.with(console_subscriber::spawn());
+
+    registry.init();
 
     info!("🚀 Bybit Dynamic Scalper Bot - Initializing...");
 
     // Load configuration
-    let config = Arc::new(Config::from_env()?);
+    let mut config = Config::from_env()?;
+    // ✅ ENDPOINT PROBE: Fastest mainnet REST/WebSocket endpoint, before anything connects
+    let endpoint_probes = endpoints::select(&mut config).await;
+    let config = Arc::new(config);
+    // ✅ REDACTION: Credentials never reach a log line or an alert
+    // (also registered from the environment at startup; this covers the final values)
+    redact::register_config(&config);
     info!("✅ Configuration loaded");
+    info!("   - Environment: {}", config.environment());
     info!("   - API URL: {}", config.rest_api_url());
     info!("   - WebSocket: {}", config.ws_url());
     info!("   - Max Position: ${}", config.max_position_size_usd);
     info!("   - Stop Loss: {}%", config.stop_loss_percent);
+    if config.hard_stop_gap_percent > 0.0 {
+        info!("   - Hard Stop: {}% beyond the stop loss (exchange-side)", config.hard_stop_gap_percent);
+    }
     info!("   - Scan Interval: {}s", config.scan_interval_secs);
 
     // Create Bybit client
-    let client = BybitClient::new(
+    let mut client = BybitClient::new(
         config.bybit_api_key.clone(),
         config.bybit_api_secret.clone(),
         config.rest_api_url().to_string(),
-    );
+    )
+    .with_order_links(order_recovery::link_prefix(&config.bot_id));
+
+    // ✅ PREFLIGHT: Bad credentials stop the launch instead of the first live order
+    preflight::run(&client).await?;
+
+    // ✅ DEMO: Optional virtual top-up (Bybit rate-limits these requests)
+    if config.demo_trading && config.demo_topup_usdt > 0.0 {
+        match client.demo_apply_money("USDT", &config.demo_topup_usdt.to_string()).await {
+            Ok(()) => info!("💰 Demo wallet topped up with {} USDT", config.demo_topup_usdt),
+            Err(e) => warn!("Demo top-up failed: {}", e),
+        }
+    }
+
+    // ✅ AUDIT: Every order request and response, hash-chained, apart from the logs
+    if config.order_audit {
+        let audit = OrderAudit::open(config.data_path("order_audit.jsonl"))?;
+        info!("   - Order audit: {}", audit.path().display());
+        client = client.with_audit(audit);
+    }
+
+    // ✅ CHAOS: Soak-test the recovery paths against injected exchange faults (after the preflight)
+    if let Some(scenario) = config.fault_scenario {
+        warn!("🧪 Fault injection ON: {}", scenario);
+        client = client.with_faults(scenario);
+    }
+
+    // ✅ JOURNAL: Trades + equity snapshots on disk (equity curve survives restarts)
+    let journal = Journal::new(config.data_path("journal.jsonl"));
+    if let Some(snapshot) = journal.snapshot_equity(&client, SnapshotReason::Startup).await {
+        info!("   - Equity: {} USD (journal: {})", snapshot.total_equity, journal.path().display());
+    }
+
+    // ✅ METRICS: Channel depth/drop counters
+    let metrics = Metrics::new();
+    endpoints::record(&metrics, &config, &endpoint_probes);
+    if !endpoint_probes.is_empty() && config.endpoint_probe_interval_secs > 0 {
+        tokio::spawn(endpoints::monitor(config.clone(), metrics.clone()));
+    }
 
     // Actor Communication Channels
     // Scanner -> MarketData
     // ✅ FIXED: Increased from 32 to 256 to prevent deadlock
-    let (market_data_cmd_tx, market_data_cmd_rx) = mpsc::channel(256);
+    let (market_data_cmd_tx, market_data_cmd_rx) =
+        metered_channel("market_data_cmd", 256, OverflowPolicy::Block { timeout: None }, &metrics);
 
-    // MarketData -> Strategy
-    let (strategy_tx, strategy_rx) = mpsc::channel(1000);
+    // Any actor -> Strategy (control, fills, warm-ups); blocking sends
+    let (strategy_tx, strategy_rx) =
+        metered_channel("strategy", 1000, OverflowPolicy::Block { timeout: None }, &metrics);
 
     // Strategy -> Execution
-    let (execution_tx, execution_rx) = mpsc::channel(100);
+    let (execution_normal_tx, execution_rx) =
+        metered_channel("execution", 100, OverflowPolicy::Block { timeout: None }, &metrics);
+    // ✅ PRIORITY LANE: Closes never wait behind an entry confirmation poll
+    let (execution_priority_tx, execution_priority_rx) =
+        metered_channel("execution_priority", 100, OverflowPolicy::Block { timeout: None }, &metrics);
+    let execution_tx = execution::ExecutionSender::new(execution_normal_tx, execution_priority_tx);
+
+    // Any actor -> Notifier (alerts)
+    // During alert storms the newest alerts matter most, but critical ones are never evicted
+    let (notifier_tx, notifier_rx) = metered_channel_keeping(
+        "notifier",
+        100,
+        OverflowPolicy::DropOldest,
+        NotifierMessage::must_deliver,
+        &metrics,
+    );
+
+    // Control API -> Scanner (symbol pinning)
+    let (scanner_cmd_tx, scanner_cmd_rx) =
+        metered_channel("scanner_cmd", 16, OverflowPolicy::Block { timeout: None }, &metrics);
+
+    // ✅ CRASH SAFETY: Every panic becomes a critical alert
+    crash::install_panic_hook(notifier_tx.clone());
+
+    // ✅ ORDER RECOVERY: Orders a crashed run left in flight are cancelled, their fills adopted
+    match order_recovery::run(&client, &order_recovery::link_prefix(&config.bot_id)).await {
+        Ok(positions) => {
+            let mut pinned = false;
+            for pos in positions {
+                let line = format!("{} {} {} @ {}", pos.symbol, pos.side, pos.size, pos.avg_price);
+                // A shard adopts its own symbol; a single strategy is pinned to the first one
+                let action = if config.trading_symbols.contains(&pos.symbol) {
+                    "adopted by its shard"
+                } else if config.trading_symbols.is_empty() && !pinned {
+                    pinned = true;
+                    let _ = scanner_cmd_tx.send(ScannerMessage::AdoptSymbol(Symbol::from(pos.symbol.as_str()))).await;
+                    "pinned and adopted (unpinned once it is closed)"
+                } else {
+                    "not traded by this run: close it manually"
+                };
+                let _ = notifier_tx.try_send(NotifierMessage::Alert(Alert::warning(format!(
+                    "🧹 <b>Position from an order in flight before the restart</b>\n{}\n{}",
+                    line, action
+                ))));
+            }
+        }
+        Err(e) => warn!("Order recovery failed, orders of a previous run are not checked: {:#}", e),
+    }
 
     info!("🔧 Setting up Actor System...");
 
+    // ✅ WATCHDOG: Each actor reports progress here
+    let health = HealthRegistry::new();
+
+    // Instrument specs shared by scanner (switch/TTL refresh) and execution (precision rejections)
+    let specs_cache = SpecsCache::new()
+        .with_ttl(Duration::from_secs(config.specs_ttl_secs))
+        .with_file(config.data_path("specs_cache.json"));
+
     // Initialize ScannerActor
+    // ✅ AUTO-BLACKLIST: Shared between the strategy (adds) and the scanner (skips)
+    let temp_blacklist = TempBlacklist::load(config.data_path("temp_blacklist.json"));
+    // ✅ SYMBOL LISTS: Env black/whitelist plus Telegram and control API edits (persisted, audited)
+    let symbol_lists = SymbolLists::load(&config);
+
     let scanner = scanner::ScannerActor::new(
         client.clone(),
         config.clone(),
         market_data_cmd_tx.clone(),
         strategy_tx.clone(),
+        scanner_cmd_rx,
+        specs_cache.clone(),
+        temp_blacklist.clone(),
+        symbol_lists.clone(),
+        health.register("scanner", Duration::from_secs(config.scan_interval_secs)),
     );
 
     // Initialize MarketDataActor
-    let market_data = websocket::MarketDataActor::new(
+    let mut market_data = websocket::MarketDataActor::new(
         config.clone(),
-        strategy_tx.clone(),
         market_data_cmd_rx,
+        notifier_tx.clone(),
+        health.register("market_data", Duration::from_secs(websocket::PING_INTERVAL_SECS)),
     );
+    if let Some(ref addr) = config.zmq_pub_addr {
+        market_data.set_feed(MarketFeed::bind(addr)?);
+    }
 
-    // Initialize StrategyEngine
-    let strategy = strategy::StrategyEngine::new(
-        config.clone(),
-        strategy_rx,
-        execution_tx.clone(),
-    );
+    // ✅ QUEUE POSITION: Resting maker entries are repriced off the live queue
+    let queue_board = queue_position::QueueBoard::new();
+    if config.entry_max_reprices > 0 {
+        market_data.set_queue_board(queue_board.clone());
+    }
+
+    // ✅ SIGNAL MODEL: A configured model that can't load is a startup error
+    let signal_model = match config.signal_model_path.as_deref() {
+        Some(path) => {
+            let model = SignalModel::load(path)?;
+            info!("   - Signal model: {} (threshold {:.2})", path.display(), config.signal_model_threshold);
+            Some(model)
+        }
+        None => None,
+    };
+
+    // ✅ EVENTS: Outbound integrations subscribe here; a configured one that can't start is a startup error
+    let events = EventBus::new(config.bot_id.as_str());
+    let redis_bridge = redis_bridge::from_config(&config, &events, &strategy_tx)?;
+    if redis_bridge.is_some() {
+        info!("   - Redis bridge: channel prefix {}", config.redis_channel_prefix);
+    }
+    let event_publishers = EventPublisher::from_config(&config, &events).await?;
+    for publisher in &event_publishers {
+        info!("   - {} events: {}.<type> (bot_id {})", publisher.name(), config.event_topic_prefix, config.bot_id);
+    }
+
+    // ✅ SHADOW: A second configuration on simulated fills, a twin per live engine
+    let shadow = config.shadow()?.map(|shadow_config| Shadow::new("shadow", shadow_config, &metrics));
+    if let (Some(shadow), Some(path)) = (&shadow, &config.shadow_config) {
+        info!("   - Shadow strategy: {} (journal: {})", path.display(), shadow.journal().path().display());
+    }
+    // ✅ ARENA: One more shadow per paper profile, ranked by /leaderboard
+    let paper_profiles: Vec<(String, Shadow)> = config
+        .paper_profiles()?
+        .into_iter()
+        .map(|(name, profile_config)| {
+            let shadow = Shadow::new(&format!("paper_{}", name), profile_config, &metrics);
+            (name, shadow)
+        })
+        .collect();
+    for (name, shadow) in &paper_profiles {
+        info!("   - Paper profile '{}' (journal: {})", name, shadow.journal().path().display());
+    }
+    let arena = Arena::new(paper_profiles.iter().map(|(name, shadow)| (name.as_str(), shadow)));
+    let shadows: Vec<&Shadow> = shadow.iter().chain(paper_profiles.iter().map(|(_, shadow)| shadow)).collect();
+    let mut shadow_twins = Vec::new();
+
+    // Initialize StrategyEngine + PositionMonitor (SL/TP/trailing on every price, off the strategy task)
+    // ✅ SHARDS: With TRADING_SYMBOLS one pair per symbol, fed by a router on the strategy channel
+    let strategy_period = Duration::from_secs(strategy::POSITION_VERIFY_INTERVAL_SECS);
+    let monitor_period = Duration::from_secs(position_monitor::MONITOR_BEAT_SECS);
+    let mut engines = Vec::new();
+    // The shards' risk gate, also held by the pair trader
+    let mut position_gate = None;
+    let strategy_router = if config.trading_symbols.is_empty() {
+        // MarketData -> Strategy
+        // ✅ SPSC: Lock-free ring; orderbooks use try_send, trades send_timeout
+        let (market_tx, market_rx) = spsc::market_ring("market_data", 1000, &metrics);
+        market_data.set_market_ring(market_tx);
+        let mut strategy = strategy::StrategyEngine::new(
+            config.clone(),
+            strategy_rx,
+            market_rx,
+            execution_tx.clone(),
+            notifier_tx.clone(),
+            journal.clone(),
+            temp_blacklist.clone(),
+            signal_model,
+            events.clone(),
+            health.register("strategy", strategy_period),
+        );
+        strategy.set_state_stats(metrics.register_state_machine("strategy"));
+        for shadow in &shadows {
+            shadow_twins.push(shadow.twin(&mut strategy, None, market_data.prices(), &metrics)?);
+        }
+        let position_monitor = position_monitor::PositionMonitor::new(
+            market_data.prices(),
+            strategy.exit_plans(),
+            execution_tx.clone(),
+            strategy_tx.clone(),
+            health.register("position_monitor", monitor_period),
+        );
+        engines.push(("strategy", strategy, "position_monitor", position_monitor));
+        None
+    } else {
+        let gate = PositionGate::new(config.max_open_positions);
+        position_gate = Some(gate.clone());
+        let daily_trades = DailyTradeLimit::load(
+            config.data_path("daily_trades.json"),
+            config.max_trades_per_day,
+            chrono::Utc::now(),
+        );
+        let mut router = router::StrategyRouter::new(
+            strategy_rx,
+            health.register("strategy_router", Duration::from_secs(router::ROUTER_BEAT_SECS)),
+        );
+        let mut signal_model = signal_model;
+        for symbol in &config.trading_symbols {
+            let symbol = Symbol::from(symbol.as_str());
+            // Shard names are needed for the whole run
+            let name: &'static str = Box::leak(format!("strategy_{}", symbol).into_boxed_str());
+            let monitor_name: &'static str = Box::leak(format!("position_monitor_{}", symbol).into_boxed_str());
+            let ring_name: &'static str = Box::leak(format!("market_data_{}", symbol).into_boxed_str());
+            let (shard_tx, shard_rx) = metered_channel(name, 1000, OverflowPolicy::Block { timeout: None }, &metrics);
+            let (market_tx, market_rx) = spsc::market_ring(ring_name, 1000, &metrics);
+            market_data.add_route(symbol.clone(), market_tx);
+            // Every shard scores with its own model session
+            let model = match signal_model.take() {
+                Some(model) => Some(model),
+                None => config.signal_model_path.as_deref().map(SignalModel::load).transpose()?,
+            };
+            let mut strategy = strategy::StrategyEngine::new(
+                config.clone(),
+                shard_rx,
+                market_rx,
+                execution_tx.clone(),
+                notifier_tx.clone(),
+                journal.clone(),
+                temp_blacklist.clone(),
+                model,
+                events.clone(),
+                health.register(name, strategy_period),
+            );
+            strategy.set_shard(&symbol, gate.clone(), daily_trades.clone());
+            strategy.set_state_stats(metrics.register_state_machine(name));
+            for shadow in &shadows {
+                shadow_twins.push(shadow.twin(&mut strategy, Some(&symbol), market_data.prices_for(symbol.clone()), &metrics)?);
+            }
+            let position_monitor = position_monitor::PositionMonitor::new(
+                market_data.prices_for(symbol.clone()),
+                strategy.exit_plans(),
+                execution_tx.clone(),
+                strategy_tx.clone(),
+                health.register(monitor_name, monitor_period),
+            );
+            router.add_shard(symbol, shard_tx);
+            engines.push((name, strategy, monitor_name, position_monitor));
+        }
+        info!(
+            "   - Strategy shards: {} (max {} open positions)",
+            config.trading_symbols.join(", "),
+            config.max_open_positions
+        );
+        Some(router)
+    };
+
+    // Initialize ReferenceFeedActor (BINANCE_REFERENCE: Binance book tops of the watched symbols, lead-lag features)
+    let reference = reference_feed::ReferencePrices::new(config.reference_lead_ms);
+    let reference_feed = config.binance_reference.then(|| {
+        let bybit = if config.trading_symbols.is_empty() {
+            vec![market_data.prices()]
+        } else {
+            config.trading_symbols.iter().map(|symbol| market_data.prices_for(Symbol::from(symbol.as_str()))).collect()
+        };
+        reference_feed::ReferenceFeedActor::new(
+            bybit,
+            reference.clone(),
+            // Apart from the live registry: an optional Binance feed must not trip the watchdog
+            HealthRegistry::new().register("reference_feed", Duration::from_secs(reference_feed::REFERENCE_RESUBSCRIBE_SECS)),
+        )
+    });
+    for (_, strategy, _, _) in &mut engines {
+        strategy.set_symbol_lists(symbol_lists.clone());
+    }
+    if reference_feed.is_some() {
+        for (_, strategy, _, _) in &mut engines {
+            strategy.set_reference(reference.clone());
+        }
+        info!("   - Binance reference feed: lead over {}ms", config.reference_lead_ms);
+    }
+
+    // Account state of the private stream: execution stops polling as soon as it reports an order done
+    let private_state = PrivateState::new();
+
+    // Entries of this run: the only positions a crash flattens
+    let owned_positions = crash::OwnedPositions::default();
 
     // Initialize ExecutionActor
     let execution = execution::ExecutionActor::new(
         client.clone(),
         config.clone(),
         execution_rx,
+        execution_priority_rx,
         strategy_tx.clone(),
+        notifier_tx.clone(),
+        specs_cache.clone(),
+        journal.clone(),
+        queue_board,
+        config.private_ws_enabled.then(|| private_state.clone()),
+        owned_positions.clone(),
+        health.register("execution", Duration::from_secs(execution::EXECUTION_LIVENESS_SECS)),
+        health.register("execution_priority", Duration::from_secs(execution::EXECUTION_LIVENESS_SECS)),
+    );
+
+    // Initialize NotifierActor
+    let notifier = notifier::NotifierActor::new(
+        config.clone(),
+        notifier_rx,
+        health.register("notifier", Duration::from_secs(1)),
+    );
+
+    // Initialize WatchdogActor
+    // ✅ SYSTEMD: READY/STOPPING and the watchdog when run as a Type=notify unit
+    let systemd = SystemdNotifier::from_env();
+    let watchdog = watchdog::WatchdogActor::new(
+        config.clone(),
+        health.clone(),
+        metrics.clone(),
+        notifier_tx.clone(),
+        systemd.clone(),
+    );
+
+    // Initialize PrivateStreamActor (authenticated account stream, own reconnect loop)
+    let private_stream = config.private_ws_enabled.then(|| {
+        PrivateStreamActor::new(
+            config.clone(),
+            client.clone(),
+            private_state.clone(),
+            health.register("private_stream", Duration::from_secs(PRIVATE_PING_INTERVAL_SECS)),
+        )
+    });
+
+    // Initialize AccountMonitorActor (margin rate alerts)
+    let account_monitor = (config.margin_check_interval_secs > 0).then(|| {
+        account_monitor::AccountMonitorActor::new(
+            client.clone(),
+            config.clone(),
+            strategy_tx.clone(),
+            notifier_tx.clone(),
+            health.register("account_monitor", Duration::from_secs(config.margin_check_interval_secs)),
+        )
+    });
+
+    // Initialize PairTrader (PAIR_SYMBOLS: spread trading on two symbols, both legs at once)
+    let pair_trader = pair_trader::PairTrader::from_config(
+        config.clone(),
+        |symbol| market_data.prices_for(symbol),
+        client.clone(),
+        specs_cache.clone(),
+        market_data_cmd_tx.clone(),
+        execution_tx.clone(),
+        strategy_tx.clone(),
+        notifier_tx.clone(),
+        position_gate,
+        health.register("pair_trader", Duration::from_secs(pair_trader::PAIR_LIVENESS_SECS)),
+    );
+    if let Some((a, b)) = &config.pair_symbols {
+        info!("   - Pair trading: {} / {} (${} per leg)", a, b, config.pair_notional_usd);
+    }
+
+    // Initialize TelegramCommandActor (only if an allow-list is configured)
+    let telegram_commands = commands::TelegramCommandActor::new(
+        config.clone(),
+        strategy_tx.clone(),
+        scanner_cmd_tx.clone(),
+        symbol_lists.clone(),
+        journal.clone(),
+        arena.clone(),
     );
 
     info!("✅ All actors initialized");
 
     // Spawn actors as independent tasks
-    let scanner_handle = tokio::spawn(async move {
+    // ✅ DIAGNOSTICS: Poll-time monitors per actor task (slow polls = blocked worker)
+    let scanner_handle = tokio::spawn(metrics.task_monitor("scanner").instrument(async move {
         scanner.run().await;
+    }));
+
+    // ✅ HOT PATH: Optionally on its own thread, away from Telegram/metrics/REST tasks
+    let hot_path = runtime_config.hot_path()?;
+    if hot_path.is_dedicated() {
+        info!(
+            "⚡ Market data + strategy on a dedicated runtime thread{}",
+            runtime_config.hot_path_cpu.map(|c| format!(" (core {})", c)).unwrap_or_default()
+        );
+    }
+
+    // Critical while it runs: a dead pair trader leaves both legs unmanaged
+    let pair_trader_handle = pair_trader.map(|pair_trader| {
+        tokio::spawn(metrics.task_monitor("pair_trader").instrument(async move {
+            pair_trader.run().await;
+        }))
     });
+    let pair_trader_handle = async move {
+        match pair_trader_handle {
+            Some(handle) => handle.await,
+            None => std::future::pending().await,
+        }
+    };
 
-    let market_data_handle = tokio::spawn(async move {
+    let market_data_handle = hot_path.spawn(metrics.task_monitor("market_data").instrument(async move {
         market_data.run().await;
-    });
+    }));
 
-    let strategy_handle = tokio::spawn(async move {
-        strategy.run().await;
-    });
+    let mut strategy_handles = Vec::new();
+    let mut position_monitor_handles = Vec::new();
+    for (name, strategy, monitor_name, position_monitor) in engines {
+        strategy_handles.push(hot_path.spawn(metrics.task_monitor(name).instrument(async move {
+            strategy.run().await;
+        })));
+        // Main runtime on purpose: exits keep running while the hot path thread is busy
+        position_monitor_handles.push(tokio::spawn(metrics.task_monitor(monitor_name).instrument(async move {
+            position_monitor.run().await;
+        })));
+    }
+    if let Some(router) = strategy_router {
+        strategy_handles.push(hot_path.spawn(metrics.task_monitor("strategy_router").instrument(async move {
+            router.run().await;
+        })));
+    }
+    // Main runtime and outside the critical set: the shadow never holds up or takes down the live bot
+    for twin in shadow_twins {
+        let (engine, monitor, execution) = (twin.engine, twin.monitor, twin.execution);
+        let (engine, monitor, execution) = {
+            // Scoped: TaskMonitor has an `instrument` of its own
+            use tracing::Instrument;
+            let span = info_span!("shadow", task = twin.name);
+            (
+                async move { engine.run().await }.instrument(span.clone()),
+                async move { monitor.run().await }.instrument(span.clone()),
+                async move { execution.run().await }.instrument(span),
+            )
+        };
+        tokio::spawn(metrics.task_monitor(twin.name).instrument(engine));
+        tokio::spawn(monitor);
+        tokio::spawn(execution);
+    }
+    if let Some(ref shadow) = shadow {
+        tokio::spawn(bybit_scalper_bot::shadow::run_daily_comparison(
+            journal.clone(),
+            shadow.journal().clone(),
+            notifier_tx.clone(),
+        ));
+    }
+
+    // Any shard (or the router) ending counts as the strategy ending
+    let strategy_handle = select_all(strategy_handles);
+    let position_monitor_handle = select_all(position_monitor_handles);
 
-    let execution_handle = tokio::spawn(async move {
+    let mut execution_handle = tokio::spawn(metrics.task_monitor("execution").instrument(async move {
         execution.run().await;
+    }));
+
+    let notifier_handle = tokio::spawn(metrics.task_monitor("notifier").instrument(async move {
+        notifier.run().await;
+    }));
+
+    tokio::spawn(async move {
+        watchdog.run().await;
     });
 
+    // ✅ METRICS: Optional Prometheus exporter
+    if let Some(ref addr) = config.metrics_addr {
+        match addr.parse() {
+            Ok(addr) => {
+                let metrics = metrics.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = metrics::serve(addr, metrics).await {
+                        error!("Metrics exporter failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => error!("Invalid METRICS_ADDR '{}': {}", addr, e),
+        }
+    }
+
+    // Not critical either: events are best-effort
+    if let Some(redis_bridge) = redis_bridge {
+        tokio::spawn(metrics.task_monitor("redis_bridge").instrument(async move {
+            redis_bridge.run().await;
+        }));
+    }
+    for publisher in event_publishers {
+        tokio::spawn(metrics.task_monitor(publisher.task_name()).instrument(async move {
+            publisher.run().await;
+        }));
+    }
+
+    // ✅ WEBHOOK: Optional external signal endpoint
+    if let Some(ref addr) = config.webhook_addr {
+        match addr.parse() {
+            Ok(addr) => {
+                let token: Arc<str> = config.webhook_token.as_str().into();
+                let strategy_tx = strategy_tx.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = webhook::serve(addr, token, strategy_tx).await {
+                        error!("Signal webhook failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => error!("Invalid WEBHOOK_ADDR '{}': {}", addr, e),
+        }
+    }
+
+    // ✅ CONTROL API: Optional token-protected remote control
+    if let Some(ref addr) = config.control_api_addr {
+        match addr.parse() {
+            Ok(addr) => {
+                let api = ControlApi::new(
+                    &config,
+                    strategy_tx.clone(),
+                    scanner_cmd_tx.clone(),
+                    symbol_lists.clone(),
+                    arena.clone(),
+                );
+                tokio::spawn(async move {
+                    if let Err(e) = api.serve(addr).await {
+                        error!("Control API failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => error!("Invalid CONTROL_API_ADDR '{}': {}", addr, e),
+        }
+    }
+
+    // ✅ EVENT WEBSOCKET: Live events for dashboards and mobile clients
+    if let Some(ref addr) = config.events_ws_addr {
+        match addr.parse() {
+            Ok(addr) => {
+                let token: Option<Arc<str>> = config.events_ws_token.as_deref().map(Into::into);
+                if token.is_none() {
+                    warn!("⚠️ EVENTS_WS_TOKEN is not set: anyone who can reach {} sees the bot's events", addr);
+                }
+                let events = events.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = events_ws::serve(addr, token, events).await {
+                        error!("Event WebSocket failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => error!("Invalid EVENTS_WS_ADDR '{}': {}", addr, e),
+        }
+    }
+
+    // Not part of the critical set below: a dead private stream only loses push updates
+    if let Some(private_stream) = private_stream {
+        tokio::spawn(metrics.task_monitor("private_stream").instrument(async move {
+            private_stream.run().await;
+        }));
+    }
+
+    // Outside the critical set too: without it entries just lose the reference filter
+    if let Some(reference_feed) = reference_feed {
+        tokio::spawn(metrics.task_monitor("reference_feed").instrument(async move {
+            reference_feed.run().await;
+        }));
+    }
+
+    if config.equity_snapshot_interval_secs > 0 {
+        let period = Duration::from_secs(config.equity_snapshot_interval_secs);
+        tokio::spawn(journal::run_equity_snapshots(journal.clone(), client.clone(), period));
+    }
+
+    if let Some(account_monitor) = account_monitor {
+        tokio::spawn(metrics.task_monitor("account_monitor").instrument(async move {
+            account_monitor.run().await;
+        }));
+    }
+
+    if let Some(telegram_commands) = telegram_commands {
+        tokio::spawn(async move {
+            telegram_commands.run().await;
+        });
+    }
+
     info!("🎯 Bot is now LIVE and hunting for opportunities!");
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    if let Some(ref systemd) = systemd {
+        systemd.ready();
+    }
 
-    // Setup graceful shutdown
+    // ✅ SHUTDOWN: Ctrl+C and quitting the dashboard both stop the bot through `shutdown`
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel::<&'static str>(2);
+    let signal_tx = shutdown_tx.clone();
     tokio::spawn(async move {
         tokio::signal::ctrl_c()
             .await
             .expect("Failed to listen for Ctrl+C");
-        info!("🛑 Shutdown signal received, stopping bot...");
-        std::process::exit(0);
+        let _ = signal_tx.send("Shutdown signal received").await;
     });
 
+    // In raw mode Ctrl+C is a key press, so the dashboard handles quitting itself
+    if tui_mode {
+        let strategy_tx = strategy_tx.clone();
+        let events = events.clone();
+        tokio::spawn(async move {
+            match tui::run(strategy_tx, events).await {
+                Ok(()) => {
+                    let _ = shutdown_tx.send("TUI closed").await;
+                }
+                // A broken terminal must not take the trading down with it
+                Err(e) => {
+                    error!("TUI failed, running headless: {:#}", e);
+                    eprintln!("TUI failed, running headless (logs in tui.log): {:#}", e);
+                }
+            }
+        });
+    }
+
     // Wait for all actors (they should run indefinitely)
-    let results = tokio::try_join!(
-        scanner_handle,
-        market_data_handle,
-        strategy_handle,
-        execution_handle
-    );
+    let failed = tokio::select! {
+        Some(why) = shutdown_rx.recv() => {
+            info!("🛑 {}, stopping bot...", why);
+            None
+        }
+        r = scanner_handle => Some(("scanner", r)),
+        r = market_data_handle => Some(("market_data", r)),
+        (r, _, _) = strategy_handle => Some(("strategy", r)),
+        (r, _, _) = position_monitor_handle => Some(("position_monitor", r)),
+        r = &mut execution_handle => Some(("execution", r)),
+        r = pair_trader_handle => Some(("pair_trader", r)),
+    };
+    let Some((actor, result)) = failed else {
+        shutdown(systemd.as_ref(), &execution_tx, execution_handle, notifier_tx, notifier_handle).await;
+        info!("Bot stopped");
+        return Ok(());
+    };
 
-    if let Err(e) = results {
-        error!("Actor task failed: {}", e);
-    }
+    // ✅ CRASH SAFETY: A dead actor means nobody is managing the position
+    handle_actor_failure(
+        actor,
+        result,
+        &config,
+        &client,
+        &owned_positions,
+        &strategy_tx,
+        notifier_tx,
+        notifier_handle,
+    )
+    .await;
 
     info!("Bot terminated");
-    Ok(())
+    std::process::exit(1);
+}
+
+/// Tell systemd, let execution finish an in-flight close, flush alerts
+async fn shutdown(
+    systemd: Option<&SystemdNotifier>,
+    execution_tx: &execution::ExecutionSender,
+    execution_handle: JoinHandle<()>,
+    notifier_tx: MeteredSender<NotifierMessage>,
+    notifier_handle: JoinHandle<()>,
+) {
+    if let Some(systemd) = systemd {
+        systemd.stopping();
+    }
+    if let Err(e) = execution_tx.send(ExecutionMessage::Shutdown).await {
+        warn!("Failed to stop execution: {}", e);
+    } else if tokio::time::timeout(Duration::from_secs(45), execution_handle).await.is_err() {
+        warn!("Execution did not stop in time");
+    }
+    let _ = notifier_tx.try_send(NotifierMessage::Shutdown);
+    if tokio::time::timeout(Duration::from_secs(10), notifier_handle).await.is_err() {
+        warn!("Notifier did not flush in time");
+    }
+}
+
+/// Alert, engage the kill switch, flatten our positions directly via REST, flush alerts
+#[allow(clippy::too_many_arguments)]
+async fn handle_actor_failure(
+    actor: &str,
+    result: Result<(), JoinError>,
+    config: &Config,
+    client: &BybitClient,
+    owned: &crash::OwnedPositions,
+    strategy_tx: &MeteredSender<StrategyMessage>,
+    notifier_tx: MeteredSender<NotifierMessage>,
+    notifier_handle: JoinHandle<()>,
+) {
+    let cause = match result {
+        Ok(()) => "exited unexpectedly".to_string(),
+        Err(e) if e.is_panic() => "panicked".to_string(),
+        Err(e) => format!("failed: {}", e),
+    };
+    error!("💥 Actor task '{}' {}", actor, cause);
+
+    // Kill-switch path: stops new entries and closes via execution if both are still alive
+    if actor != "strategy" {
+        let _ = strategy_tx.try_send(StrategyMessage::Control(ControlCommand::KillSwitch));
+    }
+
+    let mut text = format!("💥 <b>ACTOR {} {}</b>\nBot is shutting down.", actor.to_uppercase(), cause.to_uppercase());
+    if config.crash_flatten_positions {
+        // Give the kill switch a moment, then close whatever is still open ourselves (reduce-only)
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        match tokio::time::timeout(Duration::from_secs(15), crash::emergency_flatten(client, owned)).await {
+            Ok(Ok(report)) if report.is_empty() => text.push_str("\nNo open positions."),
+            Ok(Ok(report)) => {
+                text.push_str("\n<b>Emergency close:</b>\n");
+                text.push_str(&report.join("\n"));
+            }
+            Ok(Err(e)) => {
+                error!("Emergency flatten failed: {}", e);
+                text.push_str("\n⚠️ <b>Could not query positions — CHECK THE EXCHANGE MANUALLY</b>");
+            }
+            Err(_) => {
+                error!("Emergency flatten timed out");
+                text.push_str("\n⚠️ <b>Emergency close timed out — CHECK THE EXCHANGE MANUALLY</b>");
+            }
+        }
+    } else {
+        text.push_str("\n⚠️ Positions were NOT closed (CRASH_FLATTEN_POSITIONS=false)");
+    }
+
+    let _ = notifier_tx.try_send(NotifierMessage::Alert(Alert::critical(text)));
+    let _ = notifier_tx.try_send(NotifierMessage::Shutdown);
+    if tokio::time::timeout(Duration::from_secs(10), notifier_handle).await.is_err() {
+        warn!("Notifier did not flush in time");
+    }
 }
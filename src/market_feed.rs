@@ -0,0 +1,118 @@
+//! Market Data Fan-out
+//!
+//! Republishes the normalized trade ticks and orderbook snapshots of the bot's
+//! own WebSocket connection on a ZeroMQ PUB socket, so other local processes
+//! (notebooks, a second strategy) don't need their own exchange connection.
+//! Two frames per message: topic (`trade.BTCUSDT` / `orderbook.BTCUSDT`, for
+//! SUB prefix filters) and the JSON payload. Needs the `zmq` build feature.
+
+use crate::models::{OrderBookSnapshot, TradeTick};
+use anyhow::Result;
+
+pub enum FeedMessage {
+    Trade(TradeTick),
+    OrderBook(OrderBookSnapshot),
+}
+
+impl FeedMessage {
+    /// (topic frame, payload frame)
+    pub fn encode(&self) -> Result<(String, Vec<u8>)> {
+        Ok(match self {
+            FeedMessage::Trade(tick) => (format!("trade.{}", tick.symbol), serde_json::to_vec(tick)?),
+            FeedMessage::OrderBook(book) => (format!("orderbook.{}", book.symbol), serde_json::to_vec(book)?),
+        })
+    }
+}
+
+#[cfg(feature = "zmq")]
+mod imp {
+    use super::*;
+    use anyhow::Context;
+    use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+    use tracing::{info, warn};
+
+    /// Messages queued for the socket thread; beyond this the feed drops data
+    /// rather than slow down the market data actor
+    const FEED_QUEUE: usize = 4096;
+
+    pub struct MarketFeed {
+        tx: SyncSender<FeedMessage>,
+    }
+
+    impl MarketFeed {
+        /// Bind the PUB socket (e.g. "tcp://127.0.0.1:5556") on its own thread
+        pub fn bind(addr: &str) -> Result<Self> {
+            let context = zmq::Context::new();
+            let socket = context.socket(zmq::PUB).context("failed to create ZeroMQ socket")?;
+            socket.bind(addr).with_context(|| format!("failed to bind ZMQ_PUB_ADDR {}", addr))?;
+            info!("📡 Market data fan-out on {}", addr);
+
+            let (tx, rx) = sync_channel::<FeedMessage>(FEED_QUEUE);
+            std::thread::Builder::new()
+                .name("zmq-feed".to_string())
+                .spawn(move || {
+                    // Ends when the market data actor (the only sender) is gone
+                    for message in rx {
+                        let sent = message
+                            .encode()
+                            .and_then(|(topic, payload)| Ok(socket.send_multipart([topic.into_bytes(), payload], 0)?));
+                        if let Err(e) = sent {
+                            warn!("ZeroMQ publish failed: {:#}", e);
+                        }
+                    }
+                })
+                .context("failed to start ZeroMQ thread")?;
+            Ok(Self { tx })
+        }
+
+        /// Never blocks; drops the message when the socket thread is behind
+        pub fn publish(&self, message: FeedMessage) {
+            if let Err(TrySendError::Disconnected(_)) = self.tx.try_send(message) {
+                warn!("ZeroMQ feed thread is gone");
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "zmq"))]
+mod imp {
+    use super::*;
+
+    pub enum MarketFeed {}
+
+    impl MarketFeed {
+        pub fn bind(addr: &str) -> Result<Self> {
+            anyhow::bail!("ZMQ_PUB_ADDR={} needs a build with `--features zmq`", addr)
+        }
+
+        pub fn publish(&self, _message: FeedMessage) {
+            match *self {}
+        }
+    }
+}
+
+pub use imp::MarketFeed;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Symbol, TradeSide};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_feed_topic_carries_kind_and_symbol() {
+        let tick = TradeTick {
+            symbol: Symbol::from("BTCUSDT"),
+            price: Decimal::new(650001, 1),
+            size: Decimal::new(5, 3),
+            timestamp: 1_700_000_000_000,
+            side: TradeSide::Sell,
+        };
+        let (topic, payload) = FeedMessage::Trade(tick).encode().unwrap();
+        assert_eq!(topic, "trade.BTCUSDT");
+
+        let json: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(json["price"], "65000.1");
+        assert_eq!(json["side"], "Sell");
+    }
+}
@@ -0,0 +1,332 @@
+//! Metrics Registry
+//!
+//! Lock-free counters for the hot path, rendered in Prometheus text format.
+//! Channel stats are registered by `channel::metered_channel`; tokio runtime
+//! and per-task poll stats are read at render time. Endpoint RTTs come from
+//! `endpoints` probes.
+
+use parking_lot::Mutex;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_metrics::TaskMonitor;
+
+/// Per-channel counters (shared between the sender wrapper and the registry)
+pub struct ChannelStats {
+    pub name: &'static str,
+    pub capacity: usize,
+    sent: AtomicU64,
+    dropped: AtomicU64,
+    /// Skipped by the receiver because a newer message for the same key was queued
+    conflated: AtomicU64,
+    /// Current queue depth, refreshed on every send
+    depth: AtomicU64,
+}
+
+impl ChannelStats {
+    pub fn record_sent(&self, depth: usize) {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+        self.depth.store(depth as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_dropped(&self, depth: usize) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        self.depth.store(depth as u64, Ordering::Relaxed);
+    }
+
+    /// Take back a drop that turned out to be a retry (blocking send)
+    pub fn undo_dropped(&self) {
+        self.dropped.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn record_conflated(&self) {
+        self.conflated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn conflated(&self) -> u64 {
+        self.conflated.load(Ordering::Relaxed)
+    }
+
+    pub fn depth(&self) -> u64 {
+        self.depth.load(Ordering::Relaxed)
+    }
+}
+
+/// Strategy state machine counters (see `actors::strategy_state`)
+#[derive(Default)]
+pub struct StateMachineStats {
+    transitions: AtomicU64,
+    /// Transitions outside the table
+    illegal: AtomicU64,
+    /// States held past their limit
+    stuck: AtomicU64,
+}
+
+impl StateMachineStats {
+    pub fn record_transition(&self) {
+        self.transitions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_illegal(&self) {
+        self.illegal.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_stuck(&self) {
+        self.stuck.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn transitions(&self) -> u64 {
+        self.transitions.load(Ordering::Relaxed)
+    }
+
+    pub fn illegal(&self) -> u64 {
+        self.illegal.load(Ordering::Relaxed)
+    }
+
+    pub fn stuck(&self) -> u64 {
+        self.stuck.load(Ordering::Relaxed)
+    }
+}
+
+/// Latest probe of one exchange endpoint
+struct EndpointRtt {
+    kind: &'static str,
+    url: String,
+    /// None = unreachable
+    rtt: Option<Duration>,
+    in_use: bool,
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    channels: Mutex<Vec<Arc<ChannelStats>>>,
+    state_machines: Mutex<Vec<(&'static str, Arc<StateMachineStats>)>>,
+    tasks: Mutex<Vec<(&'static str, TaskMonitor)>>,
+    endpoints: Mutex<Vec<EndpointRtt>>,
+}
+
+impl Metrics {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    pub fn register_channel(&self, name: &'static str, capacity: usize) -> Arc<ChannelStats> {
+        let stats = Arc::new(ChannelStats {
+            name,
+            capacity,
+            sent: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            conflated: AtomicU64::new(0),
+            depth: AtomicU64::new(0),
+        });
+        self.channels.lock().push(stats.clone());
+        stats
+    }
+
+    pub fn channels(&self) -> Vec<Arc<ChannelStats>> {
+        self.channels.lock().clone()
+    }
+
+    /// Counters for the state machine of the strategy (or shard) called `name`
+    pub fn register_state_machine(&self, name: &'static str) -> Arc<StateMachineStats> {
+        let stats = Arc::new(StateMachineStats::default());
+        self.state_machines.lock().push((name, stats.clone()));
+        stats
+    }
+
+    /// Poll-time monitor for a long-running task: `tokio::spawn(monitor.instrument(fut))`
+    pub fn task_monitor(&self, name: &'static str) -> TaskMonitor {
+        let monitor = TaskMonitor::new();
+        self.tasks.lock().push((name, monitor.clone()));
+        monitor
+    }
+
+    /// Latest RTT of a `kind` ("rest", "ws") endpoint, replacing its previous probe
+    pub fn record_endpoint(&self, kind: &'static str, url: &str, rtt: Option<Duration>, in_use: bool) {
+        let mut endpoints = self.endpoints.lock();
+        endpoints.retain(|e| !(e.kind == kind && e.url == url));
+        endpoints.push(EndpointRtt {
+            kind,
+            url: url.to_string(),
+            rtt,
+            in_use,
+        });
+    }
+
+    /// Prometheus text exposition format
+    pub fn render_prometheus(&self) -> String {
+        let channels = self.channels();
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# TYPE channel_sent_total counter");
+        for c in &channels {
+            let _ = writeln!(out, "channel_sent_total{{channel=\"{}\"}} {}", c.name, c.sent());
+        }
+        let _ = writeln!(out, "# TYPE channel_dropped_total counter");
+        for c in &channels {
+            let _ = writeln!(out, "channel_dropped_total{{channel=\"{}\"}} {}", c.name, c.dropped());
+        }
+        let _ = writeln!(out, "# TYPE channel_conflated_total counter");
+        for c in &channels {
+            let _ = writeln!(out, "channel_conflated_total{{channel=\"{}\"}} {}", c.name, c.conflated());
+        }
+        let _ = writeln!(out, "# TYPE channel_depth gauge");
+        for c in &channels {
+            let _ = writeln!(out, "channel_depth{{channel=\"{}\"}} {}", c.name, c.depth());
+        }
+        let _ = writeln!(out, "# TYPE channel_capacity gauge");
+        for c in &channels {
+            let _ = writeln!(out, "channel_capacity{{channel=\"{}\"}} {}", c.name, c.capacity);
+        }
+
+        self.render_state_machines(&mut out);
+        self.render_tasks(&mut out);
+        self.render_endpoints(&mut out);
+        render_runtime(&mut out);
+
+        out
+    }
+
+    fn render_state_machines(&self, out: &mut String) {
+        let machines = self.state_machines.lock().clone();
+
+        let _ = writeln!(out, "# TYPE strategy_state_transitions_total counter");
+        for (name, m) in &machines {
+            let _ = writeln!(out, "strategy_state_transitions_total{{strategy=\"{}\"}} {}", name, m.transitions());
+        }
+        let _ = writeln!(out, "# TYPE strategy_illegal_transitions_total counter");
+        for (name, m) in &machines {
+            let _ = writeln!(out, "strategy_illegal_transitions_total{{strategy=\"{}\"}} {}", name, m.illegal());
+        }
+        let _ = writeln!(out, "# TYPE strategy_stuck_states_total counter");
+        for (name, m) in &machines {
+            let _ = writeln!(out, "strategy_stuck_states_total{{strategy=\"{}\"}} {}", name, m.stuck());
+        }
+    }
+
+    fn render_endpoints(&self, out: &mut String) {
+        let endpoints = self.endpoints.lock();
+        if endpoints.is_empty() {
+            return;
+        }
+        let _ = writeln!(out, "# TYPE endpoint_rtt_seconds gauge");
+        for e in endpoints.iter() {
+            let rtt = e.rtt.map_or("NaN".to_string(), |rtt| format!("{:.6}", rtt.as_secs_f64()));
+            let _ = writeln!(out, "endpoint_rtt_seconds{{kind=\"{}\",endpoint=\"{}\"}} {}", e.kind, e.url, rtt);
+        }
+        let _ = writeln!(out, "# TYPE endpoint_in_use gauge");
+        for e in endpoints.iter() {
+            let _ = writeln!(out, "endpoint_in_use{{kind=\"{}\",endpoint=\"{}\"}} {}", e.kind, e.url, u8::from(e.in_use));
+        }
+    }
+
+    /// Per-task poll stats (slow polls = the task blocked its worker)
+    fn render_tasks(&self, out: &mut String) {
+        let tasks: Vec<_> = self
+            .tasks
+            .lock()
+            .iter()
+            .map(|(name, monitor)| (*name, monitor.cumulative()))
+            .collect();
+
+        let _ = writeln!(out, "# TYPE tokio_task_polls_total counter");
+        for (name, m) in &tasks {
+            let _ = writeln!(out, "tokio_task_polls_total{{task=\"{}\"}} {}", name, m.total_poll_count);
+        }
+        let _ = writeln!(out, "# TYPE tokio_task_slow_polls_total counter");
+        for (name, m) in &tasks {
+            let _ = writeln!(out, "tokio_task_slow_polls_total{{task=\"{}\"}} {}", name, m.total_slow_poll_count);
+        }
+        let _ = writeln!(out, "# TYPE tokio_task_poll_seconds_total counter");
+        for (name, m) in &tasks {
+            let _ = writeln!(
+                out,
+                "tokio_task_poll_seconds_total{{task=\"{}\"}} {:.6}",
+                name,
+                m.total_poll_duration.as_secs_f64()
+            );
+        }
+        let _ = writeln!(out, "# TYPE tokio_task_scheduled_seconds_total counter");
+        for (name, m) in &tasks {
+            let _ = writeln!(
+                out,
+                "tokio_task_scheduled_seconds_total{{task=\"{}\"}} {:.6}",
+                name,
+                m.total_scheduled_duration.as_secs_f64()
+            );
+        }
+    }
+}
+
+/// Runtime-wide stats (stable tokio metrics API)
+fn render_runtime(out: &mut String) {
+    let Ok(handle) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let rt = handle.metrics();
+
+    let _ = writeln!(out, "# TYPE tokio_workers gauge");
+    let _ = writeln!(out, "tokio_workers {}", rt.num_workers());
+    let _ = writeln!(out, "# TYPE tokio_alive_tasks gauge");
+    let _ = writeln!(out, "tokio_alive_tasks {}", rt.num_alive_tasks());
+    let _ = writeln!(out, "# TYPE tokio_global_queue_depth gauge");
+    let _ = writeln!(out, "tokio_global_queue_depth {}", rt.global_queue_depth());
+
+    let _ = writeln!(out, "# TYPE tokio_worker_busy_seconds_total counter");
+    for worker in 0..rt.num_workers() {
+        let _ = writeln!(
+            out,
+            "tokio_worker_busy_seconds_total{{worker=\"{}\"}} {:.6}",
+            worker,
+            rt.worker_total_busy_duration(worker).as_secs_f64()
+        );
+    }
+    let _ = writeln!(out, "# TYPE tokio_worker_park_total counter");
+    for worker in 0..rt.num_workers() {
+        let _ = writeln!(out, "tokio_worker_park_total{{worker=\"{}\"}} {}", worker, rt.worker_park_count(worker));
+    }
+}
+
+/// Minimal HTTP exporter: `GET /metrics` returns the Prometheus text
+pub async fn serve(addr: std::net::SocketAddr, metrics: Arc<Metrics>) -> anyhow::Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("📈 Metrics exporter listening on http://{}/metrics", addr);
+
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let metrics = metrics.clone();
+
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            let n = match stream.read(&mut buf).await {
+                Ok(n) => n,
+                Err(_) => return,
+            };
+            let request = String::from_utf8_lossy(&buf[..n]);
+
+            let response = if request.starts_with("GET /metrics") {
+                let body = metrics.render_prometheus();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+            };
+
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
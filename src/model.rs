@@ -0,0 +1,64 @@
+//! Signal Model
+//!
+//! Optional scoring stage: the signal's feature vector (`SignalFeatures::vector`,
+//! float32 `[1, 9]`) goes through a user-supplied ONNX model and the entry is
+//! only taken when the predicted win probability clears the threshold.
+//! Needs the `onnx` build feature and an onnxruntime library at runtime.
+
+use crate::dataset::SignalFeatures;
+use anyhow::Result;
+use std::path::Path;
+
+#[cfg(feature = "onnx")]
+mod imp {
+    use super::*;
+    use anyhow::{anyhow, Context};
+    use ort::session::Session;
+    use ort::value::Tensor;
+
+    pub struct SignalModel {
+        session: Session,
+    }
+
+    impl SignalModel {
+        pub fn load(path: &Path) -> Result<Self> {
+            let session = Session::builder()
+                .and_then(|builder| builder.commit_from_file(path))
+                .with_context(|| format!("failed to load ONNX model {}", path.display()))?;
+            Ok(Self { session })
+        }
+
+        /// Positive-class probability: the last value of the first float output
+        /// (e.g. `probabilities` of a binary classifier exported without ZipMap)
+        pub fn score(&mut self, features: &SignalFeatures) -> Result<f32> {
+            let input = Tensor::from_array(([1usize, SignalFeatures::NAMES.len()], features.vector().to_vec()))?;
+            let outputs = self.session.run(ort::inputs![input])?;
+            outputs
+                .iter()
+                .find_map(|(_, value)| value.try_extract_tensor::<f32>().ok().and_then(|(_, data)| data.last().copied()))
+                .ok_or_else(|| anyhow!("model produced no float32 output"))
+        }
+    }
+}
+
+#[cfg(not(feature = "onnx"))]
+mod imp {
+    use super::*;
+
+    pub enum SignalModel {}
+
+    impl SignalModel {
+        pub fn load(path: &Path) -> Result<Self> {
+            anyhow::bail!(
+                "SIGNAL_MODEL_PATH={} needs a build with `--features onnx`",
+                path.display()
+            )
+        }
+
+        pub fn score(&mut self, _features: &SignalFeatures) -> Result<f32> {
+            match *self {}
+        }
+    }
+}
+
+pub use imp::SignalModel;
@@ -0,0 +1,230 @@
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// ATR lookback (closed candles)
+pub const ATR_PERIOD: usize = 14;
+/// Fast/slow EMA lookbacks (closed candles)
+pub const EMA_FAST_PERIOD: usize = 20;
+pub const EMA_SLOW_PERIOD: usize = 50;
+
+/// OHLCV bar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    /// Bar open time, ms
+    pub start: i64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+}
+
+impl Candle {
+    fn from_trade(start: i64, price: Decimal, size: Decimal) -> Self {
+        Self {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: size,
+        }
+    }
+
+    /// True range against the previous close
+    fn true_range(&self, prev_close: Decimal) -> Decimal {
+        (self.high - self.low)
+            .max((self.high - prev_close).abs())
+            .max((self.low - prev_close).abs())
+    }
+}
+
+/// Trend vs. range, from EMA separation measured in ATRs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketRegime {
+    Trending,
+    Ranging,
+}
+
+impl fmt::Display for MarketRegime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarketRegime::Trending => write!(f, "TRENDING"),
+            MarketRegime::Ranging => write!(f, "RANGING"),
+        }
+    }
+}
+
+/// Fixed-interval candles built from live trades; the last candle is the one still forming.
+/// Indicators only use closed candles.
+#[derive(Debug, Clone)]
+pub struct CandleSeries {
+    interval_ms: i64,
+    capacity: usize,
+    candles: VecDeque<Candle>,
+}
+
+impl CandleSeries {
+    pub fn new(interval_ms: i64, capacity: usize) -> Self {
+        Self {
+            interval_ms,
+            capacity,
+            candles: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.candles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candles.is_empty()
+    }
+
+    /// Fold a trade into its bar (trades older than the forming bar are ignored)
+    pub fn push_trade(&mut self, price: Decimal, size: Decimal, timestamp: i64) {
+        let start = timestamp - timestamp.rem_euclid(self.interval_ms);
+        match self.candles.back_mut() {
+            Some(last) if last.start == start => {
+                last.high = last.high.max(price);
+                last.low = last.low.min(price);
+                last.close = price;
+                last.volume += size;
+            }
+            Some(last) if last.start > start => {}
+            _ => {
+                self.candles.push_back(Candle::from_trade(start, price, size));
+                if self.candles.len() > self.capacity {
+                    self.candles.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Put historical candles (oldest first) in front of the live ones.
+    /// Live bars win: history only fills the time before the first live bar.
+    pub fn warm_start(&mut self, history: Vec<Candle>) -> usize {
+        let first_live = self.candles.front().map(|c| c.start).unwrap_or(i64::MAX);
+        let mut merged: VecDeque<Candle> = history.into_iter().filter(|c| c.start < first_live).collect();
+        let added = merged.len();
+        merged.extend(self.candles.drain(..));
+        while merged.len() > self.capacity {
+            merged.pop_front();
+        }
+        self.candles = merged;
+        added
+    }
+
+    fn closed(&self) -> impl DoubleEndedIterator<Item = &Candle> + ExactSizeIterator {
+        let closed = self.candles.len().saturating_sub(1);
+        self.candles.iter().take(closed)
+    }
+
+    /// Mean true range over the last `period` closed candles
+    pub fn atr(&self, period: usize) -> Option<Decimal> {
+        let closed: Vec<&Candle> = self.closed().collect();
+        if period == 0 || closed.len() < period + 1 {
+            return None;
+        }
+        let window = &closed[closed.len() - period - 1..];
+        let sum: Decimal = window.windows(2).map(|w| w[1].true_range(w[0].close)).sum();
+        Some(sum / Decimal::from(period))
+    }
+
+    /// EMA of closes, seeded with the SMA of the first `period` closed candles
+    pub fn ema(&self, period: usize) -> Option<Decimal> {
+        if period == 0 || self.closed().len() < period {
+            return None;
+        }
+        let alpha = Decimal::from(2) / Decimal::from(period + 1);
+        let mut closes = self.closed().map(|c| c.close);
+        let seed: Decimal = closes.by_ref().take(period).sum::<Decimal>() / Decimal::from(period);
+        Some(closes.fold(seed, |ema, close| ema + alpha * (close - ema)))
+    }
+
+    /// Trending when the fast/slow EMAs are more than one ATR apart
+    pub fn regime(&self) -> Option<MarketRegime> {
+        let fast = self.ema(EMA_FAST_PERIOD)?;
+        let slow = self.ema(EMA_SLOW_PERIOD)?;
+        let atr = self.atr(ATR_PERIOD)?;
+        Some(if (fast - slow).abs() > atr {
+            MarketRegime::Trending
+        } else {
+            MarketRegime::Ranging
+        })
+    }
+
+    /// ATR as a percentage of the last close
+    pub fn atr_percent(&self) -> Option<f64> {
+        let atr = self.atr(ATR_PERIOD)?;
+        let close = self.candles.back()?.close;
+        if close.is_zero() {
+            return None;
+        }
+        (atr / close * Decimal::from(100)).to_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+
+    fn candle(i: i64, close: i64) -> Candle {
+        let close = Decimal::from(close);
+        Candle {
+            start: i * MINUTE,
+            open: close,
+            high: close + Decimal::ONE,
+            low: close - Decimal::ONE,
+            close,
+            volume: Decimal::ONE,
+        }
+    }
+
+    #[test]
+    fn test_trades_fold_into_minute_bars() {
+        let mut series = CandleSeries::new(MINUTE, 10);
+        series.push_trade(Decimal::from(100), Decimal::ONE, 1_000);
+        series.push_trade(Decimal::from(105), Decimal::ONE, 30_000);
+        series.push_trade(Decimal::from(98), Decimal::ONE, 59_999);
+        series.push_trade(Decimal::from(101), Decimal::ONE, MINUTE);
+        // Late trade for a bar that is already closed
+        series.push_trade(Decimal::from(50), Decimal::ONE, 10_000);
+
+        assert_eq!(series.len(), 2);
+        let first = &series.candles[0];
+        assert_eq!((first.open, first.high, first.low, first.close), (
+            Decimal::from(100), Decimal::from(105), Decimal::from(98), Decimal::from(98)
+        ));
+        assert_eq!(first.volume, Decimal::from(3));
+    }
+
+    #[test]
+    fn test_warm_start_makes_indicators_valid_immediately() {
+        let mut series = CandleSeries::new(MINUTE, 200);
+        // One live bar already forming
+        series.push_trade(Decimal::from(160), Decimal::ONE, 60 * MINUTE + 5);
+        assert!(series.regime().is_none());
+
+        // Steady uptrend: closes 100, 101, ... with a 2-point range → ATR 2
+        let history: Vec<Candle> = (0..61).map(|i| candle(i, 100 + i)).collect();
+        assert_eq!(series.warm_start(history), 60);
+
+        assert_eq!(series.len(), 61);
+        assert_eq!(series.atr(ATR_PERIOD), Some(Decimal::from(2)));
+        assert!(series.ema(EMA_FAST_PERIOD).unwrap() > series.ema(EMA_SLOW_PERIOD).unwrap());
+        assert_eq!(series.regime(), Some(MarketRegime::Trending));
+    }
+
+    #[test]
+    fn test_flat_market_is_ranging() {
+        let mut series = CandleSeries::new(MINUTE, 200);
+        series.warm_start((0..80).map(|i| candle(i, 100 + i % 2)).collect());
+        assert_eq!(series.regime(), Some(MarketRegime::Ranging));
+    }
+}
@@ -1,3 +1,9 @@
+pub mod candle;
+pub mod orderbook;
+pub mod trade;
 pub mod types;
 
+pub use candle::*;
+pub use orderbook::*;
+pub use trade::*;
 pub use types::*;
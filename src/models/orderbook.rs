@@ -0,0 +1,142 @@
+//! Local L2 Order Book
+//!
+//! Maintains price levels from Bybit `orderbook.N` snapshot + delta frames
+//! so the strategy can see depth beyond the best bid/ask.
+
+use crate::models::{OrderBookSnapshot, Symbol};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// Price -> size levels for one symbol
+#[derive(Debug, Clone, Default)]
+pub struct LocalOrderBook {
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl LocalOrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the whole book (Bybit "snapshot" frame)
+    pub fn apply_snapshot(&mut self, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) {
+        self.bids.clear();
+        self.asks.clear();
+        self.apply_delta(bids, asks);
+    }
+
+    /// Update individual levels (Bybit "delta" frame). Size 0 removes the level.
+    pub fn apply_delta(&mut self, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) {
+        Self::apply_levels(&mut self.bids, bids);
+        Self::apply_levels(&mut self.asks, asks);
+    }
+
+    fn apply_levels(side: &mut BTreeMap<Decimal, Decimal>, levels: &[(Decimal, Decimal)]) {
+        for &(price, size) in levels {
+            if size.is_zero() {
+                side.remove(&price);
+            } else {
+                side.insert(price, size);
+            }
+        }
+    }
+
+    /// Keep only the `max_levels` best levels per side (bounds memory on runaway deltas)
+    pub fn trim(&mut self, max_levels: usize) {
+        while self.bids.len() > max_levels {
+            self.bids.pop_first();
+        }
+        while self.asks.len() > max_levels {
+            self.asks.pop_last();
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<(Decimal, Decimal)> {
+        self.bids.iter().next_back().map(|(p, s)| (*p, *s))
+    }
+
+    pub fn best_ask(&self) -> Option<(Decimal, Decimal)> {
+        self.asks.iter().next().map(|(p, s)| (*p, *s))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() || self.asks.is_empty()
+    }
+
+    /// Build a strategy snapshot with up to `levels` price levels per side.
+    /// None if a side is empty or the book is crossed (desynced - wait for the next snapshot).
+    pub fn to_snapshot(&self, symbol: Symbol, timestamp: i64, levels: usize) -> Option<OrderBookSnapshot> {
+        let (best_bid, bid_size) = self.best_bid()?;
+        let (best_ask, ask_size) = self.best_ask()?;
+        if best_bid >= best_ask {
+            return None;
+        }
+
+        let bids = self.bids.iter().rev().take(levels).map(|(p, s)| (*p, *s)).collect();
+        let asks = self.asks.iter().take(levels).map(|(p, s)| (*p, *s)).collect();
+
+        Some(
+            OrderBookSnapshot::new(symbol, timestamp, best_bid, best_ask, bid_size, ask_size)
+                .with_depth(bids, asks),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderSide;
+
+    fn d(v: &str) -> Decimal {
+        v.parse().unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_then_delta() {
+        let mut book = LocalOrderBook::new();
+        book.apply_snapshot(
+            &[(d("99"), d("5")), (d("98"), d("10"))],
+            &[(d("101"), d("4")), (d("102"), d("8"))],
+        );
+        assert_eq!(book.best_bid(), Some((d("99"), d("5"))));
+        assert_eq!(book.best_ask(), Some((d("101"), d("4"))));
+
+        // Remove best ask, add a better bid
+        book.apply_delta(&[(d("100"), d("1"))], &[(d("101"), d("0"))]);
+        assert_eq!(book.best_bid(), Some((d("100"), d("1"))));
+        assert_eq!(book.best_ask(), Some((d("102"), d("8"))));
+    }
+
+    #[test]
+    fn test_depth_within_spread_multiple() {
+        let mut book = LocalOrderBook::new();
+        book.apply_snapshot(
+            &[(d("99"), d("5")), (d("98"), d("10")), (d("90"), d("1000"))],
+            &[(d("100"), d("4")), (d("101"), d("8")), (d("110"), d("1000"))],
+        );
+        let snapshot = book.to_snapshot(Symbol::from("TESTUSDT"), 0, 50).unwrap();
+
+        // Spread = 1, so 2 spreads reaches 102 on the ask side: 4 + 8
+        assert_eq!(snapshot.depth_within(OrderSide::Buy, d("2")), d("12"));
+        assert_eq!(snapshot.depth_within(OrderSide::Sell, d("2")), d("15"));
+
+        // 12 visible vs 5 * 2.0 needed -> liquid; 5 * 3.0 -> not
+        assert!(snapshot.is_liquid(OrderSide::Buy, d("5"), 2.0, 2.0));
+        assert!(!snapshot.is_liquid(OrderSide::Buy, d("5"), 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_crossed_book_yields_no_snapshot_and_trim_keeps_best() {
+        let mut book = LocalOrderBook::new();
+        book.apply_snapshot(&[(d("99"), d("1")), (d("98"), d("1"))], &[(d("100"), d("1")), (d("101"), d("1"))]);
+        book.apply_delta(&[(d("100.5"), d("1"))], &[]);
+        assert!(book.to_snapshot(Symbol::from("TESTUSDT"), 0, 50).is_none());
+
+        book.apply_delta(&[(d("100.5"), d("0"))], &[]);
+        book.trim(1);
+        let snapshot = book.to_snapshot(Symbol::from("TESTUSDT"), 0, 50).unwrap();
+        assert_eq!(snapshot.bids, vec![(d("99"), d("1"))]);
+        assert_eq!(snapshot.asks, vec![(d("100"), d("1"))]);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::config::TradingMode;
+use crate::models::{OrderSide, PositionSide, Symbol};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Execution report for a filled order (entry or close)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub symbol: Symbol,
+    pub order_id: String,
+    pub side: OrderSide,
+    pub qty: Decimal,
+    pub avg_price: Decimal,
+    /// Fee paid in quote currency (negative = rebate)
+    pub fee: Decimal,
+}
+
+/// Why a position was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExitReason {
+    TakeProfit,
+    StopLoss,
+    TrailingStop,
+    Breakeven,
+    FlashCrash,
+    TimeExit,
+    SymbolSwitch,
+    Manual,
+    /// Price came within LIQ_BUFFER_PERCENT of the liquidation price
+    LiquidationGuard,
+    /// Exchange position differed from the strategy's (POSITION_DRIFT_ACTION=flatten)
+    PositionDrift,
+    Unknown,
+}
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ExitReason::TakeProfit => "TAKE PROFIT",
+            ExitReason::StopLoss => "STOP LOSS",
+            ExitReason::TrailingStop => "TRAILING STOP",
+            ExitReason::Breakeven => "BREAKEVEN",
+            ExitReason::FlashCrash => "FLASH CRASH",
+            ExitReason::TimeExit => "TIME EXIT",
+            ExitReason::SymbolSwitch => "SYMBOL SWITCH",
+            ExitReason::Manual => "MANUAL",
+            ExitReason::LiquidationGuard => "LIQUIDATION GUARD",
+            ExitReason::PositionDrift => "POSITION DRIFT",
+            ExitReason::Unknown => "UNKNOWN",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Completed round-trip trade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub symbol: Symbol,
+    pub side: PositionSide,
+    pub qty: Decimal,
+    pub entry_price: Decimal,
+    pub exit_price: Decimal,
+    pub gross_pnl: Decimal,
+    pub fees: Decimal,
+    pub net_pnl: Decimal,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: DateTime<Utc>,
+    pub hold_secs: u64,
+    pub mode: TradingMode,
+    pub exit_reason: ExitReason,
+    /// Position leverage (1 if unknown)
+    pub leverage: Decimal,
+    /// Gross price move in the trade's direction, %
+    pub pnl_percent: f64,
+    /// Net PnL relative to the initial margin (entry notional / leverage), %
+    pub roe_percent: f64,
+    /// Ticks that confirmed the entry signal
+    #[serde(default)]
+    pub confirmations: Option<u8>,
+    /// Symbol's 24h price change at entry (0.25 = +25%)
+    #[serde(default)]
+    pub price_change_24h: Option<f64>,
+    /// 1m ATR at entry, % of price
+    #[serde(default)]
+    pub entry_atr_percent: Option<f64>,
+    /// Entry fill vs. the mid price the order was sized at, bps (positive = worse)
+    #[serde(default)]
+    pub entry_slippage_bps: Option<f64>,
+}
+
+/// Signal context captured when the entry order is sent (for per-mode attribution)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntryContext {
+    pub confirmations: u8,
+    pub price_change_24h: Option<f64>,
+    pub atr_percent: Option<f64>,
+    /// Mid price the entry order was sized at
+    pub ref_price: Option<Decimal>,
+}
+
+impl TradeRecord {
+    /// Gross PnL of a round trip in quote currency
+    pub fn gross_pnl(side: PositionSide, qty: Decimal, entry_price: Decimal, exit_price: Decimal) -> Decimal {
+        match side {
+            PositionSide::Long => (exit_price - entry_price) * qty,
+            PositionSide::Short => (entry_price - exit_price) * qty,
+        }
+    }
+
+    /// (price move %, ROE %) of a round trip: gross PnL over the entry notional,
+    /// net PnL over the margin that notional needed at `leverage`
+    pub fn returns(gross_pnl: Decimal, net_pnl: Decimal, qty: Decimal, entry_price: Decimal, leverage: Decimal) -> (f64, f64) {
+        let notional = qty * entry_price;
+        if notional.is_zero() {
+            return (0.0, 0.0);
+        }
+        let margin = notional / leverage.max(Decimal::ONE);
+        let percent = |pnl: Decimal, base: Decimal| (pnl / base * Decimal::from(100)).to_f64().unwrap_or(0.0);
+        (percent(gross_pnl, notional), percent(net_pnl, margin))
+    }
+
+    /// Fill price vs. the expected price, bps; positive when the fill was worse for `side`
+    pub fn slippage_bps(side: OrderSide, expected: Decimal, filled: Decimal) -> f64 {
+        if expected.is_zero() {
+            return 0.0;
+        }
+        let adverse = match side {
+            OrderSide::Buy => filled - expected,
+            OrderSide::Sell => expected - filled,
+        };
+        (adverse / expected * Decimal::from(10_000)).to_f64().unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_returns_report_price_move_and_leveraged_roe() {
+        // Long 2 @ 100 → 101 at 10x, $0.20 fees: +1% price, $1.80 net on $20 margin
+        let gross = TradeRecord::gross_pnl(PositionSide::Long, Decimal::from(2), Decimal::from(100), Decimal::from(101));
+        let net = gross - Decimal::new(2, 1);
+        let (pnl_percent, roe_percent) = TradeRecord::returns(gross, net, Decimal::from(2), Decimal::from(100), Decimal::from(10));
+        assert!((pnl_percent - 1.0).abs() < 1e-9);
+        assert!((roe_percent - 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_slippage_is_positive_when_fill_is_worse() {
+        let mid = Decimal::from(100);
+        assert!((TradeRecord::slippage_bps(OrderSide::Buy, mid, Decimal::new(10001, 2)) - 1.0).abs() < 1e-9);
+        assert!((TradeRecord::slippage_bps(OrderSide::Sell, mid, Decimal::new(10001, 2)) + 1.0).abs() < 1e-9);
+    }
+}
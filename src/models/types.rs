@@ -1,11 +1,28 @@
+use crate::config::PriceSource;
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
 use std::fmt;
+use std::sync::Arc;
 
-/// Core trading symbol representation
+/// Core trading symbol representation. Shared, so the per-tick clones on the
+/// market-data path are a refcount bump rather than an allocation.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct Symbol(pub String);
+pub struct Symbol(pub Arc<str>);
+
+impl Symbol {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Lets symbol-keyed maps be queried with a `&str` straight from a frame
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
 
 impl fmt::Display for Symbol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -15,13 +32,13 @@ impl fmt::Display for Symbol {
 
 impl From<String> for Symbol {
     fn from(s: String) -> Self {
-        Symbol(s)
+        Symbol(s.into())
     }
 }
 
 impl From<&str> for Symbol {
     fn from(s: &str) -> Self {
-        Symbol(s.to_string())
+        Symbol(s.into())
     }
 }
 
@@ -35,7 +52,7 @@ pub struct VolatilityScore {
 }
 
 /// Real-time orderbook snapshot
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderBookSnapshot {
     pub symbol: Symbol,
     pub timestamp: i64,
@@ -45,6 +62,10 @@ pub struct OrderBookSnapshot {
     pub ask_size: Decimal,
     pub mid_price: Decimal,
     pub spread_bps: f64, // basis points
+    /// Visible bid levels (price, size), best first
+    pub bids: Vec<(Decimal, Decimal)>,
+    /// Visible ask levels (price, size), best first
+    pub asks: Vec<(Decimal, Decimal)>,
 }
 
 impl OrderBookSnapshot {
@@ -76,12 +97,87 @@ impl OrderBookSnapshot {
             ask_size,
             mid_price,
             spread_bps,
+            // Top of book only until full depth is attached via with_depth()
+            bids: vec![(best_bid, bid_size)],
+            asks: vec![(best_ask, ask_size)],
+        }
+    }
+
+    /// Attach full visible depth (levels must be sorted best first)
+    pub fn with_depth(mut self, bids: Vec<(Decimal, Decimal)>, asks: Vec<(Decimal, Decimal)>) -> Self {
+        if !bids.is_empty() {
+            self.bids = bids;
+        }
+        if !asks.is_empty() {
+            self.asks = asks;
+        }
+        self
+    }
+
+    /// Size-weighted microprice: bid * ask_size + ask * bid_size over both sizes,
+    /// so a heavy bid pulls it toward the ask (mid when the touch is empty)
+    pub fn microprice(&self) -> Decimal {
+        let total = self.bid_size + self.ask_size;
+        if total <= Decimal::ZERO {
+            return self.mid_price;
+        }
+        (self.best_bid * self.ask_size + self.best_ask * self.bid_size) / total
+    }
+
+    /// Price of the book per `PRICE_SOURCE`
+    pub fn price(&self, source: PriceSource) -> Decimal {
+        match source {
+            PriceSource::Mid => self.mid_price,
+            PriceSource::Microprice => self.microprice(),
         }
     }
 
-    /// Check if orderbook is liquid enough for market orders
-    pub fn is_liquid(&self) -> bool {
-        self.spread_bps < 10.0 && self.bid_size > Decimal::from(100) && self.ask_size > Decimal::from(100)
+    /// Absolute spread (ask - bid), never negative
+    pub fn spread(&self) -> Decimal {
+        (self.best_ask - self.best_bid).max(Decimal::ZERO)
+    }
+
+    /// Visible size an order on `side` would consume, counting only levels
+    /// within `max_distance` of the touch (Buy eats asks, Sell eats bids)
+    pub fn depth_within(&self, side: OrderSide, max_distance: Decimal) -> Decimal {
+        match side {
+            OrderSide::Buy => {
+                let limit = self.best_ask + max_distance;
+                self.asks
+                    .iter()
+                    .take_while(|(price, _)| *price <= limit)
+                    .map(|(_, size)| *size)
+                    .sum()
+            }
+            OrderSide::Sell => {
+                let limit = self.best_bid - max_distance;
+                self.bids
+                    .iter()
+                    .take_while(|(price, _)| *price >= limit)
+                    .map(|(_, size)| *size)
+                    .sum()
+            }
+        }
+    }
+
+    /// Visible book imbalance: (bids - asks) / (bids + asks), from -1 (all asks) to 1 (all bids)
+    pub fn imbalance(&self) -> f64 {
+        let bids: Decimal = self.bids.iter().map(|(_, size)| *size).sum();
+        let asks: Decimal = self.asks.iter().map(|(_, size)| *size).sum();
+        let total = bids + asks;
+        if total.is_zero() {
+            return 0.0;
+        }
+        ((bids - asks) / total).to_f64().unwrap_or(0.0)
+    }
+
+    /// ✅ Order-size-aware liquidity check
+    /// Depth on the consumed side within `spread_multiple` spreads of the touch
+    /// must cover `qty` at least `depth_factor` times over.
+    pub fn is_liquid(&self, side: OrderSide, qty: Decimal, spread_multiple: f64, depth_factor: f64) -> bool {
+        let multiple = Decimal::from_f64(spread_multiple).unwrap_or(Decimal::ONE);
+        let factor = Decimal::from_f64(depth_factor).unwrap_or(Decimal::ONE);
+        self.depth_within(side, self.spread() * multiple) >= qty * factor
     }
 }
 
@@ -95,6 +191,47 @@ pub struct TradeTick {
     pub side: TradeSide,
 }
 
+/// `tickers.{symbol}` push: a snapshot, then deltas carrying only the fields that changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TickerUpdate {
+    pub symbol: Symbol,
+    pub last_price: Option<Decimal>,
+    pub mark_price: Option<Decimal>,
+    pub index_price: Option<Decimal>,
+}
+
+/// f64 copy of a tick for indicator math (FAST_MATH); orders keep using Decimal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FastTick {
+    pub price: f64,
+    pub size: f64,
+}
+
+impl From<&TradeTick> for FastTick {
+    fn from(tick: &TradeTick) -> Self {
+        Self {
+            price: tick.price.to_f64().unwrap_or(0.0),
+            size: tick.size.to_f64().unwrap_or(0.0),
+        }
+    }
+}
+
+/// Volume-weighted average price (None without volume)
+pub fn vwap<'a>(ticks: impl Iterator<Item = &'a TradeTick>) -> Option<Decimal> {
+    let (value, volume) = ticks.fold((Decimal::ZERO, Decimal::ZERO), |(value, volume), tick| {
+        (value + tick.price * tick.size, volume + tick.size)
+    });
+    (!volume.is_zero()).then(|| value / volume)
+}
+
+/// Same as `vwap` in f64
+pub fn vwap_fast<'a>(ticks: impl Iterator<Item = &'a FastTick>) -> Option<f64> {
+    let (value, volume) = ticks.fold((0.0, 0.0), |(value, volume), tick| {
+        (value + tick.price * tick.size, volume + tick.size)
+    });
+    (volume > 0.0).then(|| value / volume)
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TradeSide {
     Buy,
@@ -102,7 +239,7 @@ pub enum TradeSide {
 }
 
 /// Position state
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub symbol: Symbol,
     pub side: PositionSide,
@@ -111,6 +248,16 @@ pub struct Position {
     pub current_price: Decimal,
     pub unrealized_pnl: Decimal,
     pub stop_loss: Option<Decimal>,
+    /// Exchange leverage of the position (1 if unknown)
+    pub leverage: Decimal,
+    /// Exchange-estimated liquidation price (None when there is none)
+    pub liq_price: Option<Decimal>,
+    /// Realized PnL of the current holding (partial closes, fees, funding), USD
+    #[serde(default)]
+    pub realized_pnl: Decimal,
+    /// Realized PnL of every position held on the symbol, USD
+    #[serde(default)]
+    pub cum_realized_pnl: Decimal,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -136,6 +283,30 @@ impl Position {
             .unwrap_or(0.0)
     }
 
+    /// Return on the initial margin: price move × leverage
+    pub fn roe_percent(&self) -> f64 {
+        self.pnl_percent() * self.leverage.to_f64().unwrap_or(1.0)
+    }
+
+    /// PnL of the current holding so far: unrealized plus what partial closes and fees realized, USD
+    pub fn net_pnl(&self) -> Decimal {
+        self.unrealized_pnl + self.realized_pnl
+    }
+
+    /// Distance from the current price to the liquidation price, % of current price
+    pub fn liquidation_distance_percent(&self) -> Option<f64> {
+        let liq_price = self.liq_price.filter(|p| *p > Decimal::ZERO)?;
+        if self.current_price.is_zero() {
+            return None;
+        }
+        // Negative once price has crossed the liquidation price
+        let distance = match self.side {
+            PositionSide::Long => self.current_price - liq_price,
+            PositionSide::Short => liq_price - self.current_price,
+        };
+        (distance / self.current_price * Decimal::from(100)).to_f64()
+    }
+
     pub fn should_stop_loss(&self) -> bool {
         if let Some(sl) = self.stop_loss {
             match self.side {
@@ -162,6 +333,17 @@ pub struct Order {
     pub qty_step: Option<Decimal>,
     /// Tick size for price rounding (e.g., "0.0001")
     pub tick_size: Option<Decimal>,
+    /// Expected fill price of a market order (mid at signal time), for margin checks
+    pub ref_price: Option<Decimal>,
+    /// Deliberate repeat of a recent identical entry: skips the duplicate-order guard
+    #[serde(default)]
+    pub allow_duplicate: bool,
+    /// Entry only: exchange-side stop-loss this far from the fill price, % (None = no hard stop)
+    #[serde(default)]
+    pub hard_stop_percent: Option<f64>,
+    /// Position the order acts on: 0 in one-way mode, 1 (long) / 2 (short) in hedge mode
+    #[serde(default)]
+    pub position_idx: u8,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -274,3 +456,93 @@ impl<T: Clone> RingBuffer<T> {
         self.size == 0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_roe_scales_price_move_by_leverage() {
+        let position = Position {
+            symbol: Symbol::from("BTCUSDT"),
+            side: PositionSide::Short,
+            size: Decimal::ONE,
+            entry_price: Decimal::from(100),
+            current_price: Decimal::from(99),
+            unrealized_pnl: Decimal::ONE,
+            stop_loss: None,
+            leverage: Decimal::from(10),
+            liq_price: Some(Decimal::from(108)),
+            realized_pnl: Decimal::new(-25, 2),
+            cum_realized_pnl: Decimal::from(3),
+        };
+        assert!((position.pnl_percent() - 1.0).abs() < 1e-9);
+        assert!((position.roe_percent() - 10.0).abs() < 1e-9);
+        // The entry fee already realized comes off the unrealized gain
+        assert_eq!(position.net_pnl(), Decimal::new(75, 2));
+
+        // Short @ 99 with liquidation at 108 → 9.09% away
+        let distance = position.liquidation_distance_percent().unwrap();
+        assert!((distance - 9.0909).abs() < 1e-3);
+        assert!(Position { liq_price: None, ..position }.liquidation_distance_percent().is_none());
+    }
+
+    #[test]
+    fn test_microprice_leans_toward_the_thin_side() {
+        let book = OrderBookSnapshot::new(Symbol::from("BTCUSDT"), 0, Decimal::from(100), Decimal::from(102), Decimal::from(3), Decimal::ONE);
+        // Heavy bid: the ask is the side about to go
+        assert_eq!(book.microprice(), Decimal::new(1015, 1));
+        assert_eq!(book.price(PriceSource::Mid), Decimal::from(101));
+        assert_eq!(book.price(PriceSource::Microprice), Decimal::new(1015, 1));
+
+        let empty = OrderBookSnapshot::new(Symbol::from("BTCUSDT"), 0, Decimal::from(100), Decimal::from(102), Decimal::ZERO, Decimal::ZERO);
+        assert_eq!(empty.microprice(), Decimal::from(101));
+    }
+
+    #[test]
+    fn test_fast_vwap_matches_decimal_vwap() {
+        let ticks: Vec<TradeTick> = [(650001, 5), (650105, 12), (649980, 3)]
+            .iter()
+            .map(|&(price, size)| TradeTick {
+                symbol: Symbol::from("BTCUSDT"),
+                price: Decimal::new(price, 1),
+                size: Decimal::new(size, 3),
+                timestamp: 0,
+                side: TradeSide::Buy,
+            })
+            .collect();
+        let fast: Vec<FastTick> = ticks.iter().map(FastTick::from).collect();
+
+        let exact = vwap(ticks.iter()).unwrap().to_f64().unwrap();
+        let approx = vwap_fast(fast.iter()).unwrap();
+        assert!((exact - approx).abs() / exact < 1e-12, "{} vs {}", exact, approx);
+        assert!(vwap(std::iter::empty()).is_none());
+        assert!(vwap_fast(std::iter::empty()).is_none());
+    }
+
+    proptest! {
+        /// RingBuffer behaves like "the last `capacity` pushed items" in every view
+        #[test]
+        fn prop_ring_buffer_matches_model(
+            capacity in 1usize..32,
+            items in proptest::collection::vec(any::<i32>(), 0..100),
+        ) {
+            let mut ring = RingBuffer::new(capacity);
+            for (i, item) in items.iter().enumerate() {
+                ring.push(*item);
+
+                let pushed = &items[..=i];
+                let expected = &pushed[pushed.len().saturating_sub(capacity)..];
+                prop_assert_eq!(ring.len(), expected.len());
+                prop_assert_eq!(ring.last(), expected.last());
+                prop_assert_eq!(ring.iter().copied().collect::<Vec<_>>(), expected.to_vec());
+                prop_assert_eq!(
+                    ring.iter_rev().copied().collect::<Vec<_>>(),
+                    expected.iter().rev().copied().collect::<Vec<_>>()
+                );
+            }
+            prop_assert_eq!(ring.is_empty(), items.is_empty());
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Alert Types
+//!
+//! Everything the bot wants a human to see goes through `Alert`.
+//! Trade fills get their own level so they can be routed/filtered separately.
+
+use crate::config::TradingMode;
+use crate::models::{Fill, OrderSide, TradeRecord};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Alert severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AlertLevel {
+    Info,
+    Success,
+    /// Periodic "still alive" status (never batched or held back)
+    Heartbeat,
+    /// Entry/exit fill notifications
+    Trade,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl AlertLevel {
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            AlertLevel::Info => "ℹ️",
+            AlertLevel::Success => "✅",
+            AlertLevel::Heartbeat => "💓",
+            AlertLevel::Trade => "💱",
+            AlertLevel::Warning => "⚠️",
+            AlertLevel::Error => "❌",
+            AlertLevel::Critical => "🚨",
+        }
+    }
+}
+
+impl fmt::Display for AlertLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            AlertLevel::Info => "INFO",
+            AlertLevel::Success => "SUCCESS",
+            AlertLevel::Heartbeat => "HEARTBEAT",
+            AlertLevel::Trade => "TRADE",
+            AlertLevel::Warning => "WARNING",
+            AlertLevel::Error => "ERROR",
+            AlertLevel::Critical => "CRITICAL",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for AlertLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "INFO" => Ok(AlertLevel::Info),
+            "SUCCESS" => Ok(AlertLevel::Success),
+            "HEARTBEAT" => Ok(AlertLevel::Heartbeat),
+            "TRADE" => Ok(AlertLevel::Trade),
+            "WARNING" | "WARN" => Ok(AlertLevel::Warning),
+            "ERROR" => Ok(AlertLevel::Error),
+            "CRITICAL" => Ok(AlertLevel::Critical),
+            _ => Err(anyhow::anyhow!("Invalid alert level: '{}'", s)),
+        }
+    }
+}
+
+/// A single human-facing notification (text is Telegram HTML)
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub level: AlertLevel,
+    pub text: String,
+}
+
+impl Alert {
+    pub fn new(level: AlertLevel, text: impl Into<String>) -> Self {
+        Self {
+            level,
+            text: text.into(),
+        }
+    }
+
+    pub fn info(text: impl Into<String>) -> Self {
+        Self::new(AlertLevel::Info, text)
+    }
+
+    pub fn success(text: impl Into<String>) -> Self {
+        Self::new(AlertLevel::Success, text)
+    }
+
+    pub fn warning(text: impl Into<String>) -> Self {
+        Self::new(AlertLevel::Warning, text)
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self::new(AlertLevel::Error, text)
+    }
+
+    pub fn critical(text: impl Into<String>) -> Self {
+        Self::new(AlertLevel::Critical, text)
+    }
+
+    /// Entry fill notification
+    pub fn trade_opened(fill: &Fill, mode: TradingMode) -> Self {
+        let side = match fill.side {
+            OrderSide::Buy => "LONG",
+            OrderSide::Sell => "SHORT",
+        };
+        Self::new(
+            AlertLevel::Trade,
+            format!(
+                "🟢 <b>ENTRY {} {}</b>\n\
+                 Size: {} @ {}\n\
+                 Fee: ${:.4}\n\
+                 Mode: {}",
+                side,
+                escape_html(&fill.symbol.0),
+                fill.qty,
+                fill.avg_price,
+                fill.fee,
+                mode
+            ),
+        )
+    }
+
+    /// Exit fill notification with full round-trip context
+    pub fn trade_closed(trade: &TradeRecord) -> Self {
+        let icon = if trade.net_pnl.is_sign_negative() { "🔴" } else { "💰" };
+        Self::new(
+            AlertLevel::Trade,
+            format!(
+                "{} <b>EXIT {:?} {}</b> — {}\n\
+                 Size: {}\n\
+                 Entry: {} → Exit: {}\n\
+                 Net PnL: <b>${:.4}</b> (gross ${:.4}, fees ${:.4})\n\
+                 Move: {:+.2}% | ROE: <b>{:+.2}%</b> @ {}x\n\
+                 Hold: {}\n\
+                 Mode: {}",
+                icon,
+                trade.side,
+                escape_html(&trade.symbol.0),
+                trade.exit_reason,
+                trade.qty,
+                trade.entry_price,
+                trade.exit_price,
+                trade.net_pnl,
+                trade.gross_pnl,
+                trade.fees,
+                trade.pnl_percent,
+                trade.roe_percent,
+                trade.leverage,
+                format_duration(trade.hold_secs),
+                trade.mode
+            ),
+        )
+    }
+
+    /// Text as delivered: level emoji + body
+    pub fn render(&self) -> String {
+        format!("{} {}", self.level.emoji(), self.text)
+    }
+}
+
+/// Escape text for Telegram HTML parse mode
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Human-readable duration, e.g. "4m 12s"
+pub fn format_duration(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
@@ -0,0 +1,11 @@
+pub mod alert;
+pub mod quiet_hours;
+pub mod routing;
+pub mod telegram;
+pub mod throttle;
+
+pub use alert::*;
+pub use quiet_hours::*;
+pub use routing::*;
+pub use telegram::*;
+pub use throttle::*;
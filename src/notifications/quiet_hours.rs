@@ -0,0 +1,100 @@
+//! Alert Quiet Hours
+//!
+//! Daily UTC window (may wrap midnight, e.g. "23:00-07:00") during which
+//! Info/Success alerts are held back; Warning and above still page immediately.
+
+use anyhow::{Context, Result};
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// What happens to low-priority alerts during quiet hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum QuietHoursMode {
+    /// Drop them
+    Suppress,
+    /// Queue them for a morning digest when quiet hours end
+    Digest,
+}
+
+impl FromStr for QuietHoursMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "SUPPRESS" => Ok(QuietHoursMode::Suppress),
+            "DIGEST" => Ok(QuietHoursMode::Digest),
+            _ => Err(anyhow::anyhow!(
+                "Invalid QUIET_HOURS_MODE: '{}'. Must be 'SUPPRESS' or 'DIGEST'",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// True if `time` (UTC) falls inside the window
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            // Wraps midnight
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for QuietHours {
+    type Err = anyhow::Error;
+
+    /// "HH:MM-HH:MM"
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, end) = s
+            .split_once('-')
+            .with_context(|| format!("Quiet hours '{}' must look like HH:MM-HH:MM", s))?;
+        let parse = |t: &str| {
+            NaiveTime::parse_from_str(t.trim(), "%H:%M")
+                .with_context(|| format!("Invalid time '{}' in quiet hours", t.trim()))
+        };
+        let quiet = QuietHours {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if quiet.start == quiet.end {
+            anyhow::bail!("Quiet hours '{}' has zero length", s);
+        }
+        Ok(quiet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_quiet_hours_wrapping_midnight() {
+        let quiet: QuietHours = "23:00-07:00".parse().unwrap();
+        assert!(quiet.contains(t(23, 30)));
+        assert!(quiet.contains(t(3, 0)));
+        assert!(!quiet.contains(t(7, 0)));
+        assert!(!quiet.contains(t(12, 0)));
+
+        let day: QuietHours = "12:00-13:30".parse().unwrap();
+        assert!(day.contains(t(12, 45)));
+        assert!(!day.contains(t(14, 0)));
+
+        assert!("25:00-07:00".parse::<QuietHours>().is_err());
+        assert!("07:00".parse::<QuietHours>().is_err());
+    }
+}
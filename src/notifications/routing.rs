@@ -0,0 +1,91 @@
+//! Alert Routing
+//!
+//! Maps alert levels to Telegram chats, e.g. errors to an ops group and
+//! trade fills to a personal chat. Levels without a route fall back to
+//! `TELEGRAM_CHAT_ID`.
+//!
+//! Route spec format (`TELEGRAM_ROUTES`):
+//! `ERROR,CRITICAL=-100111;TRADE=12345,-100111`
+
+use crate::notifications::AlertLevel;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+
+/// Parse a route spec into level -> chat IDs
+pub fn parse_alert_routes(spec: &str) -> Result<HashMap<AlertLevel, Vec<String>>> {
+    let mut routes: HashMap<AlertLevel, Vec<String>> = HashMap::new();
+
+    for rule in spec.split(';').map(str::trim).filter(|r| !r.is_empty()) {
+        let (levels, chats) = rule
+            .split_once('=')
+            .with_context(|| format!("Route '{}' must look like LEVEL[,LEVEL]=CHAT[,CHAT]", rule))?;
+
+        let chats: Vec<String> = chats
+            .split(',')
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect();
+        if chats.is_empty() {
+            anyhow::bail!("Route '{}' has no chat IDs", rule);
+        }
+
+        for level in levels.split(',') {
+            let level: AlertLevel = level.parse()?;
+            let targets = routes.entry(level).or_default();
+            for chat in &chats {
+                if !targets.contains(chat) {
+                    targets.push(chat.clone());
+                }
+            }
+        }
+    }
+
+    Ok(routes)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AlertRouter {
+    default_chat: Option<String>,
+    routes: HashMap<AlertLevel, Vec<String>>,
+}
+
+impl AlertRouter {
+    pub fn new(default_chat: Option<String>, routes: HashMap<AlertLevel, Vec<String>>) -> Self {
+        Self { default_chat, routes }
+    }
+
+    /// Chats that should receive an alert of this level
+    pub fn recipients(&self, level: AlertLevel) -> Vec<&str> {
+        match self.routes.get(&level) {
+            Some(chats) => chats.iter().map(String::as_str).collect(),
+            None => self.default_chat.iter().map(String::as_str).collect(),
+        }
+    }
+
+    /// True if at least one chat is configured
+    pub fn has_recipients(&self) -> bool {
+        self.default_chat.is_some() || !self.routes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes_by_level_with_default_fallback() {
+        let routes = parse_alert_routes("ERROR,CRITICAL=-100111; TRADE=12345,-100111").unwrap();
+        let router = AlertRouter::new(Some("999".to_string()), routes);
+
+        assert_eq!(router.recipients(AlertLevel::Error), vec!["-100111"]);
+        assert_eq!(router.recipients(AlertLevel::Trade), vec!["12345", "-100111"]);
+        assert_eq!(router.recipients(AlertLevel::Info), vec!["999"]);
+    }
+
+    #[test]
+    fn test_invalid_route_spec_is_rejected() {
+        assert!(parse_alert_routes("FATAL=1").is_err());
+        assert!(parse_alert_routes("ERROR").is_err());
+        assert!(parse_alert_routes("ERROR=").is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Telegram Bot API Client
+//!
+//! Minimal Bot API wrapper used by the NotifierActor and the command listener.
+//! The chat is chosen per message so one bot can serve several recipients.
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+#[derive(Clone)]
+pub struct TelegramClient {
+    client: Client,
+    bot_token: String,
+}
+
+impl TelegramClient {
+    pub fn new(bot_token: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            bot_token,
+        }
+    }
+
+    /// POST /bot{token}/sendMessage (HTML parse mode)
+    pub async fn send_message(&self, chat_id: &str, text: &str) -> Result<()> {
+        self.call::<Value>(
+            "sendMessage",
+            json!({
+                "chat_id": chat_id,
+                "text": text,
+                "parse_mode": "HTML",
+                "disable_web_page_preview": true,
+            }),
+            None,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// sendMessage with one row of inline keyboard buttons: (label, callback_data)
+    pub async fn send_with_buttons(&self, chat_id: &str, text: &str, buttons: &[(&str, &str)]) -> Result<()> {
+        let row: Vec<Value> = buttons
+            .iter()
+            .map(|(label, data)| json!({ "text": label, "callback_data": data }))
+            .collect();
+
+        self.call::<Value>(
+            "sendMessage",
+            json!({
+                "chat_id": chat_id,
+                "text": text,
+                "parse_mode": "HTML",
+                "reply_markup": { "inline_keyboard": [row] },
+            }),
+            None,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Replace the text of a sent message (also removes its inline keyboard)
+    pub async fn edit_message_text(&self, chat_id: i64, message_id: i64, text: &str) -> Result<()> {
+        self.call::<Value>(
+            "editMessageText",
+            json!({
+                "chat_id": chat_id,
+                "message_id": message_id,
+                "text": text,
+                "parse_mode": "HTML",
+            }),
+            None,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Acknowledge a button tap (stops the client-side spinner)
+    pub async fn answer_callback_query(&self, callback_query_id: &str, text: &str) -> Result<()> {
+        self.call::<Value>(
+            "answerCallbackQuery",
+            json!({
+                "callback_query_id": callback_query_id,
+                "text": text,
+            }),
+            None,
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Long-poll for updates after `offset`
+    pub async fn get_updates(&self, offset: i64, timeout_secs: u64) -> Result<Vec<Update>> {
+        self.call(
+            "getUpdates",
+            json!({
+                "offset": offset,
+                "timeout": timeout_secs,
+                "allowed_updates": ["message", "callback_query"],
+            }),
+            // Long poll must outlive the server-side timeout
+            Some(Duration::from_secs(timeout_secs + 10)),
+        )
+        .await
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, body: Value, timeout: Option<Duration>) -> Result<T> {
+        let url = format!("{}/bot{}/{}", TELEGRAM_API_URL, self.bot_token, method);
+
+        let mut request = self.client.post(&url).json(&body);
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to call Telegram {}", method))?;
+
+        let status = response.status();
+        let data: TelegramResponse<T> = response
+            .json()
+            .await
+            .context("Failed to parse Telegram response")?;
+
+        match (data.ok, data.result) {
+            (true, Some(result)) => Ok(result),
+            _ => anyhow::bail!(
+                "Telegram API error {}: {}",
+                status,
+                data.description.unwrap_or_default()
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TelegramResponse<T> {
+    ok: bool,
+    description: Option<String>,
+    result: Option<T>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Update {
+    pub update_id: i64,
+    pub message: Option<Message>,
+    pub callback_query: Option<CallbackQuery>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Message {
+    pub message_id: i64,
+    pub chat: Chat,
+    pub from: Option<User>,
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Chat {
+    pub id: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    pub id: i64,
+    pub username: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CallbackQuery {
+    pub id: String,
+    pub from: User,
+    pub message: Option<Message>,
+    pub data: Option<String>,
+}
@@ -0,0 +1,234 @@
+//! Alert Rate Limiting
+//!
+//! - Identical alerts within the dedup window collapse into one message
+//!   plus a "repeated N×" follow-up when the window closes. Critical alerts
+//!   are never collapsed: each one goes out.
+//! - Low-priority alerts (Info/Success) can be batched into a periodic digest,
+//!   split into several messages to stay under Telegram's 4096-character limit.
+
+use crate::notifications::{Alert, AlertLevel};
+use std::collections::HashMap;
+use tokio::time::{Duration, Instant};
+
+/// Longest digest message, in chars (Telegram's limit is 4096, leave room for the markup)
+pub const MAX_DIGEST_CHARS: usize = 4000;
+
+struct DedupEntry {
+    first_seen: Instant,
+    suppressed: u32,
+}
+
+pub struct AlertThrottle {
+    dedup_window: Duration,
+    /// None = digest disabled, low-priority alerts go out immediately
+    digest_interval: Option<Duration>,
+    recent: HashMap<(AlertLevel, String), DedupEntry>,
+    digest: Vec<Alert>,
+    last_digest: Instant,
+}
+
+impl AlertThrottle {
+    pub fn new(dedup_window_secs: u64, digest_interval_secs: u64) -> Self {
+        Self {
+            dedup_window: Duration::from_secs(dedup_window_secs),
+            digest_interval: (digest_interval_secs > 0).then(|| Duration::from_secs(digest_interval_secs)),
+            recent: HashMap::new(),
+            digest: Vec::new(),
+            last_digest: Instant::now(),
+        }
+    }
+
+    /// Returns the alert if it should be delivered right now
+    pub fn admit(&mut self, alert: Alert, now: Instant) -> Option<Alert> {
+        if !self.dedup_window.is_zero() && alert.level != AlertLevel::Critical {
+            let key = (alert.level, alert.text.clone());
+            match self.recent.get_mut(&key) {
+                Some(entry) if now.duration_since(entry.first_seen) < self.dedup_window => {
+                    entry.suppressed += 1;
+                    return None;
+                }
+                _ => {
+                    self.recent.insert(key, DedupEntry { first_seen: now, suppressed: 0 });
+                }
+            }
+        }
+
+        if self.digest_interval.is_some() && Self::is_low_priority(alert.level) {
+            self.digest.push(alert);
+            return None;
+        }
+
+        Some(alert)
+    }
+
+    /// Alerts that became due: repeat summaries for closed dedup windows and the digest
+    pub fn flush(&mut self, now: Instant) -> Vec<Alert> {
+        let mut out = Vec::new();
+
+        let window = self.dedup_window;
+        self.recent.retain(|(level, text), entry| {
+            if now.duration_since(entry.first_seen) < window {
+                return true;
+            }
+            if entry.suppressed > 0 {
+                out.push(Alert::new(
+                    *level,
+                    format!(
+                        "{}\n<i>(repeated {}× in the last {}s)</i>",
+                        text,
+                        entry.suppressed,
+                        window.as_secs()
+                    ),
+                ));
+            }
+            false
+        });
+
+        if let Some(interval) = self.digest_interval {
+            if now.duration_since(self.last_digest) >= interval {
+                self.last_digest = now;
+                out.extend(self.take_digest());
+            }
+        }
+
+        out
+    }
+
+    /// Drain queued low-priority alerts into digest messages
+    pub fn take_digest(&mut self) -> Vec<Alert> {
+        build_digest("📋 <b>Digest", std::mem::take(&mut self.digest))
+    }
+
+    pub fn is_low_priority(level: AlertLevel) -> bool {
+        matches!(level, AlertLevel::Info | AlertLevel::Success)
+    }
+}
+
+/// Fold alerts into Info messages: "<title> (N alerts)</b>" + one bullet per alert.
+/// Over `MAX_DIGEST_CHARS` the bullets spread over several messages ("part 2/3"),
+/// and a single bullet that wouldn't fit on its own is cut short as plain text
+pub fn build_digest(title: &str, alerts: Vec<Alert>) -> Vec<Alert> {
+    if alerts.is_empty() {
+        return Vec::new();
+    }
+    let total = alerts.len();
+    // Room for the title line, including a "part N/M" suffix
+    let budget = MAX_DIGEST_CHARS.saturating_sub(title.chars().count() + 48);
+
+    let mut parts: Vec<Vec<String>> = vec![Vec::new()];
+    let mut used = 0;
+    for alert in &alerts {
+        let mut item = format!("• {} {}", alert.level.emoji(), alert.text);
+        if item.chars().count() > budget {
+            item = truncate_html(&item, budget);
+        }
+        let len = item.chars().count() + 1;
+        if used > 0 && used + len > budget {
+            parts.push(Vec::new());
+            used = 0;
+        }
+        used += len;
+        parts.last_mut().expect("parts is never empty").push(item);
+    }
+
+    let count = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, items)| {
+            let part = if count > 1 { format!(", part {}/{}", i + 1, count) } else { String::new() };
+            Alert::info(format!("{} ({} alerts{})</b>\n{}", title, total, part, items.join("\n")))
+        })
+        .collect()
+}
+
+/// `text` without its tags, cut to `max` chars with "…" (a cut tag or entity would
+/// make Telegram reject the whole message)
+fn truncate_html(text: &str, max: usize) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    if plain.chars().count() <= max {
+        return plain;
+    }
+    let mut cut: String = plain.chars().take(max.saturating_sub(1)).collect();
+    // Don't leave half an entity ("&am")
+    if let Some(amp) = cut.rfind('&') {
+        if !cut[amp..].contains(';') {
+            cut.truncate(amp);
+        }
+    }
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_alerts_collapse_with_repeat_count() {
+        let mut throttle = AlertThrottle::new(60, 0);
+        let t0 = Instant::now();
+
+        assert!(throttle.admit(Alert::warning("feed stalled"), t0).is_some());
+        assert!(throttle.admit(Alert::warning("feed stalled"), t0 + Duration::from_secs(1)).is_none());
+        assert!(throttle.admit(Alert::warning("feed stalled"), t0 + Duration::from_secs(2)).is_none());
+        // Different text is not a duplicate
+        assert!(throttle.admit(Alert::warning("other"), t0 + Duration::from_secs(3)).is_some());
+
+        assert!(throttle.flush(t0 + Duration::from_secs(30)).is_empty());
+        let flushed = throttle.flush(t0 + Duration::from_secs(61));
+        assert_eq!(flushed.len(), 1);
+        assert!(flushed[0].text.contains("repeated 2×"));
+    }
+
+    #[test]
+    fn test_low_priority_alerts_are_batched() {
+        let mut throttle = AlertThrottle::new(0, 300);
+        let t0 = Instant::now();
+
+        assert!(throttle.admit(Alert::info("a"), t0).is_none());
+        assert!(throttle.admit(Alert::success("b"), t0).is_none());
+        assert!(throttle.admit(Alert::error("c"), t0).is_some());
+
+        let flushed = throttle.flush(t0 + Duration::from_secs(301));
+        assert_eq!(flushed.len(), 1);
+        assert!(flushed[0].text.contains("Digest (2 alerts)"));
+    }
+
+    #[test]
+    fn test_critical_alerts_are_never_collapsed() {
+        let mut throttle = AlertThrottle::new(60, 0);
+        let t0 = Instant::now();
+
+        assert!(throttle.admit(Alert::critical("kill switch"), t0).is_some());
+        assert!(throttle.admit(Alert::critical("kill switch"), t0 + Duration::from_secs(1)).is_some());
+        assert!(throttle.flush(t0 + Duration::from_secs(61)).is_empty());
+    }
+
+    #[test]
+    fn test_long_digest_is_split_under_the_telegram_limit() {
+        let alerts: Vec<Alert> = (0..100).map(|i| Alert::info(format!("<b>alert {}</b> {}", i, "x".repeat(100)))).collect();
+        let digest = build_digest("📋 <b>Digest", alerts);
+
+        assert!(digest.len() > 1);
+        assert!(digest[0].text.contains(&format!("Digest (100 alerts, part 1/{})", digest.len())));
+        assert!(digest.iter().all(|d| d.text.chars().count() <= MAX_DIGEST_CHARS));
+        let bullets: usize = digest.iter().map(|d| d.text.matches('•').count()).sum();
+        assert_eq!(bullets, 100);
+
+        // One oversized alert is cut short as plain text
+        let digest = build_digest("📋 <b>Digest", vec![Alert::info(format!("<code>{}</code> &amp;", "y".repeat(5000)))]);
+        assert_eq!(digest.len(), 1);
+        assert!(digest[0].text.chars().count() <= MAX_DIGEST_CHARS);
+        assert!(!digest[0].text.contains("<code>") && digest[0].text.ends_with('…'));
+    }
+}
@@ -0,0 +1,177 @@
+//! Crash-Window Order Recovery
+//!
+//! Every order goes out with an orderLinkId starting with the bot's prefix
+//! (`BOT_ID`, see `BybitClient::with_order_links`). If the process dies
+//! between sending an order and learning its fate, the exchange still knows:
+//! at startup, before any actor runs, the orders of the last
+//! `RECOVERY_WINDOW` carrying our prefix are looked up.
+//! - Orders still open belong to nobody anymore and are cancelled.
+//! - Symbols where our newest filled (fully or partly) order is an entry, not
+//!   one of our reduce-only closes, and a position is still open are reported
+//!   for adoption (a position opened after our close is someone else's): the
+//!   strategy trading that symbol finds the position on its next verification
+//!   and takes it over as drift (`POSITION_DRIFT_ACTION`).
+
+use crate::exchange::{BybitClient, OrderStatusResponse, PositionInfo};
+use anyhow::Result;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How far back our orders are looked up
+pub const RECOVERY_WINDOW: Duration = Duration::from_secs(24 * 3600);
+
+/// Longest part of BOT_ID kept in the prefix (orderLinkId is limited to 36 characters)
+const MAX_PREFIX_ID_LEN: usize = 12;
+
+/// orderLinkId prefix of this bot: BOT_ID cut to the characters Bybit accepts, then '-'
+pub fn link_prefix(bot_id: &str) -> String {
+    let id: String = bot_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .take(MAX_PREFIX_ID_LEN)
+        .collect();
+    format!("{}-", if id.is_empty() { "scalper" } else { id.as_str() })
+}
+
+/// What startup does about the orders a previous run left behind
+#[derive(Debug, Default)]
+pub struct Plan {
+    /// Our orders still open
+    pub cancel: Vec<OrderStatusResponse>,
+    /// Open positions built by our entries
+    pub adopt: Vec<PositionInfo>,
+}
+
+/// Pure part of the recovery: pick our orders out of the exchange's lists
+pub fn plan(prefix: &str, open: Vec<OrderStatusResponse>, history: &[OrderStatusResponse], positions: Vec<PositionInfo>) -> Plan {
+    let ours = |order: &OrderStatusResponse| order.order_link_id.starts_with(prefix);
+    let filled = |order: &OrderStatusResponse| Decimal::from_str(&order.cum_exec_qty).is_ok_and(|qty| qty > Decimal::ZERO);
+    let updated = |order: &OrderStatusResponse| order.updated_time.parse::<i64>().unwrap_or(0);
+
+    let cancel: Vec<OrderStatusResponse> = open.into_iter().filter(ours).collect();
+    // Our newest filled order per symbol (open orders first, then history newest first: ties keep the earlier)
+    let mut newest: HashMap<&str, &OrderStatusResponse> = HashMap::new();
+    for order in cancel.iter().chain(history).filter(|order| ours(order) && filled(order)) {
+        newest
+            .entry(order.symbol.as_str())
+            .and_modify(|current| {
+                if updated(order) > updated(current) {
+                    *current = order;
+                }
+            })
+            .or_insert(order);
+    }
+    let adopt = positions
+        .into_iter()
+        .filter(|pos| Decimal::from_str(&pos.size).is_ok_and(|size| !size.is_zero()))
+        .filter(|pos| newest.get(pos.symbol.as_str()).is_some_and(|order| !order.reduce_only))
+        .collect();
+    Plan { cancel, adopt }
+}
+
+/// Cancel our leftover open orders; returns the positions to adopt
+pub async fn run(client: &BybitClient, prefix: &str) -> Result<Vec<PositionInfo>> {
+    let since = chrono::Utc::now().timestamp_millis() - RECOVERY_WINDOW.as_millis() as i64;
+    let open = client.get_open_orders().await?;
+    let history = client.get_order_history(since).await?;
+    let positions = client.get_open_positions().await?;
+
+    let plan = plan(prefix, open, &history, positions);
+    for order in &plan.cancel {
+        warn!(
+            "🧹 Cancelling order left by the previous run: {} {} {} {} ({})",
+            order.symbol, order.side, order.qty, order.order_status, order.order_link_id
+        );
+        if let Err(e) = client.cancel_order(&order.symbol, &order.order_id).await {
+            warn!("Failed to cancel {}: {}", order.order_link_id, e);
+        }
+    }
+    for pos in &plan.adopt {
+        warn!("🧹 Position from an order of the previous run: {} {} {} @ {}", pos.symbol, pos.side, pos.size, pos.avg_price);
+    }
+    if plan.cancel.is_empty() && plan.adopt.is_empty() {
+        info!("✅ No orders left in flight by a previous run");
+    }
+    Ok(plan.adopt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(link_id: &str, symbol: &str, status: &str, cum_exec_qty: &str, reduce_only: bool) -> OrderStatusResponse {
+        serde_json::from_value(serde_json::json!({
+            "orderId": format!("id-{}", link_id), "orderLinkId": link_id, "symbol": symbol, "orderStatus": status,
+            "orderType": "Limit", "side": "Buy", "price": "100", "qty": "1", "cumExecQty": cum_exec_qty,
+            "cumExecValue": "0", "cumExecFee": "0", "avgPrice": "100", "reduceOnly": reduce_only,
+            "updatedTime": "1700000000000"
+        }))
+        .unwrap()
+    }
+
+    fn at(order: OrderStatusResponse, updated_ms: i64) -> OrderStatusResponse {
+        OrderStatusResponse { updated_time: updated_ms.to_string(), ..order }
+    }
+
+    fn position(symbol: &str, size: &str) -> PositionInfo {
+        serde_json::from_value(serde_json::json!({
+            "symbol": symbol, "side": "Buy", "size": size, "avgPrice": "100", "unrealisedPnl": "0"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_prefix_keeps_link_ids_short_and_valid() {
+        assert_eq!(link_prefix("scalper"), "scalper-");
+        assert_eq!(link_prefix("eu.bot #2 with a long name"), "eubot2withal-");
+        assert_eq!(link_prefix("..."), "scalper-");
+        // Prefix, kind, unix ms, '-', a six-digit counter
+        assert!(link_prefix("a_very_long_bot_id").len() + 1 + 13 + 1 + 6 <= 36);
+    }
+
+    #[test]
+    fn test_only_our_filled_entries_are_adopted() {
+        let open = vec![
+            order("scalper-e1-0", "SOLUSDT", "PartiallyFilled", "0.5", false),
+            order("manual", "XRPUSDT", "New", "0", false),
+        ];
+        let history = vec![
+            order("scalper-e2-1", "BTCUSDT", "Filled", "1", false),
+            order("scalper-e3-2", "ETHUSDT", "Cancelled", "0", false),
+            order("scalper-c4-3", "DOGEUSDT", "Filled", "1", true),
+            order("other-e5-4", "PEPEUSDT", "Filled", "1", false),
+        ];
+        let positions = ["SOLUSDT", "BTCUSDT", "ETHUSDT", "DOGEUSDT", "PEPEUSDT", "XRPUSDT"]
+            .into_iter()
+            .map(|symbol| position(symbol, "1"))
+            .chain([position("ADAUSDT", "0")])
+            .collect();
+
+        let plan = plan("scalper-", open, &history, positions);
+        let cancelled: Vec<&str> = plan.cancel.iter().map(|o| o.order_link_id.as_str()).collect();
+        assert_eq!(cancelled, vec!["scalper-e1-0"]);
+        // Unfilled entries, closes and other bots' or manual orders built nothing of ours
+        let adopted: Vec<&str> = plan.adopt.iter().map(|p| p.symbol.as_str()).collect();
+        assert_eq!(adopted, vec!["SOLUSDT", "BTCUSDT"]);
+    }
+
+    #[test]
+    fn test_position_after_our_close_is_not_adopted() {
+        // BTC: entered, closed by us, then a manual position. ETH: closed an earlier trade, entered again
+        let history = vec![
+            at(order("scalper-e3-2", "ETHUSDT", "Filled", "1", false), 3_000),
+            at(order("scalper-c2-1", "BTCUSDT", "Filled", "1", true), 2_000),
+            at(order("scalper-c4-3", "ETHUSDT", "Filled", "1", true), 1_500),
+            at(order("scalper-e1-0", "BTCUSDT", "Filled", "1", false), 1_000),
+            at(order("scalper-e0-0", "ETHUSDT", "Filled", "1", false), 500),
+        ];
+        let positions = vec![position("BTCUSDT", "1"), position("ETHUSDT", "1")];
+
+        let plan = plan("scalper-", Vec::new(), &history, positions);
+        let adopted: Vec<&str> = plan.adopt.iter().map(|p| p.symbol.as_str()).collect();
+        assert_eq!(adopted, vec!["ETHUSDT"]);
+    }
+}
@@ -0,0 +1,144 @@
+//! Local State Files
+//!
+//! Small JSON files under `DATA_DIR` that survive restarts. Writes go to a
+//! temp file first and are renamed into place, so a crash mid-write leaves
+//! the previous version intact instead of a truncated file.
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Read a JSON file; `Ok(None)` if it does not exist yet
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    let value = serde_json::from_slice(&bytes)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(Some(value))
+}
+
+/// Atomically replace a JSON file (creates parent directories as needed)
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+
+    let json = serde_json::to_vec_pretty(value)?;
+    replace(path, &json)
+}
+
+fn replace(path: &Path, json: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, json).with_context(|| format!("failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+/// ✅ OFF THE HOT PATH: Saves a state file from a blocking task, so the caller
+/// (the strategy) never waits on the disk. The snapshot is serialized by the
+/// caller and an older one never replaces a newer one. Outside a runtime
+/// (tools, plain tests) the file is written inline. Clones share the file.
+#[derive(Debug, Clone)]
+pub struct StateFile {
+    path: Arc<PathBuf>,
+    /// Version of the last snapshot taken
+    taken: Arc<AtomicU64>,
+    /// Version of the snapshot on disk
+    written: Arc<Mutex<u64>>,
+}
+
+impl StateFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Arc::new(path.into()),
+            taken: Arc::new(AtomicU64::new(0)),
+            written: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Replace the file with `value`; `what` names it in the warning if that fails
+    pub fn save<T: Serialize>(&self, value: &T, what: &'static str) {
+        let json = match serde_json::to_vec_pretty(value) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("⚠️ Failed to persist {}: {}", what, e);
+                return;
+            }
+        };
+        let version = self.taken.fetch_add(1, Ordering::Relaxed) + 1;
+        let path = self.path.clone();
+        let written = self.written.clone();
+        let write = move || {
+            let mut written = written.lock();
+            if version <= *written {
+                return;
+            }
+            match replace(&path, &json) {
+                Ok(()) => *written = version,
+                Err(e) => warn!("⚠️ Failed to persist {}: {:#}", what, e),
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => drop(runtime.spawn_blocking(write)),
+            Err(_) => write(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_save_then_load_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("persist-test-{}", std::process::id()));
+        let path = dir.join("nested").join("state.json");
+
+        assert!(load_json::<HashMap<String, u32>>(&path).unwrap().is_none());
+
+        let state = HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        save_json(&path, &state).unwrap();
+        assert_eq!(load_json::<HashMap<String, u32>>(&path).unwrap(), Some(state));
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(load_json::<HashMap<String, u32>>(&path).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_state_file_is_written_off_the_caller_and_keeps_the_latest() {
+        let dir = std::env::temp_dir().join(format!("state-file-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = StateFile::new(dir.join("count.json"));
+        for count in 1..=20u32 {
+            file.save(&count, "count");
+        }
+
+        let mut latest = None;
+        for _ in 0..100 {
+            latest = load_json::<u32>(file.path()).unwrap_or(None);
+            if latest == Some(20) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(latest, Some(20));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
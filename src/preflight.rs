@@ -0,0 +1,143 @@
+//! Startup Preflight
+//!
+//! Checks the API key before any actor starts: it must authenticate, be
+//! read-write and carry the contract-trade permissions the bot needs. Bad
+//! credentials fail the launch with a clear message instead of surfacing as
+//! a rejected order in the middle of a trade.
+
+use crate::exchange::{ApiKeyInfo, BybitClient, BybitError};
+use anyhow::{bail, Result};
+use tracing::{info, warn};
+
+/// Warn when the key expires within this many days
+const EXPIRY_WARN_DAYS: i64 = 7;
+
+/// Contract-trade permissions needed to place orders and read positions
+const REQUIRED_CONTRACT_PERMISSIONS: [&str; 2] = ["Order", "Position"];
+
+#[derive(Debug, Clone)]
+pub struct PreflightReport {
+    /// "Unified" or "Classic"
+    pub account_type: &'static str,
+    /// Days until the key expires (None = no expiry)
+    pub days_left: Option<i64>,
+    pub warnings: Vec<String>,
+}
+
+/// Query the key and log the report; Err means the bot must not start
+pub async fn run(client: &BybitClient) -> Result<PreflightReport> {
+    let info = match client.get_api_key_info().await {
+        Ok(info) => info,
+        Err(e @ BybitError::Auth { .. }) => bail!(
+            "API key rejected: {}. Check BYBIT_API_KEY / BYBIT_API_SECRET and that the key belongs \
+             to the selected environment (mainnet / testnet / demo)",
+            e
+        ),
+        Err(e @ BybitError::Timestamp { .. }) => bail!("API preflight failed: {}. Check the system clock (NTP)", e),
+        Err(e) => bail!("API preflight failed: {}", e),
+    };
+
+    let report = evaluate(&info)?;
+    info!("🔑 API key OK ({} account)", report.account_type);
+    match report.days_left {
+        Some(days) => info!("   - Expires in {} days ({})", days, info.expired_at),
+        None => info!("   - No expiry"),
+    }
+    for warning in &report.warnings {
+        warn!("⚠️  {}", warning);
+    }
+    Ok(report)
+}
+
+/// Pure check of the key description
+pub fn evaluate(info: &ApiKeyInfo) -> Result<PreflightReport> {
+    if info.read_only != 0 {
+        bail!("API key is read-only: create a key with Read-Write access");
+    }
+
+    let contract = info.permissions.get("ContractTrade").map(Vec::as_slice).unwrap_or_default();
+    let missing: Vec<&str> = REQUIRED_CONTRACT_PERMISSIONS
+        .into_iter()
+        .filter(|p| !contract.iter().any(|c| c == p))
+        .collect();
+    if !missing.is_empty() {
+        bail!(
+            "API key lacks Contract trading permission(s): {} (has: {:?})",
+            missing.join(", "),
+            contract
+        );
+    }
+
+    let mut warnings = Vec::new();
+    let account_type = if info.uta == 1 {
+        "Unified"
+    } else {
+        warnings.push("Classic account: the bot is built and tested for Unified Trading Account".to_string());
+        "Classic"
+    };
+
+    let days_left = (!info.expired_at.is_empty()).then_some(info.deadline_day);
+    if let Some(days) = days_left {
+        if days <= EXPIRY_WARN_DAYS {
+            warnings.push(format!("API key expires in {} days: renew it or bind an IP whitelist", days));
+        }
+    }
+    if info.ips.is_empty() || info.ips.iter().any(|ip| ip == "*") {
+        warnings.push("API key has no IP whitelist".to_string());
+    }
+
+    Ok(PreflightReport {
+        account_type,
+        days_left,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn key(read_only: i32, contract: &[&str]) -> ApiKeyInfo {
+        ApiKeyInfo {
+            note: "bot".to_string(),
+            read_only,
+            permissions: HashMap::from([(
+                "ContractTrade".to_string(),
+                contract.iter().map(|p| p.to_string()).collect(),
+            )]),
+            ips: vec!["1.2.3.4".to_string()],
+            deadline_day: 0,
+            expired_at: String::new(),
+            uta: 1,
+        }
+    }
+
+    #[test]
+    fn test_valid_unified_key_passes() {
+        let report = evaluate(&key(0, &["Order", "Position"])).unwrap();
+        assert_eq!(report.account_type, "Unified");
+        assert_eq!(report.days_left, None);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_read_only_or_missing_permission_fails() {
+        assert!(evaluate(&key(1, &["Order", "Position"])).is_err());
+        assert!(evaluate(&key(0, &["Position"])).is_err());
+    }
+
+    #[test]
+    fn test_expiring_classic_key_warns() {
+        let mut info = key(0, &["Order", "Position"]);
+        info.uta = 0;
+        info.ips.clear();
+        info.deadline_day = 3;
+        info.expired_at = "2026-10-19T00:00:00Z".to_string();
+
+        let report = evaluate(&info).unwrap();
+        assert_eq!(report.account_type, "Classic");
+        assert_eq!(report.days_left, Some(3));
+        assert_eq!(report.warnings.len(), 3);
+    }
+}
@@ -0,0 +1,138 @@
+//! Redis Bridge
+//!
+//! Publishes every domain event as JSON to `{prefix}:{type}` (e.g.
+//! `scalper:trade_closed`, `PSUBSCRIBE scalper:*` for all of them) and takes
+//! operator commands ("/close", "/killswitch", "/pause", "/resume") from
+//! `{prefix}:control`. Redis commands apply at once: there is no confirmation
+//! step like in Telegram, so the Redis instance must be trusted.
+//! Needs the `redis` build feature.
+
+use crate::actors::messages::StrategyMessage;
+use crate::channel::MeteredSender;
+use crate::config::Config;
+use crate::events::EventBus;
+use anyhow::Result;
+use std::sync::Arc;
+
+#[cfg(feature = "redis")]
+mod imp {
+    use super::*;
+    use crate::actors::commands::parse_command;
+    use crate::events::BotEvent;
+    use anyhow::Context;
+    use futures_util::StreamExt;
+    use redis::AsyncCommands;
+    use tokio::sync::broadcast::{self, error::RecvError};
+    use tokio::time::{sleep, Duration};
+    use tracing::{debug, info, warn};
+
+    const RECONNECT_DELAY_SECS: u64 = 5;
+
+    pub struct RedisBridge {
+        client: redis::Client,
+        prefix: String,
+        events: broadcast::Receiver<BotEvent>,
+        strategy_tx: MeteredSender<StrategyMessage>,
+    }
+
+    impl RedisBridge {
+        pub fn new(config: &Config, url: &str, events: &EventBus, strategy_tx: MeteredSender<StrategyMessage>) -> Result<Self> {
+            let client = redis::Client::open(url).context("invalid REDIS_URL")?;
+            Ok(Self {
+                client,
+                prefix: config.redis_channel_prefix.clone(),
+                events: events.subscribe(),
+                strategy_tx,
+            })
+        }
+
+        pub async fn run(mut self) {
+            info!("🧱 Redis bridge started (events → {}:*, commands ← {}:control)", self.prefix, self.prefix);
+            loop {
+                if let Err(e) = self.session().await {
+                    warn!("⚠️ Redis bridge: {:#}, reconnecting in {}s", e, RECONNECT_DELAY_SECS);
+                }
+                sleep(Duration::from_secs(RECONNECT_DELAY_SECS)).await;
+            }
+        }
+
+        /// One connection's lifetime; returns on any Redis error
+        async fn session(&mut self) -> Result<()> {
+            let mut publisher = self
+                .client
+                .get_multiplexed_async_connection()
+                .await
+                .context("connect failed")?;
+            let mut pubsub = self.client.get_async_pubsub().await.context("pubsub connect failed")?;
+            let control_channel = format!("{}:control", self.prefix);
+            pubsub.subscribe(&control_channel).await.context("subscribe failed")?;
+            let mut commands = pubsub.into_on_message();
+            info!("🧱 Redis connected, listening on {}", control_channel);
+
+            loop {
+                tokio::select! {
+                    event = self.events.recv() => match event {
+                        Ok(event) => {
+                            let channel = format!("{}:{}", self.prefix, event.kind.name());
+                            let payload = event.to_json()?;
+                            let _: () = publisher.publish(&channel, payload).await.context("publish failed")?;
+                        }
+                        Err(RecvError::Lagged(missed)) => warn!("⚠️ Redis bridge fell behind, {} events dropped", missed),
+                        Err(RecvError::Closed) => return Ok(()),
+                    },
+                    message = commands.next() => {
+                        let Some(message) = message else {
+                            anyhow::bail!("control subscription closed");
+                        };
+                        let text: String = message.get_payload().unwrap_or_default();
+                        self.handle_command(text.trim());
+                    }
+                }
+            }
+        }
+
+        fn handle_command(&self, text: &str) {
+            let slashed = if text.starts_with('/') { text.to_string() } else { format!("/{}", text) };
+            let Some(command) = parse_command(&slashed) else {
+                debug!("Ignoring unknown Redis command '{}'", text);
+                return;
+            };
+            info!("🧱 Redis command: {}", command);
+            if let Err(e) = self.strategy_tx.try_send(StrategyMessage::Control(command)) {
+                warn!("Failed to forward Redis command {}: {}", command, e);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "redis"))]
+mod imp {
+    use super::*;
+
+    pub enum RedisBridge {}
+
+    impl RedisBridge {
+        pub fn new(_config: &Config, url: &str, _events: &EventBus, _strategy_tx: MeteredSender<StrategyMessage>) -> Result<Self> {
+            anyhow::bail!("REDIS_URL={} needs a build with `--features redis`", url)
+        }
+
+        pub async fn run(self) {
+            match self {}
+        }
+    }
+}
+
+pub use imp::RedisBridge;
+
+/// Bridge for `config.redis_url`, if one is set
+pub fn from_config(
+    config: &Arc<Config>,
+    events: &EventBus,
+    strategy_tx: &MeteredSender<StrategyMessage>,
+) -> Result<Option<RedisBridge>> {
+    config
+        .redis_url
+        .as_deref()
+        .map(|url| RedisBridge::new(config, url, events, strategy_tx.clone()))
+        .transpose()
+}
@@ -0,0 +1,370 @@
+//! Deterministic Replay
+//!
+//! Runs a decision log (`DECISION_LOG`) back through a fresh strategy and
+//! checks that it decides the same things again: every recorded input is fed
+//! at its recorded time on a paused tokio clock (cooldowns, confirmation
+//! windows and the calendar all see the live timing), and the decisions that
+//! come out are compared with the recorded ones. "Why did it short here at
+//! 03:00?" becomes `--replay decisions.jsonl`, a local run under a debugger,
+//! and a divergence report as soon as a code or config change decides
+//! differently.
+//!
+//! A log holds one session per bot start (`started` lines); each session
+//! starts over from a clean strategy with the current config. Nothing is
+//! written to DATA_DIR: the journal, loss streak and datasets of the replay go
+//! to a temp directory that is removed afterwards.
+
+use crate::actors::execution::ExecutionSender;
+use crate::actors::messages::{ExecutionMessage, NotifierMessage, Traced};
+use crate::actors::strategy::StrategyEngine;
+use crate::channel::{metered_channel, MeteredReceiver, OverflowPolicy};
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::decision_log::{self, DecisionEntry, DecisionLine};
+use crate::events::EventBus;
+use crate::health::HealthRegistry;
+use crate::journal::Journal;
+use crate::metrics::Metrics;
+use crate::model::SignalModel;
+use crate::risk_limits::TempBlacklist;
+use crate::spsc;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::time::Duration;
+
+/// First decision that came out differently
+#[derive(Debug)]
+pub struct Divergence {
+    /// Time of the input the decision followed
+    pub at: DateTime<Utc>,
+    /// Trade in flight when it was recorded
+    pub cid: Option<String>,
+    /// What the log says (None = the replay decided something extra)
+    pub expected: Option<DecisionEntry>,
+    /// What the strategy decides now (None = it no longer decides this)
+    pub actual: Option<DecisionEntry>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |entry: &Option<DecisionEntry>| match entry {
+            Some(entry) => serde_json::to_string(entry).unwrap_or_else(|_| format!("{:?}", entry)),
+            None => "nothing".to_string(),
+        };
+        write!(f, "at {}", self.at.to_rfc3339())?;
+        if let Some(cid) = &self.cid {
+            write!(f, " (trade {})", cid)?;
+        }
+        write!(f, ": recorded {}, replayed {}", show(&self.expected), show(&self.actual))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub sessions: usize,
+    /// Inputs fed to the strategy
+    pub inputs: usize,
+    /// Recorded decisions the replay reproduced
+    pub decisions: usize,
+    /// The replay stops at the first one: past it the strategies are in different states
+    pub divergence: Option<Divergence>,
+}
+
+/// Replay the log at `path` on a runtime of its own
+pub fn replay_file(config: &Config, path: &Path) -> Result<ReplayReport> {
+    let lines = decision_log::read_all(path)?;
+    anyhow::ensure!(!lines.is_empty(), "no decision log lines in {}", path.display());
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .start_paused(true)
+        .build()
+        .context("failed to build the replay runtime")?
+        .block_on(replay(config, lines))
+}
+
+/// Replay `lines` (in file order). Needs a current-thread runtime with paused
+/// time, which `replay_file` and `#[tokio::test(start_paused = true)]` give.
+pub async fn replay(config: &Config, lines: Vec<DecisionLine>) -> Result<ReplayReport> {
+    let dir = std::env::temp_dir().join(format!("replay-{}", std::process::id()));
+    let mut report = ReplayReport::default();
+    let result = async {
+        for session in sessions(lines) {
+            report.sessions += 1;
+            replay_session(config, &dir, session, &mut report).await?;
+            if report.divergence.is_some() {
+                break;
+            }
+        }
+        Ok(())
+    }
+    .await;
+    let _ = std::fs::remove_dir_all(&dir);
+    result.map(|()| report)
+}
+
+/// Split at every `started` line (lines before the first one are a session too)
+fn sessions(lines: Vec<DecisionLine>) -> Vec<Vec<DecisionLine>> {
+    let mut sessions: Vec<Vec<DecisionLine>> = Vec::new();
+    for line in lines {
+        if matches!(line.entry, DecisionEntry::Started) || sessions.is_empty() {
+            sessions.push(Vec::new());
+        }
+        if !matches!(line.entry, DecisionEntry::Started) {
+            sessions.last_mut().expect("pushed above").push(line);
+        }
+    }
+    sessions.retain(|session| !session.is_empty());
+    sessions
+}
+
+async fn replay_session(config: &Config, dir: &Path, lines: Vec<DecisionLine>, report: &mut ReplayReport) -> Result<()> {
+    let Some(start) = lines.first().map(|line| line.at) else {
+        return Ok(());
+    };
+    // Every session starts without the previous one's loss streak and journal
+    let _ = std::fs::remove_dir_all(dir);
+    let clock = Clock::replay(start);
+    let mut strategy = ReplayStrategy::new(config, dir.to_path_buf(), clock)?;
+
+    let mut recorded: Vec<DecisionLine> = Vec::new();
+    let mut replayed: Vec<DecisionLine> = Vec::new();
+    // The first input's decisions are compared when the second input comes up
+    let mut last_input: Option<(DateTime<Utc>, Option<String>)> = None;
+    let mut lines = lines.into_iter().peekable();
+
+    while let Some(line) = lines.next() {
+        if line.entry.is_decision() {
+            recorded.push(line);
+        } else if let Some(msg) = line.entry.into_message(line.symbol.as_ref()) {
+            report.divergence = compare(&recorded, &replayed, last_input.take(), &mut report.decisions);
+            if report.divergence.is_some() {
+                return Ok(());
+            }
+            recorded.clear();
+
+            let ahead = line.at - clock.now();
+            if let Ok(ahead) = ahead.to_std() {
+                tokio::time::advance(ahead).await;
+            }
+            strategy.engine.process(msg).await;
+            report.inputs += 1;
+            replayed = strategy.engine.take_decision_lines();
+            replayed.retain(|line| line.entry.is_decision());
+            last_input = Some((line.at, line.cid));
+        }
+        if lines.peek().is_none() {
+            report.divergence = compare(&recorded, &replayed, last_input.take(), &mut report.decisions);
+        }
+    }
+    Ok(())
+}
+
+/// Decisions taken for one input, recorded vs. replayed, in order
+fn compare(
+    recorded: &[DecisionLine],
+    replayed: &[DecisionLine],
+    input: Option<(DateTime<Utc>, Option<String>)>,
+    matched: &mut usize,
+) -> Option<Divergence> {
+    for i in 0..recorded.len().max(replayed.len()) {
+        let (expected, actual) = (recorded.get(i), replayed.get(i));
+        if let (Some(expected), Some(actual)) = (expected, actual) {
+            if comparable(&expected.entry) == comparable(&actual.entry) {
+                *matched += 1;
+                continue;
+            }
+        }
+        let (at, cid) = input.clone().unwrap_or_else(|| (expected.or(actual).expect("within both lengths").at, None));
+        return Some(Divergence {
+            at,
+            cid: expected.and_then(|line| line.cid.clone()).or(cid),
+            expected: expected.map(|line| line.entry.clone()),
+            actual: actual.map(|line| line.entry.clone()),
+        });
+    }
+    None
+}
+
+/// A decision without the fields that depend on when it was written, not on what was decided
+fn comparable(entry: &DecisionEntry) -> serde_json::Value {
+    let mut value = serde_json::to_value(entry).unwrap_or_default();
+    if let Some(sample) = value.get_mut("sample").and_then(serde_json::Value::as_object_mut) {
+        sample.remove("at");
+    }
+    value
+}
+
+/// A strategy wired to channels nobody serves: orders and alerts are dropped
+/// (their outcome is in the log as the next inputs), sends never block
+struct ReplayStrategy {
+    engine: StrategyEngine,
+    _execution_rx: [MeteredReceiver<Traced<ExecutionMessage>>; 2],
+    _notifier_rx: MeteredReceiver<NotifierMessage>,
+}
+
+impl ReplayStrategy {
+    fn new(config: &Config, data_dir: PathBuf, clock: Clock) -> Result<Self> {
+        let mut config = config.clone();
+        config.data_dir = data_dir.to_string_lossy().into_owned();
+        config.decision_log = false;
+        config.signal_dataset = false;
+        let config = Arc::new(config);
+        let signal_model = config.signal_model_path.as_deref().map(SignalModel::load).transpose()?;
+
+        let metrics = Metrics::new();
+        let drop = OverflowPolicy::DropOldest;
+        // Nothing is sent on the inputs, `process` is called directly
+        let (_, strategy_rx) = metered_channel("strategy", 1, drop, &metrics);
+        let (_, market_rx) = spsc::market_ring("market_data", 1, &metrics);
+        let (execution_tx, execution_rx) = metered_channel("execution", 16, drop, &metrics);
+        let (priority_tx, priority_rx) = metered_channel("execution_priority", 16, drop, &metrics);
+        let (notifier_tx, notifier_rx) = metered_channel("notifier", 16, drop, &metrics);
+
+        let mut engine = StrategyEngine::new(
+            config.clone(),
+            strategy_rx,
+            market_rx,
+            ExecutionSender::new(execution_tx, priority_tx),
+            notifier_tx,
+            Journal::new(config.data_path("journal.jsonl")),
+            TempBlacklist::new(),
+            signal_model,
+            EventBus::new(config.bot_id.as_str()),
+            HealthRegistry::new().register("strategy_replay", Duration::from_secs(60)),
+        );
+        engine.set_replay(clock);
+        Ok(Self {
+            engine,
+            _execution_rx: [execution_rx, priority_rx],
+            _notifier_rx: notifier_rx,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actors::messages::StrategyMessage;
+    use crate::exchange::SymbolSpecs;
+    use crate::models::{OrderBookSnapshot, Position, PositionSide, Symbol};
+    use rust_decimal::Decimal;
+
+    fn config() -> Config {
+        std::env::set_var("BYBIT_API_KEY", "replay");
+        std::env::set_var("BYBIT_API_SECRET", "replay");
+        Config::from_env().unwrap()
+    }
+
+    fn inputs() -> Vec<StrategyMessage> {
+        let btc = Symbol::from("BTCUSDT");
+        let signal = |side: &str| serde_json::from_value(serde_json::json!({"symbol": "BTCUSDT", "side": side})).unwrap();
+        vec![
+            StrategyMessage::SymbolChanged {
+                symbol: btc.clone(),
+                specs: SymbolSpecs {
+                    symbol: "BTCUSDT".to_string(),
+                    qty_step: Decimal::new(1, 3),
+                    min_order_qty: Decimal::new(1, 3),
+                    max_order_qty: Decimal::from(100),
+                    tick_size: Decimal::new(1, 1),
+                },
+                price_change_24h: 0.01,
+            },
+            StrategyMessage::OrderBook(OrderBookSnapshot::new(
+                btc.clone(),
+                0,
+                Decimal::new(1_000_000, 1),
+                Decimal::new(1_000_001, 1),
+                Decimal::ONE,
+                Decimal::ONE,
+            )),
+            StrategyMessage::ExternalSignal(signal("buy")),
+            StrategyMessage::OrderFailed { symbol: btc, reason: "rejected".to_string(), retryable: false },
+            // Inside the cooldown of the failed entry: ignored
+            StrategyMessage::ExternalSignal(signal("sell")),
+        ]
+    }
+
+    /// Run `inputs` through a strategy 10s apart and keep its log
+    async fn record(config: &Config) -> Vec<DecisionLine> {
+        let start = DateTime::parse_from_rfc3339("2026-03-01T03:00:00Z").unwrap().with_timezone(&Utc);
+        let dir = std::env::temp_dir().join(format!("replay-record-{}", std::process::id()));
+        let mut strategy = ReplayStrategy::new(config, dir.clone(), Clock::replay(start)).unwrap();
+        let mut lines = vec![decision_log_line(start, DecisionEntry::Started)];
+        for msg in inputs() {
+            strategy.engine.process(msg).await;
+            lines.extend(strategy.engine.take_decision_lines());
+            tokio::time::advance(Duration::from_secs(10)).await;
+        }
+        let _ = std::fs::remove_dir_all(dir);
+        lines
+    }
+
+    fn decision_log_line(at: DateTime<Utc>, entry: DecisionEntry) -> DecisionLine {
+        DecisionLine { at, symbol: None, state: "Idle".to_string(), cid: None, entry }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stale_position_updates_are_dropped() {
+        let start = DateTime::parse_from_rfc3339("2026-03-01T03:00:00Z").unwrap().with_timezone(&Utc);
+        let dir = std::env::temp_dir().join(format!("replay-stale-{}", std::process::id()));
+        let mut strategy = ReplayStrategy::new(&config(), dir.clone(), Clock::replay(start)).unwrap();
+        strategy.engine.process(inputs().remove(0)).await;
+        let update = |symbol: &str, query| StrategyMessage::PositionUpdate {
+            symbol: Symbol::from(symbol),
+            position: Some(Position {
+                symbol: Symbol::from(symbol),
+                side: PositionSide::Long,
+                size: Decimal::ONE,
+                entry_price: Decimal::from(100_000),
+                current_price: Decimal::from(100_000),
+                unrealized_pnl: Decimal::ZERO,
+                stop_loss: None,
+                leverage: Decimal::ONE,
+                liq_price: None,
+                realized_pnl: Decimal::ZERO,
+                cum_realized_pnl: Decimal::ZERO,
+            }),
+            query,
+        };
+        let is_update = |line: &DecisionLine| matches!(line.entry, DecisionEntry::PositionUpdate { .. });
+
+        // Another symbol's position, and the answer to a verification asked before the last fill
+        strategy.engine.process(update("ETHUSDT", None)).await;
+        strategy.engine.process(update("BTCUSDT", Some(7))).await;
+        assert!(!strategy.engine.take_decision_lines().iter().any(is_update));
+
+        strategy.engine.process(update("BTCUSDT", None)).await;
+        let lines = strategy.engine.take_decision_lines();
+        assert!(lines.iter().any(is_update));
+        assert!(lines.iter().any(|line| matches!(line.entry, DecisionEntry::StateChanged { ref from, .. } if from == "Idle")));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_replay_reproduces_decisions_and_reports_divergence() {
+        let config = config();
+        let lines = record(&config).await;
+        assert!(lines.iter().any(|line| matches!(line.entry, DecisionEntry::OrderSent { .. })));
+
+        // Round trip through JSON, as from the file
+        let lines: Vec<DecisionLine> = lines
+            .iter()
+            .map(|line| serde_json::from_str(&serde_json::to_string(line).unwrap()).unwrap())
+            .collect();
+        let report = replay(&config, lines.clone()).await.unwrap();
+        assert!(report.divergence.is_none(), "{}", report.divergence.unwrap());
+        assert_eq!((report.sessions, report.inputs), (1, 5));
+        assert_eq!(report.decisions, lines.iter().filter(|line| line.entry.is_decision()).count());
+
+        // With a tighter spread limit the entry is no longer taken
+        let mut tight = config.clone();
+        tight.max_spread_bps = 0.001;
+        let divergence = replay(&tight, lines).await.unwrap().divergence.expect("diverges");
+        assert!(divergence.cid.is_some());
+        assert!(divergence.expected.is_some() && divergence.actual.is_none(), "{}", divergence);
+    }
+}
@@ -0,0 +1,481 @@
+//! Entry Limits
+//!
+//! Guards that stop the strategy from opening new positions for a while,
+//! independent of any single signal.
+
+use crate::persist::{self, StateFile};
+use chrono::{DateTime, NaiveDate, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DailyTradeState {
+    day: NaiveDate,
+    count: u32,
+}
+
+#[derive(Debug)]
+struct DailyTrades {
+    max_per_day: u32,
+    day: NaiveDate,
+    count: u32,
+}
+
+/// ✅ DAILY TRADE LIMIT: At most N entries per UTC day (0 = unlimited), across
+/// strategy shards: clones share the count. Loaded from a file, today's count
+/// survives a restart.
+#[derive(Debug, Clone)]
+pub struct DailyTradeLimit {
+    trades: Arc<Mutex<DailyTrades>>,
+    /// None = counted in memory only
+    file: Option<StateFile>,
+}
+
+impl DailyTradeLimit {
+    pub fn new(max_per_day: u32, now: DateTime<Utc>) -> Self {
+        Self {
+            trades: Arc::new(Mutex::new(DailyTrades {
+                max_per_day,
+                day: now.date_naive(),
+                count: 0,
+            })),
+            file: None,
+        }
+    }
+
+    pub fn load(path: impl Into<PathBuf>, max_per_day: u32, now: DateTime<Utc>) -> Self {
+        let file = StateFile::new(path);
+        let state = match persist::load_json::<DailyTradeState>(file.path()) {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("⚠️ Ignoring daily trade count file: {:#}", e);
+                None
+            }
+        };
+        let limit = Self {
+            file: Some(file),
+            ..Self::new(max_per_day, now)
+        };
+        {
+            let mut trades = limit.trades.lock();
+            if let Some(state) = state.filter(|state| state.day == trades.day) {
+                trades.count = state.count;
+            }
+        }
+        limit
+    }
+
+    /// Entries counted so far today
+    pub fn count(&self, now: DateTime<Utc>) -> u32 {
+        let trades = self.trades.lock();
+        if now.date_naive() == trades.day {
+            trades.count
+        } else {
+            0
+        }
+    }
+
+    pub fn max_per_day(&self) -> u32 {
+        self.trades.lock().max_per_day
+    }
+
+    /// Today's count is kept
+    pub fn set_max_per_day(&self, max_per_day: u32) {
+        self.trades.lock().max_per_day = max_per_day;
+    }
+
+    pub fn is_reached(&self, now: DateTime<Utc>) -> bool {
+        let max_per_day = self.max_per_day();
+        max_per_day > 0 && self.count(now) >= max_per_day
+    }
+
+    /// Count an entry; true if it used up today's allowance
+    pub fn record_entry(&self, now: DateTime<Utc>) -> bool {
+        let mut trades = self.trades.lock();
+        // Start counting from zero when the UTC day changes
+        let today = now.date_naive();
+        if today != trades.day {
+            trades.day = today;
+            trades.count = 0;
+        }
+        trades.count += 1;
+        if let Some(ref file) = self.file {
+            file.save(&DailyTradeState { day: trades.day, count: trades.count }, "daily trade count");
+        }
+        trades.max_per_day > 0 && trades.count == trades.max_per_day
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CooldownState {
+    last_trade_at: Option<DateTime<Utc>>,
+}
+
+/// ✅ TRADE COOLDOWN: Wall-clock end of the last trade, persisted so a restart
+/// doesn't cut the cooldown after it short
+#[derive(Debug)]
+pub struct TradeCooldown {
+    file: StateFile,
+    state: CooldownState,
+}
+
+impl TradeCooldown {
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let file = StateFile::new(path);
+        let state = match persist::load_json(file.path()) {
+            Ok(state) => state.unwrap_or_default(),
+            Err(e) => {
+                warn!("⚠️ Ignoring trade cooldown file: {:#}", e);
+                CooldownState::default()
+            }
+        };
+        Self { file, state }
+    }
+
+    /// Time since the last trade (None = no trade recorded)
+    pub fn since_last_trade(&self, now: DateTime<Utc>) -> Option<std::time::Duration> {
+        let elapsed = now - self.state.last_trade_at?;
+        Some(elapsed.to_std().unwrap_or_default())
+    }
+
+    pub fn record_trade(&mut self, now: DateTime<Utc>) {
+        self.state.last_trade_at = Some(now);
+        self.file.save(&self.state, "trade cooldown");
+    }
+}
+
+/// What a closed trade did to the loss streak
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreakAction {
+    None,
+    /// Entries paused until then
+    Paused(DateTime<Utc>),
+    /// Entries stopped until an operator resumes
+    Halted,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct LossStreakState {
+    consecutive_losses: u32,
+    paused_until: Option<DateTime<Utc>>,
+    halted: bool,
+}
+
+/// ✅ LOSS STREAK: Consecutive losing trades escalate from a timed pause to a halt that
+/// needs a manual resume. Persisted, so a restart doesn't clear either.
+pub struct LossStreak {
+    file: StateFile,
+    /// Losses that start the timed pause (0 = never)
+    pause_after: u32,
+    pause: chrono::Duration,
+    /// Losses that halt entries until resumed (0 = never)
+    halt_after: u32,
+    state: LossStreakState,
+}
+
+impl LossStreak {
+    pub fn load(path: impl Into<PathBuf>, pause_after: u32, pause: chrono::Duration, halt_after: u32) -> Self {
+        let file = StateFile::new(path);
+        let state = match persist::load_json(file.path()) {
+            Ok(state) => state.unwrap_or_default(),
+            Err(e) => {
+                warn!("⚠️ Ignoring loss streak file: {:#}", e);
+                LossStreakState::default()
+            }
+        };
+        Self {
+            file,
+            pause_after,
+            pause,
+            halt_after,
+            state,
+        }
+    }
+
+    pub fn consecutive_losses(&self) -> u32 {
+        self.state.consecutive_losses
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.state.halted
+    }
+
+    /// End of the timed pause, if one is running
+    pub fn paused_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.state.paused_until.filter(|until| *until > now)
+    }
+
+    pub fn blocks_entry(&self, now: DateTime<Utc>) -> bool {
+        self.is_halted() || self.paused_until(now).is_some()
+    }
+
+    /// A losing trade extends the streak (and may pause or halt), anything else ends it
+    pub fn record_trade(&mut self, net_pnl: Decimal, now: DateTime<Utc>) -> StreakAction {
+        if net_pnl >= Decimal::ZERO {
+            if self.state.consecutive_losses > 0 {
+                self.state.consecutive_losses = 0;
+                self.save();
+            }
+            return StreakAction::None;
+        }
+
+        self.state.consecutive_losses += 1;
+        let losses = self.state.consecutive_losses;
+        let action = if self.halt_after > 0 && losses >= self.halt_after {
+            self.state.halted = true;
+            StreakAction::Halted
+        } else if self.pause_after > 0 && losses >= self.pause_after {
+            let until = now + self.pause;
+            self.state.paused_until = Some(until);
+            StreakAction::Paused(until)
+        } else {
+            StreakAction::None
+        };
+        self.save();
+        action
+    }
+
+    /// Operator resume: clears the halt, any pause and the streak itself
+    pub fn resume(&mut self) {
+        self.state = LossStreakState::default();
+        self.save();
+    }
+
+    fn save(&self) {
+        self.file.save(&self.state, "loss streak");
+    }
+}
+
+/// ✅ AUTO-BLACKLIST: Symbols kept out of scanning and entries until a deadline.
+/// Clones share the same list (scanner + strategy). Loaded from a file, blocks
+/// survive a restart.
+#[derive(Debug, Clone, Default)]
+pub struct TempBlacklist {
+    until: Arc<DashMap<String, DateTime<Utc>>>,
+    /// None = kept in memory only
+    file: Option<StateFile>,
+}
+
+impl TempBlacklist {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expired entries are dropped on their next lookup, as in memory
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let file = StateFile::new(path);
+        let until = match persist::load_json::<BTreeMap<String, DateTime<Utc>>>(file.path()) {
+            Ok(until) => until.unwrap_or_default(),
+            Err(e) => {
+                warn!("⚠️ Ignoring temporary blacklist file: {:#}", e);
+                BTreeMap::new()
+            }
+        };
+        for (symbol, until) in &until {
+            info!("🚫 {} temporarily blacklisted until {} (carried over)", symbol, until);
+        }
+        Self {
+            until: Arc::new(until.into_iter().collect()),
+            file: Some(file),
+        }
+    }
+
+    pub fn add(&self, symbol: &str, until: DateTime<Utc>) {
+        self.until.insert(symbol.to_uppercase(), until);
+        if let Some(ref file) = self.file {
+            let entries: BTreeMap<String, DateTime<Utc>> =
+                self.until.iter().map(|entry| (entry.key().clone(), *entry.value())).collect();
+            file.save(&entries, "temporary blacklist");
+        }
+    }
+
+    /// End of the block, if the symbol is still blocked (expired entries are dropped)
+    pub fn blocked_until(&self, symbol: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let key = symbol.to_uppercase();
+        let until = *self.until.get(&key)?;
+        if until > now {
+            return Some(until);
+        }
+        self.until.remove(&key);
+        info!("✅ {} is off the temporary blacklist", key);
+        None
+    }
+
+    pub fn is_blocked(&self, symbol: &str, now: DateTime<Utc>) -> bool {
+        self.blocked_until(symbol, now).is_some()
+    }
+}
+
+/// ✅ SHARED RISK GATE: Caps the positions open at once across strategy shards.
+/// A symbol holds a slot from its entry order until its shard is flat again;
+/// clones share the slots.
+#[derive(Debug, Clone)]
+pub struct PositionGate {
+    max_open: usize,
+    holders: Arc<Mutex<HashSet<String>>>,
+}
+
+impl PositionGate {
+    pub fn new(max_open: usize) -> Self {
+        Self {
+            max_open,
+            holders: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    pub fn max_open(&self) -> usize {
+        self.max_open
+    }
+
+    /// Take a slot for `symbol` (true if it got one or already had it)
+    pub fn try_acquire(&self, symbol: &str) -> bool {
+        let mut holders = self.holders.lock();
+        if holders.contains(symbol) {
+            return true;
+        }
+        if holders.len() >= self.max_open {
+            return false;
+        }
+        holders.insert(symbol.to_string());
+        true
+    }
+
+    pub fn release(&self, symbol: &str) {
+        self.holders.lock().remove(symbol);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_daily_limit_resets_at_utc_midnight() {
+        let morning = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let limit = DailyTradeLimit::new(2, morning);
+        let other_shard = limit.clone();
+
+        assert!(!limit.record_entry(morning));
+        assert!(!limit.is_reached(morning));
+        // Shards share the allowance
+        assert!(other_shard.record_entry(morning));
+        assert!(limit.is_reached(morning));
+        assert!(limit.is_reached(Utc.with_ymd_and_hms(2024, 3, 1, 23, 59, 59).unwrap()));
+
+        let next_day = Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap();
+        assert!(!limit.is_reached(next_day));
+        assert_eq!(limit.count(next_day), 0);
+
+        let unlimited = DailyTradeLimit::new(0, morning);
+        assert!(!unlimited.record_entry(morning));
+        assert!(!unlimited.is_reached(morning));
+    }
+
+    #[test]
+    fn test_loss_streak_escalates_and_survives_restart() {
+        let path = std::env::temp_dir().join(format!("loss-streak-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let pause = chrono::Duration::minutes(15);
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let loss = Decimal::new(-25, 2);
+
+        let mut streak = LossStreak::load(&path, 3, pause, 5);
+        assert_eq!(streak.record_trade(loss, now), StreakAction::None);
+        assert_eq!(streak.record_trade(Decimal::ONE, now), StreakAction::None);
+        assert_eq!(streak.consecutive_losses(), 0);
+
+        streak.record_trade(loss, now);
+        streak.record_trade(loss, now);
+        assert_eq!(streak.record_trade(loss, now), StreakAction::Paused(now + pause));
+        assert!(streak.blocks_entry(now + chrono::Duration::minutes(14)));
+        assert!(!streak.blocks_entry(now + pause));
+
+        // Restart mid-streak: the next losses still count towards the halt
+        let mut restarted = LossStreak::load(&path, 3, pause, 5);
+        assert_eq!(restarted.consecutive_losses(), 3);
+        restarted.record_trade(loss, now);
+        assert_eq!(restarted.record_trade(loss, now), StreakAction::Halted);
+        assert!(LossStreak::load(&path, 3, pause, 5).blocks_entry(now + chrono::Duration::days(1)));
+
+        restarted.resume();
+        assert!(!LossStreak::load(&path, 3, pause, 5).blocks_entry(now));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_temp_blacklist_is_shared_and_expires() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let blacklist = TempBlacklist::new();
+        let scanner_view = blacklist.clone();
+
+        blacklist.add("dogeusdt", now + chrono::Duration::hours(24));
+        assert!(scanner_view.is_blocked("DOGEUSDT", now));
+        assert!(!scanner_view.is_blocked("BTCUSDT", now));
+        assert!(!scanner_view.is_blocked("DOGEUSDT", now + chrono::Duration::hours(24)));
+        assert!(blacklist.until.is_empty());
+    }
+
+    #[test]
+    fn test_daily_trade_count_survives_a_restart() {
+        let path = std::env::temp_dir().join(format!("daily-trades-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+        DailyTradeLimit::load(&path, 2, now).record_entry(now);
+        assert!(DailyTradeLimit::load(&path, 2, now).record_entry(now));
+        // Yesterday's count doesn't carry into a new day
+        let tomorrow = now + chrono::Duration::days(1);
+        assert_eq!(DailyTradeLimit::load(&path, 2, tomorrow).count(tomorrow), 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_trade_cooldown_survives_a_restart() {
+        let path = std::env::temp_dir().join(format!("trade-cooldown-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+        let mut cooldown = TradeCooldown::load(&path);
+        assert_eq!(cooldown.since_last_trade(now), None);
+        cooldown.record_trade(now);
+        let restarted = TradeCooldown::load(&path);
+        assert_eq!(restarted.since_last_trade(now + chrono::Duration::seconds(30)), Some(std::time::Duration::from_secs(30)));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_temp_blacklist_survives_a_restart() {
+        let path = std::env::temp_dir().join(format!("temp-blacklist-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+
+        TempBlacklist::load(&path).add("dogeusdt", now + chrono::Duration::hours(2));
+        let blacklist = TempBlacklist::load(&path);
+        assert!(blacklist.is_blocked("DOGEUSDT", now));
+        assert!(!blacklist.is_blocked("DOGEUSDT", now + chrono::Duration::hours(2)));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_position_gate_caps_open_positions() {
+        let gate = PositionGate::new(1);
+        let other_shard = gate.clone();
+        assert!(gate.try_acquire("BTCUSDT"));
+        assert!(gate.try_acquire("BTCUSDT"));
+        assert!(!other_shard.try_acquire("ETHUSDT"));
+
+        gate.release("BTCUSDT");
+        assert!(other_shard.try_acquire("ETHUSDT"));
+    }
+}
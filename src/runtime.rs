@@ -0,0 +1,217 @@
+//! Runtime Layout
+//!
+//! By default everything shares one multi-threaded tokio runtime. For lower
+//! and steadier latency the market data and strategy actors can get their own
+//! current-thread runtime on a dedicated OS thread (`HOT_PATH_RUNTIME`),
+//! optionally pinned to a core (`HOT_PATH_CPU`), while the main runtime's
+//! workers (Telegram, metrics, REST, ...) are kept on other cores
+//! (`RUNTIME_WORKER_CPUS`). Read before the runtime exists, so it has its own
+//! `from_env` instead of living in `Config`.
+
+use anyhow::{Context, Result};
+use std::env;
+use std::future::Future;
+use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::task::JoinHandle;
+
+/// Core indices `pin_current_thread` can take (CPU_SET panics past the set size)
+#[cfg(target_os = "linux")]
+const CPU_SET_SIZE: usize = libc::CPU_SETSIZE as usize;
+#[cfg(not(target_os = "linux"))]
+const CPU_SET_SIZE: usize = 1024;
+
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeConfig {
+    /// Main runtime worker threads (None = one per core)
+    pub worker_threads: Option<usize>,
+    /// Cores the main runtime's workers may run on (empty = any)
+    pub worker_cpus: Vec<usize>,
+    /// Market data + strategy on a dedicated current-thread runtime
+    pub hot_path_runtime: bool,
+    /// Core to pin the hot path thread to
+    pub hot_path_cpu: Option<usize>,
+}
+
+impl RuntimeConfig {
+    pub fn from_env() -> Result<Self> {
+        dotenvy::dotenv().ok();
+
+        let worker_threads = match env::var("RUNTIME_WORKER_THREADS").ok().filter(|s| !s.trim().is_empty()) {
+            Some(s) => Some(s.trim().parse::<usize>().context("RUNTIME_WORKER_THREADS must be a number")?),
+            None => None,
+        }
+        .filter(|&n| n > 0);
+        let worker_cpus = parse_cpu_list(&env::var("RUNTIME_WORKER_CPUS").unwrap_or_default())
+            .context("RUNTIME_WORKER_CPUS must look like \"1-3,6\"")?;
+        let hot_path_runtime = env::var("HOT_PATH_RUNTIME")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+        let hot_path_cpu = match env::var("HOT_PATH_CPU").ok().filter(|s| !s.trim().is_empty()) {
+            Some(s) => Some(s.trim().parse::<usize>().context("HOT_PATH_CPU must be a core index")?),
+            None => None,
+        };
+        if let Some(cpu) = hot_path_cpu.filter(|&cpu| cpu >= CPU_SET_SIZE) {
+            anyhow::bail!("HOT_PATH_CPU {} is out of range: expected below {}", cpu, CPU_SET_SIZE);
+        }
+
+        if hot_path_cpu.is_some() && !hot_path_runtime {
+            anyhow::bail!("HOT_PATH_CPU needs HOT_PATH_RUNTIME=true");
+        }
+        if let Some(cpu) = hot_path_cpu {
+            if worker_cpus.contains(&cpu) {
+                anyhow::bail!("HOT_PATH_CPU {} is also in RUNTIME_WORKER_CPUS", cpu);
+            }
+        }
+
+        Ok(Self {
+            worker_threads,
+            worker_cpus,
+            hot_path_runtime,
+            hot_path_cpu,
+        })
+    }
+
+    /// The main multi-threaded runtime
+    pub fn build_main(&self) -> Result<Runtime> {
+        let mut builder = Builder::new_multi_thread();
+        builder.enable_all();
+        if let Some(threads) = self.worker_threads {
+            builder.worker_threads(threads);
+        }
+        if !self.worker_cpus.is_empty() {
+            let cpus = self.worker_cpus.clone();
+            builder.on_thread_start(move || {
+                if let Err(e) = pin_current_thread(&cpus) {
+                    eprintln!("Failed to pin runtime thread to cores {:?}: {}", cpus, e);
+                }
+            });
+        }
+        Ok(builder.build()?)
+    }
+
+    /// Where the latency-critical actors run
+    pub fn hot_path(&self) -> Result<HotPath> {
+        if !self.hot_path_runtime {
+            return Ok(HotPath { handle: None });
+        }
+
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        let handle = runtime.handle().clone();
+        let cpu = self.hot_path_cpu;
+        std::thread::Builder::new()
+            .name("hot-path".to_string())
+            .spawn(move || {
+                if let Some(cpu) = cpu {
+                    if let Err(e) = pin_current_thread(&[cpu]) {
+                        tracing::warn!("⚠️ Failed to pin the hot path to core {}: {}", cpu, e);
+                    }
+                }
+                // Drives the spawned actors for the life of the process
+                runtime.block_on(std::future::pending::<()>());
+            })
+            .context("failed to start the hot path thread")?;
+        Ok(HotPath { handle: Some(handle) })
+    }
+}
+
+/// Spawns onto the dedicated hot path runtime, or the main one when there is none
+pub struct HotPath {
+    handle: Option<Handle>,
+}
+
+impl HotPath {
+    pub fn is_dedicated(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        match self.handle {
+            Some(ref handle) => handle.spawn(future),
+            None => tokio::spawn(future),
+        }
+    }
+}
+
+/// "1-3,6" -> [1, 2, 3, 6]; empty -> []. Every index must fit a cpu set
+fn parse_cpu_list(list: &str) -> Result<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in list.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to): (usize, usize) = (from.trim().parse()?, to.trim().parse()?);
+                if from > to {
+                    anyhow::bail!("bad range {}", part);
+                }
+                if to >= CPU_SET_SIZE {
+                    anyhow::bail!("core {} is out of range: expected below {}", to, CPU_SET_SIZE);
+                }
+                cpus.extend(from..=to);
+            }
+            None => {
+                let cpu: usize = part.parse()?;
+                if cpu >= CPU_SET_SIZE {
+                    anyhow::bail!("core {} is out of range: expected below {}", cpu, CPU_SET_SIZE);
+                }
+                cpus.push(cpu);
+            }
+        }
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Ok(cpus)
+}
+
+#[cfg(target_os = "linux")]
+fn pin_current_thread(cpus: &[usize]) -> std::io::Result<()> {
+    // SAFETY: cpu_set_t is plain data; from_env keeps every index below CPU_SETSIZE
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        for &cpu in cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_current_thread(_cpus: &[usize]) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "core pinning is Linux-only"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("").unwrap(), Vec::<usize>::new());
+        assert_eq!(parse_cpu_list("1-3, 6,2").unwrap(), vec![1, 2, 3, 6]);
+        assert!(parse_cpu_list("3-1").is_err());
+        assert!(parse_cpu_list("a").is_err());
+        assert!(parse_cpu_list(&CPU_SET_SIZE.to_string()).is_err());
+        assert!(parse_cpu_list(&format!("0-{}", CPU_SET_SIZE)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hot_path_tasks_run_on_the_dedicated_thread() {
+        let config = RuntimeConfig {
+            hot_path_runtime: true,
+            ..Default::default()
+        };
+        let hot_path = config.hot_path().unwrap();
+        let thread = hot_path
+            .spawn(async { std::thread::current().name().map(str::to_string) })
+            .await
+            .unwrap();
+        assert_eq!(thread.as_deref(), Some("hot-path"));
+    }
+}
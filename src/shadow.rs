@@ -0,0 +1,335 @@
+//! Shadow Trading
+//!
+//! A second strategy configuration (`SHADOW_CONFIG`: a .env-style file of
+//! overrides) runs beside the live one for A/B testing parameter changes
+//! without risking money on them. Every live engine gets a shadow twin that
+//! sees the same market inputs (the live engine passes a copy of each one
+//! along) but trades against `PaperExecutionActor`, with its own position
+//! monitor, journal and state files under `DATA_DIR/shadow`. Its alerts go
+//! nowhere and its blacklist and position slots are its own, so nothing it
+//! does reaches the exchange, the operator or the live engine. After every
+//! UTC day the closed trades of both journals are compared in one alert.
+//!
+//! The arena (`PAPER_PROFILES`) runs one such shadow per named profile, under
+//! `DATA_DIR/arena/<name>`. `/leaderboard` ranks them against live, and
+//! `/promote <name>` hands the winner's runtime parameters to the live engine.
+
+use crate::actors::execution::{ExecutionSender, EXECUTION_LIVENESS_SECS};
+use crate::actors::messages::{NotifierMessage, ParamPatch};
+use crate::actors::paper_execution::PaperExecutionActor;
+use crate::actors::position_monitor::{MarkPrice, PositionMonitor, MONITOR_BEAT_SECS};
+use crate::actors::strategy::{StrategyEngine, POSITION_VERIFY_INTERVAL_SECS};
+use crate::channel::{metered_channel, MeteredReceiver, MeteredSender, OverflowPolicy};
+use crate::config::Config;
+use crate::events::EventBus;
+use crate::health::HealthRegistry;
+use crate::journal::Journal;
+use crate::metrics::Metrics;
+use crate::model::SignalModel;
+use crate::models::{Symbol, TradeRecord};
+use crate::notifications::Alert;
+use crate::risk_limits::{DailyTradeLimit, PositionGate, TempBlacklist};
+use crate::spsc;
+use crate::stats::{self, PerformanceStats};
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, NaiveDate, Utc};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+/// Shadow engine, its position monitor and its paper execution (one per live engine)
+pub struct ShadowTwin {
+    pub name: &'static str,
+    pub engine: StrategyEngine,
+    pub monitor: PositionMonitor,
+    pub execution: PaperExecutionActor,
+}
+
+/// What all shadow twins of one configuration share
+pub struct Shadow {
+    /// Prefix of channel, task and event names ("shadow", "paper_<profile>")
+    label: String,
+    config: Arc<Config>,
+    journal: Journal,
+    events: EventBus,
+    temp_blacklist: TempBlacklist,
+    gate: PositionGate,
+    daily_trades: DailyTradeLimit,
+    notifier_tx: MeteredSender<NotifierMessage>,
+    /// Never read: shadow alerts are dropped
+    _notifier_rx: MeteredReceiver<NotifierMessage>,
+    /// Apart from the live registry: a stalled shadow must not trip the watchdog
+    health: Arc<HealthRegistry>,
+}
+
+impl Shadow {
+    pub fn new(label: &str, config: Config, metrics: &Metrics) -> Self {
+        let config = Arc::new(config);
+        let notifier = leak(format!("{}_notifier", label));
+        let (notifier_tx, notifier_rx) = metered_channel(notifier, 1, OverflowPolicy::DropOldest, metrics);
+        Self {
+            label: label.to_string(),
+            journal: Journal::new(config.data_path("journal.jsonl")),
+            events: EventBus::new(format!("{}-{}", config.bot_id, label)),
+            temp_blacklist: TempBlacklist::new(),
+            gate: PositionGate::new(config.max_open_positions),
+            daily_trades: DailyTradeLimit::load(
+                config.data_path("daily_trades.json"),
+                config.max_trades_per_day,
+                chrono::Utc::now(),
+            ),
+            notifier_tx,
+            _notifier_rx: notifier_rx,
+            health: HealthRegistry::new(),
+            config,
+        }
+    }
+
+    pub fn journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    /// Twin of `live` on the shadow config, fed by it from now on; `shard` = the
+    /// live engine's shard symbol, `prices` = the mid price cell its monitor reads
+    pub fn twin(
+        &self,
+        live: &mut StrategyEngine,
+        shard: Option<&Symbol>,
+        prices: watch::Receiver<Option<MarkPrice>>,
+        metrics: &Metrics,
+    ) -> Result<ShadowTwin> {
+        let suffix = shard.map(|symbol| format!("_{}", symbol)).unwrap_or_default();
+        let name = |base: &str| leak(format!("{}_{}{}", self.label, base, suffix));
+        let block = OverflowPolicy::Block { timeout: None };
+
+        let (market_tx, market_rx) = spsc::market_ring(name("market_data"), 1000, metrics);
+        let (strategy_tx, strategy_rx) = metered_channel(name("strategy"), 1000, block, metrics);
+        let (execution_normal_tx, execution_rx) = metered_channel(name("execution"), 100, block, metrics);
+        let (execution_priority_tx, execution_priority_rx) =
+            metered_channel(name("execution_priority"), 100, block, metrics);
+        let execution_tx = ExecutionSender::new(execution_normal_tx, execution_priority_tx);
+
+        let signal_model = self.config.signal_model_path.as_deref().map(SignalModel::load).transpose()?;
+        let mut engine = StrategyEngine::new(
+            self.config.clone(),
+            strategy_rx,
+            market_rx,
+            execution_tx.clone(),
+            self.notifier_tx.clone(),
+            self.journal.clone(),
+            self.temp_blacklist.clone(),
+            signal_model,
+            self.events.clone(),
+            self.health.register(name("strategy"), Duration::from_secs(POSITION_VERIFY_INTERVAL_SECS)),
+        );
+        if let Some(symbol) = shard {
+            engine.set_shard(symbol, self.gate.clone(), self.daily_trades.clone());
+        }
+        engine.set_state_stats(metrics.register_state_machine(name("strategy")));
+        live.add_shadow(market_tx);
+
+        let monitor = PositionMonitor::new(
+            prices.clone(),
+            engine.exit_plans(),
+            execution_tx,
+            strategy_tx.clone(),
+            self.health.register(name("position_monitor"), Duration::from_secs(MONITOR_BEAT_SECS)),
+        );
+        let execution = PaperExecutionActor::new(
+            self.config.clone(),
+            execution_rx,
+            execution_priority_rx,
+            strategy_tx,
+            prices,
+            self.health.register(name("execution"), Duration::from_secs(EXECUTION_LIVENESS_SECS)),
+        );
+
+        Ok(ShadowTwin {
+            name: name("strategy"),
+            engine,
+            monitor,
+            execution,
+        })
+    }
+}
+
+/// ✅ ARENA: The paper profiles as the leaderboard and `/promote` see them
+#[derive(Clone, Default)]
+pub struct Arena {
+    profiles: Arc<[ArenaProfile]>,
+}
+
+struct ArenaProfile {
+    name: String,
+    journal: Journal,
+    /// The profile's values of the parameters the live engine can change at runtime
+    params: ParamPatch,
+}
+
+impl Arena {
+    pub fn new<'a>(profiles: impl IntoIterator<Item = (&'a str, &'a Shadow)>) -> Self {
+        let profiles: Vec<ArenaProfile> = profiles
+            .into_iter()
+            .map(|(name, shadow)| ArenaProfile {
+                name: name.to_string(),
+                journal: shadow.journal.clone(),
+                params: ParamPatch {
+                    momentum_threshold: Some(shadow.config.momentum_threshold),
+                    trade_cooldown_secs: None,
+                    max_trades_per_day: Some(shadow.config.max_trades_per_day),
+                },
+            })
+            .collect();
+        Self { profiles: profiles.into() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// Telegram HTML leaderboard of live and every profile over the last `days`
+    pub fn leaderboard(&self, live: &Journal, days: u32) -> Result<String> {
+        let mut entries = vec![("live".to_string(), live.trades()?)];
+        for profile in self.profiles.iter() {
+            entries.push((profile.name.clone(), profile.journal.trades()?));
+        }
+        let since = Utc::now() - ChronoDuration::days(i64::from(days));
+        Ok(stats::render_leaderboard(&stats::leaderboard(&entries, since), days))
+    }
+
+    /// Patch that gives the live engine the runtime parameters of profile `name`
+    pub fn promotion(&self, name: &str) -> Result<ParamPatch, String> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .map(|profile| profile.params)
+            .ok_or_else(|| {
+                let names: Vec<&str> = self.profiles.iter().map(|profile| profile.name.as_str()).collect();
+                format!("No paper profile '{}' (profiles: {})", name, names.join(", "))
+            })
+    }
+}
+
+/// Channel and task names are needed for the whole run
+fn leak(name: String) -> &'static str {
+    Box::leak(name.into_boxed_str())
+}
+
+/// Telegram HTML report: live vs. shadow over the trades closed on `day` (UTC)
+pub fn render_comparison(day: NaiveDate, live: &[TradeRecord], shadow: &[TradeRecord]) -> String {
+    let stats = |trades: &[TradeRecord]| {
+        let mut stats = PerformanceStats::default();
+        for trade in trades.iter().filter(|trade| trade.closed_at.date_naive() == day) {
+            stats.add(trade);
+        }
+        stats
+    };
+    let (live, shadow) = (stats(live), stats(shadow));
+    let line = |name: &str, stats: &PerformanceStats| {
+        format!(
+            "\n<b>{}</b>: ${:.4} | {} trades | Win {:.0}% | Fees ${:.4}",
+            name,
+            stats.net_pnl,
+            stats.trades,
+            stats.win_rate(),
+            stats.fees
+        )
+    };
+    format!(
+        "👥 <b>Shadow comparison</b> {}{}{}\nShadow − live: <b>${:+.4}</b>",
+        day,
+        line("Live", &live),
+        line("Shadow", &shadow),
+        shadow.net_pnl - live.net_pnl
+    )
+}
+
+/// After every UTC midnight, alert the comparison of the day that just ended
+pub async fn run_daily_comparison(live: Journal, shadow: Journal, notifier_tx: MeteredSender<NotifierMessage>) {
+    loop {
+        let now = Utc::now();
+        let next_day = (now.date_naive() + ChronoDuration::days(1)).and_hms_opt(0, 0, 0).expect("midnight exists").and_utc();
+        // A little past midnight: trades closing on the stroke of it are journaled by then
+        let wait = (next_day - now).to_std().unwrap_or_default() + Duration::from_secs(5);
+        tokio::time::sleep(wait).await;
+
+        let day = next_day.date_naive() - ChronoDuration::days(1);
+        let (live_trades, shadow_trades) = match (live.trades(), shadow.trades()) {
+            (Ok(live), Ok(shadow)) => (live, shadow),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Failed to read journals for the shadow comparison: {:#}", e);
+                continue;
+            }
+        };
+        let text = render_comparison(day, &live_trades, &shadow_trades);
+        info!("👥 Shadow comparison for {} sent", day);
+        if let Err(e) = notifier_tx.send(NotifierMessage::Alert(Alert::info(text))).await {
+            warn!("Failed to send shadow comparison: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TradingMode;
+    use crate::models::{ExitReason, PositionSide};
+    use chrono::{DateTime, TimeZone};
+    use rust_decimal::Decimal;
+
+    fn trade(closed_at: DateTime<Utc>, net_pnl: i64) -> TradeRecord {
+        TradeRecord {
+            symbol: Symbol::from("BTCUSDT"),
+            side: PositionSide::Long,
+            qty: Decimal::ONE,
+            entry_price: Decimal::from(100),
+            exit_price: Decimal::from(100 + net_pnl),
+            gross_pnl: Decimal::from(net_pnl),
+            fees: Decimal::ZERO,
+            net_pnl: Decimal::from(net_pnl),
+            opened_at: closed_at,
+            closed_at,
+            hold_secs: 0,
+            mode: TradingMode::Momentum,
+            exit_reason: ExitReason::TakeProfit,
+            leverage: Decimal::ONE,
+            pnl_percent: 0.0,
+            roe_percent: 0.0,
+            confirmations: None,
+            price_change_24h: None,
+            entry_atr_percent: None,
+            entry_slippage_bps: None,
+        }
+    }
+
+    #[test]
+    fn test_comparison_counts_only_that_day() {
+        let day = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let at = |d: u32, h: u32| Utc.with_ymd_and_hms(2026, 3, d, h, 0, 0).unwrap();
+        let live = [trade(at(1, 3), 2), trade(at(1, 23), -1), trade(at(2, 0), 50)];
+        let shadow = [trade(at(1, 12), 4), trade(at(2, 1), -50)];
+
+        let text = render_comparison(day, &live, &shadow);
+        assert!(text.contains("<b>Live</b>: $1.0000 | 2 trades | Win 50%"), "{}", text);
+        assert!(text.contains("<b>Shadow</b>: $4.0000 | 1 trades | Win 100%"), "{}", text);
+        assert!(text.contains("Shadow − live: <b>$+3.0000</b>"), "{}", text);
+    }
+
+    #[test]
+    fn test_promotion_carries_the_profile_params() {
+        std::env::set_var("BYBIT_API_KEY", "paper");
+        std::env::set_var("BYBIT_API_SECRET", "paper");
+        let mut config = Config::from_env().unwrap();
+        config.momentum_threshold = 0.25;
+        config.max_trades_per_day = 12;
+        let shadow = Shadow::new("paper_tight", config, &Metrics::new());
+        let arena = Arena::new([("tight", &shadow)]);
+
+        let patch = arena.promotion("tight").unwrap();
+        assert_eq!(patch.momentum_threshold, Some(0.25));
+        assert_eq!(patch.max_trades_per_day, Some(12));
+        assert!(patch.validate().is_ok());
+        assert!(arena.promotion("loose").unwrap_err().contains("profiles: tight"));
+    }
+}
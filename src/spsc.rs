@@ -0,0 +1,215 @@
+//! Lock-Free Market Data Ring
+//!
+//! Orderbooks and trades go from the WebSocket reader to the strategy on a
+//! bounded single-producer/single-consumer ring (`rtrb`) instead of the
+//! mutex-guarded `metered_channel`: a push or pop is a couple of atomics, and
+//! the reader never contends with the control traffic on the strategy inbox.
+//! A `Notify` wakes the consumer. Same `SendError`s, stats and conflation as
+//! `channel`, so callers keep their overflow handling.
+
+use crate::channel::SendError;
+use crate::metrics::{ChannelStats, Metrics};
+use rtrb::{Consumer, Producer, PushError, RingBuffer};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::time::{Duration, Instant};
+
+struct Signals {
+    stats: Arc<ChannelStats>,
+    /// Wakes the consumer when a message arrives or the producer leaves
+    item_ready: Notify,
+    /// Wakes a producer waiting in `send_timeout` when space frees up or the consumer leaves
+    space_ready: Notify,
+}
+
+/// Producing half, owned by the one task that writes market data
+pub struct RingSender<T> {
+    producer: Producer<T>,
+    signals: Arc<Signals>,
+}
+
+/// Consuming half, owned by the strategy
+pub struct RingReceiver<T> {
+    consumer: Consumer<T>,
+    signals: Arc<Signals>,
+}
+
+/// Bounded SPSC ring, reporting to `metrics` under `name`
+pub fn market_ring<T>(name: &'static str, capacity: usize, metrics: &Metrics) -> (RingSender<T>, RingReceiver<T>) {
+    assert!(capacity > 0, "ring capacity must be > 0");
+    let (producer, consumer) = RingBuffer::new(capacity);
+    let signals = Arc::new(Signals {
+        stats: metrics.register_channel(name, capacity),
+        item_ready: Notify::new(),
+        space_ready: Notify::new(),
+    });
+    (
+        RingSender { producer, signals: signals.clone() },
+        RingReceiver { consumer, signals },
+    )
+}
+
+impl<T> RingSender<T> {
+    /// Never waits: rejects when the ring is full
+    pub fn try_send(&mut self, value: T) -> Result<(), SendError<T>> {
+        if self.producer.is_abandoned() {
+            self.signals.stats.record_dropped(self.depth());
+            return Err(SendError::Closed(value));
+        }
+        match self.producer.push(value) {
+            Ok(()) => {
+                self.signals.stats.record_sent(self.depth());
+                self.signals.item_ready.notify_one();
+                Ok(())
+            }
+            Err(PushError::Full(value)) => {
+                self.signals.stats.record_dropped(self.depth());
+                Err(SendError::Full(value))
+            }
+        }
+    }
+
+    /// Wait up to `timeout` for space
+    pub async fn send_timeout(&mut self, mut value: T, timeout: Duration) -> Result<(), SendError<T>> {
+        let deadline = Instant::now() + timeout;
+        let signals = self.signals.clone();
+        loop {
+            // Register interest before checking so a wakeup between check and await isn't lost
+            let notified = signals.space_ready.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            match self.try_send(value) {
+                Err(SendError::Full(v)) => value = v,
+                other => return other,
+            }
+            // try_send counted the Full as a drop; we are still trying
+            signals.stats.undo_dropped();
+
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                signals.stats.record_dropped(self.depth());
+                return Err(SendError::Timeout(value));
+            }
+        }
+    }
+
+    /// Messages currently queued
+    pub fn depth(&self) -> usize {
+        self.producer.buffer().capacity() - self.producer.slots()
+    }
+}
+
+impl<T> Drop for RingSender<T> {
+    fn drop(&mut self) {
+        // Let the consumer drain and observe the close
+        self.signals.item_ready.notify_one();
+    }
+}
+
+impl<T> RingReceiver<T> {
+    /// Next message; None once the producer is gone and the ring is drained
+    pub async fn recv(&mut self) -> Option<T> {
+        let signals = self.signals.clone();
+        loop {
+            let notified = signals.item_ready.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(value) = self.try_recv() {
+                return Some(value);
+            }
+            if self.consumer.is_abandoned() {
+                return self.try_recv();
+            }
+
+            notified.await;
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<T> {
+        let value = self.consumer.pop().ok()?;
+        self.signals.space_ready.notify_one();
+        Some(value)
+    }
+
+    /// Like `recv`, but a keyed message is skipped while a newer message with
+    /// the same key is still queued (see `MeteredReceiver::recv_conflated`)
+    pub async fn recv_conflated<K>(&mut self, key: impl Fn(&T) -> Option<&K>) -> Option<T>
+    where
+        K: PartialEq + ?Sized,
+    {
+        loop {
+            let value = self.recv().await?;
+            let Some(value_key) = key(&value) else {
+                return Some(value);
+            };
+            // Peek without consuming: a chunk that isn't committed stays queued
+            let superseded = match self.consumer.read_chunk(self.consumer.slots()) {
+                Ok(chunk) => {
+                    let (first, second) = chunk.as_slices();
+                    first.iter().chain(second).any(|queued| key(queued) == Some(value_key))
+                }
+                Err(_) => false,
+            };
+            if !superseded {
+                return Some(value);
+            }
+            self.signals.stats.record_conflated();
+        }
+    }
+}
+
+impl<T> Drop for RingReceiver<T> {
+    fn drop(&mut self) {
+        self.signals.space_ready.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ring_rejects_when_full_and_closes() {
+        let metrics = Metrics::new();
+        let (mut tx, mut rx) = market_ring::<u32>("market", 2, &metrics);
+
+        assert!(tx.try_send(1).is_ok());
+        assert!(tx.try_send(2).is_ok());
+        assert!(matches!(tx.try_send(3), Err(SendError::Full(3))));
+        assert_eq!(tx.depth(), 2);
+        assert_eq!(metrics.channels()[0].dropped(), 1);
+
+        assert_eq!(rx.recv().await, Some(1));
+        drop(tx);
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_ring_wakes_consumer_and_conflates() {
+        let metrics = Metrics::new();
+        let (mut tx, mut rx) = market_ring::<(&str, u32)>("market", 8, &metrics);
+        fn key<'a>(message: &'a (&str, u32)) -> Option<&'a str> {
+            (message.0 != "trade").then_some(message.0)
+        }
+
+        let consumer = tokio::spawn(async move {
+            let mut received = Vec::new();
+            while let Some(message) = rx.recv_conflated(key).await {
+                received.push(message.1);
+            }
+            received
+        });
+        tokio::task::yield_now().await;
+
+        for message in [("btc", 1), ("trade", 2), ("btc", 3)] {
+            tx.send_timeout(message, Duration::from_millis(100)).await.unwrap();
+        }
+        drop(tx);
+        let received = consumer.await.unwrap();
+        // The consumer may have been woken before the second book arrived
+        assert!(received == vec![2, 3] || received == vec![1, 2, 3], "{:?}", received);
+        assert_eq!(received.last(), Some(&3));
+    }
+}
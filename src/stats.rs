@@ -0,0 +1,280 @@
+//! Performance Stats
+//!
+//! Aggregates closed trades from the journal, split by signal mode and by
+//! symbol, so a losing side or coin can be spotted and switched off, and
+//! ranks journals against each other (the paper profile leaderboard).
+
+use crate::models::TradeRecord;
+use crate::notifications::escape_html;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// Totals over a group of closed trades
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PerformanceStats {
+    pub trades: u32,
+    pub wins: u32,
+    pub net_pnl: Decimal,
+    pub fees: Decimal,
+    /// Sum of ROE %, for the average
+    roe_sum: f64,
+    /// Sum of confirmation ticks over trades that recorded them
+    confirmations_sum: u32,
+    confirmations_count: u32,
+    slippage_sum: f64,
+    slippage_count: u32,
+}
+
+impl PerformanceStats {
+    pub fn add(&mut self, trade: &TradeRecord) {
+        self.trades += 1;
+        if trade.net_pnl > Decimal::ZERO {
+            self.wins += 1;
+        }
+        self.net_pnl += trade.net_pnl;
+        self.fees += trade.fees;
+        self.roe_sum += trade.roe_percent;
+        if let Some(confirmations) = trade.confirmations {
+            self.confirmations_sum += u32::from(confirmations);
+            self.confirmations_count += 1;
+        }
+        if let Some(slippage) = trade.entry_slippage_bps {
+            self.slippage_sum += slippage;
+            self.slippage_count += 1;
+        }
+    }
+
+    /// Winning trades, % of all
+    pub fn win_rate(&self) -> f64 {
+        if self.trades == 0 {
+            return 0.0;
+        }
+        f64::from(self.wins) / f64::from(self.trades) * 100.0
+    }
+
+    pub fn avg_net_pnl(&self) -> Decimal {
+        if self.trades == 0 {
+            return Decimal::ZERO;
+        }
+        self.net_pnl / Decimal::from(self.trades)
+    }
+
+    pub fn avg_roe_percent(&self) -> f64 {
+        if self.trades == 0 {
+            return 0.0;
+        }
+        self.roe_sum / f64::from(self.trades)
+    }
+
+    pub fn avg_confirmations(&self) -> Option<f64> {
+        (self.confirmations_count > 0)
+            .then(|| f64::from(self.confirmations_sum) / f64::from(self.confirmations_count))
+    }
+
+    /// Mean entry slippage, bps (positive = worse than the sizing mid)
+    pub fn avg_slippage_bps(&self) -> Option<f64> {
+        (self.slippage_count > 0).then(|| self.slippage_sum / f64::from(self.slippage_count))
+    }
+}
+
+/// Trades grouped by an arbitrary key
+pub fn group_by<'a, K: Ord>(
+    trades: impl IntoIterator<Item = &'a TradeRecord>,
+    key: impl Fn(&TradeRecord) -> K,
+) -> BTreeMap<K, PerformanceStats> {
+    let mut groups: BTreeMap<K, PerformanceStats> = BTreeMap::new();
+    for trade in trades {
+        groups.entry(key(trade)).or_default().add(trade);
+    }
+    groups
+}
+
+/// Trades split by signal mode (MOMENTUM / MEAN_REVERSION)
+pub fn by_mode<'a>(trades: impl IntoIterator<Item = &'a TradeRecord>) -> BTreeMap<String, PerformanceStats> {
+    group_by(trades, |t| t.mode.to_string())
+}
+
+pub fn by_symbol<'a>(trades: impl IntoIterator<Item = &'a TradeRecord>) -> BTreeMap<String, PerformanceStats> {
+    group_by(trades, |t| t.symbol.to_string())
+}
+
+/// Symbols shown in the /stats report (worst first)
+const REPORT_SYMBOLS: usize = 10;
+
+/// Telegram HTML report, one line per symbol, worst net PnL first
+pub fn render_by_symbol(trades: &[TradeRecord]) -> String {
+    let mut symbols: Vec<(String, PerformanceStats)> = by_symbol(trades).into_iter().collect();
+    if symbols.is_empty() {
+        return "🪙 <b>Performance by symbol</b>\nNo closed trades yet".to_string();
+    }
+    symbols.sort_by_key(|(_, stats)| stats.net_pnl);
+
+    let mut text = format!("🪙 <b>Performance by symbol</b> ({} symbols)", symbols.len());
+    for (symbol, stats) in symbols.iter().take(REPORT_SYMBOLS) {
+        text.push_str(&format!(
+            "\n<b>{}</b>: ${:.4} | {} trades | Win {:.0}% | Slip {}",
+            escape_html(symbol),
+            stats.net_pnl,
+            stats.trades,
+            stats.win_rate(),
+            stats.avg_slippage_bps().map(|s| format!("{:+.1} bps", s)).unwrap_or_else(|| "-".to_string())
+        ));
+    }
+    if symbols.len() > REPORT_SYMBOLS {
+        text.push_str(&format!("\n… and {} more", symbols.len() - REPORT_SYMBOLS));
+    }
+    text
+}
+
+/// Telegram HTML report, one block per mode
+pub fn render_by_mode(trades: &[TradeRecord]) -> String {
+    if trades.is_empty() {
+        return "📊 <b>Performance by mode</b>\nNo closed trades yet".to_string();
+    }
+    let mut text = format!("📊 <b>Performance by mode</b> ({} trades)", trades.len());
+    for (mode, stats) in by_mode(trades) {
+        text.push_str(&format!(
+            "\n\n<b>{}</b>: {} trades | Win rate {:.0}%\n\
+             Net PnL: <b>${:.4}</b> (avg ${:.4}, fees ${:.4})\n\
+             Avg ROE: {:+.2}% | Avg confirmations: {}",
+            escape_html(&mode),
+            stats.trades,
+            stats.win_rate(),
+            stats.net_pnl,
+            stats.avg_net_pnl(),
+            stats.fees,
+            stats.avg_roe_percent(),
+            stats.avg_confirmations().map(|c| format!("{:.1}", c)).unwrap_or_else(|| "-".to_string())
+        ));
+    }
+    let losing: Vec<String> = by_mode(trades)
+        .into_iter()
+        .filter(|(_, s)| s.net_pnl < Decimal::ZERO)
+        .map(|(mode, _)| mode)
+        .collect();
+    if !losing.is_empty() {
+        text.push_str(&format!("\n\n⚠️ Losing money: {}", escape_html(&losing.join(", "))));
+    }
+    text
+}
+
+/// Totals of each named journal over the trades closed since `since`, best net PnL first
+pub fn leaderboard(entries: &[(String, Vec<TradeRecord>)], since: DateTime<Utc>) -> Vec<(String, PerformanceStats)> {
+    let mut rows: Vec<(String, PerformanceStats)> = entries
+        .iter()
+        .map(|(name, trades)| {
+            let mut stats = PerformanceStats::default();
+            for trade in trades.iter().filter(|t| t.closed_at >= since) {
+                stats.add(trade);
+            }
+            (name.clone(), stats)
+        })
+        .collect();
+    rows.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.net_pnl));
+    rows
+}
+
+/// Telegram HTML leaderboard, one line per journal
+pub fn render_leaderboard(rows: &[(String, PerformanceStats)], days: u32) -> String {
+    let mut text = format!("🏆 <b>Leaderboard</b> (last {} days)", days);
+    for (rank, (name, stats)) in rows.iter().enumerate() {
+        text.push_str(&format!(
+            "\n{}. <b>{}</b>: ${:.4} | {} trades | Win {:.0}% | Avg ROE {:+.2}%",
+            rank + 1,
+            escape_html(name),
+            stats.net_pnl,
+            stats.trades,
+            stats.win_rate(),
+            stats.avg_roe_percent()
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TradingMode;
+    use crate::models::{ExitReason, PositionSide, Symbol};
+    use chrono::Utc;
+
+    fn trade(mode: TradingMode, net_pnl: i64, confirmations: Option<u8>) -> TradeRecord {
+        TradeRecord {
+            symbol: Symbol::from("BTCUSDT"),
+            side: PositionSide::Long,
+            qty: Decimal::ONE,
+            entry_price: Decimal::from(100),
+            exit_price: Decimal::from(100 + net_pnl),
+            gross_pnl: Decimal::from(net_pnl),
+            fees: Decimal::ZERO,
+            net_pnl: Decimal::from(net_pnl),
+            opened_at: Utc::now(),
+            closed_at: Utc::now(),
+            hold_secs: 60,
+            mode,
+            exit_reason: ExitReason::TakeProfit,
+            leverage: Decimal::ONE,
+            pnl_percent: net_pnl as f64,
+            roe_percent: net_pnl as f64,
+            confirmations,
+            price_change_24h: None,
+            entry_atr_percent: None,
+            entry_slippage_bps: Some(net_pnl.abs() as f64),
+        }
+    }
+
+    #[test]
+    fn test_stats_split_by_mode() {
+        let trades = vec![
+            trade(TradingMode::Momentum, 2, Some(3)),
+            trade(TradingMode::Momentum, -1, Some(5)),
+            trade(TradingMode::MeanReversion, -3, None),
+        ];
+        let split = by_mode(&trades);
+
+        let momentum = &split["MOMENTUM"];
+        assert_eq!((momentum.trades, momentum.wins), (2, 1));
+        assert_eq!(momentum.net_pnl, Decimal::ONE);
+        assert!((momentum.win_rate() - 50.0).abs() < 1e-9);
+        assert_eq!(momentum.avg_confirmations(), Some(4.0));
+
+        let reversion = &split["MEAN_REVERSION"];
+        assert_eq!(reversion.net_pnl, Decimal::from(-3));
+        assert_eq!(reversion.avg_confirmations(), None);
+
+        assert!(render_by_mode(&trades).contains("Losing money: MEAN_REVERSION"));
+    }
+
+    #[test]
+    fn test_stats_by_symbol_track_slippage() {
+        let mut trades = vec![trade(TradingMode::Momentum, 2, None), trade(TradingMode::Momentum, -4, None)];
+        trades[1].symbol = Symbol::from("DOGEUSDT");
+
+        let split = by_symbol(&trades);
+        assert_eq!(split["BTCUSDT"].avg_slippage_bps(), Some(2.0));
+        assert_eq!(split["DOGEUSDT"].net_pnl, Decimal::from(-4));
+
+        // Worst symbol first
+        let report = render_by_symbol(&trades);
+        assert!(report.find("DOGEUSDT").unwrap() < report.find("BTCUSDT").unwrap());
+    }
+
+    #[test]
+    fn test_leaderboard_ranks_by_net_pnl_in_window() {
+        let mut old = trade(TradingMode::Momentum, 100, None);
+        old.closed_at = Utc::now() - chrono::Duration::days(30);
+        let entries = vec![
+            ("live".to_string(), vec![trade(TradingMode::Momentum, 1, None), old]),
+            ("tight".to_string(), vec![trade(TradingMode::Momentum, 3, None), trade(TradingMode::Momentum, -1, None)]),
+            ("loose".to_string(), vec![]),
+        ];
+
+        let rows = leaderboard(&entries, Utc::now() - chrono::Duration::days(7));
+        let names: Vec<&str> = rows.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["tight", "live", "loose"]);
+        // The month-old trade is outside the window
+        assert_eq!(rows[1].1.net_pnl, Decimal::ONE);
+        assert!(render_leaderboard(&rows, 7).contains("1. <b>tight</b>: $2.0000 | 2 trades | Win 50%"));
+    }
+}
@@ -0,0 +1,367 @@
+//! Operator Symbol Lists
+//!
+//! `BLACKLIST_SYMBOLS` keeps coins out of scanning and entries; a non-empty
+//! `WHITELIST_SYMBOLS` is the only coins allowed in. Both can be edited while
+//! running (Telegram `/blacklist`, control API `/blacklist` and `/whitelist`).
+//! Edits are persisted as overrides of the env lists (`DATA_DIR/blacklist.json`,
+//! `DATA_DIR/whitelist.json`), so the env list and the edits both survive a
+//! restart, and every edit is appended to `DATA_DIR/symbol_lists_audit.jsonl`
+//! with who made it. Clones share the same lists (scanner, strategies, Telegram,
+//! control API). Each edit is decided and applied under the list's lock; the
+//! files are written on a blocking thread. The last whitelisted symbol can't be
+//! removed: an empty whitelist would let every coin in.
+
+use crate::config::Config;
+use crate::persist;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeSet, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Runtime edits on top of the env list (the env list itself stays as configured)
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Overrides {
+    added: BTreeSet<String>,
+    removed: BTreeSet<String>,
+    /// Bumped by every edit; orders the writes
+    #[serde(skip)]
+    version: u64,
+}
+
+impl Overrides {
+    fn contains(&self, configured: &HashSet<String>, key: &str) -> bool {
+        self.added.contains(key) || (configured.contains(key) && !self.removed.contains(key))
+    }
+
+    fn len(&self, configured: &HashSet<String>) -> usize {
+        configured.iter().filter(|s| !self.removed.contains(*s)).count() + self.added.len()
+    }
+}
+
+/// What a list edit did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    Changed,
+    /// Already on the list (add) or not on it (remove)
+    Unchanged,
+    /// Removing the last whitelisted symbol would lift the whitelist
+    LastEntry,
+}
+
+/// One env-configured list with its persisted runtime edits
+#[derive(Debug, Clone)]
+pub struct SymbolList {
+    configured: Arc<HashSet<String>>,
+    overrides: Arc<Mutex<Overrides>>,
+    /// None = edits live in memory only
+    path: Option<PathBuf>,
+    /// Version of the overrides on disk (a late write never lands over a newer one)
+    written: Arc<Mutex<u64>>,
+}
+
+impl SymbolList {
+    /// The env list alone, nothing persisted (replays, paper engines, tests)
+    pub fn fixed(symbols: &[String]) -> Self {
+        Self {
+            configured: Arc::new(symbols.iter().map(|s| s.to_uppercase()).collect()),
+            overrides: Arc::default(),
+            path: None,
+            written: Arc::default(),
+        }
+    }
+
+    pub fn load(path: impl Into<PathBuf>, symbols: &[String]) -> Self {
+        let path = path.into();
+        let overrides = match persist::load_json(&path) {
+            Ok(overrides) => overrides.unwrap_or_default(),
+            Err(e) => {
+                warn!("⚠️ Ignoring symbol list file: {:#}", e);
+                Overrides::default()
+            }
+        };
+        Self {
+            overrides: Arc::new(Mutex::new(overrides)),
+            path: Some(path),
+            ..Self::fixed(symbols)
+        }
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.overrides.lock().contains(&self.configured, &symbol.to_uppercase())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.lock().len(&self.configured) == 0
+    }
+
+    pub async fn add(&self, symbol: &str) -> Edit {
+        let key = symbol.to_uppercase();
+        self.edit(|configured, overrides| {
+            if overrides.contains(configured, &key) {
+                return Edit::Unchanged;
+            }
+            if !overrides.removed.remove(&key) {
+                overrides.added.insert(key);
+            }
+            Edit::Changed
+        })
+        .await
+    }
+
+    /// `keep_last`: refuse to remove the only symbol left
+    pub async fn remove(&self, symbol: &str, keep_last: bool) -> Edit {
+        let key = symbol.to_uppercase();
+        self.edit(|configured, overrides| {
+            if !overrides.contains(configured, &key) {
+                return Edit::Unchanged;
+            }
+            if keep_last && overrides.len(configured) == 1 {
+                return Edit::LastEntry;
+            }
+            if !overrides.added.remove(&key) {
+                overrides.removed.insert(key);
+            }
+            Edit::Changed
+        })
+        .await
+    }
+
+    /// Symbols on the list, sorted
+    pub fn list(&self) -> Vec<String> {
+        let overrides = self.overrides.lock();
+        let configured = self.configured.iter().filter(|s| !overrides.removed.contains(*s));
+        configured
+            .chain(&overrides.added)
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Decide and apply `change` under the lock, then persist what it changed
+    async fn edit(&self, change: impl FnOnce(&HashSet<String>, &mut Overrides) -> Edit) -> Edit {
+        let snapshot = {
+            let mut overrides = self.overrides.lock();
+            let edit = change(&self.configured, &mut overrides);
+            if edit != Edit::Changed {
+                return edit;
+            }
+            overrides.version += 1;
+            overrides.clone()
+        };
+        let Some(path) = self.path.clone() else {
+            return Edit::Changed;
+        };
+        let written = self.written.clone();
+        let persisted = tokio::task::spawn_blocking(move || {
+            let mut written = written.lock();
+            if snapshot.version > *written {
+                persist::save_json(&path, &snapshot)?;
+                *written = snapshot.version;
+            }
+            Ok::<_, anyhow::Error>(())
+        })
+        .await;
+        match persisted {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("⚠️ Failed to persist symbol list: {:#}", e),
+            Err(e) => warn!("⚠️ Failed to persist symbol list: {}", e),
+        }
+        Edit::Changed
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListKind {
+    Blacklist,
+    Whitelist,
+}
+
+impl std::fmt::Display for ListKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListKind::Blacklist => write!(f, "blacklist"),
+            ListKind::Whitelist => write!(f, "whitelist"),
+        }
+    }
+}
+
+/// Black- and whitelist together, with the audit trail of their edits
+#[derive(Debug, Clone)]
+pub struct SymbolLists {
+    blacklist: SymbolList,
+    whitelist: SymbolList,
+    /// None = edits are not recorded
+    audit: Option<PathBuf>,
+}
+
+impl SymbolLists {
+    pub fn new(blacklist: SymbolList, whitelist: SymbolList, audit: Option<PathBuf>) -> Self {
+        Self { blacklist, whitelist, audit }
+    }
+
+    /// Env lists without persistence (each engine's default until the live lists are shared)
+    pub fn fixed(config: &Config) -> Self {
+        Self::new(
+            SymbolList::fixed(&config.blacklist_symbols),
+            SymbolList::fixed(&config.whitelist_symbols),
+            None,
+        )
+    }
+
+    /// Env lists plus the edits persisted under DATA_DIR
+    pub fn load(config: &Config) -> Self {
+        Self::new(
+            SymbolList::load(config.data_path("blacklist.json"), &config.blacklist_symbols),
+            SymbolList::load(config.data_path("whitelist.json"), &config.whitelist_symbols),
+            Some(config.data_path("symbol_lists_audit.jsonl")),
+        )
+    }
+
+    fn get(&self, kind: ListKind) -> &SymbolList {
+        match kind {
+            ListKind::Blacklist => &self.blacklist,
+            ListKind::Whitelist => &self.whitelist,
+        }
+    }
+
+    /// Why `symbol` may not be traded (None = allowed)
+    pub fn rejects(&self, symbol: &str) -> Option<&'static str> {
+        if self.blacklist.contains(symbol) {
+            Some("blacklisted")
+        } else if !self.whitelist.is_empty() && !self.whitelist.contains(symbol) {
+            Some("not whitelisted")
+        } else {
+            None
+        }
+    }
+
+    pub fn allows(&self, symbol: &str) -> bool {
+        self.rejects(symbol).is_none()
+    }
+
+    pub fn list(&self, kind: ListKind) -> Vec<String> {
+        self.get(kind).list()
+    }
+
+    /// Only a change is recorded
+    pub async fn add(&self, kind: ListKind, symbol: &str, by: &str) -> Edit {
+        let edit = self.get(kind).add(symbol).await;
+        if edit == Edit::Changed {
+            self.record(kind, "add", symbol, by).await;
+        }
+        edit
+    }
+
+    /// Only a change is recorded; the whitelist keeps its last symbol
+    pub async fn remove(&self, kind: ListKind, symbol: &str, by: &str) -> Edit {
+        let edit = self.get(kind).remove(symbol, kind == ListKind::Whitelist).await;
+        if edit == Edit::Changed {
+            self.record(kind, "remove", symbol, by).await;
+        }
+        edit
+    }
+
+    async fn record(&self, kind: ListKind, action: &str, symbol: &str, by: &str) {
+        info!("📋 {} {} {} (by {})", kind, action, symbol.to_uppercase(), by);
+        let Some(path) = self.audit.clone() else {
+            return;
+        };
+        let entry = json!({
+            "at": Utc::now(),
+            "list": kind,
+            "action": action,
+            "symbol": symbol.to_uppercase(),
+            "by": by,
+        });
+        match tokio::task::spawn_blocking(move || append_line(&path, &entry.to_string())).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("⚠️ Failed to record symbol list edit: {:#}", e),
+            Err(e) => warn!("⚠️ Failed to record symbol list edit: {}", e),
+        }
+    }
+}
+
+fn append_line(path: &Path, line: &str) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    writeln!(file, "{}", line).with_context(|| format!("failed to append to {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_edits_persist_over_the_env_list() {
+        let dir = std::env::temp_dir().join(format!("symbol-list-test-{}", std::process::id()));
+        let path = dir.join("blacklist.json");
+        let configured = vec!["PEPEUSDT".to_string()];
+
+        let blacklist = SymbolList::load(&path, &configured);
+        assert!(blacklist.contains("pepeusdt"));
+        assert_eq!(blacklist.add("dogeusdt").await, Edit::Changed);
+        assert_eq!(blacklist.add("DOGEUSDT").await, Edit::Unchanged);
+        assert_eq!(blacklist.remove("PEPEUSDT", false).await, Edit::Changed);
+        assert_eq!(blacklist.remove("SOLUSDT", false).await, Edit::Unchanged);
+        assert_eq!(blacklist.list(), vec!["DOGEUSDT".to_string()]);
+
+        // A restart keeps both edits
+        let reloaded = SymbolList::load(&path, &configured);
+        assert!(reloaded.contains("DOGEUSDT"));
+        assert!(!reloaded.contains("PEPEUSDT"));
+        assert_eq!(reloaded.add("PEPEUSDT").await, Edit::Changed);
+        assert_eq!(reloaded.list(), vec!["DOGEUSDT".to_string(), "PEPEUSDT".to_string()]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_whitelist_fences_in_and_edits_are_audited() {
+        let dir = std::env::temp_dir().join(format!("symbol-lists-test-{}", std::process::id()));
+        let audit = dir.join("audit.jsonl");
+        let lists = SymbolLists::new(
+            SymbolList::fixed(&["PEPEUSDT".to_string()]),
+            SymbolList::fixed(&[]),
+            Some(audit.clone()),
+        );
+        // Empty whitelist = everything not blacklisted
+        assert!(lists.allows("SOLUSDT"));
+        assert_eq!(lists.rejects("PEPEUSDT"), Some("blacklisted"));
+
+        assert_eq!(lists.add(ListKind::Whitelist, "solusdt", "control_api").await, Edit::Changed);
+        assert!(lists.allows("SOLUSDT"));
+        assert_eq!(lists.rejects("DOGEUSDT"), Some("not whitelisted"));
+        // No-op and refused edits leave no trace; the whitelist can't be emptied into allowing everything
+        assert_eq!(lists.add(ListKind::Whitelist, "SOLUSDT", "control_api").await, Edit::Unchanged);
+        assert_eq!(lists.remove(ListKind::Whitelist, "SOLUSDT", "control_api").await, Edit::LastEntry);
+        assert_eq!(lists.rejects("DOGEUSDT"), Some("not whitelisted"));
+        assert_eq!(lists.remove(ListKind::Blacklist, "PEPEUSDT", "telegram:42").await, Edit::Changed);
+
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&audit)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!((&lines[0]["list"], &lines[0]["action"], &lines[0]["symbol"]), (&json!("whitelist"), &json!("add"), &json!("SOLUSDT")));
+        assert_eq!((&lines[1]["list"], &lines[1]["by"]), (&json!("blacklist"), &json!("telegram:42")));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
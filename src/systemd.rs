@@ -0,0 +1,131 @@
+//! systemd Notifications
+//!
+//! With `Type=notify` the bot reports `READY=1` once every actor is running
+//! and `STOPPING=1` on shutdown. With `WatchdogSec=` the watchdog actor sends
+//! `WATCHDOG=1` only while no actor is stalled, so a hung bot stops petting
+//! systemd and gets restarted. Outside systemd (no `NOTIFY_SOCKET`) all of
+//! this is off.
+
+use std::env;
+use tokio::time::Duration;
+use tracing::{debug, warn};
+
+#[derive(Debug, Clone)]
+pub struct SystemdNotifier {
+    /// `NOTIFY_SOCKET`: a path, or `@name` for an abstract socket
+    socket: String,
+    /// Petting deadline from `WATCHDOG_USEC` (None = watchdog not enabled)
+    watchdog_timeout: Option<Duration>,
+}
+
+impl SystemdNotifier {
+    /// None when not started by systemd with `Type=notify`
+    pub fn from_env() -> Option<Self> {
+        let socket = env::var("NOTIFY_SOCKET").ok().filter(|s| !s.is_empty())?;
+        Some(Self {
+            socket,
+            watchdog_timeout: watchdog_timeout(
+                env::var("WATCHDOG_USEC").ok().as_deref(),
+                env::var("WATCHDOG_PID").ok().as_deref(),
+                std::process::id(),
+            ),
+        })
+    }
+
+    pub fn watchdog_timeout(&self) -> Option<Duration> {
+        self.watchdog_timeout
+    }
+
+    pub fn ready(&self) {
+        self.notify("READY=1\nSTATUS=Trading");
+    }
+
+    pub fn stopping(&self) {
+        self.notify("STOPPING=1");
+    }
+
+    pub fn watchdog(&self) {
+        self.notify("WATCHDOG=1");
+    }
+
+    /// Best effort: a failed notification is logged, never fatal
+    fn notify(&self, state: &str) {
+        match self.send(state) {
+            Ok(()) => debug!("systemd notify: {}", state.replace('\n', " ")),
+            Err(e) => warn!("systemd notify '{}' failed: {}", state.replace('\n', " "), e),
+        }
+    }
+
+    #[cfg(unix)]
+    fn send(&self, state: &str) -> std::io::Result<()> {
+        use std::os::unix::net::UnixDatagram;
+
+        let socket = UnixDatagram::unbound()?;
+        match self.socket.strip_prefix('@') {
+            #[cfg(target_os = "linux")]
+            Some(name) => {
+                use std::os::linux::net::SocketAddrExt;
+                let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+                socket.send_to_addr(state.as_bytes(), &addr)?;
+            }
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "abstract sockets are Linux-only",
+                ))
+            }
+            None => {
+                socket.send_to(state.as_bytes(), &self.socket)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn send(&self, _state: &str) -> std::io::Result<()> {
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "sd_notify needs a Unix socket"))
+    }
+}
+
+/// `WATCHDOG_USEC`, unless `WATCHDOG_PID` says it's meant for another process
+fn watchdog_timeout(usec: Option<&str>, pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    if pid.is_some_and(|pid| pid.trim().parse::<u32>().ok() != Some(own_pid)) {
+        return None;
+    }
+    let usec: u64 = usec?.trim().parse().ok().filter(|&u| u > 0)?;
+    Some(Duration::from_micros(usec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchdog_timeout_only_for_this_process() {
+        assert_eq!(watchdog_timeout(Some("30000000"), None, 42), Some(Duration::from_secs(30)));
+        assert_eq!(watchdog_timeout(Some("30000000"), Some("42"), 42), Some(Duration::from_secs(30)));
+        assert_eq!(watchdog_timeout(Some("30000000"), Some("7"), 42), None);
+        assert_eq!(watchdog_timeout(Some("0"), None, 42), None);
+        assert_eq!(watchdog_timeout(None, None, 42), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_notify_reaches_the_socket() {
+        let path = std::env::temp_dir().join(format!("sd-notify-test-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+
+        let notifier = SystemdNotifier {
+            socket: path.to_string_lossy().into_owned(),
+            watchdog_timeout: None,
+        };
+        notifier.watchdog();
+
+        let mut buf = [0u8; 64];
+        let n = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"WATCHDOG=1");
+        let _ = std::fs::remove_file(&path);
+    }
+}
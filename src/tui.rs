@@ -0,0 +1,332 @@
+//! Terminal Dashboard
+//!
+//! `--tui` replaces the log output with live panels: symbol and state, order
+//! book top, indicators, the open position and a scrolling trade log. Data
+//! comes from the strategy's status snapshot (polled) and the event bus; logs
+//! go to `DATA_DIR/tui.log` meanwhile. `q`, `Esc` or `Ctrl+C` stops the bot; if
+//! the terminal can't be set up the bot keeps trading headless.
+//! Needs the `tui` build feature.
+
+use crate::events::{BotEvent, EventKind};
+use crate::models::OrderSide;
+use crate::redact::redact;
+
+/// Whether this build can run `--tui`
+pub const AVAILABLE: bool = cfg!(feature = "tui");
+
+/// One trade log line for an event (None for periodic ones), credentials redacted
+pub fn log_line(event: &BotEvent) -> Option<String> {
+    let text = match &event.kind {
+        EventKind::Signal {
+            symbol,
+            bullish,
+            price,
+            source,
+            taken,
+            reject_reason,
+        } => format!(
+            "SIGNAL  {} {} @ {} ({}) {}",
+            symbol,
+            if *bullish { "LONG" } else { "SHORT" },
+            price,
+            source,
+            if *taken {
+                "→ entry".to_string()
+            } else {
+                format!("rejected: {}", reject_reason.as_deref().unwrap_or("-"))
+            }
+        ),
+        EventKind::OrderSubmitted { order } => format!(
+            "ORDER   {} {} {} {:?}",
+            order.symbol,
+            if order.side == OrderSide::Buy { "BUY" } else { "SELL" },
+            order.qty,
+            order.order_type
+        ),
+        EventKind::EntryFilled { fill } => format!("FILLED  {} {} @ {}", fill.symbol, fill.qty, fill.avg_price),
+        EventKind::TradeClosed { trade } => format!(
+            "CLOSED  {} {:?} net {} USDT (ROE {:+.2}%) {} after {}s",
+            trade.symbol, trade.side, trade.net_pnl, trade.roe_percent, trade.exit_reason, trade.hold_secs
+        ),
+        EventKind::StateChanged { from, to, .. } => format!("STATE   {} → {}", from, to),
+        EventKind::ControlCommand { command } => format!("COMMAND {}", command),
+        EventKind::TickStats { .. } | EventKind::PositionPnl { .. } => return None,
+    };
+    Some(format!("{} {}", event.at.format("%H:%M:%S"), redact(&text)))
+}
+
+#[cfg(feature = "tui")]
+mod imp {
+    use super::*;
+    use crate::actors::messages::{StrategyMessage, StrategyStatus};
+    use crate::channel::MeteredSender;
+    use crate::events::EventBus;
+    use anyhow::Result;
+    use parking_lot::Mutex;
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::{Block, Borders, Paragraph};
+    use ratatui::Frame;
+    use rust_decimal::Decimal;
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use tokio::sync::broadcast::error::RecvError;
+    use tokio::sync::oneshot;
+    use tokio::time::{interval, timeout, Duration};
+
+    const STATUS_POLL_MS: u64 = 500;
+    const TRADE_LOG_LINES: usize = 200;
+    const BOOK_LEVELS: usize = 5;
+
+    #[derive(Default)]
+    struct Dashboard {
+        status: Option<StrategyStatus>,
+        trade_log: VecDeque<String>,
+    }
+
+    /// Run until the user quits; the caller stops the bot afterwards
+    pub async fn run(strategy_tx: MeteredSender<StrategyMessage>, events: EventBus) -> Result<()> {
+        let dashboard = Arc::new(Mutex::new(Dashboard::default()));
+
+        let poller = tokio::spawn(poll_status(strategy_tx, dashboard.clone()));
+        let collector = tokio::spawn(collect_events(events, dashboard.clone()));
+
+        // Terminal drawing and key polling block, keep them off the runtime workers
+        let ui = tokio::task::spawn_blocking(move || -> Result<()> {
+            let mut terminal = ratatui::try_init()?;
+            let result = (|| -> Result<()> {
+                loop {
+                    terminal.draw(|frame| draw(frame, &dashboard.lock()))?;
+                    if event::poll(Duration::from_millis(250))? {
+                        if let Event::Key(key) = event::read()? {
+                            let ctrl_c = key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c');
+                            if key.kind == KeyEventKind::Press
+                                && (ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc))
+                            {
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
+            })();
+            ratatui::restore();
+            result
+        })
+        .await;
+
+        poller.abort();
+        collector.abort();
+        ui?
+    }
+
+    async fn poll_status(strategy_tx: MeteredSender<StrategyMessage>, dashboard: Arc<Mutex<Dashboard>>) {
+        let mut tick = interval(Duration::from_millis(STATUS_POLL_MS));
+        loop {
+            tick.tick().await;
+            let (reply_tx, reply_rx) = oneshot::channel();
+            if strategy_tx.try_send(StrategyMessage::Status(reply_tx)).is_err() {
+                continue;
+            }
+            if let Ok(Ok(status)) = timeout(Duration::from_secs(2), reply_rx).await {
+                dashboard.lock().status = Some(status);
+            }
+        }
+    }
+
+    async fn collect_events(events: EventBus, dashboard: Arc<Mutex<Dashboard>>) {
+        let mut rx = events.subscribe();
+        loop {
+            let line = match rx.recv().await {
+                Ok(event) => log_line(&event),
+                Err(RecvError::Lagged(missed)) => Some(format!("… {} events skipped", missed)),
+                Err(RecvError::Closed) => return,
+            };
+            if let Some(line) = line {
+                let mut dashboard = dashboard.lock();
+                if dashboard.trade_log.len() == TRADE_LOG_LINES {
+                    dashboard.trade_log.pop_front();
+                }
+                dashboard.trade_log.push_back(line);
+            }
+        }
+    }
+
+    fn opt<T: std::fmt::Display>(value: Option<T>) -> String {
+        value.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+    }
+
+    fn pnl_style(value: f64) -> Style {
+        Style::default().fg(if value >= 0.0 { Color::Green } else { Color::Red })
+    }
+
+    fn draw(frame: &mut Frame, dashboard: &Dashboard) {
+        let [header, panels, log, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(2 * BOOK_LEVELS as u16 + 4),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [book, indicators, position] = Layout::horizontal([Constraint::Ratio(1, 3); 3]).areas(panels);
+
+        let Some(status) = dashboard.status.as_ref() else {
+            frame.render_widget(
+                Paragraph::new("Waiting for the strategy...").block(Block::default().borders(Borders::ALL)),
+                header,
+            );
+            return;
+        };
+
+        // Header: symbol, state, flags
+        let mut flags = Vec::new();
+        if status.kill_switch {
+            flags.push(Span::styled(" KILL SWITCH ", Style::default().bg(Color::Red)));
+        }
+        if status.paused {
+            flags.push(Span::styled(" PAUSED ", Style::default().bg(Color::Yellow).fg(Color::Black)));
+        }
+        if status.circuit_breaker {
+            flags.push(Span::styled(" CIRCUIT BREAKER ", Style::default().bg(Color::Yellow).fg(Color::Black)));
+        }
+        if status.loss_streak_halted || status.loss_streak_paused_until.is_some() {
+            flags.push(Span::styled(" LOSS STREAK ", Style::default().bg(Color::Magenta)));
+        }
+        let mut title = vec![
+            Span::styled(
+                opt(status.symbol.as_ref()),
+                Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+            ),
+            Span::raw(format!(
+                "  {}  up {}m  last tick {}s ago  trades today {}  ",
+                status.state,
+                status.uptime_secs / 60,
+                opt(status.last_tick_age_secs),
+                status.trades_today
+            )),
+        ];
+        title.extend(flags);
+        frame.render_widget(
+            Paragraph::new(Line::from(title)).block(Block::default().borders(Borders::ALL).title(" Scalper ")),
+            header,
+        );
+
+        // Order book top: asks (best at the bottom) over bids
+        let mut book_lines = Vec::new();
+        if let Some(ref orderbook) = status.orderbook {
+            for (price, size) in orderbook.asks.iter().take(BOOK_LEVELS).rev() {
+                book_lines.push(Line::styled(format!("{:>14} {:>12}", price, size), Style::default().fg(Color::Red)));
+            }
+            book_lines.push(Line::from(format!("{:>14} {:.1} bps", orderbook.mid_price, orderbook.spread_bps)));
+            for (price, size) in orderbook.bids.iter().take(BOOK_LEVELS) {
+                book_lines.push(Line::styled(format!("{:>14} {:>12}", price, size), Style::default().fg(Color::Green)));
+            }
+        }
+        frame.render_widget(
+            Paragraph::new(book_lines).block(Block::default().borders(Borders::ALL).title(" Order book ")),
+            book,
+        );
+
+        // Indicators vs. the entry threshold
+        let indicator_lines = vec![
+            Line::from(format!("Last price   {}", opt(status.last_price))),
+            Line::from(format!("Ticks        {}", status.ticks_buffered)),
+            Line::from(format!(
+                "Momentum     {} (threshold ±{:.3}%)",
+                status.momentum_percent.map(|m| format!("{:+.3}%", m)).unwrap_or_else(|| "-".to_string()),
+                status.params.momentum_threshold
+            )),
+            Line::from(format!("ATR (1m)     {}", status.atr_percent.map(|a| format!("{:.3}%", a)).unwrap_or_else(|| "-".to_string()))),
+            Line::from(format!("Regime       {}", opt(status.regime.as_ref()))),
+            Line::from(format!("Loss streak  {}", status.consecutive_losses)),
+        ];
+        frame.render_widget(
+            Paragraph::new(indicator_lines).block(Block::default().borders(Borders::ALL).title(" Indicators ")),
+            indicators,
+        );
+
+        // Open position
+        let position_lines = match status.position {
+            Some(ref p) => {
+                let pnl = p.pnl_percent();
+                let roe = p.roe_percent();
+                vec![
+                    Line::from(format!("{:?} {} @ {}x", p.side, p.size, p.leverage)),
+                    Line::from(format!("Entry  {}", p.entry_price)),
+                    Line::from(format!("Mark   {}", p.current_price)),
+                    Line::styled(format!("PnL    {:+.2}%  ROE {:+.2}%", pnl, roe), pnl_style(pnl)),
+                    Line::from(format!("USD    {:+.2} unreal. {:+.2} real.", p.unrealized_pnl, p.realized_pnl)),
+                    Line::from(format!("Peak   {:+.2}%", status.peak_pnl_percent)),
+                    Line::from(format!("Liq    {}", opt(p.liq_price.filter(|l| *l > Decimal::ZERO)))),
+                ]
+            }
+            None => vec![Line::from("No open position")],
+        };
+        frame.render_widget(
+            Paragraph::new(position_lines).block(Block::default().borders(Borders::ALL).title(" Position ")),
+            position,
+        );
+
+        // Trade log, newest at the bottom
+        let visible = log.height.saturating_sub(2) as usize;
+        let skip = dashboard.trade_log.len().saturating_sub(visible);
+        let log_lines: Vec<Line> = dashboard.trade_log.iter().skip(skip).map(|l| Line::from(l.as_str())).collect();
+        frame.render_widget(
+            Paragraph::new(log_lines).block(Block::default().borders(Borders::ALL).title(" Trade log ")),
+            log,
+        );
+
+        frame.render_widget(
+            Paragraph::new(" q / Esc / Ctrl+C — stop the bot").style(Style::default().fg(Color::DarkGray)),
+            footer,
+        );
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+mod imp {
+    use crate::actors::messages::StrategyMessage;
+    use crate::channel::MeteredSender;
+    use crate::events::EventBus;
+    use anyhow::Result;
+
+    pub async fn run(_strategy_tx: MeteredSender<StrategyMessage>, _events: EventBus) -> Result<()> {
+        anyhow::bail!("--tui needs a build with `--features tui`")
+    }
+}
+
+pub use imp::run;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+    use crate::models::Symbol;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_trade_log_lines_skip_periodic_events() {
+        let bus = EventBus::new("bot-a");
+        let mut rx = bus.subscribe();
+        bus.publish(EventKind::Signal {
+            symbol: Symbol::from("BTCUSDT"),
+            bullish: false,
+            price: Decimal::new(650001, 1),
+            source: "momentum".to_string(),
+            taken: false,
+            reject_reason: Some("spread".to_string()),
+        });
+        bus.publish(EventKind::TickStats {
+            symbol: Symbol::from("BTCUSDT"),
+            ticks: 10,
+            last_price: None,
+            spread_bps: None,
+            momentum_percent: None,
+        });
+
+        let line = log_line(&rx.try_recv().unwrap()).unwrap();
+        assert!(line.ends_with("SIGNAL  BTCUSDT SHORT @ 65000.1 (momentum) rejected: spread"), "{}", line);
+        assert!(log_line(&rx.try_recv().unwrap()).is_none());
+    }
+}
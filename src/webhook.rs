@@ -0,0 +1,143 @@
+//! External Signal Webhook
+//!
+//! `POST /signal` with a JSON body (e.g. a TradingView alert) injects an
+//! external entry signal into the strategy. The token goes in an
+//! `Authorization: Bearer` / `X-Webhook-Token` header or, for senders that
+//! can't set headers, a `"token"` field in the body. Accepted signals still
+//! go through every risk check and the strategy's state machine.
+
+use crate::actors::messages::StrategyMessage;
+use crate::channel::MeteredSender;
+use crate::http::{self, token_matches, Request};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Entry signal from outside the bot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalSignal {
+    /// Must match the symbol being traded (checked by the strategy)
+    pub symbol: String,
+    /// "buy"/"long" or "sell"/"short"
+    pub side: String,
+    /// Free-form origin for logs and alerts (e.g. "tradingview")
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing)]
+    token: Option<String>,
+}
+
+impl ExternalSignal {
+    /// Some(true) = long, Some(false) = short, None = unknown side
+    pub fn is_bullish(&self) -> Option<bool> {
+        match self.side.trim().to_lowercase().as_str() {
+            "buy" | "long" => Some(true),
+            "sell" | "short" => Some(false),
+            _ => None,
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        self.source.as_deref().unwrap_or("webhook")
+    }
+}
+
+/// Token from the headers, if any
+fn header_token(request: &Request) -> Option<&str> {
+    request
+        .header("x-webhook-token")
+        .map(str::trim)
+        .or_else(|| request.bearer_token())
+}
+
+/// (status line, body) for a request
+fn handle(request: &Request, token: &str, strategy_tx: &MeteredSender<StrategyMessage>) -> (&'static str, String) {
+    if request.route() != "/signal" {
+        return ("404 Not Found", String::new());
+    }
+    if request.method != "POST" {
+        return ("405 Method Not Allowed", String::new());
+    }
+
+    let signal: ExternalSignal = match serde_json::from_slice(&request.body) {
+        Ok(signal) => signal,
+        Err(e) => return ("400 Bad Request", format!("invalid JSON: {}", e)),
+    };
+    let given = header_token(request).or(signal.token.as_deref()).unwrap_or("");
+    if !token_matches(token, given) {
+        warn!("🚫 Webhook signal with a bad token ({} {})", signal.symbol, signal.side);
+        return ("401 Unauthorized", String::new());
+    }
+    if signal.is_bullish().is_none() {
+        return ("400 Bad Request", format!("unknown side '{}'", signal.side));
+    }
+
+    info!("📨 External signal from {}: {} {}", signal.source(), signal.side, signal.symbol);
+    match strategy_tx.try_send(StrategyMessage::ExternalSignal(signal)) {
+        Ok(()) => ("202 Accepted", "queued".to_string()),
+        Err(e) => {
+            warn!("External signal dropped: {}", e);
+            ("503 Service Unavailable", "strategy busy".to_string())
+        }
+    }
+}
+
+/// Serve `POST /signal` until the listener fails
+pub async fn serve(addr: SocketAddr, token: Arc<str>, strategy_tx: MeteredSender<StrategyMessage>) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("📨 Signal webhook listening on http://{}/signal", addr);
+
+    loop {
+        let (mut stream, peer) = listener.accept().await?;
+        let token = token.clone();
+        let strategy_tx = strategy_tx.clone();
+
+        tokio::spawn(async move {
+            let (status, body) = match http::read_request(&mut stream).await {
+                Ok(request) => handle(&request, &token, &strategy_tx),
+                Err(status) => {
+                    debug!("Bad webhook request from {}: {}", peer, status);
+                    (status, String::new())
+                }
+            };
+            http::respond(&mut stream, status, "text/plain", &body).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::parse_request;
+
+    #[test]
+    fn test_parse_request_waits_for_the_full_body() {
+        let body = r#"{"symbol":"BTCUSDT","side":"buy"}"#;
+        let raw = format!(
+            "POST /signal HTTP/1.1\r\nContent-Length: {}\r\nX-Webhook-Token: s3cret\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        assert!(parse_request(&raw.as_bytes()[..raw.len() - 5]).is_none());
+
+        let request = parse_request(raw.as_bytes()).unwrap();
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/signal"));
+        assert_eq!(header_token(&request), Some("s3cret"));
+
+        let signal: ExternalSignal = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(signal.is_bullish(), Some(true));
+        assert_eq!(signal.source(), "webhook");
+    }
+
+    #[test]
+    fn test_token_from_body_for_header_less_senders() {
+        let signal: ExternalSignal =
+            serde_json::from_str(r#"{"symbol":"ETHUSDT","side":"Short","source":"tradingview","token":"abc"}"#).unwrap();
+        assert_eq!(signal.is_bullish(), Some(false));
+        assert!(token_matches("abc", signal.token.as_deref().unwrap()));
+        assert!(!token_matches("abc", "abd"));
+        assert!(!token_matches("abc", ""));
+    }
+}
@@ -0,0 +1,883 @@
+//! BybitClient against a mock HTTP server: signing, retries, retCode mapping, malformed bodies
+
+use bybit_scalper_bot::audit::{self, OrderAudit};
+use bybit_scalper_bot::crash;
+use bybit_scalper_bot::exchange::{BybitClient, BybitError};
+use bybit_scalper_bot::models::*;
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::time::Duration;
+use wiremock::matchers::{body_partial_json, header_exists, method, path, query_param};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const KEY: &str = "test_key";
+const SECRET: &str = "test_secret";
+
+async fn setup() -> (MockServer, BybitClient) {
+    let server = MockServer::start().await;
+    let client = BybitClient::new(KEY.to_string(), SECRET.to_string(), server.uri())
+        .with_retry_base_delay(Duration::from_millis(1));
+    (server, client)
+}
+
+fn ok(result: Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({ "retCode": 0, "retMsg": "OK", "result": result }))
+}
+
+fn ret_code(code: i32, msg: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({ "retCode": code, "retMsg": msg, "result": {} }))
+}
+
+fn malformed() -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_string("<html>502 Bad Gateway</html>")
+}
+
+/// Recompute the V5 signature for a received request (query string for GET, body for POST)
+fn assert_signed(request: &Request) {
+    let header = |name: &str| request.headers.get(name).unwrap().to_str().unwrap().to_string();
+    assert_eq!(header("X-BAPI-API-KEY"), KEY);
+
+    let params = if request.method.as_str() == "GET" {
+        request.url.query().unwrap_or("").to_string()
+    } else {
+        String::from_utf8(request.body.clone()).unwrap()
+    };
+    let payload = format!(
+        "{}{}{}{}",
+        header("X-BAPI-TIMESTAMP"),
+        KEY,
+        header("X-BAPI-RECV-WINDOW"),
+        params
+    );
+    let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+    mac.update(payload.as_bytes());
+    assert_eq!(header("X-BAPI-SIGN"), hex::encode(mac.finalize().into_bytes()));
+}
+
+fn market_order(qty: f64) -> Order {
+    Order {
+        symbol: Symbol::from("BTCUSDT"),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        qty: Decimal::from_f64(qty).unwrap(),
+        price: None,
+        time_in_force: TimeInForce::IOC,
+        reduce_only: false,
+        qty_step: Some(Decimal::new(1, 3)),
+        tick_size: None,
+        ref_price: None,
+        allow_duplicate: false,
+        hard_stop_percent: None,
+        position_idx: 0,
+    }
+}
+
+fn tickers_result() -> Value {
+    json!({
+        "category": "linear",
+        "list": [{
+            "symbol": "BTCUSDT", "lastPrice": "60000", "price24hPcnt": "0.01",
+            "turnover24h": "1000000", "volume24h": "100", "bid1Price": "59999",
+            "ask1Price": "60001", "bid1Size": "1", "ask1Size": "1"
+        }]
+    })
+}
+
+fn order_status_result(status: &str) -> Value {
+    json!({
+        "list": [{
+            "orderId": "abc", "orderLinkId": "", "symbol": "BTCUSDT", "orderStatus": status,
+            "orderType": "Market", "side": "Buy", "price": "0", "qty": "0.01",
+            "cumExecQty": "0.01", "cumExecValue": "600", "cumExecFee": "0.33", "avgPrice": "60000"
+        }]
+    })
+}
+
+// ---- get_tickers ----
+
+#[tokio::test]
+async fn tickers_parse_and_are_unsigned() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v5/market/tickers"))
+        .and(query_param("category", "linear"))
+        .respond_with(ok(tickers_result()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let tickers = client.get_tickers("linear").await.unwrap();
+    assert_eq!(tickers.list[0].symbol, "BTCUSDT");
+    assert!(server.received_requests().await.unwrap()[0].headers.get("X-BAPI-SIGN").is_none());
+}
+
+#[tokio::test]
+async fn tickers_retry_on_5xx_then_succeed() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/market/tickers"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/market/tickers"))
+        .respond_with(ok(tickers_result()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert!(client.get_tickers("linear").await.is_ok());
+}
+
+#[tokio::test]
+async fn tickers_give_up_after_max_retries() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/market/tickers"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(4) // first try + 3 retries
+        .mount(&server)
+        .await;
+
+    let err = client.get_tickers("linear").await.unwrap_err();
+    assert!(matches!(err, BybitError::ServerError { status: 500, .. }));
+}
+
+#[tokio::test]
+async fn tickers_rate_limit_ret_code_is_retried() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/market/tickers"))
+        .respond_with(ret_code(10006, "Too many visits!"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/market/tickers"))
+        .respond_with(ok(tickers_result()))
+        .mount(&server)
+        .await;
+
+    assert!(client.get_tickers("linear").await.is_ok());
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn tickers_malformed_body_is_decode_error() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/market/tickers"))
+        .respond_with(malformed())
+        .expect(1) // not retryable
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.get_tickers("linear").await, Err(BybitError::Decode(_))));
+}
+
+// ---- get_instrument_info ----
+
+#[tokio::test]
+async fn recent_trades_parse_and_clamp_limit() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/market/recent-trade"))
+        .and(query_param("symbol", "BTCUSDT"))
+        .and(query_param("limit", "1000"))
+        .respond_with(ok(json!({
+            "category": "linear",
+            "list": [
+                { "execId": "2", "symbol": "BTCUSDT", "price": "60001", "size": "0.5", "side": "Sell", "time": "1700000000002", "isBlockTrade": false },
+                { "execId": "1", "symbol": "BTCUSDT", "price": "60000", "size": "0.1", "side": "Buy", "time": "1700000000001", "isBlockTrade": false }
+            ]
+        })))
+        .mount(&server)
+        .await;
+
+    let trades = client.get_recent_trades("BTCUSDT", 5000).await.unwrap();
+    assert_eq!(trades.len(), 2);
+    assert_eq!(trades[0].exec_id, "2");
+    assert_eq!(trades[1].side, "Buy");
+}
+
+#[tokio::test]
+async fn top_of_book_reads_best_levels() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/market/orderbook"))
+        .and(query_param("symbol", "BTCUSDT"))
+        .and(query_param("limit", "1"))
+        .respond_with(ok(json!({
+            "s": "BTCUSDT",
+            "b": [["60000.5", "1.2"]],
+            "a": [["60001", "0.4"]],
+            "ts": 1700000000000u64,
+            "u": 42
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/market/orderbook"))
+        .and(query_param("symbol", "EMPTYUSDT"))
+        .respond_with(ok(json!({ "s": "EMPTYUSDT", "b": [], "a": [], "ts": 1700000000000u64, "u": 1 })))
+        .mount(&server)
+        .await;
+
+    let (bid, ask) = client.get_top_of_book("BTCUSDT").await.unwrap();
+    assert_eq!((bid.to_string(), ask.to_string()), ("60000.5".to_string(), "60001".to_string()));
+    assert!(matches!(client.get_top_of_book("EMPTYUSDT").await, Err(BybitError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn instrument_info_parses_filters() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/market/instruments-info"))
+        .and(query_param("symbol", "BTCUSDT"))
+        .respond_with(ok(json!({
+            "list": [{
+                "symbol": "BTCUSDT",
+                "lotSizeFilter": { "qtyStep": "0.001", "minOrderQty": "0.001", "maxOrderQty": "100" },
+                "priceFilter": { "tickSize": "0.10" }
+            }]
+        })))
+        .mount(&server)
+        .await;
+
+    let info = client.get_instrument_info("BTCUSDT").await.unwrap();
+    assert_eq!(info.lot_size_filter.qty_step, "0.001");
+    assert_eq!(info.price_filter.tick_size, "0.10");
+}
+
+#[tokio::test]
+async fn instrument_info_empty_list_is_not_found() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/market/instruments-info"))
+        .respond_with(ok(json!({ "list": [] })))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.get_instrument_info("NOPEUSDT").await, Err(BybitError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn instrument_info_invalid_symbol_maps_ret_code() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/market/instruments-info"))
+        .respond_with(ret_code(10001, "params error: symbol invalid"))
+        .mount(&server)
+        .await;
+
+    let err = client.get_instrument_info("???").await.unwrap_err();
+    assert!(matches!(err, BybitError::InvalidOrder { code: 10001, .. }));
+    assert_eq!(err.ret_code(), Some(10001));
+}
+
+// ---- place_order ----
+
+#[tokio::test]
+async fn place_order_signs_exact_body_and_rounds_qty() {
+    let (server, client) = setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v5/order/create"))
+        .and(header_exists("X-BAPI-SIGN"))
+        .and(body_partial_json(json!({
+            "category": "linear", "symbol": "BTCUSDT", "side": "Buy",
+            "orderType": "Market", "qty": "0.012", "timeInForce": "IOC"
+        })))
+        .respond_with(ok(json!({ "orderId": "abc", "orderLinkId": "" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client.place_order(&market_order(0.0129)).await.unwrap();
+    assert_eq!(response.order_id, "abc");
+    assert_signed(&server.received_requests().await.unwrap()[0]);
+}
+
+#[tokio::test]
+async fn place_order_retries_5xx_with_fresh_signature() {
+    let (server, client) = setup().await;
+    let client = client.with_order_links("bot-");
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ResponseTemplate::new(502))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ok(json!({ "orderId": "abc", "orderLinkId": "" })))
+        .mount(&server)
+        .await;
+
+    assert!(client.place_order(&market_order(0.01)).await.is_ok());
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 3);
+    requests.iter().for_each(assert_signed);
+}
+
+#[tokio::test]
+async fn place_order_without_link_id_resends_only_refused_requests() {
+    let (server, client) = setup().await;
+    // A 502 may come after Bybit took the order: with no orderLinkId a resend could double it
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ResponseTemplate::new(502))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ret_code(10006, "too many visits"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ok(json!({ "orderId": "abc", "orderLinkId": "" })))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.place_order(&market_order(0.01)).await, Err(BybitError::ServerError { status: 502, .. })));
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+    // A rate limit refusal never reached the matching engine
+    assert!(client.place_order(&market_order(0.01)).await.is_ok());
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn place_order_business_rejection_is_not_retried() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ret_code(110007, "ab not enough for new order"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let err = client.place_order(&market_order(0.01)).await.unwrap_err();
+    assert!(matches!(err, BybitError::InsufficientBalance { code: 110007, .. }));
+    assert!(!err.is_retryable());
+}
+
+#[tokio::test]
+async fn order_link_id_is_tagged_and_reused_across_retries() {
+    let (server, client) = setup().await;
+    let client = client.with_order_links("bot-");
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ResponseTemplate::new(502))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    // The 502 came after Bybit took the order: the retry's id is a duplicate
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ret_code(110072, "OrderLinkedID is duplicate"))
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/realtime"))
+        .respond_with(ok(json!({ "list": [{
+            "orderId": "abc", "orderLinkId": "bot-e1-0", "symbol": "BTCUSDT", "orderStatus": "New",
+            "orderType": "Market", "side": "Buy", "price": "0", "qty": "0.01",
+            "cumExecQty": "0", "cumExecValue": "0", "avgPrice": "0"
+        }] })))
+        .mount(&server)
+        .await;
+
+    let response = client.place_order(&market_order(0.01)).await.unwrap();
+    assert_eq!(response.order_id, "abc");
+
+    let requests = server.received_requests().await.unwrap();
+    let link_id = |request: &Request| {
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        body["orderLinkId"].as_str().unwrap().to_string()
+    };
+    let first = link_id(&requests[0]);
+    assert!(first.starts_with("bot-e"), "{}", first);
+    assert_eq!(link_id(&requests[1]), first);
+    assert_eq!(requests[2].url.query_pairs().find(|(k, _)| k == "orderLinkId").unwrap().1, first);
+    assert_signed(&requests[2]);
+
+    // The next order gets a fresh id; a close is marked as one
+    let close = Order { reduce_only: true, ..market_order(0.01) };
+    let _ = client.place_order(&close).await;
+    let requests = server.received_requests().await.unwrap();
+    let next = link_id(&requests[3]);
+    assert!(next.starts_with("bot-c") && next != first, "{}", next);
+}
+
+#[tokio::test]
+async fn open_orders_and_history_are_signed_lists() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/order/realtime"))
+        .and(query_param("settleCoin", "USDT"))
+        .respond_with(ok(order_status_result("New")))
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/history"))
+        .and(query_param("startTime", "1700000000000"))
+        .respond_with(ok(order_status_result("Filled")))
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.get_open_orders().await.unwrap()[0].order_status, "New");
+    let history = client.get_order_history(1_700_000_000_000).await.unwrap();
+    assert_eq!(history[0].order_status, "Filled");
+    assert!(!history[0].reduce_only);
+    server.received_requests().await.unwrap().iter().for_each(assert_signed);
+}
+
+#[tokio::test]
+async fn order_requests_and_rejects_are_audited() {
+    let (server, client) = setup().await;
+    let path_buf = std::env::temp_dir().join(format!("order-audit-client-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path_buf);
+    let client = client.with_audit(OrderAudit::open(path_buf.clone()).unwrap());
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ret_code(110007, "ab not enough for new order"))
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/cancel")).respond_with(ResponseTemplate::new(500)).mount(&server).await;
+
+    client.place_order(&market_order(0.01)).await.unwrap_err();
+    client.cancel_order("BTCUSDT", "abc").await.unwrap_err();
+
+    // Rejects and 5xx responses are answers too: request + response for each call
+    assert_eq!(audit::verify(&path_buf).unwrap(), 4);
+    let lines: Vec<Value> = std::fs::read_to_string(&path_buf)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines[0]["type"], "request");
+    assert_eq!(lines[0]["endpoint"], "/v5/order/create");
+    assert!(lines[0]["body"].as_str().unwrap().contains("\"symbol\":\"BTCUSDT\""));
+    assert_eq!((lines[1]["type"].as_str(), lines[1]["request_seq"].as_u64()), (Some("response"), Some(1)));
+    assert!(lines[1]["body"].as_str().unwrap().contains("110007"));
+    assert_eq!(lines[3]["status"], 500);
+    let _ = std::fs::remove_file(&path_buf);
+}
+
+#[tokio::test]
+async fn place_order_auth_failure() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ret_code(10004, "error sign!"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(
+        client.place_order(&market_order(0.01)).await,
+        Err(BybitError::Auth { code: 10004, .. })
+    ));
+}
+
+#[tokio::test]
+async fn place_order_malformed_body_is_decode_error() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ok(json!({ "unexpected": true })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.place_order(&market_order(0.01)).await, Err(BybitError::Decode(_))));
+}
+
+// ---- get_position / get_open_positions ----
+
+#[tokio::test]
+async fn get_position_signs_query_and_parses() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v5/position/list"))
+        .and(query_param("symbol", "BTCUSDT"))
+        .respond_with(ok(json!({
+            "list": [{ "symbol": "BTCUSDT", "side": "Buy", "size": "0.01", "avgPrice": "60000", "unrealisedPnl": "1.5" }]
+        })))
+        .mount(&server)
+        .await;
+
+    let positions = client.get_position("BTCUSDT").await.unwrap();
+    assert_eq!(positions[0].size, "0.01");
+    assert_signed(&server.received_requests().await.unwrap()[0]);
+}
+
+#[tokio::test]
+async fn get_position_errors_are_not_read_as_no_position() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/position/list"))
+        .respond_with(ret_code(10003, "API key is invalid."))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/position/list"))
+        .respond_with(ret_code(10006, "too many visits"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/position/list"))
+        .respond_with(malformed())
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.get_position("BTCUSDT").await, Err(BybitError::Auth { code: 10003, .. })));
+    assert!(matches!(client.get_position("BTCUSDT").await, Err(BybitError::RateLimited { .. })));
+    assert!(matches!(client.get_position("BTCUSDT").await, Err(BybitError::Decode(_))));
+}
+
+#[tokio::test]
+async fn get_open_positions_surfaces_errors() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/position/list"))
+        .and(query_param("settleCoin", "USDT"))
+        .respond_with(ret_code(10003, "API key is invalid."))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.get_open_positions().await, Err(BybitError::Auth { code: 10003, .. })));
+    assert_signed(&server.received_requests().await.unwrap()[0]);
+}
+
+#[tokio::test]
+async fn emergency_flatten_closes_only_owned_positions_under_their_index() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/position/list"))
+        .and(query_param("settleCoin", "USDT"))
+        .respond_with(ok(json!({ "list": [
+            { "symbol": "BTCUSDT", "side": "Sell", "size": "0.01", "avgPrice": "50000", "unrealisedPnl": "0", "positionIdx": 2 },
+            { "symbol": "ETHUSDT", "side": "Buy", "size": "1", "avgPrice": "3000", "unrealisedPnl": "0", "positionIdx": 0 }
+        ] })))
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ok(json!({ "orderId": "abc", "orderLinkId": "" })))
+        .mount(&server)
+        .await;
+
+    let owned = crash::OwnedPositions::default();
+    owned.opening(&Symbol::from("BTCUSDT"));
+    let report = crash::emergency_flatten(&client, &owned).await.unwrap();
+    assert_eq!(report.len(), 1, "{:?}", report);
+
+    // ETHUSDT isn't ours: one close, on the short side of a hedge-mode account
+    let requests = server.received_requests().await.unwrap();
+    let closes: Vec<Value> = requests
+        .iter()
+        .filter(|r| r.url.path() == "/v5/order/create")
+        .map(|r| serde_json::from_slice(&r.body).unwrap())
+        .collect();
+    assert_eq!(closes.len(), 1);
+    assert_eq!(closes[0]["symbol"], "BTCUSDT");
+    assert_eq!(closes[0]["side"], "Buy");
+    assert_eq!(closes[0]["positionIdx"], 2);
+    assert_eq!(closes[0]["reduceOnly"], true);
+}
+
+// ---- get_api_key_info ----
+
+#[tokio::test]
+async fn api_key_info_signs_and_parses_permissions() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v5/user/query-api"))
+        .respond_with(ok(json!({
+            "id": "1", "note": "bot", "apiKey": KEY, "readOnly": 0, "secret": "",
+            "permissions": { "ContractTrade": ["Order", "Position"], "Spot": [] },
+            "ips": ["*"], "type": 1, "deadlineDay": 83, "expiredAt": "2027-01-01T00:00:00Z",
+            "createdAt": "2026-10-01T00:00:00Z", "unified": 0, "uta": 1
+        })))
+        .mount(&server)
+        .await;
+
+    let info = client.get_api_key_info().await.unwrap();
+    assert_eq!(info.read_only, 0);
+    assert_eq!(info.permissions["ContractTrade"], vec!["Order", "Position"]);
+    assert_eq!(info.deadline_day, 83);
+    assert_signed(&server.received_requests().await.unwrap()[0]);
+}
+
+// ---- get_order_status ----
+
+#[tokio::test]
+async fn order_status_parses_fill() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/order/realtime"))
+        .and(query_param("orderId", "abc"))
+        .respond_with(ok(order_status_result("Filled")))
+        .mount(&server)
+        .await;
+
+    let status = client.get_order_status("BTCUSDT", "abc").await.unwrap();
+    assert_eq!(status.order_status, "Filled");
+    assert_eq!(status.avg_price, "60000");
+    assert_signed(&server.received_requests().await.unwrap()[0]);
+}
+
+#[tokio::test]
+async fn order_status_missing_order_is_not_found() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/order/realtime"))
+        .respond_with(ok(json!({ "list": [] })))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(client.get_order_status("BTCUSDT", "abc").await, Err(BybitError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn order_status_http_errors_are_classified() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/order/realtime"))
+        .respond_with(ResponseTemplate::new(403).set_body_string("access too frequent"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/realtime"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let err = client.get_order_status("BTCUSDT", "abc").await.unwrap_err();
+    assert!(matches!(err, BybitError::RateLimited { code: None, .. }));
+    assert!(err.is_retryable());
+    assert!(matches!(
+        client.get_order_status("BTCUSDT", "abc").await,
+        Err(BybitError::Http { status: 404, .. })
+    ));
+}
+
+// ---- cancel_order / cancel_all_orders / cancel_own_orders ----
+
+#[tokio::test]
+async fn cancel_order_tolerates_ret_code_but_not_server_error() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/order/cancel"))
+        .and(body_partial_json(json!({ "symbol": "BTCUSDT", "orderId": "abc" })))
+        .respond_with(ret_code(110001, "order not exists or too late to cancel"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/cancel"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    assert!(client.cancel_order("BTCUSDT", "abc").await.is_ok());
+    assert_signed(&server.received_requests().await.unwrap()[0]);
+    assert!(matches!(
+        client.cancel_order("BTCUSDT", "abc").await,
+        Err(BybitError::ServerError { status: 500, .. })
+    ));
+}
+
+#[tokio::test]
+async fn cancel_all_orders_maps_ret_code() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/order/cancel-all"))
+        .respond_with(ok(json!({ "list": [] })))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/cancel-all"))
+        .respond_with(ret_code(12345, "something odd"))
+        .mount(&server)
+        .await;
+
+    assert!(client.cancel_all_orders("BTCUSDT").await.is_ok());
+    assert!(matches!(
+        client.cancel_all_orders("BTCUSDT").await,
+        Err(BybitError::Api { code: 12345, .. })
+    ));
+}
+
+#[tokio::test]
+async fn cancel_own_orders_without_links_leaves_conditional_orders() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/order/cancel-all"))
+        .and(body_partial_json(json!({ "symbol": "BTCUSDT", "orderFilter": "Order" })))
+        .respond_with(ok(json!({ "list": [] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert!(client.cancel_own_orders("BTCUSDT").await.is_ok());
+    assert_signed(&server.received_requests().await.unwrap()[0]);
+}
+
+#[tokio::test]
+async fn klines_parse_from_string_arrays() {
+    let (server, client) = setup().await;
+    Mock::given(path("/v5/market/kline"))
+        .and(query_param("symbol", "BTCUSDT"))
+        .and(query_param("interval", "1"))
+        .and(query_param("limit", "200"))
+        .respond_with(ok(json!({
+            "category": "linear",
+            "symbol": "BTCUSDT",
+            "list": [
+                ["1700000060000", "60010", "60050", "59990", "60020", "12.5", "750250"],
+                ["1700000000000", "60000", "60030", "59980", "60010", "8.1", "486081"]
+            ]
+        })))
+        .mount(&server)
+        .await;
+
+    let klines = client.get_klines("BTCUSDT", "1", 200).await.unwrap();
+    assert_eq!(klines.len(), 2);
+    assert_eq!(klines[0].start_time, "1700000060000");
+    assert_eq!(klines[1].close, "60010");
+}
+
+#[tokio::test]
+async fn wallet_balance_signs_and_parses_unified_totals() {
+    let (server, client) = setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v5/account/wallet-balance"))
+        .and(query_param("accountType", "UNIFIED"))
+        .respond_with(ok(json!({
+            "list": [{
+                "accountType": "UNIFIED",
+                "totalEquity": "1050.5",
+                "totalWalletBalance": "1000",
+                "totalAvailableBalance": "800.25",
+                "totalInitialMargin": "250.25",
+                "totalMaintenanceMargin": "20.1",
+                "accountIMRate": "0.2382",
+                "accountMMRate": "0.0191",
+                "coin": []
+            }]
+        })))
+        .mount(&server)
+        .await;
+
+    let wallet = client.get_wallet_balance().await.unwrap();
+    assert_eq!(wallet.total_available_balance, "800.25");
+    assert_eq!(wallet.account_mm_rate, "0.0191");
+    assert_signed(&server.received_requests().await.unwrap()[0]);
+}
+
+#[tokio::test]
+async fn set_leverage_treats_not_modified_as_success() {
+    let (server, client) = setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v5/position/set-leverage"))
+        .and(body_partial_json(json!({ "symbol": "BTCUSDT", "buyLeverage": "5", "sellLeverage": "5" })))
+        .respond_with(ret_code(110043, "leverage not modified"))
+        .mount(&server)
+        .await;
+
+    client.set_leverage("BTCUSDT", "5").await.unwrap();
+    assert_signed(&server.received_requests().await.unwrap()[0]);
+}
+
+#[tokio::test]
+async fn set_stop_loss_covers_the_whole_position_at_mark_price() {
+    let (server, client) = setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v5/position/trading-stop"))
+        .and(body_partial_json(json!({
+            "symbol": "BTCUSDT", "tpslMode": "Full", "stopLoss": "49500", "slTriggerBy": "MarkPrice"
+        })))
+        .respond_with(ok(json!({})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v5/position/trading-stop"))
+        .and(body_partial_json(json!({ "symbol": "ETHUSDT" })))
+        .respond_with(ret_code(34040, "not modified"))
+        .mount(&server)
+        .await;
+
+    client.set_stop_loss("BTCUSDT", Decimal::new(4950000, 2)).await.unwrap();
+    client.set_stop_loss("ETHUSDT", Decimal::from(3000)).await.unwrap();
+    assert_signed(&server.received_requests().await.unwrap()[0]);
+}
+
+// ---- fault injection ----
+
+#[tokio::test]
+async fn injected_faults_never_reach_the_exchange() {
+    let (server, client) = setup().await;
+    let client = client.with_faults("5xx=1,seed=1".parse().unwrap());
+    Mock::given(path("/v5/market/tickers"))
+        .respond_with(ok(tickers_result()))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // Retried like a real 5xx, then given up on
+    let err = client.get_tickers("linear").await.unwrap_err();
+    assert!(matches!(err, BybitError::ServerError { status: 503, .. }));
+
+    let (server, client) = setup().await;
+    let client = client.with_faults("timeout=1,hang_ms=1,seed=1".parse().unwrap());
+    Mock::given(path("/v5/order/realtime"))
+        .respond_with(ok(order_status_result("Filled")))
+        .mount(&server)
+        .await;
+    let err = client.get_order_status("BTCUSDT", "abc").await.unwrap_err();
+    assert!(matches!(err, BybitError::Timeout { sent, .. } if sent != err.is_retryable()));
+}
+
+#[tokio::test]
+async fn injected_timeout_after_sending_is_not_retried() {
+    let (server, client) = setup().await;
+    let client = client.with_order_links("bot-").with_faults("timeout=1,hang_ms=1,seed=1".parse().unwrap());
+    Mock::given(path("/v5/order/create"))
+        .respond_with(ok(json!({ "orderId": "abc", "orderLinkId": "" })))
+        .mount(&server)
+        .await;
+
+    // The order went out once; a resend would be the duplicate the scenario looks for
+    let err = client.place_order(&market_order(0.01)).await.unwrap_err();
+    assert!(matches!(err, BybitError::Timeout { sent: true, .. }));
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn injected_out_of_order_status_repeats_an_older_one() {
+    let (server, client) = setup().await;
+    let client = client.with_faults("out_of_order=1,seed=1".parse().unwrap());
+    Mock::given(path("/v5/order/realtime"))
+        .respond_with(ok(order_status_result("New")))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/realtime"))
+        .respond_with(ok(order_status_result("Filled")))
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.get_order_status("BTCUSDT", "abc").await.unwrap().order_status, "New");
+    assert_eq!(client.get_order_status("BTCUSDT", "abc").await.unwrap().order_status, "New");
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn injected_latency_delays_every_round_trip() {
+    let (server, client) = setup().await;
+    let client = client.with_faults("latency_ms=120,seed=1".parse().unwrap());
+    Mock::given(path("/v5/market/tickers"))
+        .respond_with(ok(tickers_result()))
+        .mount(&server)
+        .await;
+
+    let start = std::time::Instant::now();
+    client.get_tickers("linear").await.unwrap();
+    client.get_tickers("linear").await.unwrap();
+    assert!(start.elapsed() >= std::time::Duration::from_millis(240));
+}
+
+#[tokio::test]
+async fn order_history_follows_the_page_cursor() {
+    let (server, client) = setup().await;
+    let mut first = order_status_result("Filled");
+    first["nextPageCursor"] = json!("page_args%3Dabc");
+    Mock::given(path("/v5/order/history"))
+        .and(query_param("cursor", "page_args=abc"))
+        .respond_with(ok(order_status_result("Cancelled")))
+        .mount(&server)
+        .await;
+    Mock::given(path("/v5/order/history")).respond_with(ok(first)).mount(&server).await;
+
+    let history = client.get_order_history(1_700_000_000_000).await.unwrap();
+    let statuses: Vec<&str> = history.iter().map(|o| o.order_status.as_str()).collect();
+    assert_eq!(statuses, vec!["Filled", "Cancelled"]);
+    // The cursor is passed back exactly as Bybit sent it, and signed that way
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].url.query().unwrap().ends_with("&cursor=page_args%3Dabc"));
+    requests.iter().for_each(assert_signed);
+}